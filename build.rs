@@ -0,0 +1,14 @@
+//! Generates the `Converter` gRPC service `serve --grpc` answers from its
+//! definition in proto/converter.proto, with the protoc of
+//! protoc-bin-vendored so that none has to be installed.
+
+fn main() {
+    #[cfg(feature = "server")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path()
+            .expect("protoc-bin-vendored has no protoc for this platform");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::compile_protos("proto/converter.proto")
+            .expect("cannot generate the gRPC service from proto/converter.proto");
+    }
+}
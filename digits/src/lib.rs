@@ -0,0 +1,820 @@
+//! The digit extraction at the heart of the converter, without the standard
+//! library, so that firmware can produce the same digits as the CLI.
+//!
+//! Digits come out of iterators one at a time, or are handed to a closure
+//! by [`convert_with`], and nothing is allocated.
+//! With the `alloc` feature, [`digit_vec`] collects them into a `Vec` and
+//! [`BaseNumber`] holds a whole converted number, and with the `wasm`
+//! feature the crate exports `to_base` to WebAssembly hosts.
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::str::FromStr;
+
+/// The number of fractional bits [`ScaledDigits`] keeps in a `u128`. It
+/// leaves 32 bits of headroom, so multiplying by any base up to
+/// [`MAX_BASE`] cannot overflow.
+pub const SCALE_BITS: u32 = 96;
+
+/// The largest base digits are extracted in, 2^32, whose largest digit is
+/// the largest `u32`, as the limbs of a bignum with 32-bit limbs are.
+pub const MAX_BASE: u64 = 1 << 32;
+
+/// Splits the fractional part of `|value|` into an integer mantissa and the
+/// number of fractional bits, so that it equals `mantissa / 2^bits` exactly.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(digits::fraction_bits(2.75), (3, 2));
+/// assert_eq!(digits::fraction_bits(-0.5), (1, 1));
+/// assert_eq!(digits::fraction_bits(3.0), (0, 0));
+/// ```
+pub fn fraction_bits(value: f64) -> (u64, u32) {
+    let bits = value.to_bits();
+    let exponent = (bits >> 52 & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+    // Subnormal numbers have no implicit leading one.
+    let (mantissa, exponent) = if exponent == 0 {
+        (mantissa, -1074)
+    } else {
+        (mantissa | 1 << 52, exponent - 1075)
+    };
+    // Infinities and NaN land here too, as their exponent field is all ones.
+    if exponent >= 0 {
+        return (0, 0);
+    }
+
+    // Keep only the bits below the point.
+    let fractional = match 1u64.checked_shl((-exponent) as u32) {
+        Some(one) => mantissa & (one - 1),
+        None => mantissa,
+    };
+    if fractional == 0 {
+        return (0, 0);
+    }
+    let shift = fractional.trailing_zeros();
+    (fractional >> shift, (-exponent) as u32 - shift)
+}
+
+/// The digits of `|value|` in a base from repeatedly multiplying an `f64`
+/// fraction by the base, which drifts once the products need more than 53
+/// bits. Values of one or more yield digits of the base or more.
+///
+/// # Example
+///
+/// ```
+/// let digits: Vec<u32> = digits::F64Digits::new(0.75, 2, 8).collect();
+/// assert_eq!(digits, [1, 1]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct F64Digits {
+    /// The part of the value not yet written as digits.
+    fraction: f64,
+    /// The base the digits are in.
+    base: f64,
+    /// How many more digits may be produced.
+    remaining: u32,
+    /// Whether the fraction has run out.
+    done: bool,
+}
+
+impl F64Digits {
+    /// Starts the digits of `|value|` in `base`, stopping after `max_digits`.
+    pub fn new(value: f64, base: u64, max_digits: u32) -> F64Digits {
+        F64Digits {
+            fraction: value.abs(),
+            base: base as f64,
+            remaining: max_digits,
+            done: false,
+        }
+    }
+
+    /// The part of the value left over after the digits produced so far,
+    /// which is zero once the expansion has terminated.
+    pub fn remainder(&self) -> f64 {
+        self.fraction
+    }
+}
+
+impl Iterator for F64Digits {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.done || self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.fraction *= self.base;
+        // The fraction is never negative, so truncating is taking the floor.
+        let digit = self.fraction as u32;
+        self.fraction -= digit as f64;
+        self.done = self.fraction == 0.0;
+        Some(digit)
+    }
+}
+
+/// The digits of the fractional part of `|value|` in a base from the
+/// fraction scaled to a `u128` with [`SCALE_BITS`] fractional bits, which is
+/// exact for every `f64` whose lowest set bit is at least 2^-96. A fraction
+/// with bits below that is held in [`WIDE_LIMBS`] limbs of 32 bits instead,
+/// which is exact for every `f64` down to the smallest subnormal.
+///
+/// # Example
+///
+/// ```
+/// // 0.1 is stored as a little more than 0.1.
+/// let digits: Vec<u32> = digits::ScaledDigits::new(0.1, 10, 20).collect();
+/// assert_eq!(digits[16..], [0, 5, 5, 5]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScaledDigits {
+    /// The fraction not yet written as digits.
+    fraction: Scaled,
+    /// The base the digits are in.
+    base: u64,
+    /// How many more digits may be produced.
+    remaining: u32,
+    /// Whether the fraction has run out.
+    done: bool,
+}
+
+/// The number of 32-bit limbs a fraction with bits below 2^-96 is held in:
+/// enough for the 1074 fractional bits of the smallest subnormal `f64` and
+/// the 32 bits a digit of [`MAX_BASE`] adds above them.
+pub const WIDE_LIMBS: usize = 35;
+
+/// A fraction being written as digits, as wide as its bits need.
+#[derive(Debug, Clone)]
+enum Scaled {
+    /// The fraction scaled by 2^[`SCALE_BITS`].
+    Narrow(u128),
+    /// The fraction as `limbs / 2^bits`, least significant limb first.
+    Wide { limbs: [u32; WIDE_LIMBS], bits: u32 },
+}
+
+impl ScaledDigits {
+    /// Starts the digits of the fractional part of `|value|` in `base`, at
+    /// most [`MAX_BASE`], stopping after `max_digits`.
+    pub fn new(value: f64, base: u64, max_digits: u32) -> ScaledDigits {
+        let (mantissa, bits) = fraction_bits(value);
+        let fraction = if bits <= SCALE_BITS {
+            Scaled::Narrow((mantissa as u128) << (SCALE_BITS - bits))
+        } else {
+            let mut limbs = [0; WIDE_LIMBS];
+            limbs[0] = mantissa as u32;
+            limbs[1] = (mantissa >> 32) as u32;
+            Scaled::Wide { limbs, bits }
+        };
+        ScaledDigits {
+            fraction,
+            base,
+            remaining: max_digits,
+            done: false,
+        }
+    }
+
+    /// The part of the fraction left over after the digits produced so far,
+    /// which is zero once the expansion has terminated.
+    pub fn remainder(&self) -> f64 {
+        match &self.fraction {
+            Scaled::Narrow(fraction) => *fraction as f64 * power_of_two(-(SCALE_BITS as i32)),
+            Scaled::Wide { limbs, bits } => limbs
+                .iter()
+                .enumerate()
+                .map(|(i, &limb)| limb as f64 * power_of_two(32 * i as i32 - *bits as i32))
+                .sum(),
+        }
+    }
+}
+
+/// 2^`exponent`, built from its bits since `powi` needs the standard
+/// library, and zero below the smallest subnormal.
+fn power_of_two(exponent: i32) -> f64 {
+    if exponent >= -1022 {
+        f64::from_bits(((exponent + 1023) as u64) << 52)
+    } else if exponent >= -1074 {
+        f64::from_bits(1 << (exponent + 1074))
+    } else {
+        0.0
+    }
+}
+
+impl Iterator for ScaledDigits {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.done || self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (digit, done) = match &mut self.fraction {
+            Scaled::Narrow(fraction) => {
+                *fraction *= self.base as u128;
+                let digit = (*fraction >> SCALE_BITS) as u32;
+                *fraction &= (1u128 << SCALE_BITS) - 1;
+                (digit, *fraction == 0)
+            }
+            Scaled::Wide { limbs, bits } => {
+                let mut carry = 0u64;
+                for limb in limbs.iter_mut() {
+                    let product = *limb as u64 * self.base + carry;
+                    *limb = product as u32;
+                    carry = product >> 32;
+                }
+                // The digit is the 32 bits from the point up, which the
+                // headroom of the top limb keeps inside the limbs.
+                let (index, offset) = ((*bits / 32) as usize, *bits % 32);
+                let above = (limbs[index] as u64 >> offset)
+                    | limbs
+                        .get(index + 1)
+                        .map_or(0, |&limb| (limb as u64) << (32 - offset));
+                limbs[index] &= ((1u64 << offset) - 1) as u32;
+                limbs[index + 1..].iter_mut().for_each(|limb| *limb = 0);
+                (above as u32, limbs.iter().all(|&limb| limb == 0))
+            }
+        };
+        self.done = done;
+        Some(digit)
+    }
+}
+
+/// Writes `value` in `base` the way the CLI table does, as `0.d;d;d;` with
+/// the integer digits (if any) separated by `;`, using up to `max_digits`
+/// exact fractional digits.
+///
+/// # Returns
+///
+/// An error if the sink fails, if the value is not finite or its integer
+/// part does not fit in a `u64`, or if the base is below 2 or above
+/// [`MAX_BASE`].
+///
+/// # Example
+///
+/// ```
+/// let mut text = String::new();
+/// digits::write_in_base(&mut text, -2.75, 2, 8).unwrap();
+/// assert_eq!(text, "-1;0.1;1;");
+/// ```
+pub fn write_in_base(
+    sink: &mut impl fmt::Write,
+    value: f64,
+    base: u64,
+    max_digits: u32,
+) -> fmt::Result {
+    let integer_digits = IntegerDigits::new(value, base).ok_or(fmt::Error)?;
+    if value < 0.0 {
+        sink.write_str("-")?;
+    }
+    write_digits(
+        sink,
+        integer_digits,
+        ScaledDigits::new(value, base, max_digits),
+    )
+}
+
+/// Calls `visit` with every digit of `|value|` in `base` as it is extracted,
+/// most significant first, so that a renderer or a writer can consume the
+/// digits without collecting them. Every digit comes with its position, the
+/// power of the base it stands for: 0 for the units digit and -1 for the
+/// first fractional digit. The digits are those `write_in_base` writes, with
+/// up to `max_digits` exact fractional digits.
+///
+/// # Returns
+///
+/// The number of digits visited, or `None`, before any is visited, if the
+/// value is not finite or its integer part does not fit in a `u64`, or if
+/// the base is below 2 or above [`MAX_BASE`].
+///
+/// # Example
+///
+/// ```
+/// let mut digits = Vec::new();
+/// let count = digits::convert_with(-2.75, 2, 8, |position, digit| {
+///     digits.push((position, digit))
+/// });
+/// assert_eq!(count, Some(4));
+/// assert_eq!(digits, [(1, 1), (0, 0), (-1, 1), (-2, 1)]);
+/// ```
+pub fn convert_with(
+    value: f64,
+    base: u64,
+    max_digits: u32,
+    mut visit: impl FnMut(i32, u32),
+) -> Option<usize> {
+    let integer_digits = IntegerDigits::new(value, base)?;
+    let mut position = integer_digits.clone().count() as i32;
+    let mut count = 0;
+    for digit in integer_digits.chain(ScaledDigits::new(value, base, max_digits)) {
+        position -= 1;
+        visit(position, digit);
+        count += 1;
+    }
+    Some(count)
+}
+
+/// Writes integer and fractional digits as `d;d.d;d;`, or `0.d;` without
+/// integer digits.
+fn write_digits(
+    sink: &mut impl fmt::Write,
+    integer_digits: impl Iterator<Item = u32>,
+    digits: impl Iterator<Item = u32>,
+) -> fmt::Result {
+    let mut any = false;
+    for digit in integer_digits {
+        if any {
+            sink.write_str(";")?;
+        }
+        write!(sink, "{}", digit)?;
+        any = true;
+    }
+    if !any {
+        sink.write_str("0")?;
+    }
+    sink.write_str(".")?;
+    for digit in digits {
+        write!(sink, "{};", digit)?;
+    }
+    Ok(())
+}
+
+/// The digits of the integer part of `|value|` in a base, most significant
+/// first, and none for a zero integer part.
+#[derive(Debug, Clone)]
+struct IntegerDigits {
+    /// The part of the integer not yet written as digits.
+    rest: u64,
+    /// The place value of the next digit, or 0 once all are written.
+    place: u64,
+    /// The base the digits are in.
+    base: u64,
+}
+
+impl IntegerDigits {
+    /// Starts the integer digits of `|value|` in `base`, or `None` if the
+    /// value is not finite, its integer part does not fit in a `u64` or the
+    /// base is below 2 or above [`MAX_BASE`].
+    fn new(value: f64, base: u64) -> Option<IntegerDigits> {
+        let magnitude = value.abs();
+        // 2^64, the first integer part that does not fit in a `u64`.
+        if !magnitude.is_finite()
+            || magnitude >= 18446744073709551616.0
+            || !(2..=MAX_BASE).contains(&base)
+        {
+            return None;
+        }
+        let integer = magnitude as u64;
+        let mut place = if integer == 0 { 0 } else { 1 };
+        while place > 0 && integer / place >= base {
+            place *= base;
+        }
+        Some(IntegerDigits {
+            rest: integer,
+            place,
+            base,
+        })
+    }
+}
+
+impl Iterator for IntegerDigits {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.place == 0 {
+            return None;
+        }
+        let digit = self.rest / self.place;
+        self.rest %= self.place;
+        self.place /= self.base;
+        Some(digit as u32)
+    }
+}
+
+/// A number converted to a base, as its sign and digits rather than text,
+/// so that library users need not parse the `0.d;d;d;` the CLI writes.
+/// Its `Display` writes that same text.
+///
+/// # Example
+///
+/// ```
+/// let number = digits::BaseNumber::new(-2.75, 2, 8).unwrap();
+/// assert_eq!(number.integer_digits, [1, 0]);
+/// assert_eq!(number.fraction_digits, [1, 1]);
+/// assert_eq!(number.to_string(), "-1;0.1;1;");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseNumber {
+    /// The base the digits are in.
+    pub base: u64,
+    /// Whether the number is negative.
+    pub negative: bool,
+    /// The digits of the integer part, most significant first, and empty
+    /// when it is zero.
+    pub integer_digits: Vec<u32>,
+    /// The exact fractional digits, most significant first, up to the digit
+    /// limit it was converted with.
+    pub fraction_digits: Vec<u32>,
+}
+
+#[cfg(feature = "alloc")]
+impl BaseNumber {
+    /// Converts `value` to `base` with up to `max_digits` exact fractional
+    /// digits, like [`write_in_base`].
+    ///
+    /// # Returns
+    ///
+    /// The number, or `None` if the value is not finite, its integer part
+    /// does not fit in a `u64` or the base is below 2 or above
+    /// [`MAX_BASE`].
+    pub fn new(value: f64, base: u64, max_digits: u32) -> Option<BaseNumber> {
+        Some(BaseNumber {
+            base,
+            negative: value < 0.0,
+            integer_digits: IntegerDigits::new(value, base)?.collect(),
+            fraction_digits: digit_vec(value, base, max_digits),
+        })
+    }
+
+    /// Parses a number written in a base, the way [`FromStr`] does, but
+    /// falling back to `base` when the text does not say its own base.
+    ///
+    /// The text is an optional `<base>:` prefix, an optional `-`, the
+    /// integer digits, a `.` and the fractional digits, and an optional
+    /// subscript base. Digits are either separated by `;`, as the CLI writes
+    /// them, or written one character each as `0`–`9` and `A`–`Z`, with
+    /// larger digits between brackets, as in `0.[40]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The number, such as `2:0.1;0;1;1;` or `0.1011₂`.
+    /// * `base` - The base of a text without a prefix or subscript, or
+    ///   `None` when it must have one.
+    ///
+    /// # Returns
+    ///
+    /// The number, or an error if it has no base, the prefix and subscript
+    /// disagree, the base is below 2 or above [`MAX_BASE`] or a digit is not
+    /// a digit of the base.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let number = digits::BaseNumber::parse("1;0.1;", Some(2)).unwrap();
+    /// assert_eq!(number, digits::BaseNumber::new(2.5, 2, 8).unwrap());
+    /// ```
+    pub fn parse(text: &str, base: Option<u64>) -> Result<BaseNumber, String> {
+        let invalid = || format!("`{}` is not a number in a base", text);
+        let (prefix, rest) = match text.split_once(':') {
+            Some((prefix, rest)) => (Some(prefix.parse::<u64>().map_err(|_| invalid())?), rest),
+            None => (None, text),
+        };
+        let digits_end = rest
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| subscript_digit(c).is_some())
+            .last()
+            .map_or(rest.len(), |(i, _)| i);
+        let (rest, subscript) = rest.split_at(digits_end);
+        let subscript = match subscript {
+            "" => None,
+            subscript => Some(
+                subscript
+                    .chars()
+                    .filter_map(subscript_digit)
+                    .try_fold(0u64, |base, digit| {
+                        base.checked_mul(10)?.checked_add(u64::from(digit))
+                    })
+                    .ok_or_else(invalid)?,
+            ),
+        };
+        let base = match (prefix, subscript) {
+            (Some(prefix), Some(subscript)) if prefix != subscript => {
+                return Err(format!(
+                    "`{}` says it is in both base {} and base {}",
+                    text, prefix, subscript
+                ))
+            }
+            (Some(embedded), _) | (None, Some(embedded)) => embedded,
+            (None, None) => base.ok_or_else(|| format!("`{}` does not say its base", text))?,
+        };
+        if base < 2 {
+            return Err(format!("base {} of `{}` is below 2", base, text));
+        }
+        if base > MAX_BASE {
+            return Err(format!("base {} of `{}` is above 2^32", base, text));
+        }
+
+        let (negative, rest) = match rest.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let (integer, fraction) = rest.split_once('.').unwrap_or((rest, ""));
+        let separated = rest.contains(';');
+        let mut integer_digits = parse_digit_list(integer, separated, base).ok_or_else(invalid)?;
+        if integer_digits.is_empty() && !separated {
+            return Err(invalid());
+        }
+        // A zero integer part is written `0` but held as no digits.
+        let leading_zeros = integer_digits
+            .iter()
+            .take_while(|&&digit| digit == 0)
+            .count();
+        integer_digits.drain(..leading_zeros);
+        let fraction = if separated {
+            fraction.strip_suffix(';').unwrap_or(fraction)
+        } else {
+            fraction
+        };
+        Ok(BaseNumber {
+            base,
+            negative,
+            integer_digits,
+            fraction_digits: parse_digit_list(fraction, separated, base).ok_or_else(invalid)?,
+        })
+    }
+
+    /// The value the digits stand for, rounded to an `f64`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let number: digits::BaseNumber = "2:-1;0.1;1;".parse().unwrap();
+    /// assert_eq!(number.value(), -2.75);
+    /// ```
+    pub fn value(&self) -> f64 {
+        let base = self.base as f64;
+        let integer = self
+            .integer_digits
+            .iter()
+            .fold(0.0, |value, &digit| value * base + digit as f64);
+        let fraction = self
+            .fraction_digits
+            .iter()
+            .rev()
+            .fold(0.0, |value, &digit| (value + digit as f64) / base);
+        let magnitude = integer + fraction;
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromStr for BaseNumber {
+    type Err = String;
+
+    /// Parses a number that says its own base, as in `2:0.1;0;1;1;` or
+    /// `0.1011₂`. See [`BaseNumber::parse`] for the syntax.
+    fn from_str(text: &str) -> Result<BaseNumber, String> {
+        BaseNumber::parse(text, None)
+    }
+}
+
+/// The value of a subscript digit such as `₂`.
+#[cfg(feature = "alloc")]
+fn subscript_digit(c: char) -> Option<u32> {
+    match c {
+        '₀'..='₉' => Some(c as u32 - '₀' as u32),
+        _ => None,
+    }
+}
+
+/// Parses the digits of one part of a number, either separated by `;` or
+/// written one character (or bracketed number) each, or `None` if one of
+/// them is not a digit of the base.
+#[cfg(feature = "alloc")]
+fn parse_digit_list(text: &str, separated: bool, base: u64) -> Option<Vec<u32>> {
+    let mut digits = Vec::new();
+    if separated {
+        if !text.is_empty() {
+            for digit in text.split(';') {
+                digits.push(digit.parse().ok()?);
+            }
+        }
+    } else {
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            let digit = if c == '[' {
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                inner.parse().ok()?
+            } else {
+                c.to_digit(36)?
+            };
+            digits.push(digit);
+        }
+    }
+    digits
+        .iter()
+        .all(|&digit| u64::from(digit) < base)
+        .then_some(digits)
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for BaseNumber {
+    /// Writes the number as `d;d.d;d;`, as [`write_in_base`] does.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        write_digits(
+            f,
+            self.integer_digits.iter().copied(),
+            self.fraction_digits.iter().copied(),
+        )
+    }
+}
+
+/// Collects up to `max_digits` exact digits of the fractional part of
+/// `|value|` in `base`.
+#[cfg(feature = "alloc")]
+pub fn digit_vec(value: f64, base: u64, max_digits: u32) -> Vec<u32> {
+    ScaledDigits::new(value, base, max_digits).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_f64_digits() {
+        let digits: Vec<u32> = F64Digits::new(-0.625, 2, 8).collect();
+        assert_that!(digits, equal_to(std::vec![1, 0, 1]));
+        // Zero is written as one zero digit.
+        assert_that!(
+            F64Digits::new(0.0, 2, 8).collect::<Vec<_>>(),
+            equal_to(std::vec![0])
+        );
+
+        let mut third = F64Digits::new(1.0 / 3.0, 10, 4);
+        assert_that!(third.by_ref().count(), equal_to(4));
+        assert!(third.remainder() > 0.0);
+    }
+
+    #[test]
+    fn test_scaled_digits() {
+        let mut digits = ScaledDigits::new(2.6875, 2, 8);
+        assert_that!(
+            digits.by_ref().collect::<Vec<_>>(),
+            equal_to(std::vec![1, 0, 1, 1])
+        );
+        assert_that!(digits.remainder(), equal_to(0.0));
+
+        let mut cut = ScaledDigits::new(0.1, 2, 3);
+        assert_that!(cut.by_ref().count(), equal_to(3));
+        assert_that!(cut.remainder(), equal_to(0.1 * 8.0));
+
+        // Bits below 2^-96 are kept, down to the smallest subnormal.
+        let mut tiny = ScaledDigits::new(1e-30, 2, 120);
+        let digits: Vec<u32> = tiny.by_ref().collect();
+        assert_that!(
+            digits.iter().position(|&digit| digit == 1),
+            equal_to(Some(99))
+        );
+        assert!(tiny.remainder() > 0.0);
+        let digits: Vec<u32> = ScaledDigits::new(1e-20, 10, 40).collect();
+        assert_that!(digits[20..30].to_vec(), equal_to(std::vec![9; 10]));
+        assert_that!(digits[36..].to_vec(), equal_to(std::vec![4, 5, 1, 5]));
+        let digits: Vec<u32> = ScaledDigits::new(5e-324, 2, 1074).collect();
+        assert_that!(digits.len(), equal_to(1074));
+        assert_that!(digits[1073], equal_to(1));
+        let digits: Vec<u32> = ScaledDigits::new(1e-30, MAX_BASE, 4).collect();
+        assert_that!(digits, equal_to(std::vec![0, 0, 0, 340282366]));
+    }
+
+    #[test]
+    fn test_largest_base() {
+        // The largest f64 below one fills a whole 2^32 digit and most of
+        // the next.
+        let below_one = 1.0 - 2f64.powi(-53);
+        let digits: Vec<u32> = ScaledDigits::new(below_one, MAX_BASE, 4).collect();
+        assert_that!(digits, equal_to(std::vec![u32::MAX, 0xffff_f800]));
+        let digits: Vec<u32> = F64Digits::new(below_one, MAX_BASE, 4).collect();
+        assert_that!(digits, equal_to(std::vec![u32::MAX, 0xffff_f800]));
+        // The largest integer part a u64 holds below 2^64.
+        let integer: Vec<u32> = IntegerDigits::new(18446744073709549568.0, MAX_BASE)
+            .unwrap()
+            .collect();
+        assert_that!(integer, equal_to(std::vec![u32::MAX, 0xffff_f800]));
+        assert!(IntegerDigits::new(1.0, MAX_BASE + 1).is_none());
+    }
+
+    #[test]
+    fn test_write_in_base() {
+        let written = |value: f64, base: u64, max_digits: u32| {
+            let mut text = std::string::String::new();
+            write_in_base(&mut text, value, base, max_digits).map(|()| text)
+        };
+        assert_that!(written(0.5, 2, 8), equal_to(Ok("0.1;".into())));
+        assert_that!(written(0.0, 2, 8), equal_to(Ok("0.0;".into())));
+        assert_that!(written(255.5, 16, 8), equal_to(Ok("15;15.8;".into())));
+        assert_that!(written(16.0, 16, 8), equal_to(Ok("1;0.0;".into())));
+        assert_that!(written(-0.1, 10, 3), equal_to(Ok("-0.1;0;0;".into())));
+        assert!(written(f64::NAN, 2, 8).is_err());
+        assert!(written(1e20, 2, 8).is_err());
+        assert!(written(0.5, 1, 8).is_err());
+    }
+
+    #[test]
+    fn test_convert_with() {
+        let visited = |value: f64, base: u64, max_digits: u32| {
+            let mut digits = Vec::new();
+            convert_with(value, base, max_digits, |position, digit| {
+                digits.push((position, digit))
+            })
+            .map(|count| (count, digits))
+        };
+        assert_that!(
+            visited(255.5, 16, 8),
+            equal_to(Some((3, std::vec![(1, 15), (0, 15), (-1, 8)])))
+        );
+        assert_that!(
+            visited(0.1, 10, 3),
+            equal_to(Some((3, std::vec![(-1, 1), (-2, 0), (-3, 0)])))
+        );
+        assert_that!(visited(0.0, 2, 8), equal_to(Some((1, std::vec![(-1, 0)]))));
+        // Without fractional digits the units digit is the last.
+        assert_that!(
+            visited(16.0, 16, 0),
+            equal_to(Some((2, std::vec![(1, 1), (0, 0)])))
+        );
+        assert_that!(visited(f64::INFINITY, 2, 8), equal_to(None));
+        assert_that!(visited(0.5, 1, 8), equal_to(None));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_base_numbers() {
+        use std::string::ToString;
+
+        let number = BaseNumber::new(255.5, 16, 8).unwrap();
+        assert_that!(number.to_string(), equal_to("15;15.8;"));
+        assert_that!(number.integer_digits, equal_to(std::vec![15, 15]));
+        assert_that!(number.fraction_digits, equal_to(std::vec![8]));
+        let zero = BaseNumber::new(0.0, 2, 8).unwrap();
+        assert!(zero.integer_digits.is_empty() && !zero.negative);
+        assert_that!(zero.to_string(), equal_to("0.0;"));
+        assert_that!(
+            BaseNumber::new(-0.1, 10, 3).unwrap().to_string(),
+            equal_to("-0.1;0;0;")
+        );
+        assert_that!(BaseNumber::new(f64::NAN, 2, 8), equal_to(None));
+        assert_that!(BaseNumber::new(0.5, 1, 8), equal_to(None));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_parse_base_numbers() {
+        let number = BaseNumber::new(0.6875, 2, 8).unwrap();
+        assert_that!("2:0.1;0;1;1;".parse(), equal_to(Ok(number.clone())));
+        assert_that!("0.1011₂".parse(), equal_to(Ok(number.clone())));
+        assert_that!("0.1;0;1;1;₂".parse(), equal_to(Ok(number.clone())));
+        assert_that!(
+            BaseNumber::parse("0.1;0;1;1;", Some(2)),
+            equal_to(Ok(number))
+        );
+        assert_that!(
+            "16:-FF.8".parse(),
+            equal_to(Ok(BaseNumber::new(-255.5, 16, 8).unwrap()))
+        );
+        assert_that!(
+            BaseNumber::parse("0.[40]", Some(64)).map(|number| number.fraction_digits),
+            equal_to(Ok(std::vec![40]))
+        );
+        assert_that!(
+            "10:12.5".parse::<BaseNumber>().unwrap().value(),
+            equal_to(12.5)
+        );
+        // A subscript and a prefix that disagree, a digit too large for the
+        // base and a text without a base are all rejected.
+        assert!("3:0.1₂".parse::<BaseNumber>().is_err());
+        assert!("2:0.2".parse::<BaseNumber>().is_err());
+        assert!("0.1;".parse::<BaseNumber>().is_err());
+        assert!(BaseNumber::parse("0.x", Some(2)).is_err());
+        assert!(BaseNumber::parse("0.1", Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_fraction_bits() {
+        assert_that!(fraction_bits(0.1), equal_to((0xccccccccccccd, 55)));
+        assert_that!(fraction_bits(f64::MIN_POSITIVE / 4.0), equal_to((1, 1024)));
+        assert_that!(fraction_bits(1e300), equal_to((0, 0)));
+        assert_that!(fraction_bits(f64::INFINITY), equal_to((0, 0)));
+    }
+}
@@ -0,0 +1,102 @@
+//! The functions a WebAssembly host calls, built with
+//! `cargo rustc -p digits --release --features wasm --target wasm32-unknown-unknown --crate-type cdylib`.
+//!
+//! `to_base` writes its result into a buffer in the module's memory and
+//! returns its length, so that JavaScript can read it without any bindings:
+//!
+//! ```js
+//! const { instance } = await WebAssembly.instantiateStreaming(fetch("digits.wasm"));
+//! const { to_base, result_ptr, memory } = instance.exports;
+//! const length = to_base(0.1, 2, 20);
+//! const bytes = new Uint8Array(memory.buffer, result_ptr(), length);
+//! const text = new TextDecoder().decode(bytes); // "0.0;0;0;1;1;0;0;1;1;…"
+//! const digits = text.split(".")[1].split(";").filter(Boolean).map(Number);
+//! ```
+
+use core::fmt;
+use core::ptr::addr_of_mut;
+
+use crate::write_in_base;
+
+/// The most bytes a result can take.
+pub const RESULT_CAPACITY: usize = 64 * 1024;
+
+/// A result written by `to_base`.
+struct Output {
+    /// The text, of which the first `length` bytes are written.
+    bytes: [u8; RESULT_CAPACITY],
+    /// How many bytes are written.
+    length: usize,
+}
+
+impl fmt::Write for Output {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = self.length + text.len();
+        if end > RESULT_CAPACITY {
+            return Err(fmt::Error);
+        }
+        self.bytes[self.length..end].copy_from_slice(text.as_bytes());
+        self.length = end;
+        Ok(())
+    }
+}
+
+/// The result of the last call to `to_base`. A WebAssembly module runs on
+/// one thread, so it is never written and read at once.
+static mut RESULT: Output = Output {
+    bytes: [0; RESULT_CAPACITY],
+    length: 0,
+};
+
+/// Converts `value` to `base` with up to `precision` fractional digits,
+/// written as `0.d;d;d;` like the CLI does.
+///
+/// # Returns
+///
+/// The length in bytes of the UTF-8 result at `result_ptr()`, or 0 if the
+/// value cannot be converted (it is not finite, its integer part does not
+/// fit in 64 bits, the base is below 2 or the result is too long).
+#[no_mangle]
+pub extern "C" fn to_base(value: f64, base: u32, precision: u32) -> u32 {
+    // SAFETY: the module is single-threaded and no other reference to the
+    // result is alive while it is written.
+    let result = unsafe { &mut *addr_of_mut!(RESULT) };
+    result.length = 0;
+    match write_in_base(result, value, base.into(), precision) {
+        Ok(()) => result.length as u32,
+        Err(fmt::Error) => {
+            result.length = 0;
+            0
+        }
+    }
+}
+
+/// The address of the result of the last call to `to_base`.
+#[no_mangle]
+pub extern "C" fn result_ptr() -> *const u8 {
+    // SAFETY: only the address is taken, no reference.
+    unsafe { addr_of_mut!(RESULT.bytes) as *const u8 }
+}
+
+/// Without the standard library, a WebAssembly module must say what a panic
+/// does; nothing in the conversion panics, so it simply stops.
+#[cfg(all(target_arch = "wasm32", not(test)))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    core::arch::wasm32::unreachable()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_to_base() {
+        let length = to_base(0.75, 2, 8) as usize;
+        // SAFETY: the tests of this module are the only users of the result.
+        let bytes = unsafe { core::slice::from_raw_parts(result_ptr(), length) };
+        assert_that!(bytes, equal_to(&b"0.1;1;"[..]));
+        assert_that!(to_base(f64::INFINITY, 2, 8), equal_to(0));
+    }
+}
@@ -0,0 +1,40 @@
+/*
+ * The C interface of the converter, implemented by the ma2 crate of the
+ * Machine_Assignment_1 workspace. Build it with `cargo build -p ma2 --release`
+ * and link against target/release/libma2.so (or libma2.a), e.g.
+ *
+ *     cc demo.c -Iffi/include -Ltarget/release -lma2
+ */
+#ifndef MA2_H
+#define MA2_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The number of fractional digits ma2_convert produces. */
+#define MA2_DEFAULT_DIGITS 8
+
+/*
+ * Converts value to base, writing it as "0.d;d;d;" like the command-line
+ * table, with up to MA2_DEFAULT_DIGITS exact fractional digits.
+ *
+ * Like snprintf, at most len bytes are written to buf, including the
+ * terminating NUL, and the return value is the length of the whole result,
+ * so a result was cut off when it is len or more. Returns -1 if the value
+ * cannot be converted: it is not finite, its integer part does not fit in
+ * 64 bits, or base is below 2.
+ */
+int ma2_convert(double value, uint32_t base, char *buf, size_t len);
+
+/* Like ma2_convert, with up to digits fractional digits. */
+int ma2_convert_digits(double value, uint32_t base, uint32_t digits, char *buf, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,134 @@
+//! The C interface of the converter, declared in `include/ma2.h`, so that C
+//! and C++ tooling can link against the same conversion code as the CLI.
+
+use std::ffi::c_char;
+use std::fmt::{self, Write};
+use std::slice;
+
+use digits::write_in_base;
+
+/// The number of fractional digits `ma2_convert` produces, which is
+/// `MA2_DEFAULT_DIGITS` in the header.
+pub const DEFAULT_DIGITS: u32 = 8;
+
+/// A C buffer filled like `snprintf` does: text past its end is dropped but
+/// still counted.
+struct CBuffer<'a> {
+    /// The bytes of the buffer, leaving out the room for the NUL.
+    bytes: &'a mut [u8],
+    /// The length of all the text written, including any dropped.
+    length: usize,
+}
+
+impl Write for CBuffer<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let start = self.length.min(self.bytes.len());
+        let end = (self.length + text.len()).min(self.bytes.len());
+        self.bytes[start..end].copy_from_slice(&text.as_bytes()[..end - start]);
+        self.length += text.len();
+        Ok(())
+    }
+}
+
+/// Converts `value` to `base` into a C buffer with up to
+/// [`DEFAULT_DIGITS`] fractional digits. See `ma2.h`.
+///
+/// # Safety
+///
+/// `buf` must point to `len` writable bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn ma2_convert(value: f64, base: u32, buf: *mut c_char, len: usize) -> i32 {
+    ma2_convert_digits(value, base, DEFAULT_DIGITS, buf, len)
+}
+
+/// Converts `value` to `base` into a C buffer with up to `digits`
+/// fractional digits. See `ma2.h`.
+///
+/// # Safety
+///
+/// `buf` must point to `len` writable bytes, or `len` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn ma2_convert_digits(
+    value: f64,
+    base: u32,
+    digits: u32,
+    buf: *mut c_char,
+    len: usize,
+) -> i32 {
+    let bytes: &mut [u8] = if buf.is_null() || len == 0 {
+        &mut []
+    } else {
+        // SAFETY: the caller guarantees `len` writable bytes at `buf`.
+        unsafe { slice::from_raw_parts_mut(buf as *mut u8, len) }
+    };
+    // The last byte is kept for the NUL.
+    let capacity = bytes.len().saturating_sub(1);
+    let mut buffer = CBuffer {
+        bytes: &mut bytes[..capacity],
+        length: 0,
+    };
+    let converted = write_in_base(&mut buffer, value, base.into(), digits);
+    let length = buffer.length;
+
+    match converted {
+        Ok(()) => {
+            if let Some(nul) = bytes.get_mut(length.min(capacity)) {
+                *nul = 0;
+            }
+            i32::try_from(length).unwrap_or(i32::MAX)
+        }
+        Err(fmt::Error) => {
+            if let Some(first) = bytes.first_mut() {
+                *first = 0;
+            }
+            -1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    /// Calls `ma2_convert_digits` with a buffer of `len` bytes, returning
+    /// its result and the NUL-terminated text.
+    fn convert(value: f64, base: u32, digits: u32, len: usize) -> (i32, String) {
+        let mut buf = vec![0x7f_u8; len.max(1)];
+        let length = unsafe {
+            ma2_convert_digits(value, base, digits, buf.as_mut_ptr() as *mut c_char, len)
+        };
+        let text = buf
+            .iter()
+            .take_while(|&&byte| byte != 0)
+            .map(|&byte| byte as char);
+        (length, text.take(len).collect())
+    }
+
+    #[test]
+    fn test_convert_into_c_buffers() {
+        assert_that!(convert(0.75, 2, 8, 16), equal_to((6, "0.1;1;".to_string())));
+        assert_that!(
+            convert(255.5, 16, 8, 16),
+            equal_to((8, "15;15.8;".to_string()))
+        );
+        // A short buffer keeps what fits and reports the whole length.
+        assert_that!(convert(0.75, 2, 8, 4), equal_to((6, "0.1".to_string())));
+        assert_that!(convert(0.75, 2, 8, 0).0, equal_to(6));
+        assert_that!(convert(f64::NAN, 2, 8, 16), equal_to((-1, String::new())));
+        assert_that!(convert(0.5, 1, 8, 16).0, equal_to(-1));
+        // Bits below 2^-96 still reach the digits.
+        let (_, tiny) = convert(1e-30, 2, 120, 256);
+        assert_that!(tiny.find('1'), equal_to(Some(2 + 2 * 99)));
+    }
+
+    #[test]
+    fn test_header_declares_every_function() {
+        let header = include_str!("../include/ma2.h");
+        assert!(
+            header.contains("int ma2_convert(double value, uint32_t base, char *buf, size_t len);")
+        );
+        assert!(header.contains("int ma2_convert_digits(double value, uint32_t base, uint32_t digits, char *buf, size_t len);"));
+        assert!(header.contains(&format!("#define MA2_DEFAULT_DIGITS {}", DEFAULT_DIGITS)));
+    }
+}
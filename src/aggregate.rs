@@ -0,0 +1,602 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, Write};
+
+use crate::beta::GOLDEN_RATIO;
+use crate::cli::flag_value;
+use crate::conversion::Conversion;
+use crate::csv::split_fields;
+use crate::exit::write_error_message;
+use crate::format::OutputFormat;
+#[cfg(feature = "serde")]
+use crate::json::write_json_string;
+use crate::matrix::MATRIX_HEADER;
+use crate::output::{open_output, OutputOptions};
+
+/// One row of a result file: a conversion, and the digit limit it was run
+/// with when it is a row of a `--matrix-options` dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// The conversion of the input to the base.
+    pub conversion: Conversion,
+    /// The digit limit of the conversion, which only the rows of
+    /// `--matrix-options` record.
+    pub precision: Option<u32>,
+}
+
+impl Record {
+    /// Reads a record from the fields of a row, looked up by their names:
+    /// those of `MATRIX_HEADER`, or with no `precision` the fields of a
+    /// `Conversion` that `--format csv` and `jsonl` write, with or without
+    /// a `label`.
+    fn from_fields<'a>(field: impl Fn(&str) -> Option<&'a str>) -> Option<Record> {
+        let input = field("input")?.parse().ok()?;
+        let base = field("base")?.to_string();
+        let (conversion, precision) = match field("precision") {
+            Some(precision) => (
+                Conversion {
+                    label: None,
+                    input,
+                    base,
+                    digits: field("digit_count")?.parse().ok()?,
+                    truncated: !field("exact")?.parse::<bool>().ok()?,
+                    repr: field("representation")?.to_string(),
+                },
+                Some(precision.parse().ok()?),
+            ),
+            None => (
+                Conversion {
+                    label: field("label")
+                        .filter(|label| !label.is_empty())
+                        .map(str::to_string),
+                    input,
+                    base,
+                    digits: field("digits")?.parse().ok()?,
+                    truncated: field("truncated")?.parse().ok()?,
+                    repr: field("repr")?.to_string(),
+                },
+                None,
+            ),
+        };
+        Some(Record {
+            conversion,
+            precision,
+        })
+    }
+
+    /// Writes the record as a CSV row of the dataset of `--matrix-options`.
+    pub fn to_csv(&self) -> String {
+        let conversion = &self.conversion;
+        format!(
+            "{},{},{},{},{},{}",
+            conversion.input,
+            conversion.base,
+            self.precision.unwrap_or_default(),
+            conversion.digits,
+            !conversion.truncated,
+            conversion.repr
+        )
+    }
+
+    /// Writes the record as a line of NDJSON with the fields of
+    /// `MATRIX_HEADER`, as `--matrix-options --format jsonl` does.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let conversion = &self.conversion;
+        let mut object = format!("{{\"input\":{},\"base\":", conversion.input);
+        write_json_string(&mut object, &conversion.base);
+        object.push_str(&format!(
+            ",\"precision\":{},\"digit_count\":{},\"exact\":{},\"representation\":",
+            self.precision.unwrap_or_default(),
+            conversion.digits,
+            !conversion.truncated
+        ));
+        write_json_string(&mut object, &conversion.repr);
+        object.push('}');
+        object
+    }
+
+    /// The numeric value of the base, so that base 10 sorts after base 2.
+    fn base_value(&self) -> f64 {
+        if self.conversion.base == "φ" {
+            GOLDEN_RATIO
+        } else {
+            self.conversion.base.parse().unwrap_or(f64::NAN)
+        }
+    }
+
+    /// Orders records by input, then label, then base, then precision, as
+    /// the rows of a single run are ordered.
+    fn compare_keys(&self, other: &Record) -> Ordering {
+        self.conversion
+            .input
+            .total_cmp(&other.conversion.input)
+            .then_with(|| self.conversion.label.cmp(&other.conversion.label))
+            .then(self.base_value().total_cmp(&other.base_value()))
+            .then_with(|| self.conversion.base.cmp(&other.conversion.base))
+            .then(self.precision.cmp(&other.precision))
+    }
+}
+
+/// Parses a CSV row of a dataset without a header, in the order of
+/// `MATRIX_HEADER`.
+pub fn parse_csv_row(line: &str) -> Option<Record> {
+    let header: Vec<String> = MATRIX_HEADER.split(',').map(str::to_string).collect();
+    parse_csv_fields(&header, line)
+}
+
+/// Parses a CSV row under a header row, which names its fields. The
+/// representation is the last field and never holds a comma, but any that
+/// are left unquoted are kept in it.
+fn parse_csv_fields(header: &[String], line: &str) -> Option<Record> {
+    let mut fields = split_fields(line);
+    if fields.len() > header.len() {
+        let rest = fields.split_off(header.len() - 1).join(",");
+        fields.push(rest);
+    }
+    if fields.len() != header.len() {
+        return None;
+    }
+    Record::from_fields(|name| {
+        let i = header.iter().position(|field| field == name)?;
+        Some(fields[i].as_str())
+    })
+}
+
+/// Parses one line of NDJSON holding a flat object with the fields of
+/// `MATRIX_HEADER`, such as `{"input": 0.5, "base": "2", ...}`, or of a
+/// `Conversion`, as `--format jsonl` writes it. Values may be strings,
+/// numbers or booleans.
+pub fn parse_ndjson_row(line: &str) -> Option<Record> {
+    let pairs = parse_json_fields(line)?;
+    Record::from_fields(|name| {
+        pairs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    })
+}
+
+/// Reads the keys and values of a flat JSON object on one line, with every
+/// value as text: strings unescaped, and numbers and booleans as written.
+pub fn parse_json_fields(line: &str) -> Option<Vec<(String, String)>> {
+    let body = line.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut pairs = Vec::new();
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let (key, after_key) = parse_json_string(rest)?;
+        let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start();
+        let (value, after_value) = if after_colon.starts_with('"') {
+            parse_json_string(after_colon)?
+        } else {
+            let end = after_colon.find(',').unwrap_or(after_colon.len());
+            (after_colon[..end].trim().to_string(), &after_colon[end..])
+        };
+        pairs.push((key, value));
+        let after_value = after_value.trim_start();
+        rest = match after_value.strip_prefix(',') {
+            Some(next) => next.trim_start(),
+            None if after_value.is_empty() => after_value,
+            None => return None,
+        };
+    }
+    Some(pairs)
+}
+
+/// Reads a JSON string at the start of `text`, returning it unescaped with
+/// the text after it.
+fn parse_json_string(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &text[i + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'u' => {
+                    let hex: String = (0..4)
+                        .filter_map(|_| chars.next())
+                        .map(|(_, c)| c)
+                        .collect();
+                    value.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                escaped => value.push(escaped),
+            },
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+/// Reads every record of a result file, which is NDJSON if its first line
+/// starts with `{` and CSV otherwise. A CSV file whose first line names the
+/// fields, as the header of `--matrix-options` or of `--format csv` does,
+/// is read by them, and one without a header as a `--matrix-options`
+/// dataset.
+pub fn read_records(path: &str) -> Result<Vec<Record>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("cannot read `{}`: {}", path, error))?;
+    let ndjson = text.trim_start().starts_with('{');
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+    let mut header: Vec<String> = MATRIX_HEADER.split(',').map(str::to_string).collect();
+    if let Some((_, first)) = lines.peek().filter(|_| !ndjson) {
+        let names = split_fields(first.trim());
+        if names.iter().any(|name| name == "input") {
+            header = names;
+            lines.next();
+        }
+    }
+    lines
+        .map(|(number, line)| {
+            let record = if ndjson {
+                parse_ndjson_row(line)
+            } else {
+                parse_csv_fields(&header, line.trim())
+            };
+            record.ok_or_else(|| format!("{}: line {}: invalid result row", path, number + 1))
+        })
+        .collect()
+}
+
+/// The merged dataset of several result files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    /// The records, sorted and without duplicates.
+    pub records: Vec<Record>,
+    /// The number of records read from all files.
+    pub read: usize,
+    /// The number of records dropped as duplicates of another.
+    pub duplicates: usize,
+}
+
+/// Merges the records of several runs, sorting them and dropping the rows
+/// that more than one shard produced.
+///
+/// # Returns
+///
+/// The merged dataset, or a message naming the first input, base and
+/// precision that two files disagree about, since their runs must then have
+/// used different settings.
+///
+/// # Example
+///
+/// ```
+/// let merged = merge(vec![parse_csv_row("0.5,2,8,1,true,0.1;").unwrap(); 2]).unwrap();
+/// assert_eq!((merged.records.len(), merged.duplicates), (1, 1));
+/// ```
+pub fn merge(mut records: Vec<Record>) -> Result<Aggregate, String> {
+    let read = records.len();
+    let matrix = records
+        .iter()
+        .filter(|record| record.precision.is_some())
+        .count();
+    if matrix != 0 && matrix != read {
+        return Err(
+            "the rows of --matrix-options cannot be merged with those of --format csv or jsonl"
+                .to_string(),
+        );
+    }
+    records.sort_by(Record::compare_keys);
+
+    let mut merged: Vec<Record> = Vec::with_capacity(records.len());
+    for record in records {
+        match merged.last() {
+            Some(last) if last.compare_keys(&record) == Ordering::Equal => {
+                if *last != record {
+                    let precision = record
+                        .precision
+                        .map(|precision| format!(", precision {}", precision))
+                        .unwrap_or_default();
+                    return Err(format!(
+                        "conflicting results for input {}, base {}{}: `{}` and `{}`",
+                        record.conversion.input,
+                        record.conversion.base,
+                        precision,
+                        last.conversion.repr,
+                        record.conversion.repr
+                    ));
+                }
+            }
+            _ => merged.push(record),
+        }
+    }
+
+    Ok(Aggregate {
+        duplicates: read - merged.len(),
+        records: merged,
+        read,
+    })
+}
+
+/// Writes the combined summary of a merged dataset: how much was read and
+/// merged, and how many of the rows of every base are exact.
+pub fn display_aggregate_summary(
+    out: &mut dyn Write,
+    files: usize,
+    aggregate: &Aggregate,
+) -> io::Result<()> {
+    let mut inputs: Vec<f64> = aggregate
+        .records
+        .iter()
+        .map(|record| record.conversion.input)
+        .collect();
+    inputs.dedup_by(|a, b| a.total_cmp(b) == Ordering::Equal);
+    writeln!(
+        out,
+        "{} files, {} rows read, {} duplicates removed, {} rows for {} inputs",
+        files,
+        aggregate.read,
+        aggregate.duplicates,
+        aggregate.records.len(),
+        inputs.len()
+    )?;
+
+    let mut bases: Vec<&Record> = Vec::new();
+    for record in &aggregate.records {
+        if !bases
+            .iter()
+            .any(|base| base.conversion.base == record.conversion.base)
+        {
+            bases.push(record);
+        }
+    }
+    bases.sort_by(|a, b| a.base_value().total_cmp(&b.base_value()));
+    for base in bases {
+        let rows: Vec<&Record> = aggregate
+            .records
+            .iter()
+            .filter(|record| record.conversion.base == base.conversion.base)
+            .collect();
+        let exact = rows
+            .iter()
+            .filter(|record| !record.conversion.truncated)
+            .count();
+        writeln!(
+            out,
+            "base {}: {} rows, {} exact ({:.1}%)",
+            base.conversion.base,
+            rows.len(),
+            exact,
+            100.0 * exact as f64 / rows.len() as f64
+        )?;
+    }
+    Ok(())
+}
+
+/// The settings of the `aggregate` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateOptions {
+    /// The result files to merge.
+    pub files: Vec<String>,
+    /// Where the merged dataset goes.
+    pub output: OutputOptions,
+}
+
+/// Parses the arguments of the `aggregate` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `aggregate`.
+///
+/// # Returns
+///
+/// The parsed `AggregateOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- aggregate shard-1.csv shard-2.csv --output all.csv
+/// ```
+pub fn parse_aggregate_args(args: &[String]) -> Result<AggregateOptions, String> {
+    let mut options = AggregateOptions {
+        files: Vec::new(),
+        output: OutputOptions::default(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--output", &mut iter)? {
+            options.output.path = Some(value.to_string());
+        } else if arg == "--force" {
+            options.output.force = true;
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown aggregate argument `{}`", arg));
+        } else {
+            options.files.push(arg.clone());
+        }
+    }
+
+    if options.files.is_empty() {
+        return Err("aggregate requires at least one result file".to_string());
+    }
+    Ok(options)
+}
+
+/// Writes merged records as the CSV they were read as: the dataset of
+/// `--matrix-options`, or the rows of `--format csv` for conversions
+/// without a precision.
+fn write_records(out: &mut dyn Write, records: &[Record]) -> io::Result<()> {
+    if records.iter().all(|record| record.precision.is_some()) {
+        writeln!(out, "{}", MATRIX_HEADER)?;
+        return records
+            .iter()
+            .try_for_each(|record| writeln!(out, "{}", record.to_csv()));
+    }
+    let labelled = records
+        .iter()
+        .any(|record| record.conversion.label.is_some());
+    OutputFormat::Csv.write_header(out, labelled)?;
+    let conversions = records.iter().map(|record| Conversion {
+        label: labelled.then(|| record.conversion.label.clone().unwrap_or_default()),
+        ..record.conversion.clone()
+    });
+    OutputFormat::Csv.write_records(out, conversions)
+}
+
+/// Runs the `aggregate` subcommand: writes the merged dataset as CSV to the
+/// `--output` file or standard output, and the combined summary to standard
+/// error so that the dataset stays valid CSV.
+///
+/// # Returns
+///
+/// Whether every file was read and merged.
+pub fn display_aggregate(options: &AggregateOptions) -> bool {
+    let result = options
+        .files
+        .iter()
+        .map(|path| read_records(path))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|records| merge(records.concat()))
+        .and_then(|aggregate| {
+            let mut out = open_output(&options.output)?;
+            let written = write_records(&mut *out, &aggregate.records)
+                .and_then(|()| out.flush())
+                .and_then(|()| {
+                    display_aggregate_summary(&mut io::stderr(), options.files.len(), &aggregate)
+                });
+            written.map_err(write_error_message)
+        });
+
+    match result {
+        Ok(()) => true,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::make_conversions;
+    use crate::pipeline::Pipeline;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_parse_rows() {
+        let csv = parse_csv_row("0.1,2,4,4,false,0.0;0;0;1;").unwrap();
+        assert_that!(csv.conversion.input, equal_to(0.1));
+        assert_that!(csv.conversion.repr.as_str(), equal_to("0.0;0;0;1;"));
+        let ndjson = parse_ndjson_row(
+            r#"{"input": 0.1, "base": "2", "precision": 4, "digit_count": 4, "exact": false, "representation": "0.0;0;0;1;"}"#,
+        )
+        .unwrap();
+        assert_that!(ndjson, equal_to(csv));
+
+        assert!(parse_csv_row("0.1,2,4").is_none());
+        assert!(parse_ndjson_row(r#"{"input": 0.1}"#).is_none());
+        assert!(parse_ndjson_row("[0.1]").is_none());
+    }
+
+    #[test]
+    fn test_read_conversion_rows() {
+        let dir = std::env::temp_dir().join(format!("aggregate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let pipeline = Pipeline::with_bases(vec![2.0, 16.0]);
+        let write =
+            |name: &str, format: OutputFormat, numbers: &[f64], labels: &[Option<String>]| {
+                let fractions = vec![None; numbers.len()];
+                let limits = vec![None; numbers.len()];
+                let mut out = Vec::new();
+                format
+                    .write_header(&mut out, labels.iter().any(Option::is_some))
+                    .unwrap();
+                let conversions = make_conversions(&pipeline, numbers, &fractions, &limits, labels);
+                format.write_records(&mut out, conversions).unwrap();
+                let path = dir.join(name).display().to_string();
+                fs::write(&path, out).unwrap();
+                path
+            };
+        let first = write("a.csv", OutputFormat::Csv, &[0.5, 0.1], &[None, None]);
+        let labels = [Some("quarter, exactly".to_string())];
+        let second = write("b.csv", OutputFormat::Csv, &[0.25], &labels);
+        let mut records = read_records(&first).unwrap();
+        records.extend(read_records(&second).unwrap());
+        #[cfg(feature = "serde")]
+        {
+            let third = write("c.jsonl", OutputFormat::JsonLines, &[0.5], &[None]);
+            records.extend(read_records(&third).unwrap());
+        }
+        let merged = merge(records).unwrap();
+        let mut out = Vec::new();
+        write_records(&mut out, &merged.records).unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "label,input,base,digits,truncated,repr\n\
+                 ,0.1,2,8,true,0.0;0;0;1;1;0;0;1;\n\
+                 ,0.1,16,8,true,0.1;9;9;9;9;9;9;9;\n\
+                 \"quarter, exactly\",0.25,2,2,false,0.0;1;\n\
+                 \"quarter, exactly\",0.25,16,1,false,0.4;\n\
+                 ,0.5,2,1,false,0.1;\n\
+                 ,0.5,16,1,false,0.8;\n"
+            )
+        );
+        #[cfg(feature = "serde")]
+        assert_that!(merged.duplicates, equal_to(2));
+
+        let matrix = parse_csv_row("0.5,2,8,1,true,0.1;").unwrap();
+        #[cfg(feature = "serde")]
+        assert_that!(
+            parse_ndjson_row(&matrix.to_json()),
+            equal_to(Some(matrix.clone()))
+        );
+        assert!(merge(vec![matrix, merged.records[0].clone()]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_shards() {
+        let rows = |lines: &[&str]| -> Vec<Record> {
+            lines
+                .iter()
+                .map(|line| parse_csv_row(line).unwrap())
+                .collect()
+        };
+        let mut records = rows(&["0.5,10,8,1,true,0.5;", "0.5,2,8,1,true,0.1;"]);
+        records.extend(rows(&["0.25,2,8,2,true,0.0;1;", "0.5,2,8,1,true,0.1;"]));
+        let merged = merge(records).unwrap();
+        let csv: Vec<String> = merged.records.iter().map(Record::to_csv).collect();
+        assert_that!(
+            csv,
+            equal_to(vec![
+                "0.25,2,8,2,true,0.0;1;".to_string(),
+                "0.5,2,8,1,true,0.1;".to_string(),
+                "0.5,10,8,1,true,0.5;".to_string(),
+            ])
+        );
+        assert_that!(merged.duplicates, equal_to(1));
+
+        let mut summary = Vec::new();
+        display_aggregate_summary(&mut summary, 2, &merged).unwrap();
+        assert_that!(
+            String::from_utf8(summary).unwrap(),
+            equal_to(
+                "2 files, 4 rows read, 1 duplicates removed, 3 rows for 2 inputs\n\
+                 base 2: 2 rows, 2 exact (100.0%)\n\
+                 base 10: 1 rows, 1 exact (100.0%)\n"
+            )
+        );
+
+        let conflicting = rows(&["0.5,2,8,1,true,0.1;", "0.5,2,8,2,true,0.1;0;"]);
+        assert!(merge(conflicting).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_arguments() {
+        let args: Vec<String> = ["a.csv", "b.ndjson", "--output=all.csv", "--force"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let options = parse_aggregate_args(&args).unwrap();
+        assert_that!(options.files.len(), equal_to(2));
+        assert!(options.output.force);
+        assert!(parse_aggregate_args(&args[2..]).is_err());
+        assert!(parse_aggregate_args(&["--sort".to_string()]).is_err());
+    }
+}
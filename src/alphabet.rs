@@ -0,0 +1,631 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+
+use crate::bench::workload;
+use crate::cli::flag_value;
+use crate::convert::{Expansion, Representation, MAX_DIGITS};
+use crate::find::{parse_digits, parse_single_base};
+use crate::pipeline::Pipeline;
+
+/// The numeral scripts `--digit-script` writes digits in, by name, with the
+/// character of their digit zero. Every script has its ten digits in a row
+/// from there, as Unicode lays them out.
+pub const DIGIT_SCRIPTS: [(&str, char); 9] = [
+    ("latin", '0'),
+    ("arabic-indic", '\u{660}'),
+    ("eastern-arabic", '\u{6f0}'),
+    ("devanagari", '\u{966}'),
+    ("bengali", '\u{9e6}'),
+    ("tamil", '\u{be6}'),
+    ("thai", '\u{e50}'),
+    ("tibetan", '\u{f20}'),
+    ("fullwidth", '\u{ff10}'),
+];
+
+/// The symbols written for every digit value, in the order of the values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    /// The symbol of every digit value, so `symbols[3]` is written for a 3.
+    pub symbols: Vec<String>,
+}
+
+/// The digits read back from an encoded number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// Whether the number started with a minus sign.
+    pub negative: bool,
+    /// The digits before the radix point, most significant first.
+    pub integer_digits: Vec<u32>,
+    /// The digits after the radix point.
+    pub digits: Vec<u32>,
+}
+
+impl Alphabet {
+    /// Parses an alphabet given on the command line.
+    ///
+    /// Without whitespace, every character is the symbol of one digit, as in
+    /// `0123456789ABCDEF`. With whitespace, every word is a symbol, so that
+    /// symbols may be longer than one character, as in `zero one two`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let dna = Alphabet::parse("ACGT").unwrap();
+    /// assert_eq!(dna.symbols, vec!["A", "C", "G", "T"]);
+    /// ```
+    pub fn parse(spec: &str) -> Result<Alphabet, String> {
+        let symbols: Vec<String> = if spec.chars().any(char::is_whitespace) {
+            spec.split_whitespace().map(String::from).collect()
+        } else {
+            spec.chars().map(String::from).collect()
+        };
+        if symbols.len() < 2 {
+            return Err(format!(
+                "alphabet `{}` needs at least two symbols",
+                spec.trim()
+            ));
+        }
+        Ok(Alphabet { symbols })
+    }
+
+    /// The alphabet of a numeral script from [`DIGIT_SCRIPTS`], such as
+    /// `devanagari`: its ten digits, followed by the Latin letters `A`-`Z`
+    /// for the digits from ten up, which no script has digits of its own for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let arabic = Alphabet::script("arabic-indic").unwrap();
+    /// assert_eq!(arabic.symbols[..3], ["٠", "١", "٢"]);
+    /// assert_eq!(arabic.symbols[15], "F");
+    /// ```
+    pub fn script(name: &str) -> Result<Alphabet, String> {
+        let &(_, zero) = DIGIT_SCRIPTS
+            .iter()
+            .find(|(script, _)| *script == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = DIGIT_SCRIPTS.iter().map(|(script, _)| *script).collect();
+                format!(
+                    "unknown digit script `{}`; expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+        let digits = (0..10).filter_map(|digit| char::from_u32(zero as u32 + digit));
+        let symbols = digits.chain('A'..='Z').map(String::from).collect();
+        Ok(Alphabet { symbols })
+    }
+
+    /// Reads an alphabet from a file, as [`Alphabet::parse`] reads it from
+    /// the command line.
+    pub fn load(path: &str) -> Result<Alphabet, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read alphabet file `{}`: {}", path, error))?;
+        Alphabet::parse(text.trim())
+    }
+
+    /// Writes an expansion with the symbols of this alphabet and no separators
+    /// between its digits, or `None` if a digit has no symbol.
+    pub fn encode(&self, expansion: &Expansion) -> Option<String> {
+        let symbol = |&digit: &u32| self.symbols.get(digit as usize).map(String::as_str);
+        let integer: String = if expansion.integer_digits.is_empty() {
+            self.symbols[0].clone()
+        } else {
+            expansion
+                .integer_digits
+                .iter()
+                .map(symbol)
+                .collect::<Option<_>>()?
+        };
+        let fraction: String = expansion.digits.iter().map(symbol).collect::<Option<_>>()?;
+        let sign = if expansion.negative { "-" } else { "" };
+        Some(format!("{}{}.{}", sign, integer, fraction))
+    }
+
+    /// Reads back a number written by `encode`, always taking the longest
+    /// symbol that matches next.
+    ///
+    /// # Returns
+    ///
+    /// The digits, or `None` if some of the text is not made of symbols.
+    pub fn decode(&self, text: &str) -> Option<Decoded> {
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (integer, fraction) = unsigned.split_once('.')?;
+        let mut integer_digits = self.decode_digits(integer)?;
+        // A lone zero is how `encode` writes an empty integer part.
+        if integer_digits == [0] {
+            integer_digits.clear();
+        }
+        Some(Decoded {
+            negative,
+            integer_digits,
+            digits: self.decode_digits(fraction)?,
+        })
+    }
+
+    /// Splits a run of symbols into digit values.
+    fn decode_digits(&self, mut text: &str) -> Option<Vec<u32>> {
+        let mut digits = Vec::new();
+        while !text.is_empty() {
+            let (digit, symbol) = self
+                .symbols
+                .iter()
+                .enumerate()
+                .filter(|(_, symbol)| text.starts_with(symbol.as_str()))
+                .max_by_key(|(_, symbol)| symbol.len())?;
+            digits.push(digit as u32);
+            text = &text[symbol.len()..];
+        }
+        Some(digits)
+    }
+}
+
+/// An expansion written with the symbols of a `--alphabet`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Encoded<'a> {
+    /// The expansion shown.
+    pub expansion: Expansion,
+    /// The symbols it is written with.
+    pub alphabet: &'a Alphabet,
+}
+
+impl Representation for Encoded<'_> {
+    fn value(&self) -> f64 {
+        self.expansion.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.expansion.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.expansion.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.expansion.positional_digits()
+    }
+}
+
+impl fmt::Display for Encoded<'_> {
+    /// Writes the expansion with the alphabet, or as usual if a digit has no
+    /// symbol.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.alphabet.encode(&self.expansion) {
+            Some(encoded) => write!(f, "{}", encoded),
+            None => write!(f, "{}", self.expansion),
+        }
+    }
+}
+
+/// Something that keeps an alphabet from writing every number unambiguously.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The base needs more digits than the alphabet has symbols.
+    TooFewSymbols { target_base: u64, symbols: usize },
+    /// Two digits share a symbol.
+    Duplicate {
+        symbol: String,
+        first: u32,
+        second: u32,
+    },
+    /// The symbol of one digit starts the symbol of another, so a run of
+    /// digits can be split in more than one way.
+    Prefix { shorter: u32, longer: u32 },
+    /// A symbol contains the radix point or the minus sign.
+    Reserved { digit: u32, symbol: String },
+    /// A symbol starts with a combining mark, which is drawn on top of the
+    /// symbol before it, or with a character that is not drawn at all.
+    Invisible { digit: u32, symbol: String },
+    /// A number did not decode back to the digits it was encoded from.
+    RoundTrip {
+        value: f64,
+        encoded: String,
+        failures: usize,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::TooFewSymbols {
+                target_base,
+                symbols,
+            } => write!(
+                f,
+                "base {} needs {} symbols, but the alphabet has {}",
+                target_base, target_base, symbols
+            ),
+            Problem::Duplicate {
+                symbol,
+                first,
+                second,
+            } => write!(
+                f,
+                "ambiguous: `{}` is the symbol of both {} and {}",
+                symbol, first, second
+            ),
+            Problem::Prefix { shorter, longer } => write!(
+                f,
+                "ambiguous: the symbol of {} starts the symbol of {}",
+                shorter, longer
+            ),
+            Problem::Reserved { digit, symbol } => write!(
+                f,
+                "reserved: the symbol `{}` of {} contains `.` or `-`",
+                symbol, digit
+            ),
+            Problem::Invisible { digit, symbol } => write!(
+                f,
+                "invisible: the symbol {:?} of {} starts with a combining or zero-width character",
+                symbol, digit
+            ),
+            Problem::RoundTrip {
+                value,
+                encoded,
+                failures,
+            } => write!(
+                f,
+                "round trip: {} is written `{}`, which does not decode back ({} values fail)",
+                value, encoded, failures
+            ),
+        }
+    }
+}
+
+/// Whether `c` is a combining mark or a character that takes up no space.
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+    ) || c.is_control()
+}
+
+/// The values every alphabet check encodes and decodes: simple fractions,
+/// numbers with integer parts and the fixed `bench-compare` workload.
+fn battery(target_base: u64) -> Vec<f64> {
+    let base = target_base as f64;
+    let mut values = vec![
+        0.0,
+        0.5,
+        -0.75,
+        1.0 / 3.0,
+        1.0 / base,
+        base - 1.0,
+        base,
+        base * base + 1.0 / base,
+    ];
+    values.extend(
+        workload(200)
+            .iter()
+            .map(|fraction| fraction.value() * base * base),
+    );
+    values
+}
+
+/// Validates an alphabet, first on its own symbols and then by encoding and
+/// decoding a battery of values in the given base.
+///
+/// # Arguments
+///
+/// * `alphabet` - The alphabet to check.
+/// * `target_base` - The base the alphabet is meant for.
+/// * `digits` - The number of fractional digits every value is converted to.
+///
+/// # Returns
+///
+/// Every problem found, or nothing if the alphabet is safe to use.
+pub fn check_alphabet(alphabet: &Alphabet, target_base: u64, digits: u32) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let symbols = &alphabet.symbols[..alphabet.symbols.len().min(target_base as usize)];
+
+    if alphabet.symbols.len() < target_base as usize {
+        problems.push(Problem::TooFewSymbols {
+            target_base,
+            symbols: alphabet.symbols.len(),
+        });
+    }
+    for (digit, symbol) in symbols.iter().enumerate() {
+        let digit = digit as u32;
+        if symbol.contains(['.', '-']) {
+            problems.push(Problem::Reserved {
+                digit,
+                symbol: symbol.clone(),
+            });
+        }
+        if symbol.chars().next().is_some_and(is_invisible) {
+            problems.push(Problem::Invisible {
+                digit,
+                symbol: symbol.clone(),
+            });
+        }
+        for (other, other_symbol) in symbols.iter().enumerate().skip(digit as usize + 1) {
+            let other = other as u32;
+            if symbol == other_symbol {
+                problems.push(Problem::Duplicate {
+                    symbol: symbol.clone(),
+                    first: digit,
+                    second: other,
+                });
+            } else if other_symbol.starts_with(symbol.as_str()) {
+                problems.push(Problem::Prefix {
+                    shorter: digit,
+                    longer: other,
+                });
+            } else if symbol.starts_with(other_symbol.as_str()) {
+                problems.push(Problem::Prefix {
+                    shorter: other,
+                    longer: digit,
+                });
+            }
+        }
+    }
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let pipeline = Pipeline {
+        digits,
+        ..Pipeline::with_bases(vec![target_base as f64])
+    };
+    let mut first_failure = None;
+    let mut failures = 0;
+    for value in battery(target_base) {
+        let expansion = pipeline.expand_to(value, target_base as f64);
+        let encoded = alphabet.encode(&expansion).unwrap_or_default();
+        let round_trips = alphabet.decode(&encoded).is_some_and(|decoded| {
+            decoded.negative == expansion.negative
+                && decoded.integer_digits == expansion.integer_digits
+                && decoded.digits == expansion.digits
+        });
+        if !round_trips {
+            failures += 1;
+            first_failure.get_or_insert((value, encoded));
+        }
+    }
+    if let Some((value, encoded)) = first_failure {
+        problems.push(Problem::RoundTrip {
+            value,
+            encoded,
+            failures,
+        });
+    }
+
+    problems
+}
+
+/// The settings of the `alphabet check` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphabetOptions {
+    /// The alphabet to check.
+    pub alphabet: Alphabet,
+    /// The base the alphabet is meant for, by default one digit per symbol.
+    pub target_base: u64,
+    /// The number of fractional digits of every value in the battery.
+    pub digits: u32,
+}
+
+/// Parses the arguments of the `alphabet` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `alphabet`.
+///
+/// # Returns
+///
+/// The parsed `AlphabetOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- alphabet check 0123456789ABCDEFGHJKMNPQRSTVWXYZ --digits 12
+/// ```
+pub fn parse_alphabet_args(args: &[String]) -> Result<AlphabetOptions, String> {
+    let mut iter = args.iter();
+    match iter.next().map(String::as_str) {
+        Some("check") => {}
+        Some(action) => return Err(format!("unknown alphabet action `{}`", action)),
+        None => return Err("alphabet needs an action such as `check`".to_string()),
+    }
+
+    let mut alphabet = None;
+    let mut target_base = None;
+    let mut digits = MAX_DIGITS;
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            target_base = Some(parse_single_base(value)?);
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = parse_digits(value)?;
+        } else if alphabet.is_none() {
+            alphabet = Some(Alphabet::parse(arg)?);
+        } else {
+            return Err(format!("unknown alphabet argument `{}`", arg));
+        }
+    }
+
+    let alphabet = alphabet.ok_or("alphabet check needs the alphabet to check")?;
+    Ok(AlphabetOptions {
+        target_base: target_base.unwrap_or(alphabet.symbols.len() as u64),
+        alphabet,
+        digits,
+    })
+}
+
+/// Runs the `alphabet check` subcommand, printing every problem found.
+///
+/// # Returns
+///
+/// Whether the alphabet passed every check, or the error writing to `out`.
+///
+/// # Example
+/// ```
+/// // cargo run -- alphabet check "a b ab"
+/// ```
+/// Output:
+/// checking 3 symbols in base 3
+/// ambiguous: the symbol of 0 starts the symbol of 2
+pub fn display_alphabet_check(out: &mut impl Write, options: &AlphabetOptions) -> io::Result<bool> {
+    writeln!(
+        out,
+        "checking {} symbols in base {}",
+        options.alphabet.symbols.len(),
+        options.target_base
+    )?;
+    let problems = check_alphabet(&options.alphabet, options.target_base, options.digits);
+    for problem in &problems {
+        writeln!(out, "{}", problem)?;
+    }
+    if problems.is_empty() {
+        writeln!(
+            out,
+            "ok: {} values round-trip",
+            battery(options.target_base).len()
+        )?;
+    }
+    Ok(problems.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_encoded_column() {
+        let crockford = Alphabet::parse("0123456789ABCDEFGHJKMNPQRSTVWXYZ").unwrap();
+        let encoded = Encoded {
+            expansion: Pipeline::with_bases(vec![32.0]).expand_to(0.6875, 32.0),
+            alphabet: &crockford,
+        };
+        // 0.6875 is 22/32, and digit 22 is written P.
+        assert_that!(encoded.to_string(), equal_to("0.P"));
+        assert_that!(encoded.value(), equal_to(0.6875));
+        assert!(Alphabet::load("/nonexistent/alphabet").is_err());
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        let dna = Alphabet::parse("ACGT").unwrap();
+        let expansion = Pipeline::with_bases(vec![4.0]).expand_to(-5.75, 4.0);
+        let encoded = dna.encode(&expansion).unwrap();
+        assert_that!(encoded.as_str(), equal_to("-CC.T"));
+        assert_that!(
+            dna.decode(&encoded),
+            equal_to(Some(Decoded {
+                negative: true,
+                integer_digits: vec![1, 1],
+                digits: vec![3],
+            }))
+        );
+        assert_that!(dna.decode("A.X"), equal_to(None));
+
+        let words = Alphabet::parse("zero one").unwrap();
+        assert_that!(words.symbols, equal_to(vec!["zero", "one"]));
+        assert!(Alphabet::parse("A").is_err());
+    }
+
+    #[test]
+    fn test_digit_scripts() {
+        let devanagari = Alphabet::script("devanagari").unwrap();
+        let expansion = Pipeline::with_bases(vec![10.0]).expand_to(12.25, 10.0);
+        assert_that!(
+            devanagari.encode(&expansion),
+            equal_to(Some("१२.२५".to_string()))
+        );
+        let thai = Alphabet::script("thai").unwrap();
+        assert_that!(thai.symbols[9].as_str(), equal_to("๙"));
+        assert_that!(thai.symbols.len(), equal_to(36));
+        for (name, _) in DIGIT_SCRIPTS {
+            let script = Alphabet::script(name).unwrap();
+            assert!(script.symbols[..10]
+                .iter()
+                .all(|symbol| symbol.chars().all(|c| c.is_numeric())));
+        }
+        assert!(Alphabet::script("roman").is_err());
+    }
+
+    #[test]
+    fn test_safe_alphabets_pass() {
+        let crockford = Alphabet::parse("0123456789ABCDEFGHJKMNPQRSTVWXYZ").unwrap();
+        assert_that!(
+            check_alphabet(&crockford, 32, 8),
+            equal_to(Vec::<Problem>::new())
+        );
+        let digits = Alphabet::parse("٠١٢٣٤٥٦٧٨٩").unwrap();
+        assert_that!(
+            check_alphabet(&digits, 10, 8),
+            equal_to(Vec::<Problem>::new())
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_alphabets_are_reported() {
+        let problems =
+            |spec, target_base| check_alphabet(&Alphabet::parse(spec).unwrap(), target_base, 8);
+        assert_that!(
+            problems("a b ab", 3),
+            equal_to(vec![Problem::Prefix {
+                shorter: 0,
+                longer: 2
+            }])
+        );
+        assert_that!(
+            problems("0121", 4),
+            equal_to(vec![Problem::Duplicate {
+                symbol: "1".to_string(),
+                first: 1,
+                second: 3
+            }])
+        );
+        assert_that!(
+            problems("01.", 3),
+            equal_to(vec![Problem::Reserved {
+                digit: 2,
+                symbol: ".".to_string()
+            }])
+        );
+        assert_that!(
+            problems("0\u{301}", 2),
+            equal_to(vec![Problem::Invisible {
+                digit: 1,
+                symbol: "\u{301}".to_string()
+            }])
+        );
+        assert_that!(
+            problems("01", 3),
+            equal_to(vec![Problem::TooFewSymbols {
+                target_base: 3,
+                symbols: 2
+            }])
+        );
+    }
+
+    #[test]
+    fn test_alphabet_arguments() {
+        let options = parse_alphabet_args(&args("check ACGT --digits 4")).unwrap();
+        assert_that!(options.target_base, equal_to(4));
+        assert_that!(options.digits, equal_to(4));
+        let options = parse_alphabet_args(&args("check 0123456789 --base 8")).unwrap();
+        assert_that!(options.target_base, equal_to(8));
+        assert!(parse_alphabet_args(&args("check")).is_err());
+        assert!(parse_alphabet_args(&args("fix ACGT")).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::{self, Write};
+
+use crate::backend::Backend;
+use crate::cli::flag_value;
+use crate::convert::{Expansion, Representation, MAX_DIGITS};
+use crate::find::{parse_digits, parse_single_base};
+use crate::pipeline::{parse_backend, Pipeline};
+use crate::rational::Fraction;
+
+/// The settings of the `analyze` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzeOptions {
+    /// The base the expansions are computed in.
+    pub target_base: u64,
+    /// The maximum number of fractional digits to produce.
+    pub digits: u32,
+    /// Whether to print the digit sum diagnostics of every expansion.
+    pub digit_sums: bool,
+    /// The backend forced with `--backend`, or `None` to pick an exact one
+    /// per input.
+    pub backend: Option<Backend>,
+    /// The numbers to analyze.
+    pub f64_numbers: Vec<f64>,
+    /// The exact fraction every number was written as, if any, as in `1/3`.
+    pub fractions: Vec<Option<Fraction>>,
+}
+
+/// The digit sums of an expansion, whose digits `d₁…dₖ`, those of the
+/// integer part followed by the fractional ones, are read as the integer
+/// `N = d₁·bᵏ⁻¹ + … + dₖ`, i.e. the number times `bᶠ` for `f` fractional
+/// digits.
+///
+/// Since `b ≡ 1 (mod b-1)`, the digit sum leaves the same remainder as `N`
+/// when divided by `b-1` (casting out nines in base 10). Since
+/// `b ≡ -1 (mod b+1)`, the same holds for the alternating digit sum and `b+1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitSums {
+    /// The base the digits are expressed in.
+    pub base: u64,
+    /// `d₁ + d₂ + … + dₖ`.
+    pub digit_sum: u64,
+    /// `dₖ - dₖ₋₁ + dₖ₋₂ - …`, starting from the last digit.
+    pub alternating_sum: i64,
+}
+
+impl DigitSums {
+    /// Computes the digit sums of the integer and fractional digits of an
+    /// expansion.
+    pub fn of(expansion: &Expansion) -> DigitSums {
+        let digits = || expansion.integer_digits.iter().chain(&expansion.digits);
+        let digit_sum = digits().map(|&digit| digit as u64).sum();
+        let alternating_sum = digits()
+            .rev()
+            .enumerate()
+            .map(|(i, &digit)| {
+                if i % 2 == 0 {
+                    digit as i64
+                } else {
+                    -(digit as i64)
+                }
+            })
+            .sum();
+        DigitSums {
+            base: expansion.base as u64,
+            digit_sum,
+            alternating_sum,
+        }
+    }
+
+    /// The remainder of the digits, read as an integer, divided by `b-1`.
+    pub fn residue_below(&self) -> u64 {
+        self.digit_sum % (self.base - 1)
+    }
+
+    /// The remainder of the digits, read as an integer, divided by `b+1`.
+    pub fn residue_above(&self) -> i64 {
+        self.alternating_sum.rem_euclid(self.base as i64 + 1)
+    }
+}
+
+/// Parses the arguments of the `analyze` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `analyze`.
+///
+/// # Returns
+///
+/// The parsed `AnalyzeOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- analyze --base 10 --digit-sums 0.75 0.123
+/// ```
+pub fn parse_analyze_args(args: &[String]) -> Result<AnalyzeOptions, String> {
+    let mut options = AnalyzeOptions {
+        target_base: 2,
+        digits: MAX_DIGITS,
+        digit_sums: false,
+        backend: None,
+        f64_numbers: Vec::new(),
+        fractions: Vec::new(),
+    };
+    // Numbers are read as the conversions read them, fractions included.
+    let decoder = Pipeline::with_bases(Vec::new());
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else if arg == "--digit-sums" {
+            options.digit_sums = true;
+        } else if let Some(value) = flag_value(arg, "--backend", &mut iter)? {
+            options.backend = parse_backend(value)?;
+        } else {
+            let (number, fraction) = decoder
+                .decode_input(arg)
+                .ok_or_else(|| format!("unknown analyze argument `{}`", arg))?;
+            options.f64_numbers.push(number);
+            options.fractions.push(fraction);
+        }
+    }
+
+    Ok(options)
+}
+
+/// Describes what a remainder says about divisibility, as in `≡ 3 (mod 9)`
+/// or `divisible by 9`.
+fn describe_residue(residue: i64, modulus: u64) -> String {
+    if residue == 0 {
+        format!("divisible by {}", modulus)
+    } else {
+        format!("≡ {} (mod {})", residue, modulus)
+    }
+}
+
+/// Runs the `analyze` subcommand, printing every expansion followed by the
+/// requested diagnostics.
+///
+/// # Example
+/// ```
+/// // cargo run -- analyze --base 10 --digit-sums 0.75
+/// ```
+/// Output:
+/// 0.75 in base 10: 0.7;5; (exact)
+///   digit sum: 12, so the digits read as an integer are ≡ 3 (mod 9)
+///   alternating digit sum: -2, so the digits read as an integer are ≡ 9 (mod 11)
+pub fn display_analysis(out: &mut impl Write, options: &AnalyzeOptions) -> io::Result<()> {
+    let pipeline = Pipeline {
+        digits: options.digits,
+        backend: options.backend,
+        ..Pipeline::with_bases(vec![options.target_base as f64])
+    };
+    for (&number, fraction) in options.f64_numbers.iter().zip(&options.fractions) {
+        let expansion =
+            pipeline.expand_input(number, fraction.as_ref(), options.target_base as f64);
+        let exactness = if expansion.is_exact() {
+            "exact"
+        } else {
+            "truncated"
+        };
+        writeln!(
+            out,
+            "{} in base {}: {} ({})",
+            number, options.target_base, expansion, exactness
+        )?;
+
+        if options.digit_sums {
+            let sums = DigitSums::of(&expansion);
+            writeln!(
+                out,
+                "  digit sum: {}, so the digits read as an integer are {}",
+                sums.digit_sum,
+                describe_residue(sums.residue_below() as i64, options.target_base - 1)
+            )?;
+            writeln!(
+                out,
+                "  alternating digit sum: {}, so the digits read as an integer are {}",
+                sums.alternating_sum,
+                describe_residue(sums.residue_above(), options.target_base + 1)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_exact_digits_by_default() {
+        let options = parse_analyze_args(&args("--base 10 --digits 20 0.1")).unwrap();
+        let exact = Backend::expand_auto(options.backend, 0.1, None, 10, 20);
+        assert_that!(
+            exact.to_string(),
+            equal_to("0.1;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;5;5;5;")
+        );
+
+        let options = parse_analyze_args(&args("--backend f64 0.1")).unwrap();
+        assert_that!(options.backend, equal_to(Some(Backend::F64)));
+        assert!(parse_analyze_args(&args("--backend abacus 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_digit_sums() {
+        let sums = DigitSums::of(&expand(0.375, 10, 8));
+        assert_that!(sums.digit_sum, equal_to(15));
+        assert_that!(sums.alternating_sum, equal_to(1));
+        assert_that!(sums.residue_below(), equal_to(375 % 9));
+        assert_that!(sums.residue_above(), equal_to(375 % 11));
+
+        // 0.E;7; in base 16 reads as 0xE7 = 231 = 15 · 15 + 6 = 17 · 13 + 10.
+        let sums = DigitSums::of(&expand(231.0 / 256.0, 16, 8));
+        assert_that!(sums.residue_below(), equal_to(6));
+        assert_that!(sums.residue_above(), equal_to(10));
+
+        // The digits of the integer part count too, and 12.5 reads as 125.
+        let sums = DigitSums::of(&expand(12.5, 10, 8));
+        assert_that!(sums.digit_sum, equal_to(8));
+        assert_that!(sums.residue_above(), equal_to(125 % 11));
+    }
+
+    #[test]
+    fn test_analyze_whole_numbers() {
+        let options = parse_analyze_args(&args("--digit-sums --base 10 123")).unwrap();
+        let mut out = Vec::new();
+        display_analysis(&mut out, &options).unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "123 in base 10: 1;2;3. (exact)\n  \
+                 digit sum: 6, so the digits read as an integer are ≡ 6 (mod 9)\n  \
+                 alternating digit sum: 2, so the digits read as an integer are ≡ 2 (mod 11)\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_analyze_arguments() {
+        let options = parse_analyze_args(&args("--base 10 --digit-sums 0.5 0.25")).unwrap();
+        assert_that!(options.target_base, equal_to(10));
+        assert!(options.digit_sums);
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.25]));
+        let options = parse_analyze_args(&args("1/4 1_000")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.25, 1000.0]));
+        assert!(options.fractions[0].is_some());
+        assert!(parse_analyze_args(&args("--sums 0.5")).is_err());
+        assert!(parse_analyze_args(&args("--base -2 0.5")).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use crate::convert::Representation;
+use crate::radices::{expand_mixed_radix, MixedRadix};
+use crate::rational::Fraction;
+
+/// The radices of the places of a degree that `--dms` writes: minutes,
+/// seconds and then decimal places of a second, the last repeating.
+pub const DMS_RADICES: [u32; 3] = [60, 60, 10];
+
+/// The most decimal places of a second `--dms` writes.
+pub const SECOND_DECIMALS: u32 = 3;
+
+/// A number of degrees shown as degrees, minutes and seconds, as in
+/// `12° 30′ 36.5″`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dms(pub MixedRadix);
+
+/// Converts decimal degrees into whole degrees, minutes, seconds and up to
+/// `SECOND_DECIMALS` decimal places of a second with the mixed radices of
+/// `DMS_RADICES`, truncating the rest. A decimal such as `12.51` is taken
+/// exactly, as the shortest decimal that reads back as its `f64`, rather
+/// than as the binary value just below it, so that it does not come out a
+/// thousandth of a second short.
+///
+/// # Arguments
+///
+/// * `decimal` - The number of degrees, which may be 1 or more.
+/// * `fraction` - The exact fraction the input was written as, if any.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(to_dms(12.51, None).to_string(), "12° 30′ 36″");
+/// assert_eq!(to_dms(-0.75, None).to_string(), "-0° 45′ 0″");
+/// ```
+pub fn to_dms(decimal: f64, fraction: Option<&Fraction>) -> Dms {
+    let shortest = Fraction::parse_decimal(&decimal.to_string());
+    Dms(expand_mixed_radix(
+        decimal,
+        fraction.or(shortest.as_ref()),
+        &DMS_RADICES,
+        2 + SECOND_DECIMALS,
+    ))
+}
+
+impl Representation for Dms {
+    fn value(&self) -> f64 {
+        self.0.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.0.is_exact()
+    }
+}
+
+impl fmt::Display for Dms {
+    /// Writes the degrees, minutes and whole seconds, with the decimal places
+    /// of the seconds only as far as they go.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = &self.0.digits;
+        let place = |i: usize| digits.get(i).copied().unwrap_or(0);
+        let zero = self.0.integer == 0u32.into() && digits.iter().all(|&digit| digit == 0);
+        if self.0.negative && !zero {
+            write!(f, "-")?;
+        }
+        write!(f, "{}° {}′ {}", self.0.integer, place(0), place(1))?;
+        if let Some(decimals) = digits.get(2..).filter(|decimals| !decimals.is_empty()) {
+            write!(f, ".")?;
+            for digit in decimals {
+                write!(f, "{}", digit)?;
+            }
+        }
+        write!(f, "″")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_dms_rendering() {
+        let dms = |decimal| to_dms(decimal, None).to_string();
+        assert_that!(dms(0.5), equal_to("0° 30′ 0″"));
+        assert_that!(dms(-0.75), equal_to("-0° 45′ 0″"));
+        assert_that!(dms(123.0), equal_to("123° 0′ 0″"));
+        assert_that!(dms(0.0), equal_to("0° 0′ 0″"));
+        assert_that!(dms(-33.8688), equal_to("-33° 52′ 7.68″"));
+        let angle = to_dms(12.51, None);
+        assert_that!(angle.to_string(), equal_to("12° 30′ 36″"));
+        assert!(angle.is_exact());
+        let seventh = Fraction::parse("1/7").unwrap();
+        let angle = to_dms(seventh.value(), Some(&seventh));
+        assert_that!(angle.to_string(), equal_to("0° 8′ 34.285″"));
+        assert!(!angle.is_exact());
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::alphabet::{Alphabet, Decoded};
+use crate::cli::flag_value;
+use crate::convert::{Expansion, Representation};
+use crate::find::parse_single_base;
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// The characters a written answer may separate its digits with.
+const SEPARATORS: [char; 4] = [';', ' ', ',', ':'];
+
+/// The settings of the `check-answer` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnswerOptions {
+    /// The decimal as given, such as `0.1` or `1/3`.
+    pub input: String,
+    /// The value of the decimal.
+    pub value: f64,
+    /// The exact fraction the decimal was written as, if any.
+    pub fraction: Option<Fraction>,
+    /// The base the answer is written in.
+    pub target_base: u64,
+    /// The claimed representation, such as `0.0;0;0;1;1;`.
+    pub claimed: String,
+    /// The symbols the answer is written with, from `--alphabet`, if any.
+    pub alphabet: Option<Alphabet>,
+}
+
+/// Parses the arguments of `check-answer <decimal> <base> <claimed>`.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `check-answer`.
+///
+/// # Returns
+///
+/// The parsed `AnswerOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- check-answer 0.1 2 0.00011
+/// ```
+pub fn parse_answer_args(args: &[String]) -> Result<AnswerOptions, String> {
+    let mut positional = Vec::new();
+    let mut alphabet = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--alphabet", &mut iter)? {
+            alphabet = Some(Alphabet::parse(value)?);
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+
+    let [input, base, claimed] = positional[..] else {
+        return Err(
+            "check-answer requires a decimal, a base and the claimed representation".to_string(),
+        );
+    };
+    let (value, fraction) = Pipeline::with_bases(vec![2.0])
+        .decode_input(input)
+        .ok_or_else(|| format!("invalid decimal `{}`", input))?;
+    Ok(AnswerOptions {
+        input: input.to_string(),
+        value,
+        fraction,
+        target_base: parse_single_base(base)?,
+        claimed: claimed.to_string(),
+        alphabet,
+    })
+}
+
+/// Reads the digits of a number written in a base, such as `0.0101`,
+/// `.0101`, `0.0;1;0;1;`, `0.0 1 0 1` or `-1;0.4`. Where the digits of a part
+/// are separated by `;`, spaces, commas or colons, every digit is a decimal
+/// number; otherwise every character is one digit, written with `0`-`9` and
+/// then `A`-`Z` in either case.
+///
+/// # Returns
+///
+/// The digits as written, or `None` if a digit does not fit the base.
+///
+/// # Example
+///
+/// ```
+/// let decoded = parse_written_digits("0.0;1;0;1;", 2).unwrap();
+/// assert_eq!(decoded.digits, vec![0, 1, 0, 1]);
+/// assert_eq!(parse_written_digits(".4c", 16).unwrap().digits, vec![4, 12]);
+/// ```
+pub fn parse_written_digits(text: &str, target_base: u64) -> Option<Decoded> {
+    let text = text.trim();
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let part = |part: &str| -> Option<Vec<u32>> {
+        let digits: Vec<u32> = if part.contains(SEPARATORS) {
+            part.split(SEPARATORS)
+                .filter(|digit| !digit.is_empty())
+                .map(|digit| digit.parse().ok())
+                .collect::<Option<_>>()?
+        } else {
+            part.chars()
+                .map(|c| c.to_digit(36))
+                .collect::<Option<_>>()?
+        };
+        digits
+            .iter()
+            .all(|&digit| u64::from(digit) < target_base)
+            .then_some(digits)
+    };
+
+    let mut integer_digits = part(integer)?;
+    while integer_digits.first() == Some(&0) {
+        integer_digits.remove(0);
+    }
+    Some(Decoded {
+        negative,
+        integer_digits,
+        digits: part(fraction)?,
+    })
+}
+
+/// Where a claimed representation first differs from the real one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// One of them is negative and the other is not.
+    Sign,
+    /// The digits before the point differ.
+    Integer,
+    /// The digit at `place` after the point (counting from 1) differs.
+    Digit {
+        place: usize,
+        expected: u32,
+        claimed: u32,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Sign => write!(f, "the sign differs"),
+            Difference::Integer => write!(f, "the digits before the point differ"),
+            Difference::Digit {
+                place,
+                expected,
+                claimed,
+            } => write!(
+                f,
+                "digit {} after the point should be {}, not {}",
+                place, expected, claimed
+            ),
+        }
+    }
+}
+
+/// Compares claimed digits with an expansion produced to as many digits as
+/// were claimed. Trailing zeros claimed after an expansion ends count as
+/// correct.
+///
+/// # Returns
+///
+/// The first difference, or `None` if every claimed digit is right.
+pub fn first_difference(expansion: &Expansion, claimed: &Decoded) -> Option<Difference> {
+    let is_zero = expansion.integer_digits.is_empty() && expansion.digits.is_empty();
+    if claimed.negative != expansion.negative && !is_zero {
+        return Some(Difference::Sign);
+    }
+    if claimed.integer_digits != expansion.integer_digits {
+        return Some(Difference::Integer);
+    }
+    claimed.digits.iter().enumerate().find_map(|(i, &digit)| {
+        let expected = expansion.digits.get(i).copied().unwrap_or(0);
+        (digit != expected).then_some(Difference::Digit {
+            place: i + 1,
+            expected,
+            claimed: digit,
+        })
+    })
+}
+
+/// Checks a claimed representation, such as a student's answer.
+///
+/// # Returns
+///
+/// The expansion to as many digits as were claimed and the first
+/// difference from it, if any, or a message if the claim cannot be read.
+pub fn check_answer(options: &AnswerOptions) -> Result<(Expansion, Option<Difference>), String> {
+    let claimed = match &options.alphabet {
+        Some(alphabet) => alphabet.decode(&options.claimed.replace(';', "")),
+        None => parse_written_digits(&options.claimed, options.target_base),
+    }
+    .ok_or_else(|| {
+        format!(
+            "`{}` is not a number in base {}",
+            options.claimed, options.target_base
+        )
+    })?;
+
+    let pipeline = Pipeline {
+        digits: claimed.digits.len() as u32,
+        ..Pipeline::with_bases(vec![options.target_base as f64])
+    };
+    let expansion = pipeline.expand_input(
+        options.value,
+        options.fraction.as_ref(),
+        options.target_base as f64,
+    );
+    let difference = first_difference(&expansion, &claimed);
+    Ok((expansion, difference))
+}
+
+/// Runs `check-answer <decimal> <base> <claimed>`, printing whether the claim
+/// matches the conversion and, if not, the first digit that differs.
+///
+/// # Returns
+///
+/// Whether the claim matches, or the error writing to `out`.
+pub fn display_answer_check(out: &mut impl Write, options: &AnswerOptions) -> io::Result<bool> {
+    let (expansion, difference) = match check_answer(options) {
+        Ok(checked) => checked,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return Ok(false);
+        }
+    };
+    let shown = if expansion.is_exact() {
+        format!("is {}", expansion)
+    } else {
+        format!("starts {}…", expansion)
+    };
+    match difference {
+        None => {
+            writeln!(
+                out,
+                "match: {} in base {} {}",
+                options.input, options.target_base, shown
+            )?;
+            Ok(true)
+        }
+        Some(difference) => {
+            writeln!(
+                out,
+                "mismatch: {} ({} in base {} {})",
+                difference, options.input, options.target_base, shown
+            )?;
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn answer(line: &str) -> AnswerOptions {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        parse_answer_args(&args).unwrap()
+    }
+
+    #[test]
+    fn test_parse_written_digits() {
+        let decoded = parse_written_digits("-1;0.0 1", 2).unwrap();
+        assert!(decoded.negative);
+        assert_that!(decoded.integer_digits, equal_to(vec![1, 0]));
+        assert_that!(decoded.digits, equal_to(vec![0, 1]));
+        assert_that!(
+            parse_written_digits("0.15;30;", 60).unwrap().digits,
+            equal_to(vec![15, 30])
+        );
+        assert!(parse_written_digits("0.2", 2).is_none());
+        assert!(parse_written_digits("0.x", 10).is_none());
+    }
+
+    #[test]
+    fn test_check_answers() {
+        let (expansion, difference) = check_answer(&answer("0.1 2 0.00011")).unwrap();
+        assert_that!(difference, equal_to(None));
+        assert!(!expansion.is_exact());
+        assert_that!(
+            check_answer(&answer("0.25 2 0.0;1;0;0")).unwrap().1,
+            equal_to(None)
+        );
+        assert_that!(
+            check_answer(&answer("0.1 2 0.0010")).unwrap().1,
+            equal_to(Some(Difference::Digit {
+                place: 3,
+                expected: 0,
+                claimed: 1
+            }))
+        );
+        assert_that!(
+            check_answer(&answer("1/3 3 0.1")).unwrap().1,
+            equal_to(None)
+        );
+        assert_that!(
+            check_answer(&answer("1.5 2 0.1")).unwrap().1,
+            equal_to(Some(Difference::Integer))
+        );
+        assert_that!(
+            check_answer(&answer("0.75 4 --alphabet ACGT A.T"))
+                .unwrap()
+                .1,
+            equal_to(None)
+        );
+        assert!(check_answer(&answer("0.5 2 0.5")).is_err());
+
+        assert!(parse_answer_args(&["0.5".to_string(), "2".to_string()]).is_err());
+    }
+}
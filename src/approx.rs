@@ -0,0 +1,235 @@
+use std::io::{self, Write};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::cli::flag_value;
+use crate::convert::Representation;
+use crate::find::parse_single_base;
+use crate::pipeline::Pipeline;
+use crate::rational::{Fraction, Ratio};
+use crate::terminate::exact_input;
+
+/// The largest denominator `approx` allows when no `--max-denominator` is given.
+pub const DEFAULT_MAX_DENOMINATOR: u64 = 1000;
+
+/// The settings of the `approx` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApproxOptions {
+    /// The largest denominator an approximation may have.
+    pub max_denominator: BigUint,
+    /// The base the approximation is expanded in.
+    pub target_base: u64,
+    /// Every input as typed, with its exact value.
+    pub inputs: Vec<(String, Ratio)>,
+}
+
+/// Parses the arguments of the `approx` subcommand.
+///
+/// Inputs are decimals, which stand for the shortest decimal that reads back
+/// as their `f64`, such as 3333/10000 for `0.3333`, or fractions such as
+/// `355/113`, which are used as written.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `approx`.
+///
+/// # Returns
+///
+/// The parsed `ApproxOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- approx 0.3333 --max-denominator 100 --base 3
+/// ```
+pub fn parse_approx_args(args: &[String]) -> Result<ApproxOptions, String> {
+    let mut options = ApproxOptions {
+        max_denominator: BigUint::from(DEFAULT_MAX_DENOMINATOR),
+        target_base: 10,
+        inputs: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--max-denominator", &mut iter)? {
+            options.max_denominator = value
+                .parse::<BigUint>()
+                .ok()
+                .filter(|denominator| !denominator.is_zero())
+                .ok_or_else(|| format!("invalid maximum denominator `{}`", value))?;
+        } else if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else {
+            let ratio = Pipeline::with_bases(vec![2.0])
+                .decode_input(arg)
+                .and_then(|(value, fraction)| exact_input(value, fraction.as_ref()))
+                .ok_or_else(|| format!("unknown approx argument `{}`", arg))?;
+            options.inputs.push((arg.clone(), ratio));
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("approx requires a value to approximate".to_string());
+    }
+    Ok(options)
+}
+
+/// Finds the fraction closest to a number among those whose denominator is
+/// at most `max_denominator`.
+///
+/// The candidates are the convergents of the continued fraction of the
+/// number, which walk down the Stern–Brocot tree towards it, and the last
+/// semiconvergent before the denominator limit. The closer of the last
+/// convergent and that semiconvergent wins, the convergent on a tie.
+///
+/// # Example
+///
+/// ```
+/// let pi = Ratio::from_f64(std::f64::consts::PI).unwrap();
+/// let best = best_approximation(&pi, &BigUint::from(1000u32));
+/// assert_eq!((best.numerator, best.denominator), (BigUint::from(355u32), BigUint::from(113u32)));
+/// ```
+pub fn best_approximation(ratio: &Ratio, max_denominator: &BigUint) -> Fraction {
+    let fraction = |numerator: BigUint, denominator: BigUint| Fraction {
+        negative: ratio.negative && !numerator.is_zero(),
+        numerator,
+        denominator,
+    };
+    // p0/q0 and p1/q1 are the last two convergents.
+    let (mut p0, mut q0) = (BigUint::zero(), BigUint::one());
+    let (mut p1, mut q1) = (BigUint::one(), BigUint::zero());
+    let mut numerator = ratio.numerator.clone();
+    let mut denominator = ratio.denominator.clone();
+    loop {
+        let term = &numerator / &denominator;
+        let q2 = &q0 + &term * &q1;
+        if q2 > *max_denominator {
+            break;
+        }
+        let p2 = &p0 + &term * &p1;
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let remainder = &numerator - &term * &denominator;
+        (numerator, denominator) = (denominator, remainder);
+        if denominator.is_zero() {
+            // The number itself fits, and its last convergent is it in
+            // lowest terms.
+            return fraction(p1, q1);
+        }
+    }
+
+    let steps = (max_denominator - &q0) / &q1;
+    let semiconvergent = (&p0 + &steps * &p1, &q0 + &steps * &q1);
+    // Compare |x - a/b| without division: |x·b - a|/b, over x's denominator.
+    let distance = |(a, b): &(BigUint, BigUint)| {
+        let scaled = &ratio.numerator * b;
+        let target = a * &ratio.denominator;
+        let gap = if scaled > target {
+            scaled - target
+        } else {
+            target - scaled
+        };
+        (gap, b.clone())
+    };
+    let convergent = (p1, q1);
+    let (convergent_gap, convergent_b) = distance(&convergent);
+    let (semi_gap, semi_b) = distance(&semiconvergent);
+    let (best_numerator, best_denominator) = if semi_gap * &convergent_b < convergent_gap * &semi_b
+    {
+        semiconvergent
+    } else {
+        convergent
+    };
+    fraction(best_numerator, best_denominator)
+}
+
+/// Runs the `approx` subcommand, printing the best approximation of every
+/// input, how far it is off and its expansion in the target base.
+///
+/// # Example
+/// ```
+/// // cargo run -- approx 0.3333
+/// ```
+/// Output:
+/// 0.3333 ≈ 1/3 (off by 3.333333333332966e-5) = 0.3;3;3;3;3;3;3;3;… in base 10
+pub fn display_approximations(out: &mut impl Write, options: &ApproxOptions) -> io::Result<()> {
+    let pipeline = Pipeline::with_bases(vec![options.target_base as f64]);
+    for (input, ratio) in &options.inputs {
+        let best = best_approximation(ratio, &options.max_denominator);
+        let exact = Fraction {
+            negative: ratio.negative,
+            numerator: ratio.numerator.clone(),
+            denominator: ratio.denominator.clone(),
+        };
+        let value = best.value();
+        let expansion = pipeline.expand_input(value, Some(&best), options.target_base as f64);
+        writeln!(
+            out,
+            "{} ≈ {}{}/{} (off by {:e}) = {}{} in base {}",
+            input,
+            if best.negative { "-" } else { "" },
+            best.numerator,
+            best.denominator,
+            value - exact.value(),
+            expansion,
+            if expansion.is_exact() { "" } else { "…" },
+            options.target_base
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn approximate(line: &str) -> String {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        let options = parse_approx_args(&args).unwrap();
+        let best = best_approximation(&options.inputs[0].1, &options.max_denominator);
+        format!(
+            "{}{}/{}",
+            if best.negative { "-" } else { "" },
+            best.numerator,
+            best.denominator
+        )
+    }
+
+    #[test]
+    fn test_best_approximation() {
+        assert_that!(approximate("0.3333"), equal_to("1/3"));
+        assert_that!(
+            approximate("0.3333 --max-denominator 10000"),
+            equal_to("3333/10000")
+        );
+        assert_that!(
+            approximate("3.14159265358979 --max-denominator 100"),
+            equal_to("311/99")
+        );
+        assert_that!(approximate("3.14159265358979"), equal_to("355/113"));
+        assert_that!(approximate("-0.75"), equal_to("-3/4"));
+        assert_that!(approximate("0.001 --max-denominator 10"), equal_to("0/1"));
+        // The semiconvergent 289/92 of π lies closer than its convergent 22/7.
+        assert_that!(
+            approximate("3.14159265358979 --max-denominator 98"),
+            equal_to("289/92")
+        );
+        assert_that!(
+            approximate("3.14159265358979 --max-denominator 10"),
+            equal_to("22/7")
+        );
+        assert_that!(approximate("1/4 --max-denominator 3"), equal_to("1/3"));
+    }
+
+    #[test]
+    fn test_approx_arguments() {
+        let args =
+            |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        let options = parse_approx_args(&args("0.5 --base 2 --max-denominator 7")).unwrap();
+        assert_that!(options.target_base, equal_to(2));
+        assert_that!(options.max_denominator, equal_to(BigUint::from(7u32)));
+        assert!(parse_approx_args(&args("--max-denominator 0 0.5")).is_err());
+        assert!(parse_approx_args(&args("--base 10")).is_err());
+        assert!(parse_approx_args(&args("pi")).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use futures_core::Stream;
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::cancel::CancelToken;
+use crate::convert::Expansion;
+use crate::pipeline::Pipeline;
+use crate::rational::{Fraction, Ratio};
+
+/// How many digits a `DigitStream` hands out in a row before it lets the
+/// other tasks of its executor run.
+const DIGITS_PER_TURN: u32 = 256;
+
+/// The exact fractional digits of a number in a base, handed out one at a
+/// time to an async task, so that a huge precision never holds up the
+/// thread the task runs on.
+///
+/// It is a `futures_core::Stream`, so a tokio consumer reads it with the
+/// `StreamExt` of `futures` or `tokio-stream`, without the crate needing an
+/// async runtime of its own.
+#[derive(Debug, Clone)]
+pub struct DigitStream {
+    /// The part of the fraction not yet written as digits, over
+    /// `denominator`.
+    remainder: BigUint,
+    /// The denominator of the number.
+    denominator: BigUint,
+    /// The base the digits are in.
+    base: BigUint,
+    /// How many more digits may be produced.
+    remaining: u32,
+    /// Whether the fraction has run out.
+    done: bool,
+    /// How many digits were handed out since the stream last gave way.
+    turn: u32,
+}
+
+/// Starts the exact fractional digits of `|value|`, or of the fraction it
+/// was written as, in a base as an async stream.
+///
+/// # Arguments
+///
+/// * `value` - The number to convert.
+/// * `fraction` - The exact fraction it was written as, if any, whose
+///   digits are produced instead of those of `value`.
+/// * `target_base` - The base to convert to.
+/// * `max_digits` - The maximum number of digits to produce.
+///
+/// # Returns
+///
+/// The stream, or `None` if the value is not finite or the base is below 2.
+///
+/// # Example
+///
+/// ```
+/// let mut digits = to_base_stream(0.625, None, 2, 8).unwrap();
+/// assert_eq!(digits.next_digit().await, Some(1));
+/// ```
+pub fn to_base_stream(
+    value: f64,
+    fraction: Option<&Fraction>,
+    target_base: u64,
+    max_digits: u32,
+) -> Option<DigitStream> {
+    if target_base < 2 {
+        return None;
+    }
+    let exact = match fraction {
+        Some(fraction) => Ratio::from(fraction.clone()),
+        None => Ratio::from_f64(value)?,
+    };
+    Some(DigitStream {
+        remainder: &exact.numerator % &exact.denominator,
+        denominator: exact.denominator,
+        base: BigUint::from(target_base),
+        remaining: max_digits,
+        done: false,
+        turn: 0,
+    })
+}
+
+impl Stream for DigitStream {
+    type Item = u32;
+
+    /// Produces the next digit, `None` once the expansion has ended or the
+    /// digit limit is reached, or `Pending` every `DIGITS_PER_TURN` digits,
+    /// after asking to be polled again, to give way to the other tasks.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+        let stream = self.get_mut();
+        if stream.done || stream.remaining == 0 {
+            return Poll::Ready(None);
+        }
+        if stream.turn == DIGITS_PER_TURN {
+            stream.turn = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        stream.turn += 1;
+        stream.remaining -= 1;
+        stream.remainder *= &stream.base;
+        let digit = (&stream.remainder / &stream.denominator)
+            .to_u32()
+            .unwrap_or_default();
+        stream.remainder %= &stream.denominator;
+        stream.done = stream.remainder.is_zero();
+        Poll::Ready(Some(digit))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = if self.done {
+            0
+        } else {
+            self.remaining as usize
+        };
+        (left.min(1), Some(left))
+    }
+}
+
+impl DigitStream {
+    /// Waits for the next digit, as `StreamExt::next` does, for a consumer
+    /// without `futures`.
+    pub fn next_digit(&mut self) -> NextDigit<'_> {
+        NextDigit(self)
+    }
+}
+
+/// The future of the next digit of a `DigitStream`.
+#[derive(Debug)]
+pub struct NextDigit<'a>(&'a mut DigitStream);
+
+impl Future for NextDigit<'_> {
+    type Output = Option<u32>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
+
+/// What the thread of a `BatchConversion` hands back to its future.
+#[derive(Debug, Default)]
+struct BatchState {
+    /// The expansions, once all are made.
+    expansions: Option<Vec<Vec<Expansion>>>,
+    /// The task to wake once they are.
+    waker: Option<Waker>,
+}
+
+/// Converts a batch on a thread of its own, and resolves to the expansions
+/// of every input in every target base of the pipeline once all are made.
+/// Dropping it stops the conversions still to come.
+#[derive(Debug)]
+pub struct BatchConversion {
+    /// Shared with the converting thread.
+    state: Arc<Mutex<BatchState>>,
+    /// Stops the converting thread early.
+    cancel: CancelToken,
+}
+
+/// Converts every input to every target base of a pipeline without
+/// blocking the task that awaits it.
+///
+/// # Arguments
+///
+/// * `pipeline` - The stages every input is run through.
+/// * `inputs` - Every input's value and the fraction it was written as, if
+///   any.
+///
+/// # Example
+///
+/// ```
+/// let batch = convert_batch(&Pipeline::with_bases(vec![2.0]), vec![(0.5, None)]);
+/// assert_eq!(batch.await[0][0].to_string(), "0.1;");
+/// ```
+pub fn convert_batch(pipeline: &Pipeline, inputs: Vec<(f64, Option<Fraction>)>) -> BatchConversion {
+    let cancel = CancelToken::new();
+    let pipeline = Pipeline {
+        cancel: cancel.clone(),
+        ..pipeline.clone()
+    };
+    let state = Arc::new(Mutex::new(BatchState::default()));
+    let shared = Arc::clone(&state);
+    thread::spawn(move || {
+        let expansions = inputs
+            .iter()
+            .take_while(|_| !pipeline.cancel.is_cancelled())
+            .map(|(value, fraction)| {
+                pipeline
+                    .target_bases
+                    .iter()
+                    .map(|&target_base| {
+                        pipeline.expand_input(*value, fraction.as_ref(), target_base)
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut state = shared.lock().unwrap_or_else(|error| error.into_inner());
+        state.expansions = Some(expansions);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    BatchConversion { state, cancel }
+}
+
+impl Future for BatchConversion {
+    type Output = Vec<Vec<Expansion>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<Vec<Expansion>>> {
+        let mut state = self.state.lock().unwrap_or_else(|error| error.into_inner());
+        match state.expansions.take() {
+            Some(expansions) => Poll::Ready(expansions),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for BatchConversion {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    /// Wakes the thread that blocks on a future, counting its wake-ups.
+    struct Unpark(Thread, AtomicUsize);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.unpark();
+        }
+    }
+
+    /// Runs a future to completion on this thread, returning its output and
+    /// how often it was woken.
+    fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+        let unpark = Arc::new(Unpark(thread::current(), AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&unpark));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return (output, unpark.1.load(Ordering::Relaxed));
+            }
+            thread::park();
+        }
+    }
+
+    fn collect(mut stream: DigitStream) -> (Vec<u32>, usize) {
+        block_on(async move {
+            let mut digits = Vec::new();
+            while let Some(digit) = stream.next_digit().await {
+                digits.push(digit);
+            }
+            digits
+        })
+    }
+
+    #[test]
+    fn test_digit_stream() {
+        let (digits, _) = collect(to_base_stream(2.625, None, 2, 8).unwrap());
+        assert_that!(digits, equal_to(vec![1, 0, 1]));
+        let third = Fraction::parse("1/3").unwrap();
+        let (digits, _) = collect(to_base_stream(1.0 / 3.0, Some(&third), 10, 5).unwrap());
+        assert_that!(digits, equal_to(vec![3, 3, 3, 3, 3]));
+        assert_that!(
+            to_base_stream(0.5, None, 2, 8).unwrap().size_hint(),
+            equal_to((1, Some(8)))
+        );
+        assert!(to_base_stream(f64::NAN, None, 2, 8).is_none());
+        assert!(to_base_stream(0.5, None, 1, 8).is_none());
+
+        // A long expansion gives way to other tasks every few hundred digits.
+        let (digits, wakes) = collect(to_base_stream(1.0 / 3.0, Some(&third), 10, 1000).unwrap());
+        assert_that!(digits.len(), equal_to(1000));
+        assert_that!(wakes, equal_to(3));
+    }
+
+    #[test]
+    fn test_convert_batch() {
+        let pipeline = Pipeline::with_bases(vec![2.0, 16.0]);
+        let (expansions, _) = block_on(convert_batch(&pipeline, vec![(0.5, None), (0.1, None)]));
+        let written: Vec<Vec<String>> = expansions
+            .iter()
+            .map(|row| row.iter().map(ToString::to_string).collect())
+            .collect();
+        assert_that!(
+            written,
+            equal_to(vec![
+                vec!["0.1;".to_string(), "0.8;".to_string()],
+                vec![
+                    "0.0;0;0;1;1;0;0;1;".to_string(),
+                    "0.1;9;9;9;9;9;9;9;".to_string()
+                ],
+            ])
+        );
+        assert!(!pipeline.cancel.is_cancelled());
+    }
+}
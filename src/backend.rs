@@ -0,0 +1,418 @@
+use digits::{fraction_bits, ScaledDigits, SCALE_BITS};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::cancel::CancelToken;
+use crate::convert::{expand, integer_digits, integer_part, Expansion};
+use crate::rational::Fraction;
+
+/// The arithmetic used to extract the digits of an expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Multiplies an `f64` fraction by the base, which drifts once the
+    /// products need more than 53 bits.
+    F64,
+    /// Works on the fraction as a `u128` scaled by 2^96 when its lowest set
+    /// bit is at least 2^-96, and on fixed-size limbs below that, which is
+    /// exact for every stored `f64`.
+    IntegerScaled,
+    /// Works on the fraction as an arbitrary precision integer over its power
+    /// of two, which is exact for every stored `f64`.
+    Bignum,
+    /// Divides the numerator of an input written as a fraction such as `1/3`,
+    /// or as a decimal with more digits than an `f64` holds, by its
+    /// denominator, which is exact for the input itself rather than the `f64`
+    /// nearest to it. Other inputs use the bignum arithmetic.
+    LongDivision,
+}
+
+impl Backend {
+    /// Every enabled backend, in the order they are compared.
+    pub const ALL: [Backend; 4] = [
+        Backend::F64,
+        Backend::IntegerScaled,
+        Backend::Bignum,
+        Backend::LongDivision,
+    ];
+
+    /// Looks up a backend by its command-line name.
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL
+            .into_iter()
+            .find(|backend| backend.name() == name)
+    }
+
+    /// Picks the fastest backend that is still exact for an input.
+    ///
+    /// An input written as a fraction or a long decimal is divided out exactly. Otherwise the
+    /// `f64` loop is exact for values below one in bases that are powers of
+    /// two, whose products never need more bits than the input has, and for
+    /// inputs whose bits leave room for the base within the 53 bits of an
+    /// `f64`. The
+    /// integer-scaled backend covers every input whose bits fit its 96, and
+    /// bignum the rest (such as tiny or subnormal values).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(Backend::select(0.1, None, 10), Backend::IntegerScaled);
+    /// assert_eq!(Backend::select(0.1, None, 16), Backend::F64);
+    /// ```
+    pub fn select(value: f64, fraction: Option<&Fraction>, target_base: u64) -> Backend {
+        if fraction.is_some() {
+            return Backend::LongDivision;
+        }
+        let (_, bits) = fraction_bits(value);
+        let base_bits = 64 - (target_base - 1).leading_zeros();
+        if value.abs() < 1.0 && (target_base.is_power_of_two() || bits + base_bits <= 53) {
+            Backend::F64
+        } else if bits <= SCALE_BITS {
+            Backend::IntegerScaled
+        } else {
+            Backend::Bignum
+        }
+    }
+
+    /// Extracts up to `max_digits` fractional digits of `decimal` with
+    /// `backend`, or without one with the backend [`Backend::select`] picks,
+    /// whose digits are exact for the bits the `f64` holds, or for
+    /// `fraction` when the input is known as one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // The f64 loop drifts after the 17th decimal digit of 0.1.
+    /// let exact = Backend::expand_auto(None, 0.1, None, 10, 20);
+    /// assert_eq!(exact.to_string(), "0.1;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;5;5;5;");
+    /// ```
+    pub fn expand_auto(
+        backend: Option<Backend>,
+        decimal: f64,
+        fraction: Option<&Fraction>,
+        target_base: u64,
+        max_digits: u32,
+    ) -> Expansion {
+        backend
+            .unwrap_or_else(|| Backend::select(decimal, fraction, target_base))
+            .expand(decimal, fraction, target_base, max_digits)
+    }
+
+    /// The command-line name of the backend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::F64 => "f64",
+            Backend::IntegerScaled => "integer-scaled",
+            Backend::Bignum => "bignum",
+            Backend::LongDivision => "long-division",
+        }
+    }
+
+    /// Extracts up to `max_digits` fractional digits of `decimal` in the
+    /// target base with this backend's arithmetic.
+    ///
+    /// # Arguments
+    ///
+    /// * `decimal` - A floating-point number to convert.
+    /// * `fraction` - The exact fraction the input was written as, if any.
+    /// * `target_base` - The base to convert to.
+    /// * `max_digits` - The maximum number of fractional digits to produce.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // The f64 stored for 0.1 is slightly larger than 0.1 itself.
+    /// let expansion = Backend::Bignum.expand(0.1, None, 10, 20);
+    /// assert_eq!(expansion.to_string(), "0.1;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;5;5;5;");
+    /// ```
+    pub fn expand(
+        &self,
+        decimal: f64,
+        fraction: Option<&Fraction>,
+        target_base: u64,
+        max_digits: u32,
+    ) -> Expansion {
+        self.expand_cancellable(
+            decimal,
+            fraction,
+            target_base,
+            max_digits,
+            &CancelToken::new(),
+        )
+    }
+
+    /// Like [`Backend::expand`], but the integer backends stop extracting
+    /// digits as soon as `cancel` is cancelled, returning the digits found
+    /// so far.
+    pub fn expand_cancellable(
+        &self,
+        decimal: f64,
+        fraction: Option<&Fraction>,
+        target_base: u64,
+        max_digits: u32,
+        cancel: &CancelToken,
+    ) -> Expansion {
+        let (integer, (digits, remainder)) = match (self, fraction) {
+            (Backend::F64, _) => return expand(decimal, target_base, max_digits),
+            (Backend::IntegerScaled, _) => (
+                integer_part(decimal),
+                integer_scaled_digits(decimal, target_base, max_digits, cancel),
+            ),
+            (Backend::LongDivision, Some(fraction)) => {
+                return long_division(fraction, target_base, max_digits, cancel)
+            }
+            (Backend::Bignum, _) | (Backend::LongDivision, None) => (
+                integer_part(decimal),
+                bignum_digits(decimal, target_base, max_digits, cancel),
+            ),
+        };
+
+        Expansion {
+            negative: decimal < 0.0,
+            base: target_base as f64,
+            integer_digits: integer_digits(integer, target_base),
+            digits,
+            remainder,
+        }
+    }
+}
+
+/// Extracts the digits of an exact fraction by long division: every step
+/// multiplies the remainder by the base, and the quotient by the denominator
+/// is the next digit.
+fn long_division(
+    fraction: &Fraction,
+    target_base: u64,
+    max_digits: u32,
+    cancel: &CancelToken,
+) -> Expansion {
+    let denominator = &fraction.denominator;
+    let mut remainder = &fraction.numerator % denominator;
+
+    let mut digits = Vec::new();
+    for _ in 0..max_digits {
+        remainder *= target_base;
+        digits.push((&remainder / denominator).to_u32().unwrap_or(0));
+        remainder %= denominator;
+
+        if remainder.is_zero() || cancel.is_cancelled() {
+            break;
+        }
+    }
+
+    Expansion {
+        negative: fraction.negative && !fraction.numerator.is_zero(),
+        base: target_base as f64,
+        integer_digits: integer_digits(&fraction.numerator / denominator, target_base),
+        digits,
+        remainder: Fraction {
+            negative: false,
+            numerator: remainder,
+            denominator: denominator.clone(),
+        }
+        .value(),
+    }
+}
+
+/// Extracts digits from the fraction scaled to a `u128` with [`SCALE_BITS`]
+/// fractional bits, or held in wider limbs when it has bits below that.
+fn integer_scaled_digits(
+    decimal: f64,
+    target_base: u64,
+    max_digits: u32,
+    cancel: &CancelToken,
+) -> (Vec<u32>, f64) {
+    let mut extracted = ScaledDigits::new(decimal, target_base, max_digits);
+    let mut digits = Vec::new();
+    for digit in extracted.by_ref() {
+        digits.push(digit);
+        if cancel.is_cancelled() {
+            break;
+        }
+    }
+    (digits, extracted.remainder())
+}
+
+/// Extracts digits from the exact fraction `mantissa / 2^bits` held in a
+/// `BigUint`.
+fn bignum_digits(
+    decimal: f64,
+    target_base: u64,
+    max_digits: u32,
+    cancel: &CancelToken,
+) -> (Vec<u32>, f64) {
+    let (mantissa, bits) = fraction_bits(decimal);
+    let mut fraction = BigUint::from(mantissa);
+    let mask = (BigUint::from(1u32) << bits) - 1u32;
+
+    let mut digits = Vec::new();
+    for _ in 0..max_digits {
+        fraction *= target_base;
+        digits.push((&fraction >> bits).to_u32().unwrap_or(0));
+        fraction &= &mask;
+
+        if fraction.is_zero() || cancel.is_cancelled() {
+            break;
+        }
+    }
+
+    // Keep at most 64 bits of the leftover so the conversion to f64 cannot overflow.
+    let dropped = bits.saturating_sub(64);
+    let leading = (fraction >> dropped).to_f64().unwrap_or(0.0);
+    (digits, leading / 2f64.powi((bits - dropped) as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Representation;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_cancelled_conversions_stop() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let third = Fraction::parse("1/3").unwrap();
+        let expansion = Backend::LongDivision.expand_cancellable(
+            third.value(),
+            Some(&third),
+            10,
+            1000,
+            &cancel,
+        );
+        assert_that!(expansion.digits, equal_to(vec![3]));
+        let expansion = Backend::Bignum.expand_cancellable(0.1, None, 3, 1000, &cancel);
+        assert_that!(expansion.digits.len(), equal_to(1));
+        assert!(!expansion.is_exact());
+    }
+
+    #[test]
+    fn test_backends_agree_in_base_2() {
+        for backend in Backend::ALL {
+            let expansion = backend.expand(0.1, None, 2, 60);
+            assert_that!(expansion.digits.len(), equal_to(55));
+            assert!(expansion.remainder == 0.0, "{}", backend.name());
+            assert_that!(expansion, equal_to(expand(0.1, 2, 60)));
+            let whole = backend.expand(2.75, None, 2, 8);
+            assert_that!(whole.to_string(), equal_to("1;0.1;1;"));
+            let hex = backend.expand(255.5, None, 16, 8);
+            assert_that!(hex.to_string(), equal_to("15;15.8;"));
+        }
+    }
+
+    #[test]
+    fn test_integer_backends_do_not_drift() {
+        let exact = "0.1;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;0;5;5;5;";
+        assert_that!(
+            Backend::Bignum.expand(0.1, None, 10, 20).to_string(),
+            equal_to(exact)
+        );
+        assert_that!(
+            Backend::IntegerScaled.expand(0.1, None, 10, 20).to_string(),
+            equal_to(exact)
+        );
+        assert_that!(
+            Backend::Bignum.expand(-1.75, None, 16, 8).to_string(),
+            equal_to("-1.12;")
+        );
+        assert_that!(
+            Backend::IntegerScaled
+                .expand(300.5, None, 60, 8)
+                .to_string(),
+            equal_to("5;0.30;")
+        );
+
+        // Bits below 2^-96 are kept by the integer-scaled backend too.
+        for (tiny, target_base, digits) in
+            [(2f64.powi(-100), 2, 100), (1e-30, 2, 120), (1e-20, 10, 40)]
+        {
+            let bignum = Backend::Bignum.expand(tiny, None, target_base, digits);
+            let scaled = Backend::IntegerScaled.expand(tiny, None, target_base, digits);
+            assert_that!(scaled.digits, equal_to(bignum.digits));
+            assert!(scaled.remainder == bignum.remainder);
+        }
+        assert!(!Backend::IntegerScaled
+            .expand(1e-30, None, 2, 120)
+            .is_exact());
+    }
+
+    #[test]
+    fn test_backends_in_large_bases() {
+        // The largest f64 below one, whose 53 bits are two limbs of 2^32.
+        let below_one = 1.0 - 2f64.powi(-53);
+        for backend in Backend::ALL {
+            let expansion = backend.expand(below_one, None, 1 << 32, 4);
+            assert_that!(expansion.digits, equal_to(vec![u32::MAX, 0xffff_f800]));
+        }
+        let tenth = vec![
+            100_000_000,
+            5,
+            551_115_123,
+            125_782_702,
+            118_158_340,
+            454_101_562,
+            500_000_000,
+        ];
+        for backend in [Backend::IntegerScaled, Backend::Bignum] {
+            let expansion = backend.expand(0.1, None, 1_000_000_000, 8);
+            assert_that!(expansion.digits, equal_to(tenth.clone()));
+            assert_that!(expansion.remainder, equal_to(0.0));
+        }
+        assert_that!(
+            Backend::Bignum
+                .expand(2f64.powi(63) + 2f64.powi(32), None, 1 << 32, 1)
+                .integer_digits,
+            equal_to(vec![(1 << 31) + 1, 0])
+        );
+        let third = Fraction::parse("1/3").unwrap();
+        assert_that!(
+            Backend::LongDivision
+                .expand(third.value(), Some(&third), 1 << 32, 3)
+                .digits,
+            equal_to(vec![0x5555_5555; 3])
+        );
+        assert_that!(
+            Backend::select(0.1, None, 1_000_000_000),
+            equal_to(Backend::IntegerScaled)
+        );
+    }
+
+    #[test]
+    fn test_long_division() {
+        let third = Fraction::parse("1/3").unwrap();
+        let expansion = Backend::LongDivision.expand(third.value(), Some(&third), 10, 30);
+        assert_that!(expansion.digits.clone(), equal_to(vec![3; 30]));
+        assert!(!expansion.is_exact());
+
+        let mixed = Fraction::parse("-7/4").unwrap();
+        assert_that!(
+            Backend::LongDivision
+                .expand(mixed.value(), Some(&mixed), 2, 8)
+                .to_string(),
+            equal_to("-1.1;1;")
+        );
+    }
+
+    #[test]
+    fn test_select_backend() {
+        let third = Fraction::parse("1/3").unwrap();
+        assert_that!(
+            Backend::select(1.0 / 3.0, Some(&third), 10),
+            equal_to(Backend::LongDivision)
+        );
+        assert_that!(Backend::select(0.1, None, 2), equal_to(Backend::F64));
+        assert_that!(Backend::select(0.75, None, 10), equal_to(Backend::F64));
+        assert_that!(
+            Backend::select(0.1, None, 10),
+            equal_to(Backend::IntegerScaled)
+        );
+        assert_that!(
+            Backend::select(1.5, None, 2),
+            equal_to(Backend::IntegerScaled)
+        );
+        assert_that!(Backend::select(1e-30, None, 10), equal_to(Backend::Bignum));
+        assert_that!(
+            Backend::from_name("bignum"),
+            equal_to(Some(Backend::Bignum))
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+use crate::convert::{evaluate_fraction, Representation};
+
+/// The only base with a balanced digit set supported by `--balanced`.
+pub const BALANCED_BASE: u32 = 3;
+
+/// A number in balanced ternary, where every digit is -1, 0 or 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalancedExpansion {
+    /// The integer part, already rounded so that the fraction lies in [-½, ½].
+    pub integer: i64,
+    /// The fractional digits, most significant first, each in {-1, 0, 1}.
+    pub digits: Vec<i8>,
+    /// The part of the value left over after the last digit, measured in
+    /// units of the last digit's place. It always lies in [-½, ½].
+    pub remainder: f64,
+}
+
+/// Rounds to the nearest integer, settling exact halves towards zero so that
+/// the leftover part stays within [-½, ½].
+fn round_half_toward_zero(value: f64) -> f64 {
+    (value.abs() - 0.5).ceil() * value.signum()
+}
+
+/// Extracts up to `max_digits` balanced ternary digits of `decimal`.
+///
+/// Instead of taking the floor of `fraction × 3` as the ordinary conversion
+/// does, every digit is the nearest integer to it. This keeps each digit in
+/// {-1, 0, 1} and the carried fraction in [-½, ½], so negative numbers need no
+/// sign.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// A `BalancedExpansion` holding the digits. Extraction stops early once the
+/// fraction is exhausted.
+///
+/// # Example
+///
+/// ```
+/// let expansion = expand_balanced(0.75, MAX_DIGITS);
+/// assert_eq!(expansion.to_string(), "1.T;1;T;1;T;1;T;1;");
+/// ```
+pub fn expand_balanced(decimal: f64, max_digits: u32) -> BalancedExpansion {
+    let integer = round_half_toward_zero(decimal);
+    let mut digits = Vec::new();
+    let mut fraction = decimal - integer;
+
+    for _ in 0..max_digits {
+        fraction *= BALANCED_BASE as f64;
+        let digit = round_half_toward_zero(fraction);
+        digits.push(digit as i8);
+        fraction -= digit;
+
+        if fraction == 0.0 {
+            break;
+        }
+    }
+
+    BalancedExpansion {
+        integer: integer as i64,
+        digits,
+        remainder: fraction,
+    }
+}
+
+/// Renders a balanced ternary digit, writing -1 as `T`.
+fn render_digit(digit: i8) -> char {
+    match digit {
+        -1 => 'T',
+        0 => '0',
+        _ => '1',
+    }
+}
+
+/// Writes an integer as balanced ternary digits, most significant first.
+fn integer_digits(integer: i64) -> Vec<i8> {
+    // Wider than the integer, so that taking off a digit of `i64::MIN`,
+    // which infinite inputs saturate to, cannot overflow.
+    let mut integer = i128::from(integer);
+    let mut digits = Vec::new();
+    while integer != 0 {
+        let digit = match integer.rem_euclid(3) {
+            2 => -1,
+            remainder => remainder as i8,
+        };
+        digits.push(digit);
+        integer = (integer - digit as i128) / 3;
+    }
+    if digits.is_empty() {
+        digits.push(0);
+    }
+    digits.reverse();
+    digits
+}
+
+impl fmt::Display for BalancedExpansion {
+    /// Writes the expansion as `1T.0;1;T;`, with each fractional digit followed by a `;`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for digit in integer_digits(self.integer) {
+            write!(f, "{}", render_digit(digit))?;
+        }
+        write!(f, ".")?;
+        for &digit in &self.digits {
+            write!(f, "{};", render_digit(digit))?;
+        }
+        Ok(())
+    }
+}
+
+impl Representation for BalancedExpansion {
+    fn value(&self) -> f64 {
+        self.integer as f64 + evaluate_fraction(&self.digits, BALANCED_BASE as f64)
+    }
+
+    fn is_exact(&self) -> bool {
+        self.remainder == 0.0
+    }
+
+    fn base(&self) -> Option<f64> {
+        Some(BALANCED_BASE as f64)
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        Some(self.digits.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_balanced_ternary_expansions() {
+        assert_that!(expand_balanced(1.0 / 3.0, 8).to_string(), equal_to("0.1;"));
+        assert_that!(expand_balanced(-1.0 / 3.0, 8).to_string(), equal_to("0.T;"));
+        assert_that!(expand_balanced(0.5, 4).to_string(), equal_to("0.1;1;1;1;"));
+        assert_that!(expand_balanced(0.75, 4).to_string(), equal_to("1.T;1;T;1;"));
+        assert_that!(expand_balanced(4.0, 4).to_string(), equal_to("11.0;"));
+        assert_that!(expand_balanced(-2.0, 4).to_string(), equal_to("T1.0;"));
+    }
+
+    #[test]
+    fn test_balanced_value() {
+        assert_that!(expand_balanced(-1.0 / 9.0, 8).value(), equal_to(-1.0 / 9.0));
+        assert!(expand_balanced(0.25, 8).value() - 0.25 < 1e-3);
+        assert!(!expand_balanced(0.5, 8).is_exact());
+    }
+}
@@ -0,0 +1,268 @@
+use std::io::{self, Write};
+
+use crate::beta::format_base;
+use crate::cli::flag_value;
+use crate::convert::MAX_DIGITS;
+use crate::find::parse_digits;
+use crate::integer::Whole;
+use crate::pipeline::{parse_base_list, Pipeline};
+use crate::rational::Fraction;
+
+/// The largest base the grid of `matrix` reads the value in, whose digits
+/// are `0`-`9` and `a`-`z`.
+const LARGEST_READ_BASE: f64 = 36.0;
+
+/// The settings of the `matrix` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixOptions {
+    /// The bases of the rows and columns, in the order given.
+    pub target_bases: Vec<f64>,
+    /// The maximum number of fractional digits of every cell.
+    pub digits: u32,
+    /// Whether to list the value in every base, read in base 10, instead of
+    /// the grid reading it in every base.
+    pub list: bool,
+    /// The value as typed.
+    pub input: String,
+}
+
+/// Parses the arguments of the `matrix` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `matrix`.
+///
+/// # Returns
+///
+/// The parsed `MatrixOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- matrix --base 2,8,10,16 0.1
+/// ```
+pub fn parse_matrix_args(args: &[String]) -> Result<MatrixOptions, String> {
+    let mut target_bases = None;
+    let mut digits = MAX_DIGITS;
+    let mut list = false;
+    let mut input = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            target_bases = Some(parse_base_list(value)?);
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = parse_digits(value)?;
+        } else if arg == "--list" {
+            list = true;
+        } else if input.is_some() {
+            return Err(format!(
+                "matrix takes one value, so `{}` is one too many",
+                arg
+            ));
+        } else {
+            input = Some(arg.clone());
+        }
+    }
+
+    let target_bases =
+        target_bases.ok_or("matrix requires a list of bases, as in --base 2,8,16")?;
+    let input = input.ok_or("matrix requires a value to convert")?;
+    let options = MatrixOptions {
+        target_bases,
+        digits,
+        list,
+        input,
+    };
+    if list {
+        if decode(&options.input).is_none() {
+            return Err(format!("`{}` is not a number", options.input));
+        }
+    } else {
+        if let Some(&target_base) = options
+            .target_bases
+            .iter()
+            .find(|&&base| base.fract() != 0.0 || !(2.0..=LARGEST_READ_BASE).contains(&base))
+        {
+            return Err(format!(
+                "the grid reads the value in every base, which base {} cannot be; \
+                 give bases from 2 to 36, or pass --list",
+                format_base(target_base)
+            ));
+        }
+        if options
+            .target_bases
+            .iter()
+            .all(|&base| read_in(&options.input, base).is_none())
+        {
+            return Err(format!(
+                "`{}` is not a number in any of the bases",
+                options.input
+            ));
+        }
+    }
+    Ok(options)
+}
+
+/// Decodes the value of `--list` as a decimal input.
+fn decode(input: &str) -> Option<(f64, Option<Fraction>)> {
+    Pipeline::with_bases(vec![10.0])
+        .decode_input(input)
+        .filter(|(value, _)| value.is_finite())
+}
+
+/// Reads the value as a number written in a base, if its digits are digits
+/// of that base.
+fn read_in(input: &str, base: f64) -> Option<Fraction> {
+    Fraction::parse_in_base(input, base as u32)
+}
+
+/// Lays out the value in every base of `matrix`: the grid, a Markdown
+/// table with a row for every base it is read in and a column for every
+/// base it is written in, with `n/a` for a base it is not a number in, or
+/// with `--list` a line for every base it is written in.
+///
+/// # Returns
+///
+/// The lines, without line ends.
+///
+/// # Example
+///
+/// ```
+/// let options = parse_matrix_args(&["--base".into(), "2,16".into(), "11".into()]).unwrap();
+/// assert_eq!(
+///     matrix_lines(&options),
+///     ["| From \\ To | Base 2 | Base 16 |", "|:----------|:-------|:--------|",
+///      "| Base 2    | 11     | 3       |", "| Base 16   | 10001  | 11      |"]
+/// );
+/// ```
+pub fn matrix_lines(options: &MatrixOptions) -> Vec<String> {
+    let mut pipeline = Pipeline::with_bases(options.target_bases.clone());
+    pipeline.digits = options.digits;
+    let cells = |value: f64, fraction: Option<&Fraction>| -> Vec<String> {
+        options
+            .target_bases
+            .iter()
+            .map(|&target_base| {
+                Whole(pipeline.expand_input(value, fraction, target_base)).to_string()
+            })
+            .collect()
+    };
+    let titles: Vec<String> = options
+        .target_bases
+        .iter()
+        .map(|&target_base| format!("Base {}", format_base(target_base)))
+        .collect();
+
+    if options.list {
+        let (value, fraction) = decode(&options.input).unwrap_or((f64::NAN, None));
+        let width = titles.iter().map(String::len).max().unwrap_or(0);
+        return titles
+            .iter()
+            .zip(cells(value, fraction.as_ref()))
+            .map(|(title, cell)| format!("{:<1$}  {2}", title, width, cell))
+            .collect();
+    }
+
+    let mut rows = vec![std::iter::once("From \\ To".to_string())
+        .chain(titles.iter().cloned())
+        .collect::<Vec<_>>()];
+    for (title, &base) in titles.iter().zip(&options.target_bases) {
+        let row = match read_in(&options.input, base) {
+            Some(exact) => cells(exact.value(), Some(&exact)),
+            None => vec!["n/a".to_string(); titles.len()],
+        };
+        rows.push(std::iter::once(title.clone()).chain(row).collect());
+    }
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |row: &[String]| {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!(" {:<1$} ", cell, width))
+            .collect();
+        format!("|{}|", cells.join("|"))
+    };
+    let divider: Vec<String> = widths
+        .iter()
+        .map(|&width| format!(":{}", "-".repeat(width + 1)))
+        .collect();
+    let mut lines = vec![line(&rows[0]), format!("|{}|", divider.join("|"))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines
+}
+
+/// Runs the `matrix` subcommand, printing the value in every base.
+///
+/// # Example
+/// ```
+/// // cargo run -- matrix --base 2,10,16 0.1
+/// ```
+/// Output:
+/// | From \ To | Base 2             | Base 10    | Base 16            |
+/// |:----------|:-------------------|:-----------|:-------------------|
+/// | Base 2    | 0.1;               | 0.5;       | 0.8;               |
+/// | Base 10   | 0.0;0;0;1;1;0;0;1; | 0.1;       | 0.1;9;9;9;9;9;9;9; |
+/// | Base 16   | 0.0;0;0;1;         | 0.0;6;2;5; | 0.1;               |
+pub fn display_base_matrix(out: &mut impl Write, options: &MatrixOptions) -> io::Result<()> {
+    for line in matrix_lines(options) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn lines(line: &str) -> Vec<String> {
+        matrix_lines(&parse_matrix_args(&args(line)).unwrap())
+    }
+
+    #[test]
+    fn test_matrix_grid() {
+        assert_that!(
+            lines("--base 2,10,16 0.1"),
+            equal_to(vec![
+                "| From \\ To | Base 2             | Base 10    | Base 16            |".to_string(),
+                "|:----------|:-------------------|:-----------|:-------------------|".to_string(),
+                "| Base 2    | 0.1;               | 0.5;       | 0.8;               |".to_string(),
+                "| Base 10   | 0.0;0;0;1;1;0;0;1; | 0.1;       | 0.1;9;9;9;9;9;9;9; |".to_string(),
+                "| Base 16   | 0.0;0;0;1;         | 0.0;6;2;5; | 0.1;               |".to_string(),
+            ])
+        );
+        // `9` is not a digit in base 8.
+        assert_that!(
+            lines("--base 8,16 19")[2].clone(),
+            equal_to("| Base 8    | n/a    | n/a     |".to_string())
+        );
+        assert!(parse_matrix_args(&args("--base 2,1.5 0.1")).is_err());
+        assert!(parse_matrix_args(&args("--base 2,8 z")).is_err());
+        assert!(parse_matrix_args(&args("--base 2,8 0.1 0.2")).is_err());
+        assert!(parse_matrix_args(&args("0.1")).is_err());
+    }
+
+    #[test]
+    fn test_matrix_list() {
+        assert_that!(
+            lines("--list --base 2,16,-2 255"),
+            equal_to(vec![
+                "Base 2   11111111".to_string(),
+                "Base 16  FF".to_string(),
+                "Base -2  1;0;0;0;0;0;0;1;1.0;".to_string(),
+            ])
+        );
+        assert!(parse_matrix_args(&args("--list --base 2 junk")).is_err());
+    }
+}
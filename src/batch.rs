@@ -0,0 +1,218 @@
+use crate::backend::Backend;
+use crate::convert::Expansion;
+use crate::log::{enabled, Level};
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// The number of inputs the batched `f64` kernel works on at once, as many
+/// as an AVX register holds.
+pub const LANES: usize = 4;
+
+/// The number of inputs of a table converted together, so that those
+/// taking the `f64` path fill the lanes.
+pub const BATCH_SIZE: usize = 256;
+
+/// Multiplies every lane of fractions by the base and splits the products
+/// into their digits and what is left of the fractions, as one step of the
+/// `f64` digit loop does for a single input.
+fn step(fractions: &mut [f64; LANES], base: f64) -> [f64; LANES] {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx") {
+        // SAFETY: the processor has just been found to support AVX.
+        return unsafe { step_avx(fractions, base) };
+    }
+    let mut digits = [0.0; LANES];
+    for (fraction, digit) in fractions.iter_mut().zip(&mut digits) {
+        let product = *fraction * base;
+        // Every product is below the base, so it fits a `u32`, and taking
+        // that is truncating it without a call to `trunc`.
+        *digit = (product as u32) as f64;
+        *fraction = product - *digit;
+    }
+    digits
+}
+
+/// [`step`] with AVX instructions, which work on all the lanes at once.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "avx")]
+unsafe fn step_avx(fractions: &mut [f64; LANES], base: f64) -> [f64; LANES] {
+    use std::arch::x86_64::{
+        _mm256_loadu_pd, _mm256_mul_pd, _mm256_round_pd, _mm256_set1_pd, _mm256_storeu_pd,
+        _mm256_sub_pd, _MM_FROUND_NO_EXC, _MM_FROUND_TO_ZERO,
+    };
+
+    let mut digits = [0.0; LANES];
+    // SAFETY: both arrays hold exactly the four `f64` an AVX register does,
+    // and the unaligned loads and stores need no alignment.
+    unsafe {
+        let product = _mm256_mul_pd(_mm256_loadu_pd(fractions.as_ptr()), _mm256_set1_pd(base));
+        let truncated = _mm256_round_pd::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(product);
+        _mm256_storeu_pd(fractions.as_mut_ptr(), _mm256_sub_pd(product, truncated));
+        _mm256_storeu_pd(digits.as_mut_ptr(), truncated);
+    }
+    digits
+}
+
+/// Extracts the digits of up to `LANES` values below one at once with the
+/// `f64` digit loop, with every value stopping once its fraction runs out.
+///
+/// # Arguments
+///
+/// * `values` - At most `LANES` values whose magnitude is below one.
+/// * `target_base` - The base to convert to.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// The expansion of every value, the same as [`crate::convert::expand`]
+/// gives for it.
+pub fn expand_lanes(values: &[f64], target_base: u64, max_digits: u32) -> Vec<Expansion> {
+    let base = target_base as f64;
+    let mut fractions = [0.0; LANES];
+    for (fraction, value) in fractions.iter_mut().zip(values) {
+        *fraction = value.abs();
+    }
+    let mut digits: Vec<Vec<u32>> = vec![Vec::new(); values.len()];
+    // Every value gives at least one digit, even zero, like the single loop.
+    let mut done = [false; LANES];
+    for _ in 0..max_digits {
+        if done[..values.len()].iter().all(|&done| done) {
+            break;
+        }
+        let step_digits = step(&mut fractions, base);
+        for lane in 0..values.len() {
+            if !done[lane] {
+                digits[lane].push(step_digits[lane] as u32);
+                done[lane] = fractions[lane] == 0.0;
+            }
+        }
+    }
+    values
+        .iter()
+        .zip(digits)
+        .zip(fractions)
+        .map(|((&value, digits), remainder)| Expansion {
+            negative: value < 0.0,
+            base,
+            integer_digits: Vec::new(),
+            digits,
+            remainder,
+        })
+        .collect()
+}
+
+/// Converts a batch of inputs to every target base of a pipeline, the same
+/// as [`Pipeline::expand`] does one at a time, but with the inputs that take
+/// the `f64` path in every base converted `LANES` at a time. With `-v`,
+/// which logs the path every input takes, each is converted on its own.
+///
+/// # Returns
+///
+/// The expansions of every input in the order of `target_bases`.
+///
+/// # Example
+///
+/// ```
+/// let pipeline = Pipeline::with_bases(vec![2.0, 16.0]);
+/// let rows = expand_batch(&pipeline, &[(0.5, None), (0.1, None)]);
+/// assert_eq!(rows[1], pipeline.expand(0.1, None));
+/// ```
+pub fn expand_batch(
+    pipeline: &Pipeline,
+    inputs: &[(f64, Option<&Fraction>)],
+) -> Vec<Vec<Expansion>> {
+    let mut rows: Vec<Vec<Expansion>> = vec![Vec::new(); inputs.len()];
+    let batched = !(enabled(Level::Info) || pipeline.shortest || pipeline.exact_digits);
+    // The rows of the inputs that take the `f64` path, and their values.
+    let mut lanes = Vec::new();
+    let mut values = Vec::new();
+    for (i, &(value, fraction)) in inputs.iter().enumerate() {
+        if batched && fraction.is_none() && takes_f64_path(pipeline, value) {
+            lanes.push(i);
+            values.push(value);
+        } else {
+            rows[i] = pipeline.expand(value, fraction);
+        }
+    }
+
+    for &target_base in &pipeline.target_bases {
+        for (chunk, chunk_lanes) in values.chunks(LANES).zip(lanes.chunks(LANES)) {
+            let expansions = expand_lanes(chunk, target_base as u64, pipeline.digits);
+            for (&i, mut expansion) in chunk_lanes.iter().zip(expansions) {
+                expansion.round(pipeline.rounding);
+                rows[i].push(expansion);
+            }
+        }
+    }
+    rows
+}
+
+/// Whether the pipeline converts a value with the `f64` loop in every one
+/// of its bases, which are then all integers of at least 2.
+fn takes_f64_path(pipeline: &Pipeline, value: f64) -> bool {
+    // Zero is a whole number, which the pipeline converts without a loop.
+    value.abs() < 1.0
+        && value != 0.0
+        && pipeline.target_bases.iter().all(|&target_base| {
+            target_base >= 2.0
+                && target_base.fract() == 0.0
+                && pipeline
+                    .backend
+                    .unwrap_or_else(|| Backend::select(value, None, target_base as u64))
+                    == Backend::F64
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{expand, Rounding};
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_expand_lanes() {
+        let values = [0.1, -0.75, 0.0, 1.0 / 3.0, 5e-324, 0.999, 0.5];
+        for target_base in [2, 3, 10, 16] {
+            let batched: Vec<Expansion> = values
+                .chunks(LANES)
+                .flat_map(|chunk| expand_lanes(chunk, target_base, 40))
+                .collect();
+            let single: Vec<Expansion> = values
+                .iter()
+                .map(|&value| expand(value, target_base, 40))
+                .collect();
+            assert_that!(batched, equal_to(single));
+        }
+    }
+
+    #[test]
+    fn test_expand_batch() {
+        let pipeline = Pipeline {
+            digits: 6,
+            rounding: Rounding::HalfUp,
+            ..Pipeline::with_bases(vec![2.0, 16.0])
+        };
+        let fifth = Fraction::parse("1/5");
+        let inputs = [
+            (0.1, None),
+            (5.5, None),
+            (0.2, fifth.as_ref()),
+            (0.625, None),
+            (0.1, None),
+            (0.3, None),
+            (0.7, None),
+            (-0.9, None),
+        ];
+        let single: Vec<Vec<Expansion>> = inputs
+            .iter()
+            .map(|&(value, fraction)| pipeline.expand(value, fraction))
+            .collect();
+        assert_that!(expand_batch(&pipeline, &inputs), equal_to(single));
+
+        let decimal = Pipeline::with_bases(vec![10.0, -2.0]);
+        assert_that!(
+            expand_batch(&decimal, &inputs[..2]),
+            equal_to(vec![decimal.expand(0.1, None), decimal.expand(5.5, None)])
+        );
+    }
+}
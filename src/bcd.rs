@@ -0,0 +1,63 @@
+use std::fmt;
+
+use crate::convert::Representation;
+
+/// An input shown in binary-coded decimal: every decimal digit as its own
+/// 4-bit nibble, with the nibbles of the integer and fractional digits
+/// split by a point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bcd(pub f64);
+
+/// Encodes one decimal digit as a nibble, e.g. `0111` for 7.
+fn nibble(digit: char) -> String {
+    format!("{:04b}", digit.to_digit(10).unwrap_or(0))
+}
+
+impl Representation for Bcd {
+    fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// The digits encoded are the shortest decimal that reads back as the
+    /// input, so nothing is lost.
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Display for Bcd {
+    /// Writes the nibbles separated by spaces, e.g. `0001 0010.0111 0101`
+    /// for 12.75.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.0.is_finite() {
+            return write!(f, "{}", self.0);
+        }
+        let decimal = self.0.abs().to_string();
+        let (integer, fraction) = decimal.split_once('.').unwrap_or((&decimal, ""));
+        let nibbles = |digits: &str| digits.chars().map(nibble).collect::<Vec<_>>().join(" ");
+
+        if self.0.is_sign_negative() && self.0 != 0.0 {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", nibbles(integer))?;
+        if !fraction.is_empty() {
+            write!(f, ".{}", nibbles(fraction))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_bcd_nibbles() {
+        assert_that!(Bcd(12.75).to_string(), equal_to("0001 0010.0111 0101"));
+        assert_that!(Bcd(0.1).to_string(), equal_to("0000.0001"));
+        assert_that!(Bcd(-9.0).to_string(), equal_to("-1001"));
+        assert_that!(Bcd(0.0).to_string(), equal_to("0000"));
+        assert_that!(Bcd(f64::NAN).to_string(), equal_to("NaN"));
+    }
+}
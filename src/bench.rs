@@ -0,0 +1,475 @@
+#[cfg(feature = "bench-alloc")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::{self, Write};
+#[cfg(feature = "bench-alloc")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use num_bigint::BigUint;
+
+use crate::backend::Backend;
+use crate::cli::flag_value;
+use crate::convert::MAX_DIGITS;
+use crate::find::{parse_digits, parse_single_base};
+use crate::rational::Fraction;
+
+/// The settings of the `bench-compare` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchOptions {
+    /// The batch sizes to time, one table row per backend each.
+    pub counts: Vec<usize>,
+    /// The base every input is converted to.
+    pub target_base: u64,
+    /// The number of digits every input is converted to.
+    pub digits: u32,
+}
+
+/// The settings of the `bench` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThroughputOptions {
+    /// The number of inputs every backend converts.
+    pub count: usize,
+    /// The base every input is converted to.
+    pub target_base: u64,
+    /// The number of digits every input is converted to.
+    pub digits: u32,
+}
+
+/// The throughput of one backend on the `bench` workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Throughput {
+    /// The backend that was timed.
+    pub backend: Backend,
+    /// The number of inputs converted.
+    pub count: usize,
+    /// How long converting the whole workload took.
+    pub elapsed: Duration,
+    /// How many allocations the conversions made, if they were counted.
+    pub allocations: Option<usize>,
+    /// How many bytes those allocations asked for, if they were counted.
+    pub allocated_bytes: Option<usize>,
+}
+
+impl Throughput {
+    /// The number of inputs converted per second.
+    pub fn conversions_per_second(&self) -> f64 {
+        self.count as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// The number of allocations the program has made.
+#[cfg(feature = "bench-alloc")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// The number of bytes the program has asked to allocate.
+#[cfg(feature = "bench-alloc")]
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting every allocation so that `bench` can
+/// report how many the conversions make. It stands in for the allocator of
+/// the whole program, so it is only built with the `bench-alloc` feature.
+#[cfg(feature = "bench-alloc")]
+struct CountingAllocator;
+
+#[cfg(feature = "bench-alloc")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[cfg(feature = "bench-alloc")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The allocations made so far and the bytes they asked for, or `None`
+/// without the `bench-alloc` feature, which counts them.
+#[cfg(feature = "bench-alloc")]
+fn allocation_counts() -> Option<(usize, usize)> {
+    Some((
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    ))
+}
+
+/// The allocations made so far and the bytes they asked for, or `None`
+/// without the `bench-alloc` feature, which counts them.
+#[cfg(not(feature = "bench-alloc"))]
+fn allocation_counts() -> Option<(usize, usize)> {
+    None
+}
+
+/// The timing of one backend on one batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    /// The backend that was timed.
+    pub backend: Backend,
+    /// The number of inputs converted.
+    pub count: usize,
+    /// How long converting the whole batch took.
+    pub elapsed: Duration,
+    /// How many inputs got exactly the digits of the bignum backend.
+    pub exact: usize,
+}
+
+/// Parses the arguments of the `bench-compare` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `bench-compare`.
+///
+/// # Returns
+///
+/// The parsed `BenchOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run --release -- bench-compare --count 1000,100000 --base 10 --digits 20
+/// ```
+pub fn parse_bench_args(args: &[String]) -> Result<BenchOptions, String> {
+    let mut options = BenchOptions {
+        counts: vec![10_000],
+        target_base: 10,
+        digits: MAX_DIGITS,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(list) = flag_value(arg, "--count", &mut iter)? {
+            options.counts = list.split(',').map(parse_count).collect::<Result<_, _>>()?;
+        } else if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else {
+            return Err(format!("unknown bench-compare argument `{}`", arg));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses a batch size, such as `1000` or `1_000_000`.
+fn parse_count(value: &str) -> Result<usize, String> {
+    value
+        .trim()
+        .replace('_', "")
+        .parse::<usize>()
+        .map_err(|_| format!("invalid count `{}`", value))
+}
+
+/// Parses the arguments of the `bench` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `bench`.
+///
+/// # Returns
+///
+/// The parsed `ThroughputOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run --release -- bench --count 1_000_000 --precision 32
+/// ```
+pub fn parse_throughput_args(args: &[String]) -> Result<ThroughputOptions, String> {
+    let mut options = ThroughputOptions {
+        count: 100_000,
+        target_base: 10,
+        digits: MAX_DIGITS,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--count", &mut iter)? {
+            options.count = parse_count(value)?;
+        } else if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--precision", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else {
+            return Err(format!("unknown bench argument `{}`", arg));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Generates `count` fractions spread over [0, 1) from a fixed seed, so that
+/// every backend and every run converts the same inputs. Every denominator is
+/// 2^53, so the `f64` value of each input is exact.
+pub fn workload(count: usize) -> Vec<Fraction> {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    (0..count)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            Fraction {
+                negative: false,
+                numerator: BigUint::from(state >> 11),
+                denominator: BigUint::from(1u64 << 53),
+            }
+        })
+        .collect()
+}
+
+/// Converts every batch with every backend, timing each one and checking its
+/// digits against the exact bignum backend.
+pub fn run_benchmarks(options: &BenchOptions) -> Vec<Timing> {
+    let mut timings = Vec::new();
+
+    for &count in &options.counts {
+        let inputs = workload(count);
+        let reference: Vec<Vec<u32>> = inputs
+            .iter()
+            .map(|input| {
+                Backend::Bignum
+                    .expand(input.value(), None, options.target_base, options.digits)
+                    .digits
+            })
+            .collect();
+
+        for backend in Backend::ALL {
+            let start = Instant::now();
+            let expansions: Vec<_> = inputs
+                .iter()
+                .map(|input| {
+                    black_box(backend.expand(
+                        input.value(),
+                        Some(input),
+                        options.target_base,
+                        options.digits,
+                    ))
+                })
+                .collect();
+            let elapsed = start.elapsed();
+
+            let exact = expansions
+                .iter()
+                .zip(&reference)
+                .filter(|(expansion, digits)| expansion.digits == **digits)
+                .count();
+            timings.push(Timing {
+                backend,
+                count,
+                elapsed,
+                exact,
+            });
+        }
+    }
+
+    timings
+}
+
+/// Converts the workload with every backend, timing the conversions and
+/// counting the allocations they make with the `bench-alloc` feature. Every
+/// expansion is dropped as soon as
+/// it is made, so that only the conversion itself is measured.
+pub fn run_throughput(options: &ThroughputOptions) -> Vec<Throughput> {
+    let inputs = workload(options.count);
+    Backend::ALL
+        .into_iter()
+        .map(|backend| {
+            let before = allocation_counts();
+            let start = Instant::now();
+            for input in &inputs {
+                black_box(backend.expand(
+                    input.value(),
+                    Some(input),
+                    options.target_base,
+                    options.digits,
+                ));
+            }
+            let elapsed = start.elapsed();
+            let counted = before.zip(allocation_counts());
+            Throughput {
+                backend,
+                count: options.count,
+                elapsed,
+                allocations: counted.map(|((before, _), (after, _))| after - before),
+                allocated_bytes: counted.map(|((_, before), (_, after))| after - before),
+            }
+        })
+        .collect()
+}
+
+/// Runs the `bench` subcommand, printing the throughput and allocations of
+/// every backend. The allocations are written as `-` in a build without the
+/// `bench-alloc` feature, which does not count them.
+///
+/// # Example
+/// ```
+/// // cargo run --release -- bench --count 1_000_000 --precision 32
+/// ```
+/// Output:
+/// |    Backend     |   Conversions/s    |  Allocations   |  Allocs/input  |  Bytes/input   |
+/// |:---------------|:-------------------|:---------------|:---------------|:---------------|
+/// | f64            | 2020501            | 5000000        | 5.0            | 264.0          |
+pub fn display_throughput(out: &mut impl Write, options: &ThroughputOptions) -> io::Result<()> {
+    writeln!(
+        out,
+        "Converting {} inputs to base {} with {} digits",
+        options.count, options.target_base, options.digits
+    )?;
+    writeln!(
+        out,
+        "| {:^14} | {:^18} | {:^14} | {:^14} | {:^14} |",
+        "Backend", "Conversions/s", "Allocations", "Allocs/input", "Bytes/input"
+    )?;
+    writeln!(
+        out,
+        "|{:-<16}|{:-<20}|{:-<16}|{:-<16}|{:-<16}|",
+        ":", ":", ":", ":", ":"
+    )?;
+
+    let throughputs = run_throughput(options);
+    for throughput in &throughputs {
+        let count = throughput.count.max(1) as f64;
+        let per_input = |total: Option<usize>| {
+            total.map_or("-".to_string(), |total| {
+                format!("{:.1}", total as f64 / count)
+            })
+        };
+        writeln!(
+            out,
+            "| {:<14} | {:<18} | {:<14} | {:<14} | {:<14} |",
+            throughput.backend.name(),
+            format!("{:.0}", throughput.conversions_per_second()),
+            throughput
+                .allocations
+                .map_or("-".to_string(), |allocations| allocations.to_string()),
+            per_input(throughput.allocations),
+            per_input(throughput.allocated_bytes)
+        )?;
+    }
+    if throughputs
+        .iter()
+        .any(|throughput| throughput.allocations.is_none())
+    {
+        writeln!(
+            out,
+            "Allocations are only counted in a build with the bench-alloc feature \
+             (cargo run --release --features bench-alloc -- bench)."
+        )?;
+    }
+    Ok(())
+}
+
+/// Runs the `bench-compare` subcommand, printing one table row per batch
+/// size and backend.
+///
+/// # Example
+/// ```
+/// // cargo run --release -- bench-compare --count 1000
+/// ```
+/// Output:
+/// |    Backend     |   Inputs   |  Total ms  |  ns/input  |   Exact    |
+/// |:---------------|:-----------|:-----------|:-----------|:-----------|
+/// | f64            | 1000       | 0.150      | 150.2      | 1000       |
+pub fn display_bench(out: &mut impl Write, options: &BenchOptions) -> io::Result<()> {
+    writeln!(
+        out,
+        "Converting to base {} with {} digits",
+        options.target_base, options.digits
+    )?;
+    writeln!(
+        out,
+        "| {:^14} | {:^10} | {:^10} | {:^10} | {:^10} |",
+        "Backend", "Inputs", "Total ms", "ns/input", "Exact"
+    )?;
+    writeln!(
+        out,
+        "|{:-<16}|{:-<12}|{:-<12}|{:-<12}|{:-<12}|",
+        ":", ":", ":", ":", ":"
+    )?;
+
+    for timing in run_benchmarks(options) {
+        let nanos = timing.elapsed.as_nanos() as f64;
+        writeln!(
+            out,
+            "| {:<14} | {:<10} | {:<10} | {:<10} | {:<10} |",
+            timing.backend.name(),
+            timing.count,
+            format!("{:.3}", nanos / 1e6),
+            format!("{:.1}", nanos / timing.count.max(1) as f64),
+            timing.exact
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_bench_arguments() {
+        let options = parse_bench_args(&args("--count 1_000,50 --base 16 --digits 20")).unwrap();
+        assert_that!(options.counts, equal_to(vec![1000, 50]));
+        assert_that!(options.target_base, equal_to(16));
+        assert_that!(options.digits, equal_to(20));
+        assert!(parse_bench_args(&args("--count many")).is_err());
+        assert!(parse_bench_args(&args("0.5")).is_err());
+    }
+
+    #[test]
+    fn test_benchmarks_cover_every_backend() {
+        let inputs = workload(100);
+        assert!(inputs
+            .iter()
+            .all(|input| (0.0..1.0).contains(&input.value())));
+        assert_that!(workload(100), equal_to(inputs));
+
+        let options = parse_bench_args(&args("--count 20 --base 10 --digits 30")).unwrap();
+        let timings = run_benchmarks(&options);
+        assert_that!(timings.len(), equal_to(Backend::ALL.len()));
+        assert_that!(timings[2].exact, equal_to(20));
+        assert!(timings[0].exact < 20);
+        assert_that!(timings[3].exact, equal_to(20));
+    }
+
+    #[test]
+    fn test_throughput() {
+        let options = parse_throughput_args(&args("--count 1_000 --precision 32")).unwrap();
+        assert_that!(
+            options.clone(),
+            equal_to(ThroughputOptions {
+                count: 1000,
+                target_base: 10,
+                digits: 32,
+            })
+        );
+        assert!(parse_throughput_args(&args("--digits 32")).is_err());
+        assert!(parse_throughput_args(&args("--count 1,2")).is_err());
+
+        let throughputs = run_throughput(&options);
+        assert_that!(throughputs.len(), equal_to(Backend::ALL.len()));
+        // Every expansion holds its digits in a vector of its own.
+        assert!(throughputs
+            .iter()
+            .all(|throughput| match cfg!(feature = "bench-alloc") {
+                true => throughput.allocations >= Some(1000),
+                false => throughput.allocations.is_none(),
+            }));
+        assert!(throughputs[0].conversions_per_second() > 0.0);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::convert::{largest_digit, Expansion};
+
+/// The golden ratio φ, the best known non-integer base.
+pub const GOLDEN_RATIO: f64 = 1.618_033_988_749_895;
+
+/// How the digits of an expansion in a non-integer base β are picked. Unlike
+/// integer bases, most numbers have many β-expansions, and these two rules
+/// give the canonical extremes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetaRule {
+    /// Always take the largest digit that does not overshoot the value.
+    Greedy,
+    /// Always take the smallest digit that the remaining digits can still
+    /// make up for.
+    Lazy,
+}
+
+impl BetaRule {
+    /// Looks up a rule by its command-line name (`greedy` or `lazy`).
+    pub fn from_name(name: &str) -> Option<BetaRule> {
+        match name {
+            "greedy" => Some(BetaRule::Greedy),
+            "lazy" => Some(BetaRule::Lazy),
+            _ => None,
+        }
+    }
+
+    /// The command-line name of the rule.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BetaRule::Greedy => "greedy",
+            BetaRule::Lazy => "lazy",
+        }
+    }
+}
+
+/// Extracts the digits of `decimal` in the non-integer base `base`.
+///
+/// The value is first divided by the smallest power of β that brings it below
+/// one, and that many leading digits become the integer part. Each step then
+/// multiplies the fraction by β and picks a digit according to `rule`: the
+/// greedy rule takes the floor, while the lazy rule takes the smallest digit
+/// that keeps the fraction at most `m/(β-1)`, the largest value the remaining
+/// digits can still reach with the largest digit `m`.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `base` - The base to convert to, greater than 1.
+/// * `rule` - How the digits are picked.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// An `Expansion` holding the digits. Extraction stops early once the
+/// fraction is exhausted.
+///
+/// # Example
+///
+/// ```
+/// let expansion = expand_beta(0.5, GOLDEN_RATIO, BetaRule::Greedy, 5);
+/// assert_eq!(expansion.to_string(), "0.0;1;0;0;1;");
+/// ```
+pub fn expand_beta(decimal: f64, base: f64, rule: BetaRule, max_digits: u32) -> Expansion {
+    let largest = largest_digit(base);
+    let tail_bound = largest as f64 / (base - 1.0);
+
+    let mut integer_places = 0;
+    let mut fraction = decimal.abs();
+    // An infinite input would never drop below one.
+    while fraction >= 1.0 && fraction.is_finite() {
+        fraction /= base;
+        integer_places += 1;
+    }
+
+    let mut digits = Vec::new();
+    for _ in 0..integer_places + max_digits {
+        let product = fraction * base;
+        let digit = match rule {
+            BetaRule::Greedy => product.floor(),
+            BetaRule::Lazy => (product - tail_bound).ceil().max(0.0),
+        }
+        .min(largest as f64);
+        digits.push(digit as u32);
+        fraction = product - digit;
+
+        if fraction == 0.0 && digits.len() > integer_places as usize {
+            break;
+        }
+    }
+
+    let fraction_digits = digits.split_off(integer_places as usize);
+    Expansion {
+        negative: decimal < 0.0,
+        base,
+        integer_digits: digits,
+        digits: fraction_digits,
+        remainder: fraction,
+    }
+}
+
+/// Parses a non-integer base written as a number greater than 1 (`1.5`) or
+/// as `phi`/`φ` for the golden ratio.
+pub fn parse_float_base(value: &str) -> Result<f64, String> {
+    match value {
+        "phi" | "φ" => Ok(GOLDEN_RATIO),
+        _ => value
+            .parse::<f64>()
+            .ok()
+            .filter(|base| base.is_finite() && *base > 1.0)
+            .ok_or_else(|| format!("invalid non-integer base `{}`", value)),
+    }
+}
+
+/// Parses a comma separated list of non-integer bases such as `phi,1.5`.
+pub fn parse_float_base_list(value: &str) -> Result<Vec<f64>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(parse_float_base)
+        .collect()
+}
+
+/// Writes a base for a column header, showing the golden ratio as `φ`.
+pub fn format_base(base: f64) -> String {
+    if base == GOLDEN_RATIO {
+        "φ".to_string()
+    } else {
+        base.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Representation;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_greedy_and_lazy_expansions() {
+        let greedy = expand_beta(0.5, GOLDEN_RATIO, BetaRule::Greedy, 8);
+        let lazy = expand_beta(0.5, GOLDEN_RATIO, BetaRule::Lazy, 8);
+        assert_that!(greedy.to_string(), equal_to("0.0;1;0;0;1;0;0;1;"));
+        assert_that!(lazy.to_string(), equal_to("0.0;0;1;1;0;1;1;0;"));
+        assert!((greedy.value() - 0.5).abs() < 0.05);
+        assert!((lazy.value() - 0.5).abs() < 0.05);
+
+        // Even in base 2, the lazy rule trades the terminating expansion for a
+        // trailing run of ones.
+        assert_that!(
+            expand_beta(0.75, 2.0, BetaRule::Greedy, 8).to_string(),
+            equal_to("0.1;1;")
+        );
+        assert_that!(
+            expand_beta(0.75, 2.0, BetaRule::Lazy, 8).to_string(),
+            equal_to("0.1;0;1;1;1;1;1;1;")
+        );
+    }
+
+    #[test]
+    fn test_beta_integer_digits() {
+        let expansion = expand_beta(2.0, 1.5, BetaRule::Greedy, 30);
+        assert_that!(expansion.integer_digits.clone(), equal_to(vec![1, 0]));
+        assert!((expansion.value() - 2.0).abs() < 1e-4);
+        assert!(expansion.digits.iter().all(|&digit| digit <= 1));
+        assert_that!(largest_digit(2.5), equal_to(2));
+    }
+
+    #[test]
+    fn test_parse_float_base() {
+        assert_that!(parse_float_base("phi"), equal_to(Ok(GOLDEN_RATIO)));
+        assert_that!(parse_float_base("1.5"), equal_to(Ok(1.5)));
+        assert!(parse_float_base("1").is_err());
+        assert!(parse_float_base("x").is_err());
+        assert_that!(
+            parse_float_base_list("phi, 2.5"),
+            equal_to(Ok(vec![GOLDEN_RATIO, 2.5]))
+        );
+        assert_that!(format_base(-2.0), equal_to("-2"));
+        assert_that!(format_base(GOLDEN_RATIO), equal_to("φ"));
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::{self, Write};
+
+/// The header of the column of the binary digits regrouped by `--bit-groups`.
+pub const BIT_GROUPS_HEADER: &str = "Bit groups";
+
+/// Reads the group size `--bit-groups` takes: `nibble` (or `4`) for groups of
+/// four bits, one hexadecimal digit each, and `byte` (or `8`) for groups of
+/// eight, two hexadecimal digits each.
+///
+/// # Returns
+///
+/// The number of bits in a group, or a message naming the valid sizes.
+pub fn parse_group_size(name: &str) -> Result<usize, String> {
+    match name {
+        "nibble" | "4" => Ok(4),
+        "byte" | "8" => Ok(8),
+        _ => Err(format!(
+            "unknown bit group `{}`; expected nibble or byte",
+            name
+        )),
+    }
+}
+
+/// Writes binary digits in groups of `group` bits counted from the binary
+/// point, followed by the hexadecimal digits of every group in the same
+/// order, so that each group can be read off against its hex equivalent.
+/// The last group of the fraction is filled up with zeros, and the first of
+/// the integer part, which does not change the value.
+///
+/// # Arguments
+///
+/// * `sink` - Where the groups are written.
+/// * `negative` - Whether a `-` goes in front.
+/// * `integer` - The base 2 digits of the integer part, most significant
+///   first, and empty when it is zero.
+/// * `fraction` - The base 2 fractional digits, most significant first.
+/// * `group` - The number of bits in a group, 4 or 8.
+///
+/// # Example
+///
+/// ```
+/// let mut cell = String::new();
+/// write_bit_groups(&mut cell, false, &[], &[0, 0, 0, 1, 1, 0, 0, 1, 1], 4).unwrap();
+/// assert_eq!(cell, "0.0001 1001 1000 = 0x0.1 9 8");
+/// ```
+pub fn write_bit_groups(
+    sink: &mut impl Write,
+    negative: bool,
+    integer: &[u32],
+    fraction: &[u32],
+    group: usize,
+) -> fmt::Result {
+    let sign = if negative { "-" } else { "" };
+    // The integer part is grouped from the binary point leftwards, so its
+    // first group is the one filled up.
+    let padding = (group - integer.len() % group) % group;
+    let integer: Vec<u32> = std::iter::repeat_n(0, padding)
+        .chain(integer.iter().copied())
+        .collect();
+    let integer: Vec<&[u32]> = integer.chunks(group).collect();
+    let fraction: Vec<&[u32]> = fraction.chunks(group).collect();
+
+    write!(sink, "{}", sign)?;
+    write_part(sink, &integer, |sink, bits| write_bits(sink, bits, group))?;
+    sink.write_char('.')?;
+    write_part(sink, &fraction, |sink, bits| write_bits(sink, bits, group))?;
+    write!(sink, " = {}0x", sign)?;
+    write_part(sink, &integer, |sink, bits| write_hex(sink, bits, group))?;
+    sink.write_char('.')?;
+    write_part(sink, &fraction, |sink, bits| write_hex(sink, bits, group))
+}
+
+/// Writes the groups of one side of the point separated by spaces, or a
+/// single `0` when there are none.
+fn write_part<W: Write>(
+    sink: &mut W,
+    groups: &[&[u32]],
+    write_group: impl Fn(&mut W, &[u32]) -> fmt::Result,
+) -> fmt::Result {
+    if groups.is_empty() {
+        return sink.write_char('0');
+    }
+    for (i, bits) in groups.iter().enumerate() {
+        if i > 0 {
+            sink.write_char(' ')?;
+        }
+        write_group(sink, bits)?;
+    }
+    Ok(())
+}
+
+/// Writes the bits of a group, filled up with zeros to `group` bits.
+fn write_bits(sink: &mut impl Write, bits: &[u32], group: usize) -> fmt::Result {
+    for &bit in bits {
+        write!(sink, "{}", bit)?;
+    }
+    write!(sink, "{:0<1$}", "", group - bits.len())
+}
+
+/// Writes the hexadecimal digits of a group, one for every four bits.
+fn write_hex(sink: &mut impl Write, bits: &[u32], group: usize) -> fmt::Result {
+    let value = (0..group).fold(0u32, |value, i| {
+        value << 1 | bits.get(i).copied().unwrap_or(0)
+    });
+    write!(sink, "{:01$X}", value, group / 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_bit_groups() {
+        let groups = |negative, integer: &[u32], fraction: &[u32], group| {
+            let mut cell = String::new();
+            write_bit_groups(&mut cell, negative, integer, fraction, group).unwrap();
+            cell
+        };
+        assert_that!(
+            groups(false, &[], &[0, 0, 0, 1, 1, 0, 0, 1, 1], 4),
+            equal_to("0.0001 1001 1000 = 0x0.1 9 8".to_string())
+        );
+        assert_that!(
+            groups(true, &[], &[1, 0, 1, 1, 1, 1, 1, 1, 0, 1], 8),
+            equal_to("-0.10111111 01000000 = -0x0.BF 40".to_string())
+        );
+        assert_that!(
+            groups(false, &[1], &[0, 0], 4),
+            equal_to("0001.0000 = 0x1.0".to_string())
+        );
+        assert_that!(
+            groups(false, &[], &[], 4),
+            equal_to("0.0 = 0x0.0".to_string())
+        );
+        assert_that!(parse_group_size("byte"), equal_to(Ok(8)));
+        assert_that!(parse_group_size("4"), equal_to(Ok(4)));
+        assert!(parse_group_size("word").is_err());
+    }
+}
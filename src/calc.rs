@@ -0,0 +1,336 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Write};
+
+use digits::BaseNumber;
+
+use crate::cli::flag_value;
+use crate::find::parse_single_base;
+
+/// An operation `calc` carries out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `+`.
+    Add,
+    /// `-`.
+    Subtract,
+    /// `*`, which can also be written `x` so that the shell leaves it alone.
+    Multiply,
+}
+
+impl Operator {
+    /// Looks up an operator by how it is written.
+    pub fn from_symbol(symbol: &str) -> Option<Operator> {
+        match symbol {
+            "+" => Some(Operator::Add),
+            "-" => Some(Operator::Subtract),
+            "*" | "x" => Some(Operator::Multiply),
+            _ => None,
+        }
+    }
+
+    /// Applies the operator to two `f64` values, for the check in base 10.
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Add => write!(f, "+"),
+            Operator::Subtract => write!(f, "-"),
+            Operator::Multiply => write!(f, "*"),
+        }
+    }
+}
+
+/// The settings of the `calc` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalcOptions {
+    /// The number left of the operator.
+    pub left: BaseNumber,
+    /// The operation.
+    pub operator: Operator,
+    /// The number right of the operator, in the same base as `left`.
+    pub right: BaseNumber,
+}
+
+/// Parses the arguments of the `calc` subcommand, two numbers written in a
+/// base with an operator between them.
+///
+/// The numbers are written as the table writes them, such as `0.1;0;1;`, or
+/// one character per digit, such as `0.101`. Their base is given with
+/// `--base`, or by the numbers themselves, as in `2:0.101` or `0.101₂`.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `calc`.
+///
+/// # Returns
+///
+/// The parsed `CalcOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- calc --base 2 0.101 + 0.011
+/// ```
+pub fn parse_calc_args(args: &[String]) -> Result<CalcOptions, String> {
+    let mut base = None;
+    let mut terms = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            base = Some(parse_single_base(value)?);
+        } else {
+            terms.push(arg.as_str());
+        }
+    }
+
+    let [left, operator, right] = terms[..] else {
+        return Err("calc requires two numbers and an operator, as in 0.101 + 0.011".to_string());
+    };
+    let operator = Operator::from_symbol(operator)
+        .ok_or_else(|| format!("unknown operator `{}`, expected +, - or *", operator))?;
+    let left = BaseNumber::parse(left, base)?;
+    let right = BaseNumber::parse(right, base.or(Some(left.base)))?;
+    if left.base != right.base {
+        return Err(format!(
+            "calc requires numbers in the same base, not base {} and base {}",
+            left.base, right.base
+        ));
+    }
+
+    Ok(CalcOptions {
+        left,
+        operator,
+        right,
+    })
+}
+
+/// The magnitude of a number as all of its digits, least significant first,
+/// with `scale` of them below the point.
+#[derive(Debug, Clone)]
+struct FixedPoint {
+    /// The digits, least significant first.
+    digits: Vec<u32>,
+    /// How many of the digits are fractional.
+    scale: usize,
+}
+
+impl FixedPoint {
+    /// The digits of a number, padded with trailing zeros to `scale`
+    /// fractional digits.
+    fn new(number: &BaseNumber, scale: usize) -> FixedPoint {
+        let mut digits = vec![0; scale - number.fraction_digits.len()];
+        digits.extend(number.fraction_digits.iter().rev());
+        digits.extend(number.integer_digits.iter().rev());
+        FixedPoint { digits, scale }
+    }
+
+    /// Turns the digits back into a number, with at least one fractional
+    /// digit and no trailing zeros after it, as the table writes numbers.
+    fn into_number(mut self, base: u64, negative: bool) -> BaseNumber {
+        while self.digits.len() > self.scale && self.digits.last() == Some(&0) {
+            self.digits.pop();
+        }
+        let mut fraction_digits: Vec<u32> =
+            self.digits[..self.scale].iter().rev().copied().collect();
+        while fraction_digits.len() > 1 && fraction_digits.last() == Some(&0) {
+            fraction_digits.pop();
+        }
+        if fraction_digits.is_empty() {
+            fraction_digits.push(0);
+        }
+        let integer_digits: Vec<u32> = self.digits[self.scale..].iter().rev().copied().collect();
+        let zero = integer_digits.is_empty() && fraction_digits.iter().all(|&digit| digit == 0);
+        BaseNumber {
+            base,
+            negative: negative && !zero,
+            integer_digits,
+            fraction_digits,
+        }
+    }
+}
+
+/// Compares two magnitudes of the same scale.
+fn compare_digits(left: &[u32], right: &[u32]) -> Ordering {
+    let significant = |digits: &[u32]| {
+        digits.len() - digits.iter().rev().take_while(|&&digit| digit == 0).count()
+    };
+    let (left, right) = (&left[..significant(left)], &right[..significant(right)]);
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+/// Adds two magnitudes digit by digit, carrying into the next place
+/// whenever a sum reaches the base.
+fn add_digits(left: &[u32], right: &[u32], base: u64) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0u64;
+    for place in 0..left.len().max(right.len()) {
+        let total =
+            carry + *left.get(place).unwrap_or(&0) as u64 + *right.get(place).unwrap_or(&0) as u64;
+        sum.push((total % base) as u32);
+        carry = total / base;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// Subtracts a magnitude from one at least as large digit by digit,
+/// borrowing one from the next place whenever a digit would go below zero.
+fn subtract_digits(left: &[u32], right: &[u32], base: u64) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(left.len());
+    let mut borrow = 0i64;
+    for (place, &digit) in left.iter().enumerate() {
+        let mut total = digit as i64 - borrow - *right.get(place).unwrap_or(&0) as i64;
+        borrow = 0;
+        if total < 0 {
+            total += base as i64;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+/// Multiplies two magnitudes the long way, adding every digit product into
+/// its place and carrying what reaches the base.
+fn multiply_digits(left: &[u32], right: &[u32], base: u64) -> Vec<u32> {
+    let base = base as u128;
+    let mut product = vec![0u128; left.len() + right.len()];
+    for (i, &a) in left.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, &b) in right.iter().enumerate() {
+            let total = product[i + j] + a as u128 * b as u128 + carry;
+            product[i + j] = total % base;
+            carry = total / base;
+        }
+        product[i + right.len()] += carry;
+    }
+    product.into_iter().map(|digit| digit as u32).collect()
+}
+
+/// Carries out an operation on two numbers in their base, with every carry
+/// and borrow taken in that base, so that the digits never go through an
+/// `f64` and come out exactly.
+///
+/// # Example
+///
+/// ```
+/// let left = BaseNumber::parse("0.101", Some(2)).unwrap();
+/// let right = BaseNumber::parse("0.011", Some(2)).unwrap();
+/// assert_eq!(calculate(&left, Operator::Add, &right).to_string(), "1.0;");
+/// ```
+pub fn calculate(left: &BaseNumber, operator: Operator, right: &BaseNumber) -> BaseNumber {
+    let base = left.base;
+    if operator == Operator::Multiply {
+        let scale = left.fraction_digits.len() + right.fraction_digits.len();
+        let digits = multiply_digits(
+            &FixedPoint::new(left, left.fraction_digits.len()).digits,
+            &FixedPoint::new(right, right.fraction_digits.len()).digits,
+            base,
+        );
+        return FixedPoint { digits, scale }.into_number(base, left.negative != right.negative);
+    }
+
+    let scale = left.fraction_digits.len().max(right.fraction_digits.len());
+    let (a, b) = (
+        FixedPoint::new(left, scale).digits,
+        FixedPoint::new(right, scale).digits,
+    );
+    // Subtracting is adding the number with its sign flipped.
+    let right_negative = right.negative != (operator == Operator::Subtract);
+    let (digits, negative) = if left.negative == right_negative {
+        (add_digits(&a, &b, base), left.negative)
+    } else if compare_digits(&a, &b) == Ordering::Less {
+        (subtract_digits(&b, &a, base), right_negative)
+    } else {
+        (subtract_digits(&a, &b, base), left.negative)
+    };
+    FixedPoint { digits, scale }.into_number(base, negative)
+}
+
+/// Runs the `calc` subcommand, printing the result in the base of its
+/// numbers and, as a check, the same calculation in base 10.
+///
+/// # Example
+/// ```
+/// // cargo run -- calc --base 2 0.101 + 0.011
+/// ```
+/// Output:
+/// 0.1;0;1; + 0.0;1;1; = 1.0; (base 2)
+/// 0.625 + 0.375 = 1 (base 10)
+pub fn display_calc(out: &mut impl Write, options: &CalcOptions) -> io::Result<()> {
+    let result = calculate(&options.left, options.operator, &options.right);
+    writeln!(
+        out,
+        "{} {} {} = {} (base {})",
+        options.left, options.operator, options.right, result, result.base
+    )?;
+    writeln!(
+        out,
+        "{} {} {} = {} (base 10)",
+        options.left.value(),
+        options.operator,
+        options.right.value(),
+        options
+            .operator
+            .apply(options.left.value(), options.right.value())
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn calc(line: &str) -> Result<String, String> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        parse_calc_args(&args)
+            .map(|options| calculate(&options.left, options.operator, &options.right).to_string())
+    }
+
+    #[test]
+    fn test_calc_in_base() {
+        assert_that!(
+            calc("--base 2 0.101 + 0.011"),
+            equal_to(Ok("1.0;".to_string()))
+        );
+        assert_that!(
+            calc("--base 2 0.1 - 0.11"),
+            equal_to(Ok("-0.0;1;".to_string()))
+        );
+        assert_that!(
+            calc("--base 2 1.1 x 1.1"),
+            equal_to(Ok("1;0.0;1;".to_string()))
+        );
+        assert_that!(calc("16:F.8 + 0.8"), equal_to(Ok("1;0.0;".to_string())));
+        assert_that!(calc("-0.1₃ * 0.2"), equal_to(Ok("-0.0;2;".to_string())));
+        assert_that!(
+            calc("--base 10 0.5 - -0.25"),
+            equal_to(Ok("0.7;5;".to_string()))
+        );
+        assert_that!(calc("--base 2 0.1 - 0.1"), equal_to(Ok("0.0;".to_string())));
+        assert_that!(
+            calc("--base 60 0.30;45; + 0.30;15;"),
+            equal_to(Ok("1.1;".to_string()))
+        );
+
+        assert!(calc("--base 2 0.1 / 0.1").is_err());
+        assert!(calc("--base 2 0.1 +").is_err());
+        assert!(calc("--base 2 0.2 + 0.1").is_err());
+        assert!(calc("0.1 + 0.1").is_err());
+        assert!(calc("2:0.1 + 0.1₃").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// A flag that stops long conversions early once it is set, so that a
+/// frontend can abort work it no longer needs. Clones share the same flag.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a token that is not cancelled yet.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Asks every conversion holding this token, or a clone of it, to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the work should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CancelToken({})", self.is_cancelled())
+    }
+}
+
+impl PartialEq for CancelToken {
+    /// Tokens are compared by their state, so that two pipelines with the
+    /// same settings are equal even though each has its own token.
+    fn eq(&self, other: &CancelToken) -> bool {
+        self.is_cancelled() == other.is_cancelled()
+    }
+}
+
+/// The token the Ctrl-C handler cancels.
+static INTERRUPT: OnceLock<CancelToken> = OnceLock::new();
+
+/// The number of the interrupt signal Ctrl-C sends.
+#[cfg(unix)]
+const SIGINT: i32 = 2;
+
+#[cfg(unix)]
+extern "C" {
+    /// Installs a signal handler, where 0 restores the default action.
+    fn signal(signum: i32, handler: usize) -> usize;
+}
+
+/// Cancels the token on the first Ctrl-C, and restores the default action
+/// so that a second Ctrl-C ends the program at once.
+#[cfg(unix)]
+extern "C" fn on_interrupt(_signum: i32) {
+    if let Some(token) = INTERRUPT.get() {
+        token.cancel();
+    }
+    // SAFETY: `signal` is async-signal-safe, and 0 is `SIG_DFL`.
+    unsafe {
+        signal(SIGINT, 0);
+    }
+}
+
+/// Makes Ctrl-C cancel `token` instead of ending the program, so that the
+/// results converted so far can still be printed. Only the first token
+/// given is ever cancelled, and on platforms other than Unix Ctrl-C keeps
+/// its default action.
+pub fn cancel_on_interrupt(token: &CancelToken) {
+    if INTERRUPT.set(token.clone()).is_err() {
+        return;
+    }
+    #[cfg(unix)]
+    // SAFETY: the handler only touches an atomic flag and reinstalls the
+    // default action.
+    unsafe {
+        signal(SIGINT, on_interrupt as extern "C" fn(i32) as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_token() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert!(token == CancelToken::new());
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(token != CancelToken::new());
+    }
+}
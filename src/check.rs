@@ -0,0 +1,323 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crate::aggregate::parse_json_fields;
+use crate::beta::GOLDEN_RATIO;
+use crate::cli::flag_value;
+use crate::conversion::{Conversion, CONVERSION_FIELDS};
+use crate::csv::{is_tsv, split_record};
+use crate::exit::write_error_message;
+use crate::find::parse_digits;
+use crate::pipeline::Pipeline;
+
+/// The settings of the `check` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckOptions {
+    /// The result file written earlier with `--format csv`, `json` or
+    /// `jsonl`.
+    pub path: String,
+    /// The stages every input is converted with again. The base of every
+    /// row is the one recorded in it.
+    pub pipeline: Pipeline,
+}
+
+/// A row of a result file whose conversion no longer comes out the same.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    /// The line of the file the row is on, from 1.
+    pub line: usize,
+    /// The conversion as recorded in the file.
+    pub recorded: Conversion,
+    /// The conversion as it comes out now.
+    pub current: Conversion,
+}
+
+/// Parses the arguments of the `check` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `check`.
+///
+/// # Returns
+///
+/// The parsed `CheckOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- check results.json --digits 12
+/// ```
+pub fn parse_check_args(args: &[String]) -> Result<CheckOptions, String> {
+    let mut path = None;
+    let mut pipeline = Pipeline::with_bases(Vec::new());
+    let mut digits = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(expression) = flag_value(arg, "--pipe", &mut iter)? {
+            pipeline = Pipeline::parse(expression)?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = Some(parse_digits(value)?);
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown check argument `{}`", arg));
+        } else if path.replace(arg.clone()).is_some() {
+            return Err("check takes a single result file".to_string());
+        }
+    }
+
+    if let Some(digits) = digits {
+        pipeline.digits = digits;
+    }
+    let path = path.ok_or("check requires a result file")?;
+    Ok(CheckOptions { path, pipeline })
+}
+
+/// Reads the conversions of a result file, which is JSON if it starts with
+/// `[` or `{`, as `--format json` and `jsonl` write it, TSV if its header
+/// has tabs, and CSV otherwise.
+///
+/// # Returns
+///
+/// Every conversion with the line it is on, or a message naming the first
+/// line that holds none.
+pub fn read_conversions(path: &str) -> Result<Vec<(usize, Conversion)>, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("cannot read `{}`: {}", path, error))?;
+    let json = text.trim_start().starts_with(['[', '{']);
+    let tsv = !json && text.lines().next().is_some_and(is_tsv);
+    let delimiter = if tsv { "\t" } else { "," };
+    let header = CONVERSION_FIELDS.join(delimiter);
+    let label = format!("label{}", delimiter);
+    text.lines()
+        .enumerate()
+        // The tabs around a TSV row are the bounds of its empty fields.
+        .map(|(number, line)| {
+            (
+                number + 1,
+                line.trim_matches(|c: char| c.is_whitespace() && !(tsv && c == '\t')),
+            )
+        })
+        .filter(|&(_, line)| {
+            let header_line = line.strip_prefix(label.as_str()).unwrap_or(line) == header;
+            !(line.is_empty() || header_line || line == "[" || line == "]")
+        })
+        // Of a document of the JSON schema, only the rows hold conversions.
+        .filter(|&(_, line)| !json || (line.starts_with('{') && line != "{"))
+        .map(|(number, line)| {
+            let conversion = if json {
+                parse_json_conversion(line.strip_suffix(',').unwrap_or(line))
+            } else {
+                parse_csv_conversion(line, tsv)
+            };
+            conversion
+                .map(|conversion| (number, conversion))
+                .ok_or_else(|| format!("{}: line {}: invalid result row", path, number))
+        })
+        .collect()
+}
+
+/// Reads a conversion from a JSON object with the fields of a `Conversion`.
+fn parse_json_conversion(line: &str) -> Option<Conversion> {
+    let pairs = parse_json_fields(line)?;
+    let fields: Option<Vec<&str>> = CONVERSION_FIELDS
+        .iter()
+        .map(|&name| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        })
+        .collect();
+    conversion_from_fields(&fields?)
+}
+
+/// Reads a conversion from a CSV row, or a TSV row if `tsv` is set, with
+/// the fields of a `Conversion`, led by its label if the inputs were
+/// labelled.
+fn parse_csv_conversion(line: &str, tsv: bool) -> Option<Conversion> {
+    let mut fields = split_record(line, tsv);
+    let label = (fields.len() == CONVERSION_FIELDS.len() + 1).then(|| fields.remove(0));
+    let conversion =
+        conversion_from_fields(&fields.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Some(Conversion {
+        label,
+        ..conversion
+    })
+}
+
+/// Builds a conversion from its fields, in the order of `CONVERSION_FIELDS`.
+fn conversion_from_fields(fields: &[&str]) -> Option<Conversion> {
+    match fields {
+        [input, base, digits, truncated, repr] => Some(Conversion {
+            label: None,
+            input: input.parse().ok()?,
+            base: base.to_string(),
+            digits: digits.parse().ok()?,
+            truncated: truncated.parse().ok()?,
+            repr: repr.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// Converts the input of every recorded row again to the base it records.
+///
+/// # Returns
+///
+/// The rows whose conversion differs from the one recorded, in order, or a
+/// message naming the first row with a base that cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let pipeline = Pipeline::with_bases(Vec::new());
+/// let recorded = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+/// assert!(check_conversions(&pipeline, &[(2, recorded)]).unwrap().is_empty());
+/// ```
+pub fn check_conversions(
+    pipeline: &Pipeline,
+    conversions: &[(usize, Conversion)],
+) -> Result<Vec<Mismatch>, String> {
+    let mut mismatches = Vec::new();
+    for (line, recorded) in conversions {
+        let target_base = match recorded.base.as_str() {
+            "φ" => GOLDEN_RATIO,
+            base => base
+                .parse::<f64>()
+                .map_err(|_| format!("line {}: invalid base `{}`", line, base))?,
+        };
+        let expansion = pipeline.expand_input(recorded.input, None, target_base);
+        let current = Conversion {
+            label: recorded.label.clone(),
+            ..Conversion::new(recorded.input, target_base, &expansion)
+        };
+        if current != *recorded {
+            mismatches.push(Mismatch {
+                line: *line,
+                recorded: recorded.clone(),
+                current,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Writes every mismatch with what was recorded and what comes out now,
+/// then how many of the rows still match.
+pub fn display_mismatches(
+    out: &mut dyn Write,
+    rows: usize,
+    mismatches: &[Mismatch],
+) -> io::Result<()> {
+    for mismatch in mismatches {
+        writeln!(
+            out,
+            "line {}: {} in base {}: recorded `{}`, now `{}`",
+            mismatch.line,
+            mismatch.recorded.input,
+            mismatch.recorded.base,
+            mismatch.recorded.repr,
+            mismatch.current.repr
+        )?;
+    }
+    if mismatches.is_empty() {
+        writeln!(out, "all {} rows match", rows)
+    } else {
+        writeln!(out, "{} of {} rows differ", mismatches.len(), rows)
+    }
+}
+
+/// Runs the `check` subcommand: converts every row of the result file again
+/// and reports the ones that no longer match.
+///
+/// # Returns
+///
+/// Whether the file was read and every row still matches.
+pub fn display_check(options: &CheckOptions) -> bool {
+    let result = read_conversions(&options.path).and_then(|conversions| {
+        let mismatches = check_conversions(&options.pipeline, &conversions)?;
+        display_mismatches(&mut io::stdout().lock(), conversions.len(), &mismatches)
+            .map_err(write_error_message)?;
+        Ok(mismatches.is_empty())
+    });
+    match result {
+        Ok(matched) => matched,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::format::{display_conversions, OutputFormat};
+    use hamcrest2::prelude::*;
+    #[cfg(feature = "serde")]
+    use std::env;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_check_result_files() {
+        let pipeline = Pipeline::with_bases(vec![2.0, 16.0]);
+        for format in [
+            OutputFormat::Csv,
+            OutputFormat::Json,
+            OutputFormat::JsonLines,
+        ] {
+            let mut results = Vec::new();
+            display_conversions(
+                &mut results,
+                format,
+                &pipeline,
+                &[0.1, 0.5],
+                &[None, None],
+                &[None, None],
+                &[],
+            )
+            .unwrap();
+            let path = env::temp_dir().join(format!(
+                "check-{}.{}",
+                std::process::id(),
+                format.extension()
+            ));
+            let path = path.to_string_lossy().into_owned();
+            fs::write(&path, results).unwrap();
+
+            let conversions = read_conversions(&path).unwrap();
+            assert_that!(conversions.len(), equal_to(4));
+            assert!(check_conversions(&pipeline, &conversions)
+                .unwrap()
+                .is_empty());
+
+            // With fewer digits, 0.1 no longer comes out the same in either
+            // base, but 0.5 still does.
+            let shorter = Pipeline {
+                digits: 4,
+                ..pipeline.clone()
+            };
+            let mismatches = check_conversions(&shorter, &conversions).unwrap();
+            assert_that!(mismatches.len(), equal_to(2));
+            assert_that!(mismatches[0].current.repr.as_str(), equal_to("0.0;0;0;1;"));
+            fs::remove_file(&path).unwrap();
+        }
+        assert!(read_conversions("/nonexistent/results.csv").is_err());
+    }
+
+    #[test]
+    fn test_check_arguments() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        let options = parse_check_args(&args("results.json --digits 12")).unwrap();
+        assert_that!(options.path.as_str(), equal_to("results.json"));
+        assert_that!(options.pipeline.digits, equal_to(12));
+        let options = parse_check_args(&args("--pipe round=half-even results.csv")).unwrap();
+        assert_that!(
+            options.pipeline.rounding,
+            equal_to(crate::convert::Rounding::HalfEven)
+        );
+        assert!(parse_check_args(&args("a.csv b.csv")).is_err());
+        assert!(parse_check_args(&args("--digits 4")).is_err());
+        assert!(parse_check_args(&args("a.csv --fast")).is_err());
+    }
+}
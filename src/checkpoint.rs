@@ -0,0 +1,127 @@
+use std::fs;
+use std::io;
+
+use crate::stream::Position;
+
+/// How many inputs a `--checkpoint` run converts between two records of its
+/// progress.
+pub const CHECKPOINT_INTERVAL: usize = 1000;
+
+/// How far a `--stream` run got through its `--input` files, which
+/// `--checkpoint` records so that an interrupted run can go on from there
+/// with `--resume` instead of starting over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The index of the `--input` file being read, in the order given.
+    pub input: usize,
+    /// The path of that file, so that a run is only resumed on the same one.
+    pub path: String,
+    /// Where the first input not yet converted lies in the file.
+    pub position: Position,
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint as one `<key> <value>` line per field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let checkpoint = Checkpoint {
+    ///     input: 0,
+    ///     path: "data.txt".to_string(),
+    ///     position: Position { line: 12, offset: 345 },
+    /// };
+    /// assert_eq!(
+    ///     checkpoint.to_text(),
+    ///     "input 0\npath data.txt\nline 12\noffset 345\n"
+    /// );
+    /// ```
+    pub fn to_text(&self) -> String {
+        format!(
+            "input {}\npath {}\nline {}\noffset {}\n",
+            self.input, self.path, self.position.line, self.position.offset
+        )
+    }
+
+    /// Reads a checkpoint written by [`Checkpoint::to_text`].
+    ///
+    /// # Returns
+    ///
+    /// The checkpoint, or `None` if a field is missing or does not parse.
+    pub fn parse(text: &str) -> Option<Checkpoint> {
+        let mut lines = text.lines();
+        let mut field = |key: &str| {
+            lines
+                .next()?
+                .strip_prefix(key)?
+                .strip_prefix(' ')
+                .map(String::from)
+        };
+        let input = field("input")?.parse().ok()?;
+        let path = field("path")?;
+        let line = field("line")?.parse().ok()?;
+        let offset = field("offset")?.parse().ok()?;
+        Some(Checkpoint {
+            input,
+            path,
+            position: Position { line, offset },
+        })
+    }
+
+    /// Reads the checkpoint `--resume` goes on from.
+    ///
+    /// # Returns
+    ///
+    /// The checkpoint, or a message describing why it cannot be read.
+    pub fn load(path: &str) -> Result<Checkpoint, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read checkpoint `{}`: {}", path, error))?;
+        Checkpoint::parse(&text).ok_or_else(|| format!("`{}` is not a checkpoint", path))
+    }
+
+    /// Writes the checkpoint to a file, replacing the one before. It is
+    /// written next to it first and then moved over it, so that a run
+    /// interrupted while saving still leaves a whole checkpoint behind.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let partial = format!("{}.partial", path);
+        fs::write(&partial, self.to_text())?;
+        fs::rename(&partial, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::env;
+
+    #[test]
+    fn test_checkpoint() {
+        let checkpoint = Checkpoint {
+            input: 1,
+            path: "my data.txt".to_string(),
+            position: Position {
+                line: 12,
+                offset: 345,
+            },
+        };
+        assert_that!(
+            Checkpoint::parse(&checkpoint.to_text()),
+            equal_to(Some(checkpoint.clone()))
+        );
+        assert_that!(
+            Checkpoint::parse("input 1\npath a\nline x\n"),
+            equal_to(None)
+        );
+        assert_that!(Checkpoint::parse(""), equal_to(None));
+
+        let path = env::temp_dir().join(format!("checkpoint-{}", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        checkpoint.save(&path).unwrap();
+        assert_that!(Checkpoint::load(&path), equal_to(Ok(checkpoint)));
+        fs::write(&path, "junk").unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(Checkpoint::load(&path).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The round constants of SHA-256: the first 32 bits of the fractional parts
+/// of the cube roots of the first 64 primes.
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The initial hash value of SHA-256: the first 32 bits of the fractional
+/// parts of the square roots of the first 8 primes.
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Where `--checksum` puts the SHA-256 of the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// A `SHA-256: <hex>` line after the results, covering everything above it.
+    Footer,
+    /// A `<file>.sha256` file next to the `--output` file, in the format
+    /// `sha256sum --check` reads.
+    Sidecar,
+}
+
+impl Checksum {
+    /// Looks up a checksum placement by its name, as in `sidecar`.
+    pub fn from_name(name: &str) -> Option<Checksum> {
+        match name {
+            "footer" => Some(Checksum::Footer),
+            "sidecar" => Some(Checksum::Sidecar),
+            _ => None,
+        }
+    }
+}
+
+/// An incremental SHA-256 hash, as specified in FIPS 180-4.
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    /// The hash of the blocks processed so far.
+    state: [u32; 8],
+    /// The bytes that do not yet fill a whole block.
+    pending: Vec<u8>,
+    /// The number of bytes hashed so far.
+    length: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+impl Sha256 {
+    /// Starts a hash of no bytes.
+    pub fn new() -> Sha256 {
+        Sha256 {
+            state: INITIAL_STATE,
+            pending: Vec::with_capacity(64),
+            length: 0,
+        }
+    }
+
+    /// Adds bytes to the hashed message.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.length += bytes.len() as u64;
+        self.pending.extend_from_slice(bytes);
+        let whole = self.pending.len() / 64 * 64;
+        for block in self.pending[..whole].chunks_exact(64) {
+            compress(&mut self.state, block);
+        }
+        self.pending.drain(..whole);
+    }
+
+    /// Pads the message and returns its hash as lowercase hexadecimal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut hash = Sha256::new();
+    /// hash.update(b"abc");
+    /// assert!(hash.finish().starts_with("ba7816bf"));
+    /// ```
+    pub fn finish(mut self) -> String {
+        let bits = self.length.wrapping_mul(8);
+        let mut padding = vec![0x80];
+        padding.resize((119 - self.pending.len()) % 64 + 1, 0);
+        padding.extend_from_slice(&bits.to_be_bytes());
+        self.update(&padding);
+        self.state
+            .iter()
+            .map(|word| format!("{:08x}", word))
+            .collect()
+    }
+}
+
+/// Mixes one 64-byte block into the hash state.
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+    let mut schedule = [0u32; 64];
+    for (word, bytes) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..64 {
+        let s0 = schedule[i - 15].rotate_right(7)
+            ^ schedule[i - 15].rotate_right(18)
+            ^ (schedule[i - 15] >> 3);
+        let s1 = schedule[i - 2].rotate_right(17)
+            ^ schedule[i - 2].rotate_right(19)
+            ^ (schedule[i - 2] >> 10);
+        schedule[i] = schedule[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(schedule[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (&constant, &word) in ROUND_CONSTANTS.iter().zip(&schedule) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(constant)
+            .wrapping_add(word);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(majority);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+/// The path `--checksum=sidecar` writes the hash of a results file to.
+pub fn sidecar_path(path: &str) -> String {
+    format!("{}.sha256", path)
+}
+
+/// Writes the hash of a results file next to it as `<hex>  <file name>`, so
+/// that `sha256sum --check` run in the same directory verifies the file.
+pub fn write_sidecar(path: &str, hash: &str) -> io::Result<()> {
+    let name = Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    fs::write(sidecar_path(path), format!("{}  {}\n", hash, name))
+}
+
+/// A writer that hashes everything it passes on.
+pub struct HashingWriter<'a> {
+    /// Where the bytes go.
+    inner: &'a mut dyn Write,
+    /// The hash of the bytes written so far.
+    hash: Sha256,
+}
+
+impl<'a> HashingWriter<'a> {
+    /// Wraps a writer, starting with an empty hash.
+    pub fn new(inner: &'a mut dyn Write) -> HashingWriter<'a> {
+        HashingWriter {
+            inner,
+            hash: Sha256::new(),
+        }
+    }
+
+    /// The hash of everything written, as lowercase hexadecimal.
+    pub fn finish(self) -> String {
+        self.hash.finish()
+    }
+}
+
+impl Write for HashingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hash.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn sha256(bytes: &[u8]) -> String {
+        let mut hash = Sha256::new();
+        hash.update(bytes);
+        hash.finish()
+    }
+
+    #[test]
+    fn test_sha256_vectors() {
+        assert_that!(
+            sha256(b""),
+            equal_to("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_that!(
+            sha256(b"abc"),
+            equal_to("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_that!(
+            sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            equal_to("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+
+        // Hashing in pieces gives the same result as hashing at once.
+        let message = vec![b'a'; 1000];
+        let mut pieces = Sha256::new();
+        for chunk in message.chunks(7) {
+            pieces.update(chunk);
+        }
+        assert_that!(pieces.finish(), equal_to(sha256(&message)));
+    }
+
+    #[test]
+    fn test_hashing_writer() {
+        let mut buffer = Vec::new();
+        let mut writer = HashingWriter::new(&mut buffer);
+        writeln!(writer, "abc").unwrap();
+        let hash = writer.finish();
+        assert_that!(buffer, equal_to(b"abc\n".to_vec()));
+        assert_that!(hash, equal_to(sha256(b"abc\n")));
+    }
+}
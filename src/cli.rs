@@ -0,0 +1,4115 @@
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+
+use crate::aggregate::{parse_aggregate_args, AggregateOptions};
+use crate::alphabet::{parse_alphabet_args, Alphabet, AlphabetOptions};
+use crate::analyze::{parse_analyze_args, AnalyzeOptions};
+use crate::angle::DMS_RADICES;
+use crate::answer::{parse_answer_args, AnswerOptions};
+use crate::approx::{parse_approx_args, ApproxOptions};
+use crate::backend::Backend;
+use crate::balanced::BALANCED_BASE;
+use crate::base_matrix::{parse_matrix_args, MatrixOptions};
+use crate::bench::{parse_bench_args, parse_throughput_args, BenchOptions, ThroughputOptions};
+use crate::beta::{format_base, parse_float_base_list, BetaRule};
+use crate::bitgroup::parse_group_size;
+use crate::calc::{parse_calc_args, CalcOptions};
+use crate::check::{parse_check_args, CheckOptions};
+use crate::checkpoint::Checkpoint;
+use crate::checksum::Checksum;
+use crate::columns::{check_columns, parse_columns, select_table_columns, Column};
+use crate::comment::input_words;
+use crate::config::{default_config_path, Config};
+use crate::continued_fraction::{parse_cf_args, CfOptions};
+use crate::counterexample::{parse_counterexample_args, CounterexampleOptions};
+use crate::csv::{parse_csv_args, CsvOptions};
+use crate::dedup::dedup_inputs;
+use crate::diagnostic::{argument_words, find_spans, line_words, Span};
+use crate::diff::{parse_diff_args, DiffOptions};
+use crate::digit_limit::split_digit_limit;
+use crate::display::Columns;
+use crate::egyptian::{parse_egyptian_args, EgyptianOptions};
+use crate::exit::{is_non_finite, ErrorMode};
+use crate::explain_float::{parse_explain_float_args, ExplainFloatOptions};
+use crate::filter::{filter_inputs, parse_row_filter, RowFilter};
+use crate::find::{parse_find_args, FindOptions};
+use crate::format::{parse_format, OutputFormat};
+use crate::glob::{expand_glob, glob_root};
+use crate::gray::GRAY_BASE;
+use crate::highlight::{parse_period_style, PeriodStyle};
+use crate::inspect::{parse_inspect_args, InspectOptions};
+use crate::label::split_label;
+use crate::locale::uses_decimal_comma;
+use crate::memo::ExpansionCache;
+use crate::memory::parse_byte_count;
+use crate::mmap::Mapped;
+use crate::notation::{parse_notation, Notation};
+use crate::output::{section_path, OutputOptions};
+use crate::pad::PadWidth;
+use crate::padic::{parse_padic_args, PadicOptions};
+use crate::pager::Paging;
+use crate::pipeline::{parse_backend, parse_base_list, parse_q_format, Decode, Pipeline};
+use crate::quiz::{parse_quiz_args, QuizOptions};
+use crate::radices::parse_radices;
+use crate::rational::Fraction;
+use crate::repl::{parse_repl_args, ReplOptions};
+use crate::script::Script;
+#[cfg(feature = "server")]
+use crate::serve::{parse_serve_args, ServeOptions};
+use crate::sign::{parse_verify_args, SigningKey, VerifyOptions};
+use crate::sort::{parse_sort_key, sort_inputs, SortKey};
+use crate::stats::{parse_stats_args, StatsOptions};
+#[cfg(feature = "server")]
+use crate::stdio::{parse_stdio_args, StdioOptions};
+use crate::stream::STDIN_PATH;
+use crate::style::{Styles, TableStyle};
+use crate::time::{TimeUnit, DAY_RADICES, TIME_BASE};
+use crate::verify::{parse_tolerance, Tolerance};
+use crate::worksheet::{parse_worksheet_args, WorksheetOptions};
+
+/// What the program was asked to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Convert the given numbers and print them (the default).
+    Convert(Box<Options>),
+    /// Scan a range of decimals for the first one satisfying a predicate.
+    Find(FindOptions),
+    /// Search for unequal decimals that share a truncated expansion.
+    Counterexamples(CounterexampleOptions),
+    /// Print diagnostics about the expansions of the given numbers.
+    Analyze(AnalyzeOptions),
+    /// Print the IEEE 754 fields and exact stored value of the given numbers.
+    Inspect(InspectOptions),
+    /// Time the same workload with every conversion backend.
+    BenchCompare(BenchOptions),
+    /// Measure the conversions per second and allocations of every backend.
+    Bench(ThroughputOptions),
+    /// Check that a digit alphabet writes every number unambiguously.
+    Alphabet(AlphabetOptions),
+    /// Print the continued fractions of the given numbers.
+    ContinuedFraction(CfOptions),
+    /// Check the signature of a results file.
+    Verify(VerifyOptions),
+    /// Convert the rows of an earlier result file again and report the ones
+    /// that no longer match.
+    Check(CheckOptions),
+    /// Check a claimed representation of a decimal in a base.
+    CheckAnswer(AnswerOptions),
+    /// Merge the datasets of several runs into one.
+    Aggregate(AggregateOptions),
+    /// Answer conversion requests over HTTP.
+    #[cfg(feature = "server")]
+    Serve(ServeOptions),
+    /// Answer conversion requests read from standard input on standard
+    /// output.
+    #[cfg(feature = "server")]
+    ServeStdio(StdioOptions),
+    /// Append the conversions of a CSV column to the CSV.
+    Csv(CsvOptions),
+    /// Ask for the expansions of random fractions and keep score.
+    Quiz(QuizOptions),
+    /// Write a file of random conversion problems and its answer key.
+    Worksheet(WorksheetOptions),
+    /// Convert the numbers entered at a prompt, keeping a history of them.
+    Repl(ReplOptions),
+    /// Print the p-adic expansions of the given numbers.
+    Padic(PadicOptions),
+    /// Print the best fractions approximating the given numbers.
+    Approx(ApproxOptions),
+    /// Print the given numbers as sums of distinct unit fractions.
+    Egyptian(EgyptianOptions),
+    /// Add, subtract or multiply two numbers in their base.
+    Calc(CalcOptions),
+    /// Count how often every digit appears in the expansions of the given
+    /// numbers.
+    Stats(StatsOptions),
+    /// Print the given numbers in two bases one above the other, with their
+    /// place values aligned.
+    Diff(DiffOptions),
+    /// Print how the given numbers are stored as `f64`, and how far that is
+    /// from the value typed.
+    ExplainFloat(ExplainFloatOptions),
+    /// Print one value in every one of a list of bases, as a grid reading it
+    /// in each of them or as a list.
+    Matrix(MatrixOptions),
+}
+
+/// The settings for one run of the program, gathered from the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// The stages every input is run through.
+    pub pipeline: Pipeline,
+    /// The decoded input values, in the order they were given.
+    pub f64_numbers: Vec<f64>,
+    /// For every input in `f64_numbers`, the exact fraction it was written
+    /// as (such as `1/3`), if any.
+    pub fractions: Vec<Option<Fraction>>,
+    /// For every input in `f64_numbers`, the digit limit it was given with,
+    /// as in `0.1:20`, if any, which replaces that of the pipeline.
+    pub digit_limits: Vec<Option<u32>>,
+    /// For every input in `f64_numbers`, the label it was given with, as in
+    /// `tenth=0.1`, if any.
+    pub labels: Vec<Option<String>>,
+    /// The digit limits requested with `--precision`. The first one is also
+    /// the pipeline's digit limit.
+    pub precisions: Vec<u32>,
+    /// Whether to emit the long-format dataset of every input, base and
+    /// precision combination instead of the table.
+    pub matrix_options: bool,
+    /// How many times every input in `f64_numbers` was given, which is more
+    /// than once only when `--dedup` collapsed repeated values into one row.
+    pub counts: Vec<usize>,
+    /// The optional table columns that were switched on.
+    pub columns: Columns,
+    /// Whether to print every multiply-by-base step before the table.
+    pub explain: bool,
+    /// Whether to print only aggregate statistics instead of the table.
+    pub summary_only: bool,
+    /// Whether to print a line below the table summarizing the batch, with
+    /// `--footer`.
+    pub footer: bool,
+    /// How every input is written, if not as the digits of its target
+    /// bases.
+    pub representation: Representation,
+    /// The number of threads large batches are converted on, from `--jobs`,
+    /// or `None` for one per core.
+    pub jobs: Option<usize>,
+    /// With `--stream`, the `--input` files, or the `--file` inputs, that
+    /// are converted one line at a time as they are read, instead of being
+    /// read up front. Empty otherwise.
+    pub stream_from: Vec<String>,
+    /// The file `--checkpoint` records the progress of a `--stream` run in,
+    /// if any.
+    pub checkpoint: Option<String>,
+    /// With `--resume`, the checkpoint of an interrupted run to go on from.
+    pub resume_from: Option<Checkpoint>,
+    /// With `--watch`, the `--input` files that are watched for changes,
+    /// each of which converts every input again. Empty otherwise.
+    pub watch_paths: Vec<String>,
+    /// The number of bytes of results that may be held in memory at once,
+    /// from `--max-memory`. Runs that would buffer more print every row as
+    /// soon as it is converted.
+    pub max_memory: Option<usize>,
+    /// How the inputs are collapsed, filtered and ordered, which is done
+    /// again for the inputs of every `--file`.
+    pub arrangement: Arrangement,
+    /// The inputs that could not be decoded, which are skipped, in the order
+    /// they were given.
+    pub invalid_inputs: Vec<String>,
+    /// For every invalid input, where on the command line or in which line
+    /// of an `--input` file it was given, if that is known.
+    pub invalid_spans: Vec<Option<Span>>,
+    /// Whether the run stops at the first invalid input, skips every one
+    /// with a warning, or reports them all at the end, from `--error-mode`
+    /// or `--strict`.
+    pub error_mode: ErrorMode,
+    /// Whether the results written to a terminal go through a pager, from
+    /// `--paging`.
+    pub paging: Paging,
+    /// The columns of the output and their order, from `--columns`, if
+    /// given; the table keeps them in their places.
+    pub selected_columns: Option<Vec<Column>>,
+    /// Whether `--quiet` hides the progress bar of large batches and the
+    /// note naming the target bases.
+    pub quiet: bool,
+    /// Whether no target base was given, so that the run converts to the
+    /// default bases.
+    pub bases_defaulted: bool,
+    /// How much is logged about every input on standard error: 1 for `-v`
+    /// and 2 for `-vv`, or 0 to stay silent.
+    pub verbosity: u8,
+    /// The column styles read from the `--style` file, if any, and the lines
+    /// of the table from `--table-style`.
+    pub styles: Styles,
+    /// The text written between the digits of the table instead of `;`,
+    /// from `--separator`, if any.
+    pub separator: Option<String>,
+    /// How the conversions are written, from `--format`.
+    pub format: OutputFormat,
+    /// Every format `--output-dir` writes a file in, the first being
+    /// `format`.
+    pub formats: Vec<OutputFormat>,
+    /// Where the results are written.
+    pub output: OutputOptions,
+    /// The files given with `--file` or as positional paths, each converted
+    /// on a thread of its own into a section, or with `--output-dir` a set
+    /// of results files, of its own, including those `--glob` found.
+    pub files: Vec<String>,
+    /// The directory the files `--glob` found lie under, whose layout
+    /// `--output-dir` mirrors, if any were given.
+    pub glob_root: Option<String>,
+    /// The column of the CSV `files` to read the inputs from, as its header
+    /// or its 1-based number, with `--column`, or of the table of the
+    /// SQLite `files` that `sqlite_table` names.
+    pub csv_column: Option<String>,
+    /// The table of the SQLite databases among the `files` to read the
+    /// `csv_column` of, with `--table`.
+    pub sqlite_table: Option<String>,
+}
+
+/// Whether the arguments of `verify` are those of an answer to check, a
+/// decimal, a base and a claimed representation, rather than those of a
+/// signed file, which comes with `--public-key` and maybe `--signature`.
+///
+/// # Example
+///
+/// ```
+/// assert!(checks_answer(&args("0.1 2 0.00011")));
+/// assert!(!checks_answer(&args("results.md --public-key answers.pub")));
+/// ```
+fn checks_answer(args: &[String]) -> bool {
+    let signed = args.iter().any(|arg| {
+        ["--public-key", "--signature"]
+            .iter()
+            .any(|flag| arg == flag || arg.starts_with(&format!("{}=", flag)))
+    });
+    !signed && args.iter().filter(|arg| !arg.starts_with("--")).count() > 1
+}
+
+/// Reads fractional numbers in base 10 and the target base for conversion
+/// from the command-line arguments and parses them into a vector of `f64` values.
+///
+/// If the first argument names a subcommand (`find`, `counterexamples`,
+/// `analyze`, `inspect`, `bench-compare`, `bench`, `alphabet`, `cf`, `check`,
+/// `check-answer`, `verify`, `aggregate`, `serve`, `csv`, `quiz`,
+/// `worksheet`, `repl`, `padic`, `approx`, `egyptian`, `calc`, `stats`,
+/// `diff`, `explain-float`, `matrix` or `compare`), the remaining arguments
+/// are parsed by that subcommand instead, and `--serve-stdio` anywhere
+/// answers requests read from standard input. `verify` checks an answer as
+/// `check-answer` does when it is given one, as in `verify 0.1 2 0.00011`.
+///
+/// # Returns
+///
+/// The parsed `Command`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // Assuming the program is run as follows:
+/// // cargo run -- 2 0.1 0.25 0.5
+/// let Command::Convert(options) = parse_input().unwrap() else { panic!() };
+/// assert_eq!(options.pipeline.target_bases, vec![2.0]);
+/// assert_eq!(options.f64_numbers, vec![0.1, 0.25, 0.5]);
+/// ```
+pub fn parse_input() -> Result<Command, String> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("find") => parse_find_args(&args[1..]).map(Command::Find),
+        Some("counterexamples") => {
+            parse_counterexample_args(&args[1..]).map(Command::Counterexamples)
+        }
+        Some("analyze") => parse_analyze_args(&args[1..]).map(Command::Analyze),
+        Some("inspect") => parse_inspect_args(&args[1..]).map(Command::Inspect),
+        Some("bench-compare") => parse_bench_args(&args[1..]).map(Command::BenchCompare),
+        Some("bench") => parse_throughput_args(&args[1..]).map(Command::Bench),
+        Some("alphabet") => parse_alphabet_args(&args[1..]).map(Command::Alphabet),
+        Some("cf") => parse_cf_args(&args[1..]).map(Command::ContinuedFraction),
+        Some("check") => parse_check_args(&args[1..]).map(Command::Check),
+        Some("check-answer") => parse_answer_args(&args[1..]).map(Command::CheckAnswer),
+        Some("verify") if checks_answer(&args[1..]) => {
+            parse_answer_args(&args[1..]).map(Command::CheckAnswer)
+        }
+        Some("verify") => parse_verify_args(&args[1..]).map(Command::Verify),
+        Some("aggregate") => parse_aggregate_args(&args[1..]).map(Command::Aggregate),
+        #[cfg(feature = "server")]
+        Some("serve") => parse_serve_args(&args[1..]).map(Command::Serve),
+        #[cfg(not(feature = "server"))]
+        Some("serve") => {
+            Err("serve needs the server feature; build with --features server".to_string())
+        }
+        Some("csv") => parse_csv_args(&args[1..]).map(Command::Csv),
+        Some("quiz") => parse_quiz_args(&args[1..]).map(Command::Quiz),
+        Some("worksheet") => parse_worksheet_args(&args[1..]).map(Command::Worksheet),
+        Some("repl") => parse_repl_args(&args[1..]).map(Command::Repl),
+        Some("padic") => parse_padic_args(&args[1..]).map(Command::Padic),
+        Some("approx") => parse_approx_args(&args[1..]).map(Command::Approx),
+        Some("egyptian") => parse_egyptian_args(&args[1..]).map(Command::Egyptian),
+        Some("calc") => parse_calc_args(&args[1..]).map(Command::Calc),
+        Some("stats") => parse_stats_args(&args[1..]).map(Command::Stats),
+        Some("diff") => parse_diff_args(&args[1..]).map(Command::Diff),
+        Some("explain-float") => parse_explain_float_args(&args[1..]).map(Command::ExplainFloat),
+        Some("matrix") => parse_matrix_args(&args[1..]).map(Command::Matrix),
+        #[cfg(feature = "server")]
+        _ if args.iter().any(|arg| arg == "--serve-stdio") => {
+            parse_stdio_args(&args).map(Command::ServeStdio)
+        }
+        #[cfg(not(feature = "server"))]
+        _ if args.iter().any(|arg| arg == "--serve-stdio") => {
+            Err("--serve-stdio needs the server feature; build with --features server".to_string())
+        }
+        Some("compare") => {
+            let environment = Config::from_environment(|name| env::var(name).ok())?;
+            parse_compare_args(&args[1..], default_config_path().as_deref(), &environment)
+                .map(|options| Command::Convert(Box::new(options)))
+        }
+        _ => {
+            let environment = Config::from_environment(|name| env::var(name).ok())?;
+            parse_args_with_config(&args, default_config_path().as_deref(), &environment)
+                .map(|options| Command::Convert(Box::new(options)))
+        }
+    }
+}
+
+/// The bases of the `compare` subcommand, which most coursework asks for.
+pub const COMPARE_BASES: &str = "2,8,10,16";
+
+/// Parses the arguments of the `compare` subcommand, which converts every
+/// input to binary, octal, decimal and hexadecimal in one table. Every other
+/// flag of a plain conversion is accepted, but not the ones choosing bases.
+///
+/// # Returns
+///
+/// The parsed `Options`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- compare 0.1 0.5 --precision 6
+/// ```
+pub fn parse_compare_args(
+    args: &[String],
+    default_config: Option<&Path>,
+    environment: &Config,
+) -> Result<Options, String> {
+    let flag_name = |arg: &String| arg.split('=').next().unwrap_or_default().to_string();
+    if let Some(arg) = args.iter().find(|arg| fixes_bases(&flag_name(arg))) {
+        return Err(format!(
+            "compare picks its own bases and does not accept `{}`",
+            arg
+        ));
+    }
+
+    let mut args = args.to_vec();
+    args.splice(0..0, ["--base".to_string(), COMPARE_BASES.to_string()]);
+    parse_args_with_config(&args, default_config, environment)
+}
+
+/// Parses the command-line arguments that follow the program name.
+///
+/// # Arguments
+///
+/// * `args` - The arguments, without the program name.
+/// * `default_config` - The configuration file to take defaults from when
+///   it exists and no `--config` is given, if any.
+/// * `environment` - The defaults of the `BASECONV_*` environment variables,
+///   which win over those of the configuration file.
+///
+/// # Returns
+///
+/// The parsed `Options`, or a message describing why the arguments are invalid.
+///
+/// # Errors
+///
+/// A first positional argument that is a whole number is the target base,
+/// but for the modes of a radix of their own, such as `--factoradic`,
+/// which refuse `--base` and read it as an input, and one that is not a base, such as `0`, `1` or a number above 2^32, is
+/// an error rather than an input. Without one the target base defaults to
+/// the configured bases, or 2, unless `--require-base` (or `--strict`)
+/// makes a first argument that is not a base an error. Nothing the arguments hold makes this
+/// function panic. The arguments after the target base are the inputs;
+/// those that are not numbers are
+/// skipped, which the run ends with exit code 1 for, unless `--strict`
+/// (or `--error-mode fail-fast`) stops it at the first of them. With
+/// `--error-mode collect` they are all reported together once the results
+/// are written, and `--error-mode skip` warns about every one as it is read.
+/// `--paging auto` shows results taller than the terminal through `$PAGER`,
+/// or a built-in pager without one, and `--paging always` pages them even
+/// when they fit.
+/// Every argument after `--` is positional, such as `-0`, which before it
+/// is `--null`.
+///
+/// With `--pipe <expression>`, the target base is taken from the expression
+/// and every positional argument is an input for its decode stage.
+///
+/// With `--base <list>` (which may be repeated), the target bases are taken
+/// from the comma separated lists, overriding any `to=` stage, and every
+/// positional argument is an input.
+///
+/// With `--precision <list>`, the digit limit is overridden. Several limits
+/// may only be given together with `--matrix-options`, which crosses every
+/// input with every target base and precision.
+///
+/// With `--error`, a round-trip error column follows every base column, and
+/// with `--terminates` a column telling whether the exact expansion ends
+/// and, if not, which prime factor of the denominator prevents it, and with
+/// `--period` a column of its pre-period and period lengths. `--digits-value`
+/// adds the exact fraction the digits of every base denote and its
+/// difference from the input, and `--sparkline` a sparkline of how fast
+/// they approach it. `--fraction`
+/// adds a column of the exact value of every input in lowest terms, and
+/// `--exact-value` one of the exact decimal value of its `f64`. `--dedup`
+/// collapses repeated values into one row with a count column, and
+/// `--bit-groups nibble|byte` adds one of the binary digits regrouped into
+/// nibbles or bytes next to their hexadecimal digits. With
+/// `--explain` the worked steps of every conversion are printed.
+/// `--summary-only` replaces the table with aggregate statistics, and
+/// `--balanced` switches to balanced ternary (base 3 unless given otherwise).
+/// `--base-float <list>` adds non-integer target bases such as `1.5` or `phi`
+/// alongside those of `--base`, and `--expansion` picks which of their
+/// β-expansions are shown. `--as-time` prints base-60 expansions of hours
+/// (or, with `--as-time=day`, of days) as `HH:MM:SS.sss`. `--exact` keeps
+/// every decimal input as the exact fraction it is written as, such as 1/10
+/// for `0.1`, and divides its digits out of it, and `--decimal` reads every
+/// input as a 96-bit decimal, refusing any other; `--float-width 32` rounds
+/// every input to the nearest `f32` first. `--interpret literal` is
+/// `--exact`, `--interpret float` converts the stored `f64` of every input,
+/// even one written as a fraction, and `--interpret both` gives every input
+/// a row of each, labelled `literal` and `float`. `--backend` forces
+/// the arithmetic used for positive integer bases instead of picking one per
+/// input, and `--hex-float` prints every input as a `%a` hex float instead.
+/// `--bcd` prints the decimal digits of every input as 4-bit nibbles,
+/// `--factoradic` writes it with the place values 1/2!, 1/3!, 1/4! and so on,
+/// `--radices 24,60,60` with a radix per place, the last one repeating,
+/// `--duration` as a number of days written `HH:MM:SS.mmm`, `--dms` as a
+/// number of degrees written `D° M′ S″`,
+/// `--zeckendorf` as sums of Fibonacci numbers and powers of 1/φ, and
+/// `--gray` adds a column with the bits of every binary expansion in
+/// reflected Gray code. `--as-fixed q<m>.<n>` prints the two's complement bit
+/// pattern of every input in that fixed-point format. `--scientific` writes
+/// every expansion as a first non-zero digit and an exponent in its base,
+/// `--shortest` with the fewest digits that still read back as the same
+/// `f64`, `--exact-digits` with exactly the digits a terminating expansion
+/// ends after, and a column saying how many that is,
+/// and `--pad-digits <width>` zero-pads every digit to the given width, or
+/// with `auto` to that of the largest digit of the base. `--alphabet
+/// <symbols>` (or `--alphabet-file <file>`) writes the digits with the given
+/// symbols, one per digit value, as `--digit-script <name>` does with the
+/// digits of a numeral script such as `arabic-indic` or `devanagari`, and
+/// `--subscript` follows every value by its base as a subscript, as in
+/// `0.1;1;₂`. `--notation alphanumeric` writes the digits as `0-9A-Z`
+/// without separators, `--notation subscript` the usual digits followed
+/// by the base as a subscript, `--notation cuneiform` every base 60 digit
+/// in Babylonian cuneiform signs, and `--notation maya` (or `maya-ascii`)
+/// every base 20 digit as a Maya dot-and-bar numeral. `--highlight-period`
+/// marks the first repetition of the repeating block of every expansion,
+/// in color on a terminal and between parentheses otherwise, or as
+/// `--highlight-period=color|underline|parens` asks. `--script <file>` runs
+/// a script on the digits of every cell, which may rewrite the cell and add
+/// columns computed from them after every base column.
+///
+/// `--input <file>` reads more inputs, separated by whitespace, from a file
+/// or from standard input for `-`, and `--mmap` maps the files into memory
+/// to decode their inputs in place. In every file a `#` at the start of a
+/// line or after whitespace starts a comment running to the end of the
+/// line. Every `--file <file>`, and every
+/// positional argument naming a file, is instead converted on its own
+/// thread into a section of its own, as is every file `--glob <pattern>`
+/// matches, whose layout `--output-dir` mirrors. With `--column <name>`
+/// every such file is read as a CSV with a header row, and its inputs are
+/// the cells of the column with that header or 1-based number, skipping
+/// the rows whose cell is no valid input with a warning naming the row.
+/// A file that is a SQLite database is read instead with `--table <name>
+/// --column <name>`, the values of that column of every row of the table,
+/// in rowid order; it cannot be queried, as that takes SQLite itself.
+/// Batches of many inputs
+/// are converted on `--jobs <n>` threads, by default one per core. With
+/// `--stream`, the `--input` files are instead converted line by line as
+/// they are read, and with `--watch` everything is converted again whenever
+/// one changes.
+/// `--checkpoint <file>` records how far a `--stream` run got, so that once
+/// interrupted it can go on from there with `--resume`.
+/// `--sort value|input-order|digit-count` orders the rows by the value of
+/// every input or by the number of digits in the first base, and `--desc`
+/// puts the largest first. `--filter terminating|repeating|truncated|error>EPS`
+/// (which may be repeated) only keeps the rows with a target base whose
+/// exact expansion ends, or repeats, or whose digits are cut off or further
+/// than `EPS` from the input.
+///
+/// `--max-memory <bytes>` caps the results held in memory before printing,
+/// and `--style <file>` sets the alignment, color and width of the columns.
+/// `--table-style ascii|unicode|borderless` draws the table with `+---+`
+/// rules, with box-drawing lines or without borders instead of in Markdown.
+/// Large batches show a progress bar on standard error, which `--quiet` hides.
+/// `-v` logs the value of every input and the path chosen to convert it to
+/// standard error, and `-vv` also how its digit loop went.
+/// `--output <file>` writes the results to a file, which `--append` adds to
+/// and `--rotate-size <bytes>` rotates once it gets that large. An existing
+/// file is only replaced with `--force`, or with `--backup`, which keeps it
+/// as `<file>.bak`. `--checksum` ends the results with their SHA-256, and
+/// `--checksum=sidecar` writes it to `<file>.sha256` instead.
+/// `--sign-key <key file>` signs the file with Ed25519 into `<file>.sig`.
+/// Without `--format`, a `.md`, `.csv`, `.json`, `.jsonl`, `.yaml` or
+/// `.msgpack` file picks its format.
+/// `--output-dir <dir>` converts the inputs once and writes them to
+/// `<dir>/results.md`, `.csv`, `.json`, `.jsonl`, `.yaml`, `.msgpack` or
+/// `.txt` for every
+/// `--format` given.
+///
+/// `--decimal-comma` accepts inputs written with a decimal comma, such as
+/// `0,5`, as does `--locale <name>` for locales that write them that way.
+/// `--auto-detect` reads inputs ending in a subscript base, such as `0.101₂`
+/// or `0.C₁₆`, in that base, alongside the `0x`, `0b` and `0o` prefixes
+/// every run reads and the bare numbers taken as decimals. An input
+/// followed by a digit limit, as in `0.1:20`, is converted to that many
+/// digits instead of those of `--precision`. An input led by a label, as in
+/// `pi_frac=0.14159`, has the label in a leading column of the table and
+/// a leading `label` field of its records.
+///
+/// `--format md|csv|tsv|json|jsonl|yaml|msgpack|xlsx|sqlite|parquet|plain`
+/// writes the conversions as the table, as CSV, TSV or JSON records, as an
+/// Excel workbook, as a `conversions` table of a new SQLite database, as
+/// the typed columns of a Parquet file, as one JSON object per line, as a
+/// YAML sequence, as a stream of MessagePack maps, or as one representation
+/// per line, which `-0` (or
+/// `--null`) ends with a NUL byte instead, and `--separator <text>` writes
+/// the given text between the digits of the table instead of `;`. `--config <file>` reads defaults for
+/// the base, precision, separator and format from a TOML file, which is
+/// otherwise `default_config`. The `BASECONV_BASE`, `BASECONV_PRECISION`,
+/// `BASECONV_SEPARATOR` and `BASECONV_FORMAT` environment variables win over
+/// the file, and flags always win over both.
+pub fn parse_args_with_config(
+    args: &[String],
+    default_config: Option<&Path>,
+    environment: &Config,
+) -> Result<Options, String> {
+    let mut pipeline = None;
+    let mut target_bases: Vec<f64> = Vec::new();
+    let mut precisions: Vec<u32> = Vec::new();
+    let mut modes = Modes::default();
+    let mut backend = None;
+    let mut jobs = None;
+    let mut max_memory = None;
+    let mut quiet = false;
+    let mut require_base = false;
+    let mut error_mode = ErrorMode::default();
+    let mut verbosity = 0;
+    let mut config_path = None;
+    let mut representation = RepresentationFlags::default();
+    let mut table = TableFlags::default();
+    let mut inputs = InputFlags::default();
+    let mut output = OutputFlags::default();
+    let mut arrangement = Arrangement::default();
+    let mut positional: Vec<&str> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            positional.extend(iter.by_ref().map(String::as_str));
+            break;
+        }
+        if let Some(expression) = flag_value(arg, "--pipe", &mut iter)? {
+            pipeline = Some(Pipeline::parse(expression)?);
+        } else if let Some(list) = flag_value(arg, "--base", &mut iter)? {
+            target_bases.extend(parse_base_list(list)?);
+        } else if let Some(list) = flag_value(arg, "--base-float", &mut iter)? {
+            target_bases.extend(parse_float_base_list(list)?);
+        } else if let Some(list) = flag_value(arg, "--precision", &mut iter)? {
+            precisions.extend(parse_precision_list(list)?);
+        } else if arg == "--matrix-options" {
+            modes.matrix_options = true;
+        } else if arg == "--explain" {
+            modes.explain = true;
+        } else if arg == "--summary-only" {
+            modes.summary_only = true;
+        } else if let Some(value) = flag_value(arg, "--backend", &mut iter)? {
+            backend = Some(parse_backend(value)?);
+        } else if let Some(path) = flag_value(arg, "--config", &mut iter)? {
+            config_path = Some(PathBuf::from(path));
+        } else if let Some(value) = flag_value(arg, "--max-memory", &mut iter)? {
+            max_memory = Some(parse_byte_count(value)?);
+        } else if arg == "--quiet" {
+            quiet = true;
+        } else if arg == "--strict" {
+            error_mode = ErrorMode::FailFast;
+            require_base = true;
+        } else if arg == "--require-base" {
+            require_base = true;
+        } else if let Some(name) = flag_value(arg, "--error-mode", &mut iter)? {
+            error_mode = ErrorMode::from_name(name).ok_or_else(|| {
+                format!(
+                    "unknown error mode `{}`; expected fail-fast, collect or skip",
+                    name
+                )
+            })?;
+        } else if arg == "-v" || arg == "-vv" || arg == "--verbose" {
+            verbosity = (verbosity + if arg == "-vv" { 2 } else { 1 }).min(2);
+        } else if let Some(value) = flag_value(arg, "--jobs", &mut iter)? {
+            jobs = Some(
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&jobs| jobs > 0)
+                    .ok_or_else(|| format!("invalid job count `{}`", value))?,
+            );
+        } else if !(representation.parse_flag(arg, &mut iter)?
+            || table.parse_flag(arg, &mut iter)?
+            || inputs.parse_flag(arg, &mut iter)?
+            || output.parse_flag(arg, &mut iter)?
+            || arrangement.parse_flag(arg, &mut iter)?)
+        {
+            positional.push(arg);
+        }
+    }
+
+    let config = environment
+        .clone()
+        .or(match config_path.as_deref().or(default_config) {
+            Some(path) if config_path.is_some() || path.exists() => Config::load(path)?,
+            _ => Config::default(),
+        });
+
+    let explicit_bases = !target_bases.is_empty();
+    let piped = pipeline.is_some();
+    let balanced = representation.representation.balanced;
+    let as_time = representation.representation.as_time.is_some();
+    let default_bases = if balanced {
+        vec![BALANCED_BASE as f64]
+    } else if as_time {
+        vec![TIME_BASE as f64]
+    } else {
+        config.base.clone().unwrap_or_else(|| vec![2.0])
+    };
+    // `--balanced`, `--as-time` and the configuration choose the bases of a
+    // run that names none, so that it needs none of its own.
+    let own_bases = balanced || as_time || config.base.is_some();
+    // The modes of a radix of their own take no target base, so a first
+    // whole number is one of their inputs.
+    let own_radix = representation.representation.own_radix();
+    let mut bases_defaulted = false;
+    let mut pipeline = match pipeline {
+        Some(pipeline) => pipeline,
+        None if explicit_bases || own_radix => Pipeline::with_bases(default_bases),
+        None if require_base && !own_bases => {
+            let arg = positional.first().ok_or(
+                "no target base was given; give it before the inputs, as in `2 0.5`, \
+                 or pass --base",
+            )?;
+            let target_bases = parse_base_list(arg).map_err(|message| {
+                format!(
+                    "{}; the first argument must be the target base, as in `2 0.5`, \
+                     or pass --base",
+                    message
+                )
+            })?;
+            positional.remove(0);
+            Pipeline::with_bases(target_bases)
+        }
+        None => match positional.first().filter(|arg| is_whole_number(arg)) {
+            Some(arg) => {
+                let target_bases = parse_base_list(arg)?;
+                positional.remove(0);
+                Pipeline::with_bases(target_bases)
+            }
+            None => {
+                bases_defaulted = !own_bases;
+                Pipeline::with_bases(default_bases)
+            }
+        },
+    };
+    if explicit_bases {
+        pipeline.target_bases = target_bases;
+    }
+
+    let run_flags = [
+        ("--base", explicit_bases),
+        ("--matrix-options", modes.matrix_options),
+        ("--summary-only", modes.summary_only),
+        ("--explain", modes.explain),
+        ("--precision", !precisions.is_empty()),
+    ];
+    let to_terminal = output.options.path.is_none() && io::stdout().is_terminal();
+    let representation = representation.check(
+        &mut pipeline,
+        &mut table.columns,
+        &run_flags,
+        modes.explain,
+        to_terminal,
+    )?;
+    let resume_from = inputs.check_stream(modes, &mut output.options)?;
+
+    // Only the plain conversions can be written as records, so a default
+    // format from the configuration file leaves the other modes as tables.
+    let other_modes =
+        modes.explain || modes.summary_only || !representation.is_plain() || table.changes_table();
+    let plain = !(modes.matrix_options || other_modes);
+    let format = output.resolve_format(
+        &config,
+        modes,
+        other_modes,
+        &inputs,
+        table.selected_columns.is_some(),
+    )?;
+    output.check_destination(&inputs, plain)?;
+    arrangement.check_flags(inputs.stream)?;
+    table.check(&output.formats, modes, inputs.stream)?;
+
+    match (precisions.first(), config.precision) {
+        (Some(&digits), _) => pipeline.digits = digits,
+        (None, Some(digits)) if !piped => {
+            pipeline.digits = digits;
+            precisions.push(digits);
+        }
+        (None, _) => precisions.push(pipeline.digits),
+    }
+    table.columns.verify = table
+        .verify
+        .map(|tolerance| tolerance.unwrap_or(Tolerance::LastPlace(pipeline.digits)));
+    if precisions.len() > 1 && !modes.matrix_options {
+        return Err("several precisions can only be used with --matrix-options".to_string());
+    }
+
+    if let Some(backend) = backend {
+        pipeline.backend = backend;
+    }
+    // A batch is held in memory anyway, so its repeated inputs may as well
+    // be converted once, while a stream keeps nothing once it is printed.
+    if !inputs.stream {
+        pipeline.cache = Some(ExpansionCache::new());
+    }
+    let both = inputs.configure(&mut pipeline, representation.balanced, modes.explain)?;
+
+    let (decoded_inputs, invalid_spans) = inputs.read(args, positional, &pipeline)?;
+    let DecodedInputs {
+        decoded,
+        digit_limits,
+        labels,
+        invalid: invalid_inputs,
+    } = decoded_inputs;
+    inputs.check_sources(
+        !(decoded.is_empty() && invalid_inputs.is_empty()),
+        output.options.dir.as_deref(),
+        format,
+    )?;
+    if digit_limits.iter().any(Option::is_some)
+        && (table.columns.count || modes.matrix_options || modes.explain || inputs.stream)
+    {
+        return Err(
+            "inputs with their own digit limit, such as 0.1:20, cannot be combined with \
+             --dedup, --matrix-options, --explain or --stream"
+                .to_string(),
+        );
+    }
+    table.columns.label = labels.iter().any(Option::is_some);
+    if let Some(selected) = &table.selected_columns {
+        select_table_columns(selected, &mut table.columns);
+    }
+    arrangement.dedup = table.columns.count;
+    let inputs_read = DecodedInputs {
+        decoded,
+        digit_limits,
+        labels,
+        invalid: Vec::new(),
+    };
+    let mut rows = arrange_inputs(inputs_read, &arrangement, &pipeline)?;
+    if both {
+        rows = rows
+            .into_iter()
+            .flat_map(|((number, fraction), (count, digit_limit, label))| {
+                let named = |reading: &str| match &label {
+                    Some(label) => Some(format!("{} ({})", label, reading)),
+                    None => Some(reading.to_string()),
+                };
+                let float = ((number, None), (count, digit_limit, named("float")));
+                [
+                    ((number, fraction), (count, digit_limit, named("literal"))),
+                    float,
+                ]
+            })
+            .collect();
+        table.columns.label = table.columns.label || table.selected_columns.is_none();
+    }
+    let (f64_numbers, fractions, counts, digit_limits, labels) = unzip_rows(rows);
+
+    let separator = match format {
+        OutputFormat::Table => output.separator.or(config.separator),
+        _ => None,
+    };
+    Ok(Options {
+        pipeline,
+        f64_numbers,
+        fractions,
+        digit_limits,
+        labels,
+        counts,
+        precisions,
+        matrix_options: modes.matrix_options,
+        columns: table.columns,
+        explain: modes.explain,
+        summary_only: modes.summary_only,
+        footer: table.footer,
+        representation,
+        jobs,
+        watch_paths: if inputs.watch {
+            inputs.input_paths.clone()
+        } else {
+            Vec::new()
+        },
+        // Every `--file` is streamed on its own, as its section is written.
+        stream_from: match (inputs.stream, inputs.files.is_empty()) {
+            (true, true) => inputs.input_paths,
+            (true, false) => inputs.files.clone(),
+            (false, _) => Vec::new(),
+        },
+        checkpoint: inputs.checkpoint,
+        resume_from,
+        max_memory,
+        arrangement,
+        invalid_inputs,
+        invalid_spans,
+        error_mode,
+        paging: output.paging,
+        selected_columns: table.selected_columns,
+        quiet,
+        bases_defaulted,
+        verbosity,
+        styles: Styles {
+            table: output.table_style,
+            ..output.styles
+        },
+        separator,
+        format,
+        formats: output.formats,
+        output: output.options,
+        files: inputs.files,
+        glob_root: inputs.glob_root,
+        csv_column: inputs.csv_column,
+        sqlite_table: inputs.sqlite_table,
+    })
+}
+
+/// The modes of a run that print something other than a table of every
+/// input in every target base.
+#[derive(Debug, Clone, Copy, Default)]
+struct Modes {
+    /// `--matrix-options`, the dataset of every input, base and precision.
+    matrix_options: bool,
+    /// `--explain`, every multiply-by-base step before the table.
+    explain: bool,
+    /// `--summary-only`, the aggregate statistics instead of the table.
+    summary_only: bool,
+}
+
+/// How every input is written instead of as the digits of its target
+/// bases, from the flags of the representations, such as `--factoradic` or
+/// `--alphabet`. Most runs ask for none.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Representation {
+    /// Whether to convert to balanced ternary, with digits -1, 0 and 1.
+    pub balanced: bool,
+    /// The β-expansions requested with `--expansion`, shown side by side for
+    /// every non-integer base. When empty, only the greedy one is shown.
+    pub beta_rules: Vec<BetaRule>,
+    /// With `--as-time`, what the inputs are fractions of; their base-60
+    /// expansions are then printed as `HH:MM:SS.sss`.
+    pub as_time: Option<TimeUnit>,
+    /// Whether to print every input in the hexadecimal float form of C's `%a`
+    /// instead of converting it.
+    pub hex_float: bool,
+    /// Whether to show the fractional bits of every binary expansion in
+    /// reflected Gray code too.
+    pub gray: bool,
+    /// Whether to print the decimal digits of every input in binary-coded
+    /// decimal instead of converting it.
+    pub bcd: bool,
+    /// Whether to write every input in the factorial number system instead
+    /// of a positional base.
+    pub factoradic: bool,
+    /// The radix of every fractional place to write every input with
+    /// instead of a single base, from `--radices`, if any.
+    pub radices: Option<Vec<u32>>,
+    /// Whether to write every input, a number of days, as a duration
+    /// `HH:MM:SS.mmm` with the radices of `DAY_RADICES`, from `--duration`.
+    pub duration: bool,
+    /// Whether to write every input, a number of degrees, as degrees,
+    /// minutes and seconds with the radices of `DMS_RADICES`, from `--dms`.
+    pub dms: bool,
+    /// Whether to write every input in the Fibonacci base, with its integer
+    /// part in Zeckendorf form.
+    pub zeckendorf: bool,
+    /// The integer and fraction bit widths of the Qm.n two's complement
+    /// format to store every input in instead of converting it, if any.
+    pub as_fixed: Option<(u32, u32)>,
+    /// Whether to write every expansion in normalized scientific notation in
+    /// its own base, such as `1.0;1;×2^-3`.
+    pub scientific: bool,
+    /// How wide every digit is zero-padded with `--pad-digits`, if at all.
+    pub pad_digits: Option<PadWidth>,
+    /// How the digits of the expansions are written, from `--notation`, if
+    /// not the usual way.
+    pub notation: Option<Notation>,
+    /// The symbols every digit is written with, from `--alphabet`,
+    /// `--alphabet-file` or `--digit-script`, if any.
+    pub alphabet: Option<Alphabet>,
+    /// How the first repetition of the repeating block of every expansion
+    /// is marked, from `--highlight-period`, if at all.
+    pub highlight_period: Option<PeriodStyle>,
+    /// Whether to follow every converted value by its base as a Unicode
+    /// subscript, as in `0.1;0;1;₂`.
+    pub subscript: bool,
+}
+
+impl Representation {
+    /// Whether a mode of a radix of its own, such as `--factoradic` or
+    /// `--bcd`, writes the inputs, so that no target base is used.
+    pub fn own_radix(&self) -> bool {
+        self.factoradic
+            || self.hex_float
+            || self.duration
+            || self.dms
+            || self.radices.is_some()
+            || self.zeckendorf
+            || self.bcd
+            || self.as_fixed.is_some()
+    }
+
+    /// Whether every input is written as the plain digits of its target
+    /// bases, which is all the records of `--format` can hold.
+    pub fn is_plain(&self) -> bool {
+        !(self.balanced
+            || self.hex_float
+            || self.gray
+            || self.bcd
+            || self.factoradic
+            || self.radices.is_some()
+            || self.zeckendorf
+            || self.scientific
+            || self.subscript
+            || self.as_fixed.is_some()
+            || self.as_time.is_some()
+            || self.pad_digits.is_some()
+            || self.alphabet.is_some()
+            || self.notation.is_some()
+            || self.highlight_period.is_some()
+            || !self.beta_rules.is_empty())
+    }
+}
+
+/// The flags of the representations as they were given, before they are
+/// checked against the target bases.
+#[derive(Debug, Default)]
+struct RepresentationFlags {
+    /// The representations, but for the style of `--highlight-period`.
+    representation: Representation,
+    /// `--highlight-period`, with the style it named, if any.
+    highlight_period: Option<Option<PeriodStyle>>,
+    /// `--shortest`, which the pipeline converts with.
+    shortest: bool,
+    /// `--exact-digits`, which the pipeline converts with.
+    exact_digits: bool,
+}
+
+impl RepresentationFlags {
+    /// Reads `arg` if it is the flag of a representation, taking its value
+    /// from `rest` when it is not given as `--name=value`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was such a flag, or a message if its value is invalid.
+    fn parse_flag<'a>(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        let representation = &mut self.representation;
+        if arg == "--balanced" {
+            representation.balanced = true;
+        } else if let Some(list) = flag_value(arg, "--expansion", rest)? {
+            representation.beta_rules.extend(parse_rule_list(list)?);
+        } else if let Some(value) = flag_value(arg, "--as-fixed", rest)? {
+            representation.as_fixed = Some(
+                parse_q_format(value)
+                    .ok_or_else(|| format!("unknown fixed-point format `{}`", value))?,
+            );
+        } else if let Some(value) = flag_value(arg, "--pad-digits", rest)? {
+            representation.pad_digits = Some(
+                PadWidth::parse(value).ok_or_else(|| format!("invalid digit width `{}`", value))?,
+            );
+        } else if let Some(value) = flag_value(arg, "--notation", rest)? {
+            representation.notation =
+                Some(parse_notation(value)?).filter(|&name| name != Notation::Semicolon);
+        } else if let Some(value) = flag_value(arg, "--alphabet-file", rest)? {
+            representation.alphabet = Some(Alphabet::load(value)?);
+        } else if let Some(value) = flag_value(arg, "--alphabet", rest)? {
+            representation.alphabet = Some(Alphabet::parse(value)?);
+        } else if let Some(name) = flag_value(arg, "--digit-script", rest)? {
+            representation.alphabet = Some(Alphabet::script(name)?);
+        } else if arg == "--subscript" {
+            representation.subscript = true;
+        } else if arg == "--highlight-period" {
+            self.highlight_period = Some(None);
+        } else if let Some(value) = arg.strip_prefix("--highlight-period=") {
+            self.highlight_period = Some(Some(parse_period_style(value)?));
+        } else if arg == "--scientific" {
+            representation.scientific = true;
+        } else if arg == "--shortest" {
+            self.shortest = true;
+        } else if arg == "--exact-digits" {
+            self.exact_digits = true;
+        } else if arg == "--bcd" {
+            representation.bcd = true;
+        } else if arg == "--factoradic" {
+            representation.factoradic = true;
+        } else if arg == "--zeckendorf" {
+            representation.zeckendorf = true;
+        } else if let Some(value) = flag_value(arg, "--radices", rest)? {
+            representation.radices = Some(parse_radices(value)?);
+        } else if arg == "--duration" {
+            representation.duration = true;
+        } else if arg == "--dms" {
+            representation.dms = true;
+        } else if arg == "--gray" {
+            representation.gray = true;
+        } else if arg == "--hex-float" {
+            representation.hex_float = true;
+        } else if arg == "--as-time" {
+            representation.as_time = Some(TimeUnit::Hour);
+        } else if let Some(unit) = arg.strip_prefix("--as-time=") {
+            representation.as_time = Some(
+                TimeUnit::from_name(unit).ok_or_else(|| format!("unknown time unit `{}`", unit))?,
+            );
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Checks the representations against the target bases of the
+    /// pipeline and the other flags of the run, and hands `--shortest` and
+    /// `--exact-digits` to the pipeline and the columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `run_flags` - The other flags that only some representations take,
+    ///   with whether each was given.
+    /// * `explain` - Whether `--explain` was given.
+    /// * `to_terminal` - Whether the results are written to a terminal,
+    ///   where the period of `--highlight-period` is colored by default.
+    ///
+    /// # Returns
+    ///
+    /// The representations, or a message naming the flags that cannot be
+    /// combined or the bases a representation does not support.
+    fn check(
+        self,
+        pipeline: &mut Pipeline,
+        columns: &mut Columns,
+        run_flags: &[(&'static str, bool)],
+        explain: bool,
+        to_terminal: bool,
+    ) -> Result<Representation, String> {
+        let mut representation = self.representation;
+        if representation.balanced && pipeline.target_bases != [BALANCED_BASE as f64] {
+            return Err(format!("--balanced only supports base {}", BALANCED_BASE));
+        }
+        pipeline.shortest |= self.shortest;
+        pipeline.exact_digits |= self.exact_digits;
+        let given = [
+            ("--expansion", !representation.beta_rules.is_empty()),
+            ("--as-fixed", representation.as_fixed.is_some()),
+            ("--bcd", representation.bcd),
+            ("--factoradic", representation.factoradic),
+            ("--radices", representation.radices.is_some()),
+            ("--duration", representation.duration),
+            ("--dms", representation.dms),
+            ("--zeckendorf", representation.zeckendorf),
+            ("--hex-float", representation.hex_float),
+            ("--as-time", representation.as_time.is_some()),
+            ("--gray", representation.gray),
+            ("--scientific", representation.scientific),
+            ("--shortest", pipeline.shortest),
+            ("--exact-digits", pipeline.exact_digits),
+            ("--alphabet", representation.alphabet.is_some()),
+            ("--notation", representation.notation.is_some()),
+            ("--pad-digits", representation.pad_digits.is_some()),
+            ("--balanced", representation.balanced),
+            ("--highlight-period", self.highlight_period.is_some()),
+            ("--subscript", representation.subscript),
+        ];
+        check_supported_flags(
+            &run_flags
+                .iter()
+                .chain(&given)
+                .filter(|(_, given)| *given)
+                .map(|&(flag, _)| flag)
+                .collect::<Vec<_>>(),
+        )?;
+        if let Some(unit) = representation.as_time {
+            if pipeline.target_bases != [TIME_BASE as f64] {
+                return Err(format!("--as-time only supports base {}", TIME_BASE));
+            }
+            if explain && unit == TimeUnit::Day {
+                return Err("--explain does not support --as-time=day".to_string());
+            }
+        }
+        if representation.duration {
+            representation.radices = Some(DAY_RADICES.to_vec());
+        } else if representation.dms {
+            representation.radices = Some(DMS_RADICES.to_vec());
+        }
+        if representation.gray && pipeline.target_bases != [GRAY_BASE as f64] {
+            return Err(format!("--gray only supports base {}", GRAY_BASE));
+        }
+        let positive_integer_bases = pipeline
+            .target_bases
+            .iter()
+            .all(|&base| base >= 2.0 && base.fract() == 0.0);
+        if representation.scientific && !positive_integer_bases {
+            return Err("--scientific only supports positive integer bases".to_string());
+        }
+        if pipeline.shortest && !positive_integer_bases {
+            return Err("--shortest only supports positive integer bases".to_string());
+        }
+        if pipeline.exact_digits {
+            columns.digits_needed = true;
+        }
+        if let Some(alphabet) = &representation.alphabet {
+            for &base in &pipeline.target_bases {
+                if base.fract() != 0.0 {
+                    return Err("--alphabet only supports integer bases".to_string());
+                }
+                if alphabet.symbols.len() < base.abs() as usize {
+                    return Err(format!(
+                        "base {} needs {} symbols, but the alphabet has {}",
+                        base,
+                        base.abs(),
+                        alphabet.symbols.len()
+                    ));
+                }
+            }
+        }
+        // Without a style, the period is colored on a terminal and put
+        // between parentheses in files and pipes.
+        representation.highlight_period = self.highlight_period.map(|style| {
+            style.unwrap_or_else(|| {
+                PeriodStyle::detect(
+                    to_terminal,
+                    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+                )
+            })
+        });
+        if representation.subscript && pipeline.target_bases.iter().any(|base| base.fract() != 0.0)
+        {
+            return Err("--subscript only supports integer bases".to_string());
+        }
+        if !representation.beta_rules.is_empty()
+            && !pipeline.target_bases.iter().any(|base| base.fract() != 0.0)
+        {
+            return Err("--expansion requires a non-integer --base-float".to_string());
+        }
+        Ok(representation)
+    }
+}
+
+/// The flags of the columns of the table and the lines around it.
+#[derive(Debug, Default)]
+struct TableFlags {
+    /// The optional columns that were switched on.
+    columns: Columns,
+    /// `--verify-roundtrip`, with the tolerance it named, if any.
+    verify: Option<Option<Tolerance>>,
+    /// `--footer`.
+    footer: bool,
+    /// The columns `--columns` named, in order, if it was given.
+    selected_columns: Option<Vec<Column>>,
+}
+
+impl TableFlags {
+    /// Reads `arg` if it is the flag of a column or of the lines around
+    /// the table, taking its value from `rest` when it is not given as
+    /// `--name=value`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was such a flag, or a message if its value is invalid.
+    fn parse_flag<'a>(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        let columns = &mut self.columns;
+        if arg == "--error" {
+            columns.round_trip_error = true;
+        } else if arg == "--terminates" {
+            columns.terminates = true;
+        } else if arg == "--period" {
+            columns.period = true;
+        } else if arg == "--fraction" {
+            columns.fraction = true;
+        } else if arg == "--exact-value" {
+            columns.exact_value = true;
+        } else if arg == "--digits-value" {
+            columns.digits_value = true;
+        } else if arg == "--sparkline" {
+            columns.sparkline = true;
+        } else if let Some(value) = flag_value(arg, "--decimal-places", rest)? {
+            columns.decimal_places = parse_decimal_places(value)?;
+        } else if arg == "--dedup" {
+            columns.count = true;
+        } else if let Some(size) = flag_value(arg, "--bit-groups", rest)? {
+            columns.bit_groups = Some(parse_group_size(size)?);
+        } else if let Some(path) = flag_value(arg, "--script", rest)? {
+            // The script is kept for the rest of the run, so that `Columns`
+            // can stay `Copy`.
+            columns.script = Some(Box::leak(Box::new(Script::load(path)?)));
+        } else if arg == "--verify-roundtrip" {
+            self.verify = Some(None);
+        } else if let Some(value) = arg.strip_prefix("--verify-roundtrip=") {
+            self.verify = Some(Some(parse_tolerance(value)?));
+        } else if arg == "--footer" {
+            self.footer = true;
+        } else if let Some(value) = flag_value(arg, "--columns", rest)? {
+            self.selected_columns = Some(parse_columns(value)?);
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Whether a column beyond the inputs and their digits, or a footer,
+    /// was asked for, which the records of `--format` cannot hold.
+    fn changes_table(&self) -> bool {
+        let columns = &self.columns;
+        columns.round_trip_error
+            || columns.digits_value
+            || columns.sparkline
+            || columns.terminates
+            || columns.period
+            || columns.fraction
+            || columns.exact_value
+            || columns.digits_needed
+            || columns.bit_groups.is_some()
+            || columns.script.is_some()
+            || columns.count
+            || self.footer
+            || self.verify.is_some()
+    }
+
+    /// Checks the columns against the formats and the modes of the run.
+    ///
+    /// # Returns
+    ///
+    /// A message naming the flags that cannot be combined, if any.
+    fn check(&self, formats: &[OutputFormat], modes: Modes, stream: bool) -> Result<(), String> {
+        if let Some(selected) = &self.selected_columns {
+            for &format in formats {
+                check_columns(selected, format)?;
+            }
+            if modes.matrix_options || modes.summary_only {
+                return Err(
+                    "--columns cannot be combined with --matrix-options or --summary-only"
+                        .to_string(),
+                );
+            }
+        }
+        if self.verify.is_some() && (modes.summary_only || modes.matrix_options) {
+            return Err(
+                "--verify-roundtrip cannot be combined with --summary-only or --matrix-options"
+                    .to_string(),
+            );
+        }
+        if self.footer && (modes.summary_only || modes.matrix_options) {
+            return Err(
+                "--footer cannot be combined with --summary-only or --matrix-options".to_string(),
+            );
+        }
+        if self.columns.count && (stream || modes.summary_only || modes.matrix_options) {
+            return Err(
+                "--dedup cannot be combined with --stream, --summary-only or --matrix-options"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The flags of where the inputs are read from and how they are read.
+#[derive(Debug, Default)]
+struct InputFlags<'a> {
+    /// The `--input` files.
+    input_paths: Vec<String>,
+    /// The `--file` inputs, to which the positional paths and the files
+    /// `--glob` found are added once the inputs are read.
+    files: Vec<String>,
+    /// The `--glob` patterns.
+    globs: Vec<String>,
+    /// The directory the files `--glob` found lie under, once they are
+    /// found.
+    glob_root: Option<String>,
+    /// `--column`.
+    csv_column: Option<String>,
+    /// `--table`.
+    sqlite_table: Option<String>,
+    /// `--mmap`.
+    mmap: bool,
+    /// `--stream`.
+    stream: bool,
+    /// `--checkpoint`.
+    checkpoint: Option<String>,
+    /// `--resume`.
+    resume: bool,
+    /// `--watch`.
+    watch: bool,
+    /// `--decimal-comma`, or a `--locale` that writes decimal commas.
+    decimal_comma: bool,
+    /// `--auto-detect`.
+    auto_detect: bool,
+    /// `--exact`.
+    exact: bool,
+    /// What `--interpret` converts, `literal`, `float` or `both`, if given.
+    interpret: Option<&'a str>,
+    /// `--decimal`.
+    decimal: bool,
+    /// Whether `--float-width 32` was given.
+    single_precision: bool,
+}
+
+impl<'a> InputFlags<'a> {
+    /// Reads `arg` if it is the flag of an input source or of how the
+    /// inputs are read, taking its value from `rest` when it is not given
+    /// as `--name=value`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was such a flag, or a message if its value is invalid.
+    fn parse_flag(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        if let Some(path) = flag_value(arg, "--input", rest)? {
+            self.input_paths.push(path.to_string());
+        } else if let Some(path) = flag_value(arg, "--file", rest)? {
+            self.files.push(path.to_string());
+        } else if let Some(pattern) = flag_value(arg, "--glob", rest)? {
+            self.globs.push(pattern.to_string());
+        } else if let Some(column) = flag_value(arg, "--column", rest)? {
+            self.csv_column = Some(column.to_string());
+        } else if let Some(table) = flag_value(arg, "--table", rest)? {
+            self.sqlite_table = Some(table.to_string());
+        } else if arg == "--mmap" {
+            self.mmap = true;
+        } else if arg == "--stream" {
+            self.stream = true;
+        } else if let Some(value) = flag_value(arg, "--checkpoint", rest)? {
+            self.checkpoint = Some(value.to_string());
+        } else if arg == "--resume" {
+            self.resume = true;
+        } else if arg == "--watch" {
+            self.watch = true;
+        } else if arg == "--decimal-comma" {
+            self.decimal_comma = true;
+        } else if let Some(locale) = flag_value(arg, "--locale", rest)? {
+            self.decimal_comma = uses_decimal_comma(locale)?;
+        } else if arg == "--auto-detect" {
+            self.auto_detect = true;
+        } else if arg == "--exact" {
+            self.exact = true;
+        } else if let Some(value) = flag_value(arg, "--interpret", rest)? {
+            if !matches!(value, "literal" | "float" | "both") {
+                return Err(format!(
+                    "invalid --interpret `{}`, expected literal, float or both",
+                    value
+                ));
+            }
+            self.interpret = Some(value);
+        } else if arg == "--decimal" {
+            self.decimal = true;
+        } else if let Some(width) = flag_value(arg, "--float-width", rest)? {
+            self.single_precision = match width {
+                "32" => true,
+                "64" => false,
+                _ => {
+                    return Err(format!(
+                        "invalid --float-width `{}`, expected 32 or 64",
+                        width
+                    ))
+                }
+            };
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Checks `--stream`, `--checkpoint` and `--resume` against the modes
+    /// of the run, and with `--resume` appends to the `--output` file of
+    /// the interrupted run.
+    ///
+    /// # Returns
+    ///
+    /// The checkpoint to resume from, if any, or a message naming the flags
+    /// that cannot be combined or why the checkpoint does not fit the run.
+    fn check_stream(
+        &self,
+        modes: Modes,
+        output: &mut OutputOptions,
+    ) -> Result<Option<Checkpoint>, String> {
+        if self.stream {
+            if self.input_paths.is_empty() && self.files.is_empty() {
+                return Err("--stream requires --input or --file".to_string());
+            }
+            if modes.explain || modes.matrix_options {
+                return Err(
+                    "--stream cannot be combined with --explain or --matrix-options".to_string(),
+                );
+            }
+        }
+        if self.checkpoint.is_some() && (!self.stream || modes.summary_only) {
+            return Err(
+                "--checkpoint requires --stream and cannot be combined with --summary-only"
+                    .to_string(),
+            );
+        }
+        if self.resume && (self.checkpoint.is_none() || output.checksum.is_some()) {
+            return Err(
+                "--resume requires --checkpoint and cannot be combined with --checksum".to_string(),
+            );
+        }
+        match &self.checkpoint {
+            Some(path) if self.resume => {
+                let resumed = Checkpoint::load(path)?;
+                if self.input_paths.get(resumed.input) != Some(&resumed.path) {
+                    return Err(format!(
+                        "checkpoint `{}` was saved reading --input file {} `{}`, which this run does not",
+                        path,
+                        resumed.input + 1,
+                        resumed.path
+                    ));
+                }
+                // The results of the interrupted run are kept, and the rest
+                // are added after them.
+                output.append = output.path.is_some();
+                Ok(Some(resumed))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Sets how the pipeline reads the inputs, from `--decimal-comma`,
+    /// `--auto-detect`, `--interpret`, `--exact`, `--decimal` and
+    /// `--float-width`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `--interpret both` gives every input two rows, or a message
+    /// if a reading does not apply to the inputs or the target bases.
+    fn configure(
+        &self,
+        pipeline: &mut Pipeline,
+        balanced: bool,
+        explain: bool,
+    ) -> Result<bool, String> {
+        if self.decimal_comma {
+            if pipeline.decode != Decode::Decimal {
+                return Err("--decimal-comma and --locale only apply to decimal inputs".to_string());
+            }
+            pipeline.decimal_comma = true;
+        }
+        if self.auto_detect {
+            if pipeline.decode != Decode::Decimal {
+                return Err("--auto-detect only applies to decimal inputs".to_string());
+            }
+            pipeline.auto_detect = true;
+        }
+        // Reading the literal text of the inputs is what `--exact` does, and
+        // both readings keep the exact fraction for the literal rows.
+        let both = self.interpret == Some("both");
+        let decimal = self.decimal;
+        if self.interpret == Some("float") {
+            if self.exact || decimal {
+                return Err(
+                    "--interpret float converts the stored f64, and cannot be combined with \
+                     --exact or --decimal"
+                        .to_string(),
+                );
+            }
+            pipeline.stored_float = true;
+        }
+        if both && (self.stream || !self.files.is_empty()) {
+            return Err("--interpret both cannot be combined with --stream or --file".to_string());
+        }
+        let exact = self.exact || matches!(self.interpret, Some("literal" | "both"));
+        if exact || decimal {
+            let flag = match self.interpret {
+                _ if decimal => "--decimal".to_string(),
+                Some(interpret @ ("literal" | "both")) => format!("--interpret {}", interpret),
+                _ => "--exact".to_string(),
+            };
+            if pipeline.decode != Decode::Decimal {
+                return Err(format!("{} only applies to decimal inputs", flag));
+            }
+            // Only the digits of positive integer bases are divided out of
+            // the fraction; the others are taken from an `f64`.
+            let integer_bases = pipeline
+                .target_bases
+                .iter()
+                .all(|&target_base| target_base >= 2.0 && target_base.fract() == 0.0);
+            if !integer_bases
+                || pipeline.shortest
+                || balanced
+                || pipeline
+                    .backend
+                    .is_some_and(|backend| backend != Backend::LongDivision)
+            {
+                return Err(format!(
+                    "{} converts to positive integer bases by long division, and cannot be \
+                     combined with --shortest, --balanced or another --backend",
+                    flag
+                ));
+            }
+            pipeline.exact = exact;
+            pipeline.decimal = decimal;
+        }
+        // The steps `--explain` shows are those of the `f64` loop, so the
+        // result they end in is converted with it too, unless another
+        // backend was asked for.
+        if explain && !exact && !decimal && pipeline.backend.is_none() {
+            pipeline.backend = Some(Backend::F64);
+        }
+        if self.single_precision {
+            // The exact fraction of an input is not the value of its `f32`.
+            if exact || decimal {
+                return Err(
+                    "--float-width 32 converts the f32 nearest to every input, and cannot be \
+                     combined with --exact or --decimal"
+                        .to_string(),
+                );
+            }
+            pipeline.single_precision = true;
+        }
+        Ok(both)
+    }
+
+    /// Reads the inputs on the command line and, unless they are streamed,
+    /// those of the `--input` files, and adds the positional paths and the
+    /// files `--glob` finds to the `--file` inputs.
+    ///
+    /// # Returns
+    ///
+    /// The decoded inputs and where every invalid one was given, or a
+    /// message if a file cannot be read or an input is not finite.
+    fn read(
+        &mut self,
+        args: &[String],
+        positional: Vec<&str>,
+        pipeline: &Pipeline,
+    ) -> Result<(DecodedInputs, Vec<Option<Span>>), String> {
+        if self.mmap && (self.input_paths.is_empty() || self.stream) {
+            return Err("--mmap requires --input and cannot be combined with --stream".to_string());
+        }
+        let mut input_text = String::new();
+        // The part of `input_text` every `--input` file was read into.
+        let mut input_parts = Vec::new();
+        let mut mapped = Vec::new();
+        if !self.stream {
+            for path in &self.input_paths {
+                if self.mmap {
+                    mapped.push(
+                        Mapped::open(path).map_err(|error| {
+                            format!("cannot map input file `{}`: {}", path, error)
+                        })?,
+                    );
+                } else {
+                    let start = input_text.len();
+                    input_text.push_str(&read_inputs(path)?);
+                    input_parts.push((path.as_str(), start..input_text.len()));
+                    input_text.push('\n');
+                }
+            }
+        }
+        // A positional argument that is no valid input but names a file is
+        // converted as a file of its own, like one given with `--file`.
+        let (file_args, positional): (Vec<&str>, Vec<&str>) = positional
+            .into_iter()
+            .partition(|arg| pipeline.decode_input(arg).is_none() && Path::new(arg).is_file());
+        self.files.extend(file_args.into_iter().map(String::from));
+        let positional: Vec<&str> = positional
+            .into_iter()
+            .flat_map(|arg| split_list(pipeline, arg))
+            .collect();
+        for pattern in &self.globs {
+            self.files.extend(expand_glob(pattern)?);
+        }
+        // The files of every pattern are written below the directory they
+        // all lie under, in the layout they have there.
+        self.glob_root = self
+            .globs
+            .iter()
+            .map(|pattern| glob_root(pattern))
+            .reduce(common_dir)
+            .map(String::from);
+        let decoded = decode_inputs(
+            pipeline,
+            positional
+                .iter()
+                .copied()
+                .chain(input_words(&input_text))
+                .chain(mapped.iter().flat_map(Mapped::words)),
+        );
+        let command_line = args.join(" ");
+        let invalid_spans = find_spans(
+            argument_words(args, &command_line, &positional)
+                .into_iter()
+                .chain(input_parts.iter().flat_map(|(path, part)| {
+                    let name = if *path == STDIN_PATH { "<stdin>" } else { path };
+                    line_words(name, &input_text[part.clone()])
+                })),
+            &decoded.invalid,
+        );
+        if let Some(input) = decoded.invalid.iter().find(|input| is_non_finite(input)) {
+            return Err(format!(
+                "`{}` is not a finite number, and infinities and NaN have no digits in any base",
+                input
+            ));
+        }
+        Ok((decoded, invalid_spans))
+    }
+
+    /// Checks the `--file` inputs and the flags that read them against the
+    /// other sources of inputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `command_line_inputs` - Whether inputs were given on the command
+    ///   line.
+    /// * `output_dir` - The `--output-dir` the sections of the files are
+    ///   written into, if any.
+    /// * `format` - The format of the results.
+    ///
+    /// # Returns
+    ///
+    /// A message naming the flags that cannot be combined, or the file two
+    /// `--file` inputs would both be written to, if any.
+    fn check_sources(
+        &self,
+        command_line_inputs: bool,
+        output_dir: Option<&str>,
+        format: OutputFormat,
+    ) -> Result<(), String> {
+        if self.checkpoint.is_some() && command_line_inputs {
+            return Err(
+                "--checkpoint only converts the --input files, not inputs on the command line"
+                    .to_string(),
+            );
+        }
+        if self.csv_column.is_some() && self.files.is_empty() {
+            return Err("--column reads the CSV files given with --file or --glob".to_string());
+        }
+        if self.sqlite_table.is_some() && self.files.is_empty() {
+            return Err(
+                "--table reads the SQLite databases given with --file or --glob".to_string(),
+            );
+        }
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        if command_line_inputs || !self.input_paths.is_empty() {
+            return Err(
+                "--file cannot be combined with inputs on the command line or --input".to_string(),
+            );
+        }
+        if self.watch || self.checkpoint.is_some() {
+            return Err("--file cannot be combined with --watch or --checkpoint".to_string());
+        }
+        if self.stream && (self.csv_column.is_some() || self.sqlite_table.is_some()) {
+            return Err(
+                "--stream reads the --file inputs line by line, and cannot be combined with \
+                 --column or --table"
+                    .to_string(),
+            );
+        }
+        if let Some(dir) = output_dir {
+            let mut paths: Vec<String> = self
+                .files
+                .iter()
+                .map(|path| section_path(dir, path, self.glob_root.as_deref(), format.extension()))
+                .collect();
+            paths.sort();
+            if let Some(pair) = paths.windows(2).find(|pair| pair[0] == pair[1]) {
+                return Err(format!(
+                    "two --file inputs would both be written to `{}`",
+                    pair[0]
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The flags of how and where the results are written.
+#[derive(Debug, Default)]
+struct OutputFlags {
+    /// Where the results are written.
+    options: OutputOptions,
+    /// Every `--format` given, in order.
+    formats: Vec<OutputFormat>,
+    /// `--separator`.
+    separator: Option<String>,
+    /// `-0` or `--null`.
+    null: bool,
+    /// The column styles of the `--style` file.
+    styles: Styles,
+    /// `--table-style`.
+    table_style: TableStyle,
+    /// `--paging`.
+    paging: Paging,
+}
+
+impl OutputFlags {
+    /// Reads `arg` if it is the flag of how or where the results are
+    /// written, taking its value from `rest` when it is not given as
+    /// `--name=value`.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was such a flag, or a message if its value is invalid.
+    fn parse_flag<'a>(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        let output = &mut self.options;
+        if let Some(path) = flag_value(arg, "--output", rest)? {
+            output.path = Some(path.to_string());
+        } else if arg == "--append" {
+            output.append = true;
+        } else if arg == "--checksum" {
+            output.checksum = Some(Checksum::Footer);
+        } else if let Some(name) = arg.strip_prefix("--checksum=") {
+            output.checksum = Some(
+                Checksum::from_name(name)
+                    .ok_or_else(|| format!("unknown checksum placement `{}`", name))?,
+            );
+        } else if let Some(path) = flag_value(arg, "--sign-key", rest)? {
+            output.sign_key = Some(SigningKey::load(path)?);
+        } else if arg == "--force" {
+            output.force = true;
+        } else if arg == "--backup" {
+            output.backup = true;
+        } else if arg == "--copy" {
+            output.copy = true;
+        } else if let Some(value) = flag_value(arg, "--rotate-size", rest)? {
+            output.rotate_size = Some(parse_byte_count(value)? as u64);
+        } else if let Some(dir) = flag_value(arg, "--output-dir", rest)? {
+            output.dir = Some(dir.to_string());
+        } else if let Some(name) = flag_value(arg, "--format", rest)? {
+            let format = parse_format(name)?;
+            if !self.formats.contains(&format) {
+                self.formats.push(format);
+            }
+        } else if let Some(text) = flag_value(arg, "--separator", rest)? {
+            self.separator = Some(text.to_string());
+        } else if arg == "-0" || arg == "--null" {
+            self.null = true;
+        } else if let Some(path) = flag_value(arg, "--style", rest)? {
+            self.styles = Styles::load(path)?;
+        } else if let Some(name) = flag_value(arg, "--table-style", rest)? {
+            self.table_style = TableStyle::parse(name)?;
+        } else if let Some(name) = flag_value(arg, "--paging", rest)? {
+            self.paging = Paging::from_name(name).ok_or_else(|| {
+                format!(
+                    "unknown paging mode `{}`; expected auto, always or never",
+                    name
+                )
+            })?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Chooses the format of the results, from `--format`, the extension
+    /// of the `--output` file or the configuration, and checks it against
+    /// the modes of the run. `formats` then holds every format written,
+    /// the first being the one returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, whose format is the default.
+    /// * `modes` - The modes of the run.
+    /// * `other_modes` - Whether a mode other than `--matrix-options`, a
+    ///   representation or a column beyond the digits was asked for, none
+    ///   of which can be written as records.
+    /// * `inputs` - The flags of the inputs.
+    /// * `selected_columns` - Whether `--columns` was given.
+    ///
+    /// # Returns
+    ///
+    /// The format, or a message naming the flags it cannot be combined with.
+    fn resolve_format(
+        &mut self,
+        config: &Config,
+        modes: Modes,
+        other_modes: bool,
+        inputs: &InputFlags,
+        selected_columns: bool,
+    ) -> Result<OutputFormat, String> {
+        let plain = !(modes.matrix_options || other_modes);
+        let stream = inputs.stream;
+        let output = &self.options;
+        if self.formats.len() > 1 && output.dir.is_none() {
+            return Err("several formats can only be written with --output-dir".to_string());
+        }
+        // Without `--format`, an `--output` file such as `results.json`
+        // picks the format of its extension, if the mode can be written
+        // that way.
+        if self.formats.is_empty() {
+            self.formats.extend(
+                output
+                    .path
+                    .as_deref()
+                    .and_then(OutputFormat::from_extension)
+                    .filter(|format| plain && (!stream || format.is_line_oriented())),
+            );
+        }
+        // The dataset of `--matrix-options` is CSV, or NDJSON with jsonl.
+        let matrix_format = modes.matrix_options
+            && !other_modes
+            && self
+                .formats
+                .iter()
+                .all(|format| matches!(format, OutputFormat::Csv | OutputFormat::JsonLines));
+        if self
+            .formats
+            .iter()
+            .any(|&format| format != OutputFormat::Table)
+        {
+            if !plain && !matrix_format {
+                return Err(
+                    "--format csv, tsv, json, jsonl, yaml, msgpack, xlsx, sqlite, parquet and plain cannot be combined with --explain, --summary-only, \
+                     --matrix-options (but for csv and jsonl), --error, --digits-value, --sparkline, --terminates, --period, --fraction, \
+                     --exact-value, --dedup, \
+                     --verify-roundtrip, --script or any other representation"
+                        .to_string(),
+                );
+            }
+            if self.separator.is_some() {
+                return Err("--separator only applies to --format md".to_string());
+            }
+            // A streamed input is written as soon as it is converted, which
+            // only a format of one record per line allows.
+            if stream
+                && self
+                    .formats
+                    .iter()
+                    .any(|&format| format != OutputFormat::Table && !format.is_line_oriented())
+            {
+                return Err(
+                    "--stream writes every input as soon as it is converted, so it only works \
+                     with --format md, csv, tsv, jsonl and plain"
+                        .to_string(),
+                );
+            }
+            if stream && selected_columns {
+                return Err("--columns cannot be combined with --stream and --format".to_string());
+            }
+        }
+        if self.separator.is_some() && modes.matrix_options {
+            return Err("--separator cannot be combined with --matrix-options".to_string());
+        }
+        let mut format = self
+            .formats
+            .first()
+            .copied()
+            .or(config
+                .format
+                .filter(|format| plain && (!stream || format.is_line_oriented())))
+            .unwrap_or_default();
+        if self.formats.is_empty() {
+            self.formats.push(format);
+        }
+        if self.null {
+            if self.formats != [OutputFormat::Plain { null: false }] {
+                return Err("-0 and --null require --format plain".to_string());
+            }
+            format = OutputFormat::Plain { null: true };
+            self.formats = vec![format];
+        }
+        // A workbook or database is a single binary file, which neither a
+        // terminal nor the sections of several --file inputs can hold, and
+        // text after it breaks.
+        if format.is_binary_file() {
+            let name = format.extension();
+            if output.append || output.checksum == Some(Checksum::Footer) {
+                return Err(format!(
+                    "--format {} cannot be combined with --append or a --checksum footer",
+                    name
+                ));
+            }
+            if output.dir.is_none() && output.path.is_none() && io::stdout().is_terminal() {
+                return Err(format!(
+                    "--format {} writes a binary file; give --output <file>.{} or redirect it",
+                    name, name
+                ));
+            }
+            if output.dir.is_none() && !inputs.files.is_empty() {
+                return Err(format!(
+                    "--format {} writes one file per --file only with --output-dir",
+                    name
+                ));
+            }
+        }
+        Ok(format)
+    }
+
+    /// Checks where the results are written against the inputs, and the
+    /// flags of the `--output` file and `--output-dir` against each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The flags of the inputs.
+    /// * `plain` - Whether the run writes nothing but the plain digits of
+    ///   every input, the only results `--output-dir` writes.
+    ///
+    /// # Returns
+    ///
+    /// A message naming the flags that cannot be combined, if any.
+    fn check_destination(&self, inputs: &InputFlags, plain: bool) -> Result<(), String> {
+        let output = &self.options;
+        let (stream, watch) = (inputs.stream, inputs.watch);
+        let files = !inputs.files.is_empty();
+        if watch {
+            if inputs.input_paths.is_empty()
+                || inputs.input_paths.iter().any(|path| path == STDIN_PATH)
+            {
+                return Err(
+                    "--watch requires an --input file other than standard input".to_string()
+                );
+            }
+            if stream || output.path.is_some() {
+                return Err("--watch cannot be combined with --stream or --output".to_string());
+            }
+        }
+
+        if output.copy && (watch || output.dir.is_some() || files) {
+            return Err(
+                "--copy copies the results of one run, and cannot be combined with \
+                 --watch, --output-dir or --file"
+                    .to_string(),
+            );
+        }
+
+        if self.paging != Paging::Never
+            && (watch || output.path.is_some() || output.dir.is_some() || files)
+        {
+            return Err(
+                "--paging pages the results on standard output, and cannot be combined with \
+                 --watch, --output, --output-dir or --file"
+                    .to_string(),
+            );
+        }
+
+        if output.dir.is_some() {
+            // The sections of `--file` inputs are streamed and checked one
+            // by one.
+            let by_file = (stream || output.checksum.is_some()) && files;
+            if output.path.is_some() || watch || ((stream || output.checksum.is_some()) && !by_file)
+            {
+                return Err(
+                    "--output-dir cannot be combined with --output or --watch, nor with --stream \
+                     or --checksum but for --file inputs"
+                        .to_string(),
+                );
+            }
+            if !plain {
+                return Err(
+                    "--output-dir cannot be combined with --explain, --summary-only, \
+                     --matrix-options, --error, --digits-value, --sparkline, --terminates, --period, --fraction, \
+                     --exact-value, --dedup, \
+                     --verify-roundtrip or any other representation"
+                        .to_string(),
+                );
+            }
+        } else if output.path.is_none() && (output.force || output.backup) {
+            return Err("--force and --backup require --output or --output-dir".to_string());
+        }
+        if output.path.is_none() && (output.append || output.rotate_size.is_some()) {
+            return Err("--append and --rotate-size require --output".to_string());
+        }
+        if output.sign_key.is_some() && (output.path.is_none() || output.rotate_size.is_some()) {
+            return Err(
+                "--sign-key requires --output and cannot be combined with --rotate-size"
+                    .to_string(),
+            );
+        }
+        if let Some(checksum) = output.checksum {
+            if checksum == Checksum::Sidecar
+                && output.path.is_none()
+                && (output.dir.is_none() || !files)
+            {
+                return Err(
+                    "--checksum=sidecar requires --output, or --output-dir with --file".to_string(),
+                );
+            }
+            if output.append || output.rotate_size.is_some() {
+                return Err(
+                    "--checksum cannot be combined with --append or --rotate-size".to_string(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Arrangement {
+    /// Reads `arg` if it is the flag of how the rows are filtered and
+    /// ordered, taking its value from `rest` when it is not given as
+    /// `--name=value`. `dedup` is set from the columns once every flag is
+    /// read.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was such a flag, or a message if its value is invalid.
+    fn parse_flag<'a>(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        if let Some(value) = flag_value(arg, "--sort", rest)? {
+            self.sort = Some(parse_sort_key(value)?);
+        } else if let Some(value) = flag_value(arg, "--filter", rest)? {
+            self.filters.push(parse_row_filter(value)?);
+        } else if arg == "--desc" {
+            self.descending = true;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Checks `--sort`, `--desc` and `--filter` against each other and
+    /// `--stream`, which prints every row before the next is read.
+    fn check_flags(&self, stream: bool) -> Result<(), String> {
+        if self.descending && self.sort.is_none() {
+            return Err("--desc requires --sort".to_string());
+        }
+        if self.sort.is_some() && stream {
+            return Err("--sort cannot be combined with --stream".to_string());
+        }
+        if !self.filters.is_empty() && stream {
+            return Err("--filter cannot be combined with --stream".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The longest directory two `--glob` roots such as `data/a/` and `data/b/`
+/// both lie under, `data/` here.
+fn common_dir<'a>(first: &'a str, second: &str) -> &'a str {
+    let common = first
+        .bytes()
+        .zip(second.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    match first[..common].rfind('/') {
+        Some(slash) => &first[..=slash],
+        None => "",
+    }
+}
+
+/// The flags that choose how the inputs are converted or written, each with
+/// the others of them it supports. A flag missing from the list of another
+/// would be ignored by it or give a meaningless result, so the two cannot be
+/// given together. Every pair is listed both ways. `--base` stands for the
+/// target bases given with it, which the modes of a radix of their own,
+/// such as `--factoradic`, leave out.
+const SUPPORTED_FLAGS: &[(&str, &[&str])] = &[
+    ("--matrix-options", &["--precision", "--base"]),
+    (
+        "--summary-only",
+        &[
+            "--explain",
+            "--as-time",
+            "--scientific",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--balanced",
+            "--highlight-period",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--explain",
+        &[
+            "--summary-only",
+            "--expansion",
+            "--as-fixed",
+            "--bcd",
+            "--hex-float",
+            "--as-time",
+            "--gray",
+            "--scientific",
+            "--precision",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--highlight-period",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--expansion",
+        &[
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--base",
+        ],
+    ),
+    ("--as-fixed", &["--explain", "--precision"]),
+    ("--bcd", &["--explain", "--precision"]),
+    ("--factoradic", &["--exact-digits", "--precision"]),
+    ("--radices", &["--exact-digits", "--precision"]),
+    ("--duration", &["--exact-digits", "--precision"]),
+    ("--dms", &["--exact-digits", "--precision"]),
+    ("--zeckendorf", &["--exact-digits", "--precision"]),
+    ("--hex-float", &["--explain", "--precision"]),
+    (
+        "--as-time",
+        &["--summary-only", "--explain", "--precision", "--base"],
+    ),
+    (
+        "--gray",
+        &[
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--base",
+        ],
+    ),
+    (
+        "--scientific",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--base",
+        ],
+    ),
+    (
+        "--shortest",
+        &[
+            "--summary-only",
+            "--expansion",
+            "--gray",
+            "--scientific",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--highlight-period",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--exact-digits",
+        &[
+            "--summary-only",
+            "--expansion",
+            "--factoradic",
+            "--radices",
+            "--duration",
+            "--dms",
+            "--zeckendorf",
+            "--gray",
+            "--scientific",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--highlight-period",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--precision",
+        &[
+            "--matrix-options",
+            "--summary-only",
+            "--explain",
+            "--expansion",
+            "--as-fixed",
+            "--bcd",
+            "--factoradic",
+            "--radices",
+            "--duration",
+            "--dms",
+            "--zeckendorf",
+            "--hex-float",
+            "--as-time",
+            "--gray",
+            "--scientific",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--balanced",
+            "--highlight-period",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--alphabet",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--notation",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--base",
+        ],
+    ),
+    (
+        "--pad-digits",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--balanced",
+        &["--summary-only", "--precision", "--subscript", "--base"],
+    ),
+    (
+        "--highlight-period",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--subscript",
+            "--base",
+        ],
+    ),
+    (
+        "--subscript",
+        &[
+            "--summary-only",
+            "--explain",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--alphabet",
+            "--pad-digits",
+            "--balanced",
+            "--highlight-period",
+            "--base",
+        ],
+    ),
+    (
+        "--base",
+        &[
+            "--matrix-options",
+            "--summary-only",
+            "--explain",
+            "--expansion",
+            "--as-time",
+            "--gray",
+            "--scientific",
+            "--shortest",
+            "--exact-digits",
+            "--precision",
+            "--alphabet",
+            "--notation",
+            "--pad-digits",
+            "--balanced",
+            "--highlight-period",
+            "--subscript",
+        ],
+    ),
+];
+
+/// The flags that give the target bases, or a base of their own, alongside
+/// the positional base.
+const BASE_FLAGS: &[&str] = &[
+    "--base",
+    "--base-float",
+    "--pipe",
+    "--balanced",
+    "--as-time",
+];
+
+/// Whether a flag fixes the target bases, as one of `BASE_FLAGS` or as a
+/// mode of `SUPPORTED_FLAGS` that does not support `--base` because it
+/// writes in a radix of its own.
+///
+/// # Example
+///
+/// ```
+/// assert!(fixes_bases("--pipe"));
+/// assert!(fixes_bases("--factoradic"));
+/// assert!(!fixes_bases("--precision"));
+/// ```
+fn fixes_bases(flag: &str) -> bool {
+    BASE_FLAGS.contains(&flag)
+        || SUPPORTED_FLAGS
+            .iter()
+            .any(|&(other, supported)| other == flag && !supported.contains(&"--base"))
+}
+
+/// Checks that the flags of `SUPPORTED_FLAGS` that were given all support
+/// one another.
+///
+/// # Arguments
+///
+/// * `given` - The flags given, such as `--factoradic` and `--precision`.
+///
+/// # Returns
+///
+/// A message naming the first flag that does not support the others, and
+/// those of them it does not support.
+///
+/// # Example
+///
+/// ```
+/// assert!(check_supported_flags(&["--alphabet", "--subscript"]).is_ok());
+/// assert_eq!(
+///     check_supported_flags(&["--factoradic", "--alphabet", "--pad-digits"]),
+///     Err("--factoradic cannot be combined with --alphabet or --pad-digits".to_string())
+/// );
+/// ```
+fn check_supported_flags(given: &[&str]) -> Result<(), String> {
+    for &(flag, supported) in SUPPORTED_FLAGS {
+        if !given.contains(&flag) {
+            continue;
+        }
+        let unsupported: Vec<&str> = given
+            .iter()
+            .copied()
+            .filter(|&other| other != flag && !supported.contains(&other))
+            .collect();
+        if let Some((last, rest)) = unsupported.split_last() {
+            let others = match rest {
+                [] => last.to_string(),
+                _ => format!("{} or {}", rest.join(", "), last),
+            };
+            return Err(format!("{} cannot be combined with {}", flag, others));
+        }
+    }
+    Ok(())
+}
+
+/// The most decimal places `--decimal-places` takes, which is as many as the
+/// exact value of the smallest `f64` has.
+const MAX_DECIMAL_PLACES: usize = 1074;
+
+/// Splits an argument holding a comma-separated list of inputs, as pasted
+/// from a spreadsheet (`0.1,0.25,0.5` or `0.1, 0.25`), into its inputs.
+/// The argument is kept whole when a piece is no valid input, when the
+/// pipeline reads decimal commas, or when every piece is a plain run of
+/// digits, as `0,5`, `1,234` and `2,8,16` may be a decimal comma, a
+/// thousands separator or a list of bases rather than a list of inputs.
+fn split_list<'a>(pipeline: &Pipeline, arg: &'a str) -> Vec<&'a str> {
+    if pipeline.decimal_comma || !arg.contains(',') {
+        return vec![arg];
+    }
+    let pieces: Vec<&str> = arg
+        .split(',')
+        .map(str::trim)
+        .filter(|piece| !piece.is_empty())
+        .collect();
+    let listed = !pieces.is_empty()
+        && pieces.iter().all(|piece| is_valid_input(pipeline, piece))
+        && !pieces
+            .iter()
+            .all(|piece| piece.bytes().all(|byte| byte.is_ascii_digit()));
+    if listed {
+        pieces
+    } else {
+        vec![arg]
+    }
+}
+
+/// Whether a positional argument is a whole number, which makes it the
+/// target base when it comes first.
+fn is_whole_number(arg: &str) -> bool {
+    let digits = arg.strip_prefix('+').unwrap_or(arg);
+    !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Parses the value of `--decimal-places`: a number of places, or
+/// `shortest` for the fewest that read back as the same `f64`.
+fn parse_decimal_places(value: &str) -> Result<Option<usize>, String> {
+    if value == "shortest" {
+        return Ok(None);
+    }
+    match value.parse::<usize>() {
+        Ok(places) if places <= MAX_DECIMAL_PLACES => Ok(Some(places)),
+        Ok(_) => Err(format!(
+            "--decimal-places is at most {}, the decimal places of the smallest f64",
+            MAX_DECIMAL_PLACES
+        )),
+        Err(_) => Err(format!(
+            "invalid --decimal-places `{}`, expected a number or shortest",
+            value
+        )),
+    }
+}
+
+/// A decoded input and the exact fraction it was written as, if any.
+pub type DecodedInput = (f64, Option<Fraction>);
+
+/// How many times an input was given, its digit limit and its label, which
+/// go along with it when the rows are sorted.
+pub type InputRow = (usize, Option<u32>, Option<String>);
+
+/// The inputs of a batch in the columns of [`Options`]: their values, the
+/// fractions they were written as, their counts, digit limits and labels.
+pub type InputColumns = (
+    Vec<f64>,
+    Vec<Option<Fraction>>,
+    Vec<usize>,
+    Vec<Option<u32>>,
+    Vec<Option<String>>,
+);
+
+/// How the decoded inputs of a run, or of every `--file` of it, are
+/// collapsed, filtered and ordered before they are converted.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arrangement {
+    /// Whether repeated inputs are collapsed into one row with their count,
+    /// with `--dedup`.
+    pub dedup: bool,
+    /// The rows to keep, from `--filter`.
+    pub filters: Vec<RowFilter>,
+    /// What the rows are ordered by, from `--sort`.
+    pub sort: Option<SortKey>,
+    /// Whether `--desc` puts the largest first.
+    pub descending: bool,
+}
+
+/// Collapses, filters and orders decoded inputs as an arrangement asks,
+/// keeping the count, digit limit and label of every input with it.
+///
+/// # Returns
+///
+/// The rows in the order they are converted, or an error if inputs with a
+/// label or a digit limit of their own are to be collapsed.
+pub fn arrange_inputs(
+    inputs: DecodedInputs,
+    arrangement: &Arrangement,
+    pipeline: &Pipeline,
+) -> Result<Vec<(DecodedInput, InputRow)>, String> {
+    let DecodedInputs {
+        mut decoded,
+        digit_limits,
+        labels,
+        ..
+    } = inputs;
+    let counts = if arrangement.dedup {
+        if labels.iter().any(Option::is_some) {
+            return Err(
+                "labelled inputs, such as tenth=0.1, cannot be combined with --dedup".to_string(),
+            );
+        }
+        if digit_limits.iter().any(Option::is_some) {
+            return Err(
+                "inputs with their own digit limit, such as 0.1:20, cannot be combined with \
+                 --dedup"
+                    .to_string(),
+            );
+        }
+        let (distinct, counts) = dedup_inputs(decoded);
+        decoded = distinct;
+        counts
+    } else {
+        vec![1; decoded.len()]
+    };
+    let mut rows: Vec<(DecodedInput, InputRow)> = decoded
+        .into_iter()
+        .zip(
+            counts
+                .into_iter()
+                .zip(digit_limits)
+                .zip(labels)
+                .map(|((count, digit_limit), label)| (count, digit_limit, label)),
+        )
+        .collect();
+    filter_inputs(&mut rows, &arrangement.filters, pipeline);
+    if let Some(key) = arrangement.sort {
+        sort_inputs(&mut rows, key, arrangement.descending, pipeline);
+    }
+    Ok(rows)
+}
+
+/// Splits arranged rows into the columns of [`Options`].
+pub fn unzip_rows(rows: Vec<(DecodedInput, InputRow)>) -> InputColumns {
+    let mut columns = InputColumns::default();
+    for ((number, fraction), (count, digit_limit, label)) in rows {
+        columns.0.push(number);
+        columns.1.push(fraction);
+        columns.2.push(count);
+        columns.3.push(digit_limit);
+        columns.4.push(label);
+    }
+    columns
+}
+
+/// The inputs of a batch as [`decode_inputs`] decodes them, in the order
+/// given.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecodedInputs {
+    /// The decoded inputs with the exact fractions they were written as.
+    pub decoded: Vec<DecodedInput>,
+    /// For every decoded input, the digit limit it was given with, as in
+    /// `0.1:20`, if any.
+    pub digit_limits: Vec<Option<u32>>,
+    /// For every decoded input, the label it was given with, as in
+    /// `tenth=0.1`, if any.
+    pub labels: Vec<Option<String>>,
+    /// The inputs that could not be decoded.
+    pub invalid: Vec<String>,
+}
+
+/// Decodes every input of a batch with the decode stage of a pipeline,
+/// after splitting off the label of any written as `tenth=0.1` and the
+/// digit limit of any written as `0.1:20`.
+pub fn decode_inputs<'a>(
+    pipeline: &Pipeline,
+    args: impl IntoIterator<Item = &'a str>,
+) -> DecodedInputs {
+    let mut inputs = DecodedInputs::default();
+    for arg in args {
+        let (label, input) = split_label(arg);
+        let input = split_digit_limit(input)
+            .and_then(|(input, digit_limit)| Some((pipeline.decode_input(input)?, digit_limit)));
+        match input {
+            Some((input, digit_limit)) => {
+                inputs.decoded.push(input);
+                inputs.digit_limits.push(digit_limit);
+                inputs.labels.push(label.map(String::from));
+            }
+            None => inputs.invalid.push(arg.to_string()),
+        }
+    }
+    inputs
+}
+
+/// The note a run writes to standard error naming the bases it converts
+/// to, and whether they are the default because no base was given, so that
+/// a mistyped base such as `2x` is not silently read as an input.
+///
+/// # Example
+///
+/// ```
+/// let options = parse_args(&["0.5".to_string()]).unwrap();
+/// assert_eq!(
+///     base_note(&options),
+///     "note: converting to base 2, the default, as no target base was given"
+/// );
+/// ```
+pub fn base_note(options: &Options) -> String {
+    let bases: Vec<String> = options
+        .pipeline
+        .target_bases
+        .iter()
+        .map(|&target_base| format_base(target_base))
+        .collect();
+    format!(
+        "note: converting to base{} {}{}",
+        if bases.len() == 1 { "" } else { "s" },
+        bases.join(", "),
+        if options.bases_defaulted {
+            ", the default, as no target base was given"
+        } else {
+            ""
+        }
+    )
+}
+
+/// Whether a text is an input `decode_inputs` decodes, with any label and
+/// digit limit.
+pub fn is_valid_input(pipeline: &Pipeline, text: &str) -> bool {
+    decode_inputs(pipeline, [text]).invalid.is_empty()
+}
+
+/// Reads the text of an `--input` file, or standard input for `-`.
+fn read_inputs(path: &str) -> Result<String, String> {
+    if path == STDIN_PATH {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("cannot read standard input: {}", error))?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path)
+            .map_err(|error| format!("cannot read input file `{}`: {}", path, error))
+    }
+}
+
+/// Parses a comma separated list of β-expansion rules such as `greedy,lazy`.
+fn parse_rule_list(value: &str) -> Result<Vec<BetaRule>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|name| {
+            BetaRule::from_name(name).ok_or_else(|| format!("unknown expansion `{}`", name))
+        })
+        .collect()
+}
+
+/// Parses a comma separated list of digit limits such as `8,16,32`.
+fn parse_precision_list(value: &str) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|digits| {
+            digits
+                .parse::<u32>()
+                .map_err(|_| format!("invalid precision `{}`", digits))
+        })
+        .collect()
+}
+
+/// Reads the value of a flag written either as `--name value` or `--name=value`.
+///
+/// # Arguments
+///
+/// * `arg` - The argument currently being parsed.
+/// * `name` - The flag name, including the leading `--`.
+/// * `rest` - The remaining arguments, from which a separate value is taken.
+///
+/// # Returns
+///
+/// The flag's value if `arg` is the flag, `None` if it is some other
+/// argument, or an error if the flag is missing its value.
+pub fn flag_value<'a>(
+    arg: &'a str,
+    name: &str,
+    rest: &mut impl Iterator<Item = &'a String>,
+) -> Result<Option<&'a str>, String> {
+    match arg.strip_prefix(name) {
+        Some("") => rest
+            .next()
+            .map(|value| Some(value.as_str()))
+            .ok_or_else(|| format!("{} requires a value", name)),
+        Some(value) => Ok(value.strip_prefix('=')),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+    use crate::balanced::expand_balanced;
+    use crate::beta::{expand_beta, GOLDEN_RATIO};
+    use crate::stream::Position;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    /// Parses the arguments without any default configuration file or
+    /// environment, so that the tests do not depend on those of the user
+    /// running them.
+    fn parse_args(args: &[String]) -> Result<Options, String> {
+        parse_args_with_config(args, None, &Config::default())
+    }
+
+    #[test]
+    fn test_positional_base_and_numbers() {
+        let options = parse_args(&args("8 0.5 abc 0.25")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![8.0]));
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.25]));
+
+        let options = parse_args(&args("0.5 0.75")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0]));
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.75]));
+    }
+
+    #[test]
+    fn test_positional_base_must_be_a_base() {
+        assert!(parse_args(&args("0 0.5")).is_err());
+        assert!(parse_args(&args("1 0.5")).is_err());
+        assert!(parse_args(&args("5000000000 0.5")).is_err());
+        assert_that!(
+            parse_args(&args("+16 0.5")).unwrap().pipeline.target_bases,
+            equal_to(vec![16.0])
+        );
+    }
+
+    #[test]
+    fn test_comma_separated_lists() {
+        let options = parse_args(&args("0.1,0.25,0.5 1/3,")).unwrap();
+        assert_that!(
+            options.f64_numbers,
+            equal_to(vec![0.1, 0.25, 0.5, 1.0 / 3.0])
+        );
+        let options = parse_args(&args("16 0.5,tenth=0.1")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert_that!(
+            options.labels,
+            equal_to(vec![None, Some("tenth".to_string())])
+        );
+
+        // Commas that may mean something else keep the argument whole.
+        for kept in ["0,5", "1,234", "2,8,16", "0.5,junk"] {
+            let options = parse_args(&args(&format!("0.25 {}", kept))).unwrap();
+            assert_that!(options.invalid_inputs, equal_to(vec![kept.to_string()]));
+        }
+        let options = parse_args(&args("--decimal-comma 0,5,0.25")).unwrap();
+        assert_that!(
+            options.invalid_inputs,
+            equal_to(vec!["0,5,0.25".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_require_base_flag() {
+        let options = parse_args(&args("--require-base 16 0.5")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert!(!options.bases_defaulted);
+        let options = parse_args(&args("--strict -2 0.5")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![-2.0]));
+        assert!(parse_args(&args("--strict 2x 0.5")).is_err());
+        assert!(parse_args(&args("--require-base 0.5")).is_err());
+        assert!(parse_args(&args("--require-base")).is_err());
+        assert!(parse_args(&args("--strict --base 8 0.5")).is_ok());
+        assert!(parse_args(&args("--strict --balanced 0.5")).is_ok());
+
+        // Without it a mistyped base is an input, converted in the default base.
+        let options = parse_args(&args("2x 0.5")).unwrap();
+        assert!(options.bases_defaulted);
+        assert_that!(options.invalid_inputs, equal_to(vec!["2x".to_string()]));
+    }
+
+    #[test]
+    fn test_base_note() {
+        assert_that!(
+            base_note(&parse_args(&args("0.5")).unwrap()),
+            equal_to(
+                "note: converting to base 2, the default, as no target base was given".to_string()
+            )
+        );
+        assert_that!(
+            base_note(&parse_args(&args("--base 2,-2 0.5")).unwrap()),
+            equal_to("note: converting to bases 2, -2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_explanation_matches_its_result() {
+        let pipeline = parse_args(&args("--explain --precision 20 10 0.1"))
+            .unwrap()
+            .pipeline;
+        let digits: Vec<u32> = crate::explain::steps(0.1, 10, 20)
+            .iter()
+            .map(|step| step.digit)
+            .collect();
+        assert_that!(pipeline.expand_to(0.1, 10.0).digits, equal_to(digits));
+
+        let expand = |line| {
+            parse_args(&args(line))
+                .unwrap()
+                .pipeline
+                .expand_to(2.75, 2.0)
+        };
+        assert_that!(expand("--explain 2 2.75").to_string(), equal_to("1;0.1;1;"));
+        assert_that!(
+            expand("--backend f64 2 2.75").to_string(),
+            equal_to("1;0.1;1;")
+        );
+        let backend = |line| parse_args(&args(line)).unwrap().pipeline.backend;
+        assert_that!(
+            backend("--explain --backend bignum 10 0.1"),
+            equal_to(Some(Backend::Bignum))
+        );
+        assert_that!(backend("--explain --exact 10 0.1"), equal_to(None));
+    }
+
+    #[test]
+    fn test_end_of_options() {
+        let options = parse_args(&args("2 -- -0 --quiet")).unwrap();
+        assert!(options.f64_numbers[0].is_sign_negative());
+        assert_that!(options.f64_numbers, equal_to(vec![-0.0]));
+        assert_that!(
+            options.invalid_inputs,
+            equal_to(vec!["--quiet".to_string()])
+        );
+        assert!(!options.quiet);
+        assert!(parse_args(&args("2 -0 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_non_finite_inputs() {
+        for input in ["inf", "-inf", "nan", "NaN", "-infinity"] {
+            let error = parse_args(&args(&format!("2 0.5 {}", input))).unwrap_err();
+            assert!(error.contains("not a finite number"), "{}", error);
+        }
+        // Too large for an `f64` is still an input to skip.
+        let options = parse_args(&args("2 1e400 0.5")).unwrap();
+        assert_that!(options.invalid_inputs, equal_to(vec!["1e400".to_string()]));
+    }
+
+    #[test]
+    fn test_adversarial_arguments_do_not_panic() {
+        let long = "9".repeat(400);
+        let parentheses = "(".repeat(50000);
+        let signs = format!("{}1", "-".repeat(50000));
+        let nested = format!("{}1{}", "(".repeat(300), ")".repeat(300));
+        let inputs = [
+            "",
+            "-",
+            "+",
+            ".",
+            "e",
+            "1e",
+            "0x",
+            "0b",
+            "1e999999999",
+            "-1e999999999",
+            "1e-999999",
+            "inf",
+            "-inf",
+            "nan",
+            "-0",
+            "1/0",
+            "0/0",
+            "-7/3",
+            "0x1p-1074",
+            "5e-324",
+            "-1.7976931348623157e308",
+            "18446744073709551616",
+            "-9223372036854775808",
+            "0.1₀",
+            "0.1₂",
+            "a=",
+            "=0.5",
+            "0.5:",
+            "0.5:0",
+            ":",
+            "½",
+            "٣",
+            "🦀",
+            "0.(3)",
+            "0.1(",
+            "1,5",
+            &long,
+            &parentheses,
+            &signs,
+            &nested,
+        ];
+        let modes = [
+            "",
+            "--base -2,-10",
+            "--balanced",
+            "--base-float phi,1.5 --expansion greedy,lazy",
+            "--exact",
+            "--decimal",
+            "--base 2^32",
+            "--float-width 32",
+        ];
+        for mode in modes {
+            for input in inputs {
+                let mut line = args(mode);
+                line.extend([input.to_string(), "0.5".to_string()]);
+                let Ok(options) = parse_args(&line) else {
+                    continue;
+                };
+                for (value, fraction) in options.f64_numbers.iter().zip(&options.fractions) {
+                    for &target_base in &options.pipeline.target_bases {
+                        options
+                            .pipeline
+                            .expand_input(*value, fraction.as_ref(), target_base)
+                            .to_string();
+                        for &rule in &options.representation.beta_rules {
+                            expand_beta(*value, target_base, rule, options.pipeline.digits)
+                                .to_string();
+                        }
+                    }
+                    if options.representation.balanced {
+                        expand_balanced(*value, options.pipeline.digits).to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipe_expression() {
+        let options = parse_args(&args("--pipe from=q1.15|to=base16 16384 0x2000")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.25]));
+
+        assert!(parse_args(&args("--pipe")).is_err());
+        assert!(parse_args(&args("--pipe=to=base1 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_multiple_bases() {
+        let options = parse_args(&args("--base 2,8 --base=16 3 0.5")).unwrap();
+        assert_that!(
+            options.pipeline.target_bases,
+            equal_to(vec![2.0, 8.0, 16.0])
+        );
+        assert_that!(options.f64_numbers, equal_to(vec![3.0, 0.5]));
+
+        let options = parse_args(&args("--pipe to=base8 --base 2,16 0.5")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0, 16.0]));
+
+        let options = parse_args(&args("--base -2,2 -0.5")).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![-2.0, 2.0]));
+        assert_that!(options.f64_numbers, equal_to(vec![-0.5]));
+
+        assert!(parse_args(&args("--base 2,x 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_precisions_and_matrix() {
+        let options = parse_args(&args("--precision 4 0.1")).unwrap();
+        assert_that!(options.pipeline.digits, equal_to(4));
+        assert_that!(options.precisions, equal_to(vec![4]));
+
+        let options = parse_args(&args("--matrix-options --precision 4,16 0.1")).unwrap();
+        assert!(options.matrix_options);
+        assert_that!(options.precisions, equal_to(vec![4, 16]));
+        let options = parse_args(&args("--matrix-options --format csv 0.1")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Csv));
+        assert!(parse_args(&args("--matrix-options --format yaml 0.1")).is_err());
+        #[cfg(feature = "serde")]
+        assert!(parse_args(&args("--matrix-options --format jsonl 0.1")).is_ok());
+
+        assert!(parse_args(&args("--precision 4,16 0.1")).is_err());
+        assert!(parse_args(&args("--precision many 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_error_column_flag() {
+        let options = parse_args(&args("--error 16 0.1")).unwrap();
+        assert!(options.columns.round_trip_error);
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert!(!parse_args(&args("0.1")).unwrap().columns.round_trip_error);
+        assert!(
+            parse_args(&args("--terminates 1/3"))
+                .unwrap()
+                .columns
+                .terminates
+        );
+        assert!(parse_args(&args("--period 1/7")).unwrap().columns.period);
+        assert!(
+            parse_args(&args("--fraction 0.125"))
+                .unwrap()
+                .columns
+                .fraction
+        );
+        assert!(
+            parse_args(&args("--exact-value 0.1"))
+                .unwrap()
+                .columns
+                .exact_value
+        );
+        assert!(
+            parse_args(&args("--digits-value 0.1"))
+                .unwrap()
+                .columns
+                .digits_value
+        );
+        assert!(parse_args(&args("--digits-value --format csv 0.1")).is_err());
+        assert!(
+            parse_args(&args("--sparkline 0.1"))
+                .unwrap()
+                .columns
+                .sparkline
+        );
+        assert!(parse_args(&args("--sparkline --format json 0.1")).is_err());
+        assert_that!(
+            parse_args(&args("--bit-groups byte 0.1"))
+                .unwrap()
+                .columns
+                .bit_groups,
+            equal_to(Some(8))
+        );
+        assert!(parse_args(&args("--bit-groups 16 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_mode_flags() {
+        let options = parse_args(&args("--explain --summary-only 0.1")).unwrap();
+        assert!(options.explain);
+        assert!(options.summary_only);
+        assert_that!(options.f64_numbers, equal_to(vec![0.1]));
+    }
+
+    #[test]
+    fn test_balanced_defaults_to_base_3() {
+        let options = parse_args(&args("--balanced 0.5")).unwrap();
+        assert!(options.representation.balanced);
+        assert_that!(options.pipeline.target_bases, equal_to(vec![3.0]));
+
+        assert!(parse_args(&args("--balanced 3 0.5")).is_ok());
+        assert!(parse_args(&args("--balanced 2 0.5")).is_err());
+        assert!(parse_args(&args("--balanced --base 3,9 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_fractions_and_backend() {
+        let options = parse_args(&args("10 1/3 0.5")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![1.0 / 3.0, 0.5]));
+        assert_that!(
+            options.fractions,
+            equal_to(vec![Fraction::parse("1/3"), None])
+        );
+        assert_that!(options.pipeline.backend, equal_to(None));
+
+        let options = parse_args(&args("--backend f64 --pipe backend=bignum 0.5")).unwrap();
+        assert_that!(options.pipeline.backend, equal_to(Some(Backend::F64)));
+        assert!(parse_args(&args("--backend abacus 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_digit_limits() {
+        let options = parse_args(&args("--sort value 0.5:20 0.25 1/3:4")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.25, 1.0 / 3.0, 0.5]));
+        assert_that!(
+            options.digit_limits,
+            equal_to(vec![None, Some(4), Some(20)])
+        );
+        let options = parse_args(&args("0.5 0.1:x")).unwrap();
+        assert_that!(options.digit_limits, equal_to(vec![None]));
+        assert!(parse_args(&args("--dedup 0.5:20 0.5")).is_err());
+        assert!(parse_args(&args("--explain 0.5:20")).is_err());
+    }
+
+    #[test]
+    fn test_labelled_inputs() {
+        let options = parse_args(&args("--sort value half=0.5:4 0.25 third=1/3")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.25, 1.0 / 3.0, 0.5]));
+        assert_that!(
+            options.labels,
+            equal_to(vec![
+                None,
+                Some("third".to_string()),
+                Some("half".to_string())
+            ])
+        );
+        assert_that!(options.digit_limits, equal_to(vec![None, None, Some(4)]));
+        assert!(options.columns.label);
+        assert!(!parse_args(&args("0.5")).unwrap().columns.label);
+        assert!(parse_args(&args("--dedup half=0.5 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_hex_float_flag() {
+        let options = parse_args(&args("--hex-float 0.1")).unwrap();
+        assert!(options.representation.hex_float);
+        assert_that!(options.f64_numbers, equal_to(vec![0.1]));
+        assert!(parse_args(&args("--hex-float --summary-only 0.1")).is_err());
+        assert!(parse_args(&args("--hex-float --as-time 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_as_fixed_flag() {
+        let options = parse_args(&args("--as-fixed q1.15 -0.5")).unwrap();
+        assert_that!(options.representation.as_fixed, equal_to(Some((1, 15))));
+        assert_that!(
+            parse_args(&args("--as-fixed=Q8.8 1.0"))
+                .unwrap()
+                .representation
+                .as_fixed,
+            equal_to(Some((8, 8)))
+        );
+        assert!(parse_args(&args("--as-fixed q40.40 0.5")).is_err());
+        assert!(parse_args(&args("--as-fixed q1.15 --hex-float 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_gray_flag() {
+        let options = parse_args(&args("--gray 0.8125")).unwrap();
+        assert!(options.representation.gray);
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0]));
+        assert!(parse_args(&args("--gray 16 0.5")).is_err());
+        assert!(parse_args(&args("--gray --summary-only 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_shortest_flag() {
+        let options = parse_args(&args("--shortest --base 2,10 0.1")).unwrap();
+        assert!(options.pipeline.shortest);
+        assert!(
+            parse_args(&args("--pipe digits=shortest|to=3 0.5"))
+                .unwrap()
+                .pipeline
+                .shortest
+        );
+        assert!(parse_args(&args("--shortest --precision 4 0.1")).is_err());
+        assert!(parse_args(&args("--shortest --base -2 0.1")).is_err());
+        assert!(parse_args(&args("--shortest --balanced 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_null_flag() {
+        let options = parse_args(&args("--format plain -0 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Plain { null: true }));
+        assert_that!(options.formats, equal_to(vec![options.format]));
+        assert!(parse_args(&args("--null --format plain 0.5")).is_ok());
+        assert!(parse_args(&args("--null 0.5")).is_err());
+        assert!(parse_args(&args("--format csv -0 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_highlight_period_flag() {
+        let options = parse_args(&args("--highlight-period=underline 1/7")).unwrap();
+        assert_that!(
+            options.representation.highlight_period,
+            equal_to(Some(PeriodStyle::Underline))
+        );
+        let options = parse_args(&args("--highlight-period --output out.md 1/7")).unwrap();
+        assert_that!(
+            options.representation.highlight_period,
+            equal_to(Some(PeriodStyle::Parentheses))
+        );
+        assert_that!(
+            parse_args(&args("1/7"))
+                .unwrap()
+                .representation
+                .highlight_period,
+            equal_to(None)
+        );
+        assert!(parse_args(&args("--highlight-period=blink 1/7")).is_err());
+        assert!(parse_args(&args("--highlight-period --hex-float 0.5")).is_err());
+        assert!(parse_args(&args("--highlight-period --format csv 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_footer_flag() {
+        assert!(parse_args(&args("--footer 0.5")).unwrap().footer);
+        assert!(!parse_args(&args("0.5")).unwrap().footer);
+        assert!(parse_args(&args("--footer --summary-only 0.5")).is_err());
+        assert!(parse_args(&args("--footer --format csv 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_exact_digits_flag() {
+        let options = parse_args(&args("--exact-digits 5/1024")).unwrap();
+        assert!(options.pipeline.exact_digits && options.columns.digits_needed);
+        assert!(
+            parse_args(&args("--pipe digits=exact|to=10 0.5"))
+                .unwrap()
+                .columns
+                .digits_needed
+        );
+        assert!(parse_args(&args("--exact-digits --shortest 0.1")).is_err());
+        assert!(parse_args(&args("--exact-digits --precision 4 0.1")).is_err());
+        assert!(parse_args(&args("--exact-digits --format csv 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_scientific_flag() {
+        let options = parse_args(&args("--scientific --base 2,16 0.001")).unwrap();
+        assert!(options.representation.scientific);
+        assert!(parse_args(&args("--scientific --base -2 0.5")).is_err());
+        assert!(parse_args(&args("--scientific --base-float 1.5 0.5")).is_err());
+        assert!(parse_args(&args("--scientific --bcd 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_pad_digits_flag() {
+        let options = parse_args(&args("--pad-digits 3 60 0.5")).unwrap();
+        assert_that!(
+            options.representation.pad_digits,
+            equal_to(Some(PadWidth::Fixed(3)))
+        );
+        let options = parse_args(&args("--pad-digits=auto 60 0.5")).unwrap();
+        assert_that!(
+            options.representation.pad_digits,
+            equal_to(Some(PadWidth::Auto))
+        );
+        assert!(parse_args(&args("--pad-digits 0 60 0.5")).is_err());
+        assert!(parse_args(&args("--pad-digits 2 --balanced 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_alphabet_flag() {
+        let options = parse_args(&args("--alphabet ACGT 4 0.75")).unwrap();
+        assert_that!(
+            options
+                .representation
+                .alphabet
+                .map(|alphabet| alphabet.symbols.len()),
+            equal_to(Some(4))
+        );
+        assert!(parse_args(&args("--alphabet ACGT 16 0.75")).is_err());
+        assert!(parse_args(&args("--alphabet ACGT --base-float 1.5 0.75")).is_err());
+        assert!(parse_args(&args("--alphabet ACGT --bcd 0.75")).is_err());
+        assert!(parse_args(&args("--alphabet-file /nonexistent 0.75")).is_err());
+        let options = parse_args(&args("--digit-script arabic-indic 16 0.75")).unwrap();
+        assert_that!(
+            options
+                .representation
+                .alphabet
+                .map(|alphabet| alphabet.symbols[12].clone()),
+            equal_to(Some("C".to_string()))
+        );
+        assert!(parse_args(&args("--digit-script klingon 0.75")).is_err());
+    }
+
+    #[test]
+    fn test_subscript_flag() {
+        assert!(
+            parse_args(&args("--subscript --base 2,16 0.75"))
+                .unwrap()
+                .representation
+                .subscript
+        );
+        assert!(parse_args(&args("--subscript --balanced 0.75")).is_ok());
+        assert!(parse_args(&args("--subscript --base-float 1.5 0.75")).is_err());
+        assert!(parse_args(&args("--subscript --hex-float 0.75")).is_err());
+    }
+
+    #[test]
+    fn test_input_and_jobs_flags() {
+        let path = std::env::temp_dir().join(format!("inputs-{}", std::process::id()));
+        fs::write(&path, "0.25\n0.5 1/3\n").unwrap();
+        let line = format!("--jobs 4 --input {} 16 0.75", path.display());
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(
+            options.f64_numbers,
+            equal_to(vec![0.75, 0.25, 0.5, 1.0 / 3.0])
+        );
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert_that!(options.jobs, equal_to(Some(4)));
+        fs::remove_file(&path).unwrap();
+
+        assert!(parse_args(&args("--jobs 0 0.5")).is_err());
+        assert!(parse_args(&args("--input /nonexistent/inputs 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_script_flag() {
+        let path = std::env::temp_dir().join(format!("script-{}", std::process::id()));
+        fs::write(
+            &path,
+            "const COLUMNS = [\"ones\"];\ncolumns.ones = digits.len();\n",
+        )
+        .unwrap();
+        let line = format!("--script {} 0.75", path.display());
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(
+            options
+                .columns
+                .script
+                .map(|script| script.columns().to_vec()),
+            equal_to(Some(vec!["ones".to_string()]))
+        );
+        assert!(parse_args(&args(&format!("{} --format json", line))).is_err());
+        fs::write(&path, "columns.ones = digit.len();\n").unwrap();
+        let error = parse_args(&args(&line)).unwrap_err();
+        assert!(error.contains("script: Undefined variable: digit"));
+        fs::remove_file(&path).unwrap();
+
+        assert!(parse_args(&args("--script /nonexistent/script 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_exact_flag() {
+        let options = parse_args(&args("--exact --base 3,10 0.1")).unwrap();
+        assert!(options.pipeline.exact);
+        assert_that!(
+            options.fractions[0]
+                .as_ref()
+                .map(|fraction| fraction.numerator.to_string()),
+            equal_to(Some("1".to_string()))
+        );
+        assert!(parse_args(&args("--exact --backend long-division 0.1")).is_ok());
+        assert!(parse_args(&args("--exact --backend f64 0.1")).is_err());
+        assert!(parse_args(&args("--exact --base-float 1.5 0.1")).is_err());
+        assert!(parse_args(&args("--exact --base -2 0.1")).is_err());
+        assert!(parse_args(&args("--exact --shortest 0.1")).is_err());
+        assert!(parse_args(&args("--exact --pipe from=q1.15 100")).is_err());
+    }
+
+    #[test]
+    fn test_filter_flag() {
+        let options = parse_args(&args("--filter repeating 0.5 0.1 1/3 0.25")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.1, 1.0 / 3.0]));
+        assert_that!(options.fractions.len(), equal_to(2));
+        let options = parse_args(&args(
+            "--base 2,10 --filter terminating --filter error>0.001 0.1 1/3 0.25",
+        ))
+        .unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.1]));
+        assert!(parse_args(&args("--filter exact 0.5")).is_err());
+        assert!(parse_args(&args("--filter error> 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_columns_flag() {
+        let options = parse_args(&args("--columns repr,error 0.5")).unwrap();
+        assert!(options.columns.hide_input);
+        assert!(!options.columns.hide_expansions);
+        assert!(options.columns.round_trip_error);
+        let options = parse_args(&args("--columns truncated,decimal --format csv 0.5")).unwrap();
+        assert_that!(
+            options.selected_columns,
+            equal_to(Some(vec![Column::Truncated, Column::Decimal]))
+        );
+        assert!(parse_args(&args("--columns digits 0.5")).is_err());
+        assert!(parse_args(&args("--columns repr --format plain 0.5")).is_err());
+        assert!(parse_args(&args("--columns repr --matrix-options 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_decimal_places_flag() {
+        let places = |line| parse_args(&args(line)).map(|options| options.columns.decimal_places);
+        assert_that!(places("0.5"), equal_to(Ok(None)));
+        assert_that!(places("--decimal-places 3 0.5"), equal_to(Ok(Some(3))));
+        assert_that!(places("--decimal-places shortest 0.5"), equal_to(Ok(None)));
+        assert!(places("--decimal-places 1075 0.5").is_err());
+        assert!(places("--decimal-places few 0.5").is_err());
+    }
+
+    #[test]
+    fn test_float_width_flag() {
+        let options = parse_args(&args("--float-width 32 0.1 0.5")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![f64::from(0.1f32), 0.5]));
+        let options = parse_args(&args("--float-width 64 0.1")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.1]));
+        assert!(parse_args(&args("--float-width 16 0.1")).is_err());
+        assert!(parse_args(&args("--float-width 32 --exact 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_interpret_flag() {
+        let options = parse_args(&args("--interpret both 0.1 half=0.5")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.1, 0.1, 0.5, 0.5]));
+        assert_that!(
+            options
+                .fractions
+                .iter()
+                .map(Option::is_some)
+                .collect::<Vec<_>>(),
+            equal_to(vec![true, false, true, false])
+        );
+        assert_that!(
+            options.labels,
+            equal_to(vec![
+                Some("literal".to_string()),
+                Some("float".to_string()),
+                Some("half (literal)".to_string()),
+                Some("half (float)".to_string()),
+            ])
+        );
+        assert!(options.columns.label);
+        let options = parse_args(&args("--interpret literal 0.1")).unwrap();
+        assert!(options.pipeline.exact);
+        let options = parse_args(&args("--interpret float 1/3")).unwrap();
+        assert_that!(options.fractions, equal_to(vec![None]));
+        assert!(parse_args(&args("--interpret float --exact 0.1")).is_err());
+        assert!(parse_args(&args("--interpret both --stream 0.1")).is_err());
+        assert!(parse_args(&args("--interpret typed 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_paging_flag() {
+        assert_that!(
+            parse_args(&args("--paging auto 0.5")).unwrap().paging,
+            equal_to(Paging::Auto)
+        );
+        assert_that!(
+            parse_args(&args("0.5")).unwrap().paging,
+            equal_to(Paging::Never)
+        );
+        assert!(parse_args(&args("--paging sometimes 0.5")).is_err());
+        assert!(parse_args(&args("--paging always --output out.txt 0.5")).is_err());
+        assert!(parse_args(&args("--paging never --output out.txt 0.5")).is_ok());
+    }
+
+    #[test]
+    fn test_decimal_flag() {
+        let options = parse_args(&args("--decimal --base 2 19.99 1/3")).unwrap();
+        assert!(options.pipeline.decimal && !options.pipeline.exact);
+        assert_that!(
+            options.fractions[0]
+                .as_ref()
+                .map(|fraction| fraction.denominator.to_string()),
+            equal_to(Some("100".to_string()))
+        );
+        assert_that!(options.invalid_inputs, equal_to(vec!["1/3".to_string()]));
+        assert_that!(
+            parse_args(&args("--decimal --shortest 0.1")).err(),
+            equal_to(Some(
+                "--decimal converts to positive integer bases by long division, and cannot be \
+                 combined with --shortest, --balanced or another --backend"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_table_style() {
+        let options = parse_args(&args("--table-style unicode 0.5")).unwrap();
+        assert_that!(options.styles.table, equal_to(TableStyle::Unicode));
+        let options = parse_args(&args("0.5")).unwrap();
+        assert_that!(options.styles.table, equal_to(TableStyle::Markdown));
+        let error = parse_args(&args("--table-style fancy 0.5")).unwrap_err();
+        assert!(error.contains("markdown, ascii, unicode, borderless"));
+    }
+
+    #[test]
+    fn test_checkpoint_flags() {
+        let path = std::env::temp_dir().join(format!("resumed-{}", std::process::id()));
+        let checkpoint = Checkpoint {
+            input: 1,
+            path: "b.txt".to_string(),
+            position: Position {
+                line: 3,
+                offset: 20,
+            },
+        };
+        checkpoint.save(&path.to_string_lossy()).unwrap();
+        let line = format!(
+            "--stream --input a.txt --input b.txt --checkpoint {} --resume --output out.md",
+            path.display()
+        );
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(options.resume_from, equal_to(Some(checkpoint)));
+        assert!(options.output.append);
+        let other = format!(
+            "--stream --input a.txt --checkpoint {} --resume",
+            path.display()
+        );
+        assert!(parse_args(&args(&other)).is_err());
+        fs::remove_file(&path).unwrap();
+
+        let fresh = parse_args(&args("--stream --input a.txt --checkpoint ck")).unwrap();
+        assert_that!(fresh.checkpoint, equal_to(Some("ck".to_string())));
+        assert_that!(fresh.resume_from, equal_to(None));
+        assert!(parse_args(&args("--input a.txt --checkpoint ck")).is_err());
+        assert!(parse_args(&args("--stream --input a.txt --checkpoint ck 0.5")).is_err());
+        assert!(parse_args(&args("--stream --input a.txt --resume")).is_err());
+        assert!(parse_args(&args(
+            "--stream --input a.txt --checkpoint /nonexistent/ck --resume"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_mapped_input() {
+        let path = std::env::temp_dir().join(format!("mapped-{}", std::process::id()));
+        fs::write(&path, "0.25\n0.5 junk 1/3\n").unwrap();
+        let line = format!("--mmap --input {} 0.75", path.display());
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(
+            options.f64_numbers,
+            equal_to(vec![0.75, 0.25, 0.5, 1.0 / 3.0])
+        );
+        assert_that!(options.invalid_inputs, equal_to(vec!["junk".to_string()]));
+        fs::remove_file(&path).unwrap();
+
+        assert!(parse_args(&args("--mmap 0.5")).is_err());
+        assert!(parse_args(&args("--mmap --input /nonexistent/inputs")).is_err());
+    }
+
+    #[test]
+    fn test_file_sections() {
+        let dir = std::env::temp_dir().join(format!("files-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        let first = dir.join("week1.txt");
+        let second = dir.join("week2.txt");
+        fs::write(&first, "0.5\n").unwrap();
+        fs::write(&second, "0.25\n").unwrap();
+        let line = format!("2 --file {} {}", first.display(), second.display());
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(
+            options.files,
+            equal_to(vec![
+                first.display().to_string(),
+                second.display().to_string()
+            ])
+        );
+        assert!(options.f64_numbers.is_empty());
+
+        let mixed = format!("--file {} 0.5", first.display());
+        assert!(parse_args(&args(&mixed)).is_err());
+        // Every file is sorted, collapsed and streamed on its own.
+        let sorted = format!("--file {} --sort value --dedup", first.display());
+        let arrangement = parse_args(&args(&sorted)).unwrap().arrangement;
+        assert_that!(arrangement.sort, equal_to(Some(SortKey::Value)));
+        assert!(arrangement.dedup);
+        let streamed = format!(
+            "--stream --file {} --file {}",
+            first.display(),
+            second.display()
+        );
+        assert_that!(
+            parse_args(&args(&streamed)).unwrap().stream_from,
+            equal_to(vec![
+                first.display().to_string(),
+                second.display().to_string()
+            ])
+        );
+        let watched = format!("--file {} --watch", first.display());
+        assert!(parse_args(&args(&watched)).is_err());
+        let column = format!("--file {} --column price", first.display());
+        assert_that!(
+            parse_args(&args(&column)).unwrap().csv_column,
+            equal_to(Some("price".to_string()))
+        );
+        assert!(parse_args(&args("--column price 0.5")).is_err());
+        let table = format!("--file {} --table items --column price", first.display());
+        assert_that!(
+            parse_args(&args(&table)).unwrap().sqlite_table,
+            equal_to(Some("items".to_string()))
+        );
+        assert!(parse_args(&args("--table items 0.5")).is_err());
+        let same = dir.join("b").join("week1.txt");
+        fs::write(&same, "0.75\n").unwrap();
+        let clash = format!(
+            "--output-dir {} --file {} --file {}",
+            dir.join("out").display(),
+            first.display(),
+            same.display()
+        );
+        assert!(parse_args(&args(&clash)).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_glob_flag() {
+        let dir = std::env::temp_dir().join(format!("globbed-{}", std::process::id()));
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a/week1.txt"), "0.5\n").unwrap();
+        fs::write(dir.join("b/week1.txt"), "0.25\n").unwrap();
+        let root = dir.display().to_string();
+        let line = format!(
+            "--glob {}/a/*.txt --glob {}/b/*.txt --output-dir out",
+            root, root
+        );
+        let options = parse_args(&args(&line)).unwrap();
+        assert_that!(
+            options.files,
+            equal_to(vec![
+                format!("{}/a/week1.txt", root),
+                format!("{}/b/week1.txt", root)
+            ])
+        );
+        // The files have the same name, but in directories of their own.
+        assert_that!(options.glob_root, equal_to(Some(format!("{}/", root))));
+        assert!(parse_args(&args(&format!("--glob {}/*.csv", root))).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_stream_flag() {
+        let options = parse_args(&args("--stream --input /nonexistent/inputs 0.5")).unwrap();
+        assert_that!(
+            options.stream_from,
+            equal_to(vec!["/nonexistent/inputs".to_string()])
+        );
+        assert_that!(options.f64_numbers, equal_to(vec![0.5]));
+        assert!(parse_args(&args("--stream 0.5")).is_err());
+        assert!(parse_args(&args("--stream --input - --explain 0.5")).is_err());
+        let jsonl = cfg!(feature = "serde").then_some("jsonl");
+        for format in ["csv", "tsv", "plain"].into_iter().chain(jsonl) {
+            let line = format!("--stream --input - --format {}", format);
+            assert_that!(
+                parse_args(&args(&line)).unwrap().format,
+                equal_to(OutputFormat::from_name(format).unwrap())
+            );
+        }
+        assert!(parse_args(&args("--stream --input - --format json")).is_err());
+        assert!(parse_args(&args("--stream --input - --format csv --columns repr")).is_err());
+    }
+
+    #[test]
+    fn test_supported_flags() {
+        for &(flag, supported) in SUPPORTED_FLAGS {
+            for other in supported {
+                let (_, others) = SUPPORTED_FLAGS
+                    .iter()
+                    .find(|(name, _)| name == other)
+                    .unwrap();
+                assert!(others.contains(&flag), "{} and {}", flag, other);
+            }
+        }
+        assert!(parse_args(&args("--factoradic --alphabet ABCDEFGHIJ 0.5")).is_err());
+        assert!(parse_args(&args("--factoradic --pad-digits 3 0.5")).is_err());
+        assert!(parse_args(&args("--radices 24,60 --pad-digits 3 0.5")).is_err());
+        assert!(parse_args(&args("--zeckendorf --subscript 0.5")).is_err());
+        assert!(parse_args(&args("--duration --dms 0.5")).is_err());
+        assert_that!(
+            parse_args(&args("--zeckendorf --notation alphanumeric 0.5")).unwrap_err(),
+            equal_to("--zeckendorf cannot be combined with --notation".to_string())
+        );
+        assert!(parse_args(&args("16 --alphabet 0123456789abcdef --subscript 0.5")).is_ok());
+        assert!(parse_args(&args("--factoradic --precision 5 0.5")).is_ok());
+    }
+
+    #[test]
+    fn test_own_radix_refuses_bases() {
+        for mode in [
+            "--factoradic",
+            "--hex-float",
+            "--duration",
+            "--dms",
+            "--radices 24,60",
+            "--zeckendorf",
+            "--bcd",
+            "--as-fixed q1.15",
+        ] {
+            let line = format!("{} --base 2,16 0.5", mode);
+            let name = mode.split(' ').next().unwrap();
+            assert_that!(
+                parse_args(&args(&line)).unwrap_err(),
+                equal_to(format!("{} cannot be combined with --base", name))
+            );
+            assert!(parse_args(&args(&format!("{} --base 2 0.5", mode))).is_err());
+            // A first whole number is an input rather than a base.
+            let options = parse_args(&args(&format!("{} 2 0.5", mode))).unwrap();
+            assert!(options.representation.own_radix());
+            assert_that!(options.f64_numbers, equal_to(vec![2.0, 0.5]));
+        }
+        assert!(parse_args(&args("--balanced --base 3 0.5")).is_ok());
+    }
+
+    #[test]
+    fn test_bcd_flag() {
+        let options = parse_args(&args("--bcd 12.75")).unwrap();
+        assert!(options.representation.bcd);
+        assert!(parse_args(&args("--bcd --hex-float 0.5")).is_err());
+        assert!(parse_args(&args("--bcd --matrix-options 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_factoradic_flag() {
+        let options = parse_args(&args("--factoradic 5/6")).unwrap();
+        assert!(options.representation.factoradic);
+        assert!(parse_args(&args("--factoradic --bcd 0.5")).is_err());
+        assert!(parse_args(&args("--factoradic --format csv 0.5")).is_err());
+
+        let options = parse_args(&args("--radices 24,60,60 0.5")).unwrap();
+        assert_that!(
+            options.representation.radices,
+            equal_to(Some(vec![24, 60, 60]))
+        );
+        assert!(parse_args(&args("--radices 24,60 --factoradic 0.5")).is_err());
+        assert!(parse_args(&args("--radices 24,0 0.5")).is_err());
+
+        let options = parse_args(&args("--duration 0.75")).unwrap();
+        assert!(options.representation.duration);
+        assert_that!(
+            options.representation.radices,
+            equal_to(Some(DAY_RADICES.to_vec()))
+        );
+        assert!(parse_args(&args("--duration --radices 24,60 0.5")).is_err());
+        assert!(parse_args(&args("--duration --as-time 0.5")).is_err());
+
+        let options = parse_args(&args("--dms 12.51")).unwrap();
+        assert!(options.representation.dms);
+        assert_that!(
+            options.representation.radices,
+            equal_to(Some(DMS_RADICES.to_vec()))
+        );
+        assert!(parse_args(&args("--dms --duration 0.5")).is_err());
+
+        assert!(
+            parse_args(&args("--zeckendorf 12.5"))
+                .unwrap()
+                .representation
+                .zeckendorf
+        );
+        assert!(parse_args(&args("--zeckendorf --factoradic 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_sort_flags() {
+        let options = parse_args(&args("--sort value --desc 0.25 1/3 -1")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![1.0 / 3.0, 0.25, -1.0]));
+        assert_that!(
+            options.fractions[0].clone(),
+            equal_to(Fraction::parse("1/3"))
+        );
+        let options = parse_args(&args("--base 2 --sort digit-count 0.1 0.5")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.1]));
+        assert!(parse_args(&args("--desc 0.5")).is_err());
+        assert!(parse_args(&args("--sort size 0.5")).is_err());
+        assert!(parse_args(&args("--sort value --stream --input - ")).is_err());
+    }
+
+    #[test]
+    fn test_dedup_flag() {
+        let options = parse_args(&args("--dedup --sort value 0.5 0.1 1/2 0.1 0.1")).unwrap();
+        assert!(options.columns.count);
+        assert_that!(options.f64_numbers, equal_to(vec![0.1, 0.5]));
+        assert_that!(options.counts, equal_to(vec![3, 2]));
+        assert_that!(
+            parse_args(&args("0.5 0.5")).unwrap().counts,
+            equal_to(vec![1, 1])
+        );
+        assert!(parse_args(&args("--dedup --format csv 0.5")).is_err());
+        assert!(parse_args(&args("--dedup --summary-only 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_output_flags() {
+        let options =
+            parse_args(&args("--output results.md --append --rotate-size 1M 0.5")).unwrap();
+        assert_that!(
+            options.output,
+            equal_to(OutputOptions {
+                path: Some("results.md".to_string()),
+                append: true,
+                rotate_size: Some(1 << 20),
+                ..OutputOptions::default()
+            })
+        );
+        let options = parse_args(&args("--output results.md --backup 0.5")).unwrap();
+        assert!(options.output.backup && !options.output.force);
+        assert!(parse_args(&args("--append 0.5")).is_err());
+        assert!(parse_args(&args("--rotate-size 10 0.5")).is_err());
+        assert!(parse_args(&args("--force 0.5")).is_err());
+        assert!(parse_args(&args("--output r.md --sign-key /nonexistent/key 0.5")).is_err());
+        assert!(parse_args(&args("--copy 0.5")).unwrap().output.copy);
+        assert!(parse_args(&args("--copy --output-dir out 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_verbosity_flags() {
+        assert_that!(parse_args(&args("0.5")).unwrap().verbosity, equal_to(0));
+        assert_that!(parse_args(&args("-v 0.5")).unwrap().verbosity, equal_to(1));
+        assert_that!(
+            parse_args(&args("-v -v 0.5")).unwrap().verbosity,
+            equal_to(2)
+        );
+        assert_that!(
+            parse_args(&args("-vv -v 0.5")).unwrap().verbosity,
+            equal_to(2)
+        );
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_kept() {
+        let options = parse_args(&args("--strict 2 0.5 junk 0.25 1/0x")).unwrap();
+        assert_that!(options.error_mode, equal_to(ErrorMode::FailFast));
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.25]));
+        assert_that!(
+            options.invalid_inputs,
+            equal_to(vec!["junk".to_string(), "1/0x".to_string()])
+        );
+        assert_that!(
+            parse_args(&args("0.5")).unwrap().error_mode,
+            equal_to(ErrorMode::Skip)
+        );
+        assert_that!(
+            parse_args(&args("--error-mode collect 0.5"))
+                .unwrap()
+                .error_mode,
+            equal_to(ErrorMode::Collect)
+        );
+        assert!(parse_args(&args("--error-mode ignore 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_verify_roundtrip_flag() {
+        let options = parse_args(&args("--verify-roundtrip --precision 12 0.1")).unwrap();
+        assert_that!(
+            options.columns.verify,
+            equal_to(Some(Tolerance::LastPlace(12)))
+        );
+        let options = parse_args(&args("--verify-roundtrip=1e-3 0.1")).unwrap();
+        assert_that!(
+            options.columns.verify,
+            equal_to(Some(Tolerance::Absolute(1e-3)))
+        );
+        assert!(parse_args(&args("--verify-roundtrip=-1 0.1")).is_err());
+        assert!(parse_args(&args("--verify-roundtrip --summary-only 0.1")).is_err());
+        assert!(parse_args(&args("--verify-roundtrip --format json 0.1")).is_err());
+    }
+
+    #[test]
+    fn test_notation_flag() {
+        let options = parse_args(&args("--notation alphanumeric --base 16 0.5")).unwrap();
+        assert_that!(
+            options.representation.notation,
+            equal_to(Some(Notation::Alphanumeric))
+        );
+        let options = parse_args(&args("--notation semicolon --format csv 0.5")).unwrap();
+        assert_that!(options.representation.notation, equal_to(None));
+        assert!(parse_args(&args("--notation subscript --format json 0.5")).is_err());
+        assert!(parse_args(&args("--notation alphanumeric --gray 0.5")).is_err());
+        let options = parse_args(&args("--notation cuneiform 60 0.25")).unwrap();
+        assert_that!(
+            options.representation.notation,
+            equal_to(Some(Notation::Cuneiform))
+        );
+        let options = parse_args(&args("--notation maya-ascii 20 0.25")).unwrap();
+        assert_that!(
+            options.representation.notation,
+            equal_to(Some(Notation::MayaAscii))
+        );
+        assert!(parse_args(&args("--notation roman 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_quiet_flag() {
+        assert!(parse_args(&args("--quiet 0.5")).unwrap().quiet);
+        assert!(!parse_args(&args("0.5")).unwrap().quiet);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_output_dir_flag() {
+        let options = parse_args(&args(
+            "--format md --format json --format md --output-dir out 0.5",
+        ))
+        .unwrap();
+        assert_that!(options.output.dir, equal_to(Some("out".to_string())));
+        assert_that!(
+            options.formats,
+            equal_to(vec![OutputFormat::Table, OutputFormat::Json])
+        );
+        let options = parse_args(&args("--output-dir out --force 0.5")).unwrap();
+        assert_that!(options.formats, equal_to(vec![OutputFormat::Table]));
+        assert!(parse_args(&args("--format md --format json 0.5")).is_err());
+        assert!(parse_args(&args("--output-dir out --output r.md 0.5")).is_err());
+        assert!(parse_args(&args("--output-dir out --append 0.5")).is_err());
+        assert!(parse_args(&args("--output-dir out --explain 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_output_format_from_extension() {
+        let options = parse_args(&args("--output results.JSON 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Json));
+        let options = parse_args(&args("--output results.json --format csv 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Csv));
+        // Modes that only print tables keep doing so whatever the file is called.
+        let options = parse_args(&args("--output results.csv --error 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Table));
+        let options = parse_args(&args("--output results.txt 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Table));
+    }
+
+    #[test]
+    fn test_checksum_flag() {
+        let options = parse_args(&args("--checksum 0.5")).unwrap();
+        assert_that!(options.output.checksum, equal_to(Some(Checksum::Footer)));
+        let options = parse_args(&args("--output r.md --checksum=sidecar 0.5")).unwrap();
+        assert_that!(options.output.checksum, equal_to(Some(Checksum::Sidecar)));
+        assert!(parse_args(&args("--checksum=sidecar 0.5")).is_err());
+        assert!(parse_args(&args("--checksum=md5 0.5")).is_err());
+        assert!(parse_args(&args("--output r.md --append --checksum 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_max_memory_flag() {
+        let options = parse_args(&args("--max-memory 64K 0.5")).unwrap();
+        assert_that!(options.max_memory, equal_to(Some(65536)));
+        assert_that!(parse_args(&args("0.5")).unwrap().max_memory, equal_to(None));
+        assert!(parse_args(&args("--max-memory lots 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_as_time_defaults_to_base_60() {
+        let options = parse_args(&args("--as-time 0.5")).unwrap();
+        assert_that!(
+            options.representation.as_time,
+            equal_to(Some(TimeUnit::Hour))
+        );
+        assert_that!(options.pipeline.target_bases, equal_to(vec![60.0]));
+
+        let options = parse_args(&args("--as-time=day --base 60 0.5")).unwrap();
+        assert_that!(
+            options.representation.as_time,
+            equal_to(Some(TimeUnit::Day))
+        );
+        assert_that!(options.f64_numbers, equal_to(vec![0.5]));
+
+        assert!(parse_args(&args("--as-time=week 0.5")).is_err());
+        assert!(parse_args(&args("--as-time 2 0.5")).is_err());
+        assert!(parse_args(&args("--as-time --matrix-options 0.5")).is_err());
+    }
+
+    #[test]
+    fn test_float_base_expansions() {
+        let options = parse_args(&args("--base 2 --base-float phi,1.5 0.5")).unwrap();
+        assert_that!(
+            options.pipeline.target_bases,
+            equal_to(vec![2.0, GOLDEN_RATIO, 1.5])
+        );
+        assert!(options.representation.beta_rules.is_empty());
+        assert_that!(options.f64_numbers, equal_to(vec![0.5]));
+
+        let options = parse_args(&args("--base-float=1.5 --expansion greedy,lazy 0.5")).unwrap();
+        assert_that!(
+            options.representation.beta_rules,
+            equal_to(vec![BetaRule::Greedy, BetaRule::Lazy])
+        );
+
+        assert!(parse_args(&args("--expansion lazy 0.5")).is_err());
+        assert!(parse_args(&args("--base-float 0.5 0.5")).is_err());
+        assert!(parse_args(&args("--base-float phi --expansion eager 0.5")).is_err());
+        assert!(parse_args(&args(
+            "--base-float phi --expansion lazy --summary-only 0.5"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_format_and_separator_flags() {
+        let options = parse_args(&args("--format json --base 2,8 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Json));
+        let options = parse_args(&args("--separator=, 0.5")).unwrap();
+        assert_that!(options.separator, equal_to(Some(",".to_string())));
+        assert_that!(options.format, equal_to(OutputFormat::Table));
+        let options = parse_args(&args("--format yaml 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Yaml));
+        assert!(parse_args(&args("--format toml 0.5")).is_err());
+        assert!(parse_args(&args("--format csv --explain 0.5")).is_err());
+        assert!(parse_args(&args("--format csv --separator , 0.5")).is_err());
+        assert!(parse_args(&args("--separator , --matrix-options 0.5")).is_err());
+        let options = parse_args(&args("--output results.xlsx 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Xlsx));
+        assert!(parse_args(&args("--output results.xlsx --append 0.5")).is_err());
+        assert!(parse_args(&args("--output results.xlsx --checksum 0.5")).is_err());
+        let options = parse_args(&args("--output results.db 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Sqlite));
+        assert!(parse_args(&args("--format sqlite --append 0.5")).is_err());
+        let options = parse_args(&args("--output results.parquet 0.5")).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Parquet));
+    }
+
+    #[test]
+    fn test_config_file_defaults() {
+        let path = env::temp_dir().join(format!("cli-config-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "base = [2, 16]\nprecision = 4\nseparator = \" \"\nformat = \"csv\"\n",
+        )
+        .unwrap();
+
+        let options =
+            parse_args_with_config(&args("0.5"), Some(&path), &Config::default()).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0, 16.0]));
+        assert_that!(options.pipeline.digits, equal_to(4));
+        assert_that!(options.format, equal_to(OutputFormat::Csv));
+        assert_that!(options.separator, equal_to(None));
+
+        let config = format!("--config={}", path.display());
+        let options = parse_args(&args(&format!("{} --precision 8 8 0.5", config))).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![8.0]));
+        assert_that!(options.pipeline.digits, equal_to(8));
+        let options = parse_args(&args(&format!("{} --format md 0.5", config))).unwrap();
+        assert_that!(options.separator, equal_to(Some(" ".to_string())));
+        let options = parse_args(&args(&format!("{} --explain 0.5", config))).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Table));
+
+        let missing = env::temp_dir().join("cli-config-missing.toml");
+        let options =
+            parse_args_with_config(&args("0.5"), Some(&missing), &Config::default()).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0]));
+        assert!(parse_args(&args(&format!("--config {} 0.5", missing.display()))).is_err());
+
+        let environment = Config {
+            precision: Some(12),
+            format: Some(OutputFormat::Json),
+            ..Config::default()
+        };
+        let options = parse_args_with_config(&args("0.5"), Some(&path), &environment).unwrap();
+        assert_that!(options.pipeline.target_bases, equal_to(vec![2.0, 16.0]));
+        assert_that!(options.pipeline.digits, equal_to(12));
+        assert_that!(options.format, equal_to(OutputFormat::Json));
+        let options =
+            parse_args_with_config(&args("--format csv 0.5"), Some(&path), &environment).unwrap();
+        assert_that!(options.format, equal_to(OutputFormat::Csv));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_auto_detect() {
+        let options = parse_args(&args("--auto-detect 0.101₂ 0x0.8 0.C₁₆ 0.25 0.3₂")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.625, 0.5, 0.75, 0.25]));
+        assert_that!(options.invalid_inputs, equal_to(vec!["0.3₂".to_string()]));
+        assert!(parse_args(&args("0.101₂")).unwrap().f64_numbers.is_empty());
+        assert!(parse_args(&args("--auto-detect --pipe from=q1.15 0x4000")).is_err());
+    }
+
+    #[test]
+    fn test_decimal_comma_inputs() {
+        let options = parse_args(&args("--decimal-comma 0,5 0.25 1,2,3")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.5, 0.25]));
+        let options = parse_args(&args("--locale de_DE.UTF-8 8 0,125")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.125]));
+        let options = parse_args(&args("--locale en_US 0,5 0.5")).unwrap();
+        assert_that!(options.f64_numbers, equal_to(vec![0.5]));
+        assert!(parse_args(&args("--locale 1 0,5")).is_err());
+        assert!(parse_args(&args("--decimal-comma --pipe from=q1.15 16384")).is_err());
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let missing = parse_args(&args("--watch --input inputs.txt 16")).unwrap_err();
+        assert!(missing.contains("inputs.txt"));
+        let path = env::temp_dir().join(format!("cli-watch-{}.txt", std::process::id()));
+        fs::write(&path, "0.5 0.25").unwrap();
+        let input = format!("--input={}", path.display());
+        let options = parse_args(&args(&format!("--watch {} 0.75", input))).unwrap();
+        assert_that!(options.watch_paths.len(), equal_to(1));
+        assert_that!(options.f64_numbers, equal_to(vec![0.75, 0.5, 0.25]));
+        assert!(parse_args(&args("--watch 0.5")).is_err());
+        assert!(parse_args(&args("--watch --input - 0.5")).is_err());
+        assert!(parse_args(&args(&format!("--watch --stream {} 0.5", input))).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_verify_forms() {
+        assert!(checks_answer(&args("0.1 2 0.00011")));
+        assert!(checks_answer(&args("--alphabet 01 0.1 2 0.00011")));
+        assert!(!checks_answer(&args("results.md --public-key answers.pub")));
+        assert!(!checks_answer(&args(
+            "results.md --signature=results.md.sig --public-key answers.pub"
+        )));
+        // A lone file is a signed one, so that a missing key is reported.
+        assert!(!checks_answer(&args("results.md")));
+    }
+
+    #[test]
+    fn test_compare_bases() {
+        let options =
+            parse_compare_args(&args("3 0.1 --precision 4"), None, &Config::default()).unwrap();
+        assert_that!(
+            options.pipeline.target_bases,
+            equal_to(vec![2.0, 8.0, 10.0, 16.0])
+        );
+        assert_that!(options.f64_numbers, equal_to(vec![3.0, 0.1]));
+        assert_that!(options.pipeline.digits, equal_to(4));
+        assert!(parse_compare_args(&args("--base=3 0.1"), None, &Config::default()).is_err());
+        for flag in [
+            "--factoradic",
+            "--as-fixed=q1.15",
+            "--radices=24,60",
+            "--pipe",
+        ] {
+            assert_that!(
+                parse_compare_args(&args(&format!("{} 0.1", flag)), None, &Config::default())
+                    .unwrap_err(),
+                equal_to(format!(
+                    "compare picks its own bases and does not accept `{}`",
+                    flag
+                ))
+            );
+        }
+    }
+}
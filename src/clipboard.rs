@@ -0,0 +1,195 @@
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// The commands tried in turn to put text on the clipboard, each with its
+/// arguments, for the platform the program runs on.
+fn clipboard_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        vec![("pbcopy", &[])]
+    } else if cfg!(windows) {
+        vec![("clip", &[])]
+    } else {
+        let mut commands: Vec<(&str, &[&str])> = Vec::new();
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            commands.push(("wl-copy", &[]));
+        }
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+        commands
+    }
+}
+
+/// Puts text on the system clipboard, without the `\x1b[…m` escape codes
+/// that color it on a terminal, through the command in
+/// `$BASECONV_CLIPBOARD` if it is set, or else the first clipboard command
+/// of the platform that is installed: `pbcopy` on macOS, `clip` on Windows,
+/// and `wl-copy`, `xclip` or `xsel` elsewhere.
+///
+/// # Returns
+///
+/// A message describing why the text could not be copied, if it could not.
+///
+/// # Example
+///
+/// ```
+/// copy_to_clipboard("| 0.5        | 0.1;                   |\n").unwrap();
+/// ```
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let command = env::var("BASECONV_CLIPBOARD")
+        .ok()
+        .filter(|command| !command.trim().is_empty());
+    copy_with(command.as_deref(), text)
+}
+
+/// Like [`copy_to_clipboard`], with the command of `$BASECONV_CLIPBOARD`,
+/// if any, given.
+fn copy_with(command: Option<&str>, text: &str) -> Result<(), String> {
+    let text = strip_escapes(text);
+    if let Some(command) = command {
+        return pipe_to(shell(command), &text)
+            .map_err(|error| format!("cannot copy the results with `{}`: {}", command, error));
+    }
+    for (program, args) in clipboard_commands() {
+        let mut command = Command::new(program);
+        command.args(args);
+        match pipe_to(command, &text) {
+            Ok(()) => return Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => {
+                return Err(format!(
+                    "cannot copy the results with {}: {}",
+                    program, error
+                ))
+            }
+        }
+    }
+    Err(
+        "no clipboard command was found; install wl-copy, xclip or xsel, \
+         or set BASECONV_CLIPBOARD to a command reading the text on its input"
+            .to_string(),
+    )
+}
+
+/// A command run by the shell of the platform, as `$PAGER` is.
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Runs a command with text on its standard input, and waits for it to
+/// succeed.
+fn pipe_to(mut command: Command, text: &str) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("it exited with {}", status)))
+    }
+}
+
+/// Leaves out the `\x1b[…m` escape codes that only change the look of text
+/// on a terminal.
+fn strip_escapes(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// A writer that keeps a copy of everything it passes on, for `--copy`.
+pub struct CopyingWriter<'a> {
+    /// Where the bytes go.
+    inner: &'a mut dyn Write,
+    /// The bytes written so far.
+    copied: Vec<u8>,
+}
+
+impl<'a> CopyingWriter<'a> {
+    /// Wraps a writer, with nothing copied yet.
+    pub fn new(inner: &'a mut dyn Write) -> CopyingWriter<'a> {
+        CopyingWriter {
+            inner,
+            copied: Vec::new(),
+        }
+    }
+
+    /// Everything written, as text.
+    pub fn finish(self) -> String {
+        String::from_utf8_lossy(&self.copied).into_owned()
+    }
+}
+
+impl Write for CopyingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.copied.extend_from_slice(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn test_copying_writer() {
+        let mut out = Vec::new();
+        let mut copying = CopyingWriter::new(&mut out);
+        copying.write_all(b"0.\x1b[36m1;").unwrap();
+        let copied = copying.finish();
+        assert_that!(copied.as_str(), equal_to("0.\x1b[36m1;"));
+        assert_that!(out, equal_to(b"0.\x1b[36m1;".to_vec()));
+        assert_that!(
+            strip_escapes("0.0;\x1b[36m0;1;\x1b[39m"),
+            equal_to("0.0;0;1;".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_copy_to_clipboard() {
+        let path = env::temp_dir().join(format!("clipboard-{}.txt", std::process::id()));
+        let copy = format!("cat > '{}'", path.display());
+        assert_that!(
+            copy_with(Some(&copy), "| 0.5 | \x1b[1m0.1;\x1b[0m |\n"),
+            equal_to(Ok(()))
+        );
+        assert_that!(
+            fs::read_to_string(&path).unwrap(),
+            equal_to("| 0.5 | 0.1; |\n".to_string())
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(copy_with(Some("exit 3"), "0.1;")
+            .unwrap_err()
+            .contains("exit status: 3"));
+    }
+}
@@ -0,0 +1,330 @@
+use std::io::{self, Write};
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::conversion::Conversion;
+use crate::display::Columns;
+use crate::format::{
+    conversion_error, write_csv_field, write_tsv_field, FieldWriter, OutputFormat,
+};
+#[cfg(feature = "serde")]
+use crate::json::to_json;
+#[cfg(feature = "serde")]
+use crate::msgpack::to_msgpack;
+#[cfg(feature = "serde")]
+use crate::schema::write_json_document;
+#[cfg(feature = "serde")]
+use crate::yaml::to_yaml_entry;
+
+/// A column `--columns` can choose for the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// The label the input was given with, as in `tenth=0.1`.
+    Label,
+    /// The input in base 10.
+    Decimal,
+    /// The target base.
+    Base,
+    /// The number of fractional digits produced.
+    Digits,
+    /// Whether the expansion was cut off before it ended.
+    Truncated,
+    /// The expansion, such as `0.0;1;`.
+    Repr,
+    /// The absolute difference between the input and the value of the
+    /// digits.
+    Error,
+}
+
+/// Every column, in the order `--columns` lists them in its messages.
+const COLUMNS: [Column; 7] = [
+    Column::Label,
+    Column::Decimal,
+    Column::Base,
+    Column::Digits,
+    Column::Truncated,
+    Column::Repr,
+    Column::Error,
+];
+
+impl Column {
+    /// The name of the column in `--columns` and in the header or keys of
+    /// the output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Label => "label",
+            Column::Decimal => "decimal",
+            Column::Base => "base",
+            Column::Digits => "digits",
+            Column::Truncated => "truncated",
+            Column::Repr => "repr",
+            Column::Error => "error",
+        }
+    }
+
+    /// Whether the table has this column. The table has a column per base
+    /// rather than a row, so it has no base, digits or truncated column.
+    fn in_table(self) -> bool {
+        !matches!(self, Column::Base | Column::Digits | Column::Truncated)
+    }
+}
+
+/// Parses the value of `--columns`, such as `decimal,repr,error`.
+///
+/// # Returns
+///
+/// The columns in the order given, or a message naming an unknown or
+/// repeated one.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(parse_columns("repr,error"), Ok(vec![Column::Repr, Column::Error]));
+/// ```
+pub fn parse_columns(value: &str) -> Result<Vec<Column>, String> {
+    let mut columns = Vec::new();
+    for name in value.split(',').map(str::trim) {
+        let column = COLUMNS
+            .into_iter()
+            .find(|column| column.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = COLUMNS.iter().map(|column| column.name()).collect();
+                format!("unknown column `{}`, expected {}", name, names.join(", "))
+            })?;
+        if columns.contains(&column) {
+            return Err(format!("column `{}` is given twice in --columns", name));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+/// Checks that the chosen columns can be written in a format.
+pub fn check_columns(columns: &[Column], format: OutputFormat) -> Result<(), String> {
+    match format {
+        OutputFormat::Table => match columns.iter().find(|column| !column.in_table()) {
+            Some(column) => Err(format!(
+                "the table has a column per base, so it has no `{}` column; \
+                 choose among label, decimal, repr and error",
+                column.name()
+            )),
+            None => Ok(()),
+        },
+        OutputFormat::Csv
+        | OutputFormat::Tsv
+        | OutputFormat::Json
+        | OutputFormat::JsonLines
+        | OutputFormat::Yaml
+        | OutputFormat::MessagePack => Ok(()),
+        _ => Err(
+            "--columns applies to --format md, csv, tsv, json, jsonl, yaml and msgpack".to_string(),
+        ),
+    }
+}
+
+/// Shows in the table only the chosen columns, in the places the table
+/// keeps them in.
+pub fn select_table_columns(columns: &[Column], table: &mut Columns) {
+    table.label = columns.contains(&Column::Label);
+    table.hide_input = !columns.contains(&Column::Decimal);
+    table.hide_expansions = !columns.contains(&Column::Repr);
+    table.round_trip_error = columns.contains(&Column::Error);
+}
+
+/// Writes conversions with only the chosen fields, in the order chosen.
+///
+/// # Arguments
+///
+/// * `out` - Where the conversions are written.
+/// * `format` - The format to write them in, one `check_columns` allows
+///   other than the table.
+/// * `columns` - The fields every record has.
+/// * `conversions` - The conversion of every input to every target base.
+///
+/// # Example
+///
+/// ```
+/// let conversions = [Conversion::new(0.5, 2.0, &expand(0.5, 2, 8))];
+/// let mut out = Vec::new();
+/// write_columns(&mut out, OutputFormat::Csv, &[Column::Repr, Column::Decimal], conversions).unwrap();
+/// assert_eq!(out, b"repr,decimal\n0.1;,0.5\n");
+/// ```
+pub fn write_columns(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    columns: &[Column],
+    conversions: impl IntoIterator<Item = Conversion>,
+) -> io::Result<()> {
+    let conversions = conversions.into_iter();
+    match format {
+        OutputFormat::Csv => write_delimited(out, columns, conversions, ",", write_csv_field),
+        OutputFormat::Tsv => write_delimited(out, columns, conversions, "\t", write_tsv_field),
+        #[cfg(feature = "serde")]
+        OutputFormat::Json => write_json_document(
+            out,
+            conversions.map(|conversion| to_json(&Selected(columns, &conversion))),
+        ),
+        #[cfg(feature = "serde")]
+        OutputFormat::JsonLines => {
+            for conversion in conversions {
+                let object = to_json(&Selected(columns, &conversion)).map_err(invalid_data)?;
+                writeln!(out, "{}", object)?;
+            }
+            Ok(())
+        }
+        #[cfg(feature = "serde")]
+        OutputFormat::Yaml => {
+            let mut empty = true;
+            for conversion in conversions {
+                let entry = to_yaml_entry(&Selected(columns, &conversion)).map_err(invalid_data)?;
+                write!(out, "{}", entry)?;
+                empty = false;
+            }
+            if empty {
+                writeln!(out, "[]")?;
+            }
+            Ok(())
+        }
+        #[cfg(feature = "serde")]
+        OutputFormat::MessagePack => {
+            for conversion in conversions {
+                let map = to_msgpack(&Selected(columns, &conversion)).map_err(invalid_data)?;
+                out.write_all(&map)?;
+            }
+            Ok(())
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "--columns cannot be written in this format",
+        )),
+    }
+}
+
+/// Writes conversions as CSV or TSV records of the chosen fields, after a
+/// header row of their names.
+fn write_delimited(
+    out: &mut dyn Write,
+    columns: &[Column],
+    conversions: impl Iterator<Item = Conversion>,
+    delimiter: &str,
+    write_field: FieldWriter,
+) -> io::Result<()> {
+    let names: Vec<&str> = columns.iter().map(|column| column.name()).collect();
+    writeln!(out, "{}", names.join(delimiter))?;
+    for conversion in conversions {
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                write!(out, "{}", delimiter)?;
+            }
+            match column {
+                Column::Label => write_field(out, conversion.label.as_deref().unwrap_or(""))?,
+                Column::Decimal => write!(out, "{}", conversion.input)?,
+                Column::Base => write_field(out, &conversion.base)?,
+                Column::Digits => write!(out, "{}", conversion.digits)?,
+                Column::Truncated => write!(out, "{}", conversion.truncated)?,
+                Column::Repr => write_field(out, &conversion.repr)?,
+                Column::Error => write!(out, "{}", conversion_error(&conversion))?,
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn invalid_data(error: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// A conversion with only the chosen fields, as it is serialized. They
+/// come in the order `--columns` gives them, which a derived `Serialize`
+/// cannot follow.
+#[cfg(feature = "serde")]
+struct Selected<'a>(&'a [Column], &'a Conversion);
+
+#[cfg(feature = "serde")]
+impl Serialize for Selected<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Selected(columns, conversion) = self;
+        let mut state = serializer.serialize_struct("Conversion", columns.len())?;
+        for &column in columns.iter() {
+            let name = column.name();
+            match column {
+                Column::Label => {
+                    state.serialize_field(name, conversion.label.as_deref().unwrap_or(""))?
+                }
+                Column::Decimal => state.serialize_field(name, &conversion.input)?,
+                Column::Base => state.serialize_field(name, &conversion.base)?,
+                Column::Digits => state.serialize_field(name, &conversion.digits)?,
+                Column::Truncated => state.serialize_field(name, &conversion.truncated)?,
+                Column::Repr => state.serialize_field(name, &conversion.repr)?,
+                Column::Error => state.serialize_field(name, &conversion_error(conversion))?,
+            }
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    fn written(format: OutputFormat, columns: &[Column]) -> String {
+        let conversions = [
+            Conversion::new(0.5, 2.0, &expand(0.5, 2, 8)),
+            Conversion {
+                label: Some("tenth".to_string()),
+                ..Conversion::new(0.1, 2.0, &expand(0.1, 2, 4))
+            },
+        ];
+        let mut out = Vec::new();
+        write_columns(&mut out, format, columns, conversions).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_that!(
+            parse_columns("decimal,repr,error"),
+            equal_to(Ok(vec![Column::Decimal, Column::Repr, Column::Error]))
+        );
+        assert_that!(
+            parse_columns(" label, base "),
+            equal_to(Ok(vec![Column::Label, Column::Base]))
+        );
+        assert!(parse_columns("repr,input").is_err());
+        assert!(parse_columns("repr,repr").is_err());
+        assert!(parse_columns("").is_err());
+        assert!(check_columns(&[Column::Base], OutputFormat::Table).is_err());
+        assert!(check_columns(&[Column::Repr], OutputFormat::Table).is_ok());
+        assert!(check_columns(&[Column::Repr], OutputFormat::Xlsx).is_err());
+    }
+
+    #[test]
+    fn test_write_columns() {
+        assert_that!(
+            written(
+                OutputFormat::Csv,
+                &[Column::Decimal, Column::Repr, Column::Error]
+            ),
+            equal_to(
+                "decimal,repr,error\n0.5,0.1;,0\n0.1,0.0;0;0;1;,0.037500000000000006\n".to_string()
+            )
+        );
+        assert_that!(
+            written(OutputFormat::Tsv, &[Column::Truncated, Column::Label]),
+            equal_to("truncated\tlabel\nfalse\t\ntrue\ttenth\n".to_string())
+        );
+        #[cfg(feature = "serde")]
+        assert_that!(
+            written(OutputFormat::JsonLines, &[Column::Repr, Column::Digits]),
+            equal_to(
+                "{\"repr\":\"0.1;\",\"digits\":1}\n{\"repr\":\"0.0;0;0;1;\",\"digits\":4}\n"
+                    .to_string()
+            )
+        );
+    }
+}
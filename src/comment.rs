@@ -0,0 +1,63 @@
+/// Where the comment of a line of an input file starts: at a `#` that
+/// begins the line or follows whitespace, as in `0.5  # one half`, so that
+/// annotated datasets can be read unmodified. A `#` inside an input, as in
+/// `1#2`, is kept.
+///
+/// # Returns
+///
+/// The offset of the `#`, or the length of the line if it has no comment.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(comment_start(b"0.5 # half"), 4);
+/// assert_eq!(comment_start(b"# header"), 0);
+/// assert_eq!(comment_start(b"0.25"), 4);
+/// ```
+pub fn comment_start(line: &[u8]) -> usize {
+    (0..line.len())
+        .find(|&i| line[i] == b'#' && (i == 0 || line[i - 1].is_ascii_whitespace()))
+        .unwrap_or(line.len())
+}
+
+/// Cuts the comment off a line of an input file.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(strip_comment("1/3 2/3 # thirds"), "1/3 2/3 ");
+/// ```
+pub fn strip_comment(line: &str) -> &str {
+    &line[..comment_start(line.as_bytes())]
+}
+
+/// The inputs of the text of an input file, separated by whitespace, leaving
+/// out blank lines and comments.
+///
+/// # Example
+///
+/// ```
+/// let words: Vec<&str> = input_words("# samples\n0.5 # half\n\n0.25\n").collect();
+/// assert_eq!(words, vec!["0.5", "0.25"]);
+/// ```
+pub fn input_words(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .flat_map(|line| strip_comment(line).split_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_comments() {
+        assert_that!(comment_start(b"0.5 # half"), equal_to(4));
+        assert_that!(comment_start(b"#0.5"), equal_to(0));
+        assert_that!(comment_start(b"0.5\t#half"), equal_to(4));
+        assert_that!(comment_start(b"1#2"), equal_to(3));
+        assert_that!(strip_comment("   # indented"), equal_to("   "));
+        let words: Vec<&str> = input_words("# header\n0.5 1/3 # two\n\n  \n0.25#x\n").collect();
+        assert_that!(words, equal_to(vec!["0.5", "1/3", "0.25#x"]));
+    }
+}
@@ -0,0 +1,339 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::format::{parse_format, OutputFormat};
+use crate::pipeline::parse_base_list;
+
+/// The defaults read from a configuration file, used for every setting the
+/// command line leaves out.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Config {
+    /// The target bases used when none are given, from `base`.
+    pub base: Option<Vec<f64>>,
+    /// The digit limit used without `--precision`, from `precision`.
+    pub precision: Option<u32>,
+    /// The text written between digits without `--separator`, from `separator`.
+    pub separator: Option<String>,
+    /// The output format used without `--format`, from `format`.
+    pub format: Option<OutputFormat>,
+}
+
+/// The environment variables read for defaults, between those of the
+/// configuration file and the flags, in the order of the `Config` fields.
+pub const ENVIRONMENT_VARIABLES: [&str; 4] = [
+    "BASECONV_BASE",
+    "BASECONV_PRECISION",
+    "BASECONV_SEPARATOR",
+    "BASECONV_FORMAT",
+];
+
+/// A value of a configuration file: a string, a number kept as written, or
+/// an array of those.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+    Number(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// Describes the value for error messages.
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Number(_) => "a number",
+            Value::Array(_) => "an array",
+        }
+    }
+}
+
+/// The configuration file read without `--config`:
+/// `$XDG_CONFIG_HOME/base-convert/config.toml`, or
+/// `~/.config/base-convert/config.toml` when that variable is not set.
+pub fn default_config_path() -> Option<PathBuf> {
+    let directory = env::var_os("XDG_CONFIG_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(directory.join("base-convert").join("config.toml"))
+}
+
+impl Config {
+    /// Reads a configuration file.
+    ///
+    /// # Returns
+    ///
+    /// The defaults it sets, or a message naming the file and the line
+    /// that cannot be read.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read config file `{}`: {}", path.display(), error))?;
+        Config::parse(&text).map_err(|message| format!("{}: {}", path.display(), message))
+    }
+
+    /// Reads the defaults set by the `BASECONV_*` environment variables,
+    /// which take the same values as the flags: `BASECONV_BASE` a comma
+    /// separated list of bases, `BASECONV_PRECISION` a digit limit,
+    /// `BASECONV_SEPARATOR` the text between digits and `BASECONV_FORMAT`
+    /// a format name. Variables that are empty count as unset.
+    ///
+    /// # Arguments
+    ///
+    /// * `var` - Looks up the value of an environment variable, if it is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let config = Config::from_environment(|name| std::env::var(name).ok()).unwrap();
+    /// ```
+    pub fn from_environment(var: impl Fn(&str) -> Option<String>) -> Result<Config, String> {
+        let [base, precision, separator, format] =
+            ENVIRONMENT_VARIABLES.map(|name| var(name).filter(|value| !value.is_empty()));
+        let context = |name: &'static str| move |message: String| format!("{}: {}", name, message);
+        Ok(Config {
+            base: base
+                .map(|list| parse_base_list(&list))
+                .transpose()
+                .map_err(context(ENVIRONMENT_VARIABLES[0]))?,
+            precision: precision
+                .map(|digits| {
+                    digits
+                        .parse()
+                        .map_err(|_| format!("invalid precision `{}`", digits))
+                })
+                .transpose()
+                .map_err(context(ENVIRONMENT_VARIABLES[1]))?,
+            separator,
+            format: format
+                .map(|name| parse_format(&name))
+                .transpose()
+                .map_err(context(ENVIRONMENT_VARIABLES[3]))?,
+        })
+    }
+
+    /// Layers two sets of defaults, keeping every setting of `self` and
+    /// taking the rest from `fallback`.
+    pub fn or(self, fallback: Config) -> Config {
+        Config {
+            base: self.base.or(fallback.base),
+            precision: self.precision.or(fallback.precision),
+            separator: self.separator.or(fallback.separator),
+            format: self.format.or(fallback.format),
+        }
+    }
+
+    /// Parses the text of a configuration file: a TOML document of
+    /// `key = value` lines, with `#` starting a comment. The keys are `base`
+    /// (a base, a comma separated list of bases as for `--base`, or an array
+    /// of them), `precision`, `separator` and `format`. Unknown keys, tables
+    /// and values spanning several lines are errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let config = Config::parse("base = [2, 16] # side by side\nprecision = 12").unwrap();
+    /// assert_eq!(config.base, Some(vec![2.0, 16.0]));
+    /// assert_eq!(config.precision, Some(12));
+    /// ```
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (number, line) in text.lines().enumerate() {
+            config
+                .parse_line(line)
+                .map_err(|message| format!("line {}: {}", number + 1, message))?;
+        }
+        Ok(config)
+    }
+
+    /// Parses one line of a configuration file into `self`.
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        if line.starts_with('[') {
+            return Err("tables are not supported; put every key at the top".to_string());
+        }
+        let (key, rest) = line
+            .split_once('=')
+            .ok_or_else(|| "expected `key = value`".to_string())?;
+        let key = key.trim();
+        let (value, rest) = parse_value(rest.trim_start())?;
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(format!(
+                "unexpected `{}` after the value of `{}`",
+                rest, key
+            ));
+        }
+
+        match (key, value) {
+            ("base", Value::Array(items)) => {
+                let mut bases = Vec::new();
+                for item in items {
+                    match item {
+                        Value::String(list) | Value::Number(list) => {
+                            bases.extend(parse_base_list(&list)?)
+                        }
+                        Value::Array(_) => return Err("base cannot hold nested arrays".to_string()),
+                    }
+                }
+                self.base = Some(bases);
+            }
+            ("base", Value::String(list) | Value::Number(list)) => {
+                self.base = Some(parse_base_list(&list)?)
+            }
+            ("precision", Value::Number(digits)) => {
+                self.precision = Some(
+                    digits
+                        .parse()
+                        .map_err(|_| format!("invalid precision `{}`", digits))?,
+                )
+            }
+            ("separator", Value::String(separator)) => self.separator = Some(separator),
+            ("format", Value::String(name)) => self.format = Some(parse_format(&name)?),
+            ("precision" | "separator" | "format", value) => {
+                return Err(format!("`{}` cannot be {}", key, value.kind()))
+            }
+            _ => return Err(format!("unknown key `{}`", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Reads the value at the start of `text`, returning it with the text after it.
+fn parse_value(text: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = text.strip_prefix('[') {
+        let mut items = Vec::new();
+        let mut rest = rest.trim_start();
+        loop {
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(items), after));
+            }
+            if rest.is_empty() {
+                return Err("arrays must end on the line they start".to_string());
+            }
+            let (item, after) = parse_value(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after.trim_start();
+            } else if !rest.starts_with(']') {
+                return Err("expected `,` or `]` in an array".to_string());
+            }
+        }
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        let end = rest
+            .find('\'')
+            .ok_or_else(|| "unterminated string".to_string())?;
+        return Ok((Value::String(rest[..end].to_string()), &rest[end + 1..]));
+    }
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((Value::String(value), &rest[i + 1..])),
+                '\\' => match chars.next().map(|(_, escaped)| escaped) {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(escaped @ ('"' | '\\')) => value.push(escaped),
+                    Some(escaped) => return Err(format!("unknown escape `\\{}`", escaped)),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        return Err("unterminated string".to_string());
+    }
+
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == ',' || c == ']' || c == '#')
+        .unwrap_or(text.len());
+    let number = text[..end].replace('_', "");
+    if number.is_empty() || number.parse::<f64>().is_err() {
+        return Err(format!("invalid value `{}`", text));
+    }
+    Ok((Value::Number(number), &text[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "# defaults for the lab machines\n\
+             \n\
+             base = \"2,16\"\n\
+             precision = 12   # digits\n\
+             separator = ' '\n\
+             format = \"json\"\n",
+        )
+        .unwrap();
+        assert_that!(
+            config,
+            equal_to(Config {
+                base: Some(vec![2.0, 16.0]),
+                precision: Some(12),
+                separator: Some(" ".to_string()),
+                format: Some(OutputFormat::Json),
+            })
+        );
+        assert_that!(
+            Config::parse("base = [8, \"16\"]")
+                .unwrap()
+                .base
+                .unwrap()
+                .len(),
+            equal_to(2)
+        );
+        assert_that!(
+            Config::parse("base = 16").unwrap().base,
+            equal_to(Some(vec![16.0]))
+        );
+
+        assert_that!(
+            Config::parse("\nprecision = \"12\"").unwrap_err(),
+            equal_to("line 2: `precision` cannot be a string".to_string())
+        );
+        assert!(Config::parse("colour = \"red\"").is_err());
+        assert!(Config::parse("[defaults]").is_err());
+        assert!(Config::parse("base = [2,").is_err());
+        assert!(Config::parse("separator = \"unterminated").is_err());
+        assert!(Config::parse("format = \"xml\"").is_err());
+        assert!(Config::parse("precision = 12 13").is_err());
+    }
+
+    #[test]
+    fn test_environment_layer() {
+        let environment = |name: &str| match name {
+            "BASECONV_BASE" => Some("8,16".to_string()),
+            "BASECONV_FORMAT" => Some("csv".to_string()),
+            "BASECONV_SEPARATOR" => Some(String::new()),
+            _ => None,
+        };
+        let config = Config::from_environment(environment).unwrap();
+        assert_that!(config.base.clone(), equal_to(Some(vec![8.0, 16.0])));
+        assert_that!(config.separator.clone(), equal_to(None));
+
+        let file = Config::parse("base = 2\nprecision = 4").unwrap();
+        let layered = config.or(file);
+        assert_that!(layered.base, equal_to(Some(vec![8.0, 16.0])));
+        assert_that!(layered.precision, equal_to(Some(4)));
+        assert_that!(layered.format, equal_to(Some(OutputFormat::Csv)));
+
+        let invalid = Config::from_environment(|name| {
+            (name == "BASECONV_PRECISION").then(|| "many".to_string())
+        });
+        assert_that!(
+            invalid.unwrap_err(),
+            equal_to("BASECONV_PRECISION: invalid precision `many`".to_string())
+        );
+    }
+}
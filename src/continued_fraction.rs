@@ -0,0 +1,197 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::Zero;
+
+use crate::cli::flag_value;
+use crate::rational::{Fraction, Ratio};
+
+/// The number of terms `cf` computes when no `--depth` is given.
+pub const DEFAULT_DEPTH: usize = 20;
+
+/// The simple continued fraction `[a0; a1, a2, …]` of a number, where `a0`
+/// is the floor of the number and every later term is positive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContinuedFraction {
+    /// The terms, starting with the integer part `a0`.
+    pub terms: Vec<BigInt>,
+    /// Whether the terms are all of them, rather than cut off at the depth.
+    pub complete: bool,
+}
+
+/// Computes the continued fraction of an exact rational with the Euclidean
+/// algorithm, which always ends because every rational has a finite one.
+///
+/// # Arguments
+///
+/// * `ratio` - The number to expand.
+/// * `depth` - The largest number of terms to produce.
+///
+/// # Example
+///
+/// ```
+/// let cf = continued_fraction(&Ratio::from_f64(-0.75).unwrap(), 20);
+/// assert_eq!(cf.to_string(), "[-1; 4]");
+/// ```
+pub fn continued_fraction(ratio: &Ratio, depth: usize) -> ContinuedFraction {
+    let mut numerator = ratio.numerator.clone();
+    let mut denominator = ratio.denominator.clone();
+    let mut terms = Vec::new();
+
+    // The floor of a negative number is one below its truncation, and what
+    // is left is one minus the truncated-away fraction.
+    let integer = &numerator / &denominator;
+    let mut remainder = &numerator % &denominator;
+    if ratio.negative && !remainder.is_zero() {
+        terms.push(-BigInt::from(integer + 1u32));
+        remainder = &denominator - remainder;
+    } else {
+        let sign = if ratio.negative {
+            Sign::Minus
+        } else {
+            Sign::Plus
+        };
+        terms.push(BigInt::from_biguint(sign, integer));
+    }
+
+    while !remainder.is_zero() && terms.len() < depth.max(1) {
+        numerator = denominator;
+        denominator = remainder;
+        let term: BigUint = &numerator / &denominator;
+        remainder = &numerator % &denominator;
+        terms.push(BigInt::from(term));
+    }
+
+    ContinuedFraction {
+        terms,
+        complete: remainder.is_zero(),
+    }
+}
+
+impl fmt::Display for ContinuedFraction {
+    /// Writes the terms as `[a0; a1, a2]`, ending in `…` when the expansion
+    /// was cut off.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}", self.terms[0])?;
+        for (i, term) in self.terms.iter().enumerate().skip(1) {
+            write!(f, "{}{}", if i == 1 { "; " } else { ", " }, term)?;
+        }
+        if !self.complete {
+            write!(f, "{}…", if self.terms.len() == 1 { "; " } else { ", " })?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// The settings of the `cf` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CfOptions {
+    /// The largest number of terms to compute for every input.
+    pub depth: usize,
+    /// Every input as typed, with its exact value.
+    pub inputs: Vec<(String, Ratio)>,
+}
+
+/// Parses the arguments of the `cf` subcommand.
+///
+/// Inputs are decimals, whose exact stored `f64` value is expanded, or
+/// fractions such as `355/113`, which are expanded as written.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `cf`.
+///
+/// # Returns
+///
+/// The parsed `CfOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- cf --depth 10 0.1 355/113
+/// ```
+pub fn parse_cf_args(args: &[String]) -> Result<CfOptions, String> {
+    let mut options = CfOptions {
+        depth: DEFAULT_DEPTH,
+        inputs: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--depth", &mut iter)? {
+            options.depth = value
+                .parse::<usize>()
+                .ok()
+                .filter(|&depth| depth > 0)
+                .ok_or_else(|| format!("invalid depth `{}`", value))?;
+        } else {
+            let ratio = match Fraction::parse(arg) {
+                Some(fraction) => Some(Ratio::from(fraction)),
+                None => arg.parse::<f64>().ok().and_then(Ratio::from_f64),
+            };
+            let ratio = ratio.ok_or_else(|| format!("unknown cf argument `{}`", arg))?;
+            options.inputs.push((arg.clone(), ratio));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Runs the `cf` subcommand, printing the continued fraction of every input.
+///
+/// # Example
+/// ```
+/// // cargo run -- cf 0.75 355/113
+/// ```
+/// Output:
+/// 0.75 = [0; 1, 3]
+/// 355/113 = [3; 7, 16]
+pub fn display_continued_fractions(out: &mut impl Write, options: &CfOptions) -> io::Result<()> {
+    for (input, ratio) in &options.inputs {
+        writeln!(
+            out,
+            "{} = {}",
+            input,
+            continued_fraction(ratio, options.depth)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn cf(input: &str, depth: usize) -> String {
+        let options = parse_cf_args(&[input.to_string()]).unwrap();
+        continued_fraction(&options.inputs[0].1, depth).to_string()
+    }
+
+    #[test]
+    fn test_continued_fractions() {
+        assert_that!(cf("0.75", 20), equal_to("[0; 1, 3]"));
+        assert_that!(cf("355/113", 20), equal_to("[3; 7, 16]"));
+        assert_that!(cf("-7/3", 20), equal_to("[-3; 1, 2]"));
+        assert_that!(cf("-2", 20), equal_to("[-2]"));
+        assert_that!(cf("1/3", 20), equal_to("[0; 3]"));
+        // The stored 0.1 is slightly above a tenth, which would be [0; 10].
+        assert_that!(cf("0.1", 3), equal_to("[0; 9, 1, …]"));
+        assert_that!(cf("0.1", 1), equal_to("[0; …]"));
+        assert!(continued_fraction(&Ratio::from_f64(0.1).unwrap(), 100).complete);
+    }
+
+    #[test]
+    fn test_cf_arguments() {
+        let args: Vec<String> = ["--depth", "5", "0.5", "2/3"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let options = parse_cf_args(&args).unwrap();
+        assert_that!(options.depth, equal_to(5));
+        assert_that!(options.inputs.len(), equal_to(2));
+        assert!(parse_cf_args(&["--depth=0".to_string()]).is_err());
+        assert!(parse_cf_args(&["pi".to_string()]).is_err());
+        assert!(parse_cf_args(&["inf".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::beta::format_base;
+use crate::convert::{Expansion, Representation};
+
+/// The result of converting one input to one base, the data model every
+/// machine-readable output shares, such as the rows of `--matrix-options`.
+///
+/// It serializes as a struct with the fields `input`, `base`, `digits`,
+/// `truncated` and `repr`, in that order, after `label` when there is one.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct Conversion {
+    /// The label the input was given with, as in `tenth=0.1`, if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub label: Option<String>,
+    /// The converted input.
+    pub input: f64,
+    /// The target base as written in the output, such as `2` or `φ`.
+    pub base: String,
+    /// The number of fractional digits produced.
+    pub digits: usize,
+    /// Whether the expansion was cut off before it ended.
+    pub truncated: bool,
+    /// The expansion as written in the table, such as `0.0;1;`.
+    pub repr: String,
+}
+
+/// The names of the fields of a serialized `Conversion`.
+pub const CONVERSION_FIELDS: [&str; 5] = ["input", "base", "digits", "truncated", "repr"];
+
+impl Conversion {
+    /// Describes the expansion of an input in a base.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let conversion = Conversion::new(0.75, 2.0, &expand(0.75, 2, 8));
+    /// assert_eq!((conversion.digits, conversion.truncated), (2, false));
+    /// ```
+    pub fn new(input: f64, target_base: f64, expansion: &Expansion) -> Conversion {
+        Conversion {
+            label: None,
+            input,
+            base: format_base(target_base),
+            digits: expansion.digits.len(),
+            truncated: !expansion.is_exact(),
+            repr: expansion.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+    #[cfg(feature = "serde")]
+    use serde::de::value::{Error, MapDeserializer};
+    #[cfg(feature = "serde")]
+    use serde::de::IntoDeserializer;
+
+    /// Deserializes a conversion from a map whose values all have the type `V`.
+    #[cfg(feature = "serde")]
+    fn from_map<V>(pairs: Vec<(&str, V)>) -> Result<Conversion, Error>
+    where
+        V: for<'de> IntoDeserializer<'de, Error>,
+    {
+        Conversion::deserialize(MapDeserializer::new(pairs.into_iter()))
+    }
+
+    #[test]
+    fn test_conversion_of_expansion() {
+        let conversion = Conversion::new(0.1, 2.0, &expand(0.1, 2, 4));
+        assert_that!(
+            conversion,
+            equal_to(Conversion {
+                label: None,
+                input: 0.1,
+                base: "2".to_string(),
+                digits: 4,
+                truncated: true,
+                repr: "0.0;0;0;1;".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deserialize_conversion() {
+        // Every field is required, and no other is accepted.
+        assert!(from_map(vec![("input", 0.5)]).is_err());
+        assert!(from_map(vec![("precision", 8u32)]).is_err());
+
+        let strings = from_map(vec![("base", "2"), ("repr", "0.1;")]);
+        assert!(strings.unwrap_err().to_string().contains("input"));
+    }
+}
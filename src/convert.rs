@@ -0,0 +1,548 @@
+use std::fmt;
+
+use digits::F64Digits;
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::notation::{write_expansion, Semicolon};
+
+/// The default number of fractional digits produced for each conversion.
+pub const MAX_DIGITS: u32 = 8;
+
+/// The rule used to settle the last emitted digit when an expansion is cut
+/// off at the requested number of digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Drop every digit past the last one (the original behaviour).
+    Truncate,
+    /// Round the last digit up when the remainder is at least one half.
+    HalfUp,
+    /// Round the last digit up when the remainder exceeds one half, and on an
+    /// exact half only when that makes the last digit even.
+    HalfEven,
+}
+
+impl Rounding {
+    /// Parses a rounding mode name as used on the command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - One of `trunc`, `half-up` or `half-even`.
+    ///
+    /// # Returns
+    ///
+    /// The matching `Rounding`, or `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Rounding> {
+        match name {
+            "trunc" | "truncate" => Some(Rounding::Truncate),
+            "half-up" => Some(Rounding::HalfUp),
+            "half-even" => Some(Rounding::HalfEven),
+            _ => None,
+        }
+    }
+
+    /// The name of the rounding mode on the command line.
+    #[cfg(feature = "serde")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rounding::Truncate => "trunc",
+            Rounding::HalfUp => "half-up",
+            Rounding::HalfEven => "half-even",
+        }
+    }
+}
+
+/// A converted number that can be shown in a table column.
+pub trait Representation: fmt::Display {
+    /// Evaluates the digits back into a decimal number, i.e. the value
+    /// `integer + Σ dᵢ·base⁻ⁱ` that the emitted digits actually represent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(expand(0.1, 2, 4).value(), 0.0625);
+    /// ```
+    fn value(&self) -> f64;
+
+    /// Returns whether the digits represent the converted value exactly, i.e.
+    /// the expansion terminated within the digit limit and was not rounded.
+    fn is_exact(&self) -> bool;
+
+    /// Returns the base the digits are positional digits in, if they are,
+    /// so that the `Terminates?` column can tell whether the expansion ends.
+    fn base(&self) -> Option<f64> {
+        None
+    }
+
+    /// Returns how many fractional digits are written, for the
+    /// representations that have fractional digits in a base.
+    fn digit_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the digits of the integer part and the fractional digits,
+    /// most significant first, for the representations made of positional
+    /// digits in a base, so that the `Bit groups` column can regroup them.
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        None
+    }
+}
+
+/// Evaluates fractional digits as `Σ dᵢ·base⁻ⁱ`, starting from the least
+/// significant digit so that the smallest terms are added first.
+///
+/// # Arguments
+///
+/// * `digits` - The fractional digits, most significant first.
+/// * `target_base` - The base the digits are expressed in, which may be negative.
+pub fn evaluate_fraction<D: Copy + Into<f64>>(digits: &[D], target_base: f64) -> f64 {
+    digits
+        .iter()
+        .rev()
+        .fold(0.0, |value, &digit| (value + digit.into()) / target_base)
+}
+
+/// The largest digit a base allows: `b - 1` for an integer base `±b` and
+/// `⌈β⌉ - 1` for a non-integer base `β`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(largest_digit(16.0), 15);
+/// assert_eq!(largest_digit(-2.0), 1);
+/// assert_eq!(largest_digit(GOLDEN_RATIO), 1);
+/// ```
+pub fn largest_digit(base: f64) -> u32 {
+    (base.abs().ceil() - 1.0) as u32
+}
+
+/// The digits of a number in a target base, as produced by [`expand`],
+/// [`expand_negabase`] and `expand_beta`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    /// Whether the converted value was negative. Negative bases represent
+    /// negative values without a sign, so this is always `false` for them.
+    pub negative: bool,
+    /// The base the digits are expressed in, negative for e.g. negabinary and
+    /// not necessarily an integer.
+    pub base: f64,
+    /// The digits of the integer part, most significant first, and empty when
+    /// it is zero. Positive bases only have an integer part when rounding
+    /// carries out of the first fractional digit.
+    pub integer_digits: Vec<u32>,
+    /// The fractional digits, most significant first.
+    pub digits: Vec<u32>,
+    /// The part of the value left over after the last digit, measured in
+    /// units of the last digit's place. It is negative once the last digit
+    /// has been rounded up.
+    pub remainder: f64,
+}
+
+impl Expansion {
+    /// Applies a rounding rule to the last digit, carrying into the preceding
+    /// digits (and the integer part) as needed.
+    ///
+    /// Expansions in negative or non-integer bases are left as they are,
+    /// since there a digit that is one larger does not always make the value
+    /// larger, and carrying past the largest digit does not preserve it.
+    ///
+    /// # Arguments
+    ///
+    /// * `rounding` - The rounding rule to apply.
+    pub fn round(&mut self, rounding: Rounding) {
+        let last_is_odd = self.digits.last().is_some_and(|digit| digit % 2 == 1);
+        let round_up = match rounding {
+            Rounding::Truncate => false,
+            Rounding::HalfUp => self.remainder >= 0.5,
+            Rounding::HalfEven => self.remainder > 0.5 || (self.remainder == 0.5 && last_is_odd),
+        };
+
+        if !round_up || self.digits.is_empty() || self.base < 0.0 || self.base.fract() != 0.0 {
+            return;
+        }
+
+        self.remainder -= 1.0;
+        let largest = largest_digit(self.base);
+        for digit in self
+            .digits
+            .iter_mut()
+            .rev()
+            .chain(self.integer_digits.iter_mut().rev())
+        {
+            *digit += 1;
+            if *digit <= largest {
+                return;
+            }
+            *digit = 0;
+        }
+        self.integer_digits.insert(0, 1);
+    }
+}
+
+impl Representation for Expansion {
+    fn value(&self) -> f64 {
+        let integer = self
+            .integer_digits
+            .iter()
+            .fold(0.0, |value, &digit| value * self.base + digit as f64);
+        let value = integer + evaluate_fraction(&self.digits, self.base);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        self.remainder == 0.0
+    }
+
+    fn base(&self) -> Option<f64> {
+        Some(self.base)
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        Some(self.digits.len())
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        Some((&self.integer_digits, &self.digits))
+    }
+}
+
+impl Expansion {
+    /// Writes the expansion as `0.d;d;d;`, with every digit zero-padded to
+    /// `width` characters (no padding for a width of 0).
+    ///
+    /// The digits go straight into `f`, which may be a formatter or any
+    /// other sink such as a reused `String`, without a string per digit.
+    pub fn write_digits(&self, f: &mut impl fmt::Write, width: usize) -> fmt::Result {
+        write_expansion(f, self, &Semicolon { width })
+    }
+}
+
+impl fmt::Display for Expansion {
+    /// Writes the expansion as `0.d;d;d;`, with each fractional digit followed
+    /// by a `;` and the integer digits (if any) separated by `;`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_digits(f, 0)
+    }
+}
+
+/// Writes the integer part of `decimal` in the target base, and extracts up
+/// to `max_digits` fractional digits by repeatedly multiplying the fraction
+/// by the base.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `target_base` - The base to convert to.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// An `Expansion` holding the digits. Extraction stops early once the
+/// fraction is exhausted.
+///
+/// # Example
+///
+/// ```
+/// let expansion = expand(0.75, 2, MAX_DIGITS);
+/// assert_eq!(expansion.digits, vec![1, 1]);
+/// ```
+pub fn expand(decimal: f64, target_base: u64, max_digits: u32) -> Expansion {
+    // A whole number other than zero ends at its point, as in `1;0;0.`.
+    let whole = decimal.fract() == 0.0 && decimal != 0.0;
+    let max_digits = if whole { 0 } else { max_digits };
+    let mut extracted = F64Digits::new(decimal.fract(), target_base, max_digits);
+    let digits = extracted.by_ref().collect();
+
+    Expansion {
+        negative: decimal < 0.0,
+        base: target_base as f64,
+        integer_digits: integer_digits(integer_part(decimal), target_base),
+        digits,
+        remainder: extracted.remainder(),
+    }
+}
+
+/// The integer part of `|value|`, which every `f64` holds exactly.
+pub fn integer_part(value: f64) -> BigUint {
+    let integer = value.abs().trunc();
+    if integer == 0.0 {
+        return BigUint::zero();
+    }
+    let bits = integer.to_bits();
+    let exponent = (bits >> 52 & 0x7ff) as i32 - 1075;
+    let mantissa = BigUint::from(bits & ((1 << 52) - 1) | 1 << 52);
+    if exponent >= 0 {
+        mantissa << exponent as u32
+    } else {
+        mantissa >> (-exponent) as u32
+    }
+}
+
+/// Writes an integer in the target base, most significant digit first,
+/// returning no digits for zero.
+pub fn integer_digits(mut integer: BigUint, target_base: u64) -> Vec<u32> {
+    let mut digits = Vec::new();
+    while !integer.is_zero() {
+        digits.push((&integer % target_base).to_u32().unwrap_or(0));
+        integer /= target_base;
+    }
+    digits.reverse();
+    digits
+}
+
+/// Extracts up to `max_digits` fractional digits of `decimal` in the negative
+/// base `-magnitude`, such as negabinary for a magnitude of 2.
+///
+/// A negative base needs no sign: every value is a sum of non-negative digits
+/// times powers of the base. The digits after the point can only reach the
+/// values between `-b/(b+1)` and `1/(b+1)` (for `b = magnitude`), so an integer
+/// part is split off first to bring the fraction into that range. Each step
+/// then multiplies the fraction by the base and picks the one digit that keeps
+/// the new fraction inside the range again, rather than taking the floor.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `magnitude` - The magnitude of the negative base, at least 2.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// An `Expansion` holding the digits. Extraction stops early once the
+/// fraction is exhausted.
+///
+/// # Example
+///
+/// ```
+/// // 1.1 in negabinary is 1 + 1·(-2)⁻¹ = 0.5
+/// assert_eq!(expand_negabase(0.5, 2, MAX_DIGITS).to_string(), "1.1;");
+/// ```
+pub fn expand_negabase(decimal: f64, magnitude: u64, max_digits: u32) -> Expansion {
+    let b = magnitude as f64;
+    let upper = 1.0 / (b + 1.0);
+
+    let integer = (decimal - upper).ceil();
+    let mut fraction = decimal - integer;
+    let mut digits = Vec::new();
+
+    for _ in 0..max_digits {
+        let product = -b * fraction;
+        // The digit that leaves a fraction of at least `-b/(b+1)` is the
+        // floor of the product plus that, or the ceiling of the product less
+        // `1/(b+1)`, which unlike the bound near one survives being added
+        // to the product of a large base.
+        let digit = (product - upper).ceil().clamp(0.0, b - 1.0);
+        digits.push(digit as u32);
+        fraction = product - digit;
+
+        if fraction == 0.0 {
+            break;
+        }
+    }
+
+    Expansion {
+        negative: false,
+        base: -(magnitude as f64),
+        integer_digits: negabase_integer_digits(integer as i64, magnitude),
+        digits,
+        remainder: fraction,
+    }
+}
+
+/// Writes an integer in the negative base `-magnitude`, most significant
+/// digit first, returning no digits for zero.
+fn negabase_integer_digits(integer: i64, magnitude: u64) -> Vec<u32> {
+    // Wider than the integer, so that taking off a digit of `i64::MIN`,
+    // which infinite inputs saturate to, cannot overflow.
+    let mut integer = i128::from(integer);
+    let b = i128::from(magnitude);
+    let mut digits = Vec::new();
+    while integer != 0 {
+        let digit = integer.rem_euclid(b);
+        digits.push(digit as u32);
+        integer = (integer - digit) / -b;
+    }
+    digits.reverse();
+    digits
+}
+
+/// Converts a decimal number (f64) to its target base representation as a string.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `target_base` - The base to convert to.
+///
+/// # Returns
+///
+/// A `String` containing the target base representation of the input `decimal`
+/// Each digit is seperated by a ; for easier readability
+///
+/// # Example
+///
+/// ```
+/// let binary = convert_from_decimal_to_binary(0.5, 2);
+/// assert_eq!(binary, "0.1;");
+/// ```
+#[cfg(test)]
+pub fn convert_from_decimal_to_binary(decimal: f64, target_base: u64) -> String {
+    expand(decimal, target_base, MAX_DIGITS).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_conversion_base_2() {
+        assert_that!(convert_from_decimal_to_binary(0.5, 2), equal_to("0.1;"));
+        assert_that!(convert_from_decimal_to_binary(0.25, 2), equal_to("0.0;1;"));
+        assert_that!(convert_from_decimal_to_binary(0.75, 2), equal_to("0.1;1;"));
+        assert_that!(
+            convert_from_decimal_to_binary(0.125, 2),
+            equal_to("0.0;0;1;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.6875, 2),
+            equal_to("0.1;0;1;1;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.7, 2),
+            equal_to("0.1;0;1;1;0;0;1;1;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.8, 2),
+            equal_to("0.1;1;0;0;1;1;0;0;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.9, 2),
+            equal_to("0.1;1;1;0;0;1;1;0;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.6, 2),
+            equal_to("0.1;0;0;1;1;0;0;1;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.3, 2),
+            equal_to("0.0;1;0;0;1;1;0;0;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.1, 2),
+            equal_to("0.0;0;0;1;1;0;0;1;")
+        );
+    }
+
+    #[test]
+    fn test_conversion_base_8() {
+        assert_that!(convert_from_decimal_to_binary(0.5, 8), equal_to("0.4;"));
+        assert_that!(convert_from_decimal_to_binary(0.25, 8), equal_to("0.2;"));
+        assert_that!(convert_from_decimal_to_binary(0.75, 8), equal_to("0.6;"));
+        assert_that!(
+            convert_from_decimal_to_binary(0.8, 8),
+            equal_to("0.6;3;1;4;6;3;1;4;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.16666, 8),
+            equal_to("0.1;2;5;2;5;0;7;2;")
+        );
+    }
+
+    #[test]
+    fn test_conversion_base_16() {
+        assert_that!(convert_from_decimal_to_binary(0.5, 16), equal_to("0.8;"));
+        assert_that!(convert_from_decimal_to_binary(0.25, 16), equal_to("0.4;"));
+        assert_that!(convert_from_decimal_to_binary(0.75, 16), equal_to("0.12;"));
+        assert_that!(
+            convert_from_decimal_to_binary(0.8, 16),
+            equal_to("0.12;12;12;12;12;12;12;12;")
+        );
+        assert_that!(
+            convert_from_decimal_to_binary(0.16666, 16),
+            equal_to("0.2;10;10;10;3;10;13;1;")
+        );
+    }
+
+    #[test]
+    fn test_conversion_base_60() {
+        assert_that!(convert_from_decimal_to_binary(0.5, 60), equal_to("0.30;"));
+        assert_that!(convert_from_decimal_to_binary(0.25, 60), equal_to("0.15;"));
+        assert_that!(convert_from_decimal_to_binary(0.75, 60), equal_to("0.45;"));
+        assert_that!(convert_from_decimal_to_binary(0.8, 60), equal_to("0.48;"));
+        assert_that!(
+            convert_from_decimal_to_binary(0.16666, 60),
+            equal_to("0.9;59;58;33;36;0;0;0;")
+        );
+    }
+
+    #[test]
+    fn test_integer_part() {
+        assert_that!(expand(2.75, 2, 8).to_string(), equal_to("1;0.1;1;"));
+        assert_that!(expand(255.5, 16, 8).to_string(), equal_to("15;15.8;"));
+        assert_that!(expand(-3.5, 2, 8).to_string(), equal_to("-1;1.1;"));
+        assert_that!(expand(4.0, 2, 8).to_string(), equal_to("1;0;0."));
+    }
+
+    #[test]
+    fn test_rounding_last_digit() {
+        let mut half_up = expand(0.7, 2, 3);
+        half_up.round(Rounding::HalfUp);
+        assert_that!(half_up.to_string(), equal_to("0.1;1;0;"));
+
+        let mut carried = expand(0.875, 2, 2);
+        carried.round(Rounding::HalfEven);
+        assert_that!(carried.to_string(), equal_to("1.0;0;"));
+
+        let mut to_even = expand(0.625, 2, 2);
+        to_even.round(Rounding::HalfEven);
+        assert_that!(to_even.to_string(), equal_to("0.1;0;"));
+    }
+
+    #[test]
+    fn test_negabinary_expansions() {
+        assert_that!(expand_negabase(0.5, 2, 8).to_string(), equal_to("1.1;"));
+        assert_that!(expand_negabase(0.25, 2, 8).to_string(), equal_to("0.0;1;"));
+        assert_that!(expand_negabase(-0.5, 2, 8).to_string(), equal_to("0.1;"));
+        assert_that!(expand_negabase(0.75, 2, 8).to_string(), equal_to("1.1;1;"));
+        assert_that!(
+            expand_negabase(-0.75, 2, 8).to_string(),
+            equal_to("1;1.0;1;")
+        );
+        assert_that!(
+            expand_negabase(1.0 / 3.0, 2, 6).to_string(),
+            equal_to("0.0;1;0;1;0;1;")
+        );
+        assert_that!(expand_negabase(0.9, 2, 1).integer_digits, equal_to(vec![1]));
+        assert_that!(expand_negabase(2.0, 2, 8).to_string(), equal_to("1;1;0.0;"));
+        assert_that!(
+            expand_negabase(-0.9, 2, 2).to_string(),
+            equal_to("1;1.0;1;")
+        );
+    }
+
+    #[test]
+    fn test_negabase_value() {
+        for decimal in [0.5, 0.25, -0.5, 0.75, -0.75, 0.3, -0.6] {
+            let expansion = expand_negabase(decimal, 2, 40);
+            assert!((expansion.value() - decimal).abs() < 1e-9, "{}", decimal);
+        }
+        let expansion = expand_negabase(0.37, 10, 12);
+        assert!((expansion.value() - 0.37).abs() < 1e-9);
+        assert!(expansion.digits.iter().all(|&digit| digit < 10));
+    }
+
+    #[test]
+    fn test_expansion_value() {
+        assert_that!(expand(0.6875, 2, MAX_DIGITS).value(), equal_to(0.6875));
+        assert_that!(expand(0.1, 2, 4).value(), equal_to(0.0625));
+        assert_that!(expand(-0.75, 8, MAX_DIGITS).value(), equal_to(-0.75));
+
+        let mut rounded = expand(0.875, 2, 2);
+        rounded.round(Rounding::HalfUp);
+        assert_that!(rounded.value(), equal_to(1.0));
+    }
+}
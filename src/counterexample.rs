@@ -0,0 +1,187 @@
+use std::io::{self, Write};
+
+use crate::backend::Backend;
+use crate::cli::flag_value;
+use crate::find::{parse_digits, parse_single_base, ScanRange};
+use crate::pipeline::parse_backend;
+
+/// The settings of the `counterexamples` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterexampleOptions {
+    /// The decimals to search through.
+    pub range: ScanRange,
+    /// The base the truncated expansions are computed in.
+    pub target_base: u64,
+    /// The number of digits every expansion is truncated to.
+    pub digits: u32,
+    /// The maximum number of pairs to report.
+    pub limit: usize,
+    /// The backend forced with `--backend`, or `None` to pick an exact one
+    /// per decimal.
+    pub backend: Option<Backend>,
+}
+
+/// Two unequal decimals whose truncated expansions are identical.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    /// The smaller decimal.
+    pub first: f64,
+    /// The larger decimal.
+    pub second: f64,
+    /// The truncated expansion both decimals share.
+    pub representation: String,
+}
+
+/// Parses the arguments of the `counterexamples` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `counterexamples`.
+///
+/// # Returns
+///
+/// The parsed `CounterexampleOptions`, or a message describing why the
+/// arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- counterexamples --base 2 --digits 3 --limit 3
+/// ```
+pub fn parse_counterexample_args(args: &[String]) -> Result<CounterexampleOptions, String> {
+    let mut options = CounterexampleOptions {
+        range: ScanRange::default(),
+        target_base: 2,
+        digits: 4,
+        limit: 5,
+        backend: None,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else if let Some(value) = flag_value(arg, "--limit", &mut iter)? {
+            options.limit = value
+                .parse::<usize>()
+                .map_err(|_| format!("invalid limit `{}`", value))?;
+        } else if let Some(value) = flag_value(arg, "--backend", &mut iter)? {
+            options.backend = parse_backend(value)?;
+        } else if !options.range.parse_flag(arg, &mut iter)? {
+            return Err(format!("unknown counterexamples argument `{}`", arg));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Searches the range for neighbouring decimals that differ but truncate to
+/// the same expansion, which are ready-made examples of why a fixed number
+/// of digits cannot tell every value apart.
+///
+/// # Returns
+///
+/// Up to `limit` collisions, in the order they are found.
+///
+/// # Example
+/// ```
+/// let options = parse_counterexample_args(&[]).unwrap();
+/// let collisions = find_collisions(&options);
+/// assert_eq!((collisions[0].first, collisions[0].second), (0.0, 0.01));
+/// ```
+pub fn find_collisions(options: &CounterexampleOptions) -> Vec<Collision> {
+    let mut collisions = Vec::new();
+    let mut previous: Option<(f64, String)> = None;
+
+    for (value, fraction) in options.range.values() {
+        if collisions.len() >= options.limit {
+            break;
+        }
+
+        let mut expansion = Backend::expand_auto(
+            options.backend,
+            value,
+            fraction.as_ref(),
+            options.target_base,
+            options.digits,
+        );
+        // Pad terminating expansions so that e.g. `0.1;` and `0.1;0;` compare equal.
+        expansion.digits.resize(options.digits as usize, 0);
+        let representation = expansion.to_string();
+
+        if let Some((first, previous_representation)) = &previous {
+            if *previous_representation == representation {
+                collisions.push(Collision {
+                    first: *first,
+                    second: value,
+                    representation: representation.clone(),
+                });
+            }
+        }
+        previous = Some((value, representation));
+    }
+
+    collisions
+}
+
+/// Runs the `counterexamples` subcommand, printing one line per collision.
+///
+/// # Returns
+///
+/// Whether any collision was found, or the error writing to `out`.
+pub fn display_counterexamples(
+    out: &mut impl Write,
+    options: &CounterexampleOptions,
+) -> io::Result<bool> {
+    let collisions = find_collisions(options);
+    for collision in &collisions {
+        writeln!(
+            out,
+            "{} and {} both truncate to {} in base {} with {} digits",
+            collision.first,
+            collision.second,
+            collision.representation,
+            options.target_base,
+            options.digits
+        )?;
+    }
+    if collisions.is_empty() {
+        writeln!(
+            out,
+            "no two decimals from {} to {} share a {} digit expansion in base {}",
+            options.range.from, options.range.to, options.digits, options.target_base
+        )?;
+    }
+    Ok(!collisions.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_collisions_share_truncations() {
+        let options =
+            parse_counterexample_args(&args("--base 2 --digits 3 --from 0.5 --limit 2")).unwrap();
+        let collisions = find_collisions(&options);
+        assert_that!(collisions.len(), equal_to(2));
+        assert_that!(collisions[0].first, equal_to(0.5));
+        assert_that!(collisions[0].second, equal_to(0.51));
+        assert_that!(&collisions[0].representation, equal_to("0.1;0;0;"));
+        assert_that!(collisions[1].first, equal_to(0.51));
+    }
+
+    #[test]
+    fn test_no_collisions_with_enough_digits() {
+        let options = parse_counterexample_args(&args("--base 2 --digits 8")).unwrap();
+        assert!(find_collisions(&options).is_empty());
+        assert!(parse_counterexample_args(&args("--limit x")).is_err());
+        assert!(parse_counterexample_args(&args("0.5")).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use std::io::{BufRead, Write};
+
+use crate::beta::format_base;
+use crate::cli::flag_value;
+use crate::exit::write_error_message;
+use crate::format::{write_csv_field, write_tsv_field, FieldWriter};
+use crate::output::{open_output, OutputOptions};
+use crate::pipeline::{parse_base_list, Pipeline};
+use crate::stream::{open_input, STDIN_PATH};
+
+/// The settings of the `csv` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// The CSV file to read, or `-` for standard input.
+    pub path: String,
+    /// The column holding the values, as its header or its 1-based number.
+    pub column: String,
+    /// Converts the values, one appended column per target base.
+    pub pipeline: Pipeline,
+    /// Where the extended CSV goes.
+    pub output: OutputOptions,
+}
+
+/// Parses the arguments of the `csv` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `csv`.
+///
+/// # Returns
+///
+/// The parsed `CsvOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- csv grades.csv --column value --base 2,16 --output converted.csv
+/// ```
+pub fn parse_csv_args(args: &[String]) -> Result<CsvOptions, String> {
+    let mut options = CsvOptions {
+        path: STDIN_PATH.to_string(),
+        column: "1".to_string(),
+        pipeline: Pipeline::with_bases(vec![2.0]),
+        output: OutputOptions::default(),
+    };
+    let mut path = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--column", &mut iter)? {
+            options.column = value.to_string();
+        } else if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.pipeline.target_bases = parse_base_list(value)?;
+        } else if let Some(value) = flag_value(arg, "--precision", &mut iter)? {
+            options.pipeline.digits = value
+                .parse()
+                .map_err(|_| format!("invalid precision `{}`", value))?;
+        } else if let Some(value) = flag_value(arg, "--output", &mut iter)? {
+            options.output.path = Some(value.to_string());
+        } else if arg == "--force" {
+            options.output.force = true;
+        } else if arg.starts_with("--") || path.is_some() {
+            return Err(format!("unknown csv argument `{}`", arg));
+        } else {
+            path = Some(arg.clone());
+        }
+    }
+
+    if let Some(path) = path {
+        options.path = path;
+    }
+    Ok(options)
+}
+
+/// Splits a CSV line into its fields, unquoting the quoted ones.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(split_fields(r#"a,"b, ""c""",d"#), vec!["a", r#"b, "c""#, "d"]);
+/// ```
+pub fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("there is always a field");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Splits a TSV line into its fields, unescaping the `\t`, `\n`, `\r` and
+/// `\\` that `--format tsv` writes for the characters TSV cannot hold.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(split_tsv_fields("a\\tb\tc"), vec!["a\tb", "c"]);
+/// ```
+pub fn split_tsv_fields(line: &str) -> Vec<String> {
+    line.split('\t')
+        .map(|field| {
+            let mut unescaped = String::with_capacity(field.len());
+            let mut chars = field.chars();
+            while let Some(c) = chars.next() {
+                match (c, chars.clone().next()) {
+                    ('\\', Some(escaped @ ('t' | 'n' | 'r' | '\\'))) => {
+                        chars.next();
+                        unescaped.push(match escaped {
+                            't' => '\t',
+                            'n' => '\n',
+                            'r' => '\r',
+                            _ => '\\',
+                        });
+                    }
+                    (c, _) => unescaped.push(c),
+                }
+            }
+            unescaped
+        })
+        .collect()
+}
+
+/// Whether a table whose header row this is has its fields separated by
+/// tabs rather than commas, as TSV exports do.
+pub fn is_tsv(header: &str) -> bool {
+    header.contains('\t')
+}
+
+/// Splits a CSV line, or a TSV line if `tsv` is set, into its fields.
+pub fn split_record(line: &str, tsv: bool) -> Vec<String> {
+    if tsv {
+        split_tsv_fields(line)
+    } else {
+        split_fields(line)
+    }
+}
+
+/// Finds the index of a column from its header or its 1-based number.
+fn find_column(headers: &[String], column: &str) -> Result<usize, String> {
+    if let Some(index) = headers.iter().position(|header| header.trim() == column) {
+        return Ok(index);
+    }
+    match column.parse::<usize>() {
+        Ok(number) if number >= 1 && number <= headers.len() => Ok(number - 1),
+        _ => Err(format!("the CSV has no column `{}`", column)),
+    }
+}
+
+/// Copies a CSV from `reader` to `out`, appending to every row the
+/// conversion of its value in `column` to every target base, under headers
+/// such as `base_16`. The rows are otherwise written exactly as read. A
+/// table whose header has tabs is read and extended as TSV.
+///
+/// # Returns
+///
+/// The line numbers and values of the rows whose value cannot be converted,
+/// which get empty cells, or a message if the CSV cannot be read.
+pub fn append_columns(
+    reader: impl BufRead,
+    out: &mut dyn Write,
+    column: &str,
+    pipeline: &Pipeline,
+) -> Result<Vec<(usize, String)>, String> {
+    let mut lines = reader.lines();
+    let header = match lines.next() {
+        Some(header) => header.map_err(|error| error.to_string())?,
+        None => return Err("the CSV is empty".to_string()),
+    };
+    let tsv = is_tsv(&header);
+    let index = find_column(&split_record(&header, tsv), column)?;
+    let (delimiter, write_field): (char, FieldWriter) = if tsv {
+        ('\t', write_tsv_field)
+    } else {
+        (',', write_csv_field)
+    };
+
+    write!(out, "{}", header).map_err(write_error_message)?;
+    for &target_base in &pipeline.target_bases {
+        write!(out, "{}base_{}", delimiter, format_base(target_base))
+            .map_err(write_error_message)?;
+    }
+    writeln!(out).map_err(write_error_message)?;
+
+    let mut skipped = Vec::new();
+    for (number, line) in (2..).zip(lines) {
+        let line = line.map_err(|error| error.to_string())?;
+        if pipeline.cancel.is_cancelled() {
+            break;
+        }
+        write!(out, "{}", line).map_err(write_error_message)?;
+        if line.trim().is_empty() {
+            writeln!(out).map_err(write_error_message)?;
+            continue;
+        }
+
+        let fields = split_record(&line, tsv);
+        let value = fields.get(index).map_or("", |value| value.trim());
+        match pipeline.decode_input(value) {
+            Some((input, fraction)) => {
+                for expansion in pipeline.expand(input, fraction.as_ref()) {
+                    write!(out, "{}", delimiter).map_err(write_error_message)?;
+                    write_field(out, &expansion.to_string()).map_err(write_error_message)?;
+                }
+            }
+            None => {
+                skipped.push((number, value.to_string()));
+                for _ in &pipeline.target_bases {
+                    write!(out, "{}", delimiter).map_err(write_error_message)?;
+                }
+            }
+        }
+        writeln!(out).map_err(write_error_message)?;
+    }
+    Ok(skipped)
+}
+
+/// Reads the cells of one column of a CSV with a header row, for
+/// `--file data.csv --column price`, or of a TSV if the header has tabs.
+///
+/// # Arguments
+///
+/// * `text` - The CSV.
+/// * `column` - The column, as its header or its 1-based number.
+///
+/// # Returns
+///
+/// The line number and trimmed cell of every row that is not blank, in
+/// order, or a message if the CSV is empty or has no such column.
+///
+/// # Example
+///
+/// ```
+/// let cells = column_values("item,price\npen,0.5\n", "price").unwrap();
+/// assert_eq!(cells, vec![(2, "0.5".to_string())]);
+/// ```
+pub fn column_values(text: &str, column: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut lines = text.lines();
+    let header = lines.next().ok_or("the CSV is empty")?;
+    let tsv = is_tsv(header);
+    let index = find_column(&split_record(header, tsv), column)?;
+    Ok((2..)
+        .zip(lines)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            let fields = split_record(line, tsv);
+            let cell = fields.get(index).map_or("", |cell| cell.trim());
+            (number, cell.to_string())
+        })
+        .collect())
+}
+
+/// Runs the `csv` subcommand, warning on standard error about every value
+/// that cannot be converted.
+///
+/// # Returns
+///
+/// Whether the CSV was read and written.
+pub fn display_csv(options: &CsvOptions) -> bool {
+    let result = open_input(&options.path)
+        .map_err(|error| error.to_string())
+        .and_then(|reader| {
+            let mut out = open_output(&options.output)?;
+            let skipped = append_columns(reader, &mut *out, &options.column, &options.pipeline)?;
+            out.flush().map_err(write_error_message)?;
+            Ok(skipped)
+        });
+
+    match result {
+        Ok(skipped) => {
+            for (line, value) in skipped {
+                eprintln!("warning: line {}: cannot convert `{}`", line, value);
+            }
+            true
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_append_columns() {
+        let csv = "name,value\n\
+                   \"Doe, Jane\",0.5\n\
+                   Roe,1/3\n\
+                   \n\
+                   Poe,n/a\n";
+        let pipeline = Pipeline {
+            digits: 4,
+            ..Pipeline::with_bases(vec![2.0, 3.0])
+        };
+        let mut out = Vec::new();
+        let skipped = append_columns(csv.as_bytes(), &mut out, "value", &pipeline).unwrap();
+        assert_that!(
+            String::from_utf8(out.clone()).unwrap(),
+            equal_to(
+                "name,value,base_2,base_3\n\
+                 \"Doe, Jane\",0.5,0.1;,0.1;1;1;1;\n\
+                 Roe,1/3,0.0;1;0;1;,0.1;\n\
+                 \n\
+                 Poe,n/a,,\n"
+                    .to_string()
+            )
+        );
+        assert_that!(skipped, equal_to(vec![(5, "n/a".to_string())]));
+
+        let mut by_number = Vec::new();
+        append_columns(csv.as_bytes(), &mut by_number, "2", &pipeline).unwrap();
+        assert_that!(by_number, equal_to(out));
+        assert!(append_columns(csv.as_bytes(), &mut Vec::new(), "score", &pipeline).is_err());
+        assert!(append_columns("".as_bytes(), &mut Vec::new(), "1", &pipeline).is_err());
+    }
+
+    #[test]
+    fn test_tsv_tables() {
+        assert_that!(
+            split_tsv_fields("a\\tb\t\\\\n\t"),
+            equal_to(vec!["a\tb".to_string(), "\\n".to_string(), String::new()])
+        );
+        let tsv = "name\tvalue\nDoe, Jane\t0.5\nPoe\tn/a\n";
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let mut out = Vec::new();
+        let skipped = append_columns(tsv.as_bytes(), &mut out, "value", &pipeline).unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("name\tvalue\tbase_2\nDoe, Jane\t0.5\t0.1;\nPoe\tn/a\t\n".to_string())
+        );
+        assert_that!(skipped, equal_to(vec![(3, "n/a".to_string())]));
+        assert_that!(
+            column_values(tsv, "value").unwrap(),
+            equal_to(vec![(2, "0.5".to_string()), (3, "n/a".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_column_values() {
+        let csv = "item,price\n\
+                   \"pen, blue\",0.5\n\
+                   \n\
+                   ink, 1/3 \n\
+                   cap\n";
+        assert_that!(
+            column_values(csv, "price").unwrap(),
+            equal_to(vec![
+                (2, "0.5".to_string()),
+                (4, "1/3".to_string()),
+                (5, String::new()),
+            ])
+        );
+        assert_that!(
+            column_values(csv, "1").unwrap()[0].clone(),
+            equal_to((2, "pen, blue".to_string()))
+        );
+        assert!(column_values(csv, "cost").is_err());
+        assert!(column_values("", "price").is_err());
+    }
+
+    #[test]
+    fn test_csv_arguments() {
+        let args: Vec<String> = [
+            "data.csv",
+            "--column=value",
+            "--base",
+            "16",
+            "--precision",
+            "4",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let options = parse_csv_args(&args).unwrap();
+        assert_that!(options.path.as_str(), equal_to("data.csv"));
+        assert_that!(options.pipeline.target_bases, equal_to(vec![16.0]));
+        assert_that!(parse_csv_args(&[]).unwrap().path.as_str(), equal_to("-"));
+        assert!(parse_csv_args(&["a.csv".to_string(), "b.csv".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use num_bigint::BigUint;
+use rust_decimal::Decimal;
+
+use crate::rational::Fraction;
+
+/// Reads a decimal such as `-1234.50`, with an optional sign, exactly: one
+/// with more than 28 fractional digits or beyond 96 bits is refused rather
+/// than rounded, as by `Decimal::from_str_exact`.
+///
+/// # Returns
+///
+/// The decimal, or a message if the text is not one.
+///
+/// # Example
+///
+/// ```
+/// let price = parse_decimal("19.99").unwrap();
+/// assert_eq!((price.mantissa(), price.scale()), (1999, 2));
+/// assert!(parse_decimal("0.1e3").is_err());
+/// ```
+pub fn parse_decimal(text: &str) -> Result<Decimal, String> {
+    Decimal::from_str_exact(text).map_err(|error| format!("`{}` is not a decimal: {}", text, error))
+}
+
+impl From<Decimal> for Fraction {
+    /// The exact fraction of a decimal, its mantissa over 10 to the power
+    /// of its scale.
+    fn from(decimal: Decimal) -> Fraction {
+        Fraction {
+            negative: decimal.is_sign_negative() && !decimal.is_zero(),
+            numerator: BigUint::from(decimal.mantissa().unsigned_abs()),
+            denominator: BigUint::from(10u32).pow(decimal.scale()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_parse_decimals() {
+        let price = parse_decimal("-1234.50").unwrap();
+        assert_that!(price.mantissa(), equal_to(-123450));
+        assert_that!(price.scale(), equal_to(2));
+        assert_that!(
+            parse_decimal("0.0000000000000000000000000001"),
+            equal_to(Ok(Decimal::from_i128_with_scale(1, 28)))
+        );
+        assert_that!(parse_decimal(".5").unwrap().scale(), equal_to(1));
+        assert!(parse_decimal("0.00000000000000000000000000001").is_err());
+        assert!(parse_decimal("79228162514264337593543950336").is_err());
+        assert!(parse_decimal("79228162514264337593543950335").is_ok());
+        for invalid in ["", ".", "1/3", "1e5", "0x1", "1.2.3"] {
+            assert!(parse_decimal(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_decimal_fractions() {
+        let fraction = Fraction::from(Decimal::from_i128_with_scale(-25, 2));
+        assert_that!(fraction.value(), equal_to(-0.25));
+        assert_that!(fraction.denominator, equal_to(BigUint::from(100u32)));
+        let huge = Decimal::from_parts(1, 0, 1, true, 3);
+        assert_that!(
+            Fraction::from(huge).numerator,
+            equal_to(BigUint::from((1u128 << 64) + 1))
+        );
+        assert!(!Fraction::from(parse_decimal("-0").unwrap()).negative);
+    }
+}
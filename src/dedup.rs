@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+
+use crate::rational::Fraction;
+use crate::terminate::{exact_input, gcd};
+
+/// The title of the column giving how many times every input was given.
+pub const COUNT_HEADER: &str = "Count";
+
+/// What two inputs must share to count as the same value: their exact value
+/// in lowest terms, or the bits of their `f64` for infinities and NaN.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Exact(bool, BigUint, BigUint),
+    Bits(u64),
+}
+
+/// The key of one input, under which `0.5`, `1/2` and `2/4` all fall.
+fn key(number: f64, fraction: Option<&Fraction>) -> Key {
+    match exact_input(number, fraction) {
+        Some(exact) => {
+            let divisor = gcd(exact.numerator.clone(), exact.denominator.clone());
+            Key::Exact(
+                exact.negative,
+                exact.numerator / &divisor,
+                exact.denominator / &divisor,
+            )
+        }
+        None => Key::Bits(number.to_bits()),
+    }
+}
+
+/// Collapses inputs with the same value into the first of them.
+///
+/// # Returns
+///
+/// The distinct inputs in the order they first appeared, and how many
+/// times each was given.
+///
+/// # Example
+///
+/// ```
+/// let inputs = vec![(0.5, None), (0.25, None), (0.5, Fraction::parse("1/2"))];
+/// let (distinct, counts) = dedup_inputs(inputs);
+/// assert_eq!(distinct, vec![(0.5, None), (0.25, None)]);
+/// assert_eq!(counts, vec![2, 1]);
+/// ```
+pub fn dedup_inputs(
+    inputs: Vec<(f64, Option<Fraction>)>,
+) -> (Vec<(f64, Option<Fraction>)>, Vec<usize>) {
+    let mut rows: HashMap<Key, usize> = HashMap::new();
+    let mut distinct = Vec::new();
+    let mut counts = Vec::new();
+    for (number, fraction) in inputs {
+        let row = *rows
+            .entry(key(number, fraction.as_ref()))
+            .or_insert(distinct.len());
+        if row == distinct.len() {
+            distinct.push((number, fraction));
+            counts.push(0);
+        }
+        counts[row] += 1;
+    }
+    (distinct, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_dedup_inputs() {
+        let inputs = vec![
+            (0.1, None),
+            (0.5, None),
+            (0.1, Fraction::parse("1/10")),
+            (0.5, Fraction::parse("2/4")),
+            (-0.5, None),
+            (f64::NAN, None),
+            (f64::NAN, None),
+            (0.1, None),
+        ];
+        let (distinct, counts) = dedup_inputs(inputs);
+        let values: Vec<f64> = distinct.iter().map(|(value, _)| *value).collect();
+        assert_that!(values[..3].to_vec(), equal_to(vec![0.1, 0.5, -0.5]));
+        assert!(values[3].is_nan());
+        assert_that!(counts, equal_to(vec![3, 2, 1, 2]));
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use crate::comment::strip_comment;
+
+/// The longest input whose every prefix is tried to find where it goes
+/// wrong; a longer one is underlined whole.
+const MAX_PROBED_LENGTH: usize = 256;
+
+/// Where an input was read: a line of a file, or the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// The file the input is in, or `None` for the command line.
+    pub path: Option<String>,
+    /// The 1-based number of the line of the file, or of the argument on
+    /// the command line.
+    pub line: usize,
+    /// The whole line, or the arguments of the command line separated by
+    /// spaces.
+    pub text: String,
+    /// The byte offset of the input in `text`.
+    pub offset: usize,
+}
+
+/// An input of some source, with where it is in it, before the line is
+/// copied into a [`Span`] for the invalid ones.
+#[derive(Debug, Clone, Copy)]
+pub struct Placed<'a> {
+    /// The input.
+    pub word: &'a str,
+    /// The fields of its [`Span`], borrowed from the source.
+    pub path: Option<&'a str>,
+    /// The number of its line or argument.
+    pub line: usize,
+    /// Its line, or the command line.
+    pub text: &'a str,
+    /// Its byte offset in `text`.
+    pub offset: usize,
+}
+
+/// The positional arguments of a command line, placed in the arguments
+/// written out as [`Span`] shows them.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the command line.
+/// * `command_line` - The arguments joined by spaces.
+/// * `positional` - The positional arguments, which are elements of `args`.
+pub fn argument_words<'a>(
+    args: &'a [String],
+    command_line: &'a str,
+    positional: &[&'a str],
+) -> Vec<Placed<'a>> {
+    positional
+        .iter()
+        .filter_map(|&word| {
+            // An argument is found by its address rather than its text, as
+            // the value of a flag may be written the same way.
+            let index = args
+                .iter()
+                .position(|arg| std::ptr::eq(arg.as_str(), word))?;
+            let offset = args[..index].iter().map(|arg| arg.len() + 1).sum();
+            Some(Placed {
+                word,
+                path: None,
+                line: index + 1,
+                text: command_line,
+                offset,
+            })
+        })
+        .collect()
+}
+
+/// The inputs of the text of an input file, as `input_words` splits them,
+/// each placed on its line.
+pub fn line_words<'a>(path: &'a str, text: &'a str) -> impl Iterator<Item = Placed<'a>> {
+    text.lines().enumerate().flat_map(move |(i, line)| {
+        let code = strip_comment(line);
+        code.split_whitespace().map(move |word| Placed {
+            word,
+            path: Some(path),
+            line: i + 1,
+            text: line,
+            // The word is a part of the line itself.
+            offset: word.as_ptr() as usize - line.as_ptr() as usize,
+        })
+    })
+}
+
+/// Finds the span of every invalid input among the inputs of its sources.
+///
+/// The invalid inputs come in the order of the sources, so every one is the
+/// next input with its text.
+///
+/// # Returns
+///
+/// A span for every invalid input, or `None` for those not found.
+pub fn find_spans<'a>(
+    words: impl IntoIterator<Item = Placed<'a>>,
+    invalid: &[String],
+) -> Vec<Option<Span>> {
+    let mut spans = Vec::with_capacity(invalid.len());
+    let mut words = words.into_iter();
+    for input in invalid {
+        let span = words
+            .by_ref()
+            .find(|placed| placed.word == input)
+            .map(|placed| Span {
+                path: placed.path.map(String::from),
+                line: placed.line,
+                text: placed.text.to_string(),
+                offset: placed.offset,
+            });
+        let found = span.is_some();
+        spans.push(span);
+        if !found {
+            // The rest cannot be found after this one.
+            spans.resize(invalid.len(), None);
+            break;
+        }
+    }
+    spans
+}
+
+/// Finds the part of an invalid input that makes it invalid: the first
+/// character after its longest prefix that is still a valid input, with
+/// what is wrong with it.
+///
+/// # Arguments
+///
+/// * `input` - The invalid input.
+/// * `reason` - Why the input is invalid, if that is already known, such
+///   as an exponent beyond every `f64`, which is then underlined.
+/// * `valid` - Whether a text is a valid input.
+///
+/// # Returns
+///
+/// The byte range of the offending part and its label.
+///
+/// # Example
+///
+/// ```
+/// let valid = |text: &str| text.parse::<f64>().is_ok();
+/// assert_eq!(locate_error("0,5", None, valid).0, 1..2);
+/// ```
+pub fn locate_error(
+    input: &str,
+    reason: Option<&str>,
+    valid: impl Fn(&str) -> bool,
+) -> (Range<usize>, String) {
+    if let Some(reason) = reason {
+        let exponent = input.find(['e', 'E']).unwrap_or(0);
+        return (exponent..input.len(), reason.to_string());
+    }
+    if let Some((_, denominator)) = input.rsplit_once('/') {
+        if !denominator.is_empty() && denominator.bytes().all(|byte| byte == b'0') {
+            let start = input.len() - denominator.len();
+            return (start..input.len(), "the denominator is zero".to_string());
+        }
+    }
+    let whole = (0..input.len(), "not a number".to_string());
+    if input.len() > MAX_PROBED_LENGTH {
+        return whole;
+    }
+    let Some(end) = input
+        .char_indices()
+        .map(|(i, _)| i)
+        .skip(1)
+        .filter(|&i| valid(&input[..i]))
+        .last()
+    else {
+        return whole;
+    };
+    let offending = input[end..].chars().next().unwrap_or_default();
+    let range = end..end + offending.len_utf8();
+    let label = if offending == ',' {
+        let pointed = format!("{}.{}", &input[..end], &input[end + 1..]);
+        if valid(&pointed) {
+            format!(
+                "a comma is not a decimal point; write `{}`, or pass --decimal-comma",
+                pointed
+            )
+        } else {
+            "a stray comma".to_string()
+        }
+    } else if offending.is_alphanumeric() {
+        format!("`{}` is not a digit here", offending)
+    } else {
+        format!("unexpected `{}`", offending)
+    };
+    (range, label)
+}
+
+/// Draws the line an input was read on, with the part of it that is wrong
+/// underlined, to go below a warning or error about it:
+///
+/// ```text
+///   --> inputs.txt:3:5
+///    |
+///  3 | 0.1 0,5
+///    |      ^ a comma is not a decimal point; write `0.5`, or pass --decimal-comma
+/// ```
+///
+/// # Arguments
+///
+/// * `span` - Where the input was read.
+/// * `error` - The byte range in the input that is wrong, and its label.
+pub fn snippet(span: &Span, (range, label): &(Range<usize>, String)) -> String {
+    let start = span.offset + range.start;
+    let end = (span.offset + range.end).min(span.text.len());
+    let column = span.text[..start].chars().count() + 1;
+    let width = span.text[start..end].chars().count().max(1);
+    let number = match &span.path {
+        Some(_) => span.line.to_string(),
+        None => String::new(),
+    };
+    let gutter = " ".repeat(number.len().max(1));
+    let mut snippet = String::new();
+    let _ = match &span.path {
+        Some(path) => writeln!(snippet, "{}--> {}:{}:{}", gutter, path, span.line, column),
+        None => writeln!(snippet, "{}--> argument {}", gutter, span.line),
+    };
+    let _ = writeln!(snippet, "{} |", gutter);
+    let _ = writeln!(snippet, "{:>2$} | {}", number, span.text, gutter.len());
+    let _ = write!(
+        snippet,
+        "{} | {}{} {}",
+        gutter,
+        " ".repeat(column - 1),
+        "^".repeat(width),
+        label
+    );
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn valid(text: &str) -> bool {
+        text.parse::<f64>().is_ok()
+    }
+
+    #[test]
+    fn test_locate_error() {
+        assert_that!(
+            locate_error("0,5", None, valid),
+            equal_to((
+                1..2,
+                "a comma is not a decimal point; write `0.5`, or pass --decimal-comma".to_string()
+            ))
+        );
+        assert_that!(
+            locate_error("1,2,3", None, valid),
+            equal_to((1..2, "a stray comma".to_string()))
+        );
+        assert_that!(
+            locate_error("0.5x", None, valid),
+            equal_to((3..4, "`x` is not a digit here".to_string()))
+        );
+        assert_that!(
+            locate_error("1.2.3", None, valid),
+            equal_to((3..4, "unexpected `.`".to_string()))
+        );
+        assert_that!(
+            locate_error("abc", None, valid),
+            equal_to((0..3, "not a number".to_string()))
+        );
+        assert_that!(
+            locate_error("3/00", None, valid),
+            equal_to((2..4, "the denominator is zero".to_string()))
+        );
+        assert_that!(
+            locate_error("1e400", Some("it is beyond the largest f64"), valid).0,
+            equal_to(1..5)
+        );
+    }
+
+    #[test]
+    fn test_spans_and_snippets() {
+        let args: Vec<String> = ["--base", "16", "0.1", "0,5"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let command_line = args.join(" ");
+        let positional = [args[2].as_str(), args[3].as_str()];
+        let spans = find_spans(
+            argument_words(&args, &command_line, &positional)
+                .into_iter()
+                .chain(line_words(
+                    "inputs.txt",
+                    "# samples\n0.25 1/3\n0.75 1.2.3 # bad\n",
+                )),
+            &["0,5".to_string(), "1.2.3".to_string(), "junk".to_string()],
+        );
+        assert_that!(
+            spans[0].clone(),
+            equal_to(Some(Span {
+                path: None,
+                line: 4,
+                text: command_line.clone(),
+                offset: 14,
+            }))
+        );
+        assert_that!(spans[2].clone(), equal_to(None));
+        let span = spans[1].clone().unwrap();
+        assert_that!(span.offset, equal_to(5));
+        assert_that!(
+            snippet(&span, &locate_error("1.2.3", None, valid)),
+            equal_to(
+                " --> inputs.txt:3:9\n  \
+                 |\n\
+                 3 | 0.75 1.2.3 # bad\n  \
+                 |         ^ unexpected `.`"
+                    .to_string()
+            )
+        );
+        assert_that!(
+            snippet(&spans[0].clone().unwrap(), &locate_error("0,5", None, valid)),
+            equal_to(
+                " --> argument 4\n  \
+                 |\n  \
+                 | --base 16 0.1 0,5\n  \
+                 |                ^ a comma is not a decimal point; write `0.5`, or pass --decimal-comma"
+                    .to_string()
+            )
+        );
+    }
+}
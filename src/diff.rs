@@ -0,0 +1,263 @@
+use std::io::{self, Write};
+
+use crate::cli::flag_value;
+use crate::convert::{Expansion, Representation};
+use crate::find::parse_digits;
+use crate::pipeline::{parse_base_list, Pipeline};
+use crate::rational::Fraction;
+
+/// The number of fractional digits `diff` writes in the larger of its two
+/// bases when no `--digits` is given. The smaller base gets as many as
+/// cover the same places.
+pub const DEFAULT_DIFF_DIGITS: u32 = 8;
+
+/// The settings of the `diff` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// The two bases compared, in the order given.
+    pub target_bases: [u32; 2],
+    /// The number of fractional digits written in the larger base.
+    pub digits: u32,
+    /// Every input as typed, with its value and the exact fraction it was
+    /// written as, if any.
+    pub inputs: Vec<(String, f64, Option<Fraction>)>,
+}
+
+/// Parses the arguments of the `diff` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `diff`.
+///
+/// # Returns
+///
+/// The parsed `DiffOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- diff --base 2,8 --digits 6 0.1 1/3
+/// ```
+pub fn parse_diff_args(args: &[String]) -> Result<DiffOptions, String> {
+    let mut target_bases = None;
+    let mut digits = DEFAULT_DIFF_DIGITS;
+    let mut inputs = Vec::new();
+    let pipeline = Pipeline::with_bases(vec![2.0]);
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            target_bases = Some(parse_base_list(value)?);
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = parse_digits(value)?;
+        } else {
+            let (value, fraction) = pipeline
+                .decode_input(arg)
+                .filter(|(value, _)| value.is_finite())
+                .ok_or_else(|| format!("unknown diff argument `{}`", arg))?;
+            inputs.push((arg.clone(), value, fraction));
+        }
+    }
+
+    let target_bases = match target_bases.as_deref() {
+        Some(&[first, second]) if first > 0.0 && second > 0.0 => [first as u32, second as u32],
+        Some(&[_, _]) => return Err("diff only supports positive bases".to_string()),
+        _ => return Err("diff requires two bases, as in --base 2,8".to_string()),
+    };
+    if digits == 0 {
+        return Err("diff requires at least one digit".to_string());
+    }
+    if inputs.is_empty() {
+        return Err("diff requires a value to convert".to_string());
+    }
+    Ok(DiffOptions {
+        target_bases,
+        digits,
+        inputs,
+    })
+}
+
+/// Lays out the expansions of a number in several bases one above the
+/// other, so that every digit sits at the end of the places it stands for.
+///
+/// A digit of base `b` stands for `log₂ b` bits, and takes up as many
+/// columns as its bits would, so that, say, every digit of base 8 ends under
+/// every third digit of base 2. Integer digits are laid out from the point
+/// leftwards the same way.
+///
+/// # Returns
+///
+/// One line for every expansion, without labels, line ends or trailing
+/// spaces.
+///
+/// # Example
+///
+/// ```
+/// let lines = align_places(&[expand(0.75, 2, 8), expand(0.75, 4, 8)]);
+/// assert_eq!(lines, ["0. 1 1", "0.   3"]);
+/// ```
+pub fn align_places(expansions: &[Expansion]) -> Vec<String> {
+    let bits = |expansion: &Expansion| expansion.base.log2();
+    let widest_digit = |expansion: &Expansion| (expansion.base as u64 - 1).to_string().len();
+    // Columns per bit, enough for every digit of every base and a space.
+    let scale = expansions
+        .iter()
+        .map(|expansion| (widest_digit(expansion) + 1) as f64 / bits(expansion))
+        .fold(0.0, f64::max);
+    let column = |places: usize, expansion: &Expansion| {
+        (places as f64 * bits(expansion) * scale).round() as usize
+    };
+    // The integer digit `k` places left of the point ends where the `k`
+    // places after it start, and the sign comes right before the first.
+    let integer_digits = |expansion: &Expansion| -> Vec<u32> {
+        if expansion.integer_digits.is_empty() {
+            vec![0]
+        } else {
+            expansion.integer_digits.clone()
+        }
+    };
+    let integer_width = |expansion: &Expansion| {
+        let digits = integer_digits(expansion);
+        column(digits.len() - 1, expansion)
+            + digits[0].to_string().len()
+            + usize::from(expansion.negative)
+    };
+    let point = expansions.iter().map(integer_width).max().unwrap_or(0);
+
+    expansions
+        .iter()
+        .map(|expansion| {
+            let mut line = vec![' '; point];
+            let digits = integer_digits(expansion);
+            for (k, digit) in digits.iter().rev().enumerate() {
+                let text = digit.to_string();
+                let start = point - column(k, expansion) - text.len();
+                for (offset, c) in text.chars().enumerate() {
+                    line[start + offset] = c;
+                }
+            }
+            if expansion.negative {
+                line[point - integer_width(expansion)] = '-';
+            }
+            line.push('.');
+            for (i, digit) in expansion.digits.iter().enumerate() {
+                let text = digit.to_string();
+                let end = (point + 1 + column(i + 1, expansion)).max(line.len() + text.len());
+                line.resize(end - text.len(), ' ');
+                line.extend(text.chars());
+            }
+            line.into_iter().collect::<String>().trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Converts an input to both bases of `diff`, with as many digits in the
+/// smaller base as cover the places of the digits in the larger one.
+pub fn diff_expansions(
+    options: &DiffOptions,
+    value: f64,
+    fraction: Option<&Fraction>,
+) -> Vec<Expansion> {
+    let largest = options.target_bases.iter().copied().max().unwrap_or(2) as f64;
+    let bits = options.digits as f64 * largest.log2();
+    options
+        .target_bases
+        .iter()
+        .map(|&target_base| {
+            let mut pipeline = Pipeline::with_bases(vec![target_base as f64]);
+            pipeline.digits = (bits / (target_base as f64).log2()).ceil() as u32;
+            pipeline.expand_input(value, fraction, target_base as f64)
+        })
+        .collect()
+}
+
+/// Runs the `diff` subcommand, printing every input in both bases one above
+/// the other with their place values aligned, followed by `…` in a base
+/// whose expansion goes on.
+///
+/// # Example
+/// ```
+/// // cargo run -- diff --base 2,8 --digits 4 0.1
+/// ```
+/// Output:
+/// 0.1
+///   Base 2  0. 0 0 0 1 1 0 0 1 1 0 0 1 …
+///   Base 8  0.     0     6     3     1 …
+pub fn display_diff(out: &mut impl Write, options: &DiffOptions) -> io::Result<()> {
+    let labels = options
+        .target_bases
+        .map(|target_base| format!("Base {}", target_base));
+    let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+    for (i, (input, value, fraction)) in options.inputs.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "{}", input)?;
+        let expansions = diff_expansions(options, *value, fraction.as_ref());
+        let lines = align_places(&expansions);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        for ((label, line), expansion) in labels.iter().zip(&lines).zip(&expansions) {
+            let more = if expansion.is_exact() { "" } else { " …" };
+            let line = if more.is_empty() {
+                line.clone()
+            } else {
+                format!("{:<1$}", line, width)
+            };
+            writeln!(
+                out,
+                "  {:<width$}  {}{}",
+                label,
+                line,
+                more,
+                width = label_width
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_aligned_places() {
+        let lines = |line: &str| {
+            let options = parse_diff_args(&args(line)).unwrap();
+            let (_, value, fraction) = &options.inputs[0];
+            align_places(&diff_expansions(&options, *value, fraction.as_ref()))
+        };
+        assert_that!(
+            lines("--base 2,8 --digits 4 0.1"),
+            equal_to(vec![
+                "0. 0 0 0 1 1 0 0 1 1 0 0 1".to_string(),
+                "0.     0     6     3     1".to_string(),
+            ])
+        );
+        assert_that!(
+            lines("--base 2,8 5.5"),
+            equal_to(vec!["1 0 1. 1".to_string(), "    5.     4".to_string()])
+        );
+        // Base 16 digits are up to two characters wide.
+        assert_that!(
+            lines("--base 16,2 -18.5"),
+            equal_to(vec![
+                "-1       2.       8".to_string(),
+                "-1 0 0 1 0. 1".to_string(),
+            ])
+        );
+
+        assert!(parse_diff_args(&args("--base 2 0.1")).is_err());
+        assert!(parse_diff_args(&args("--base 2,-2 0.1")).is_err());
+        assert!(parse_diff_args(&args("--base 2,8")).is_err());
+        assert!(parse_diff_args(&args("--base 2,8 junk")).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::find::parse_digits;
+
+/// Splits the digit limit off an input written as `0.1:20`, which is
+/// converted to 20 digits whatever `--precision` says, so that one table
+/// can mix precisions.
+///
+/// # Returns
+///
+/// The input without its limit and the limit, if it has one, or `None` if
+/// what follows the `:` is no digit count.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(split_digit_limit("0.1:20"), Some(("0.1", Some(20))));
+/// assert_eq!(split_digit_limit("1/3"), Some(("1/3", None)));
+/// assert_eq!(split_digit_limit("0.1:x"), None);
+/// ```
+pub fn split_digit_limit(arg: &str) -> Option<(&str, Option<u32>)> {
+    match arg.rsplit_once(':') {
+        Some((input, digits)) => Some((input, Some(parse_digits(digits).ok()?))),
+        None => Some((arg, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_split_digit_limit() {
+        assert_that!(
+            split_digit_limit("0.1:20"),
+            equal_to(Some(("0.1", Some(20))))
+        );
+        assert_that!(
+            split_digit_limit("-1/3:4"),
+            equal_to(Some(("-1/3", Some(4))))
+        );
+        assert_that!(split_digit_limit("0.5"), equal_to(Some(("0.5", None))));
+        assert_that!(split_digit_limit("0.1:"), equal_to(None));
+        assert_that!(split_digit_limit("0.1:-2"), equal_to(None));
+    }
+}
@@ -0,0 +1,104 @@
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+
+use crate::rational::Ratio;
+
+/// The header of the column of the exact value of the digits written.
+pub const DIGITS_VALUE_HEADER: &str = "Digits value";
+/// The header of the column of how far the input is from that value.
+pub const DIFFERENCE_HEADER: &str = "Difference";
+
+/// The exact value `integer + Σ dᵢ·base⁻ⁱ` of positional digits, as the
+/// fraction they denote rather than the `f64` they evaluate to.
+///
+/// # Arguments
+///
+/// * `negative` - Whether the digits are written with a minus sign.
+/// * `target_base` - The base the digits are in, which may be negative.
+/// * `integer` - The digits of the integer part, most significant first.
+/// * `fraction` - The fractional digits, most significant first.
+///
+/// # Returns
+///
+/// The value in lowest terms, or `None` for a base that is not a whole
+/// number of magnitude 2 or more.
+///
+/// # Example
+///
+/// ```
+/// // 0.0;0;0;1; in base 2, the first four digits of 0.1
+/// let value = digits_value(false, 2.0, &[], &[0, 0, 0, 1]).unwrap();
+/// assert_eq!(value.to_string(), "1/16");
+/// ```
+pub fn digits_value(
+    negative: bool,
+    target_base: f64,
+    integer: &[u32],
+    fraction: &[u32],
+) -> Option<Ratio> {
+    if target_base.fract() != 0.0 || !(2.0..=i64::MAX as f64).contains(&target_base.abs()) {
+        return None;
+    }
+    let base = BigInt::from(target_base as i64);
+    // The digits read as one whole number, which the fractional digits then
+    // shift below the point.
+    let whole = integer
+        .iter()
+        .chain(fraction)
+        .fold(BigInt::zero(), |value, &digit| value * &base + digit);
+    // A negative base to an odd power is negative.
+    let scale = base.pow(fraction.len() as u32);
+    let below_zero = (whole.sign() == Sign::Minus) != (scale.sign() == Sign::Minus);
+    Some(
+        Ratio {
+            negative: negative != below_zero,
+            numerator: whole.magnitude().clone(),
+            denominator: scale.magnitude().clone(),
+        }
+        .reduced(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn written(value: Option<Ratio>) -> String {
+        value.map_or("n/a".to_string(), |value| value.to_string())
+    }
+
+    #[test]
+    fn test_digits_value() {
+        assert_that!(
+            written(digits_value(false, 2.0, &[], &[0, 0, 0, 1, 1, 0, 0, 1])),
+            equal_to("25/256".to_string())
+        );
+        assert_that!(
+            written(digits_value(true, 10.0, &[1, 2], &[5])),
+            equal_to("-25/2".to_string())
+        );
+        assert_that!(
+            written(digits_value(false, 60.0, &[], &[15, 30])),
+            equal_to("31/120".to_string())
+        );
+        assert_that!(
+            written(digits_value(false, 10.0, &[], &[])),
+            equal_to("0".to_string())
+        );
+        // 1.1 in base -2 is 1 + 1/-2.
+        assert_that!(
+            written(digits_value(false, -2.0, &[1], &[1])),
+            equal_to("1/2".to_string())
+        );
+        // 0.1;1; in base -2 is -1/2 + 1/4.
+        assert_that!(
+            written(digits_value(false, -2.0, &[], &[1, 1])),
+            equal_to("-1/4".to_string())
+        );
+        assert_that!(
+            written(digits_value(false, 1.5, &[], &[1])),
+            equal_to("n/a".to_string())
+        );
+    }
+}
@@ -0,0 +1,871 @@
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+use crate::bitgroup::{write_bit_groups, BIT_GROUPS_HEADER};
+use crate::convert::Representation;
+use crate::dedup::COUNT_HEADER;
+use crate::digits_value::{digits_value, DIFFERENCE_HEADER, DIGITS_VALUE_HEADER};
+use crate::exit::fail_round_trip;
+use crate::explain_float::{exact_decimal, EXACT_VALUE_HEADER};
+use crate::label::LABEL_HEADER;
+use crate::rational::{Fraction, Ratio, FRACTION_HEADER};
+use crate::script::{Cell, Script, Scripted};
+use crate::sparkline::{convergence_sparkline, SPARKLINE_HEADER};
+use crate::style::{ColumnStyle, Rule, Styles, TableStyle};
+use crate::terminate::{
+    exact_input, period, terminating_digits, termination, Termination, DIGITS_NEEDED_HEADER,
+    PERIOD_HEADER, TERMINATES_HEADER,
+};
+use crate::verify::{Tolerance, VERIFY_HEADER};
+
+/// The header of the column of the input numbers.
+const INPUT_HEADER: &str = "Base 10";
+/// The magnitude from which an input in base 10 is written in scientific
+/// notation rather than in all its integer digits.
+const SCIENTIFIC_ABOVE: f64 = 1e16;
+/// The magnitude below which a nonzero input in base 10, written with the
+/// fewest digits that read back, is written in scientific notation rather
+/// than behind a run of leading zeros.
+const SCIENTIFIC_BELOW: f64 = 1e-7;
+/// The header of every round-trip error column.
+const ERROR_HEADER: &str = "Error";
+
+/// The optional columns shown alongside every target base column.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Columns {
+    /// Lead every row with the label its input was given with, as in
+    /// `tenth=0.1`.
+    pub label: bool,
+    /// Leave out the column of the inputs in base 10, when `--columns` does
+    /// not name `decimal`.
+    pub hide_input: bool,
+    /// Leave out the column of every base, keeping the columns after it,
+    /// when `--columns` does not name `repr`.
+    pub hide_expansions: bool,
+    /// How many decimal places the inputs in base 10 are written with, from
+    /// `--decimal-places`, or `None` for the fewest that read back as the
+    /// same `f64`, such as `0.5` (the default).
+    pub decimal_places: Option<usize>,
+    /// Show the absolute difference between the original decimal and the
+    /// value represented by the emitted digits.
+    pub round_trip_error: bool,
+    /// Show the exact fraction the emitted digits denote, `Σ dᵢ·base⁻ⁱ`, and
+    /// the exact difference of the input from it, with `--digits-value`.
+    pub digits_value: bool,
+    /// Show a sparkline of the running error of the emitted digits, one bar
+    /// per digit, with `--sparkline`.
+    pub sparkline: bool,
+    /// Tell whether the exact expansion of the input terminates, and if not,
+    /// which prime factor of its denominator prevents it.
+    pub terminates: bool,
+    /// Give the number of digits before the exact expansion of the input
+    /// repeats and the length of the repeating block.
+    pub period: bool,
+    /// Give how many fractional digits the expansion of the input needs
+    /// before it ends, with `--exact-digits`.
+    pub digits_needed: bool,
+    /// Give how many times every input was given, after `--dedup` collapsed
+    /// the repeated ones into one row.
+    pub count: bool,
+    /// Give the exact value of every input as a fraction in lowest terms,
+    /// such as 1/8 for `0.125`.
+    pub fraction: bool,
+    /// Give the exact decimal value of the `f64` every input is stored as,
+    /// such as 0.1000000000000000055511151231257827021181583404541015625 for
+    /// `0.1`, which is the value the digits are taken from.
+    pub exact_value: bool,
+    /// Mark every row `ok`, or `FAIL` when the value of one of its
+    /// expansions is further from the input than the tolerance, with
+    /// `--verify-roundtrip`.
+    pub verify: Option<Tolerance>,
+    /// Regroup the digits of every base 2 expansion into groups of this
+    /// many bits, 4 or 8, with their hexadecimal digits after them, with
+    /// `--bit-groups`.
+    pub bit_groups: Option<usize>,
+    /// Rewrite every cell and add the columns computed from its digits
+    /// after every base column, with `--script`.
+    pub script: Option<&'static Script>,
+}
+
+/// The inputs of a table: the decimal numbers, the exact fraction every one
+/// was written as, if any, how many times it was given, and its label, if
+/// any.
+pub type TableInputs<'a> = (
+    &'a [f64],
+    &'a [Option<Fraction>],
+    &'a [usize],
+    &'a [Option<String>],
+);
+
+/// The text buffers `display_row` builds its rows in. Kept from one row to
+/// the next, they grow to the length of the longest row and are then
+/// reused, so that printing a row allocates nothing.
+#[derive(Debug, Default)]
+pub struct RowBuffer {
+    /// The row being built.
+    row: String,
+    /// The unstyled text of the cell being built.
+    cell: String,
+}
+
+/// Outputs the decimal numbers and their target base fractional representations in a table format.
+///
+/// # Arguments
+///
+/// * `out` - Where the table is written.
+/// * `headers` - The titles of the converted number columns, such as `Base 2`.
+/// * `inputs` - The decimal numbers in base 10, for every one the exact
+///   fraction it was written as, if any, how many times it was given, and
+///   the label it was given with, if any.
+/// * `expansions` - For every decimal number, its target base expansions in
+///   the order of `headers`.
+/// * `columns` - The optional columns to show after every base column.
+/// * `styles` - The alignment, color and width of the columns.
+///
+/// # Example
+/// ```
+/// let pipeline = Pipeline::with_bases(vec![2.0, 8.0]);
+/// let expansions = vec![pipeline.expand(0.5, None), pipeline.expand(0.25, None)];
+/// let headers = vec!["Base 2".to_string(), "Base 8".to_string()];
+/// let mut out = std::io::stdout();
+/// let inputs = (&[0.5, 0.25][..], &[None, None][..], &[1, 1][..], &[][..]);
+/// display(&mut out, &headers, inputs, &expansions, Columns::default(), &Styles::default()).unwrap();
+/// ```
+/// Output:
+/// |   Base 10   |   Base 2   |   Base 8   |
+/// |:------------|:-----------|:-----------|
+/// | 0.5         | 0.1;       | 0.4;       |
+/// | 0.25        | 0.0;1;     | 0.2;       |
+pub fn display<R: Representation>(
+    out: &mut dyn Write,
+    headers: &[String],
+    (f64_numbers, fractions, counts, labels): TableInputs,
+    expansions: &[Vec<R>],
+    columns: Columns,
+    styles: &Styles,
+) -> io::Result<()> {
+    display_header(out, headers, columns, styles)?;
+    let mut buffer = RowBuffer::default();
+    let inputs = f64_numbers.iter().zip(fractions).zip(counts).enumerate();
+    for ((i, ((&number, fraction), &count)), row_expansions) in inputs.zip(expansions) {
+        let label = labels.get(i).and_then(Option::as_deref);
+        display_row(
+            out,
+            &mut buffer,
+            headers,
+            (number, fraction.as_ref(), count, label),
+            row_expansions,
+            columns,
+            styles,
+        )?;
+    }
+    display_table_end(out, headers, columns, styles)
+}
+
+/// Prints the header of the table, between the rules its style draws above
+/// and below it.
+///
+/// # Arguments
+///
+/// * `out` - Where the lines are written.
+/// * `headers` - The titles of the converted number columns, such as `Base 2`.
+/// * `columns` - The optional columns to show after every base column.
+/// * `styles` - The alignment, color and width of the columns.
+pub fn display_header(
+    out: &mut dyn Write,
+    headers: &[String],
+    columns: Columns,
+    styles: &Styles,
+) -> io::Result<()> {
+    let table = styles.table;
+    let mut header = String::from(table.border());
+    let mut widths = Vec::new();
+    for (title, default_width) in table_columns(headers, columns) {
+        let style = styles.get(title);
+        header += &format!(
+            " {} {}",
+            style.header_cell(title, default_width),
+            table.border()
+        );
+        widths.push(style.width(default_width));
+    }
+    table.end_row(&mut header);
+    if let Some(top) = table.rule(Rule::Top, &widths) {
+        writeln!(out, "{}", top)?;
+    }
+    out.write_all(header.as_bytes())?;
+    match table.rule(Rule::Divider, &widths) {
+        Some(divider) => writeln!(out, "{}", divider),
+        None => Ok(()),
+    }
+}
+
+/// Prints the rule below the last row of the table, if its style has one.
+///
+/// # Arguments
+///
+/// * `out` - Where the rule is written.
+/// * `headers` - The titles of the converted number columns, such as `Base 2`.
+/// * `columns` - The optional columns to show after every base column.
+/// * `styles` - The alignment, color and width of the columns.
+pub fn display_table_end(
+    out: &mut dyn Write,
+    headers: &[String],
+    columns: Columns,
+    styles: &Styles,
+) -> io::Result<()> {
+    let widths: Vec<usize> = table_columns(headers, columns)
+        .into_iter()
+        .map(|(title, default_width)| styles.get(title).width(default_width))
+        .collect();
+    match styles.table.rule(Rule::Bottom, &widths) {
+        Some(bottom) => writeln!(out, "{}", bottom),
+        None => Ok(()),
+    }
+}
+
+/// The title and unstyled width of every column of the table, in order.
+fn table_columns(headers: &[String], columns: Columns) -> Vec<(&str, usize)> {
+    let mut table = Vec::new();
+    if columns.label {
+        table.push((LABEL_HEADER, 10));
+    }
+    if !columns.hide_input {
+        table.push((INPUT_HEADER, 10));
+    }
+    if columns.count {
+        table.push((COUNT_HEADER, 5));
+    }
+    if columns.fraction {
+        table.push((FRACTION_HEADER, 12));
+    }
+    if columns.exact_value {
+        table.push((EXACT_VALUE_HEADER, 24));
+    }
+    for title in headers {
+        if !columns.hide_expansions {
+            table.push((title, 22));
+        }
+        if columns.round_trip_error {
+            table.push((ERROR_HEADER, 10));
+        }
+        if columns.digits_value {
+            table.push((DIGITS_VALUE_HEADER, 14));
+            table.push((DIFFERENCE_HEADER, 14));
+        }
+        if columns.sparkline {
+            table.push((SPARKLINE_HEADER, 11));
+        }
+        if columns.terminates {
+            table.push((TERMINATES_HEADER, 24));
+        }
+        if columns.period {
+            table.push((PERIOD_HEADER, 24));
+        }
+        if columns.digits_needed {
+            table.push((DIGITS_NEEDED_HEADER, 13));
+        }
+        if columns.bit_groups.is_some() {
+            table.push((BIT_GROUPS_HEADER, 28));
+        }
+        if let Some(script) = columns.script {
+            table.extend(script.columns().iter().map(|header| (header.as_str(), 12)));
+        }
+    }
+    if columns.verify.is_some() {
+        table.push((VERIFY_HEADER, 10));
+    }
+    table
+}
+
+/// Prints the table row of one decimal number, so that long runs can write
+/// every row as soon as it is converted.
+///
+/// # Arguments
+///
+/// * `out` - Where the row is written.
+/// * `buffer` - The buffers to build the row in, reused across rows.
+/// * `headers` - The titles of the converted number columns, such as `Base 2`.
+/// * `input` - The decimal number in base 10, the exact fraction it was
+///   written as, if any, how many times it was given, and the label it was
+///   given with, if any.
+/// * `row_expansions` - Its expansions, in the order of the table headers.
+/// * `columns` - The optional columns to show after every base column.
+/// * `styles` - The alignment, color and width of the columns.
+pub fn display_row<R: Representation>(
+    out: &mut dyn Write,
+    buffer: &mut RowBuffer,
+    headers: &[String],
+    (number, fraction, count, label): (f64, Option<&Fraction>, usize, Option<&str>),
+    row_expansions: &[R],
+    columns: Columns,
+    styles: &Styles,
+) -> io::Result<()> {
+    // Every cell is first written unstyled into `cell`, then laid out into
+    // `row`, so that neither the digits nor the cells need strings of their
+    // own. Writing into a `String` cannot fail.
+    let RowBuffer { row, cell } = buffer;
+    let table = styles.table;
+    row.clear();
+    row.push_str(table.border());
+    if columns.label {
+        push_cell(
+            row,
+            table,
+            styles.get(LABEL_HEADER),
+            label.unwrap_or(""),
+            10,
+        );
+    }
+    if !columns.hide_input {
+        cell.clear();
+        let _ = write_input(cell, number, columns.decimal_places);
+        push_cell(row, table, styles.get(INPUT_HEADER), cell, 10);
+    }
+    if columns.count {
+        cell.clear();
+        let _ = write!(cell, "{}", count);
+        push_cell(row, table, styles.get(COUNT_HEADER), cell, 5);
+    }
+    let exact = (columns.terminates
+        || columns.period
+        || columns.fraction
+        || columns.digits_value
+        || columns.sparkline)
+        .then(|| exact_input(number, fraction).map(Ratio::reduced))
+        .flatten();
+    if columns.fraction {
+        cell.clear();
+        match &exact {
+            Some(exact) => {
+                let _ = write!(cell, "{}", exact);
+            }
+            None => cell.push_str("n/a"),
+        }
+        push_cell(row, table, styles.get(FRACTION_HEADER), cell, 12);
+    }
+    if columns.exact_value {
+        let exact = exact_decimal(number);
+        push_cell(
+            row,
+            table,
+            styles.get(EXACT_VALUE_HEADER),
+            exact.as_deref().unwrap_or("n/a"),
+            24,
+        );
+    }
+    for (title, expansion) in headers.iter().zip(row_expansions) {
+        cell.clear();
+        let _ = write!(cell, "{}", expansion);
+        let scripted = columns
+            .script
+            .map(|script| run_script(script, number, expansion, cell));
+        if let Some(Some(scripted)) = &scripted {
+            cell.clone_from(&scripted.repr);
+        }
+        if !columns.hide_expansions {
+            push_cell(row, table, styles.get(title), cell, 22);
+        }
+        if columns.round_trip_error {
+            cell.clear();
+            let _ = write_error(cell, round_trip_error(number, expansion));
+            push_cell(row, table, styles.get(ERROR_HEADER), cell, 10);
+        }
+        if columns.digits_value {
+            // Negative bases write negative values without a sign.
+            let value = expansion
+                .positional_digits()
+                .zip(expansion.base())
+                .and_then(|((integer, fraction), target_base)| {
+                    let negative = target_base > 0.0 && expansion.value() < 0.0;
+                    digits_value(negative, target_base, integer, fraction)
+                });
+            cell.clear();
+            match &value {
+                Some(value) => {
+                    let _ = write!(cell, "{}", value);
+                }
+                None => cell.push_str("n/a"),
+            }
+            push_cell(row, table, styles.get(DIGITS_VALUE_HEADER), cell, 14);
+            cell.clear();
+            match exact.as_ref().zip(value.as_ref()) {
+                Some((exact, value)) => {
+                    let _ = write!(cell, "{}", exact.minus(value));
+                }
+                None => cell.push_str("n/a"),
+            }
+            push_cell(row, table, styles.get(DIFFERENCE_HEADER), cell, 14);
+        }
+        if columns.sparkline {
+            let sparkline = exact
+                .as_ref()
+                .zip(expansion.positional_digits())
+                .zip(expansion.base())
+                .and_then(|((exact, (integer, fraction)), target_base)| {
+                    let negative = target_base > 0.0 && expansion.value() < 0.0;
+                    convergence_sparkline(exact, negative, target_base, integer, fraction)
+                });
+            push_cell(
+                row,
+                table,
+                styles.get(SPARKLINE_HEADER),
+                sparkline.as_deref().unwrap_or("n/a"),
+                11,
+            );
+        }
+        if columns.terminates {
+            cell.clear();
+            let verdict = match (&exact, expansion.base()) {
+                (Some(exact), Some(target_base)) => termination(exact, target_base),
+                _ => Termination::Undefined,
+            };
+            let _ = write!(cell, "{}", verdict);
+            push_cell(row, table, styles.get(TERMINATES_HEADER), cell, 24);
+        }
+        if columns.period {
+            cell.clear();
+            match exact
+                .as_ref()
+                .zip(expansion.base())
+                .and_then(|(exact, target_base)| period(exact, target_base))
+            {
+                Some(period) => {
+                    let _ = write!(cell, "{}", period);
+                }
+                None => cell.push_str("n/a"),
+            }
+            push_cell(row, table, styles.get(PERIOD_HEADER), cell, 24);
+        }
+        if columns.digits_needed {
+            cell.clear();
+            let target_base = expansion
+                .base()
+                .filter(|&base| base >= 2.0 && base.fract() == 0.0 && base <= u32::MAX as f64);
+            match target_base.map(|base| terminating_digits(number, fraction, base as u64)) {
+                Some(Some((_, digits))) => {
+                    let _ = write!(cell, "{}", digits);
+                }
+                Some(None) => cell.push_str("never ends"),
+                None => cell.push_str("n/a"),
+            }
+            push_cell(row, table, styles.get(DIGITS_NEEDED_HEADER), cell, 13);
+        }
+        if let Some(group) = columns.bit_groups {
+            cell.clear();
+            match expansion
+                .positional_digits()
+                .filter(|_| expansion.base() == Some(2.0))
+            {
+                Some((integer, fraction)) => {
+                    let negative = expansion.value() < 0.0;
+                    let _ = write_bit_groups(cell, negative, integer, fraction, group);
+                }
+                None => cell.push_str("n/a"),
+            }
+            push_cell(row, table, styles.get(BIT_GROUPS_HEADER), cell, 28);
+        }
+        if let (Some(script), Some(scripted)) = (columns.script, &scripted) {
+            for (i, header) in script.columns().iter().enumerate() {
+                let value = scripted
+                    .as_ref()
+                    .and_then(|scripted| scripted.columns.get(i));
+                push_cell(
+                    row,
+                    table,
+                    styles.get(header),
+                    value.map_or("n/a", String::as_str),
+                    12,
+                );
+            }
+        }
+    }
+    if let Some(tolerance) = columns.verify {
+        let passed = row_expansions.iter().all(|expansion| {
+            tolerance.allows(round_trip_error(number, expansion), expansion.base())
+        });
+        if !passed {
+            fail_round_trip();
+        }
+        push_cell(
+            row,
+            table,
+            styles.get(VERIFY_HEADER),
+            if passed { "ok" } else { "FAIL" },
+            10,
+        );
+    }
+    table.end_row(row);
+    out.write_all(row.as_bytes())
+}
+
+/// Runs a `--script` on the expansion of a cell written as `repr`.
+///
+/// # Returns
+///
+/// The rewritten cell and the script's columns, or `None` after warning
+/// that the script failed, in which case the cell is left as it is and its
+/// columns are `n/a`.
+fn run_script<R: Representation>(
+    script: &Script,
+    number: f64,
+    expansion: &R,
+    repr: &str,
+) -> Option<Scripted> {
+    let (integer, digits) = expansion.positional_digits().unwrap_or((&[], &[]));
+    let value = expansion.value();
+    let cell = Cell {
+        input: number,
+        base: expansion.base().unwrap_or(f64::NAN),
+        integer,
+        digits,
+        negative: value < 0.0,
+        truncated: !expansion.is_exact(),
+        value,
+        repr,
+    };
+    script
+        .run(&cell)
+        .map_err(|error| eprintln!("warning: {}, leaving `{}` as it is", error, repr))
+        .ok()
+}
+
+/// Appends one laid out cell and the border closing it to a table row.
+fn push_cell(
+    row: &mut String,
+    table: TableStyle,
+    style: ColumnStyle,
+    text: &str,
+    default_width: usize,
+) {
+    row.push(' ');
+    let _ = style.write_cell(row, text, default_width);
+    row.push(' ');
+    row.push_str(table.border());
+}
+
+/// Computes how much precision was lost by cutting the expansion off.
+///
+/// # Arguments
+///
+/// * `number` - The original decimal number.
+/// * `expansion` - Its target base expansion.
+///
+/// # Returns
+///
+/// The absolute difference between `number` and the value of the digits.
+pub fn round_trip_error<R: Representation>(number: f64, expansion: &R) -> f64 {
+    (number - expansion.value()).abs()
+}
+
+/// Writes an input in base 10 with the given decimal places, or the fewest
+/// that read back as the same `f64`, switching to scientific notation, as in
+/// `1e308`, for magnitudes whose positional digits would not fit a column.
+fn write_input(sink: &mut impl fmt::Write, number: f64, places: Option<usize>) -> fmt::Result {
+    let magnitude = number.abs();
+    let large = magnitude >= SCIENTIFIC_ABOVE;
+    let small = magnitude != 0.0 && magnitude < SCIENTIFIC_BELOW;
+    match places {
+        Some(places) if large => write!(sink, "{:.1$e}", number, places),
+        Some(places) => write!(sink, "{:.1$}", number, places),
+        None if large || small => write!(sink, "{:e}", number),
+        None => write!(sink, "{}", number),
+    }
+}
+
+/// Formats an error value compactly in scientific notation, e.g. `3.750e-2`.
+pub fn format_error(error: f64) -> String {
+    let mut text = String::new();
+    let _ = write_error(&mut text, error);
+    text
+}
+
+/// Writes an error value like `format_error`, into an existing buffer.
+fn write_error(sink: &mut impl fmt::Write, error: f64) -> fmt::Result {
+    if error == 0.0 {
+        write!(sink, "0")
+    } else {
+        write!(sink, "{:.3e}", error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_round_trip_error() {
+        assert_that!(round_trip_error(0.75, &expand(0.75, 2, 8)), equal_to(0.0));
+        assert_that!(
+            round_trip_error(0.1, &expand(0.1, 2, 4)),
+            equal_to(0.1 - 0.0625)
+        );
+        assert_that!(format_error(0.0375), equal_to("3.750e-2"));
+        assert_that!(format_error(0.0), equal_to("0"));
+    }
+
+    #[test]
+    fn test_write_input() {
+        let input = |number, places| {
+            let mut text = String::new();
+            let _ = write_input(&mut text, number, places);
+            text
+        };
+        assert_that!(input(0.5, None), equal_to("0.5".to_string()));
+        assert_that!(input(0.0, None), equal_to("0".to_string()));
+        assert_that!(input(1e-310, None), equal_to("1e-310".to_string()));
+        assert_that!(input(-1e308, None), equal_to("-1e308".to_string()));
+        assert_that!(input(1e15, None), equal_to("1000000000000000".to_string()));
+        assert_that!(input(1e308, Some(2)), equal_to("1.00e308".to_string()));
+        assert_that!(input(1e-310, Some(2)), equal_to("0.00".to_string()));
+        assert_that!(input(0.125, Some(2)), equal_to("0.12".to_string()));
+    }
+
+    #[test]
+    fn test_table_styles() {
+        let headers = vec!["Base 2".to_string()];
+        let pipeline = crate::pipeline::Pipeline::with_bases(vec![2.0]);
+        let expansions = vec![pipeline.expand(0.5, None)];
+        let table = |table: TableStyle| {
+            let styles = Styles {
+                table,
+                ..Styles::default()
+            };
+            let mut out = Vec::new();
+            let inputs = (&[0.5][..], &[None][..], &[1][..], &[][..]);
+            display(
+                &mut out,
+                &headers,
+                inputs,
+                &expansions,
+                Columns::default(),
+                &styles,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_that!(
+            table(TableStyle::Markdown),
+            equal_to(
+                "|  Base 10   |         Base 2         |\n\
+                 |:-----------|:-----------------------|\n\
+                 | 0.5        | 0.1;                   |\n"
+            )
+        );
+        assert_that!(
+            table(TableStyle::Ascii),
+            equal_to(
+                "+------------+------------------------+\n\
+                 |  Base 10   |         Base 2         |\n\
+                 +------------+------------------------+\n\
+                 | 0.5        | 0.1;                   |\n\
+                 +------------+------------------------+\n"
+            )
+        );
+        assert_that!(
+            table(TableStyle::Unicode),
+            equal_to(
+                "┌────────────┬────────────────────────┐\n\
+                 │  Base 10   │         Base 2         │\n\
+                 ├────────────┼────────────────────────┤\n\
+                 │ 0.5        │ 0.1;                   │\n\
+                 └────────────┴────────────────────────┘\n"
+            )
+        );
+        assert_that!(
+            table(TableStyle::Borderless),
+            equal_to(
+                "  Base 10            Base 2\n \
+                 ----------  ----------------------\n \
+                 0.5         0.1;\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_script_columns() {
+        let script = Script::parse(
+            "const COLUMNS = [\"ones\", \"second\"];\n\
+             repr = digits.map(|d| digit(d)).reduce(|text, d| text + d, \"\");\n\
+             columns.ones = digits.reduce(|sum, d| sum + d, 0);\n\
+             columns.second = digits[1];",
+        )
+        .unwrap();
+        let columns = Columns {
+            script: Some(Box::leak(Box::new(script))),
+            ..Columns::default()
+        };
+        let headers = vec!["Base 2".to_string()];
+        let mut out = Vec::new();
+        display(
+            &mut out,
+            &headers,
+            (&[0.75, 0.5], &[None, None], &[1, 1], &[]),
+            &[vec![expand(0.75, 2, 4)], vec![expand(0.5, 2, 4)]],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_that!(lines[0].contains(" ones "), is(true));
+        assert_that!(lines[0].contains(" second "), is(true));
+        assert_that!(lines[2].contains("| 11 "), is(true));
+        assert_that!(lines[2].contains("| 2 "), is(true));
+        // The script fails on the single digit of 0.5, which is left as it is.
+        assert_that!(lines[3].contains("| 0.1; "), is(true));
+        assert_that!(lines[3].contains("| n/a "), is(true));
+    }
+
+    #[test]
+    fn test_rows_reuse_the_buffer() {
+        let headers = vec!["Base 2".to_string()];
+        let columns = Columns {
+            label: false,
+            hide_input: false,
+            hide_expansions: false,
+            decimal_places: Some(8),
+            round_trip_error: true,
+            digits_value: false,
+            sparkline: false,
+            terminates: false,
+            period: false,
+            digits_needed: false,
+            count: false,
+            fraction: false,
+            exact_value: false,
+            verify: None,
+            bit_groups: None,
+            script: None,
+        };
+        let mut buffer = RowBuffer::default();
+        let mut out = Vec::new();
+        for number in [0.1, 0.5] {
+            let row_expansions = [expand(number, 2, 4)];
+            display_row(
+                &mut out,
+                &mut buffer,
+                &headers,
+                (number, None, 1, None),
+                &row_expansions,
+                columns,
+                &Styles::default(),
+            )
+            .unwrap();
+        }
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "| 0.10000000 | 0.0;0;0;1;             | 3.750e-2   |\n\
+                 | 0.50000000 | 0.1;                   | 0          |\n"
+            )
+        );
+
+        let columns = Columns {
+            exact_value: true,
+            ..Columns::default()
+        };
+        let mut out = Vec::new();
+        display_row(
+            &mut out,
+            &mut buffer,
+            &headers,
+            (0.1, None, 1, None),
+            &[expand(0.1, 2, 4)],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "| 0.1        | 0.1000000000000000055511151231257827021181583404541015625 \
+                 | 0.0;0;0;1;             |\n"
+            )
+        );
+
+        let columns = Columns {
+            digits_value: true,
+            ..Columns::default()
+        };
+        let mut out = Vec::new();
+        display_row(
+            &mut out,
+            &mut buffer,
+            &headers,
+            (0.1, None, 1, None),
+            &[expand(0.1, 2, 4)],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("| 0.1        | 0.0;0;0;1;             | 1/16           | 3/80           |\n")
+        );
+
+        let columns = Columns {
+            sparkline: true,
+            ..Columns::default()
+        };
+        let mut out = Vec::new();
+        display_row(
+            &mut out,
+            &mut buffer,
+            &headers,
+            (0.1, None, 1, None),
+            &[expand(0.1, 2, 4)],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("| 0.1        | 0.0;0;0;1;             | ████▂       |\n")
+        );
+
+        let columns = Columns {
+            label: true,
+            ..Columns::default()
+        };
+        let mut out = Vec::new();
+        display_row(
+            &mut out,
+            &mut buffer,
+            &headers,
+            (0.5, None, 1, Some("half")),
+            &[expand(0.5, 2, 4)],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("| half       | 0.5        | 0.1;                   |\n")
+        );
+
+        let columns = Columns {
+            hide_input: true,
+            hide_expansions: true,
+            round_trip_error: true,
+            ..Columns::default()
+        };
+        let mut out = Vec::new();
+        display_row(
+            &mut out,
+            &mut buffer,
+            &headers,
+            (0.1, None, 1, None),
+            &[expand(0.1, 2, 4)],
+            columns,
+            &Styles::default(),
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("| 3.750e-2   |\n")
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::{self, BufRead, Write};
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+/// What the prompt of the repl starts with.
+pub const PROMPT: &str = "> ";
+
+/// Where the repl reads the lines typed at its prompt.
+pub trait LineSource {
+    /// Shows the prompt and reads the next line.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The lines entered before, oldest first.
+    /// * `out` - Where the prompt is written.
+    ///
+    /// # Returns
+    ///
+    /// The line, or `None` once the input ends.
+    fn read_entry(&mut self, history: &[String], out: &mut dyn Write)
+        -> io::Result<Option<String>>;
+}
+
+/// Lines read as they are, as from a pipe or a file, with no editing.
+impl<R: BufRead> LineSource for R {
+    fn read_entry(
+        &mut self,
+        _history: &[String],
+        out: &mut dyn Write,
+    ) -> io::Result<Option<String>> {
+        write!(out, "{}", PROMPT)?;
+        out.flush()?;
+        let mut line = String::new();
+        if self.read_line(&mut line)? == 0 {
+            writeln!(out)?;
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+}
+
+/// Lines typed at a terminal, edited with rustyline: the arrows, Home, End,
+/// Backspace and Delete (or Ctrl-A, Ctrl-E, Ctrl-B and Ctrl-F), with Up and
+/// Down going through the history and Ctrl-R searching it.
+pub struct Terminal {
+    editor: DefaultEditor,
+    /// How many of the lines of the history the editor was given.
+    known: usize,
+}
+
+impl Terminal {
+    /// Opens the terminal of standard input for editing.
+    ///
+    /// # Returns
+    ///
+    /// The terminal, or `None` if rustyline cannot use it.
+    pub fn open() -> Option<Terminal> {
+        Some(Terminal {
+            editor: DefaultEditor::new().ok()?,
+            known: 0,
+        })
+    }
+}
+
+impl LineSource for Terminal {
+    fn read_entry(
+        &mut self,
+        history: &[String],
+        out: &mut dyn Write,
+    ) -> io::Result<Option<String>> {
+        for entry in &history[self.known.min(history.len())..] {
+            self.editor
+                .add_history_entry(entry.as_str())
+                .map_err(io::Error::other)?;
+        }
+        self.known = history.len();
+        loop {
+            match self.editor.readline(PROMPT) {
+                Ok(line) => return Ok(Some(line)),
+                // Ctrl-C drops the line typed so far.
+                Err(ReadlineError::Interrupted) => {}
+                Err(ReadlineError::Eof) => {
+                    writeln!(out)?;
+                    return Ok(None);
+                }
+                Err(error) => return Err(io::Error::other(error)),
+            }
+        }
+    }
+}
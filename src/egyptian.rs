@@ -0,0 +1,195 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::cli::flag_value;
+use crate::pipeline::Pipeline;
+use crate::rational::Ratio;
+use crate::terminate::{exact_input, gcd};
+
+/// The most unit fractions `egyptian` writes when no `--max-terms` is given.
+pub const DEFAULT_MAX_TERMS: usize = 20;
+
+/// A positive number written as its integer part plus a sum of distinct
+/// unit fractions, such as 1 + 1/2 + 1/4 for 7/4.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgyptianFraction {
+    /// The integer part.
+    pub integer: BigUint,
+    /// The denominators of the unit fractions, in increasing order.
+    pub denominators: Vec<BigUint>,
+    /// Whether the unit fractions are all of them, rather than cut off.
+    pub complete: bool,
+}
+
+/// Decomposes a positive number with the greedy algorithm of Fibonacci and
+/// Sylvester: every step takes the largest unit fraction 1/c not above what
+/// is left, where c is the ceiling of its reciprocal. What is left then has
+/// a smaller numerator, so the decomposition always ends, and the
+/// denominators strictly grow, so they are distinct.
+///
+/// # Arguments
+///
+/// * `ratio` - The number to decompose, which must not be negative.
+/// * `max_terms` - The largest number of unit fractions to produce.
+///
+/// # Example
+///
+/// ```
+/// let ratio = Ratio::from(Fraction::parse("4/13").unwrap());
+/// assert_eq!(egyptian(&ratio, 20).to_string(), "1/4 + 1/18 + 1/468");
+/// ```
+pub fn egyptian(ratio: &Ratio, max_terms: usize) -> EgyptianFraction {
+    let mut numerator = &ratio.numerator % &ratio.denominator;
+    let mut denominator = ratio.denominator.clone();
+    let mut decomposition = EgyptianFraction {
+        integer: &ratio.numerator / &ratio.denominator,
+        denominators: Vec::new(),
+        complete: true,
+    };
+
+    while !numerator.is_zero() {
+        if decomposition.denominators.len() == max_terms {
+            decomposition.complete = false;
+            break;
+        }
+        let unit = (&denominator + &numerator - 1u32) / &numerator;
+        // a/b - 1/c = (a·c - b) / (b·c), which is not negative since c ≥ b/a.
+        numerator = &numerator * &unit - &denominator;
+        denominator *= &unit;
+        let divisor = gcd(numerator.clone(), denominator.clone());
+        if !divisor.is_one() {
+            numerator /= &divisor;
+            denominator /= &divisor;
+        }
+        decomposition.denominators.push(unit);
+    }
+    decomposition
+}
+
+impl fmt::Display for EgyptianFraction {
+    /// Writes the sum as `1 + 1/2 + 1/4`, ending in `+ …` when it was cut
+    /// off.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms: Vec<String> = Vec::new();
+        if !self.integer.is_zero() || self.denominators.is_empty() {
+            terms.push(self.integer.to_string());
+        }
+        terms.extend(
+            self.denominators
+                .iter()
+                .map(|denominator| format!("1/{}", denominator)),
+        );
+        if !self.complete {
+            terms.push("…".to_string());
+        }
+        write!(f, "{}", terms.join(" + "))
+    }
+}
+
+/// The settings of the `egyptian` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EgyptianOptions {
+    /// The largest number of unit fractions to write for every input.
+    pub max_terms: usize,
+    /// Every input as typed, with its exact value.
+    pub inputs: Vec<(String, Ratio)>,
+}
+
+/// Parses the arguments of the `egyptian` subcommand.
+///
+/// Inputs are positive fractions such as `4/13`, or decimals, which stand
+/// for the shortest decimal that reads back as their `f64`, such as 1/10
+/// for `0.1`.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `egyptian`.
+///
+/// # Returns
+///
+/// The parsed `EgyptianOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- egyptian --max-terms 5 4/13 0.7
+/// ```
+pub fn parse_egyptian_args(args: &[String]) -> Result<EgyptianOptions, String> {
+    let mut options = EgyptianOptions {
+        max_terms: DEFAULT_MAX_TERMS,
+        inputs: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--max-terms", &mut iter)? {
+            options.max_terms = value
+                .parse::<usize>()
+                .ok()
+                .filter(|&terms| terms > 0)
+                .ok_or_else(|| format!("invalid term count `{}`", value))?;
+        } else {
+            let ratio = Pipeline::with_bases(vec![2.0])
+                .decode_input(arg)
+                .and_then(|(value, fraction)| exact_input(value, fraction.as_ref()))
+                .ok_or_else(|| format!("unknown egyptian argument `{}`", arg))?;
+            if ratio.negative || ratio.numerator.is_zero() {
+                return Err(format!("egyptian requires positive numbers, not `{}`", arg));
+            }
+            options.inputs.push((arg.clone(), ratio));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Runs the `egyptian` subcommand, printing the decomposition of every input.
+///
+/// # Example
+/// ```
+/// // cargo run -- egyptian 4/13 7/4
+/// ```
+/// Output:
+/// 4/13 = 1/4 + 1/18 + 1/468
+/// 7/4 = 1 + 1/2 + 1/4
+pub fn display_egyptian(out: &mut impl Write, options: &EgyptianOptions) -> io::Result<()> {
+    for (input, ratio) in &options.inputs {
+        writeln!(out, "{} = {}", input, egyptian(ratio, options.max_terms))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn decompose(line: &str) -> String {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        let options = parse_egyptian_args(&args).unwrap();
+        egyptian(&options.inputs[0].1, options.max_terms).to_string()
+    }
+
+    #[test]
+    fn test_egyptian_fractions() {
+        assert_that!(decompose("4/13"), equal_to("1/4 + 1/18 + 1/468"));
+        assert_that!(decompose("7/4"), equal_to("1 + 1/2 + 1/4"));
+        assert_that!(decompose("0.1"), equal_to("1/10"));
+        assert_that!(decompose("0.75"), equal_to("1/2 + 1/4"));
+        assert_that!(decompose("3"), equal_to("3"));
+        assert_that!(
+            decompose("5/121"),
+            equal_to("1/25 + 1/757 + 1/763309 + 1/873960180913 + 1/1527612795642093418846225")
+        );
+        assert_that!(
+            decompose("--max-terms 2 5/121"),
+            equal_to("1/25 + 1/757 + …")
+        );
+
+        assert!(parse_egyptian_args(&["-1/2".to_string()]).is_err());
+        assert!(parse_egyptian_args(&["0".to_string()]).is_err());
+        assert!(parse_egyptian_args(&["--max-terms=0".to_string()]).is_err());
+    }
+}
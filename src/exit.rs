@@ -0,0 +1,251 @@
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::diagnostic::{locate_error, snippet, Span};
+use crate::rational::Fraction;
+
+/// The exit code of a run that skipped some of its inputs, or that
+/// `--strict` stopped at the first of them.
+pub const EXIT_SKIPPED: i32 = 1;
+
+/// The exit code of a run given an invalid base, flag or other usage error.
+pub const EXIT_USAGE: i32 = 2;
+
+/// The exit code of a run in which `--verify-roundtrip` failed a row.
+pub const EXIT_ROUND_TRIP: i32 = 3;
+
+/// The number of inputs skipped so far because they could not be decoded.
+static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// What a run does with the inputs it cannot read, from `--error-mode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorMode {
+    /// Skip every one with a warning as soon as it is read (the default).
+    #[default]
+    Skip,
+    /// Stop the run at the first one, as `--strict` does.
+    FailFast,
+    /// Skip every one and report them all together once the results are
+    /// written.
+    Collect,
+}
+
+impl ErrorMode {
+    /// Parses the value of `--error-mode`.
+    pub fn from_name(name: &str) -> Option<ErrorMode> {
+        match name {
+            "skip" => Some(ErrorMode::Skip),
+            "fail-fast" => Some(ErrorMode::FailFast),
+            "collect" => Some(ErrorMode::Collect),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the warnings about skipped inputs are kept for the end of the
+/// run, with `--error-mode collect`.
+static COLLECTING: AtomicBool = AtomicBool::new(false);
+
+/// The warnings kept for the end of the run, in the order of the inputs.
+static COLLECTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Sets how the warnings about skipped inputs are reported for the rest of
+/// the run.
+pub fn set_error_mode(mode: ErrorMode) {
+    COLLECTING.store(mode == ErrorMode::Collect, Ordering::Relaxed);
+}
+
+/// Prints a warning about a skipped input, or keeps it for
+/// [`report_collected`], and counts the input.
+fn warn_skipped(message: String) {
+    if COLLECTING.load(Ordering::Relaxed) {
+        COLLECTED
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .push(message);
+    } else {
+        eprintln!("warning: {}", message);
+    }
+    SKIPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Prints the warnings `--error-mode collect` kept, as errors, with how many
+/// inputs were skipped.
+pub fn report_collected() {
+    let collected =
+        std::mem::take(&mut *COLLECTED.lock().unwrap_or_else(|error| error.into_inner()));
+    if collected.is_empty() {
+        return;
+    }
+    for message in &collected {
+        eprintln!("error: {}", message);
+    }
+    eprintln!(
+        "error: {} of the inputs {} not valid",
+        collected.len(),
+        if collected.len() == 1 { "was" } else { "were" }
+    );
+}
+
+/// Warns on standard error that an input is skipped, and counts it so that
+/// the run ends with `EXIT_SKIPPED`.
+pub fn skip_input(input: &str) {
+    warn_skipped(format!(
+        "skipped `{}`, which is not a valid input{}",
+        input,
+        why_invalid(input)
+    ));
+}
+
+/// Like [`skip_input`], but also draws the line the input was read on, with
+/// the part of it that is wrong underlined.
+///
+/// # Arguments
+///
+/// * `input` - The invalid input.
+/// * `span` - Where it was read.
+/// * `valid` - Whether a text is a valid input, to find where it goes wrong.
+pub fn skip_input_at(input: &str, span: &Span, valid: impl Fn(&str) -> bool) {
+    warn_skipped(format!(
+        "skipped `{}`, which is not a valid input\n{}",
+        input,
+        snippet(
+            span,
+            &locate_error(input, reason_invalid(input).as_deref(), valid)
+        )
+    ));
+}
+
+/// Warns that the cell of a row of a `--column` CSV is skipped, and counts
+/// it as a skipped input.
+pub fn skip_row(path: &str, row: usize, cell: &str) {
+    warn_skipped(format!(
+        "`{}` row {}: skipped `{}`, which is not a valid input{}",
+        path,
+        row,
+        cell,
+        why_invalid(cell)
+    ));
+}
+
+/// The number of inputs skipped so far in this run.
+pub fn skipped_inputs() -> usize {
+    SKIPPED.load(Ordering::Relaxed)
+}
+
+/// The number of rows failed by `--verify-roundtrip`.
+static ROUND_TRIP_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a row whose round trip failed, so that the run ends with
+/// `EXIT_ROUND_TRIP`.
+pub fn fail_round_trip() {
+    ROUND_TRIP_FAILURES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of rows `--verify-roundtrip` failed so far in this run.
+pub fn round_trip_failures() -> usize {
+    ROUND_TRIP_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Ends a run whose results could not be written: quietly and with
+/// success when whatever reads them stopped reading, as `head` does, or
+/// else with the error and exit code 1.
+pub fn exit_on_write_error(error: io::Error) -> ! {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        process::exit(0);
+    }
+    eprintln!("error: {}", error);
+    process::exit(1);
+}
+
+/// The message of an error writing the results, unless whatever reads them
+/// stopped reading, which ends the run as [`exit_on_write_error`] does.
+pub fn write_error_message(error: io::Error) -> String {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        exit_on_write_error(error);
+    }
+    error.to_string()
+}
+
+/// The result of writing to standard output, ending the run as
+/// [`exit_on_write_error`] does if the writing failed.
+///
+/// # Example
+///
+/// ```
+/// // cargo run -- stats --base 2 0.1 0.3 | head -1 ends with head
+/// written(display_stats(&mut io::stdout().lock(), &options));
+/// ```
+pub fn written<T>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|error| exit_on_write_error(error))
+}
+
+/// The message `--strict` (or `--error-mode fail-fast`) stops the run with
+/// at the first invalid input.
+pub fn invalid_input(input: &str) -> String {
+    format!(
+        "`{}` is not a valid input{}, and the run stops at the first invalid input",
+        input,
+        why_invalid(input)
+    )
+}
+
+/// Like [`invalid_input`], but also draws where the input was read, as
+/// [`skip_input_at`] does.
+pub fn invalid_input_at(input: &str, span: &Span, valid: impl Fn(&str) -> bool) -> String {
+    format!(
+        "{}\n{}",
+        invalid_input(input),
+        snippet(
+            span,
+            &locate_error(input, reason_invalid(input).as_deref(), valid)
+        )
+    )
+}
+
+/// Whether an input names infinity or NaN, as `inf`, `-infinity` or `NaN`
+/// do, rather than being a number too large for an `f64` such as `1e400`.
+pub fn is_non_finite(input: &str) -> bool {
+    input.parse::<f64>().is_ok_and(|value| !value.is_finite())
+        && !input.contains(|c: char| c.is_ascii_digit())
+}
+
+/// Explains why an input written in scientific notation cannot be read,
+/// such as `1e400`, which is beyond every `f64`, or `1e`, which has no
+/// exponent, or a fraction such as `1_300/400` whose `_` reads two ways, as
+/// `: …` to follow the warning, or nothing for other inputs.
+fn why_invalid(input: &str) -> String {
+    reason_invalid(input)
+        .map(|reason| format!(": {}", reason))
+        .unwrap_or_default()
+}
+
+/// Why an input written in scientific notation, or a fraction with `_`s,
+/// cannot be read, if it is one.
+fn reason_invalid(input: &str) -> Option<String> {
+    if is_non_finite(input) {
+        return Some("infinities and NaN have no digits in any base".to_string());
+    }
+    if let Err(reason) = Fraction::parse_mixed(input.trim_end_matches('%')) {
+        return Some(reason);
+    }
+    let unsigned = input.trim_start_matches(['-', '+']);
+    let (mantissa, exponent) = unsigned.split_once(['e', 'E'])?;
+    let reason = if Fraction::parse_decimal(mantissa).is_none() {
+        None
+    } else if exponent.parse::<i64>().is_err() {
+        Some("the exponent after the e must be a whole number")
+    } else if unsigned.parse::<f64>().is_ok_and(f64::is_infinite) {
+        Some("it is beyond the largest f64")
+    } else if unsigned.parse::<f32>().is_ok_and(f32::is_infinite) {
+        // Only an input rounded to an `f32` is refused for that.
+        Some("it is beyond the largest f32")
+    } else {
+        // A number that fits was refused for another reason, such as
+        // `--decimal` reading no exponents.
+        None
+    };
+    reason.map(str::to_string)
+}
@@ -0,0 +1,160 @@
+use std::io::{self, Write};
+
+use crate::backend::Backend;
+use crate::beta::format_base;
+use crate::convert::expand;
+use crate::pipeline::Pipeline;
+
+/// One multiply-by-base step of the digit extraction loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// The fraction before it is multiplied.
+    pub fraction: f64,
+    /// The fraction multiplied by the base.
+    pub product: f64,
+    /// The digit taken from the integer part of the product.
+    pub digit: u32,
+    /// The fractional part of the product carried into the next step.
+    pub carry: f64,
+}
+
+/// Replays the digit extraction loop on the fractional part of `decimal`,
+/// recording every step it takes.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `target_base` - The base to convert to.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Returns
+///
+/// One `Step` per emitted digit, stopping early once the fraction is exhausted.
+///
+/// # Example
+///
+/// ```
+/// let steps = steps(0.75, 2, MAX_DIGITS);
+/// assert_eq!(steps[0].product, 1.5);
+/// assert_eq!(steps[1].carry, 0.0);
+/// ```
+pub fn steps(decimal: f64, target_base: u64, max_digits: u32) -> Vec<Step> {
+    let mut steps = Vec::new();
+    let mut fraction = decimal.abs().fract();
+    if fraction == 0.0 && decimal != 0.0 {
+        return steps;
+    }
+
+    for _ in 0..max_digits {
+        let product = fraction * target_base as f64;
+        let digit = product.floor() as u32;
+        let carry = product - digit as f64;
+        steps.push(Step {
+            fraction,
+            product,
+            digit,
+            carry,
+        });
+        fraction = carry;
+
+        if fraction == 0.0 {
+            break;
+        }
+    }
+
+    steps
+}
+
+/// Describes a single step as `0.7 × 2 = 1.4 → digit 1, carry 0.4`.
+pub fn describe(step: &Step, target_base: u64) -> String {
+    format!(
+        "{} × {} = {} → digit {}, carry {}",
+        step.fraction, target_base, step.product, step.digit, step.carry
+    )
+}
+
+/// Prints the worked conversion of every input into every target base: the
+/// integer part written in the base, the steps on the fractional part, and
+/// the result the pipeline produces, which ends in the same digits when the
+/// pipeline converts with `Backend::F64`, as `--explain` does unless another
+/// backend was asked for.
+///
+/// # Arguments
+///
+/// * `out` - Where the explanation is written.
+/// * `pipeline` - The stages every input is run through.
+/// * `f64_numbers` - The decoded input values.
+pub fn display_explanation(
+    out: &mut dyn Write,
+    pipeline: &Pipeline,
+    f64_numbers: &[f64],
+) -> io::Result<()> {
+    for &number in f64_numbers {
+        for &target_base in &pipeline.target_bases {
+            writeln!(out, "{} in base {}:", number, format_base(target_base))?;
+            if target_base < 0.0 || target_base.fract() != 0.0 {
+                writeln!(out, "  (steps are only shown for positive integer bases)")?;
+            } else {
+                let target_base = target_base as u64;
+                let integer = number.abs().trunc();
+                if integer != 0.0 {
+                    let written = expand(integer, target_base, 0);
+                    writeln!(out, "  integer part {} = {}", integer, written)?;
+                }
+                for step in steps(number, target_base, pipeline.digits) {
+                    writeln!(out, "  {}", describe(&step, target_base))?;
+                }
+            }
+            match pipeline.backend.filter(|&backend| backend != Backend::F64) {
+                Some(backend) => writeln!(
+                    out,
+                    "  result ({} backend): {}",
+                    backend.name(),
+                    pipeline.expand_to(number, target_base)
+                )?,
+                None => writeln!(out, "  result: {}", pipeline.expand_to(number, target_base))?,
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_steps_of_the_fractional_part() {
+        let steps = steps(2.75, 2, 8);
+        let digits: Vec<u32> = steps.iter().map(|step| step.digit).collect();
+        assert_that!(digits, equal_to(vec![1, 1]));
+        assert_that!(
+            describe(&steps[0], 2),
+            equal_to("0.75 × 2 = 1.5 → digit 1, carry 0.5")
+        );
+        assert!(super::steps(4.0, 2, 8).is_empty());
+
+        let mut out = Vec::new();
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        display_explanation(&mut out, &pipeline, &[2.75]).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("  integer part 2 = 1;0.\n  0.75 × 2 = 1.5"));
+    }
+
+    #[test]
+    fn test_steps_follow_the_conversion() {
+        let steps = steps(0.625, 2, 8);
+        let digits: Vec<u32> = steps.iter().map(|step| step.digit).collect();
+        assert_that!(digits, equal_to(vec![1, 0, 1]));
+        assert_that!(
+            describe(&steps[0], 2),
+            equal_to("0.625 × 2 = 1.25 → digit 1, carry 0.25")
+        );
+        assert_that!(
+            describe(&steps[2], 2),
+            equal_to("0.5 × 2 = 1 → digit 1, carry 0")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::{self, Write};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::inspect::{FloatFields, F64};
+use crate::pipeline::{without_underscores, Pipeline};
+use crate::rational::{Fraction, Ratio};
+use crate::terminate::{exact_input, termination, Termination};
+
+/// The title of the column giving the exact value of the `f64` every input
+/// is stored as, with `--exact-value`.
+pub const EXACT_VALUE_HEADER: &str = "Exact value";
+
+/// The settings of the `explain-float` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainFloatOptions {
+    /// Every input as typed, with its value and the exact fraction it was
+    /// written as, if any.
+    pub inputs: Vec<(String, f64, Option<Fraction>)>,
+}
+
+/// Parses the arguments of the `explain-float` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `explain-float`.
+///
+/// # Returns
+///
+/// The parsed `ExplainFloatOptions`, or a message describing why the
+/// arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- explain-float 0.1 0.5 1/3
+/// ```
+pub fn parse_explain_float_args(args: &[String]) -> Result<ExplainFloatOptions, String> {
+    let pipeline = Pipeline::with_bases(vec![2.0]);
+    let mut inputs = Vec::new();
+    for arg in args {
+        let (value, fraction) = pipeline
+            .decode_input(arg)
+            .filter(|(value, _)| value.is_finite())
+            .ok_or_else(|| format!("unknown explain-float argument `{}`", arg))?;
+        inputs.push((arg.clone(), value, fraction));
+    }
+    if inputs.is_empty() {
+        return Err("explain-float requires a value to explain".to_string());
+    }
+    Ok(ExplainFloatOptions { inputs })
+}
+
+/// Writes an exact value in full in decimal.
+///
+/// # Returns
+///
+/// The decimal, or `None` when the reduced denominator has a prime factor
+/// other than 2 and 5, so that the decimal never ends.
+///
+/// # Example
+///
+/// ```
+/// let eighth = Ratio::from(Fraction::parse("-1/8").unwrap());
+/// assert_eq!(decimal_string(&eighth).unwrap(), "-0.125");
+/// ```
+pub fn decimal_string(value: &Ratio) -> Option<String> {
+    let value = value.clone().reduced();
+    let mut rest = value.denominator.clone();
+    let mut places = [0usize; 2];
+    for (count, factor) in places.iter_mut().zip([2u32, 5]) {
+        while (&rest % factor).is_zero() {
+            rest /= factor;
+            *count += 1;
+        }
+    }
+    if !rest.is_one() {
+        return None;
+    }
+    let places = places[0].max(places[1]);
+    let scaled = value.numerator * BigUint::from(10u32).pow(places as u32) / &value.denominator;
+    let digits = format!("{:0>width$}", scaled, width = places + 1);
+    let (integer, decimals) = digits.split_at(digits.len() - places);
+    let sign = if value.negative { "-" } else { "" };
+    Some(if decimals.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, decimals)
+    })
+}
+
+/// Writes the exact value a finite `f64` holds in full in decimal, which
+/// always ends since its denominator is a power of two.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(
+///     exact_decimal(0.1).unwrap(),
+///     "0.1000000000000000055511151231257827021181583404541015625"
+/// );
+/// ```
+pub fn exact_decimal(number: f64) -> Option<String> {
+    Ratio::from_f64(number).and_then(|value| decimal_string(&value))
+}
+
+/// Says why a typed value is or is not stored exactly as an `f64`.
+///
+/// # Arguments
+///
+/// * `typed` - The exact value that was typed.
+/// * `stored` - The exact value of the nearest `f64`.
+///
+/// # Example
+///
+/// ```
+/// let tenth = Ratio::from(Fraction::parse("1/10").unwrap());
+/// let reason = explain_rounding(&tenth, &Ratio::from_f64(0.1).unwrap());
+/// assert!(reason.starts_with("its denominator 10 has the prime factor 5"));
+/// ```
+pub fn explain_rounding(typed: &Ratio, stored: &Ratio) -> String {
+    let typed = typed.clone().reduced();
+    if typed.minus(stored).numerator.is_zero() {
+        return "it is a sum of powers of two that fits in the 53 bits of an f64, \
+                so it is stored exactly"
+            .to_string();
+    }
+    match termination(&typed, 2.0) {
+        Termination::Repeats { factor, .. } => format!(
+            "its denominator {} has the prime factor {}, which does not divide 2, \
+             so its binary expansion never ends and is rounded to 53 significant bits",
+            typed.denominator, factor
+        ),
+        _ => "its binary expansion ends, but needs more significant bits or a wider \
+              exponent than an f64 has, so it is rounded to the nearest f64"
+            .to_string(),
+    }
+}
+
+/// The exact value of an input as it was typed: the fraction or long decimal
+/// it was read as, any other decimal digit for digit, and otherwise the
+/// shortest decimal that reads back as its `f64`.
+fn typed_value(input: &str, value: f64, fraction: Option<&Fraction>) -> Option<Ratio> {
+    fraction
+        .cloned()
+        .or_else(|| without_underscores(input).and_then(|input| Fraction::parse_decimal(&input)))
+        .map(Ratio::from)
+        .or_else(|| exact_input(value, None))
+        .map(Ratio::reduced)
+}
+
+/// Writes an exact value as a decimal when it ends, and as a fraction
+/// otherwise.
+fn exact_string(value: &Ratio) -> String {
+    decimal_string(value).unwrap_or_else(|| value.to_string())
+}
+
+/// Runs the `explain-float` subcommand, printing the fields of the `f64`
+/// every input is stored as, the exact value it holds, how far that is from
+/// the value typed, and why.
+///
+/// # Example
+/// ```
+/// // cargo run -- explain-float 0.1
+/// ```
+/// Output:
+/// 0.1
+///   sign:     0
+///   exponent: 01111111011 (1019, unbiased -4)
+///   mantissa: 1001100110011001100110011001100110011001100110011010
+///   stored:   1.100110011001100110011001100110011001100110011001101 × 2^-4
+///           = 0.1000000000000000055511151231257827021181583404541015625
+///   typed:    0.1 = 1/10
+///   error:    +0.0000000000000000055511151231257827021181583404541015625
+///   why:      its denominator 10 has the prime factor 5, which does not divide 2, so its binary expansion never ends and is rounded to 53 significant bits
+pub fn display_explain_float(
+    out: &mut impl Write,
+    options: &ExplainFloatOptions,
+) -> io::Result<()> {
+    for (i, (input, value, fraction)) in options.inputs.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        let fields = FloatFields::from_bits(value.to_bits(), F64);
+        let (Some(typed), Some(stored), Some(decimal)) = (
+            typed_value(input, *value, fraction.as_ref()),
+            Ratio::from_f64(*value),
+            exact_decimal(*value),
+        ) else {
+            continue;
+        };
+        let error = stored.minus(&typed);
+        writeln!(out, "{}", input)?;
+        writeln!(out, "  sign:     {}", fields.sign)?;
+        writeln!(
+            out,
+            "  exponent: {} ({}, unbiased {})",
+            fields.exponent_binary(),
+            fields.exponent,
+            fields.exponent as i64 - F64.bias()
+        )?;
+        writeln!(out, "  mantissa: {}", fields.mantissa_binary())?;
+        writeln!(out, "  stored:   {}", fields.stored_value())?;
+        writeln!(out, "          = {}", decimal)?;
+        match decimal_string(&typed) {
+            Some(typed_decimal) if !typed.denominator.is_one() => {
+                writeln!(out, "  typed:    {} = {}", typed_decimal, typed)?
+            }
+            _ => writeln!(out, "  typed:    {}", typed)?,
+        }
+        let sign = if error.negative { "" } else { "+" };
+        writeln!(out, "  error:    {}{}", sign, exact_string(&error))?;
+        writeln!(out, "  why:      {}", explain_rounding(&typed, &stored))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_explain_float() {
+        assert_that!(
+            exact_decimal(0.1).unwrap(),
+            equal_to("0.1000000000000000055511151231257827021181583404541015625".to_string())
+        );
+        assert_that!(exact_decimal(-2.5).unwrap(), equal_to("-2.5".to_string()));
+        assert_that!(exact_decimal(0.0).unwrap(), equal_to("0".to_string()));
+        assert_that!(exact_decimal(f64::NAN), equal_to(None));
+        assert_that!(
+            decimal_string(&Ratio::from(Fraction::parse("1/3").unwrap())),
+            equal_to(None)
+        );
+
+        let options = parse_explain_float_args(&["0.1".to_string()]).unwrap();
+        let (_, value, fraction) = &options.inputs[0];
+        let typed = typed_value("0.1", *value, fraction.as_ref()).unwrap();
+        let stored = Ratio::from_f64(*value).unwrap();
+        assert_that!(typed.to_string(), equal_to("1/10".to_string()));
+        assert_that!(
+            exact_string(&stored.minus(&typed)),
+            equal_to("0.0000000000000000055511151231257827021181583404541015625".to_string())
+        );
+        assert!(explain_rounding(&typed, &stored).contains("prime factor 5"));
+
+        let half = Ratio::from_f64(0.5).unwrap();
+        assert!(explain_rounding(&half, &half).contains("stored exactly"));
+        let long = typed_value("9007199254740993", 9007199254740992.0, None).unwrap();
+        assert_that!(long.to_string(), equal_to("9007199254740993".to_string()));
+        assert!(
+            explain_rounding(&long, &Ratio::from_f64(9007199254740992.0).unwrap()).contains("ends")
+        );
+
+        assert!(parse_explain_float_args(&[]).is_err());
+        assert!(parse_explain_float_args(&["junk".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::rational::{Fraction, Ratio};
+
+/// The deepest parentheses and signs can nest, so that no expression can
+/// overflow the stack of the parser.
+pub const MAX_DEPTH: usize = 256;
+
+/// Evaluates an arithmetic expression such as `0.1+0.2` or `(1/3)*2`
+/// exactly, with `+`, `-`, `*`, `/`, parentheses and signs, over decimal
+/// numbers such as `0.1`, `.5` or `2.5e-3`.
+///
+/// # Returns
+///
+/// The exact value of the expression, or `None` if it is not well formed,
+/// nests parentheses or signs more than `MAX_DEPTH` deep, or divides by
+/// zero.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(evaluate("0.1+0.2").unwrap().to_string(), "3/10");
+/// assert_eq!(evaluate("1/3*2").unwrap().to_string(), "2/3");
+/// assert_eq!(evaluate("1/(2-2)"), None);
+/// ```
+pub fn evaluate(expression: &str) -> Option<Ratio> {
+    let mut parser = Parser {
+        text: expression.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.sum()?;
+    (parser.position == parser.text.len()).then_some(value)
+}
+
+/// Reads an expression from left to right, one level of precedence per
+/// method.
+struct Parser<'a> {
+    /// The expression.
+    text: &'a [u8],
+    /// The index of the next byte to read.
+    position: usize,
+    /// How many factors are being read inside one another.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// The next byte, without reading it.
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    /// Reads terms joined by `+` and `-`.
+    fn sum(&mut self) -> Option<Ratio> {
+        let mut value = self.product()?;
+        while let Some(operator @ (b'+' | b'-')) = self.peek() {
+            self.position += 1;
+            let term = self.product()?;
+            value = if operator == b'+' {
+                value.plus(&term)
+            } else {
+                value.minus(&term)
+            };
+        }
+        Some(value)
+    }
+
+    /// Reads factors joined by `*` and `/`.
+    fn product(&mut self) -> Option<Ratio> {
+        let mut value = self.factor()?;
+        while let Some(operator @ (b'*' | b'/')) = self.peek() {
+            self.position += 1;
+            let factor = self.factor()?;
+            value = if operator == b'*' {
+                value.times(&factor)
+            } else {
+                value.divided_by(&factor)?
+            };
+        }
+        Some(value)
+    }
+
+    /// Reads a signed factor, a parenthesized expression or a number, unless
+    /// it nests too deep.
+    fn factor(&mut self) -> Option<Ratio> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.nested_factor();
+        self.depth -= 1;
+        value
+    }
+
+    /// Reads a factor, which `factor` has checked the depth of.
+    fn nested_factor(&mut self) -> Option<Ratio> {
+        match self.peek()? {
+            b'-' => {
+                self.position += 1;
+                Some(self.factor()?.negated())
+            }
+            b'+' => {
+                self.position += 1;
+                self.factor()
+            }
+            b'(' => {
+                self.position += 1;
+                let value = self.sum()?;
+                if self.peek()? != b')' {
+                    return None;
+                }
+                self.position += 1;
+                Some(value)
+            }
+            _ => self.number(),
+        }
+    }
+
+    /// Reads an unsigned decimal, with an optional exponent.
+    fn number(&mut self) -> Option<Ratio> {
+        let start = self.position;
+        let digits = |parser: &mut Parser| {
+            while parser.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+                parser.position += 1;
+            }
+        };
+        digits(self);
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits(self);
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            digits(self);
+        }
+        let text = std::str::from_utf8(&self.text[start..self.position]).ok()?;
+        // `.5` is as good as `0.5`, but `.` alone is no number.
+        let text = match text.strip_prefix('.') {
+            Some(decimals) if decimals.starts_with(|c: char| c.is_ascii_digit()) => {
+                format!("0{}", text)
+            }
+            _ => text.to_string(),
+        };
+        Fraction::parse_decimal(&text).map(Ratio::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_evaluate() {
+        let value = |expression: &str| evaluate(expression).map(|value| value.to_string());
+        assert_that!(value("0.1+0.2"), equal_to(Some("3/10".to_string())));
+        assert_that!(value("1/3*2"), equal_to(Some("2/3".to_string())));
+        assert_that!(value("1-2*3"), equal_to(Some("-5".to_string())));
+        assert_that!(value("(1-2)*3"), equal_to(Some("-3".to_string())));
+        assert_that!(value("-(1/4)--1/4"), equal_to(Some("0".to_string())));
+        assert_that!(value("2.5e-1+.25"), equal_to(Some("1/2".to_string())));
+        assert_that!(value("1e+2/8"), equal_to(Some("25/2".to_string())));
+        assert_that!(value("1/0"), equal_to(None));
+        assert_that!(value("(1+2"), equal_to(None));
+        assert_that!(value("1+"), equal_to(None));
+        assert_that!(value("1+2)"), equal_to(None));
+        assert_that!(value("."), equal_to(None));
+        assert_that!(value("0.1+x"), equal_to(None));
+
+        // Nesting deeper than the limit is refused rather than overflowing
+        // the stack.
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_that!(value(&nested), equal_to(Some("1".to_string())));
+        assert_that!(value(&"(".repeat(50000)), equal_to(None));
+        assert_that!(value(&format!("{}1", "-".repeat(50000))), equal_to(None));
+        assert_that!(
+            value(&format!("{}1", "-".repeat(MAX_DEPTH - 1))),
+            equal_to(Some("-1".to_string()))
+        );
+    }
+}
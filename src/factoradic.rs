@@ -0,0 +1,176 @@
+use std::fmt;
+
+use num_traits::{ToPrimitive, Zero};
+
+use crate::convert::Representation;
+use crate::radices::mixed_digits;
+use crate::rational::{Fraction, Ratio};
+
+/// A number in the factorial number system, where the integer digit for
+/// `k!` is at most `k` and the fractional digit for `1/k!` at most `k - 1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Factoradic {
+    /// Whether the converted value was negative.
+    pub negative: bool,
+    /// The integer digits, most significant first, ending with that of `1!`,
+    /// and empty when the integer part is zero.
+    pub integer_digits: Vec<u32>,
+    /// The fractional digits of `1/2!`, `1/3!`, `1/4!` and so on.
+    pub digits: Vec<u32>,
+    /// The part of the value left over after the last digit, measured in
+    /// units of the last digit's place.
+    pub remainder: f64,
+}
+
+/// Extracts up to `max_digits` fractional factoradic digits of an input.
+///
+/// Every step multiplies the fraction by the next place's factor, 2, 3, 4
+/// and so on, so that the digit of `1/k!` is below `k`. The input's exact
+/// value is used, which is the fraction it was written as, if any, or else
+/// the exact value of its `f64`. Since `k!` is eventually a multiple of
+/// every denominator, every such value terminates.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `fraction` - The exact fraction the input was written as, if any.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Example
+///
+/// ```
+/// // 5/6 = 1/2! + 2/3!
+/// assert_eq!(expand_factoradic(5.0 / 6.0, Fraction::parse("5/6").as_ref(), 8).to_string(), "0.1;2;");
+/// assert_eq!(expand_factoradic(5.5, None, 8).to_string(), "2;1.1;");
+/// ```
+pub fn expand_factoradic(decimal: f64, fraction: Option<&Fraction>, max_digits: u32) -> Factoradic {
+    let mut factoradic = Factoradic {
+        negative: decimal < 0.0,
+        integer_digits: Vec::new(),
+        digits: Vec::new(),
+        remainder: 0.0,
+    };
+    let exact = match fraction {
+        Some(fraction) => Ratio::from(fraction.clone()),
+        None => match Ratio::from_f64(decimal) {
+            Some(exact) => exact,
+            None => return factoradic,
+        },
+    };
+    let denominator = exact.denominator;
+
+    let mut integer = &exact.numerator / &denominator;
+    let mut place = 2u32;
+    while !integer.is_zero() {
+        factoradic
+            .integer_digits
+            .push((&integer % place).to_u32().unwrap_or(0));
+        integer /= place;
+        place += 1;
+    }
+    factoradic.integer_digits.reverse();
+
+    let (digits, rest) = mixed_digits(
+        &exact.numerator % &denominator,
+        &denominator,
+        (2..).take(max_digits as usize),
+    );
+    factoradic.digits = digits;
+    factoradic.remainder = Fraction {
+        negative: false,
+        numerator: rest,
+        denominator,
+    }
+    .value();
+    factoradic
+}
+
+impl Representation for Factoradic {
+    fn value(&self) -> f64 {
+        let integer = self
+            .integer_digits
+            .iter()
+            .rev()
+            .zip(1..)
+            .fold((0.0, 1.0), |(sum, factorial), (&digit, place)| {
+                let factorial = factorial * place as f64;
+                (sum + digit as f64 * factorial, factorial)
+            })
+            .0;
+        let fraction = self
+            .digits
+            .iter()
+            .zip(2..)
+            .fold((0.0, 1.0), |(sum, factorial), (&digit, place)| {
+                let factorial = factorial * place as f64;
+                (sum + digit as f64 / factorial, factorial)
+            })
+            .0;
+        let value = integer + fraction;
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        self.remainder == 0.0
+    }
+}
+
+impl fmt::Display for Factoradic {
+    /// Writes the expansion as `2;1.1;2;`, like an ordinary expansion.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative && !(self.integer_digits.is_empty() && self.digits.is_empty()) {
+            write!(f, "-")?;
+        }
+        if self.integer_digits.is_empty() {
+            write!(f, "0")?;
+        }
+        for (i, digit) in self.integer_digits.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", digit)?;
+        }
+        write!(f, ".")?;
+        for digit in &self.digits {
+            write!(f, "{};", digit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_factoradic_digits() {
+        let expand = |arg: &str, max_digits| {
+            let fraction = Fraction::parse(arg).unwrap();
+            expand_factoradic(fraction.value(), Some(&fraction), max_digits)
+        };
+        assert_that!(expand("5/6", 8).to_string(), equal_to("0.1;2;"));
+        assert_that!(expand("1/7", 8).to_string(), equal_to("0.0;0;3;2;0;6;"));
+        // 463 = 3·5! + 4·4! + 1·3! + 0·2! + 1·1!
+        assert_that!(expand("-463/1", 8).to_string(), equal_to("-3;4;1;0;1."));
+        assert!(expand("1/7", 8).is_exact());
+        assert!(!expand("1/7", 3).is_exact());
+        assert_that!(expand("1/7", 8).value(), close_to(1.0 / 7.0, 1e-15));
+
+        let tenth = expand_factoradic(0.1, None, 100);
+        assert!(tenth.is_exact());
+        assert!(tenth
+            .digits
+            .iter()
+            .zip(2..)
+            .all(|(&digit, place)| digit < place));
+        assert_that!(
+            expand_factoradic(0.1, None, 4).to_string(),
+            equal_to("0.0;0;2;2;")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::convert::Representation;
+use crate::display::round_trip_error;
+use crate::pipeline::Pipeline;
+use crate::rational::{Fraction, Ratio};
+use crate::terminate::{exact_input, termination, Termination};
+
+/// Which rows `--filter` keeps: those with at least one target base where
+/// the condition holds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowFilter {
+    /// The exact expansion of the input ends.
+    Terminating,
+    /// The exact expansion of the input repeats forever.
+    Repeating,
+    /// The expansion is cut off at the digit limit before it ends.
+    Truncated,
+    /// The value of the digits is further from the input than this.
+    ErrorAbove(f64),
+}
+
+/// Parses the value of `--filter`, such as `repeating` or `error>1e-6`.
+pub fn parse_row_filter(value: &str) -> Result<RowFilter, String> {
+    match value {
+        "terminating" => Ok(RowFilter::Terminating),
+        "repeating" => Ok(RowFilter::Repeating),
+        "truncated" => Ok(RowFilter::Truncated),
+        _ => match value.strip_prefix("error>") {
+            Some(epsilon) => epsilon
+                .parse::<f64>()
+                .ok()
+                .filter(|epsilon| epsilon.is_finite() && *epsilon >= 0.0)
+                .map(RowFilter::ErrorAbove)
+                .ok_or_else(|| {
+                    format!(
+                        "invalid error bound `{}` in --filter, expected a number such as 1e-6",
+                        epsilon
+                    )
+                }),
+            None => Err(format!(
+                "unknown filter `{}`, expected terminating, repeating, truncated or error>EPS",
+                value
+            )),
+        },
+    }
+}
+
+impl RowFilter {
+    /// Whether the row of an input is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The decoded input.
+    /// * `fraction` - The exact fraction it was written as, if any.
+    /// * `pipeline` - Expands the input into its target bases.
+    pub fn keeps(&self, number: f64, fraction: Option<&Fraction>, pipeline: &Pipeline) -> bool {
+        match self {
+            RowFilter::Terminating | RowFilter::Repeating => {
+                let Some(exact) = exact_input(number, fraction).map(Ratio::reduced) else {
+                    return false;
+                };
+                pipeline.target_bases.iter().any(|&target_base| {
+                    match termination(&exact, target_base) {
+                        Termination::Terminates => *self == RowFilter::Terminating,
+                        Termination::Repeats { .. } => *self == RowFilter::Repeating,
+                        Termination::Undefined => false,
+                    }
+                })
+            }
+            RowFilter::Truncated => pipeline.target_bases.iter().any(|&target_base| {
+                !pipeline
+                    .expand_input(number, fraction, target_base)
+                    .is_exact()
+            }),
+            RowFilter::ErrorAbove(epsilon) => pipeline.target_bases.iter().any(|&target_base| {
+                let expansion = pipeline.expand_input(number, fraction, target_base);
+                round_trip_error(number, &expansion) > *epsilon
+            }),
+        }
+    }
+}
+
+/// Drops the rows of the inputs that some filter does not keep, keeping the
+/// rest in their order.
+///
+/// # Arguments
+///
+/// * `inputs` - Every input's value and the fraction it was written as, if
+///   any, each with whatever else goes along with its row.
+/// * `filters` - The filters every kept row passes.
+/// * `pipeline` - Expands the inputs into their target bases.
+///
+/// # Example
+///
+/// ```
+/// let mut inputs = vec![((0.5, None), 'a'), ((0.1, Fraction::parse("1/10")), 'b')];
+/// filter_inputs(&mut inputs, &[RowFilter::Repeating], &Pipeline::with_bases(vec![2.0]));
+/// assert_eq!(inputs.len(), 1);
+/// ```
+pub fn filter_inputs<T>(
+    inputs: &mut Vec<((f64, Option<Fraction>), T)>,
+    filters: &[RowFilter],
+    pipeline: &Pipeline,
+) {
+    inputs.retain(|((number, fraction), _)| {
+        filters
+            .iter()
+            .all(|filter| filter.keeps(*number, fraction.as_ref(), pipeline))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn kept(values: &[&str], filters: &[RowFilter], bases: Vec<f64>) -> Vec<String> {
+        let pipeline = Pipeline::with_bases(bases);
+        let mut inputs: Vec<_> = values
+            .iter()
+            .map(|value| (pipeline.decode_input(value).unwrap(), value.to_string()))
+            .collect();
+        filter_inputs(&mut inputs, filters, &pipeline);
+        inputs.into_iter().map(|(_, value)| value).collect()
+    }
+
+    #[test]
+    fn test_parse_row_filter() {
+        assert_that!(
+            parse_row_filter("repeating"),
+            equal_to(Ok(RowFilter::Repeating))
+        );
+        assert_that!(
+            parse_row_filter("error>1e-6"),
+            equal_to(Ok(RowFilter::ErrorAbove(1e-6)))
+        );
+        assert!(parse_row_filter("error>-1").is_err());
+        assert!(parse_row_filter("error>x").is_err());
+        assert!(parse_row_filter("exact").is_err());
+    }
+
+    #[test]
+    fn test_filter_inputs() {
+        let values = ["0.5", "0.1", "1/3", "0.375"];
+        assert_that!(
+            kept(&values, &[RowFilter::Terminating], vec![2.0]),
+            equal_to(vec!["0.5".to_string(), "0.375".to_string()])
+        );
+        assert_that!(
+            kept(&values, &[RowFilter::Repeating], vec![2.0]),
+            equal_to(vec!["0.1".to_string(), "1/3".to_string()])
+        );
+        // A row is kept when one of its bases passes.
+        assert_that!(
+            kept(&values, &[RowFilter::Terminating], vec![2.0, 10.0]),
+            equal_to(vec![
+                "0.5".to_string(),
+                "0.1".to_string(),
+                "0.375".to_string()
+            ])
+        );
+        assert_that!(
+            kept(&values, &[RowFilter::Truncated], vec![2.0]),
+            equal_to(vec!["0.1".to_string(), "1/3".to_string()])
+        );
+        assert_that!(
+            kept(
+                &values,
+                &[RowFilter::Repeating, RowFilter::ErrorAbove(0.002)],
+                vec![2.0]
+            ),
+            equal_to(vec!["0.1".to_string()])
+        );
+    }
+}
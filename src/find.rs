@@ -0,0 +1,404 @@
+use std::io::{self, Write};
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::backend::Backend;
+use crate::cli::flag_value;
+use crate::convert::{Expansion, Representation};
+use crate::pipeline::{parse_backend, parse_base_list};
+use crate::rational::Fraction;
+
+/// The condition a scanned decimal has to satisfy for `find` to stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predicate {
+    /// The expansion terminates within the given number of digits.
+    TerminatesWithin(u32),
+    /// The expansion does not terminate within the given number of digits.
+    RunsPast(u32),
+}
+
+impl Predicate {
+    /// The number of digits the predicate looks at.
+    fn digits(&self) -> u32 {
+        match *self {
+            Predicate::TerminatesWithin(digits) | Predicate::RunsPast(digits) => digits,
+        }
+    }
+
+    /// Checks the predicate against an expansion cut off at [`Predicate::digits`].
+    fn holds(&self, expansion: &Expansion) -> bool {
+        match self {
+            Predicate::TerminatesWithin(_) => expansion.is_exact(),
+            Predicate::RunsPast(_) => !expansion.is_exact(),
+        }
+    }
+
+    /// Describes the predicate as in `terminates within 4 digits`.
+    fn describe(&self) -> String {
+        match self {
+            Predicate::TerminatesWithin(digits) => format!("terminates within {} digits", digits),
+            Predicate::RunsPast(digits) => format!("runs past {} digits", digits),
+        }
+    }
+}
+
+/// An evenly spaced range of decimals to scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanRange {
+    /// The first decimal scanned.
+    pub from: f64,
+    /// The end of the scanned range, which is itself not scanned.
+    pub to: f64,
+    /// The distance between scanned decimals.
+    pub step: f64,
+    /// How many decimal places the scanned values are rounded to, so that
+    /// e.g. a step of `0.01` visits `0.07` rather than `0.07000000000000001`.
+    pub places: Option<i32>,
+    /// The first decimal as an exact fraction, if it was written as a
+    /// fraction or a whole number.
+    pub from_fraction: Option<Fraction>,
+    /// The step as an exact fraction, if it was written as one.
+    pub step_fraction: Option<Fraction>,
+}
+
+impl Default for ScanRange {
+    /// Scans `0, 0.01, …, 0.99`.
+    fn default() -> ScanRange {
+        ScanRange {
+            from: 0.0,
+            to: 1.0,
+            step: 0.01,
+            places: Some(2),
+            from_fraction: parse_exact_scan_number("0"),
+            step_fraction: None,
+        }
+    }
+}
+
+impl ScanRange {
+    /// Applies a `--from`, `--to` or `--step` argument to the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `arg` - The argument currently being parsed.
+    /// * `rest` - The remaining arguments, from which a separate value is taken.
+    ///
+    /// # Returns
+    ///
+    /// Whether `arg` was one of the range flags, or an error if its value is invalid.
+    pub fn parse_flag<'a>(
+        &mut self,
+        arg: &'a str,
+        rest: &mut impl Iterator<Item = &'a String>,
+    ) -> Result<bool, String> {
+        if let Some(value) = flag_value(arg, "--from", rest)? {
+            self.from = parse_scan_number(value)?;
+            self.from_fraction = parse_exact_scan_number(value);
+        } else if let Some(value) = flag_value(arg, "--to", rest)? {
+            self.to = parse_scan_number(value)?;
+        } else if let Some(value) = flag_value(arg, "--step", rest)? {
+            self.step = parse_scan_number(value)?;
+            self.places = decimal_places(value);
+            self.step_fraction = Fraction::parse(value);
+            if self.step <= 0.0 {
+                return Err("--step must be positive".to_string());
+            }
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Iterates over `from, from + step, …` up to (but excluding) `to`.
+    ///
+    /// Every value comes with the exact fraction it stands for when the
+    /// start and the step were both written as fractions (or the start as a
+    /// whole number), so that e.g. `1/81` can be converted as exactly one
+    /// eighty-first rather than as the `f64` nearest to it.
+    pub fn values(&self) -> impl Iterator<Item = (f64, Option<Fraction>)> + '_ {
+        (0u64..)
+            .map(|i| {
+                let value = self.from + i as f64 * self.step;
+                let value = match self.places {
+                    Some(places) => {
+                        let scale = 10f64.powi(places);
+                        (value * scale).round() / scale
+                    }
+                    None => value,
+                };
+                (value, self.fraction_at(i))
+            })
+            .take_while(|&(value, _)| value < self.to)
+    }
+
+    /// The exact fraction `from + i·step`, if both are known exactly.
+    fn fraction_at(&self, i: u64) -> Option<Fraction> {
+        let (from, step) = (self.from_fraction.as_ref()?, self.step_fraction.as_ref()?);
+        let signed = |fraction: &Fraction, numerator: BigUint| {
+            let sign = if fraction.negative {
+                Sign::Minus
+            } else {
+                Sign::Plus
+            };
+            BigInt::from_biguint(sign, numerator)
+        };
+        let numerator = signed(from, &from.numerator * &step.denominator)
+            + signed(step, &step.numerator * &from.denominator) * i;
+        Some(Fraction {
+            negative: numerator.sign() == Sign::Minus,
+            numerator: numerator.magnitude().clone(),
+            denominator: &from.denominator * &step.denominator,
+        })
+    }
+}
+
+/// The settings of the `find` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindOptions {
+    /// The decimals to scan.
+    pub range: ScanRange,
+    /// The base the expansions are computed in.
+    pub target_base: u64,
+    /// The condition to stop at.
+    pub predicate: Predicate,
+    /// The backend forced with `--backend`, or `None` to pick an exact one
+    /// per decimal.
+    pub backend: Option<Backend>,
+}
+
+/// Parses a scan bound or step written as a decimal (`0.25`) or a fraction (`1/81`).
+fn parse_scan_number(value: &str) -> Result<f64, String> {
+    let parsed = match value.split_once('/') {
+        Some((numerator, denominator)) => numerator
+            .parse::<f64>()
+            .ok()
+            .zip(denominator.parse::<f64>().ok())
+            .filter(|&(_, denominator)| denominator != 0.0)
+            .map(|(numerator, denominator)| numerator / denominator),
+        None => value.parse::<f64>().ok(),
+    };
+    parsed
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| format!("invalid number `{}`", value))
+}
+
+/// Parses the arguments of the `find` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `find`.
+///
+/// # Returns
+///
+/// The parsed `FindOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- find --from 0 --to 1 --step 1/81 --base 3 --terminates-within 4
+/// ```
+pub fn parse_find_args(args: &[String]) -> Result<FindOptions, String> {
+    let mut options = FindOptions {
+        range: ScanRange::default(),
+        target_base: 2,
+        predicate: Predicate::TerminatesWithin(4),
+        backend: None,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--terminates-within", &mut iter)? {
+            options.predicate = Predicate::TerminatesWithin(parse_digits(value)?);
+        } else if let Some(value) = flag_value(arg, "--runs-past", &mut iter)? {
+            options.predicate = Predicate::RunsPast(parse_digits(value)?);
+        } else if let Some(value) = flag_value(arg, "--backend", &mut iter)? {
+            options.backend = parse_backend(value)?;
+        } else if !options.range.parse_flag(arg, &mut iter)? {
+            return Err(format!("unknown find argument `{}`", arg));
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses the value of a `--base` flag that only accepts one positive base.
+pub fn parse_single_base(value: &str) -> Result<u64, String> {
+    match parse_base_list(value)?[..] {
+        [target_base] if target_base < 0.0 => Err(format!(
+            "negative base `{}` is not supported here",
+            target_base
+        )),
+        [target_base] => Ok(target_base as u64),
+        _ => Err("only a single --base can be given".to_string()),
+    }
+}
+
+/// Reads a scan bound written as a fraction (`1/81`) or a whole number (`2`)
+/// as an exact fraction, or `None` for other decimals.
+fn parse_exact_scan_number(value: &str) -> Option<Fraction> {
+    Fraction::parse(value).or_else(|| Fraction::parse(&format!("{}/1", value)))
+}
+
+/// Parses a digit count argument.
+pub fn parse_digits(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .map_err(|_| format!("invalid digit count `{}`", value))
+}
+
+/// Counts the decimal places of a step written as a decimal, such as 3 for
+/// `0.001` or `1e-3`, or `None` for fractions.
+fn decimal_places(value: &str) -> Option<i32> {
+    if value.contains('/') {
+        return None;
+    }
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (value, 0),
+    };
+    let places = mantissa
+        .split_once('.')
+        .map_or(0, |(_, places)| places.len() as i32);
+    Some(places.saturating_sub(exponent).max(0))
+}
+
+/// Scans `from, from + step, …` up to (but excluding) `to` and returns the
+/// first decimal whose expansion satisfies the predicate, together with that
+/// expansion. Zero is skipped, since its expansion trivially terminates and
+/// would otherwise be the answer to every `--terminates-within` from `0`.
+///
+/// # Example
+/// ```
+/// let options = parse_find_args(&["--terminates-within".to_string(), "1".to_string()]).unwrap();
+/// let found = find_first(&options);
+/// assert_eq!(found.map(|(value, _)| value), Some(0.5));
+/// ```
+pub fn find_first(options: &FindOptions) -> Option<(f64, Expansion)> {
+    let digits = options.predicate.digits();
+    options
+        .range
+        .values()
+        .filter(|&(value, _)| value != 0.0)
+        .map(|(value, fraction)| {
+            let expansion = Backend::expand_auto(
+                options.backend,
+                value,
+                fraction.as_ref(),
+                options.target_base,
+                digits,
+            );
+            (value, expansion)
+        })
+        .find(|(_, expansion)| options.predicate.holds(expansion))
+}
+
+/// Runs the `find` subcommand, printing the first match.
+///
+/// # Returns
+///
+/// Whether a matching decimal was found, or the error writing to `out`.
+pub fn display_find(out: &mut impl Write, options: &FindOptions) -> io::Result<bool> {
+    let condition = format!(
+        "{} in base {}",
+        options.predicate.describe(),
+        options.target_base
+    );
+    match find_first(options) {
+        Some((value, expansion)) => {
+            writeln!(out, "{} {}: {}", value, condition, expansion)?;
+            Ok(true)
+        }
+        None => {
+            writeln!(
+                out,
+                "no decimal from {} to {} {}",
+                options.range.from, options.range.to, condition
+            )?;
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_find_stops_at_first_match() {
+        let options =
+            parse_find_args(&args("--from 0.01 --step 0.01 --terminates-within 2")).unwrap();
+        let (value, expansion) = find_first(&options).unwrap();
+        assert_that!(value, equal_to(0.25));
+        assert_that!(expansion.to_string(), equal_to("0.0;1;"));
+
+        let options = parse_find_args(&args(
+            "--from 1/81 --step 1/81 --base 3 --terminates-within 4",
+        ))
+        .unwrap();
+        assert_that!(
+            find_first(&options).map(|(_, e)| e.to_string()),
+            equal_to(Some("0.0;0;0;1;".to_string()))
+        );
+
+        let options = parse_find_args(&args("--base 3 --step 1/81 --terminates-within 4")).unwrap();
+        assert_that!(
+            find_first(&options).map(|(_, e)| e.to_string()),
+            equal_to(Some("0.0;0;0;1;".to_string()))
+        );
+
+        let options = parse_find_args(&args("--from 0.5 --base 10 --runs-past 3")).unwrap();
+        assert_that!(
+            find_first(&options).map(|(value, _)| value),
+            equal_to(Some(0.51))
+        );
+    }
+
+    #[test]
+    fn test_find_arguments() {
+        assert!(parse_find_args(&args("--step 0")).is_err());
+        assert!(parse_find_args(&args("--step 1/0")).is_err());
+        assert!(parse_find_args(&args("--base 2,8")).is_err());
+        assert!(parse_find_args(&args("--base -2")).is_err());
+        assert!(parse_find_args(&args("0.5")).is_err());
+        assert_that!(decimal_places("0.001"), equal_to(Some(3)));
+        assert_that!(decimal_places("1/3"), equal_to(None));
+        assert_that!(decimal_places("2.5E-2"), equal_to(Some(3)));
+        assert_that!(decimal_places("1.25e1"), equal_to(Some(1)));
+        assert_that!(decimal_places("1e3"), equal_to(Some(0)));
+    }
+
+    #[test]
+    fn test_scanned_fractions() {
+        let options = parse_find_args(&args("--from 1 --to 2 --step 1/3")).unwrap();
+        let fractions: Vec<_> = options
+            .range
+            .values()
+            .map(|(_, fraction)| {
+                fraction.map(|f| (f.numerator.to_string(), f.denominator.to_string()))
+            })
+            .collect();
+        assert_that!(
+            fractions,
+            equal_to(
+                [(3, 3), (4, 3), (5, 3)]
+                    .map(|(p, q)| Some((p.to_string(), q.to_string())))
+                    .to_vec()
+            )
+        );
+
+        let options = parse_find_args(&args("--from 0.5 --step 1/3")).unwrap();
+        assert!(options
+            .range
+            .values()
+            .all(|(_, fraction)| fraction.is_none()));
+
+        let options = parse_find_args(&args("--backend f64")).unwrap();
+        assert_that!(options.backend, equal_to(Some(Backend::F64)));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use crate::convert::{Representation, Rounding};
+
+/// A value stored as an N-bit two's complement Qm.n fixed-point number, the
+/// inverse of the `from=q<m>.<n>` input stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedPoint {
+    /// The number of integer bits, including the sign bit.
+    pub integer_bits: u32,
+    /// The number of fraction bits.
+    pub fraction_bits: u32,
+    /// The stored two's complement integer, the value times 2^fraction_bits.
+    pub raw: i64,
+    /// Whether the value was stored without rounding or saturating it.
+    pub exact: bool,
+    /// Whether the value was outside of the range of the format, and was
+    /// stored as its smallest or largest number instead.
+    pub saturated: bool,
+}
+
+/// Stores a decimal as a Qm.n fixed-point number.
+///
+/// Values outside of the range of the format saturate to its smallest or
+/// largest number, and are marked as saturated when written. Truncating drops the bits past the last one, which like
+/// an arithmetic shift rounds negative values towards minus infinity.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to store.
+/// * `integer_bits` - The number of integer bits, including the sign bit.
+/// * `fraction_bits` - The number of fraction bits.
+/// * `rounding` - How the bits past the last one are settled.
+///
+/// # Example
+///
+/// ```
+/// let q = to_fixed_point(-0.5, 1, 15, Rounding::HalfEven);
+/// assert_eq!(q.to_string(), "1.100000000000000 (0xc000)");
+/// ```
+pub fn to_fixed_point(
+    decimal: f64,
+    integer_bits: u32,
+    fraction_bits: u32,
+    rounding: Rounding,
+) -> FixedPoint {
+    let scaled = decimal * (1u64 << fraction_bits) as f64;
+    let rounded = match rounding {
+        Rounding::Truncate => scaled.floor(),
+        Rounding::HalfUp => (scaled + 0.5).floor(),
+        Rounding::HalfEven => scaled.round_ties_even(),
+    };
+    let limit = (1i64 << (integer_bits + fraction_bits - 1)) as f64;
+    let clamped = rounded.clamp(-limit, limit - 1.0);
+
+    FixedPoint {
+        integer_bits,
+        fraction_bits,
+        raw: clamped as i64,
+        exact: clamped == scaled,
+        saturated: clamped != rounded,
+    }
+}
+
+impl Representation for FixedPoint {
+    fn value(&self) -> f64 {
+        self.raw as f64 / (1u64 << self.fraction_bits) as f64
+    }
+
+    fn is_exact(&self) -> bool {
+        self.exact
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    /// Writes the bit pattern with a point after the integer bits, followed
+    /// by the same bits in hex, e.g. `0.110 (0x6)` for 0.75 in Q1.3, and
+    /// `0.111 (0x7, saturated)` for 2, which is out of its range.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.integer_bits + self.fraction_bits;
+        let bits = self.raw as u64 & (u64::MAX >> (64 - width));
+        let binary = format!("{:0width$b}", bits, width = width as usize);
+        let (integer, fraction) = binary.split_at(self.integer_bits as usize);
+        if !integer.is_empty() {
+            write!(f, "{}", integer)?;
+        }
+        if !fraction.is_empty() {
+            write!(f, ".{}", fraction)?;
+        }
+        write!(
+            f,
+            " (0x{:0width$x}{})",
+            bits,
+            if self.saturated { ", saturated" } else { "" },
+            width = width.div_ceil(4) as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Decode;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_fixed_point_patterns() {
+        let q = |decimal| to_fixed_point(decimal, 1, 15, Rounding::HalfEven);
+        assert_that!(q(0.5).to_string(), equal_to("0.100000000000000 (0x4000)"));
+        assert_that!(q(-1.0).to_string(), equal_to("1.000000000000000 (0x8000)"));
+        assert_that!(q(0.1).raw, equal_to(3277));
+        assert!(!q(0.1).is_exact());
+        assert_that!(q(0.25).value(), equal_to(0.25));
+        assert!(q(0.25).is_exact());
+
+        let q4_4 = to_fixed_point(-2.75, 4, 4, Rounding::Truncate);
+        assert_that!(q4_4.to_string(), equal_to("1101.0100 (0xd4)"));
+        assert_that!(
+            to_fixed_point(0.0, 0, 8, Rounding::Truncate).to_string(),
+            equal_to(".00000000 (0x00)")
+        );
+    }
+
+    #[test]
+    fn test_fixed_point_rounding_and_saturation() {
+        let q = |decimal, rounding| to_fixed_point(decimal, 4, 0, rounding).raw;
+        assert_that!(q(-2.5, Rounding::Truncate), equal_to(-3));
+        assert_that!(q(-2.5, Rounding::HalfUp), equal_to(-2));
+        assert_that!(q(-2.5, Rounding::HalfEven), equal_to(-2));
+        assert_that!(q(3.5, Rounding::HalfEven), equal_to(4));
+        assert_that!(q(100.0, Rounding::HalfEven), equal_to(7));
+        assert_that!(q(-100.0, Rounding::HalfEven), equal_to(-8));
+        assert!(!to_fixed_point(100.0, 4, 0, Rounding::Truncate).is_exact());
+
+        assert_that!(
+            to_fixed_point(2.0, 1, 15, Rounding::HalfEven).to_string(),
+            equal_to("0.111111111111111 (0x7fff, saturated)")
+        );
+        assert_that!(
+            to_fixed_point(-1.0, 1, 15, Rounding::HalfEven).to_string(),
+            equal_to("1.000000000000000 (0x8000)")
+        );
+        // 0.999 is in the range of Q1.3, but once rounded up to 1 it is not.
+        assert!(!to_fixed_point(0.999, 1, 3, Rounding::Truncate).saturated);
+        assert!(to_fixed_point(0.999, 1, 3, Rounding::HalfEven).saturated);
+    }
+
+    #[test]
+    fn test_fixed_point_round_trips_through_decode() {
+        let q = Decode::Fixed {
+            integer_bits: 1,
+            fraction_bits: 15,
+        };
+        let stored = to_fixed_point(-0.3, 1, 15, Rounding::HalfEven);
+        assert_that!(
+            q.decode(&stored.raw.to_string()),
+            equal_to(Some(stored.value()))
+        );
+    }
+}
@@ -0,0 +1,927 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::beta::GOLDEN_RATIO;
+use crate::conversion::{Conversion, CONVERSION_FIELDS};
+use crate::convert::{evaluate_fraction, Representation};
+use crate::display::{display, round_trip_error, Columns};
+use crate::formatter::{find_registered, registered_formatter, OutputFormatter};
+#[cfg(feature = "serde")]
+use crate::json::to_json;
+#[cfg(feature = "serde")]
+use crate::msgpack::to_msgpack;
+use crate::parquet::write_parquet;
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+#[cfg(feature = "serde")]
+use crate::schema::write_json_document;
+use crate::sqlite::write_sqlite;
+use crate::style::Styles;
+use crate::xlsx::write_xlsx;
+#[cfg(feature = "serde")]
+use crate::yaml::to_yaml_entry;
+
+/// How the conversions of a run are written, from `--format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The Markdown table (the default).
+    #[default]
+    Table,
+    /// One CSV row per input and base, with the fields of a `Conversion`.
+    Csv,
+    /// The rows of `Csv` with tabs between the fields, for `cut`, `paste`
+    /// and spreadsheets that export tabs.
+    Tsv,
+    /// A JSON array of one `Conversion` object per input and base.
+    Json,
+    /// One `Conversion` object per line, per input and base, for consumers
+    /// that read the results as they come.
+    JsonLines,
+    /// The representation of every input in every base alone on its own
+    /// line, for piping into `sort`, `diff` and the like.
+    Plain {
+        /// Whether every record ends in a NUL byte instead of a newline,
+        /// for `xargs -0`, with `--null`.
+        null: bool,
+    },
+    /// A YAML sequence of one `Conversion` mapping per input and base.
+    Yaml,
+    /// One MessagePack map per input and base, with the fields of a
+    /// `Conversion`, one after the other without an enclosing array, so that
+    /// a reader can unpack them as a stream however many there are.
+    MessagePack,
+    /// An Excel workbook with a sheet of one row per input and base.
+    Xlsx,
+    /// A SQLite database with a table of one row per input and base.
+    Sqlite,
+    /// A Parquet file with a column per field of a `Conversion` and a row
+    /// per input and base.
+    Parquet,
+    /// A format added with `register_formatter`, by its place among the
+    /// registered ones.
+    Custom(usize),
+}
+
+impl OutputFormat {
+    /// Looks a format up by the name given to `--format`: `md`, `csv`, `tsv`,
+    /// `json`, `jsonl`, `yaml`, `msgpack`, `xlsx`, `sqlite`, `parquet` or `plain`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(OutputFormat::from_name("json"), Some(OutputFormat::Json));
+    /// ```
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "md" | "markdown" | "table" => Some(OutputFormat::Table),
+            "csv" => Some(OutputFormat::Csv),
+            "tsv" | "tab" => Some(OutputFormat::Tsv),
+            "json" => Some(OutputFormat::Json),
+            "jsonl" | "ndjson" => Some(OutputFormat::JsonLines),
+            "plain" => Some(OutputFormat::Plain { null: false }),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            "msgpack" => Some(OutputFormat::MessagePack),
+            "xlsx" => Some(OutputFormat::Xlsx),
+            "sqlite" | "sqlite3" | "db" => Some(OutputFormat::Sqlite),
+            "parquet" => Some(OutputFormat::Parquet),
+            _ => find_registered(name).map(OutputFormat::Custom),
+        }
+    }
+
+    /// Infers a format from the extension of an `--output` file, such as
+    /// `results.json`, in either case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(OutputFormat::from_extension("runs/results.CSV"), Some(OutputFormat::Csv));
+    /// assert_eq!(OutputFormat::from_extension("results.txt"), None);
+    /// ```
+    pub fn from_extension(path: &str) -> Option<OutputFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        OutputFormat::from_name(&extension).filter(|_| extension != "table" && extension != "plain")
+    }
+
+    /// Whether the format is written through serde, and so needs the
+    /// `serde` feature.
+    pub fn needs_serde(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Json
+                | OutputFormat::JsonLines
+                | OutputFormat::Yaml
+                | OutputFormat::MessagePack
+        )
+    }
+
+    /// Whether the format writes one record per line, after the header of
+    /// CSV and TSV, so that `--stream` can write every input as soon as it is
+    /// converted: CSV, TSV, JSON Lines and plain.
+    pub fn is_line_oriented(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Csv
+                | OutputFormat::Tsv
+                | OutputFormat::JsonLines
+                | OutputFormat::Plain { .. }
+        )
+    }
+
+    /// Writes the header a line-oriented format starts with: the field names
+    /// of CSV and TSV, and nothing for the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `out` - Where the header is written.
+    /// * `labelled` - Whether the records start with the label of their input.
+    pub fn write_header(self, out: &mut dyn Write, labelled: bool) -> io::Result<()> {
+        match self {
+            OutputFormat::Csv => write_delimited_header(out, labelled, ","),
+            OutputFormat::Tsv => write_delimited_header(out, labelled, "\t"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes conversions as the records of a line-oriented format, without
+    /// the header that [`OutputFormat::write_header`] writes, so that the
+    /// records of several calls make up one file.
+    pub fn write_records(
+        self,
+        out: &mut dyn Write,
+        conversions: impl IntoIterator<Item = Conversion>,
+    ) -> io::Result<()> {
+        match self {
+            OutputFormat::Csv => write_delimited_records(out, conversions, ",", write_csv_field),
+            OutputFormat::Tsv => write_delimited_records(out, conversions, "\t", write_tsv_field),
+            _ => write_conversions(out, self, conversions),
+        }
+    }
+
+    /// The extension of the file `--output-dir` writes this format to.
+    pub fn extension(self) -> &'static str {
+        self.formatter().extension()
+    }
+
+    /// Whether the format is a binary file written whole, which cannot go
+    /// to a terminal, be appended to or be followed by text.
+    pub fn is_binary_file(self) -> bool {
+        self.formatter().is_binary_file()
+    }
+
+    /// The formatter the format is written with.
+    pub fn formatter(self) -> &'static dyn OutputFormatter {
+        match self {
+            OutputFormat::Table => &TABLE,
+            OutputFormat::Csv => &CSV,
+            OutputFormat::Tsv => &TSV,
+            OutputFormat::Json => &JSON,
+            OutputFormat::JsonLines => &JSON_LINES,
+            OutputFormat::Plain { null: false } => &PLAIN,
+            OutputFormat::Plain { null: true } => &NULL_PLAIN,
+            OutputFormat::Yaml => &YAML,
+            OutputFormat::MessagePack => &MESSAGE_PACK,
+            OutputFormat::Xlsx => &XLSX,
+            OutputFormat::Sqlite => &SQLITE,
+            OutputFormat::Parquet => &PARQUET,
+            OutputFormat::Custom(index) => {
+                registered_formatter(index).expect("a custom format is registered")
+            }
+        }
+    }
+}
+
+/// Parses the value of `--format`, which names a format this build can
+/// write.
+pub fn parse_format(name: &str) -> Result<OutputFormat, String> {
+    let format =
+        OutputFormat::from_name(name).ok_or_else(|| format!("unknown format `{}`", name))?;
+    if format.needs_serde() && cfg!(not(feature = "serde")) {
+        return Err(format!(
+            "--format {} needs the serde feature; build with --features serde",
+            name
+        ));
+    }
+    Ok(format)
+}
+
+/// Writes one field of a CSV or TSV row.
+pub type FieldWriter = fn(&mut dyn Write, &str) -> io::Result<()>;
+
+/// Writes a CSV field, quoting it if it holds a comma or a quote.
+pub fn write_csv_field(out: &mut dyn Write, field: &str) -> io::Result<()> {
+    if field.contains([',', '"']) {
+        write!(out, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        write!(out, "{}", field)
+    }
+}
+
+/// Writes a TSV field, escaping the tabs, line breaks and backslashes it
+/// holds as `\t`, `\n`, `\r` and `\\`, since TSV has no quoting.
+pub fn write_tsv_field(out: &mut dyn Write, field: &str) -> io::Result<()> {
+    for c in field.chars() {
+        match c {
+            '\t' => write!(out, "\\t")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\\' => write!(out, "\\\\")?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the conversion of every input to every target base with the
+/// formatter of a format.
+///
+/// # Arguments
+///
+/// * `out` - Where the conversions are written.
+/// * `format` - The format to write them in. The table is written in the
+///   default style; `display` writes it in the styles of a run.
+/// * `pipeline` - The stages every input is run through.
+/// * `f64_numbers` - The decoded input values.
+/// * `fractions` - For every input, the exact fraction it was written as, if any.
+/// * `digit_limits` - For every input, the digit limit it was given with,
+///   as in `0.1:20`, if any, which replaces that of the pipeline.
+/// * `labels` - For every input, the label it was given with, as in
+///   `tenth=0.1`, if any, which leads its records if any input has one.
+///
+/// # Example
+///
+/// ```
+/// let mut out = Vec::new();
+/// let pipeline = Pipeline::with_bases(vec![2.0]);
+/// display_conversions(
+///     &mut out, OutputFormat::Csv, &pipeline, &[0.5], &[None], &[None], &[],
+/// )
+/// .unwrap();
+/// assert_eq!(out, b"input,base,digits,truncated,repr\n0.5,2,1,false,0.1;\n");
+/// ```
+pub fn display_conversions(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    pipeline: &Pipeline,
+    f64_numbers: &[f64],
+    fractions: &[Option<Fraction>],
+    digit_limits: &[Option<u32>],
+    labels: &[Option<String>],
+) -> io::Result<()> {
+    let conversions = make_conversions(pipeline, f64_numbers, fractions, digit_limits, labels);
+    write_conversions(out, format, conversions)
+}
+
+/// Converts every input to every target base, as `display_conversions`
+/// writes them, until the run is cancelled.
+///
+/// # Arguments
+///
+/// * `pipeline` - The stages every input is run through.
+/// * `f64_numbers` - The decoded input values.
+/// * `fractions` - For every input, the exact fraction it was written as, if any.
+/// * `digit_limits` - For every input, the digit limit it was given with, if any.
+/// * `labels` - For every input, the label it was given with, if any.
+pub fn make_conversions<'a>(
+    pipeline: &'a Pipeline,
+    f64_numbers: &'a [f64],
+    fractions: &'a [Option<Fraction>],
+    digit_limits: &'a [Option<u32>],
+    labels: &'a [Option<String>],
+) -> impl Iterator<Item = Conversion> + 'a {
+    let labelled = labels.iter().any(Option::is_some);
+    f64_numbers
+        .iter()
+        .zip(fractions)
+        .zip(digit_limits)
+        .enumerate()
+        .take_while(|_| !pipeline.cancel.is_cancelled())
+        .flat_map(move |(i, ((&input, fraction), &limit))| {
+            let limited = limit.map(|digits| Pipeline {
+                digits,
+                ..pipeline.clone()
+            });
+            let label = labelled.then(|| labels.get(i).cloned().flatten().unwrap_or_default());
+            pipeline.target_bases.iter().map(move |&target_base| {
+                let pipeline = limited.as_ref().unwrap_or(pipeline);
+                let expansion = pipeline.expand_input(input, fraction.as_ref(), target_base);
+                Conversion {
+                    label: label.clone(),
+                    ..Conversion::new(input, target_base, &expansion)
+                }
+            })
+        })
+}
+
+/// Writes conversions that were already made with the formatter of a
+/// format, such as CSV, JSON, an Excel workbook or a registered one.
+///
+/// # Arguments
+///
+/// * `out` - Where the conversions are written.
+/// * `format` - The format to write them in.
+/// * `conversions` - The conversion of every input to every target base,
+///   either all with a label or all without.
+pub fn write_conversions(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    conversions: impl IntoIterator<Item = Conversion>,
+) -> io::Result<()> {
+    format.formatter().write(out, &mut conversions.into_iter())
+}
+
+/// Signature of the functions the built-in formats are written with.
+type WriteFn = fn(&mut dyn Write, &mut dyn Iterator<Item = Conversion>) -> io::Result<()>;
+
+/// A format the crate writes itself.
+struct BuiltinFormatter {
+    name: &'static str,
+    extension: &'static str,
+    binary: bool,
+    write: WriteFn,
+}
+
+impl OutputFormatter for BuiltinFormatter {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn extension(&self) -> &str {
+        self.extension
+    }
+
+    fn is_binary_file(&self) -> bool {
+        self.binary
+    }
+
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        conversions: &mut dyn Iterator<Item = Conversion>,
+    ) -> io::Result<()> {
+        (self.write)(out, conversions)
+    }
+}
+
+/// The built-in formatters, in the order of the `OutputFormat` variants.
+static TABLE: BuiltinFormatter = BuiltinFormatter {
+    name: "table",
+    extension: "md",
+    binary: false,
+    write: write_table,
+};
+static CSV: BuiltinFormatter = BuiltinFormatter {
+    name: "csv",
+    extension: "csv",
+    binary: false,
+    write: |out, conversions| write_delimited(out, conversions, ",", write_csv_field),
+};
+static TSV: BuiltinFormatter = BuiltinFormatter {
+    name: "tsv",
+    extension: "tsv",
+    binary: false,
+    write: |out, conversions| write_delimited(out, conversions, "\t", write_tsv_field),
+};
+static JSON: BuiltinFormatter = BuiltinFormatter {
+    name: "json",
+    extension: "json",
+    binary: false,
+    #[cfg(feature = "serde")]
+    write: write_json,
+    #[cfg(not(feature = "serde"))]
+    write: needs_serde,
+};
+static JSON_LINES: BuiltinFormatter = BuiltinFormatter {
+    name: "jsonl",
+    extension: "jsonl",
+    binary: false,
+    #[cfg(feature = "serde")]
+    write: write_json_lines,
+    #[cfg(not(feature = "serde"))]
+    write: needs_serde,
+};
+static PLAIN: BuiltinFormatter = BuiltinFormatter {
+    name: "plain",
+    extension: "txt",
+    binary: false,
+    write: |out, conversions| write_plain(out, conversions, '\n'),
+};
+static NULL_PLAIN: BuiltinFormatter = BuiltinFormatter {
+    name: "plain",
+    extension: "txt",
+    binary: false,
+    write: |out, conversions| write_plain(out, conversions, '\0'),
+};
+static YAML: BuiltinFormatter = BuiltinFormatter {
+    name: "yaml",
+    extension: "yaml",
+    binary: false,
+    #[cfg(feature = "serde")]
+    write: write_yaml,
+    #[cfg(not(feature = "serde"))]
+    write: needs_serde,
+};
+static MESSAGE_PACK: BuiltinFormatter = BuiltinFormatter {
+    name: "msgpack",
+    extension: "msgpack",
+    binary: false,
+    #[cfg(feature = "serde")]
+    write: write_msgpack,
+    #[cfg(not(feature = "serde"))]
+    write: needs_serde,
+};
+static XLSX: BuiltinFormatter = BuiltinFormatter {
+    name: "xlsx",
+    extension: "xlsx",
+    binary: true,
+    write: |out, conversions| write_xlsx(out, conversions),
+};
+static SQLITE: BuiltinFormatter = BuiltinFormatter {
+    name: "sqlite",
+    extension: "sqlite",
+    binary: true,
+    write: |out, conversions| write_sqlite(out, conversions),
+};
+static PARQUET: BuiltinFormatter = BuiltinFormatter {
+    name: "parquet",
+    extension: "parquet",
+    binary: true,
+    write: |out, conversions| write_parquet(out, conversions),
+};
+
+/// A conversion as a cell of the table `write_table` writes.
+struct Recorded<'a>(&'a Conversion);
+
+impl fmt::Display for Recorded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0.repr)
+    }
+}
+
+impl Representation for Recorded<'_> {
+    /// Evaluates the digits of the representation in its base, or falls
+    /// back on the input for notations that are not `;`-separated digits.
+    fn value(&self) -> f64 {
+        let conversion = self.0;
+        let base = match conversion.base.as_str() {
+            "φ" => GOLDEN_RATIO,
+            base => base.parse().unwrap_or(f64::NAN),
+        };
+        let (negative, repr) = match conversion.repr.strip_prefix('-') {
+            Some(repr) => (true, repr),
+            None => (false, conversion.repr.as_str()),
+        };
+        let digits = |part: &str| -> Option<Vec<u32>> {
+            part.split(';')
+                .filter(|digit| !digit.is_empty())
+                .map(|digit| digit.parse().ok())
+                .collect()
+        };
+        let value = repr.split_once('.').and_then(|(integer, fraction)| {
+            let integer = digits(integer)?
+                .iter()
+                .fold(0.0, |value, &digit| value * base + f64::from(digit));
+            Some(integer + evaluate_fraction(&digits(fraction)?, base))
+        });
+        match value {
+            Some(value) if negative => -value,
+            Some(value) if value.is_finite() => value,
+            _ => conversion.input,
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        !self.0.truncated
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        Some(self.0.digits)
+    }
+}
+
+/// The absolute difference between the input of a conversion and the value
+/// of its digits.
+///
+/// # Example
+///
+/// ```
+/// let conversion = Conversion::new(0.1, 2.0, &expand(0.1, 2, 4));
+/// assert_eq!(conversion_error(&conversion), 0.1 - 0.0625);
+/// ```
+pub fn conversion_error(conversion: &Conversion) -> f64 {
+    round_trip_error(conversion.input, &Recorded(conversion))
+}
+
+/// Writes conversions as the table, a row per input with a column per
+/// base, in the default style.
+fn write_table(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+) -> io::Result<()> {
+    let conversions: Vec<Conversion> = conversions.collect();
+    // The bases of every input come in the same order, so a row ends
+    // where the first base comes around again.
+    let width = conversions
+        .iter()
+        .skip(1)
+        .position(|conversion| {
+            conversions
+                .first()
+                .is_some_and(|first| first.base == conversion.base)
+        })
+        .map_or(conversions.len(), |position| position + 1)
+        .max(1);
+    let rows: Vec<&[Conversion]> = conversions.chunks(width).collect();
+    let headers: Vec<String> = rows
+        .first()
+        .map(|row| {
+            row.iter()
+                .map(|conversion| format!("Base {}", conversion.base))
+                .collect()
+        })
+        .unwrap_or_default();
+    let numbers: Vec<f64> = rows.iter().map(|row| row[0].input).collect();
+    let labels: Vec<Option<String>> = rows.iter().map(|row| row[0].label.clone()).collect();
+    let cells: Vec<Vec<Recorded>> = rows
+        .iter()
+        .map(|row| row.iter().map(Recorded).collect())
+        .collect();
+    display(
+        out,
+        &headers,
+        (
+            &numbers,
+            &vec![None; rows.len()],
+            &vec![1; rows.len()],
+            &labels,
+        ),
+        &cells,
+        Columns {
+            label: labels.iter().any(Option::is_some),
+            ..Columns::default()
+        },
+        &Styles::default(),
+    )
+}
+
+/// Writes conversions as CSV or TSV records, with a header row of the
+/// fields of a `Conversion`.
+fn write_delimited(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+    delimiter: &str,
+    write_field: FieldWriter,
+) -> io::Result<()> {
+    // Either every conversion has a label or none has.
+    let mut conversions = conversions.peekable();
+    let labelled = conversions
+        .peek()
+        .is_some_and(|conversion| conversion.label.is_some());
+    write_delimited_header(out, labelled, delimiter)?;
+    write_delimited_records(out, conversions, delimiter, write_field)
+}
+
+/// Writes the header row of CSV or TSV records.
+fn write_delimited_header(out: &mut dyn Write, labelled: bool, delimiter: &str) -> io::Result<()> {
+    if labelled {
+        write!(out, "label{}", delimiter)?;
+    }
+    writeln!(out, "{}", CONVERSION_FIELDS.join(delimiter))
+}
+
+/// Writes conversions as CSV or TSV records, without the header row.
+fn write_delimited_records(
+    out: &mut dyn Write,
+    conversions: impl IntoIterator<Item = Conversion>,
+    delimiter: &str,
+    write_field: FieldWriter,
+) -> io::Result<()> {
+    for conversion in conversions {
+        if let Some(label) = &conversion.label {
+            write_field(out, label)?;
+            write!(out, "{}", delimiter)?;
+        }
+        write!(out, "{}{}", conversion.input, delimiter)?;
+        write_field(out, &conversion.base)?;
+        write!(
+            out,
+            "{d}{}{d}{}{d}",
+            conversion.digits,
+            conversion.truncated,
+            d = delimiter
+        )?;
+        write_field(out, &conversion.repr)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn write_json(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+) -> io::Result<()> {
+    write_json_document(out, conversions.map(|conversion| to_json(&conversion)))
+}
+
+#[cfg(feature = "serde")]
+fn write_json_lines(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+) -> io::Result<()> {
+    for conversion in conversions {
+        let object = to_json(&conversion)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        writeln!(out, "{}", object)?;
+    }
+    Ok(())
+}
+
+/// Writes the representations alone, each ended by `end`.
+fn write_plain(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+    end: char,
+) -> io::Result<()> {
+    for conversion in conversions {
+        write!(out, "{}{}", conversion.repr, end)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn write_yaml(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+) -> io::Result<()> {
+    let mut empty = true;
+    for conversion in conversions {
+        let entry = to_yaml_entry(&conversion)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        write!(out, "{}", entry)?;
+        empty = false;
+    }
+    if empty {
+        writeln!(out, "[]")?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn write_msgpack(
+    out: &mut dyn Write,
+    conversions: &mut dyn Iterator<Item = Conversion>,
+) -> io::Result<()> {
+    for conversion in conversions {
+        let map = to_msgpack(&conversion)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        out.write_all(&map)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn needs_serde(_: &mut dyn Write, _: &mut dyn Iterator<Item = Conversion>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "this format needs the serde feature",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_formats_without_serde() {
+        assert_that!(
+            parse_format("json").is_ok(),
+            equal_to(cfg!(feature = "serde"))
+        );
+        assert_that!(
+            parse_format("msgpack").is_ok(),
+            equal_to(cfg!(feature = "serde"))
+        );
+        assert_that!(parse_format("csv"), equal_to(Ok(OutputFormat::Csv)));
+    }
+
+    #[test]
+    fn test_tsv_format() {
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let mut out = Vec::new();
+        display_conversions(
+            &mut out,
+            OutputFormat::Tsv,
+            &pipeline,
+            &[0.5],
+            &[None],
+            &[None],
+            &[Some("a\tb".to_string())],
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "label\tinput\tbase\tdigits\ttruncated\trepr\na\\tb\t0.5\t2\t1\tfalse\t0.1;\n"
+            )
+        );
+        assert_that!(
+            OutputFormat::from_extension("results.tsv"),
+            equal_to(Some(OutputFormat::Tsv))
+        );
+    }
+
+    #[test]
+    fn test_line_oriented_records() {
+        let conversion = Conversion::new(0.75, 2.0, &expand(0.75, 2, 8));
+        let mut out = Vec::new();
+        OutputFormat::Csv.write_header(&mut out, false).unwrap();
+        for _ in 0..2 {
+            OutputFormat::Csv
+                .write_records(&mut out, [conversion.clone()])
+                .unwrap();
+        }
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "input,base,digits,truncated,repr\n\
+                 0.75,2,2,false,0.1;1;\n\
+                 0.75,2,2,false,0.1;1;\n"
+            )
+        );
+        let mut out = Vec::new();
+        let plain = OutputFormat::Plain { null: false };
+        plain.write_header(&mut out, true).unwrap();
+        plain.write_records(&mut out, [conversion]).unwrap();
+        assert_that!(String::from_utf8(out).unwrap(), equal_to("0.1;1;\n"));
+        assert!(OutputFormat::JsonLines.is_line_oriented());
+        assert!(!OutputFormat::Json.is_line_oriented());
+        assert!(!OutputFormat::Table.is_line_oriented());
+    }
+
+    #[test]
+    fn test_table_formatter() {
+        let pipeline = Pipeline::with_bases(vec![2.0, 3.0]);
+        let mut out = Vec::new();
+        display_conversions(
+            &mut out,
+            OutputFormat::Table,
+            &pipeline,
+            &[0.5, -0.25],
+            &[None, None],
+            &[None, None],
+            &[],
+        )
+        .unwrap();
+        let table = String::from_utf8(out).unwrap();
+        assert_that!(table.lines().count(), equal_to(4));
+        assert_that!(table.contains("Base 3"), equal_to(true));
+        assert_that!(table.contains("| 0.1; "), equal_to(true));
+        assert_that!(table.contains("| -0.0;1; "), equal_to(true));
+        assert_that!(
+            Recorded(&Conversion::new(-0.25, 2.0, &expand(-0.25, 2, 8))).value(),
+            equal_to(-0.25)
+        );
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_that!(
+            OutputFormat::from_extension("results.json"),
+            equal_to(Some(OutputFormat::Json))
+        );
+        assert_that!(
+            OutputFormat::from_extension("results.Md"),
+            equal_to(Some(OutputFormat::Table))
+        );
+        assert_that!(
+            OutputFormat::from_extension("results.table"),
+            equal_to(None)
+        );
+        assert_that!(OutputFormat::from_extension("results"), equal_to(None));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_machine_readable_formats() {
+        let pipeline = Pipeline::with_bases(vec![2.0, 3.0]);
+        let fractions = [None, Fraction::parse("1/3")];
+        let write = |format| {
+            let mut out = Vec::new();
+            display_conversions(
+                &mut out,
+                format,
+                &pipeline,
+                &[0.5, 1.0 / 3.0],
+                &fractions,
+                &[None, None],
+                &[],
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_that!(
+            write(OutputFormat::Csv),
+            equal_to(
+                "input,base,digits,truncated,repr\n\
+                 0.5,2,1,false,0.1;\n\
+                 0.5,3,8,true,0.1;1;1;1;1;1;1;1;\n\
+                 0.3333333333333333,2,8,true,0.0;1;0;1;0;1;0;1;\n\
+                 0.3333333333333333,3,1,false,0.1;\n"
+                    .to_string()
+            )
+        );
+        assert_that!(
+            write(OutputFormat::Plain { null: false }),
+            equal_to("0.1;\n0.1;1;1;1;1;1;1;1;\n0.0;1;0;1;0;1;0;1;\n0.1;\n".to_string())
+        );
+        assert_that!(
+            write(OutputFormat::Plain { null: true }),
+            equal_to(
+                "0.1;\0\
+                 0.1;1;1;1;1;1;1;1;\0\
+                 0.0;1;0;1;0;1;0;1;\0\
+                 0.1;\0"
+                    .to_string()
+            )
+        );
+        let json = write(OutputFormat::Json);
+        assert!(json.starts_with("{\n  \"schema\": \"base-conversion-results\",\n"));
+        assert!(json.contains("\"rows\": [\n    {\"input\":0.5,\"base\":\"2\",\"digits\":1,"));
+        assert!(json.ends_with("\"repr\":\"0.1;\"}\n  ]\n}\n"));
+        assert_that!(json.lines().count(), equal_to(12));
+        let lines = write(OutputFormat::JsonLines);
+        assert_that!(lines.lines().count(), equal_to(4));
+        assert!(lines
+            .lines()
+            .all(|line| line.starts_with("{\"input\":") && line.ends_with('}')));
+        assert_that!(
+            OutputFormat::from_extension("results.jsonl"),
+            equal_to(Some(OutputFormat::JsonLines))
+        );
+
+        let yaml = write(OutputFormat::Yaml);
+        assert!(yaml.starts_with("- input: 0.5\n  base: \"2\"\n  digits: 1\n"));
+        assert_that!(yaml.lines().count(), equal_to(20));
+        assert_that!(
+            OutputFormat::from_extension("results.yml"),
+            equal_to(Some(OutputFormat::Yaml))
+        );
+
+        let mut packed = Vec::new();
+        display_conversions(
+            &mut packed,
+            OutputFormat::MessagePack,
+            &pipeline,
+            &[0.5, 1.0 / 3.0],
+            &fractions,
+            &[None, None],
+            &[],
+        )
+        .unwrap();
+        assert_that!(
+            packed.iter().filter(|&&byte| byte == 0x85).count(),
+            equal_to(4)
+        );
+        assert!(packed.starts_with(&[0x85, 0xa5, b'i']));
+        assert_that!(
+            OutputFormat::from_extension("results.msgpack"),
+            equal_to(Some(OutputFormat::MessagePack))
+        );
+
+        let mut empty = Vec::new();
+        display_conversions(
+            &mut empty,
+            OutputFormat::Json,
+            &pipeline,
+            &[],
+            &[],
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert!(String::from_utf8(empty)
+            .unwrap()
+            .ends_with("  \"rows\": []\n}\n"));
+        let mut empty = Vec::new();
+        display_conversions(
+            &mut empty,
+            OutputFormat::Yaml,
+            &pipeline,
+            &[],
+            &[],
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert_that!(empty, equal_to(b"[]\n".to_vec()));
+        assert_that!(
+            OutputFormat::from_name("md"),
+            equal_to(Some(OutputFormat::Table))
+        );
+        assert!(parse_format("xml").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+use std::sync::RwLock;
+
+use crate::conversion::Conversion;
+use crate::format::OutputFormat;
+
+/// Writes the conversions of a run in an output format. Every format
+/// `--format` names is written by one, so that a new format is added by
+/// registering a formatter rather than by changing how results are written.
+pub trait OutputFormatter: Sync {
+    /// The name `--format` selects the format by, which is also the
+    /// extension of the `--output` files it is inferred from.
+    fn name(&self) -> &str;
+
+    /// The extension of the files `--output-dir` writes the format to.
+    fn extension(&self) -> &str {
+        self.name()
+    }
+
+    /// Whether the format is a binary file written whole, which cannot go
+    /// to a terminal, be appended to or be followed by text.
+    fn is_binary_file(&self) -> bool {
+        false
+    }
+
+    /// Writes the conversion of every input to every target base, either
+    /// all with a label or all without, as they come.
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        conversions: &mut dyn Iterator<Item = Conversion>,
+    ) -> io::Result<()>;
+}
+
+/// The formatters registered on top of the built-in formats, in the order
+/// they were registered.
+static REGISTERED: RwLock<Vec<&'static dyn OutputFormatter>> = RwLock::new(Vec::new());
+
+/// Adds an output format, which `--format` and `--output` then accept by
+/// the formatter's name like any built-in one.
+///
+/// # Returns
+///
+/// The format that writes with the formatter, or a message if its name is
+/// already taken.
+///
+/// # Example
+///
+/// ```
+/// static SUBSCRIPTED: Subscripted = Subscripted;
+/// let format = register_formatter(&SUBSCRIPTED).unwrap();
+/// assert_eq!(OutputFormat::from_name("subscripted"), Some(format));
+/// ```
+pub fn register_formatter(formatter: &'static dyn OutputFormatter) -> Result<OutputFormat, String> {
+    let name = formatter.name();
+    if OutputFormat::from_name(name).is_some() {
+        return Err(format!("the format `{}` already exists", name));
+    }
+    let mut registered = REGISTERED
+        .write()
+        .unwrap_or_else(|error| error.into_inner());
+    registered.push(formatter);
+    Ok(OutputFormat::Custom(registered.len() - 1))
+}
+
+/// The formatter registered at an index, as `OutputFormat::Custom` holds it.
+pub fn registered_formatter(index: usize) -> Option<&'static dyn OutputFormatter> {
+    let registered = REGISTERED.read().unwrap_or_else(|error| error.into_inner());
+    registered.get(index).copied()
+}
+
+/// The index of the formatter registered under a name, if any.
+pub fn find_registered(name: &str) -> Option<usize> {
+    let registered = REGISTERED.read().unwrap_or_else(|error| error.into_inner());
+    registered
+        .iter()
+        .position(|formatter| formatter.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use crate::format::write_conversions;
+    use hamcrest2::prelude::*;
+
+    /// Writes only the representations, with their bases.
+    struct Subscripted;
+
+    impl OutputFormatter for Subscripted {
+        fn name(&self) -> &str {
+            "subscripted"
+        }
+
+        fn write(
+            &self,
+            out: &mut dyn Write,
+            conversions: &mut dyn Iterator<Item = Conversion>,
+        ) -> io::Result<()> {
+            for conversion in conversions {
+                writeln!(out, "{}_{}", conversion.repr, conversion.base)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Claims the name of a built-in format.
+    struct Impostor;
+
+    impl OutputFormatter for Impostor {
+        fn name(&self) -> &str {
+            "csv"
+        }
+
+        fn write(
+            &self,
+            _: &mut dyn Write,
+            _: &mut dyn Iterator<Item = Conversion>,
+        ) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registered_formats() {
+        static SUBSCRIPTED: Subscripted = Subscripted;
+        static IMPOSTOR: Impostor = Impostor;
+        let format = register_formatter(&SUBSCRIPTED).unwrap();
+        assert_that!(
+            OutputFormat::from_name("subscripted"),
+            equal_to(Some(format))
+        );
+        assert_that!(
+            OutputFormat::from_extension("results.subscripted"),
+            equal_to(Some(format))
+        );
+        assert_that!(format.extension(), equal_to("subscripted"));
+        assert!(register_formatter(&SUBSCRIPTED).is_err());
+        assert!(register_formatter(&IMPOSTOR).is_err());
+
+        let mut out = Vec::new();
+        let conversion = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+        write_conversions(&mut out, format, [conversion]).unwrap();
+        assert_that!(String::from_utf8(out).unwrap(), equal_to("0.1;_2\n"));
+    }
+}
@@ -0,0 +1,232 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Finds the files a `--glob` pattern such as `data/*.txt` matches. Within
+/// a path component, `*` matches any run of characters, `?` any one and
+/// `[abc]` or `[a-z]` one of a set; a `**` component matches any number of
+/// directories, so that `data/**/*.txt` reaches into every subdirectory.
+/// Wildcards do not match names starting with `.` unless the pattern does.
+///
+/// # Returns
+///
+/// The paths of the matching files in sorted order, or a message if none
+/// match or a directory cannot be read.
+///
+/// # Example
+///
+/// ```
+/// let files = expand_glob("data/week*.txt").unwrap();
+/// assert_eq!(files, ["data/week1.txt", "data/week2.txt"]);
+/// ```
+pub fn expand_glob(pattern: &str) -> Result<Vec<String>, String> {
+    let root = glob_root(pattern);
+    let components: Vec<&str> = pattern[root.len()..]
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect();
+    let start = if root.is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(root)
+    };
+    let mut files = Vec::new();
+    walk(&start, root.is_empty(), &components, &mut files)
+        .map_err(|error| format!("cannot search `{}`: {}", pattern, error))?;
+    if files.is_empty() {
+        return Err(format!("no files match `{}`", pattern));
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// The part of a pattern before its first component with a wildcard, such
+/// as `data/` for `data/*/in.txt`, which the matching files are found under
+/// and which `--output-dir` mirrors the layout of.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(glob_root("data/week*/*.txt"), "data/");
+/// assert_eq!(glob_root("*.txt"), "");
+/// ```
+pub fn glob_root(pattern: &str) -> &str {
+    let wildcard = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    match pattern[..wildcard].rfind('/') {
+        Some(slash) => &pattern[..=slash],
+        None => "",
+    }
+}
+
+/// Collects the files under `dir` that the remaining components match.
+///
+/// # Arguments
+///
+/// * `dir` - The directory the components are matched in.
+/// * `relative` - Whether `dir` is the working directory, whose `./` is left
+///   out of the paths found.
+/// * `components` - The components of the pattern still to match.
+/// * `files` - Where the paths of the matching files are added.
+fn walk(
+    dir: &Path,
+    relative: bool,
+    components: &[&str],
+    files: &mut Vec<String>,
+) -> std::io::Result<()> {
+    let Some((&component, rest)) = components.split_first() else {
+        return Ok(());
+    };
+    if component == "**" {
+        // Matching no directory at all, then one more level at a time.
+        walk(dir, relative, rest, files)?;
+        for (name, path) in entries(dir, relative)? {
+            if path.is_dir() && !name.starts_with('.') {
+                walk(&path, false, components, files)?;
+            }
+        }
+        return Ok(());
+    }
+    for (name, path) in entries(dir, relative)? {
+        if name.starts_with('.') && !component.starts_with('.') {
+            continue;
+        }
+        if !matches(component.as_bytes(), name.as_bytes()) {
+            continue;
+        }
+        if rest.is_empty() {
+            if path.is_file() {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        } else if path.is_dir() {
+            walk(&path, false, rest, files)?;
+        }
+    }
+    Ok(())
+}
+
+/// The names of the entries of a directory and their paths, or nothing if
+/// it does not exist.
+fn entries(dir: &Path, relative: bool) -> std::io::Result<Vec<(String, PathBuf)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    fs::read_dir(dir)?
+        .map(|entry| {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let path = if relative {
+                PathBuf::from(&name)
+            } else {
+                dir.join(&name)
+            };
+            Ok((name, path))
+        })
+        .collect()
+}
+
+/// Whether a name matches one component of a pattern, with `*`, `?` and
+/// `[...]` as wildcards.
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+        Some((b'[', rest)) => {
+            let Some(close) = rest.iter().position(|&byte| byte == b']') else {
+                return name.first() == Some(&b'[') && matches(rest, &name[1..]);
+            };
+            let Some(&first) = name.first() else {
+                return false;
+            };
+            in_set(&rest[..close], first) && matches(&rest[close + 1..], &name[1..])
+        }
+        Some((&byte, rest)) => name.first() == Some(&byte) && matches(rest, &name[1..]),
+    }
+}
+
+/// Whether a byte is in a set such as `abc` or `a-z` from between `[` and
+/// `]`.
+fn in_set(set: &[u8], byte: u8) -> bool {
+    let mut i = 0;
+    while i < set.len() {
+        if i + 2 < set.len() && set[i + 1] == b'-' {
+            if (set[i]..=set[i + 2]).contains(&byte) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if set[i] == byte {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::env;
+
+    #[test]
+    fn test_matches() {
+        let matching = |pattern: &str, name: &str| matches(pattern.as_bytes(), name.as_bytes());
+        assert!(matching("*.txt", "week1.txt"));
+        assert!(matching("week?.txt", "week2.txt"));
+        assert!(matching("week[1-3].txt", "week3.txt"));
+        assert!(!matching("week[1-3].txt", "week4.txt"));
+        assert!(matching("*", ""));
+        assert!(!matching("*.txt", "week1.md"));
+        assert!(matching("a[b", "a[b"));
+        assert_that!(glob_root("data/week*/*.txt"), equal_to("data/"));
+        assert_that!(glob_root("/tmp/in.txt"), equal_to("/tmp/"));
+        assert_that!(glob_root("*.txt"), equal_to(""));
+    }
+
+    #[test]
+    fn test_expand_glob() {
+        let dir = env::temp_dir().join(format!("glob-{}", std::process::id()));
+        fs::create_dir_all(dir.join("week1/extra")).unwrap();
+        fs::create_dir_all(dir.join("week2")).unwrap();
+        for file in [
+            "week1/a.txt",
+            "week1/b.md",
+            "week1/extra/c.txt",
+            "week2/d.txt",
+            "week2/.hidden.txt",
+        ] {
+            fs::write(dir.join(file), "0.5\n").unwrap();
+        }
+        let root = dir.display().to_string();
+        let found = |pattern: &str| {
+            expand_glob(&format!("{}/{}", root, pattern)).map(|files| {
+                files
+                    .iter()
+                    .map(|file| file[root.len() + 1..].to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_that!(
+            found("*/*.txt"),
+            equal_to(Ok(vec![
+                "week1/a.txt".to_string(),
+                "week2/d.txt".to_string()
+            ]))
+        );
+        assert_that!(
+            found("**/*.txt"),
+            equal_to(Ok(vec![
+                "week1/a.txt".to_string(),
+                "week1/extra/c.txt".to_string(),
+                "week2/d.txt".to_string()
+            ]))
+        );
+        assert_that!(
+            found("week2/.*"),
+            equal_to(Ok(vec!["week2/.hidden.txt".to_string()]))
+        );
+        assert!(found("*/*.csv").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+use crate::convert::{Expansion, Representation};
+
+/// The only base `--gray` can rewrite.
+pub const GRAY_BASE: u32 = 2;
+
+/// One column of the `--gray` table: a binary expansion written either as
+/// it is or with its fractional bits in reflected Gray code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrayColumn {
+    /// The plain binary expansion.
+    Binary(Expansion),
+    /// The same expansion, with the fractional bits in Gray code.
+    Gray(Expansion),
+}
+
+/// Converts a bit string to its reflected Gray code, where every bit after
+/// the first is the exclusive or of itself and the bit before it.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(to_gray(&[1, 0, 1, 1]), vec![1, 1, 1, 0]);
+/// ```
+pub fn to_gray(bits: &[u32]) -> Vec<u32> {
+    let mut previous = 0;
+    bits.iter()
+        .map(|&bit| {
+            let gray = bit ^ previous;
+            previous = bit;
+            gray
+        })
+        .collect()
+}
+
+impl GrayColumn {
+    /// The binary expansion shown in this column.
+    fn expansion(&self) -> &Expansion {
+        match self {
+            GrayColumn::Binary(expansion) | GrayColumn::Gray(expansion) => expansion,
+        }
+    }
+}
+
+impl Representation for GrayColumn {
+    fn value(&self) -> f64 {
+        self.expansion().value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion().is_exact()
+    }
+}
+
+impl fmt::Display for GrayColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrayColumn::Binary(expansion) => write!(f, "{}", expansion),
+            GrayColumn::Gray(expansion) => {
+                let gray = Expansion {
+                    digits: to_gray(&expansion.digits),
+                    ..expansion.clone()
+                };
+                write!(f, "{}", gray)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_gray_code() {
+        assert_that!(to_gray(&[0, 1, 1, 0, 1]), equal_to(vec![0, 1, 0, 1, 1]));
+        // Consecutive bit strings have Gray codes one bit apart.
+        let codes: Vec<Vec<u32>> = (0..16u32)
+            .map(|n| to_gray(&(0..4).rev().map(|i| n >> i & 1).collect::<Vec<_>>()))
+            .collect();
+        for pair in codes.windows(2) {
+            let changed = pair[0].iter().zip(&pair[1]).filter(|(a, b)| a != b).count();
+            assert_that!(changed, equal_to(1));
+        }
+    }
+
+    #[test]
+    fn test_gray_columns() {
+        // 0.8125 is 0.1101 in binary.
+        let expansion = expand(0.8125, GRAY_BASE.into(), 8);
+        let gray = GrayColumn::Gray(expansion.clone());
+        assert_that!(gray.to_string(), equal_to("0.1;0;1;1;"));
+        assert_that!(
+            GrayColumn::Binary(expansion).to_string(),
+            equal_to("0.1;1;0;1;")
+        );
+        assert_that!(gray.value(), equal_to(0.8125));
+        assert!(gray.is_exact());
+    }
+}
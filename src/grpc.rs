@@ -0,0 +1,233 @@
+// A `Status` is large, but it is the error every call of a tonic service
+// returns.
+#![allow(clippy::result_large_err)]
+
+use std::io;
+use std::net::TcpListener;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::conversion::Conversion;
+use crate::pipeline::Pipeline;
+use crate::serve::{
+    check_precision, convert_value, ConnectionLimit, ConnectionSlot, MAX_CONNECTIONS,
+};
+
+/// The messages and the service tonic-build generates from
+/// proto/converter.proto.
+pub mod proto {
+    tonic::include_proto!("baseconv");
+}
+
+pub use proto::converter_server::{Converter, ConverterServer};
+pub use proto::{ConvertReply, ConvertRequest};
+
+/// The address `serve --grpc` listens on without `--address`.
+pub const DEFAULT_GRPC_ADDRESS: &str = "127.0.0.1:50051";
+
+/// The service definition clients are generated from, which `serve --grpc`
+/// implements.
+pub const PROTO: &str = include_str!("../proto/converter.proto");
+
+impl ConvertRequest {
+    /// Converts the value of the request, where a base of 0 stands for 2
+    /// and a precision of 0 for the usual 8 digits.
+    ///
+    /// # Returns
+    ///
+    /// The conversion, or a message saying which field is invalid.
+    pub fn convert(&self) -> Result<Conversion, String> {
+        let base = match self.base {
+            0 => 2,
+            base if base.abs() >= 2 => base,
+            base => return Err(format!("invalid target base `{}`", base)),
+        };
+        let mut pipeline = Pipeline::with_bases(vec![f64::from(base)]);
+        if self.precision > 0 {
+            pipeline.digits = check_precision(self.precision)?;
+        }
+        convert_value(&pipeline, &self.value)
+    }
+}
+
+impl From<Conversion> for ConvertReply {
+    /// A reply with the fields of a conversion, leaving out its label.
+    fn from(conversion: Conversion) -> ConvertReply {
+        ConvertReply {
+            input: conversion.input,
+            base: conversion.base,
+            digits: conversion.digits as u32,
+            truncated: conversion.truncated,
+            repr: conversion.repr,
+        }
+    }
+}
+
+/// Answers a request, or refuses it with `INVALID_ARGUMENT` and the reason.
+fn answer(request: &ConvertRequest) -> Result<ConvertReply, Status> {
+    request
+        .convert()
+        .map(ConvertReply::from)
+        .map_err(Status::invalid_argument)
+}
+
+/// The `baseconv.Converter` service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConverterService;
+
+/// The replies of a `ConvertStream` call.
+type ReplyStream = Pin<Box<dyn Stream<Item = Result<ConvertReply, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Converter for ConverterService {
+    async fn convert(
+        &self,
+        request: Request<ConvertRequest>,
+    ) -> Result<Response<ConvertReply>, Status> {
+        answer(request.get_ref()).map(Response::new)
+    }
+
+    type ConvertStreamStream = ReplyStream;
+
+    /// Answers every request as it arrives. The first error, such as an
+    /// invalid value, ends the call with its status.
+    async fn convert_stream(
+        &self,
+        request: Request<Streaming<ConvertRequest>>,
+    ) -> Result<Response<ReplyStream>, Status> {
+        let replies = request.into_inner().map(|request| answer(&request?));
+        Ok(Response::new(Box::pin(replies)))
+    }
+}
+
+/// A connection holding its place in the `ConnectionLimit` of the server
+/// until it is closed.
+struct LimitedStream {
+    stream: tokio::net::TcpStream,
+    _slot: ConnectionSlot,
+}
+
+impl Connected for LimitedStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> TcpConnectInfo {
+        self.stream.connect_info()
+    }
+}
+
+impl AsyncRead for LimitedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for LimitedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(context)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(context)
+    }
+}
+
+/// Answers gRPC calls on a listener until it fails, with at most
+/// `MAX_CONNECTIONS` connections served at once; more are closed as soon
+/// as they are accepted.
+pub fn serve_grpc(listener: TcpListener) -> io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let limit = ConnectionLimit::new(MAX_CONNECTIONS);
+        let incoming = TcpListenerStream::new(tokio::net::TcpListener::from_std(listener)?)
+            .filter_map(move |stream| match stream {
+                Ok(stream) => limit.acquire().map(|slot| {
+                    Ok(LimitedStream {
+                        stream,
+                        _slot: slot,
+                    })
+                }),
+                Err(error) => Some(Err(error)),
+            });
+        Server::builder()
+            .add_service(ConverterServer::new(ConverterService))
+            .serve_with_incoming(incoming)
+            .await
+            .map_err(io::Error::other)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use proto::converter_client::ConverterClient;
+    use std::thread;
+    use tonic::Code;
+
+    #[test]
+    fn test_messages() {
+        let half = ConvertRequest {
+            value: "0.5".to_string(),
+            ..Default::default()
+        };
+        let reply = answer(&half).unwrap();
+        assert_that!(reply.digits, equal_to(1));
+        assert_that!(reply.repr.as_str(), equal_to("0.1;"));
+        let base_one = ConvertRequest { base: 1, ..half };
+        assert_that!(
+            answer(&base_one).unwrap_err().code(),
+            equal_to(Code::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_serving_calls() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve_grpc(listener).unwrap());
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let mut client = ConverterClient::connect(format!("http://{}", address))
+                .await
+                .unwrap();
+            let request = |value: &str| ConvertRequest {
+                value: value.to_string(),
+                base: 2,
+                precision: 4,
+            };
+            let reply = client.convert(request("0.1")).await.unwrap().into_inner();
+            assert_that!(reply.digits, equal_to(4));
+            assert_that!(reply.repr.as_str(), equal_to("0.0;0;0;1;"));
+
+            // A streamed call whose second request is invalid.
+            let requests = tokio_stream::iter([request("0.5"), request("x"), request("0.25")]);
+            let mut replies = client.convert_stream(requests).await.unwrap().into_inner();
+            let first = replies.next().await.unwrap().unwrap();
+            assert_that!(first.repr.as_str(), equal_to("0.1;"));
+            let error = replies.next().await.unwrap().unwrap_err();
+            assert_that!(error.code(), equal_to(Code::InvalidArgument));
+            assert!(replies.next().await.is_none());
+        });
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::ops::Range;
+
+use num_traits::ToPrimitive;
+
+use crate::convert::{Expansion, Representation};
+use crate::rational::{Fraction, Ratio};
+use crate::terminate::{exact_input, period};
+
+/// How `--highlight-period` marks the first repetition of the repeating
+/// block of an expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodStyle {
+    /// The block is printed in cyan.
+    Color,
+    /// The block is underlined.
+    Underline,
+    /// The block is put between parentheses, as in `0.(1;4;2;8;5;7;)1;4;`,
+    /// for output that is not read on a terminal.
+    Parentheses,
+}
+
+impl PeriodStyle {
+    /// Looks up a style by the name given to `--highlight-period=<style>`.
+    pub fn from_name(name: &str) -> Option<PeriodStyle> {
+        match name {
+            "color" => Some(PeriodStyle::Color),
+            "underline" => Some(PeriodStyle::Underline),
+            "parens" | "parentheses" => Some(PeriodStyle::Parentheses),
+            _ => None,
+        }
+    }
+
+    /// The style used when none is named: color on a terminal, unless the
+    /// `NO_COLOR` convention asks for none, and parentheses everywhere else.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_terminal` - Whether the table is written to a terminal.
+    /// * `no_color` - Whether `NO_COLOR` is set to something other than
+    ///   the empty string.
+    pub fn detect(to_terminal: bool, no_color: bool) -> PeriodStyle {
+        if to_terminal && !no_color {
+            PeriodStyle::Color
+        } else {
+            PeriodStyle::Parentheses
+        }
+    }
+
+    /// What is written before and after the highlighted digits.
+    fn markers(self) -> (&'static str, &'static str) {
+        match self {
+            PeriodStyle::Color => ("\x1b[36m", "\x1b[39m"),
+            PeriodStyle::Underline => ("\x1b[4m", "\x1b[24m"),
+            PeriodStyle::Parentheses => ("(", ")"),
+        }
+    }
+}
+
+/// Parses the style of `--highlight-period=<style>`.
+pub fn parse_period_style(value: &str) -> Result<PeriodStyle, String> {
+    PeriodStyle::from_name(value).ok_or_else(|| {
+        format!(
+            "unknown period style `{}`, expected color, underline or parens",
+            value
+        )
+    })
+}
+
+/// Finds the fractional digits an expansion of an input repeats first: the
+/// digits from the end of the pre-period to the end of the first period, or
+/// to the last digit written if the expansion is cut off before that.
+///
+/// # Returns
+///
+/// The range of the block within `expansion.digits`, or `None` when the
+/// expansion terminates, when its base has no period, or when the block
+/// starts past the last digit written.
+///
+/// # Example
+///
+/// ```
+/// // 1/6 is 0.1;6;6;6;… in base 10, which repeats from the second digit.
+/// let sixth = Fraction::parse("1/6").unwrap();
+/// let expansion = Backend::LongDivision.expand(1.0 / 6.0, Some(&sixth), 10, 8);
+/// assert_eq!(repeating_digits(&expansion, 1.0 / 6.0, Some(&sixth)), Some(1..2));
+/// ```
+pub fn repeating_digits(
+    expansion: &Expansion,
+    number: f64,
+    fraction: Option<&Fraction>,
+) -> Option<Range<usize>> {
+    if expansion.base < 2.0 || expansion.base.fract() != 0.0 {
+        return None;
+    }
+    let exact = exact_input(number, fraction).map(Ratio::reduced)?;
+    let shape = period(&exact, expansion.base)?;
+    let start = usize::try_from(shape.pre_period).ok()?;
+    let length = shape.period.to_usize().unwrap_or(usize::MAX);
+    if length == 0 || start >= expansion.digits.len() {
+        return None;
+    }
+    Some(start..start.saturating_add(length).min(expansion.digits.len()))
+}
+
+/// An expansion with the first repetition of its repeating block marked, so
+/// that where the cycle starts and ends can be seen at a glance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodHighlighted {
+    /// The expansion shown.
+    pub expansion: Expansion,
+    /// The digits marked, from [`repeating_digits`].
+    pub block: Option<Range<usize>>,
+    /// How they are marked.
+    pub style: PeriodStyle,
+}
+
+impl PeriodHighlighted {
+    /// Marks the repeating block of the expansion of an input.
+    pub fn new(
+        expansion: Expansion,
+        number: f64,
+        fraction: Option<&Fraction>,
+        style: PeriodStyle,
+    ) -> PeriodHighlighted {
+        let block = repeating_digits(&expansion, number, fraction);
+        PeriodHighlighted {
+            expansion,
+            block,
+            style,
+        }
+    }
+}
+
+impl Representation for PeriodHighlighted {
+    fn value(&self) -> f64 {
+        self.expansion.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.expansion.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.expansion.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.expansion.positional_digits()
+    }
+}
+
+impl fmt::Display for PeriodHighlighted {
+    /// Writes the expansion as usual, with the markers of the style around
+    /// the digits of the block and the `;` that follows each of them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(block) = &self.block else {
+            return write!(f, "{}", self.expansion);
+        };
+        let mut text = String::new();
+        self.expansion.write_digits(&mut text, 0)?;
+        // Every fractional digit ends in a `;`, and the first one starts
+        // after the point.
+        let point = text.find('.').map_or(0, |point| point + 1);
+        let ends: Vec<usize> = text[point..]
+            .match_indices(';')
+            .map(|(i, _)| point + i + 1)
+            .collect();
+        let start = match block.start {
+            0 => point,
+            start => ends[start - 1],
+        };
+        let end = ends[block.end - 1];
+        let (open, close) = self.style.markers();
+        f.write_str(&text[..start])?;
+        f.write_str(open)?;
+        f.write_str(&text[start..end])?;
+        f.write_str(close)?;
+        f.write_str(&text[end..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+    use hamcrest2::prelude::*;
+
+    fn highlight(input: &str, target_base: u64, digits: u32, style: PeriodStyle) -> String {
+        let fraction = Fraction::parse(input);
+        let number = fraction
+            .as_ref()
+            .map_or_else(|| input.parse().unwrap(), Fraction::value);
+        let expansion =
+            Backend::LongDivision.expand(number, fraction.as_ref(), target_base, digits);
+        PeriodHighlighted::new(expansion, number, fraction.as_ref(), style).to_string()
+    }
+
+    #[test]
+    fn test_highlight_period() {
+        assert_that!(
+            highlight("1/7", 10, 8, PeriodStyle::Parentheses),
+            equal_to("0.(1;4;2;8;5;7;)1;4;".to_string())
+        );
+        assert_that!(
+            highlight("1/7", 10, 4, PeriodStyle::Parentheses),
+            equal_to("0.(1;4;2;8;)".to_string())
+        );
+        assert_that!(
+            highlight("0.1", 2, 8, PeriodStyle::Color),
+            equal_to("0.0;\x1b[36m0;0;1;1;\x1b[39m0;0;1;".to_string())
+        );
+        assert_that!(
+            highlight("0.25", 2, 8, PeriodStyle::Parentheses),
+            equal_to("0.0;1;".to_string())
+        );
+        assert_that!(
+            highlight("1/6", 10, 1, PeriodStyle::Parentheses),
+            equal_to("0.1;".to_string())
+        );
+
+        assert_that!(
+            PeriodStyle::detect(true, false),
+            equal_to(PeriodStyle::Color)
+        );
+        assert_that!(
+            PeriodStyle::detect(true, true),
+            equal_to(PeriodStyle::Parentheses)
+        );
+        assert!(parse_period_style("blink").is_err());
+    }
+}
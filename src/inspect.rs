@@ -0,0 +1,333 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::convert::Representation;
+
+/// The layout of an IEEE 754 binary floating-point format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatFormat {
+    /// The name of the format, as in `f64`.
+    pub name: &'static str,
+    /// The width of the biased exponent field.
+    pub exponent_bits: u32,
+    /// The width of the mantissa (trailing significand) field.
+    pub mantissa_bits: u32,
+}
+
+/// IEEE 754 double precision, the format the inputs are parsed into.
+pub const F64: FloatFormat = FloatFormat {
+    name: "f64",
+    exponent_bits: 11,
+    mantissa_bits: 52,
+};
+
+/// IEEE 754 single precision.
+pub const F32: FloatFormat = FloatFormat {
+    name: "f32",
+    exponent_bits: 8,
+    mantissa_bits: 23,
+};
+
+impl FloatFormat {
+    /// The amount added to the exponent before it is stored.
+    pub fn bias(&self) -> i64 {
+        (1 << (self.exponent_bits - 1)) - 1
+    }
+}
+
+/// The three fields of a stored floating-point number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatFields {
+    /// The format the fields were taken from.
+    pub format: FloatFormat,
+    /// The sign bit, 1 for negative numbers.
+    pub sign: u64,
+    /// The biased exponent field.
+    pub exponent: u64,
+    /// The mantissa field, without the implicit leading bit.
+    pub mantissa: u64,
+}
+
+impl FloatFields {
+    /// Splits the raw bits of a number stored in `format` into its fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let fields = FloatFields::from_bits(0.5f64.to_bits(), F64);
+    /// assert_eq!((fields.sign, fields.exponent, fields.mantissa), (0, 1022, 0));
+    /// ```
+    pub fn from_bits(bits: u64, format: FloatFormat) -> FloatFields {
+        let mantissa_mask = (1 << format.mantissa_bits) - 1;
+        let exponent_mask = (1 << format.exponent_bits) - 1;
+        FloatFields {
+            format,
+            sign: bits >> (format.exponent_bits + format.mantissa_bits) & 1,
+            exponent: bits >> format.mantissa_bits & exponent_mask,
+            mantissa: bits & mantissa_mask,
+        }
+    }
+
+    /// Writes a field of the given width in binary, padded with leading zeros.
+    fn binary(value: u64, width: u32) -> String {
+        format!("{:0width$b}", value, width = width as usize)
+    }
+
+    /// The exponent field in binary.
+    pub fn exponent_binary(&self) -> String {
+        FloatFields::binary(self.exponent, self.format.exponent_bits)
+    }
+
+    /// The mantissa field in binary.
+    pub fn mantissa_binary(&self) -> String {
+        FloatFields::binary(self.mantissa, self.format.mantissa_bits)
+    }
+
+    /// Writes the exact value the fields store as a binary significand times
+    /// a power of two, e.g. `1.1 × 2^-1` for 0.75.
+    ///
+    /// Every finite binary floating-point number is a terminating binary
+    /// fraction, so nothing is rounded here; trailing zeros of the mantissa are
+    /// left out.
+    pub fn stored_value(&self) -> String {
+        let sign = if self.sign == 1 { "-" } else { "" };
+        let all_ones = (1 << self.format.exponent_bits) - 1;
+
+        if self.exponent == all_ones {
+            return if self.mantissa == 0 {
+                format!("{}infinity", sign)
+            } else {
+                "NaN".to_string()
+            };
+        }
+        if self.exponent == 0 && self.mantissa == 0 {
+            return format!("{}0", sign);
+        }
+
+        // Subnormal numbers have no implicit leading one and the smallest exponent.
+        let (leading, exponent) = if self.exponent == 0 {
+            ('0', 1 - self.format.bias())
+        } else {
+            ('1', self.exponent as i64 - self.format.bias())
+        };
+        let mantissa = self.mantissa_binary();
+        let fraction = match mantissa.trim_end_matches('0') {
+            "" => "0",
+            trimmed => trimmed,
+        };
+        format!("{}{}.{} × 2^{}", sign, leading, fraction, exponent)
+    }
+
+    /// Writes the fields in the hexadecimal form of C's `%a`, e.g.
+    /// `0x1.999999999999ap-4` for 0.1.
+    ///
+    /// The mantissa is padded on the right to a whole number of hex digits,
+    /// and trailing zero digits are left out, so this too is exact.
+    pub fn hex_float(&self) -> String {
+        let sign = if self.sign == 1 { "-" } else { "" };
+        let all_ones = (1 << self.format.exponent_bits) - 1;
+
+        if self.exponent == all_ones {
+            return if self.mantissa == 0 {
+                format!("{}inf", sign)
+            } else {
+                "nan".to_string()
+            };
+        }
+        if self.exponent == 0 && self.mantissa == 0 {
+            return format!("{}0x0p+0", sign);
+        }
+
+        let (leading, exponent) = if self.exponent == 0 {
+            ('0', 1 - self.format.bias())
+        } else {
+            ('1', self.exponent as i64 - self.format.bias())
+        };
+        let hex_digits = self.format.mantissa_bits.div_ceil(4);
+        let mantissa = self.mantissa << (hex_digits * 4 - self.format.mantissa_bits);
+        let mantissa = format!("{:0width$x}", mantissa, width = hex_digits as usize);
+        match mantissa.trim_end_matches('0') {
+            "" => format!("{}0x{}p{:+}", sign, leading, exponent),
+            fraction => format!("{}0x{}.{}p{:+}", sign, leading, fraction, exponent),
+        }
+    }
+}
+
+/// An input shown in the hexadecimal float form of C's `%a`, which writes
+/// the stored `f64` without losing anything.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexFloat(pub f64);
+
+impl Representation for HexFloat {
+    fn value(&self) -> f64 {
+        self.0
+    }
+
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Display for HexFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = FloatFields::from_bits(self.0.to_bits(), F64);
+        write!(f, "{}", fields.hex_float())
+    }
+}
+
+/// The settings of the `inspect` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectOptions {
+    /// The formats every input is shown in: `f64`, and `f32` after it with `--f32`.
+    pub formats: Vec<FloatFormat>,
+    /// The numbers to inspect.
+    pub f64_numbers: Vec<f64>,
+}
+
+/// Parses the arguments of the `inspect` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `inspect`.
+///
+/// # Returns
+///
+/// The parsed `InspectOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- inspect --f32 0.1 -2.5
+/// ```
+pub fn parse_inspect_args(args: &[String]) -> Result<InspectOptions, String> {
+    let mut options = InspectOptions {
+        formats: vec![F64],
+        f64_numbers: Vec::new(),
+    };
+
+    for arg in args {
+        if arg == "--f32" {
+            if !options.formats.contains(&F32) {
+                options.formats.push(F32);
+            }
+        } else {
+            let number = arg
+                .parse::<f64>()
+                .map_err(|_| format!("unknown inspect argument `{}`", arg))?;
+            options.f64_numbers.push(number);
+        }
+    }
+
+    Ok(options)
+}
+
+/// Takes the stored bits of `number` in `format`, rounding it to `f32` first
+/// if needed.
+fn stored_bits(number: f64, format: FloatFormat) -> u64 {
+    if format == F32 {
+        (number as f32).to_bits() as u64
+    } else {
+        number.to_bits()
+    }
+}
+
+/// Runs the `inspect` subcommand, printing the fields and the exact stored
+/// value of every input in every requested format.
+///
+/// # Example
+/// ```
+/// // cargo run -- inspect 0.75
+/// ```
+/// Output:
+/// 0.75 as f64:
+///   sign:     0
+///   exponent: 01111111110 (1022, unbiased -1)
+///   mantissa: 1000000000000000000000000000000000000000000000000000
+///   stored:   1.1 × 2^-1
+///   hex:      0x1.8p-1
+pub fn display_inspect(out: &mut impl Write, options: &InspectOptions) -> io::Result<()> {
+    for &number in &options.f64_numbers {
+        for &format in &options.formats {
+            let fields = FloatFields::from_bits(stored_bits(number, format), format);
+            writeln!(out, "{} as {}:", number, format.name)?;
+            writeln!(out, "  sign:     {}", fields.sign)?;
+            writeln!(
+                out,
+                "  exponent: {} ({}, unbiased {})",
+                fields.exponent_binary(),
+                fields.exponent,
+                fields.exponent as i64 - format.bias()
+            )?;
+            writeln!(out, "  mantissa: {}", fields.mantissa_binary())?;
+            writeln!(out, "  stored:   {}", fields.stored_value())?;
+            writeln!(out, "  hex:      {}", fields.hex_float())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn fields(number: f64, format: FloatFormat) -> FloatFields {
+        FloatFields::from_bits(stored_bits(number, format), format)
+    }
+
+    #[test]
+    fn test_f64_fields() {
+        let tenth = fields(0.1, F64);
+        assert_that!(tenth.sign, equal_to(0));
+        assert_that!(tenth.exponent_binary(), equal_to("01111111011"));
+        assert_that!(
+            tenth.mantissa_binary(),
+            equal_to("1001100110011001100110011001100110011001100110011010")
+        );
+        assert_that!(
+            tenth.stored_value(),
+            equal_to("1.100110011001100110011001100110011001100110011001101 × 2^-4")
+        );
+
+        assert_that!(fields(-2.0, F64).stored_value(), equal_to("-1.0 × 2^1"));
+        assert_that!(fields(0.0, F64).stored_value(), equal_to("0"));
+        assert_that!(
+            fields(f64::INFINITY, F64).stored_value(),
+            equal_to("infinity")
+        );
+        assert_that!(
+            fields(f64::MIN_POSITIVE / 4.0, F64).stored_value(),
+            equal_to("0.01 × 2^-1022")
+        );
+    }
+
+    #[test]
+    fn test_f32_fields() {
+        let tenth = fields(0.1, F32);
+        assert_that!(tenth.exponent, equal_to(123));
+        assert_that!(tenth.mantissa_binary(), equal_to("10011001100110011001101"));
+        assert_that!(F32.bias(), equal_to(127));
+    }
+
+    #[test]
+    fn test_hex_float() {
+        assert_that!(HexFloat(0.1).to_string(), equal_to("0x1.999999999999ap-4"));
+        assert_that!(HexFloat(1.0).to_string(), equal_to("0x1p+0"));
+        assert_that!(HexFloat(-2.5).to_string(), equal_to("-0x1.4p+1"));
+        assert_that!(HexFloat(0.0).to_string(), equal_to("0x0p+0"));
+        assert_that!(
+            HexFloat(f64::MIN_POSITIVE / 4.0).to_string(),
+            equal_to("0x0.4p-1022")
+        );
+        assert_that!(HexFloat(f64::NEG_INFINITY).to_string(), equal_to("-inf"));
+        assert_that!(fields(0.1, F32).hex_float(), equal_to("0x1.99999ap-4"));
+    }
+
+    #[test]
+    fn test_inspect_arguments() {
+        let options = parse_inspect_args(&["--f32".to_string(), "0.5".to_string()]).unwrap();
+        assert_that!(options.formats, equal_to(vec![F64, F32]));
+        assert_that!(options.f64_numbers, equal_to(vec![0.5]));
+        assert!(parse_inspect_args(&["--f16".to_string()]).is_err());
+        assert!(parse_inspect_args(&["abc".to_string()]).is_err());
+    }
+}
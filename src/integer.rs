@@ -0,0 +1,166 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+
+use crate::convert::{Expansion, Representation};
+use crate::notation::{write_expansion, Alphanumeric, Semicolon};
+use crate::rational::Fraction;
+
+/// The largest base whose digits are written as `0`-`9` and `A`-`Z`.
+const LARGEST_ALPHANUMERIC_BASE: f64 = 36.0;
+
+/// Converts a whole number by repeated division, without a fractional
+/// digit to extract.
+///
+/// # Arguments
+///
+/// * `value` - The input.
+/// * `fraction` - The exact fraction it was written as, if any, whose
+///   digits are taken instead of those of `value`.
+/// * `target_base` - The base to convert to, at least 2.
+///
+/// # Returns
+///
+/// The expansion with only integer digits, or `None` if the input is not a
+/// whole number.
+///
+/// # Example
+///
+/// ```
+/// let expansion = expand_whole(255.0, None, 16).unwrap();
+/// assert_eq!(expansion.integer_digits, vec![15, 15]);
+/// assert_eq!(expand_whole(0.5, None, 16), None);
+/// ```
+pub fn expand_whole(
+    value: f64,
+    fraction: Option<&Fraction>,
+    target_base: u64,
+) -> Option<Expansion> {
+    if !value.is_finite() || value.fract() != 0.0 {
+        return None;
+    }
+    let magnitude = match fraction {
+        Some(fraction) => {
+            if !(&fraction.numerator % &fraction.denominator).is_zero() {
+                return None;
+            }
+            &fraction.numerator / &fraction.denominator
+        }
+        None => BigUint::from_f64(value.abs())?,
+    };
+    Some(Expansion {
+        negative: value < 0.0,
+        base: target_base as f64,
+        integer_digits: whole_digits(magnitude, target_base),
+        digits: Vec::new(),
+        remainder: 0.0,
+    })
+}
+
+/// The digits of a whole number in a base, most significant first, and
+/// none for zero.
+fn whole_digits(magnitude: BigUint, target_base: u64) -> Vec<u32> {
+    let mut digits = Vec::new();
+    // Numbers that fit in 64 bits are divided without big integers.
+    if let Some(mut rest) = magnitude.to_u64() {
+        while rest > 0 {
+            digits.push((rest % target_base) as u32);
+            rest /= target_base;
+        }
+    } else {
+        let base = BigUint::from(target_base);
+        let mut rest = magnitude;
+        while !rest.is_zero() {
+            digits.push((&rest % &base).to_u32().unwrap_or_default());
+            rest /= &base;
+        }
+    }
+    digits.reverse();
+    digits
+}
+
+/// An expansion written as a plain integer, such as `FF` in base 16, when it
+/// is a whole number, and as it is otherwise. Digits of bases above 36 are
+/// written in base 10 with `;` between them, as in `4;15` in base 60.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Whole(pub Expansion);
+
+impl fmt::Display for Whole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Whole(expansion) = self;
+        if !expansion.digits.is_empty() || !expansion.is_exact() || expansion.base < 2.0 {
+            return write!(f, "{}", expansion);
+        }
+        let mut text = String::new();
+        if expansion.base <= LARGEST_ALPHANUMERIC_BASE {
+            write_expansion(&mut text, expansion, &Alphanumeric)?;
+        } else {
+            write_expansion(&mut text, expansion, &Semicolon::default())?;
+        }
+        // Without fractional digits the point is all that follows.
+        f.write_str(text.strip_suffix('.').unwrap_or(&text))
+    }
+}
+
+impl Representation for Whole {
+    fn value(&self) -> f64 {
+        self.0.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.0.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.0.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.0.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.0.positional_digits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    fn whole(value: f64, target_base: u64) -> String {
+        Whole(expand_whole(value, None, target_base).unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_expand_whole() {
+        assert_that!(whole(255.0, 16), equal_to("FF".to_string()));
+        assert_that!(whole(255.0, 2), equal_to("11111111".to_string()));
+        assert_that!(whole(-35.0, 36), equal_to("-Z".to_string()));
+        assert_that!(whole(0.0, 8), equal_to("0".to_string()));
+        assert_that!(whole(255.0, 60), equal_to("4;15".to_string()));
+        assert_that!(whole(1e20, 16), equal_to("56BC75E2D63100000".to_string()));
+        assert_that!(expand_whole(0.5, None, 2), equal_to(None));
+        assert_that!(expand_whole(f64::INFINITY, None, 2), equal_to(None));
+
+        let exact = Fraction::parse_long_decimal("12345678901234567890123").unwrap();
+        let expansion = expand_whole(exact.value(), Some(&exact), 10).unwrap();
+        assert_that!(
+            Whole(expansion).to_string(),
+            equal_to("12345678901234567890123".to_string())
+        );
+        let third = Fraction::parse("4/3").unwrap();
+        assert_that!(expand_whole(1.0, Some(&third), 10), equal_to(None));
+    }
+
+    #[test]
+    fn test_fractional_expansions_are_kept() {
+        assert_that!(
+            Whole(expand(0.75, 2, 8)).to_string(),
+            equal_to("0.1;1;".to_string())
+        );
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt::{self, Display, Write};
+
+use serde::ser::{self, Serialize};
+
+/// Why a value could not be written as JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError(String);
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl ser::Error for JsonError {
+    fn custom<T: Display>(message: T) -> JsonError {
+        JsonError(message.to_string())
+    }
+}
+
+/// Writes a value as compact JSON, such as a `Conversion` as
+/// `{"input":0.5,"base":"2","digits":1,"truncated":false,"repr":"0.1;"}`.
+///
+/// Numbers that JSON cannot hold, such as NaN, are written as `null`.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, JsonError> {
+    let mut serializer = JsonSerializer {
+        output: String::new(),
+    };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// Writes a string as a quoted JSON string, escaping what JSON requires.
+pub fn write_json_string(output: &mut String, text: &str) {
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// A serde serializer writing compact JSON into a string.
+struct JsonSerializer {
+    /// The JSON written so far.
+    output: String,
+}
+
+impl JsonSerializer {
+    /// Writes a number, or `null` for one JSON cannot hold.
+    fn number(&mut self, value: impl Display, finite: bool) {
+        if finite {
+            let _ = write!(self.output, "{}", value);
+        } else {
+            self.output.push_str("null");
+        }
+    }
+}
+
+/// Writes the elements of a sequence or the entries of a map or struct,
+/// separating them with commas.
+struct Compound<'a> {
+    /// The serializer the elements are written with.
+    serializer: &'a mut JsonSerializer,
+    /// Whether no element has been written yet.
+    first: bool,
+    /// What closes the compound, `]` or `}`.
+    close: &'static str,
+}
+
+impl Compound<'_> {
+    /// Writes the comma before every element but the first.
+    fn separate(&mut self) {
+        if !self.first {
+            self.serializer.output.push(',');
+        }
+        self.first = false;
+    }
+
+    /// Writes a struct field name followed by its value.
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), JsonError> {
+        self.separate();
+        write_json_string(&mut self.serializer.output, key);
+        self.serializer.output.push(':');
+        value.serialize(&mut *self.serializer)
+    }
+
+    /// Writes a sequence element.
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.separate();
+        value.serialize(&mut *self.serializer)
+    }
+
+    /// Closes the compound.
+    fn finish(self) -> Result<(), JsonError> {
+        self.serializer.output.push_str(self.close);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut JsonSerializer {
+    type Ok = ();
+    type Error = JsonError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, value: bool) -> Result<(), JsonError> {
+        self.output.push_str(if value { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), JsonError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), JsonError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), JsonError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), JsonError> {
+        self.number(value, true);
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), JsonError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), JsonError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), JsonError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), JsonError> {
+        self.number(value, true);
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), JsonError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), JsonError> {
+        self.number(value, value.is_finite());
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), JsonError> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), JsonError> {
+        write_json_string(&mut self.output, value);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), JsonError> {
+        let mut seq = ser::Serializer::serialize_seq(self, Some(value.len()))?;
+        for byte in value {
+            seq.element(byte)?;
+        }
+        seq.finish()
+    }
+
+    fn serialize_none(self) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), JsonError> {
+        self.output.push_str("null");
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.output.push('{');
+        write_json_string(&mut self.output, variant);
+        self.output.push(':');
+        value.serialize(&mut *self)?;
+        self.output.push('}');
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, JsonError> {
+        self.output.push('[');
+        Ok(Compound {
+            serializer: self,
+            first: true,
+            close: "]",
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, JsonError> {
+        self.output.push('{');
+        write_json_string(&mut self.output, variant);
+        self.output.push_str(":[");
+        Ok(Compound {
+            serializer: self,
+            first: true,
+            close: "]}",
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, JsonError> {
+        self.output.push('{');
+        Ok(Compound {
+            serializer: self,
+            first: true,
+            close: "}",
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a>, JsonError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, JsonError> {
+        self.output.push('{');
+        write_json_string(&mut self.output, variant);
+        self.output.push_str(":{");
+        Ok(Compound {
+            serializer: self,
+            first: true,
+            close: "}}",
+        })
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    /// Keys are written as JSON strings whatever their type, as JSON
+    /// requires.
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), JsonError> {
+        self.separate();
+        let key = to_json(key)?;
+        if key.starts_with('"') {
+            self.serializer.output.push_str(&key);
+        } else {
+            write_json_string(&mut self.serializer.output, &key);
+        }
+        self.serializer.output.push(':');
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::Conversion;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_conversion_as_json() {
+        let conversion = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+        assert_that!(
+            to_json(&conversion).unwrap(),
+            equal_to(r#"{"input":0.5,"base":"2","digits":1,"truncated":false,"repr":"0.1;"}"#)
+        );
+        let labelled = Conversion {
+            label: Some("half".to_string()),
+            ..conversion
+        };
+        assert!(to_json(&labelled)
+            .unwrap()
+            .starts_with(r#"{"label":"half","input":0.5,"#));
+    }
+
+    #[test]
+    fn test_json_values() {
+        assert_that!(to_json(&f64::NAN).unwrap(), equal_to("null"));
+        assert_that!(
+            to_json(&vec![Some("a\"b\n"), None]).unwrap(),
+            equal_to(r#"["a\"b\n",null]"#)
+        );
+        let map: BTreeMap<u32, bool> = [(2, true), (3, false)].into_iter().collect();
+        assert_that!(to_json(&map).unwrap(), equal_to(r#"{"2":true,"3":false}"#));
+    }
+}
@@ -0,0 +1,54 @@
+/// The header of the column of the labels inputs were given with.
+pub const LABEL_HEADER: &str = "Label";
+
+/// Splits the label off an input written as `pi_frac=0.14159`, which names
+/// its row in the table and its records, so that rows stay identifiable
+/// when many similar values are converted. A label starts with a letter or
+/// `_` and goes on with letters, digits, `_` and `-`.
+///
+/// # Returns
+///
+/// The label, if the input has one, and the input without it.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(split_label("pi_frac=0.14159"), (Some("pi_frac"), "0.14159"));
+/// assert_eq!(split_label("0.5"), (None, "0.5"));
+/// ```
+pub fn split_label(arg: &str) -> (Option<&str>, &str) {
+    match arg.split_once('=') {
+        Some((label, input)) if is_label(label) => (Some(label), input),
+        _ => (None, arg),
+    }
+}
+
+/// Whether a name can label an input.
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_split_label() {
+        assert_that!(
+            split_label("pi_frac=0.14159"),
+            equal_to((Some("pi_frac"), "0.14159"))
+        );
+        assert_that!(
+            split_label("run-2=1/3:20"),
+            equal_to((Some("run-2"), "1/3:20"))
+        );
+        assert_that!(split_label("0.5"), equal_to((None, "0.5")));
+        assert_that!(split_label("2x=0.5"), equal_to((None, "2x=0.5")));
+        assert_that!(split_label("=0.5"), equal_to((None, "=0.5")));
+    }
+}
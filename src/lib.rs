@@ -0,0 +1,113 @@
+//! The conversions, formats and modes of the command-line tool, as a
+//! library for programs that convert without running it, such as servers
+//! that await [`asynchronous::to_base_stream`] or register formats of their
+//! own with [`formatter::register_formatter`].
+
+pub mod aggregate;
+pub mod alphabet;
+pub mod analyze;
+pub mod angle;
+pub mod answer;
+pub mod approx;
+pub mod asynchronous;
+pub mod backend;
+pub mod balanced;
+pub mod base_matrix;
+pub mod batch;
+pub mod bcd;
+pub mod bench;
+pub mod beta;
+pub mod bitgroup;
+pub mod calc;
+pub mod cancel;
+pub mod check;
+pub mod checkpoint;
+pub mod checksum;
+pub mod cli;
+pub mod clipboard;
+pub mod columns;
+pub mod comment;
+pub mod config;
+pub mod continued_fraction;
+pub mod conversion;
+pub mod convert;
+pub mod counterexample;
+pub mod csv;
+pub mod decimal;
+pub mod dedup;
+pub mod diagnostic;
+pub mod diff;
+pub mod digit_limit;
+pub mod digits_value;
+pub mod display;
+pub mod editor;
+pub mod egyptian;
+pub mod exit;
+pub mod explain;
+pub mod explain_float;
+pub mod expression;
+pub mod factoradic;
+pub mod filter;
+pub mod find;
+pub mod fixed;
+pub mod format;
+pub mod formatter;
+pub mod glob;
+pub mod gray;
+#[cfg(feature = "server")]
+pub mod grpc;
+pub mod highlight;
+pub mod inspect;
+pub mod integer;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod label;
+pub mod locale;
+pub mod log;
+pub mod matrix;
+pub mod memo;
+pub mod memory;
+pub mod mmap;
+#[cfg(feature = "serde")]
+pub mod msgpack;
+pub mod notation;
+pub mod output;
+pub mod pad;
+pub mod padic;
+pub mod pager;
+pub mod parallel;
+pub mod parquet;
+pub mod pipeline;
+pub mod progress;
+pub mod quiz;
+pub mod radices;
+pub mod rational;
+pub mod repl;
+#[cfg(feature = "serde")]
+pub mod schema;
+pub mod scientific;
+pub mod script;
+pub mod separator;
+#[cfg(feature = "server")]
+pub mod serve;
+pub mod shortest;
+pub mod sign;
+pub mod sort;
+pub mod sparkline;
+pub mod sqlite;
+pub mod stats;
+#[cfg(feature = "server")]
+pub mod stdio;
+pub mod stream;
+pub mod style;
+pub mod subscript;
+pub mod summary;
+pub mod terminate;
+pub mod time;
+pub mod verify;
+pub mod watch;
+pub mod worksheet;
+pub mod xlsx;
+#[cfg(feature = "serde")]
+pub mod yaml;
+pub mod zeckendorf;
@@ -0,0 +1,85 @@
+/// The languages that write decimals with a comma, such as `0,5`, as ISO
+/// 639-1 codes.
+const COMMA_LANGUAGES: [&str; 34] = [
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id", "is",
+    "it", "lt", "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr",
+    "uk", "vi",
+];
+
+/// The countries whose German and Italian speakers write decimals with a
+/// point, unlike the rest of those languages.
+const POINT_COUNTRIES: [&str; 2] = ["CH", "LI"];
+
+/// Tells whether a locale writes decimals with a comma, from a name such as
+/// `de`, `fr-FR` or `de_DE.UTF-8`, where `C` and `POSIX` use a point.
+///
+/// # Returns
+///
+/// Whether the decimal separator is a comma. Languages that are not known
+/// to use one, such as `en` or `ja`, use a point.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(uses_decimal_comma("de_DE.UTF-8"), Ok(true));
+/// assert_eq!(uses_decimal_comma("de-CH"), Ok(false));
+/// assert_eq!(uses_decimal_comma("en_US"), Ok(false));
+/// ```
+pub fn uses_decimal_comma(locale: &str) -> Result<bool, String> {
+    let name = locale.split(['.', '@']).next().unwrap_or("");
+    if name == "C" || name == "POSIX" {
+        return Ok(false);
+    }
+    let mut parts = name.split(['_', '-']);
+    let language = parts.next().unwrap_or("").to_ascii_lowercase();
+    let country = parts.next().unwrap_or("").to_ascii_uppercase();
+    if language.len() < 2 || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("invalid locale `{}`", locale));
+    }
+
+    if POINT_COUNTRIES.contains(&country.as_str()) && (language == "de" || language == "it") {
+        return Ok(false);
+    }
+    Ok(COMMA_LANGUAGES.contains(&language.as_str()))
+}
+
+/// Rewrites a number written with a decimal comma, such as `0,5`, with a
+/// point, leaving numbers already written with a point as they are.
+///
+/// # Returns
+///
+/// The number with a point, or `None` if it holds both a comma and a point
+/// or more than one comma, since those cannot be read unambiguously.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(with_decimal_point("-0,25").as_deref(), Some("-0.25"));
+/// assert_eq!(with_decimal_point("1.234,5"), None);
+/// ```
+pub fn with_decimal_point(arg: &str) -> Option<String> {
+    match arg.matches(',').count() {
+        0 => Some(arg.to_string()),
+        1 if !arg.contains('.') => Some(arg.replace(',', ".")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_decimal_comma_locales() {
+        assert_that!(uses_decimal_comma("fr"), equal_to(Ok(true)));
+        assert_that!(uses_decimal_comma("pt_BR.UTF-8"), equal_to(Ok(true)));
+        assert_that!(uses_decimal_comma("it-CH"), equal_to(Ok(false)));
+        assert_that!(uses_decimal_comma("C.UTF-8"), equal_to(Ok(false)));
+        assert!(uses_decimal_comma("").is_err());
+
+        assert_that!(with_decimal_point("0,5"), equal_to(Some("0.5".to_string())));
+        assert_that!(with_decimal_point("0.5"), equal_to(Some("0.5".to_string())));
+        assert_that!(with_decimal_point("1,2,3"), equal_to(None));
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much `-v` and `-vv` ask to be told about every input on standard
+/// error. Without them nothing is logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// `-v`: the value every input was parsed as and the path chosen to
+    /// convert it.
+    Info = 1,
+    /// `-vv`: also how the digit loop of every conversion went.
+    Debug = 2,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+        }
+    }
+}
+
+/// The verbosity of the run, set once from the number of `-v` flags.
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Sets how many `-v` flags were given.
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+/// Whether messages of a level are logged in this run.
+pub fn enabled(level: Level) -> bool {
+    VERBOSITY.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Logs a message to standard error as `[debug convert] …`, if its level is
+/// enabled. The message is only built when it is logged, so that logging
+/// costs nothing in the default silent runs.
+///
+/// # Arguments
+///
+/// * `level` - How verbose the run must be for the message to show.
+/// * `target` - The stage the message comes from, such as `decode`.
+/// * `message` - Builds the message.
+///
+/// # Example
+///
+/// ```
+/// log(Level::Info, "decode", || format!("`{}` parsed as {}", "0.1", 0.1));
+/// ```
+pub fn log(level: Level, target: &str, message: impl FnOnce() -> String) {
+    if enabled(level) {
+        let _ = writeln!(io::stderr().lock(), "[{} {}] {}", level, target, message());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_follow_verbosity() {
+        assert!(Level::Info < Level::Debug);
+        set_verbosity(1);
+        assert!(enabled(Level::Info) && !enabled(Level::Debug));
+        set_verbosity(2);
+        assert!(enabled(Level::Debug));
+        set_verbosity(0);
+        assert!(!enabled(Level::Info));
+        log(Level::Info, "test", || {
+            unreachable!("silent runs build no message")
+        });
+    }
+}
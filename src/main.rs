@@ -1,6 +1,89 @@
-use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::mem::size_of;
+use std::path::Path;
+use std::process;
 
-const MAX_DIGITS: u32 = 8;
+use baseconv::aggregate::display_aggregate;
+use baseconv::alphabet::{display_alphabet_check, Encoded};
+use baseconv::analyze::display_analysis;
+use baseconv::angle::to_dms;
+use baseconv::answer::display_answer_check;
+use baseconv::approx::display_approximations;
+use baseconv::balanced::expand_balanced;
+use baseconv::base_matrix::display_base_matrix;
+use baseconv::batch::{expand_batch, BATCH_SIZE};
+use baseconv::bcd::Bcd;
+use baseconv::bench::{display_bench, display_throughput};
+use baseconv::beta::{expand_beta, format_base, BetaRule};
+use baseconv::calc::display_calc;
+use baseconv::cancel::cancel_on_interrupt;
+use baseconv::check::display_check;
+use baseconv::checkpoint::{Checkpoint, CHECKPOINT_INTERVAL};
+use baseconv::checksum::{write_sidecar, Checksum, HashingWriter};
+use baseconv::cli::{
+    arrange_inputs, base_note, decode_inputs, is_valid_input, parse_input, unzip_rows, Command,
+    Options,
+};
+use baseconv::clipboard::{copy_to_clipboard, CopyingWriter};
+use baseconv::columns::write_columns;
+use baseconv::comment::input_words;
+use baseconv::continued_fraction::display_continued_fractions;
+use baseconv::conversion::Conversion;
+use baseconv::convert::{Expansion, Representation};
+use baseconv::counterexample::display_counterexamples;
+use baseconv::csv::{column_values, display_csv};
+use baseconv::diagnostic::{find_spans, line_words, Span};
+use baseconv::diff::display_diff;
+use baseconv::display::{display, display_header, display_row, display_table_end, RowBuffer};
+use baseconv::egyptian::display_egyptian;
+use baseconv::exit::{
+    invalid_input, invalid_input_at, report_collected, round_trip_failures, set_error_mode,
+    skip_input, skip_input_at, skip_row, skipped_inputs, written, ErrorMode, EXIT_ROUND_TRIP,
+    EXIT_SKIPPED, EXIT_USAGE,
+};
+use baseconv::explain::display_explanation;
+use baseconv::explain_float::display_explain_float;
+use baseconv::factoradic::expand_factoradic;
+use baseconv::find::display_find;
+use baseconv::fixed::to_fixed_point;
+use baseconv::format::{display_conversions, make_conversions, write_conversions, OutputFormat};
+use baseconv::gray::GrayColumn;
+use baseconv::highlight::PeriodHighlighted;
+use baseconv::inspect::{display_inspect, HexFloat};
+use baseconv::log::set_verbosity;
+use baseconv::matrix::display_matrix;
+use baseconv::memory::must_stream;
+use baseconv::notation::Notated;
+use baseconv::output::{open_output, results_path, section_path, OutputOptions};
+use baseconv::pad::PaddedExpansion;
+use baseconv::padic::display_padic;
+use baseconv::pager::Pager;
+use baseconv::parallel::{file_worker_count, parallel_map, worker_count};
+use baseconv::pipeline::Pipeline;
+use baseconv::progress::Progress;
+use baseconv::quiz::display_quiz;
+use baseconv::radices::expand_mixed_radix;
+use baseconv::rational::Fraction;
+use baseconv::repl::display_repl;
+#[cfg(feature = "serde")]
+use baseconv::schema::record_run_options;
+use baseconv::scientific::expand_scientific;
+use baseconv::separator::Separated;
+#[cfg(feature = "server")]
+use baseconv::serve::display_serve;
+use baseconv::sign::{display_verify, write_signature};
+use baseconv::sqlite::{table_inputs, SQLITE_MAGIC};
+use baseconv::stats::display_stats;
+#[cfg(feature = "server")]
+use baseconv::stdio::display_serve_stdio;
+use baseconv::stream::{for_each_streamed_input, Position};
+use baseconv::subscript::{subscript, Subscripted};
+use baseconv::summary::{display_footer, display_summary, Footer, Summary};
+use baseconv::time::{to_clock_time, to_duration};
+use baseconv::watch::{stamps, wait_for_change, POLL_INTERVAL};
+use baseconv::worksheet::display_worksheet;
+use baseconv::zeckendorf::expand_zeckendorf;
 
 /// The entry point of the program that reads command-line arguments,
 /// Converts the arguments from decimal to target base, and prints the results.
@@ -8,222 +91,1378 @@ const MAX_DIGITS: u32 = 8;
 /// This function expects that the first command-line argument is the target base for conversion
 /// followed by a list of floating-point numbers, which will be converted to target base. The program
 /// will print a table displaying the original decimal numbers and their
-/// target base equivalents.
+/// target base equivalents. Alternatively, `--pipe` describes the decode, convert
+/// and format stages every input goes through, and `--base` lists several
+/// target bases to show side by side. With `--matrix-options`, a long-format
+/// CSV dataset of every input, base and precision combination is printed instead.
+/// With `--explain`, every multiply-by-base step is shown before the table, and
+/// with `--summary-only` only aggregate statistics are printed. `--balanced`
+/// converts to balanced ternary instead, and `--base-float` adds non-integer bases.
+/// `--as-time` renders base-60 expansions as times.
+///
+/// The other representations replace the expansion of every input in the
+/// same way: `--as-fixed`, `--bcd`, `--factoradic`, `--radices`,
+/// `--duration`, `--dms`, `--zeckendorf`, `--hex-float`, `--gray` and
+/// `--scientific`, while `--shortest`, `--exact-digits`, `--precision`,
+/// `--alphabet`, `--notation`, `--pad-digits`, `--highlight-period` and
+/// `--subscript` change how its digits are found or written, and
+/// `--columns` picks the columns shown beside them. Only the combinations
+/// the `SUPPORTED_FLAGS` table of the cli module allows are accepted.
+///
+/// The inputs can also be read with `--file`, `--glob`, `--column` and
+/// `--table`, or one at a time with `--stream`, and the results written as
+/// `--format` or by the extension of `--output`, into `--output-dir`, to the
+/// clipboard with `--copy`, or signed with `--sign-key`. The first
+/// argument may instead name a subcommand, as listed on `parse_input`.
 fn main() {
-    let (target_base, f64_numbers) = parse_input();
+    let options = match parse_input() {
+        Ok(Command::Convert(options)) => *options,
+        Ok(Command::Find(find_options)) => {
+            if !written(display_find(&mut io::stdout().lock(), &find_options)) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Counterexamples(counterexample_options)) => {
+            if !written(display_counterexamples(
+                &mut io::stdout().lock(),
+                &counterexample_options,
+            )) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Analyze(analyze_options)) => {
+            written(display_analysis(&mut io::stdout().lock(), &analyze_options));
+            return;
+        }
+        Ok(Command::Inspect(inspect_options)) => {
+            written(display_inspect(&mut io::stdout().lock(), &inspect_options));
+            return;
+        }
+        Ok(Command::BenchCompare(bench_options)) => {
+            written(display_bench(&mut io::stdout().lock(), &bench_options));
+            return;
+        }
+        Ok(Command::Bench(throughput_options)) => {
+            written(display_throughput(
+                &mut io::stdout().lock(),
+                &throughput_options,
+            ));
+            return;
+        }
+        Ok(Command::ContinuedFraction(cf_options)) => {
+            written(display_continued_fractions(
+                &mut io::stdout().lock(),
+                &cf_options,
+            ));
+            return;
+        }
+        Ok(Command::Aggregate(aggregate_options)) => {
+            if !display_aggregate(&aggregate_options) {
+                process::exit(1);
+            }
+            return;
+        }
+        #[cfg(feature = "server")]
+        Ok(Command::Serve(serve_options)) => {
+            if !display_serve(&serve_options) {
+                process::exit(1);
+            }
+            return;
+        }
+        #[cfg(feature = "server")]
+        Ok(Command::ServeStdio(stdio_options)) => {
+            if !display_serve_stdio(&stdio_options) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Quiz(quiz_options)) => {
+            display_quiz(&quiz_options);
+            return;
+        }
+        Ok(Command::Worksheet(worksheet_options)) => {
+            if !written(display_worksheet(
+                &mut io::stdout().lock(),
+                &worksheet_options,
+            )) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Repl(repl_options)) => {
+            display_repl(&repl_options);
+            return;
+        }
+        Ok(Command::Padic(padic_options)) => {
+            written(display_padic(&mut io::stdout().lock(), &padic_options));
+            return;
+        }
+        Ok(Command::Approx(approx_options)) => {
+            written(display_approximations(
+                &mut io::stdout().lock(),
+                &approx_options,
+            ));
+            return;
+        }
+        Ok(Command::Egyptian(egyptian_options)) => {
+            written(display_egyptian(
+                &mut io::stdout().lock(),
+                &egyptian_options,
+            ));
+            return;
+        }
+        Ok(Command::Calc(calc_options)) => {
+            written(display_calc(&mut io::stdout().lock(), &calc_options));
+            return;
+        }
+        Ok(Command::Diff(diff_options)) => {
+            written(display_diff(&mut io::stdout().lock(), &diff_options));
+            return;
+        }
+        Ok(Command::Matrix(matrix_options)) => {
+            written(display_base_matrix(
+                &mut io::stdout().lock(),
+                &matrix_options,
+            ));
+            return;
+        }
+        Ok(Command::ExplainFloat(explain_float_options)) => {
+            written(display_explain_float(
+                &mut io::stdout().lock(),
+                &explain_float_options,
+            ));
+            return;
+        }
+        Ok(Command::Stats(stats_options)) => {
+            written(display_stats(&mut io::stdout().lock(), &stats_options));
+            return;
+        }
+        Ok(Command::Csv(csv_options)) => {
+            if !display_csv(&csv_options) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Check(check_options)) => {
+            if !display_check(&check_options) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Verify(verify_options)) => {
+            if !written(display_verify(&mut io::stdout().lock(), &verify_options)) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::CheckAnswer(answer_options)) => {
+            if !written(display_answer_check(
+                &mut io::stdout().lock(),
+                &answer_options,
+            )) {
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Alphabet(alphabet_options)) => {
+            if !written(display_alphabet_check(
+                &mut io::stdout().lock(),
+                &alphabet_options,
+            )) {
+                process::exit(1);
+            }
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    let fail_fast = options.error_mode == ErrorMode::FailFast;
+    set_error_mode(options.error_mode);
+    #[cfg(feature = "serde")]
+    record_run_options(&options.pipeline);
+    if !options.quiet && !options.representation.own_radix() {
+        eprintln!("{}", base_note(&options));
+    }
+    let valid = |text: &str| is_valid_input(&options.pipeline, text);
+    if let Some(input) = options.invalid_inputs.first().filter(|_| fail_fast) {
+        match options.invalid_spans.first() {
+            Some(Some(span)) => eprintln!("error: {}", invalid_input_at(input, span, valid)),
+            _ => eprintln!("error: {}", invalid_input(input)),
+        }
+        process::exit(EXIT_SKIPPED);
+    }
+    for (input, span) in options.invalid_inputs.iter().zip(&options.invalid_spans) {
+        match span {
+            Some(span) => skip_input_at(input, span, valid),
+            None => skip_input(input),
+        }
+    }
+
+    if !options.files.is_empty() {
+        set_verbosity(options.verbosity);
+        cancel_on_interrupt(&options.pipeline.cancel);
+        if let Err(message) = convert_files(&options) {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+        report_collected();
+        if round_trip_failures() > 0 {
+            process::exit(EXIT_ROUND_TRIP);
+        }
+        if skipped_inputs() > 0 {
+            process::exit(EXIT_SKIPPED);
+        }
+        return;
+    }
+
+    if let Some(dir) = &options.output.dir {
+        cancel_on_interrupt(&options.pipeline.cancel);
+        if let Err(message) = convert_to_dir(&options, dir) {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+        report_collected();
+        if skipped_inputs() > 0 {
+            process::exit(EXIT_SKIPPED);
+        }
+        return;
+    }
+
+    set_verbosity(options.verbosity);
+    let mut pager = Pager::start(options.paging);
+    let mut out = match &mut pager {
+        Some(pager) => Box::new(pager),
+        None => match open_output(&options.output) {
+            Ok(out) => out,
+            Err(message) => {
+                eprintln!("error: {}", message);
+                process::exit(EXIT_USAGE);
+            }
+        },
+    };
+    cancel_on_interrupt(&options.pipeline.cancel);
+    if !options.watch_paths.is_empty() {
+        watch(options, &mut *out);
+        return;
+    }
+    let converted = convert_copied(&options, &mut *out).and_then(|()| out.flush());
+    drop(out);
+    written(converted.and_then(|()| pager.map_or(Ok(()), Pager::finish)));
+    if options.pipeline.cancel.is_cancelled() {
+        eprintln!("note: interrupted, so the results above are partial");
+        process::exit(130);
+    }
+    if let (Some(key), Some(path)) = (&options.output.sign_key, &options.output.path) {
+        if let Err(message) = write_signature(path, key) {
+            eprintln!("error: {}", message);
+            process::exit(1);
+        }
+    }
+    report_collected();
+    if round_trip_failures() > 0 {
+        eprintln!(
+            "error: {} of the rows failed the round-trip check",
+            round_trip_failures()
+        );
+        process::exit(EXIT_ROUND_TRIP);
+    }
+    if skipped_inputs() > 0 {
+        process::exit(EXIT_SKIPPED);
+    }
+}
 
-    let target_base_numbers: Vec<String> = f64_numbers
+/// Converts the inputs like [`convert`], then again every time one of the
+/// `--watch` files changes, clearing the terminal first, until Ctrl-C.
+/// Every change parses the arguments anew, so that the files are read again.
+fn watch(mut options: Options, out: &mut dyn Write) {
+    let cancel = options.pipeline.cancel.clone();
+    let clear = io::stdout().is_terminal();
+    let run = |options: &Options, out: &mut dyn Write| {
+        let cleared = if clear {
+            write!(out, "\x1b[2J\x1b[H")
+        } else {
+            Ok(())
+        };
+        let result = cleared
+            .and_then(|()| convert_checked(options, out))
+            .and_then(|()| out.flush());
+        if let Err(error) = result {
+            eprintln!("error: {}", error);
+        }
+    };
+
+    run(&options, out);
+    let mut last = stamps(&options.watch_paths);
+    while let Some(current) = wait_for_change(&options.watch_paths, &last, &cancel, POLL_INTERVAL) {
+        last = current;
+        match parse_input() {
+            Ok(Command::Convert(reparsed)) => {
+                options = *reparsed;
+                options.pipeline.cancel = cancel.clone();
+                run(&options, out);
+            }
+            Ok(_) => {}
+            Err(message) => eprintln!("error: {}", message),
+        }
+    }
+}
+
+/// Converts the inputs once and writes them to a results file in `dir` for
+/// every `--format`, such as `results.md` and `results.json`, so that the
+/// table and the records come from the same expansions.
+fn convert_to_dir(options: &Options, dir: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|error| format!("cannot create `{}`: {}", dir, error))?;
+    let pipeline = &options.pipeline;
+    let progress = Progress::new(options.f64_numbers.len(), options.quiet);
+    let limited = limited_options(options);
+    let expansions: Vec<Vec<Expansion>> = options
+        .f64_numbers
         .iter()
-        .map(|&num| convert_from_decimal_to_binary(num, target_base))
+        .zip(&options.fractions)
+        .zip(&options.digit_limits)
+        .map(|((&num, fraction), limit)| {
+            let pipeline = &row_options(options, &limited, *limit).pipeline;
+            let row_expansions = pipeline.expand(num, fraction.as_ref());
+            progress.tick();
+            row_expansions
+        })
+        .take_while(|_| !pipeline.cancel.is_cancelled())
         .collect();
+    progress.finish();
+    let converted = expansions.len();
 
-    display(target_base, f64_numbers, target_base_numbers);
+    for &format in &options.formats {
+        let path = results_path(dir, format.extension());
+        let mut out = open_output(&OutputOptions {
+            path: Some(path.clone()),
+            force: options.output.force,
+            backup: options.output.backup,
+            ..OutputOptions::default()
+        })?;
+        let written = match format {
+            OutputFormat::Table => {
+                let headers: Vec<String> = pipeline
+                    .target_bases
+                    .iter()
+                    .map(|&target_base| format!("Base {}", format_base(target_base)))
+                    .collect();
+                let separated: Vec<Vec<Separated<Expansion>>> = expansions
+                    .iter()
+                    .map(|row_expansions| {
+                        row_expansions
+                            .iter()
+                            .map(|expansion| Separated {
+                                representation: expansion.clone(),
+                                separator: options.separator.as_deref(),
+                            })
+                            .collect()
+                    })
+                    .collect();
+                display(
+                    &mut *out,
+                    &headers,
+                    (
+                        &options.f64_numbers[..converted],
+                        &options.fractions[..converted],
+                        &options.counts[..converted],
+                        &options.labels,
+                    ),
+                    &separated,
+                    options.columns,
+                    &options.styles,
+                )
+            }
+            _ => {
+                let labelled = options.columns.label;
+                let conversions = options
+                    .f64_numbers
+                    .iter()
+                    .zip(&expansions)
+                    .enumerate()
+                    .flat_map(|(i, (&input, row_expansions))| {
+                        let label = labelled
+                            .then(|| options.labels.get(i).cloned().flatten().unwrap_or_default());
+                        pipeline.target_bases.iter().zip(row_expansions).map(
+                            move |(&target_base, expansion)| Conversion {
+                                label: label.clone(),
+                                ..Conversion::new(input, target_base, expansion)
+                            },
+                        )
+                    });
+                match &options.selected_columns {
+                    Some(columns) => write_columns(&mut *out, format, columns, conversions),
+                    None => write_conversions(&mut *out, format, conversions),
+                }
+            }
+        };
+        written
+            .and_then(|()| out.flush())
+            .map_err(|error| format!("cannot write `{}`: {}", path, error))?;
+    }
+    if pipeline.cancel.is_cancelled() {
+        eprintln!("note: interrupted, so the results in `{}` are partial", dir);
+    }
+    Ok(())
 }
 
-/// Reads fractional numbers in base 10 and the target base for conversion
-/// from the command-line arguments and parses them into a vector of `f64` values.
-///
-/// # Returns
-///
-/// A tuple contaiting a u32 target base for conversion, and a f64 vector of floating point numbers.
-///
-/// # Panics
-///
-/// This function assumes the first command line arguement is a valid
-/// u32 number to be used as target base for conversion. If no arguement is provided,
-/// or the arguement is a non-integer, the target base defaults to 2.
-/// All arguments after the target base are valid
-/// floating-point numbers. If invalid arguments are provided, they will
-/// be skipped.
-///
-/// # Example
-/// ```
-/// // Assuming the program is run as follows:
-/// // cargo run -- 2 0.1 0.25 0.5
-/// let (base, parsed) = parse_input();
-/// assert_eq!(base, 2);
-/// assert_eq!(parsed, vec![0.1, 0.25, 0.5]);
-/// ```
-fn parse_input() -> (u32, Vec<f64>) {
-    let args: Vec<String> = env::args().collect();
-
-    let target_base: u32 = args
-        .get(1)
-        .and_then(|arg| arg.parse::<u32>().ok())
-        .unwrap_or(2);
-
-    let skip_count = if args
-        .get(1)
-        .and_then(|arg| arg.parse::<u32>().ok())
-        .is_some()
-    {
-        2
+/// Converts every `--file` input on a thread of its own, then writes the
+/// results of each as a section headed `==> <file> <==`, or with
+/// `--output-dir` as results files named after it, one for every
+/// `--format`. With `--stream`, the files are instead converted one after
+/// the other, each line by line as it is read.
+fn convert_files(options: &Options) -> Result<(), String> {
+    let formats = match options.output.dir {
+        Some(_) => options.formats.clone(),
+        None => vec![options.format],
+    };
+    let sections = if options.stream_from.is_empty() {
+        let workers = file_worker_count(options.jobs, options.files.len());
+        parallel_map(&options.files, workers, |path| {
+            convert_file(options, &formats, path)
+        })
     } else {
-        1
+        Vec::new()
+    };
+
+    let mut file_out = match &options.output.dir {
+        Some(dir) => {
+            fs::create_dir_all(dir)
+                .map_err(|error| format!("cannot create `{}`: {}", dir, error))?;
+            None
+        }
+        None => Some(open_output(&options.output)?),
+    };
+    // A sidecar of a single `--output` file covers every section in it.
+    let whole_file = match (options.output.checksum, &options.output.path) {
+        (Some(Checksum::Sidecar), Some(path)) if file_out.is_some() => Some(path),
+        _ => None,
+    };
+    let hash = match (&mut file_out, whole_file) {
+        (Some(file_out), Some(_)) => {
+            let mut hashing = HashingWriter::new(&mut **file_out);
+            write_sections(options, &formats, sections, Some(&mut hashing))?;
+            Some(hashing.finish())
+        }
+        (file_out, _) => {
+            let out = file_out.as_mut().map(|out| &mut **out as &mut dyn Write);
+            write_sections(options, &formats, sections, out)?;
+            None
+        }
     };
+    if let (Some(path), Some(hash), Some(file_out)) = (whole_file, hash, &mut file_out) {
+        file_out
+            .flush()
+            .and_then(|()| write_sidecar(path, &hash))
+            .map_err(|error| format!("cannot write `{}`: {}", path, error))?;
+    }
+    if options.pipeline.cancel.is_cancelled() {
+        eprintln!("note: interrupted, so the results are partial");
+    }
+    Ok(())
+}
 
-    let f64_numbers: Vec<f64> = args
+/// Writes the sections of the `--file` inputs to `out`, or to the
+/// `--output-dir` when there is no `out`, converting each streamed file
+/// when its turn comes.
+fn write_sections(
+    options: &Options,
+    formats: &[OutputFormat],
+    sections: Vec<Result<FileSection, String>>,
+    mut out: Option<&mut dyn Write>,
+) -> Result<(), String> {
+    let mut sections = sections.into_iter();
+    for (i, path) in options.files.iter().enumerate() {
+        if options.pipeline.cancel.is_cancelled() {
+            break;
+        }
+        let Some(section) = sections.next() else {
+            // A streamed file is converted straight into its section.
+            let streamed = Options {
+                stream_from: vec![path.clone()],
+                files: Vec::new(),
+                ..options.clone()
+            };
+            for &format in formats {
+                let section = Options {
+                    format,
+                    ..streamed.clone()
+                };
+                write_section(options, &mut out, (i, path), format, |out| {
+                    convert(&section, out)
+                })?;
+            }
+            continue;
+        };
+        let (invalid_inputs, results) = section?;
+        let valid = |text: &str| is_valid_input(&options.pipeline, text);
+        match invalid_inputs
+            .first()
+            .filter(|_| options.error_mode == ErrorMode::FailFast)
+        {
+            Some((Some(row), cell, _)) => {
+                return Err(format!(
+                    "in `{}` row {}: {}",
+                    path,
+                    row,
+                    invalid_input(cell)
+                ));
+            }
+            Some((None, input, Some(span))) => return Err(invalid_input_at(input, span, valid)),
+            Some((None, input, None)) => {
+                return Err(format!("in `{}`: {}", path, invalid_input(input)))
+            }
+            None => {}
+        }
+        for (row, input, span) in &invalid_inputs {
+            match (row, span) {
+                (Some(row), _) => skip_row(path, *row, input),
+                (None, Some(span)) => skip_input_at(input, span, valid),
+                (None, None) => skip_input(input),
+            }
+        }
+        for (format, results) in results {
+            write_section(options, &mut out, (i, path), format, |out| {
+                out.write_all(&results)
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// The inputs of a `--file` that could not be decoded, each with the CSV
+/// row or rowid it is in, or where in the text it is, and the results of
+/// every format.
+type FileSection = (
+    Vec<(Option<usize>, String, Option<Span>)>,
+    Vec<(OutputFormat, Vec<u8>)>,
+);
+
+/// Reads, arranges and converts the inputs of one `--file` in every format.
+fn convert_file(
+    options: &Options,
+    formats: &[OutputFormat],
+    path: &str,
+) -> Result<FileSection, String> {
+    let bytes =
+        fs::read(path).map_err(|error| format!("cannot read input file `{}`: {}", path, error))?;
+    let (cells, text) = if bytes.starts_with(SQLITE_MAGIC) {
+        let table = options.sqlite_table.as_deref();
+        let cells = table_inputs(path, table, options.csv_column.as_deref())
+            .map_err(|error| format!("in `{}`: {}", path, error))?;
+        (Some(cells), String::new())
+    } else {
+        let text = String::from_utf8(bytes)
+            .map_err(|error| format!("cannot read input file `{}`: {}", path, error))?;
+        let cells = match &options.csv_column {
+            Some(column) => Some(
+                column_values(&text, column)
+                    .map_err(|error| format!("in `{}`: {}", path, error))?,
+            ),
+            None => None,
+        };
+        (cells, text)
+    };
+    let (inputs, invalid) = match cells {
+        Some(cells) => {
+            let inputs = decode_inputs(
+                &options.pipeline,
+                cells.iter().map(|(_, cell)| cell.as_str()),
+            );
+            let invalid: Vec<(Option<usize>, String, Option<Span>)> = cells
+                .into_iter()
+                .filter(|(_, cell)| inputs.invalid.contains(cell))
+                .map(|(row, cell)| (Some(row), cell, None))
+                .collect();
+            (inputs, invalid)
+        }
+        None => {
+            let inputs = decode_inputs(&options.pipeline, input_words(&text));
+            let spans = find_spans(line_words(path, &text), &inputs.invalid);
+            let invalid = inputs
+                .invalid
+                .iter()
+                .zip(spans)
+                .map(|(input, span)| (None, input.clone(), span))
+                .collect();
+            (inputs, invalid)
+        }
+    };
+    let rows = arrange_inputs(inputs, &options.arrangement, &options.pipeline)
+        .map_err(|error| format!("in `{}`: {}", path, error))?;
+    let (f64_numbers, fractions, counts, digit_limits, labels) = unzip_rows(rows);
+    // The footer of the section counts the inputs skipped in this file.
+    let section = Options {
+        f64_numbers,
+        fractions,
+        counts,
+        digit_limits,
+        labels,
+        files: Vec::new(),
+        invalid_inputs: invalid.iter().map(|(_, input, _)| input.clone()).collect(),
+        invalid_spans: Vec::new(),
+        ..options.clone()
+    };
+    let results = formats
         .iter()
-        .skip(skip_count)
-        .flat_map(|arg| arg.parse::<f64>())
+        .map(|&format| {
+            let mut results = Vec::new();
+            convert(
+                &Options {
+                    format,
+                    ..section.clone()
+                },
+                &mut results,
+            )
+            .map(|()| (format, results))
+            .map_err(|error| format!("cannot convert `{}`: {}", path, error))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((invalid, results))
+}
+
+/// Writes the results of the `i`th `--file` in one format, as a section of
+/// the output headed `==> <file> <==`, or as a file of `--output-dir`, with
+/// the `--checksum` of the section after it or in a sidecar next to it.
+fn write_section(
+    options: &Options,
+    out: &mut Option<&mut dyn Write>,
+    (i, path): (usize, &str),
+    format: OutputFormat,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> Result<(), String> {
+    let checksum = options.output.checksum;
+    let checked = |out: &mut dyn Write, sidecar: Option<&str>| {
+        let Some(checksum) = checksum else {
+            return write(out);
+        };
+        let mut hashing = HashingWriter::new(out);
+        write(&mut hashing)?;
+        let hash = hashing.finish();
+        match (checksum, sidecar) {
+            (Checksum::Sidecar, Some(target)) => {
+                out.flush().and_then(|()| write_sidecar(target, &hash))
+            }
+            // A single `--output` file gets one sidecar for all sections.
+            (Checksum::Sidecar, None) if options.output.path.is_some() => Ok(()),
+            _ => writeln!(out, "SHA-256: {}", hash),
+        }
+    };
+    match (out, &options.output.dir) {
+        (Some(out), _) => {
+            let gap = if i > 0 { "\n" } else { "" };
+            writeln!(out, "{}==> {} <==", gap, path)
+                .and_then(|()| checked(&mut **out, None))
+                .and_then(|()| out.flush())
+                .map_err(|error| error.to_string())
+        }
+        (None, Some(dir)) => {
+            let target = section_path(dir, path, options.glob_root.as_deref(), format.extension());
+            if let Some(parent) = Path::new(&target).parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|error| format!("cannot create `{}`: {}", parent.display(), error))?;
+            }
+            let mut file = open_output(&OutputOptions {
+                path: Some(target.clone()),
+                force: options.output.force,
+                backup: options.output.backup,
+                ..OutputOptions::default()
+            })?;
+            checked(&mut *file, Some(&target))
+                .and_then(|()| file.flush())
+                .map_err(|error| format!("cannot write `{}`: {}", target, error))
+        }
+        (None, None) => Ok(()),
+    }
+}
+
+/// Converts the inputs like [`convert_checked`], then puts the results on
+/// the clipboard where `--copy` asks for it.
+fn convert_copied(options: &Options, out: &mut dyn Write) -> io::Result<()> {
+    if !options.output.copy {
+        return convert_checked(options, out);
+    }
+
+    let mut copying = CopyingWriter::new(out);
+    convert_checked(options, &mut copying)?;
+    copy_to_clipboard(&copying.finish()).map_err(io::Error::other)?;
+    if !options.quiet {
+        eprintln!("note: copied the results to the clipboard");
+    }
+    Ok(())
+}
+
+/// Converts the inputs like [`convert`], then writes the SHA-256 of the
+/// results where `--checksum` asks for it.
+fn convert_checked(options: &Options, out: &mut dyn Write) -> io::Result<()> {
+    let checksum = match options.output.checksum {
+        Some(checksum) => checksum,
+        None => return convert(options, out),
+    };
+
+    let mut hashing = HashingWriter::new(out);
+    convert(options, &mut hashing)?;
+    let hash = hashing.finish();
+    match (checksum, &options.output.path) {
+        (Checksum::Sidecar, Some(path)) => {
+            out.flush()?;
+            write_sidecar(path, &hash)
+        }
+        _ => writeln!(out, "SHA-256: {}", hash),
+    }
+}
+
+/// Converts the inputs as the options ask and writes the results to `out`.
+fn convert(options: &Options, out: &mut dyn Write) -> io::Result<()> {
+    if options.matrix_options {
+        return display_matrix(
+            out,
+            options.format,
+            &options.pipeline,
+            &options.precisions,
+            &options.f64_numbers,
+            options.max_memory,
+            options.jobs,
+        );
+    }
+
+    if let (Some(columns), true) = (
+        &options.selected_columns,
+        options.format != OutputFormat::Table,
+    ) {
+        let conversions = make_conversions(
+            &options.pipeline,
+            &options.f64_numbers,
+            &options.fractions,
+            &options.digit_limits,
+            &options.labels,
+        );
+        return write_columns(out, options.format, columns, conversions);
+    }
+    if options.format != OutputFormat::Table && !options.stream_from.is_empty() {
+        return stream_records(options, out);
+    }
+    if options.format != OutputFormat::Table {
+        return display_conversions(
+            out,
+            options.format,
+            &options.pipeline,
+            &options.f64_numbers,
+            &options.fractions,
+            &options.digit_limits,
+            &options.labels,
+        );
+    }
+
+    if !options.representation.beta_rules.is_empty() {
+        let columns = beta_columns(options);
+        let headers: Vec<String> = columns
+            .iter()
+            .map(|&(target_base, rule)| match rule {
+                Some(rule) => format!("Base {} {}", format_base(target_base), rule.name()),
+                None => format!("Base {}", format_base(target_base)),
+            })
+            .collect();
+        return present(options, out, &headers, |options, num, _| {
+            columns
+                .iter()
+                .map(|&(target_base, rule)| match rule {
+                    Some(rule) => expand_beta(num, target_base, rule, options.pipeline.digits),
+                    None => options.pipeline.expand_to(num, target_base),
+                })
+                .collect()
+        });
+    }
+
+    if let Some((integer_bits, fraction_bits)) = options.representation.as_fixed {
+        let headers = vec![format!("Q{}.{}", integer_bits, fraction_bits)];
+        return present(options, out, &headers, |options, num, _| {
+            vec![to_fixed_point(
+                num,
+                integer_bits,
+                fraction_bits,
+                options.pipeline.rounding,
+            )]
+        });
+    }
+
+    if options.representation.bcd {
+        let headers = vec!["BCD".to_string()];
+        return present(options, out, &headers, |_, num, _| vec![Bcd(num)]);
+    }
+
+    if options.representation.factoradic {
+        let headers = vec!["Factoradic".to_string()];
+        return present(options, out, &headers, |options, num, fraction| {
+            vec![expand_factoradic(num, fraction, options.pipeline.digits)]
+        });
+    }
+
+    if options.representation.dms {
+        let headers = vec!["DMS".to_string()];
+        return present(options, out, &headers, |_, num, fraction| {
+            vec![to_dms(num, fraction)]
+        });
+    }
+
+    if options.representation.duration {
+        let headers = vec!["Duration".to_string()];
+        return present(options, out, &headers, |_, num, fraction| {
+            vec![to_duration(num, fraction)]
+        });
+    }
+
+    if let Some(radices) = &options.representation.radices {
+        let list: Vec<String> = radices.iter().map(|radix| radix.to_string()).collect();
+        let headers = vec![format!("Radices {}", list.join(","))];
+        return present(options, out, &headers, |options, num, fraction| {
+            vec![expand_mixed_radix(
+                num,
+                fraction,
+                radices,
+                options.pipeline.digits,
+            )]
+        });
+    }
+
+    if options.representation.zeckendorf {
+        let headers = vec!["Zeckendorf".to_string()];
+        return present(options, out, &headers, |options, num, _| {
+            vec![expand_zeckendorf(num, options.pipeline.digits)]
+        });
+    }
+
+    if options.representation.hex_float {
+        let headers = vec!["Hex float".to_string()];
+        return present(options, out, &headers, |_, num, _| vec![HexFloat(num)]);
+    }
+
+    let headers: Vec<String> = options
+        .pipeline
+        .target_bases
+        .iter()
+        .map(|&target_base| format!("Base {}", format_base(target_base)))
         .collect();
-    (target_base, f64_numbers)
+    if let Some(unit) = options.representation.as_time {
+        present(options, out, &headers, |options, num, _| {
+            vec![to_clock_time(num, unit, &options.pipeline)]
+        })
+    } else if options.representation.gray {
+        let headers = vec![headers[0].clone(), "Gray".to_string()];
+        present(options, out, &headers, |options, num, fraction| {
+            let expansion = options.pipeline.expand(num, fraction).remove(0);
+            vec![
+                GrayColumn::Binary(expansion.clone()),
+                GrayColumn::Gray(expansion),
+            ]
+        })
+    } else if options.representation.scientific {
+        present(options, out, &headers, |options, num, fraction| {
+            options
+                .pipeline
+                .target_bases
+                .iter()
+                .map(|&target_base| {
+                    expand_scientific(&options.pipeline, num, fraction, target_base)
+                })
+                .collect()
+        })
+    } else if let Some(alphabet) = &options.representation.alphabet {
+        present(options, out, &headers, |options, num, fraction| {
+            let encoded = options
+                .pipeline
+                .expand(num, fraction)
+                .into_iter()
+                .map(|expansion| Encoded {
+                    expansion,
+                    alphabet,
+                })
+                .collect();
+            annotate(options, encoded)
+        })
+    } else if let Some(notation) = options.representation.notation {
+        present(options, out, &headers, |options, num, fraction| {
+            options
+                .pipeline
+                .expand(num, fraction)
+                .into_iter()
+                .map(|expansion| Notated {
+                    expansion,
+                    formatter: notation.formatter(),
+                })
+                .collect()
+        })
+    } else if let Some(pad) = options.representation.pad_digits {
+        present(options, out, &headers, |options, num, fraction| {
+            let padded = options
+                .pipeline
+                .expand(num, fraction)
+                .into_iter()
+                .map(|expansion| PaddedExpansion::new(expansion, pad))
+                .collect();
+            annotate(options, padded)
+        })
+    } else if options.representation.balanced {
+        present(options, out, &headers, |options, num, _| {
+            annotate(options, vec![expand_balanced(num, options.pipeline.digits)])
+        })
+    } else if let Some(style) = options.representation.highlight_period {
+        present(options, out, &headers, |options, num, fraction| {
+            let highlighted = options
+                .pipeline
+                .expand(num, fraction)
+                .into_iter()
+                .map(|expansion| PeriodHighlighted::new(expansion, num, fraction, style))
+                .collect();
+            annotate(options, highlighted)
+        })
+    } else {
+        present_batched(options, out, &headers, |options, inputs| {
+            expand_batch(&options.pipeline, inputs)
+                .into_iter()
+                .map(|row_expansions| annotate(options, row_expansions))
+                .collect()
+        })
+    }
 }
 
-/// Converts a decimal number (f64) to its target base representation as a string.
-///
-/// # Arguments
-///
-/// * `decimal` - A floating-point number to convert.
-/// * `target_base` - The base to convert to.
-///
-/// # Returns
-///
-/// A `String` containing the target base representation of the input `decimal`
-/// Each digit is seperated by a ; for easier readability
+/// Follows the representation of an input in every target base by that
+/// base as a subscript when `--subscript` is given.
+fn annotate<R>(options: &Options, columns: Vec<R>) -> Vec<Subscripted<R>> {
+    columns
+        .into_iter()
+        .zip(&options.pipeline.target_bases)
+        .map(|(representation, &target_base)| Subscripted {
+            representation,
+            subscript: options
+                .representation
+                .subscript
+                .then(|| subscript(target_base as i64)),
+        })
+        .collect()
+}
+
+/// Lists the table columns when `--expansion` is given: one for every integer
+/// base, and one for every requested rule of every non-integer base.
+fn beta_columns(options: &Options) -> Vec<(f64, Option<BetaRule>)> {
+    let mut columns = Vec::new();
+    for &target_base in &options.pipeline.target_bases {
+        if target_base.fract() == 0.0 {
+            columns.push((target_base, None));
+        } else {
+            columns.extend(
+                options
+                    .representation
+                    .beta_rules
+                    .iter()
+                    .map(|&rule| (target_base, Some(rule))),
+            );
+        }
+    }
+    columns
+}
+
+/// Converts and prints every input as soon as it is read for `--stream`: the
+/// inputs on the command line first, then those of the `--input` files line
+/// by line. No row is kept once it is printed, so the memory used does not
+/// grow with the number of inputs.
 ///
-/// # Example
+/// With `--checkpoint`, where the first input not yet printed lies is saved
+/// every `CHECKPOINT_INTERVAL` inputs and when the run stops early, and the
+/// checkpoint is removed once every input is converted. With `--resume`,
+/// the run goes on from the checkpoint, without printing the header again.
+fn present_streamed<R: Representation>(
+    options: &Options,
+    out: &mut dyn Write,
+    headers: &[String],
+    expand: &impl Fn(f64, Option<&Fraction>) -> Vec<R>,
+) -> io::Result<()> {
+    let mut summary = options
+        .summary_only
+        .then(|| Summary::new(&options.pipeline.target_bases));
+    if summary.is_none() && options.resume_from.is_none() {
+        display_header(out, headers, options.columns, &options.styles)?;
+    }
+
+    let mut buffer = RowBuffer::default();
+    let mut footer = Footer::default();
+    let streamed = stream_inputs(options, out, |out, num, fraction, label| {
+        let row_expansions = expand(num, fraction);
+        if options.pipeline.cancel.is_cancelled() {
+            return Ok(false);
+        }
+        match &mut summary {
+            Some(summary) => summary.add(num, &row_expansions),
+            None => {
+                footer.add(&row_expansions);
+                display_row(
+                    out,
+                    &mut buffer,
+                    headers,
+                    (num, fraction, 1, label),
+                    &row_expansions,
+                    options.columns,
+                    &options.styles,
+                )?;
+            }
+        }
+        Ok(true)
+    })?;
+
+    if let Some(summary) = summary {
+        return display_summary(out, &summary);
+    }
+    // A table to be resumed is left open, for the rest of its rows to go
+    // on from where it stopped.
+    if options.checkpoint.is_none() || !options.pipeline.cancel.is_cancelled() {
+        display_table_end(out, headers, options.columns, &options.styles)?;
+    }
+    if options.footer {
+        display_footer(out, &footer, options.invalid_inputs.len() + streamed)?;
+    }
+    Ok(())
+}
+
+/// Like [`present_streamed`], but writes the conversions of every input in
+/// the line-oriented `--format` of the run, such as CSV or JSON Lines, as
+/// soon as it is read. The CSV and TSV header is written first, unless the
+/// run is resumed.
+fn stream_records(options: &Options, out: &mut dyn Write) -> io::Result<()> {
+    let labelled = options.labels.iter().any(Option::is_some);
+    if options.resume_from.is_none() {
+        options.format.write_header(out, labelled)?;
+    }
+    stream_inputs(options, out, |out, num, fraction, label| {
+        let pipeline = &options.pipeline;
+        let expansions: Vec<Expansion> = pipeline
+            .target_bases
+            .iter()
+            .map(|&target_base| pipeline.expand_input(num, fraction, target_base))
+            .collect();
+        if pipeline.cancel.is_cancelled() {
+            return Ok(false);
+        }
+        let conversions =
+            pipeline
+                .target_bases
+                .iter()
+                .zip(&expansions)
+                .map(|(&target_base, expansion)| Conversion {
+                    label: labelled.then(|| label.unwrap_or_default().to_string()),
+                    ..Conversion::new(num, target_base, expansion)
+                });
+        options.format.write_records(out, conversions)?;
+        Ok(true)
+    })
+    .map(|_| ())
+}
+
+/// Reads the inputs of a `--stream` run, the ones on the command line first
+/// and then those of the `--input` files line by line, and calls `show`
+/// with every one of them, its fraction and its label, if any, keeping the
+/// `--checkpoint` up to date as [`present_streamed`] describes.
 ///
-/// ```
-/// let binary = convert_from_decimal_to_binary(0.5, 2);
-/// assert_eq!(binary, "0.1;");
-/// ```
-fn convert_from_decimal_to_binary(decimal: f64, target_base: u32) -> String {
-    let mut result = String::from("0.");
-    let mut fraction = decimal;
-
-    for _ in 0..MAX_DIGITS {
-        fraction *= target_base as f64;
-        let digit = fraction.floor() as u32;
-        result += &format!("{};", digit);
-        fraction -= digit as f64;
-
-        if fraction == 0.0 {
+/// `show` says whether it wrote the input, which it does not once the run
+/// is cancelled. Returns how many of the streamed inputs were skipped.
+fn stream_inputs(
+    options: &Options,
+    out: &mut dyn Write,
+    mut show: impl FnMut(&mut dyn Write, f64, Option<&Fraction>, Option<&str>) -> io::Result<bool>,
+) -> io::Result<usize> {
+    for (i, (&num, fraction)) in options
+        .f64_numbers
+        .iter()
+        .zip(&options.fractions)
+        .enumerate()
+    {
+        let label = options.labels.get(i).and_then(Option::as_deref);
+        show(out, num, fraction.as_ref(), label)?;
+    }
+
+    // Only printed inputs move the checkpoint on, so the invalid inputs
+    // after the last of them are warned about again on resuming.
+    let mut reached = options.resume_from.clone();
+    let mut since_saved = 0;
+    let mut skipped = 0;
+    let mut streamed = Ok(());
+    for (input, path) in options.stream_from.iter().enumerate() {
+        let start = match &options.resume_from {
+            Some(resumed) if input < resumed.input => continue,
+            Some(resumed) if input == resumed.input => resumed.position,
+            _ => Position::START,
+        };
+        streamed = for_each_streamed_input(
+            path,
+            start,
+            &options.pipeline,
+            |num, fraction, after| {
+                if !show(out, num, fraction, None)? {
+                    return Ok(());
+                }
+                let checkpoint = Checkpoint {
+                    input,
+                    path: path.clone(),
+                    position: after,
+                };
+                since_saved += 1;
+                if let Some(file) = options.checkpoint.as_ref() {
+                    if since_saved >= CHECKPOINT_INTERVAL {
+                        since_saved = 0;
+                        out.flush()?;
+                        checkpoint.save(file)?;
+                    }
+                }
+                reached = Some(checkpoint);
+                Ok(())
+            },
+            |input, _| {
+                if options.error_mode == ErrorMode::FailFast {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        invalid_input(input),
+                    ));
+                }
+                skip_input(input);
+                skipped += 1;
+                Ok(())
+            },
+        );
+        if streamed.is_err() || options.pipeline.cancel.is_cancelled() {
             break;
         }
     }
-
-    result
+    if let Some(file) = &options.checkpoint {
+        out.flush()?;
+        if streamed.is_ok() && !options.pipeline.cancel.is_cancelled() {
+            if Path::new(file).exists() {
+                fs::remove_file(file)?;
+            }
+        } else if let Some(reached) = &reached {
+            reached.save(file)?;
+            eprintln!(
+                "note: stopped at line {} of `{}`; run again with --resume to go on from there",
+                reached.position.line, reached.path
+            );
+        }
+    }
+    streamed.map(|()| skipped)
 }
 
-/// Outputs the decimal numbers and their target base fractional representations in a table format.
+/// Prints the summary or the table of the converted inputs.
 ///
 /// # Arguments
 ///
-/// * `target_base` - An integer indicating the base of converted numbers.
-/// * `f64_numbers` - A vector of decimal numbers in base 10.
-/// * `target_base_numbers` - A vector of target base fractional strings corresponding to the decimal numbers.
-///
-/// # Example
-/// ```
-/// display(vec![0.5, 0.25], vec!["0.1;".to_string(), "0.0;1;".to_string()]);
-/// ```
-/// Output:
-/// |   Base 10   |   Base 2   |
-/// |:------------|:-----------|
-/// | 0.5         | 0.1;       |
-/// | 0.25        | 0.0;1;     |
-fn display(target_base: u32, f64_numbers: Vec<f64>, target_base_numbers: Vec<String>) {
-    println!(
-        "| {:^10} | {:^22} |",
-        "Base 10",
-        format!("Base {}", target_base)
-    );
-
-    println!("|{:-<12}|{:-<24}|", ":", ":");
-
-    for i in 0..target_base_numbers.len() {
-        println!(
-            "| {:<7} | {:<22} |",
-            format!("{:.1$}", f64_numbers[i], MAX_DIGITS as usize),
-            target_base_numbers[i]
-        );
-    }
+/// * `options` - The settings for this run.
+/// * `out` - Where the results are written.
+/// * `headers` - The titles of the table columns, one for every representation.
+/// * `expand` - Converts one input, and the fraction it was written as if any,
+///   into its representation for every target base, with the settings it
+///   is given, which are those of an input with a digit limit of its own
+///   for such an input. The digits of every representation are then
+///   separated by the `--separator`, if any.
+fn present<R: Representation + Send>(
+    options: &Options,
+    out: &mut dyn Write,
+    headers: &[String],
+    expand: impl Fn(&Options, f64, Option<&Fraction>) -> Vec<R> + Sync,
+) -> io::Result<()> {
+    present_batched(options, out, headers, |options, inputs| {
+        inputs
+            .iter()
+            .map(|&(num, fraction)| expand(options, num, fraction))
+            .collect()
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use hamcrest2::prelude::*;
+/// The settings of every distinct digit limit the inputs were given with,
+/// as in `0.1:20`: those of the run with that digit limit instead.
+fn limited_options(options: &Options) -> Vec<(u32, Options)> {
+    let mut limits: Vec<u32> = options.digit_limits.iter().flatten().copied().collect();
+    limits.sort_unstable();
+    limits.dedup();
+    limits
+        .into_iter()
+        .map(|digits| {
+            let pipeline = Pipeline {
+                digits,
+                ..options.pipeline.clone()
+            };
+            (
+                digits,
+                Options {
+                    pipeline,
+                    ..options.clone()
+                },
+            )
+        })
+        .collect()
+}
 
-    #[test]
-    fn test_conversion_base_2() {
-        assert_that!(convert_from_decimal_to_binary(0.5, 2), equal_to("0.1;"));
-        assert_that!(convert_from_decimal_to_binary(0.25, 2), equal_to("0.0;1;"));
-        assert_that!(convert_from_decimal_to_binary(0.75, 2), equal_to("0.1;1;"));
-        assert_that!(
-            convert_from_decimal_to_binary(0.125, 2),
-            equal_to("0.0;0;1;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.6875, 2),
-            equal_to("0.1;0;1;1;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.7, 2),
-            equal_to("0.1;0;1;1;0;0;1;1;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.8, 2),
-            equal_to("0.1;1;0;0;1;1;0;0;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.9, 2),
-            equal_to("0.1;1;1;0;0;1;1;0;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.6, 2),
-            equal_to("0.1;0;0;1;1;0;0;1;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.3, 2),
-            equal_to("0.0;1;0;0;1;1;0;0;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.1, 2),
-            equal_to("0.0;0;0;1;1;0;0;1;")
-        );
+/// The settings an input with the given digit limit, if any, is converted
+/// with, out of those of [`limited_options`].
+fn row_options<'a>(
+    options: &'a Options,
+    limited: &'a [(u32, Options)],
+    limit: Option<u32>,
+) -> &'a Options {
+    limit
+        .and_then(|limit| limited.iter().find(|(digits, _)| *digits == limit))
+        .map_or(options, |(_, limited)| limited)
+}
+
+/// Like [`present`], but converts the inputs `BATCH_SIZE` at a time with
+/// `expand`, which gives the representations of every input of a batch, so
+/// that a kernel can work on several inputs at once.
+fn present_batched<R: Representation + Send>(
+    options: &Options,
+    out: &mut dyn Write,
+    headers: &[String],
+    expand: impl Fn(&Options, &[(f64, Option<&Fraction>)]) -> Vec<Vec<R>> + Sync,
+) -> io::Result<()> {
+    let separator = options.separator.as_deref();
+    let expand =
+        |options: &Options, inputs: &[(f64, Option<&Fraction>)]| -> Vec<Vec<Separated<R>>> {
+            expand(options, inputs)
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|representation| Separated {
+                            representation,
+                            separator,
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+    if !options.stream_from.is_empty() {
+        let expand_one =
+            |num, fraction: Option<&Fraction>| expand(options, &[(num, fraction)]).remove(0);
+        return present_streamed(options, out, headers, &expand_one);
     }
 
-    #[test]
-    fn test_conversion_base_8() {
-        assert_that!(convert_from_decimal_to_binary(0.5, 8), equal_to("0.4;"));
-        assert_that!(convert_from_decimal_to_binary(0.25, 8), equal_to("0.2;"));
-        assert_that!(convert_from_decimal_to_binary(0.75, 8), equal_to("0.6;"));
-        assert_that!(
-            convert_from_decimal_to_binary(0.8, 8),
-            equal_to("0.6;3;1;4;6;3;1;4;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.16666, 8),
-            equal_to("0.1;2;5;2;5;0;7;2;")
-        );
+    // Once the run is cancelled, the input being converted is dropped along
+    // with the rest, leaving the results of those converted before.
+    let cancelled = || options.pipeline.cancel.is_cancelled();
+    let count = options.f64_numbers.len();
+    let limited = limited_options(options);
+    let expand_range = |start: usize| {
+        let end = (start + BATCH_SIZE).min(count);
+        let inputs: Vec<(f64, Option<&Fraction>)> = (start..end)
+            .map(|i| (options.f64_numbers[i], options.fractions[i].as_ref()))
+            .collect();
+        let limits = &options.digit_limits[start..end];
+        let rows: Vec<Vec<Separated<R>>> = if limits.iter().all(Option::is_none) {
+            expand(options, &inputs)
+        } else {
+            // The inputs with a digit limit of their own, as in `0.1:20`,
+            // are converted one at a time with the settings of that limit.
+            inputs
+                .iter()
+                .zip(limits)
+                .map(|(input, &limit)| {
+                    let row_options = row_options(options, &limited, limit);
+                    expand(row_options, std::slice::from_ref(input)).remove(0)
+                })
+                .collect()
+        };
+        (start..end).zip(rows)
+    };
+    let inputs = (0..count)
+        .step_by(BATCH_SIZE)
+        .flat_map(expand_range)
+        .map(|(i, row_expansions)| {
+            (
+                options.f64_numbers[i],
+                &options.fractions[i],
+                options.counts[i],
+                options.labels.get(i).and_then(Option::as_deref),
+                row_expansions,
+            )
+        })
+        .take_while(|_| !cancelled());
+
+    if options.summary_only {
+        let progress = Progress::new(count, options.quiet);
+        let mut summary = Summary::new(&options.pipeline.target_bases);
+        for (num, _, _, _, row_expansions) in inputs {
+            summary.add(num, &row_expansions);
+            progress.tick();
+        }
+        progress.finish();
+        return display_summary(out, &summary);
     }
 
-    #[test]
-    fn test_conversion_base_16() {
-        assert_that!(convert_from_decimal_to_binary(0.5, 16), equal_to("0.8;"));
-        assert_that!(convert_from_decimal_to_binary(0.25, 16), equal_to("0.4;"));
-        assert_that!(convert_from_decimal_to_binary(0.75, 16), equal_to("0.12;"));
-        assert_that!(
-            convert_from_decimal_to_binary(0.8, 16),
-            equal_to("0.12;12;12;12;12;12;12;12;")
-        );
-        assert_that!(
-            convert_from_decimal_to_binary(0.16666, 16),
-            equal_to("0.2;10;10;10;3;10;13;1;")
-        );
+    if options.explain {
+        display_explanation(out, &options.pipeline, &options.f64_numbers)?;
     }
 
-    #[test]
-    fn test_conversion_base_60() {
-        assert_that!(convert_from_decimal_to_binary(0.5, 60), equal_to("0.30;"));
-        assert_that!(convert_from_decimal_to_binary(0.25, 60), equal_to("0.15;"));
-        assert_that!(convert_from_decimal_to_binary(0.75, 60), equal_to("0.45;"));
-        assert_that!(convert_from_decimal_to_binary(0.8, 60), equal_to("0.48;"));
-        assert_that!(
-            convert_from_decimal_to_binary(0.16666, 60),
-            equal_to("0.9;59;58;33;36;0;0;0;")
-        );
+    // Every buffered representation holds up to a digit per place.
+    let row_bytes = headers.len() * (size_of::<R>() + options.pipeline.digits as usize * 4);
+    if must_stream(options.max_memory, row_bytes.saturating_mul(count)) {
+        display_header(out, headers, options.columns, &options.styles)?;
+        let mut buffer = RowBuffer::default();
+        let mut footer = Footer::default();
+        for (num, fraction, count, label, row_expansions) in inputs {
+            footer.add(&row_expansions);
+            display_row(
+                out,
+                &mut buffer,
+                headers,
+                (num, fraction.as_ref(), count, label),
+                &row_expansions,
+                options.columns,
+                &options.styles,
+            )?;
+        }
+        display_table_end(out, headers, options.columns, &options.styles)?;
+        if options.footer {
+            display_footer(out, &footer, options.invalid_inputs.len())?;
+        }
+        return Ok(());
+    }
+
+    // Large batches are converted on several threads, still in input order.
+    let starts: Vec<usize> = (0..count).step_by(BATCH_SIZE).collect();
+    let workers = worker_count(options.jobs, count);
+    let progress = Progress::new(count, options.quiet);
+    let expansions: Vec<Vec<Separated<R>>> = parallel_map(&starts, workers, |&start| {
+        let rows: Vec<Vec<Separated<R>>> = expand_range(start)
+            .map(|(_, row_expansions)| {
+                progress.tick();
+                row_expansions
+            })
+            .collect();
+        (!cancelled()).then_some(rows)
+    })
+    .into_iter()
+    .map_while(|rows| rows)
+    .flatten()
+    .collect();
+    progress.finish();
+
+    display(
+        out,
+        headers,
+        (
+            &options.f64_numbers[..expansions.len()],
+            &options.fractions[..expansions.len()],
+            &options.counts[..expansions.len()],
+            &options.labels,
+        ),
+        &expansions,
+        options.columns,
+        &options.styles,
+    )?;
+    if options.footer {
+        let mut footer = Footer::default();
+        for row_expansions in &expansions {
+            footer.add(row_expansions);
+        }
+        display_footer(out, &footer, options.invalid_inputs.len())?;
     }
+    Ok(())
 }
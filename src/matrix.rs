@@ -0,0 +1,186 @@
+use std::io::{self, Write};
+use std::mem::size_of;
+
+use crate::aggregate::Record;
+use crate::conversion::Conversion;
+use crate::convert::largest_digit;
+use crate::format::OutputFormat;
+use crate::memory::must_stream;
+use crate::parallel::{parallel_map, worker_count};
+use crate::pipeline::Pipeline;
+
+/// The header line of the long-format dataset written by `--matrix-options`.
+pub const MATRIX_HEADER: &str = "input,base,precision,digit_count,exact,representation";
+
+/// A generous guess at the bytes of a row besides its digits: the input, the
+/// base, the precision, the digit count and the exactness.
+const ROW_OVERHEAD: usize = 64;
+
+/// Crosses every input with every target base and precision, producing one
+/// CSV row per combination.
+///
+/// # Arguments
+///
+/// * `pipeline` - The stages every input is run through. Its digit limit is
+///   replaced by each precision in turn.
+/// * `precisions` - The digit limits to try.
+/// * `f64_numbers` - The decoded input values.
+///
+/// # Returns
+///
+/// The CSV rows (without the header), ordered by input, then base, then precision.
+///
+/// # Example
+/// ```
+/// let rows = matrix_rows(&Pipeline::with_bases(vec![2.0]), &[1, 8], &[0.75]);
+/// assert_eq!(rows, vec!["0.75,2,1,1,false,0.1;", "0.75,2,8,2,true,0.1;1;"]);
+/// ```
+pub fn matrix_rows(pipeline: &Pipeline, precisions: &[u32], f64_numbers: &[f64]) -> Vec<String> {
+    let mut rows = Vec::new();
+    for_each_matrix_record(pipeline, precisions, f64_numbers, |record| {
+        rows.push(record.to_csv());
+        Ok(())
+    })
+    .expect("collecting rows cannot fail");
+    rows
+}
+
+/// Produces the records of `matrix_rows` one at a time, handing each to
+/// `emit` and stopping at the first error it returns.
+fn for_each_matrix_record(
+    pipeline: &Pipeline,
+    precisions: &[u32],
+    f64_numbers: &[f64],
+    mut emit: impl FnMut(Record) -> io::Result<()>,
+) -> io::Result<()> {
+    for &number in f64_numbers {
+        for &target_base in &pipeline.target_bases {
+            for &precision in precisions {
+                let stage = Pipeline {
+                    digits: precision,
+                    ..pipeline.clone()
+                };
+                let expansion = stage.expand_to(number, target_base);
+                if pipeline.cancel.is_cancelled() {
+                    return Ok(());
+                }
+                let record = Record {
+                    conversion: Conversion::new(number, target_base, &expansion),
+                    precision: Some(precision),
+                };
+                emit(record)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Estimates how many bytes the rows of `matrix_rows` take up, assuming every
+/// expansion runs to its digit limit with digits as wide as they can be.
+///
+/// # Arguments
+///
+/// * `pipeline` - Supplies the target bases.
+/// * `precisions` - The digit limits to try.
+/// * `input_count` - The number of inputs.
+pub fn matrix_bytes(pipeline: &Pipeline, precisions: &[u32], input_count: usize) -> usize {
+    let per_input: usize = pipeline
+        .target_bases
+        .iter()
+        .map(|&target_base| {
+            let digit_width = largest_digit(target_base).to_string().len() + 1;
+            precisions
+                .iter()
+                .map(|&precision| {
+                    size_of::<String>() + ROW_OVERHEAD + precision as usize * digit_width
+                })
+                .sum::<usize>()
+        })
+        .sum();
+    per_input.saturating_mul(input_count)
+}
+
+/// Prints the long-format dataset of every input, base and precision
+/// combination as CSV, ready to be loaded into R or pandas, or with
+/// `--format jsonl` as one JSON object per row.
+///
+/// # Arguments
+///
+/// * `out` - Where the dataset is written.
+/// * `format` - How every row is written: `Csv` and `Table` as CSV after
+///   the `MATRIX_HEADER`, and `JsonLines` as NDJSON.
+/// * `pipeline` - The stages every input is run through.
+/// * `precisions` - The digit limits to try.
+/// * `f64_numbers` - The decoded input values.
+/// * `max_memory` - The cap given with `--max-memory`, if any. When the rows
+///   would not fit under it, every row is printed as soon as it is made
+///   instead of after all of them are.
+/// * `jobs` - The number of threads to make the rows of many inputs on, or
+///   `None` for one per core.
+pub fn display_matrix(
+    out: &mut dyn Write,
+    format: OutputFormat,
+    pipeline: &Pipeline,
+    precisions: &[u32],
+    f64_numbers: &[f64],
+    max_memory: Option<usize>,
+    jobs: Option<usize>,
+) -> io::Result<()> {
+    let row = |record: &Record| match format {
+        #[cfg(feature = "serde")]
+        OutputFormat::JsonLines => record.to_json(),
+        _ => record.to_csv(),
+    };
+    if format != OutputFormat::JsonLines {
+        writeln!(out, "{}", MATRIX_HEADER)?;
+    }
+    if must_stream(
+        max_memory,
+        matrix_bytes(pipeline, precisions, f64_numbers.len()),
+    ) {
+        return for_each_matrix_record(pipeline, precisions, f64_numbers, |record| {
+            writeln!(out, "{}", row(&record))
+        });
+    }
+    let workers = worker_count(jobs, f64_numbers.len());
+    let rows = parallel_map(f64_numbers, workers, |&number| {
+        let mut rows = Vec::new();
+        for_each_matrix_record(pipeline, precisions, &[number], |record| {
+            rows.push(row(&record));
+            Ok(())
+        })
+        .map(|()| rows)
+    });
+    for row in rows {
+        for row in row? {
+            writeln!(out, "{}", row)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_matrix_crosses_every_combination() {
+        let rows = matrix_rows(&Pipeline::with_bases(vec![2.0, 8.0]), &[2, 4], &[0.5, 0.1]);
+        assert_that!(rows.len(), equal_to(8));
+        assert_that!(&rows[0], equal_to("0.5,2,2,1,true,0.1;"));
+        assert_that!(&rows[3], equal_to("0.5,8,4,1,true,0.4;"));
+        assert_that!(&rows[5], equal_to("0.1,2,4,4,false,0.0;0;0;1;"));
+        assert_that!(&rows[6], equal_to("0.1,8,2,2,false,0.0;6;"));
+    }
+
+    #[test]
+    fn test_matrix_bytes_cover_the_rows() {
+        let pipeline = Pipeline::with_bases(vec![2.0, 60.0]);
+        let numbers = [0.1, 1.0 / 7.0, 0.3];
+        let rows = matrix_rows(&pipeline, &[8, 32], &numbers);
+        let bytes: usize = rows.iter().map(|row| size_of::<String>() + row.len()).sum();
+        assert!(matrix_bytes(&pipeline, &[8, 32], numbers.len()) >= bytes);
+        assert_that!(matrix_bytes(&pipeline, &[8], 0), equal_to(0));
+    }
+}
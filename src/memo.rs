@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::backend::Backend;
+use crate::convert::{Expansion, Rounding};
+use crate::rational::Fraction;
+
+/// The most conversions an `ExpansionCache` holds. Once it is full, inputs
+/// not in it yet are converted every time they are given.
+pub const CACHE_CAPACITY: usize = 4096;
+
+/// Everything the digits of a fixed-precision conversion depend on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// The bits of the `f64` converted.
+    pub bits: u64,
+    /// The exact fraction the input was written as, if any.
+    pub fraction: Option<Fraction>,
+    /// The base converted to.
+    pub target_base: u64,
+    /// The maximum number of fractional digits.
+    pub digits: u32,
+    /// How the last digit is settled.
+    pub rounding: Rounding,
+    /// The arithmetic the digits are extracted with.
+    pub backend: Backend,
+}
+
+/// The expansions a batch has converted so far, so that an input given many
+/// times, as in sensor dumps full of repeated readings, goes through the
+/// digit loop only once. Clones share the same expansions, so that every
+/// thread of a batch finds those of the others.
+#[derive(Clone, Default)]
+pub struct ExpansionCache(Arc<Mutex<HashMap<CacheKey, Expansion>>>);
+
+impl ExpansionCache {
+    /// Creates an empty cache.
+    pub fn new() -> ExpansionCache {
+        ExpansionCache::default()
+    }
+
+    /// Looks up the expansion converted for a key, or converts it with
+    /// `convert` and keeps it if there is still room.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - What the expansion depends on.
+    /// * `convert` - Converts the input, and says whether its expansion may
+    ///   be kept, which one cut short by a cancelled run may not.
+    pub fn get_or_convert(
+        &self,
+        key: CacheKey,
+        convert: impl FnOnce() -> (Expansion, bool),
+    ) -> Expansion {
+        if let Some(expansion) = self
+            .0
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&key).cloned())
+        {
+            return expansion;
+        }
+        // The lock is not held while converting, so that the other threads
+        // go on; two of them may then convert the same input at once.
+        let (expansion, keep) = convert();
+        if let Ok(mut cache) = self.0.lock() {
+            if keep && cache.len() < CACHE_CAPACITY {
+                cache.insert(key, expansion.clone());
+            }
+        }
+        expansion
+    }
+}
+
+impl fmt::Debug for ExpansionCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let entries = self.0.lock().map_or(0, |cache| cache.len());
+        write!(f, "ExpansionCache({} entries)", entries)
+    }
+}
+
+impl PartialEq for ExpansionCache {
+    /// A cache only saves work, and never changes an expansion, so two
+    /// pipelines with the same settings are equal whatever they cached.
+    fn eq(&self, _: &ExpansionCache) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::cell::Cell;
+
+    fn key(bits: u64) -> CacheKey {
+        CacheKey {
+            bits,
+            fraction: None,
+            target_base: 2,
+            digits: 8,
+            rounding: Rounding::Truncate,
+            backend: Backend::F64,
+        }
+    }
+
+    #[test]
+    fn test_expansion_cache() {
+        let cache = ExpansionCache::new();
+        let converted = Cell::new(0);
+        let convert = |value: f64, keep: bool| {
+            let converted = &converted;
+            move || {
+                converted.set(converted.get() + 1);
+                (Backend::F64.expand(value, None, 2, 8), keep)
+            }
+        };
+        let half = cache.get_or_convert(key(0.5f64.to_bits()), convert(0.5, true));
+        let again = cache
+            .clone()
+            .get_or_convert(key(0.5f64.to_bits()), convert(0.5, true));
+        assert_that!(&again, equal_to(&half));
+        assert_that!(converted.get(), equal_to(1));
+
+        // An expansion that is not to be kept is converted every time.
+        cache.get_or_convert(key(0.25f64.to_bits()), convert(0.25, false));
+        cache.get_or_convert(key(0.25f64.to_bits()), convert(0.25, false));
+        assert_that!(converted.get(), equal_to(3));
+        assert_that!(cache.0.lock().unwrap().len(), equal_to(1));
+    }
+}
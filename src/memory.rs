@@ -0,0 +1,53 @@
+/// Parses a byte count for `--max-memory`, such as `4096`, `1_000_000` or
+/// `64M`. The suffixes `K`, `M` and `G` stand for KiB, MiB and GiB.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(parse_byte_count("64K"), Ok(65536));
+/// ```
+pub fn parse_byte_count(value: &str) -> Result<usize, String> {
+    let digits = value.trim().replace('_', "");
+    let (digits, unit) = match digits.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&digits[..digits.len() - 1], 1 << 10),
+        Some('M') => (&digits[..digits.len() - 1], 1 << 20),
+        Some('G') => (&digits[..digits.len() - 1], 1 << 30),
+        _ => (&digits[..], 1),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|count| count.checked_mul(unit))
+        .ok_or_else(|| format!("invalid byte count `{}`", value))
+}
+
+/// Decides whether results taking up about `bytes` may be held in memory
+/// until the run is done, or must be written out as soon as they are made.
+///
+/// # Arguments
+///
+/// * `max_memory` - The cap given with `--max-memory`, if any.
+/// * `bytes` - The estimated size of all the buffered results.
+pub fn must_stream(max_memory: Option<usize>, bytes: usize) -> bool {
+    max_memory.is_some_and(|cap| bytes > cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_byte_counts() {
+        assert_that!(parse_byte_count("4096"), equal_to(Ok(4096)));
+        assert_that!(parse_byte_count("1_000"), equal_to(Ok(1000)));
+        assert_that!(parse_byte_count("2m"), equal_to(Ok(2 << 20)));
+        assert_that!(parse_byte_count("1G"), equal_to(Ok(1 << 30)));
+        assert!(parse_byte_count("lots").is_err());
+        assert!(parse_byte_count("K").is_err());
+
+        assert!(!must_stream(None, usize::MAX));
+        assert!(!must_stream(Some(100), 100));
+        assert!(must_stream(Some(100), 101));
+    }
+}
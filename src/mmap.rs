@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+
+use crate::comment::comment_start;
+use crate::stream::STDIN_PATH;
+
+/// The pages may be read.
+#[cfg(unix)]
+const PROT_READ: i32 = 1;
+/// Changes to the pages are not written back to the file.
+#[cfg(unix)]
+const MAP_PRIVATE: i32 = 2;
+
+#[cfg(unix)]
+extern "C" {
+    /// Maps `length` bytes of the file `fd` into memory, returning `!0` on
+    /// failure.
+    fn mmap(
+        address: *mut u8,
+        length: usize,
+        protection: i32,
+        flags: i32,
+        fd: i32,
+        offset: i64,
+    ) -> *mut u8;
+    /// Unmaps the pages a call to `mmap` mapped.
+    fn munmap(address: *mut u8, length: usize) -> i32;
+}
+
+/// The bytes of an `--input` file read with `--mmap`: the file mapped into
+/// memory, so that its inputs are decoded where they lie instead of being
+/// copied into a string first, or, for standard input and on platforms
+/// other than Unix, the bytes read into memory.
+#[derive(Debug)]
+pub struct Mapped {
+    /// Where the bytes are.
+    bytes: Backing,
+}
+
+/// The memory a `Mapped` reads its bytes from.
+#[derive(Debug)]
+enum Backing {
+    /// Pages mapped from a file, unmapped when dropped.
+    #[cfg(unix)]
+    Map { address: *mut u8, length: usize },
+    /// Bytes read into memory.
+    Read(Vec<u8>),
+}
+
+impl Mapped {
+    /// Maps a file into memory, or reads standard input for `-`.
+    ///
+    /// # Returns
+    ///
+    /// The mapped bytes, or the error that kept the file from being opened
+    /// or mapped.
+    pub fn open(path: &str) -> io::Result<Mapped> {
+        if path == STDIN_PATH {
+            let mut bytes = Vec::new();
+            io::stdin().read_to_end(&mut bytes)?;
+            return Ok(Mapped {
+                bytes: Backing::Read(bytes),
+            });
+        }
+        let file = File::open(path)?;
+        let length = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "file too large to map"))?;
+        // An empty file cannot be mapped, and holds no inputs anyway.
+        if length == 0 {
+            return Ok(Mapped {
+                bytes: Backing::Read(Vec::new()),
+            });
+        }
+        Mapped::map(file, length, path)
+    }
+
+    /// Maps the `length` bytes of an open file.
+    #[cfg(unix)]
+    fn map(file: File, length: usize, _path: &str) -> io::Result<Mapped> {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: the file is open for reading and `length` is its size;
+        // the mapping is private and read-only, and outlives the file
+        // descriptor, which may be closed once it is made.
+        let address = unsafe {
+            mmap(
+                std::ptr::null_mut(),
+                length,
+                PROT_READ,
+                MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if address as usize == !0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapped {
+            bytes: Backing::Map { address, length },
+        })
+    }
+
+    /// Reads the file, since there is no `mmap` to map it with.
+    #[cfg(not(unix))]
+    fn map(_file: File, _length: usize, path: &str) -> io::Result<Mapped> {
+        Ok(Mapped {
+            bytes: Backing::Read(std::fs::read(path)?),
+        })
+    }
+
+    /// The inputs in the bytes, separated by ASCII whitespace, leaving out
+    /// comments. An input that is not valid UTF-8 is given as `�`, which no
+    /// decoder reads.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.split(|&byte| byte == b'\n')
+            .flat_map(|line| line[..comment_start(line)].split(u8::is_ascii_whitespace))
+            .filter(|word| !word.is_empty())
+            .map(|word| std::str::from_utf8(word).unwrap_or("\u{fffd}"))
+    }
+}
+
+impl Deref for Mapped {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.bytes {
+            // SAFETY: the pages stay mapped until `self` is dropped, and
+            // nothing in this program writes to them.
+            #[cfg(unix)]
+            Backing::Map { address, length } => unsafe {
+                std::slice::from_raw_parts(*address, *length)
+            },
+            Backing::Read(bytes) => bytes,
+        }
+    }
+}
+
+impl Drop for Mapped {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Backing::Map { address, length } = self.bytes {
+            // SAFETY: the pages were mapped by `mmap` with this length, and
+            // no slice of them outlives `self`.
+            unsafe {
+                munmap(address, length);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::{env, fs};
+
+    #[test]
+    fn test_mapped_words() {
+        let path = env::temp_dir().join(format!("mmap-{}", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        fs::write(&path, b"0.25 1/3\n\n\t0.5 \xff\n").unwrap();
+        let mapped = Mapped::open(&path).unwrap();
+        assert_that!(mapped.to_vec(), equal_to(fs::read(&path).unwrap()));
+        assert_that!(
+            mapped.words().collect::<Vec<_>>(),
+            equal_to(vec!["0.25", "1/3", "0.5", "\u{fffd}"])
+        );
+
+        fs::write(&path, "").unwrap();
+        assert_that!(Mapped::open(&path).unwrap().words().count(), equal_to(0));
+        fs::remove_file(&path).unwrap();
+        assert!(Mapped::open(&path).is_err());
+    }
+}
@@ -0,0 +1,459 @@
+use std::fmt::{self, Display};
+
+use serde::ser::{self, Serialize};
+
+/// Why a value could not be written as MessagePack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsgpackError(String);
+
+impl Display for MsgpackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for MsgpackError {}
+
+impl ser::Error for MsgpackError {
+    fn custom<T: Display>(message: T) -> MsgpackError {
+        MsgpackError(message.to_string())
+    }
+}
+
+/// Writes a value as MessagePack, such as a `Conversion` as a map of its five
+/// fields, 52 bytes for `0.5` in base 2 against 67 as JSON.
+///
+/// Integers take the smallest encoding that holds them, floats are always
+/// written as 64-bit, and enum variants with data as a map of the variant
+/// name to the data, as JSON writes them.
+///
+/// # Returns
+///
+/// The bytes, or an error for a sequence or map whose length is not known
+/// up front, which MessagePack writes before the elements.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(to_msgpack(&vec![1, -1]).unwrap(), vec![0x92, 0x01, 0xff]);
+/// ```
+pub fn to_msgpack<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, MsgpackError> {
+    let mut serializer = MsgpackSerializer { output: Vec::new() };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+/// A serde serializer writing MessagePack into a buffer.
+struct MsgpackSerializer {
+    /// The bytes written so far.
+    output: Vec<u8>,
+}
+
+impl MsgpackSerializer {
+    /// Writes a marker byte followed by a big-endian value.
+    fn marked(&mut self, marker: u8, bytes: &[u8]) {
+        self.output.push(marker);
+        self.output.extend_from_slice(bytes);
+    }
+
+    /// Writes the header of a string, binary, array or map of some length,
+    /// in the smallest form that holds the length.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of bytes, elements or entries that follow.
+    /// * `fixed` - The marker of the fixed form, which holds the length in its
+    ///   low bits, and the smallest length too large for it, or `None` if
+    ///   the type has no fixed form.
+    /// * `markers` - The markers of the 8-, 16- and 32-bit forms, or `None`
+    ///   for a form the type does not have.
+    fn header(
+        &mut self,
+        len: usize,
+        fixed: Option<(u8, usize)>,
+        markers: [Option<u8>; 3],
+    ) -> Result<(), MsgpackError> {
+        match (fixed, markers) {
+            (Some((marker, limit)), _) if len < limit => self.output.push(marker | len as u8),
+            (_, [Some(marker), _, _]) if len <= u8::MAX as usize => {
+                self.marked(marker, &[len as u8])
+            }
+            (_, [_, Some(marker), _]) if len <= u16::MAX as usize => {
+                self.marked(marker, &(len as u16).to_be_bytes())
+            }
+            (_, [_, _, Some(marker)]) if len <= u32::MAX as usize => {
+                self.marked(marker, &(len as u32).to_be_bytes())
+            }
+            _ => return Err(MsgpackError(format!("{} elements do not fit", len))),
+        }
+        Ok(())
+    }
+
+    /// Writes the header of an array of `len` elements.
+    fn array(&mut self, len: usize) -> Result<(), MsgpackError> {
+        self.header(len, Some((0x90, 16)), [None, Some(0xdc), Some(0xdd)])
+    }
+
+    /// Writes the header of a map of `len` entries.
+    fn map(&mut self, len: usize) -> Result<(), MsgpackError> {
+        self.header(len, Some((0x80, 16)), [None, Some(0xde), Some(0xdf)])
+    }
+
+    /// Writes the single-entry map header that opens an enum variant with
+    /// data, followed by the name of the variant as its key.
+    fn variant(&mut self, variant: &str) -> Result<(), MsgpackError> {
+        self.map(1)?;
+        ser::Serializer::serialize_str(self, variant)
+    }
+}
+
+/// The error for a sequence or map whose length serde does not give.
+fn unknown_length() -> MsgpackError {
+    MsgpackError("MessagePack needs the length of every sequence and map up front".to_string())
+}
+
+impl ser::Serializer for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value: bool) -> Result<(), MsgpackError> {
+        self.output.push(if value { 0xc3 } else { 0xc2 });
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), MsgpackError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), MsgpackError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), MsgpackError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), MsgpackError> {
+        if value >= 0 {
+            return self.serialize_u64(value as u64);
+        }
+        if value >= -32 {
+            self.output.push(value as u8);
+        } else if value >= i8::MIN as i64 {
+            self.marked(0xd0, &(value as i8).to_be_bytes());
+        } else if value >= i16::MIN as i64 {
+            self.marked(0xd1, &(value as i16).to_be_bytes());
+        } else if value >= i32::MIN as i64 {
+            self.marked(0xd2, &(value as i32).to_be_bytes());
+        } else {
+            self.marked(0xd3, &value.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), MsgpackError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), MsgpackError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), MsgpackError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), MsgpackError> {
+        if value < 0x80 {
+            self.output.push(value as u8);
+        } else if value <= u8::MAX as u64 {
+            self.marked(0xcc, &[value as u8]);
+        } else if value <= u16::MAX as u64 {
+            self.marked(0xcd, &(value as u16).to_be_bytes());
+        } else if value <= u32::MAX as u64 {
+            self.marked(0xce, &(value as u32).to_be_bytes());
+        } else {
+            self.marked(0xcf, &value.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), MsgpackError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), MsgpackError> {
+        self.marked(0xcb, &value.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), MsgpackError> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), MsgpackError> {
+        self.header(
+            value.len(),
+            Some((0xa0, 32)),
+            [Some(0xd9), Some(0xda), Some(0xdb)],
+        )?;
+        self.output.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), MsgpackError> {
+        self.header(value.len(), None, [Some(0xc4), Some(0xc5), Some(0xc6)])?;
+        self.output.extend_from_slice(value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), MsgpackError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), MsgpackError> {
+        self.output.push(0xc0);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), MsgpackError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), MsgpackError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), MsgpackError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), MsgpackError> {
+        self.variant(variant)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, MsgpackError> {
+        self.array(len.ok_or_else(unknown_length)?)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, MsgpackError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self, MsgpackError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, MsgpackError> {
+        self.variant(variant)?;
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, MsgpackError> {
+        self.map(len.ok_or_else(unknown_length)?)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self, MsgpackError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, MsgpackError> {
+        self.variant(variant)?;
+        self.serialize_map(Some(len))
+    }
+}
+
+impl ser::SerializeSeq for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), MsgpackError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MsgpackError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), MsgpackError> {
+        ser::Serializer::serialize_str(&mut **self, key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut MsgpackSerializer {
+    type Ok = ();
+    type Error = MsgpackError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), MsgpackError> {
+        ser::Serializer::serialize_str(&mut **self, key)?;
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), MsgpackError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::Conversion;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_conversion_as_msgpack() {
+        let conversion = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+        let mut expected = vec![0x85, 0xa5];
+        expected.extend(b"input");
+        expected.push(0xcb);
+        expected.extend(0.5f64.to_be_bytes());
+        expected.push(0xa4);
+        expected.extend(b"base");
+        expected.extend([0xa1, b'2', 0xa6]);
+        expected.extend(b"digits");
+        expected.extend([0x01, 0xa9]);
+        expected.extend(b"truncated");
+        expected.extend([0xc2, 0xa4]);
+        expected.extend(b"repr");
+        expected.push(0xa4);
+        expected.extend(b"0.1;");
+        assert_that!(to_msgpack(&conversion).unwrap(), equal_to(expected));
+    }
+
+    #[test]
+    fn test_msgpack_values() {
+        assert_that!(to_msgpack(&-33i64).unwrap(), equal_to(vec![0xd0, 0xdf]));
+        assert_that!(
+            to_msgpack(&300u32).unwrap(),
+            equal_to(vec![0xcd, 0x01, 0x2c])
+        );
+        assert_that!(
+            to_msgpack(&vec![Some(true), None]).unwrap(),
+            equal_to(vec![0x92, 0xc3, 0xc0])
+        );
+        let long = "x".repeat(40);
+        let mut expected = vec![0xd9, 40];
+        expected.extend(long.as_bytes());
+        assert_that!(to_msgpack(&long).unwrap(), equal_to(expected));
+        assert_that!(
+            to_msgpack(&vec![0u8; 20]).unwrap()[..3].to_vec(),
+            equal_to(vec![0xdc, 0, 20])
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::fmt;
+
+use crate::convert::{Expansion, Representation};
+use crate::subscript::subscript;
+
+/// How the digits of an expansion are written out, separately from how they
+/// are computed, so that a new notation only needs its own formatter.
+///
+/// An expansion is laid out as its sign, the prefix, the integer digits
+/// with the separator between them (or the zero when there are none),
+/// a `.`, every fractional digit followed by the separator, and the suffix.
+pub trait DigitFormatter: Sync {
+    /// Writes what comes between the sign and the first digit.
+    fn prefix(&self, _f: &mut dyn fmt::Write, _expansion: &Expansion) -> fmt::Result {
+        Ok(())
+    }
+
+    /// Writes one digit.
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result;
+
+    /// Writes the integer part of an expansion that has no integer digits.
+    fn zero(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        self.digit(f, 0)
+    }
+
+    /// The text between two integer digits and after every fractional digit.
+    fn separator(&self) -> &str;
+
+    /// Writes what comes after the last digit.
+    fn suffix(&self, _f: &mut dyn fmt::Write, _expansion: &Expansion) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Writes an expansion with a formatter into any sink, such as a formatter
+/// or a reused `String`.
+///
+/// # Example
+///
+/// ```
+/// let mut text = String::new();
+/// write_expansion(&mut text, &expand(0.625, 2, 8), &Semicolon::default()).unwrap();
+/// assert_eq!(text, "0.1;0;1;");
+/// ```
+pub fn write_expansion<F: DigitFormatter + ?Sized>(
+    f: &mut impl fmt::Write,
+    expansion: &Expansion,
+    formatter: &F,
+) -> fmt::Result {
+    if expansion.negative {
+        f.write_str("-")?;
+    }
+    formatter.prefix(f, expansion)?;
+    if expansion.integer_digits.is_empty() {
+        formatter.zero(f)?;
+    }
+    for (i, &digit) in expansion.integer_digits.iter().enumerate() {
+        if i > 0 {
+            f.write_str(formatter.separator())?;
+        }
+        formatter.digit(f, digit)?;
+    }
+    f.write_str(".")?;
+    for &digit in &expansion.digits {
+        formatter.digit(f, digit)?;
+        f.write_str(formatter.separator())?;
+    }
+    formatter.suffix(f, expansion)
+}
+
+/// The usual notation, `0.1;0;1;`, with every digit in base 10 and
+/// optionally zero-padded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Semicolon {
+    /// The width every digit is zero-padded to, or 0 for no padding.
+    pub width: usize,
+}
+
+impl DigitFormatter for Semicolon {
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result {
+        write!(f, "{:01$}", digit, self.width)
+    }
+
+    /// Writes a lone `0`, which is never padded.
+    fn zero(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_str("0")
+    }
+
+    fn separator(&self) -> &str {
+        ";"
+    }
+}
+
+/// Digits as the characters `0`–`9` and `A`–`Z` without separators, as in
+/// `0.1A3` in base 16, for bases up to 36. Larger digits are written in
+/// base 10 between brackets, as in `0.[40]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alphanumeric;
+
+impl DigitFormatter for Alphanumeric {
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result {
+        match char::from_digit(digit, 36) {
+            Some(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            None => write!(f, "[{}]", digit),
+        }
+    }
+
+    fn separator(&self) -> &str {
+        ""
+    }
+}
+
+/// The usual notation followed by the base as a subscript, as in
+/// `0.1;0;1;₂`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscript;
+
+impl DigitFormatter for Subscript {
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result {
+        Semicolon::default().digit(f, digit)
+    }
+
+    fn separator(&self) -> &str {
+        ";"
+    }
+
+    fn suffix(&self, f: &mut dyn fmt::Write, expansion: &Expansion) -> fmt::Result {
+        if expansion.base.fract() == 0.0 {
+            f.write_str(&subscript(expansion.base as i64))?;
+        }
+        Ok(())
+    }
+}
+
+/// The ones signs of the cuneiform numerals, for 1 to 9.
+const CUNEIFORM_ONES: [char; 9] = [
+    '\u{12079}',
+    '\u{1222B}',
+    '\u{12408}',
+    '\u{12409}',
+    '\u{1240A}',
+    '\u{1240B}',
+    '\u{1240C}',
+    '\u{1240D}',
+    '\u{1240E}',
+];
+
+/// The tens signs of the cuneiform numerals, for 10 to 50.
+const CUNEIFORM_TENS: [char; 5] = [
+    '\u{1230B}',
+    '\u{12399}',
+    '\u{1230D}',
+    '\u{1240F}',
+    '\u{12410}',
+];
+
+/// The placeholder later Babylonian scribes wrote for an empty place.
+const CUNEIFORM_ZERO: char = '\u{12472}';
+
+/// Babylonian cuneiform, for base 60: every sexagesimal digit is its tens
+/// sign followed by its ones sign, as in `𒌋𒐊` for 15, with a space between
+/// the digits and the placeholder `𒑲` for a zero digit. Digits of 60 and
+/// above, which other bases give, are written in base 10 between brackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cuneiform;
+
+impl DigitFormatter for Cuneiform {
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result {
+        if digit >= 60 {
+            return write!(f, "[{}]", digit);
+        }
+        if digit == 0 {
+            return f.write_char(CUNEIFORM_ZERO);
+        }
+        let (tens, ones) = (digit / 10, digit % 10);
+        if tens > 0 {
+            f.write_char(CUNEIFORM_TENS[tens as usize - 1])?;
+        }
+        if ones > 0 {
+            f.write_char(CUNEIFORM_ONES[ones as usize - 1])?;
+        }
+        Ok(())
+    }
+
+    fn zero(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_char(CUNEIFORM_ZERO)
+    }
+
+    fn separator(&self) -> &str {
+        " "
+    }
+}
+
+/// Maya dot-and-bar numerals, for base 20: every vigesimal digit is the
+/// Unicode Maya numeral for it, such as `𝋭` for 13, with a space between
+/// the digits. The ASCII form writes a digit as its bars of five, `|`,
+/// followed by its dots of one, `o`, as in `||ooo` for 13, and a zero as
+/// the shell `@`. Digits of 20 and above, which other bases give, are
+/// written in base 10 between brackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Maya {
+    /// Whether to draw the digits in ASCII instead of with the Unicode
+    /// numerals, for terminals whose fonts lack them.
+    pub ascii: bool,
+}
+
+impl DigitFormatter for Maya {
+    fn digit(&self, f: &mut dyn fmt::Write, digit: u32) -> fmt::Result {
+        if digit >= 20 {
+            return write!(f, "[{}]", digit);
+        }
+        if !self.ascii {
+            return f.write_char(char::from_u32(0x1D2E0 + digit).unwrap_or('?'));
+        }
+        if digit == 0 {
+            return f.write_char('@');
+        }
+        write!(
+            f,
+            "{}{}",
+            "|".repeat(digit as usize / 5),
+            "o".repeat(digit as usize % 5)
+        )
+    }
+
+    fn zero(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        self.digit(f, 0)
+    }
+
+    fn separator(&self) -> &str {
+        " "
+    }
+}
+
+/// The notations `--notation` can pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// [`Semicolon`], the default.
+    Semicolon,
+    /// [`Alphanumeric`].
+    Alphanumeric,
+    /// [`Subscript`].
+    Subscript,
+    /// [`Cuneiform`].
+    Cuneiform,
+    /// [`Maya`] with the Unicode numerals.
+    Maya,
+    /// [`Maya`] drawn in ASCII.
+    MayaAscii,
+}
+
+impl Notation {
+    /// Looks up a notation by its command-line name.
+    pub fn from_name(name: &str) -> Option<Notation> {
+        match name {
+            "semicolon" => Some(Notation::Semicolon),
+            "alphanumeric" => Some(Notation::Alphanumeric),
+            "subscript" => Some(Notation::Subscript),
+            "cuneiform" => Some(Notation::Cuneiform),
+            "maya" => Some(Notation::Maya),
+            "maya-ascii" => Some(Notation::MayaAscii),
+            _ => None,
+        }
+    }
+
+    /// The formatter that writes this notation.
+    pub fn formatter(self) -> &'static dyn DigitFormatter {
+        match self {
+            Notation::Semicolon => &Semicolon { width: 0 },
+            Notation::Alphanumeric => &Alphanumeric,
+            Notation::Subscript => &Subscript,
+            Notation::Cuneiform => &Cuneiform,
+            Notation::Maya => &Maya { ascii: false },
+            Notation::MayaAscii => &Maya { ascii: true },
+        }
+    }
+}
+
+/// Parses the value of `--notation`.
+pub fn parse_notation(value: &str) -> Result<Notation, String> {
+    Notation::from_name(value).ok_or_else(|| {
+        format!(
+            "unknown notation `{}`, expected semicolon, alphanumeric, subscript, cuneiform, maya \
+             or maya-ascii",
+            value
+        )
+    })
+}
+
+/// An expansion written with the formatter of a notation.
+#[derive(Clone)]
+pub struct Notated {
+    /// The expansion shown.
+    pub expansion: Expansion,
+    /// How its digits are written.
+    pub formatter: &'static dyn DigitFormatter,
+}
+
+impl Representation for Notated {
+    fn value(&self) -> f64 {
+        self.expansion.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.expansion.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.expansion.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.expansion.positional_digits()
+    }
+}
+
+impl fmt::Display for Notated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_expansion(f, &self.expansion, self.formatter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::{expand, expand_negabase};
+    use hamcrest2::prelude::*;
+
+    fn notate(expansion: Expansion, notation: Notation) -> String {
+        Notated {
+            expansion,
+            formatter: notation.formatter(),
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn test_notations() {
+        let expansion = expand(0.6875, 16, 8);
+        assert_that!(
+            notate(expansion.clone(), Notation::Semicolon),
+            equal_to(expansion.to_string())
+        );
+        assert_that!(
+            notate(expand(0.6875, 16, 8), Notation::Alphanumeric),
+            equal_to("0.B")
+        );
+        assert_that!(
+            notate(expand(-0.5, 2, 8), Notation::Subscript),
+            equal_to("-0.1;₂")
+        );
+        assert_that!(
+            notate(expand_negabase(0.5, 2, 8), Notation::Alphanumeric),
+            equal_to("1.1")
+        );
+        assert_that!(
+            notate(expand(40.0 / 64.0, 64, 8), Notation::Alphanumeric),
+            equal_to("0.[40]")
+        );
+        assert_that!(
+            notate(expand(0.25, 60, 8), Notation::Cuneiform),
+            equal_to("\u{12472}.\u{1230B}\u{1240A} ")
+        );
+        let expansion = Expansion {
+            integer_digits: vec![1, 20],
+            digits: vec![0, 30, 59, 64],
+            ..expand(0.0, 60, 8)
+        };
+        assert_that!(
+            notate(expansion, Notation::Cuneiform),
+            equal_to("\u{12079} \u{12399}.\u{12472} \u{1230D} \u{12410}\u{1240E} [64] ")
+        );
+        let expansion = Expansion {
+            integer_digits: vec![13],
+            digits: vec![0, 19, 25],
+            ..expand(0.0, 20, 8)
+        };
+        assert_that!(
+            notate(expansion.clone(), Notation::Maya),
+            equal_to("\u{1D2ED}.\u{1D2E0} \u{1D2F3} [25] ")
+        );
+        assert_that!(
+            notate(expansion, Notation::MayaAscii),
+            equal_to("||ooo.@ |||oooo [25] ")
+        );
+        assert_that!(
+            notate(expand(0.25, 20, 8), Notation::MayaAscii),
+            equal_to("@.| ")
+        );
+        assert!(parse_notation("roman").is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+
+use crate::checksum::Checksum;
+use crate::sign::SigningKey;
+
+/// How many rotated files `--rotate-size` keeps besides the current one.
+pub const ROTATED_FILES: u32 = 5;
+
+/// Where the results of a conversion are written.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OutputOptions {
+    /// The file given with `--output`, or `None` for standard output.
+    pub path: Option<String>,
+    /// The directory given with `--output-dir`, which gets a results file
+    /// for every `--format` instead.
+    pub dir: Option<String>,
+    /// Whether to add to the end of an existing file instead of replacing it.
+    pub append: bool,
+    /// The size in bytes from `--rotate-size` at which the file is moved
+    /// aside and a new one started, if any.
+    pub rotate_size: Option<u64>,
+    /// Whether an existing file may be replaced, from `--force`.
+    pub force: bool,
+    /// Whether an existing file is kept as `<path>.bak` before it is
+    /// replaced, from `--backup`.
+    pub backup: bool,
+    /// Where the SHA-256 of the results goes, from `--checksum`, if anywhere.
+    pub checksum: Option<Checksum>,
+    /// The key from `--sign-key` that signs the file once it is written.
+    pub sign_key: Option<SigningKey>,
+    /// Whether the results are also put on the system clipboard, from
+    /// `--copy`.
+    pub copy: bool,
+}
+
+/// A results file that is rotated once it grows past a size.
+///
+/// The file is only ever rotated between two lines, so it can grow past the
+/// size by the length of one line. On rotation, the file becomes `<path>.1`,
+/// an older `<path>.1` becomes `<path>.2`, and so on up to `ROTATED_FILES`.
+#[derive(Debug)]
+pub struct OutputFile {
+    /// The path of the file currently written to.
+    path: String,
+    /// The file currently written to.
+    file: File,
+    /// The size of the current file.
+    written: u64,
+    /// The size at which the file is rotated, if any.
+    rotate_size: Option<u64>,
+    /// Whether the last byte written ended a line.
+    at_line_start: bool,
+}
+
+/// The path `--backup` moves an existing results file to.
+pub fn backup_path(path: &str) -> String {
+    format!("{}.bak", path)
+}
+
+/// The file `--output-dir` writes the results of one format to, such as
+/// `out/results.json`.
+pub fn results_path(dir: &str, extension: &str) -> String {
+    Path::new(dir)
+        .join(format!("results.{}", extension))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The file `--output-dir` writes the results of one `--file` input to in
+/// one format, named after it, such as `out/week1.json` for `week1.txt`.
+/// A file found by `--glob` under `root` keeps its place below it, as in
+/// `out/week1/a.json` for `data/week1/a.txt` under `data/`.
+pub fn section_path(dir: &str, input_path: &str, root: Option<&str>, extension: &str) -> String {
+    if let Some(relative) = root.and_then(|root| input_path.strip_prefix(root)) {
+        return Path::new(dir)
+            .join(relative)
+            .with_extension(extension)
+            .to_string_lossy()
+            .into_owned();
+    }
+    let stem = Path::new(input_path)
+        .file_stem()
+        .map_or_else(|| input_path.into(), |stem| stem.to_string_lossy());
+    Path::new(dir)
+        .join(format!("{}.{}", stem, extension))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The path a results file is moved to on its `n`-th rotation.
+pub fn rotated_path(path: &str, n: u32) -> String {
+    format!("{}.{}", path, n)
+}
+
+impl OutputFile {
+    /// Opens a results file, creating it if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write to.
+    /// * `append` - Whether to keep what the file already holds.
+    /// * `rotate_size` - The size at which to rotate the file, if any.
+    pub fn open(path: &str, append: bool, rotate_size: Option<u64>) -> io::Result<OutputFile> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        let written = file.metadata()?.len();
+        Ok(OutputFile {
+            path: path.to_string(),
+            file,
+            written,
+            rotate_size,
+            at_line_start: true,
+        })
+    }
+
+    /// Moves the current file and the older rotated files one place along,
+    /// dropping the oldest, and starts a new empty file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for n in (1..ROTATED_FILES).rev() {
+            let older = rotated_path(&self.path, n);
+            if Path::new(&older).exists() {
+                fs::rename(&older, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let full = self.rotate_size.is_some_and(|size| self.written >= size);
+        if full && self.at_line_start && !buf.is_empty() {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        if written > 0 {
+            self.at_line_start = buf[written - 1] == b'\n';
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Opens where the results of a conversion go: the `--output` file, or
+/// standard output without one.
+///
+/// An existing file is only replaced with `--force`, or with `--backup`,
+/// which first moves it to `<path>.bak`. Appending to it is always allowed.
+///
+/// # Returns
+///
+/// The writer, or a message describing why the file cannot be opened.
+pub fn open_output(options: &OutputOptions) -> Result<Box<dyn Write>, String> {
+    let path = match &options.path {
+        None => return Ok(Box::new(io::stdout().lock())),
+        Some(path) => path,
+    };
+
+    if !options.append && Path::new(path).exists() {
+        if options.backup {
+            fs::rename(path, backup_path(path))
+                .map_err(|error| format!("cannot back up `{}`: {}", path, error))?;
+        } else if !options.force {
+            return Err(format!(
+                "`{}` already exists; use --force to overwrite it or --backup to keep a copy",
+                path
+            ));
+        }
+    }
+
+    OutputFile::open(path, options.append, options.rotate_size)
+        .map(|file| Box::new(LineWriter::new(file)) as Box<dyn Write>)
+        .map_err(|error| format!("cannot open `{}`: {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::env;
+
+    /// A path in the temporary directory that no other test uses.
+    fn temporary_path(name: &str) -> String {
+        let path = env::temp_dir().join(format!("output-{}-{}", std::process::id(), name));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_append_keeps_earlier_results() {
+        let path = temporary_path("append");
+        for append in [false, true] {
+            let mut out = open_output(&OutputOptions {
+                path: Some(path.clone()),
+                append,
+                ..OutputOptions::default()
+            })
+            .unwrap();
+            writeln!(out, "run").unwrap();
+        }
+        assert_that!(fs::read_to_string(&path).unwrap(), equal_to("run\nrun\n"));
+
+        let mut out = open_output(&OutputOptions {
+            path: Some(path.clone()),
+            force: true,
+            ..OutputOptions::default()
+        })
+        .unwrap();
+        writeln!(out, "replaced").unwrap();
+        drop(out);
+        assert_that!(fs::read_to_string(&path).unwrap(), equal_to("replaced\n"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_existing_files_are_protected() {
+        let path = temporary_path("protect");
+        fs::write(&path, "graded\n").unwrap();
+        let options = OutputOptions {
+            path: Some(path.clone()),
+            ..OutputOptions::default()
+        };
+        assert!(open_output(&options).is_err());
+        assert_that!(fs::read_to_string(&path).unwrap(), equal_to("graded\n"));
+
+        let mut out = open_output(&OutputOptions {
+            backup: true,
+            ..options
+        })
+        .unwrap();
+        writeln!(out, "new").unwrap();
+        drop(out);
+        assert_that!(fs::read_to_string(&path).unwrap(), equal_to("new\n"));
+        assert_that!(
+            fs::read_to_string(backup_path(&path)).unwrap(),
+            equal_to("graded\n")
+        );
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(backup_path(&path)).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_between_lines() {
+        let path = temporary_path("rotate");
+        let mut out = OutputFile::open(&path, false, Some(10)).unwrap();
+        for line in 0..(ROTATED_FILES + 3) {
+            write!(out, "line ").unwrap();
+            writeln!(out, "{:05}", line).unwrap();
+        }
+        drop(out);
+
+        assert_that!(fs::read_to_string(&path).unwrap(), equal_to("line 00007\n"));
+        assert_that!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            equal_to("line 00006\n")
+        );
+        assert_that!(
+            fs::read_to_string(rotated_path(&path, ROTATED_FILES)).unwrap(),
+            equal_to("line 00002\n")
+        );
+        assert!(!Path::new(&rotated_path(&path, ROTATED_FILES + 1)).exists());
+
+        fs::remove_file(&path).unwrap();
+        for n in 1..=ROTATED_FILES {
+            fs::remove_file(rotated_path(&path, n)).unwrap();
+        }
+    }
+}
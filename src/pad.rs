@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::convert::{Expansion, Representation};
+
+/// How wide `--pad-digits` makes every digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadWidth {
+    /// As wide as the largest digit of the base, e.g. 2 for base 60 and 3
+    /// for base 256.
+    Auto,
+    /// A fixed number of characters.
+    Fixed(usize),
+}
+
+impl PadWidth {
+    /// Parses the value of `--pad-digits`: `auto` or a positive width.
+    pub fn parse(value: &str) -> Option<PadWidth> {
+        match value {
+            "auto" => Some(PadWidth::Auto),
+            _ => value
+                .parse::<usize>()
+                .ok()
+                .filter(|&width| width > 0)
+                .map(PadWidth::Fixed),
+        }
+    }
+
+    /// The width of the digits of an expansion in `base`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(PadWidth::Auto.width(60.0), 2);
+    /// assert_eq!(PadWidth::Fixed(4).width(60.0), 4);
+    /// ```
+    pub fn width(&self, base: f64) -> usize {
+        match *self {
+            PadWidth::Auto => (base.abs().ceil() as u64 - 1).max(1).to_string().len(),
+            PadWidth::Fixed(width) => width,
+        }
+    }
+}
+
+/// An expansion whose digits are zero-padded to one width, so that the
+/// digits of every row of a table line up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaddedExpansion {
+    /// The expansion shown.
+    pub expansion: Expansion,
+    /// The width of every digit.
+    pub width: usize,
+}
+
+impl PaddedExpansion {
+    /// Pads the digits of an expansion as `pad` asks for its base.
+    pub fn new(expansion: Expansion, pad: PadWidth) -> PaddedExpansion {
+        let width = pad.width(expansion.base);
+        PaddedExpansion { expansion, width }
+    }
+}
+
+impl Representation for PaddedExpansion {
+    fn value(&self) -> f64 {
+        self.expansion.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.expansion.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.expansion.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.expansion.positional_digits()
+    }
+}
+
+impl fmt::Display for PaddedExpansion {
+    /// Writes the expansion as `0.dd;dd;dd;`, e.g. `0.09;59;58;` in base 60.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.expansion.write_digits(f, self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_pad_widths() {
+        assert_that!(PadWidth::Auto.width(2.0), equal_to(1));
+        assert_that!(PadWidth::Auto.width(10.0), equal_to(1));
+        assert_that!(PadWidth::Auto.width(16.0), equal_to(2));
+        assert_that!(PadWidth::Auto.width(256.0), equal_to(3));
+        assert_that!(PadWidth::parse("3"), equal_to(Some(PadWidth::Fixed(3))));
+        assert_that!(PadWidth::parse("auto"), equal_to(Some(PadWidth::Auto)));
+        assert_that!(PadWidth::parse("0"), equal_to(None));
+    }
+
+    #[test]
+    fn test_padded_digits() {
+        // 0.25 is 15/60 and 0.1 is 6/60, so both end after one digit.
+        let padded = |value| PaddedExpansion::new(expand(value, 60, 8), PadWidth::Auto);
+        assert_that!(padded(0.25).to_string(), equal_to("0.15;"));
+        assert_that!(padded(0.1).to_string(), equal_to("0.06;"));
+        let wide = PaddedExpansion::new(expand(0.5, 2, 8), PadWidth::Fixed(3));
+        assert_that!(wide.to_string(), equal_to("0.001;"));
+        assert_that!(wide.value(), equal_to(0.5));
+    }
+}
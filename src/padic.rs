@@ -0,0 +1,256 @@
+use std::fmt;
+use std::io::{self, Write};
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{ToPrimitive, Zero};
+
+use crate::cli::flag_value;
+use crate::find::parse_digits;
+use crate::rational::{Fraction, Ratio};
+
+/// The number of digits `padic` computes when no `--digits` is given.
+pub const DEFAULT_DIGITS: u32 = 10;
+
+/// The p-adic expansion `…d2 d1 d0.d-1` of a rational number, which goes on
+/// forever to the left unless the number is a non-negative integer (or such
+/// an integer over a power of p).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PAdic {
+    /// The prime p.
+    pub prime: u32,
+    /// The p-adic valuation: the place of the lowest non-zero digit.
+    pub valuation: i64,
+    /// The digits from the place `valuation` upwards, least significant
+    /// first.
+    pub digits: Vec<u32>,
+    /// Whether the digits are all of them, rather than cut off.
+    pub complete: bool,
+}
+
+/// Divides every factor of `prime` out of a number.
+///
+/// # Returns
+///
+/// What is left and the number of factors divided out.
+fn strip_factors(mut number: BigUint, prime: u32) -> (BigUint, i64) {
+    let mut count = 0;
+    while !number.is_zero() && (&number % prime).is_zero() {
+        number /= prime;
+        count += 1;
+    }
+    (number, count)
+}
+
+/// Computes the p-adic expansion of an exact rational.
+///
+/// The valuation v is found by dividing the powers of p out of both parts,
+/// which leaves `u/w` with neither divisible by p. Every digit is then
+/// `u · w⁻¹ mod p`, and `u` becomes `(u - digit · w) / p`, which divides
+/// exactly. The expansion ends when `u` reaches zero, which happens only for
+/// non-negative integers over a power of p; the rest repeat forever.
+///
+/// # Arguments
+///
+/// * `ratio` - The number to expand.
+/// * `prime` - The prime p.
+/// * `max_digits` - The largest number of digits to produce.
+///
+/// # Example
+///
+/// ```
+/// let third = Ratio::from(Fraction::parse("1/3").unwrap());
+/// assert_eq!(padic(&third, 5, 5).to_string(), "…1;3;1;3;2");
+/// ```
+pub fn padic(ratio: &Ratio, prime: u32, max_digits: u32) -> PAdic {
+    let (numerator, above) = strip_factors(ratio.numerator.clone(), prime);
+    let (denominator, below) = strip_factors(ratio.denominator.clone(), prime);
+    let mut expansion = PAdic {
+        prime,
+        valuation: above - below,
+        digits: Vec::new(),
+        complete: true,
+    };
+    if numerator.is_zero() {
+        expansion.valuation = 0;
+        return expansion;
+    }
+
+    let p = BigInt::from(prime);
+    let w = BigInt::from(denominator);
+    // p is prime, so w^(p-2) is the inverse of w modulo p.
+    let inverse = w.modpow(&BigInt::from(prime - 2), &p);
+    let sign = if ratio.negative {
+        Sign::Minus
+    } else {
+        Sign::Plus
+    };
+    let mut u = BigInt::from_biguint(sign, numerator);
+    while !u.is_zero() && expansion.digits.len() < max_digits as usize {
+        let residue = ((&u % &p) + &p) % &p;
+        let digit = (residue * &inverse) % &p;
+        u = (u - &digit * &w) / &p;
+        expansion.digits.push(digit.to_u32().unwrap_or(0));
+    }
+    expansion.complete = u.is_zero();
+    expansion
+}
+
+impl fmt::Display for PAdic {
+    /// Writes the digits most significant first, as in `…2;2;2.3`, starting
+    /// with `…` when they go on, and with the places between the lowest
+    /// non-zero digit and the point written as zeros.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.digits.is_empty() && self.complete {
+            return write!(f, "0");
+        }
+        if !self.complete {
+            write!(f, "…")?;
+        }
+        let digit_at = |place: i64| match place - self.valuation {
+            index if index >= 0 => self.digits.get(index as usize).copied().unwrap_or(0),
+            _ => 0,
+        };
+        let highest = (self.valuation + self.digits.len() as i64 - 1).max(0);
+        for place in (self.valuation.min(0)..=highest).rev() {
+            if place == -1 {
+                write!(f, ".")?;
+            } else if place < highest {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", digit_at(place))?;
+        }
+        Ok(())
+    }
+}
+
+/// The settings of the `padic` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PadicOptions {
+    /// The prime p.
+    pub prime: u32,
+    /// The largest number of digits to compute for every input.
+    pub digits: u32,
+    /// Every input as typed, with its exact value.
+    pub inputs: Vec<(String, Ratio)>,
+}
+
+/// Whether a number is prime, by trial division.
+fn is_prime(number: u32) -> bool {
+    number >= 2
+        && (2..)
+            .take_while(|factor: &u32| factor.saturating_mul(*factor) <= number)
+            .all(|factor| !number.is_multiple_of(factor))
+}
+
+/// Parses the arguments of the `padic` subcommand.
+///
+/// Inputs are fractions such as `1/3`, which are expanded as written, or
+/// decimals, whose exact stored `f64` value is expanded.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `padic`.
+///
+/// # Returns
+///
+/// The parsed `PadicOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- padic --prime 5 --digits 8 1/3 -1
+/// ```
+pub fn parse_padic_args(args: &[String]) -> Result<PadicOptions, String> {
+    let mut prime = None;
+    let mut digits = DEFAULT_DIGITS;
+    let mut inputs = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--prime", &mut iter)? {
+            prime = Some(
+                value
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|&prime| is_prime(prime))
+                    .ok_or_else(|| format!("`{}` is not a prime", value))?,
+            );
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = parse_digits(value)?;
+        } else {
+            let ratio = match Fraction::parse(arg) {
+                Some(fraction) => Some(Ratio::from(fraction)),
+                None => arg.parse::<f64>().ok().and_then(Ratio::from_f64),
+            };
+            let ratio = ratio.ok_or_else(|| format!("unknown padic argument `{}`", arg))?;
+            inputs.push((arg.clone(), ratio));
+        }
+    }
+
+    Ok(PadicOptions {
+        prime: prime.ok_or("padic requires --prime followed by a prime")?,
+        digits,
+        inputs,
+    })
+}
+
+/// Runs the `padic` subcommand, printing the p-adic expansion of every input.
+///
+/// # Example
+/// ```
+/// // cargo run -- padic --prime 5 1/3 -1
+/// ```
+/// Output:
+/// 1/3 = …3;1;3;1;3;1;3;1;3;2 (5-adic)
+/// -1 = …4;4;4;4;4;4;4;4;4;4 (5-adic)
+pub fn display_padic(out: &mut impl Write, options: &PadicOptions) -> io::Result<()> {
+    for (input, ratio) in &options.inputs {
+        writeln!(
+            out,
+            "{} = {} ({}-adic)",
+            input,
+            padic(ratio, options.prime, options.digits),
+            options.prime
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn expand(input: &str, prime: &str, digits: u32) -> String {
+        let args = ["--prime", prime, input].map(String::from);
+        let options = parse_padic_args(&args).unwrap();
+        padic(&options.inputs[0].1, options.prime, digits).to_string()
+    }
+
+    #[test]
+    fn test_padic_expansions() {
+        assert_that!(expand("1/3", "5", 5), equal_to("…1;3;1;3;2"));
+        assert_that!(expand("-1", "5", 4), equal_to("…4;4;4;4"));
+        assert_that!(expand("1/10", "5", 4), equal_to("…2;2;2.3"));
+        assert_that!(expand("25", "5", 4), equal_to("1;0;0"));
+        assert_that!(expand("1/25", "5", 4), equal_to("0.0;1"));
+        assert_that!(expand("6", "2", 10), equal_to("1;1;0"));
+        assert_that!(expand("0", "7", 10), equal_to("0"));
+        assert_that!(expand("0.5", "2", 10), equal_to("0.1"));
+        // 1/3 is …01011 in the 2-adics, and -2/3 is twice its negation.
+        assert_that!(expand("1/3", "2", 6), equal_to("…1;0;1;0;1;1"));
+        assert_that!(expand("-2/3", "2", 6), equal_to("…0;1;0;1;0;1;0"));
+    }
+
+    #[test]
+    fn test_padic_arguments() {
+        let args =
+            |line: &str| -> Vec<String> { line.split_whitespace().map(String::from).collect() };
+        let options = parse_padic_args(&args("--prime 7 --digits 3 1/2 0.5")).unwrap();
+        assert_that!(options.digits, equal_to(3));
+        assert_that!(options.inputs.len(), equal_to(2));
+        assert!(parse_padic_args(&args("1/3")).is_err());
+        assert!(parse_padic_args(&args("--prime 6 1/3")).is_err());
+        assert!(parse_padic_args(&args("--prime 1 1/3")).is_err());
+        assert!(parse_padic_args(&args("--prime 5 pi")).is_err());
+    }
+}
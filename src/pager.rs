@@ -0,0 +1,301 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+/// The height assumed for `--paging always` on a terminal whose height
+/// cannot be found.
+const DEFAULT_HEIGHT: usize = 24;
+
+/// When the results written to a terminal go through a pager, from
+/// `--paging`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Paging {
+    /// Page the results once they are taller than the terminal.
+    Auto,
+    /// Page the results even when they fit on the terminal.
+    Always,
+    /// Write the results straight to the terminal (the default).
+    #[default]
+    Never,
+}
+
+impl Paging {
+    /// Parses the value of `--paging`.
+    pub fn from_name(name: &str) -> Option<Paging> {
+        match name {
+            "auto" => Some(Paging::Auto),
+            "always" => Some(Paging::Always),
+            "never" => Some(Paging::Never),
+            _ => None,
+        }
+    }
+}
+
+/// Standard output on a terminal, held back until it is known to need a
+/// pager: the `$PAGER` command, which gets the results as they are written
+/// once they fill the terminal, or without one the built-in pager, which
+/// shows them a screen at a time once they are all written.
+#[derive(Debug)]
+pub struct Pager {
+    /// How many lines must be written before the results are paged.
+    threshold: usize,
+    /// The height of the terminal.
+    height: usize,
+    /// The results written so far, while no `$PAGER` runs.
+    buffer: Vec<u8>,
+    /// How many lines `buffer` holds.
+    lines: usize,
+    /// The `$PAGER` running, once the results are paged through it.
+    child: Option<Child>,
+    /// Whether `$PAGER` was quit before the results were all written, which
+    /// drops the rest.
+    quit: bool,
+}
+
+impl Pager {
+    /// Starts paging standard output, or returns `None` when it is not a
+    /// terminal or `paging` is [`Paging::Never`], or with [`Paging::Auto`]
+    /// when the height of the terminal cannot be found.
+    pub fn start(paging: Paging) -> Option<Pager> {
+        if paging == Paging::Never || !io::stdout().is_terminal() {
+            return None;
+        }
+        let height = match (terminal_height(), paging) {
+            (Some(height), _) => height,
+            (None, Paging::Always) => DEFAULT_HEIGHT,
+            (None, _) => return None,
+        };
+        Some(Pager {
+            // The built-in pager keeps a line for its prompt.
+            threshold: if paging == Paging::Always { 1 } else { height },
+            height,
+            buffer: Vec::new(),
+            lines: 0,
+            child: None,
+            quit: false,
+        })
+    }
+
+    /// Shows the last of the results: waits for `$PAGER` to be quit, or
+    /// pages the held back results with the built-in pager when they need
+    /// it, or else writes them to standard output.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(mut child) = self.child.take() {
+            drop(child.stdin.take());
+            child.wait()?;
+            return Ok(());
+        }
+        let mut stdout = io::stdout().lock();
+        if self.lines < self.threshold {
+            stdout.write_all(&self.buffer)?;
+            return stdout.flush();
+        }
+        let text = String::from_utf8_lossy(&self.buffer);
+        match open_keyboard() {
+            Some(mut keys) => page(&text, self.height, &mut keys, &mut stdout),
+            None => {
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()
+            }
+        }
+    }
+
+    /// Starts `$PAGER`, if it is set, and hands it the results held back.
+    fn spawn(&mut self) -> io::Result<()> {
+        let Some(command) = env::var("PAGER")
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+        else {
+            return Ok(());
+        };
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        shell.arg(&command).stdin(Stdio::piped());
+        // As git does, let `less` quit by itself on results that fit and
+        // keep their colors.
+        if env::var_os("LESS").is_none() {
+            shell.env("LESS", "FRX");
+        }
+        // A pager that cannot be started leaves the built-in one.
+        let Ok(child) = shell.spawn() else {
+            return Ok(());
+        };
+        let held = std::mem::take(&mut self.buffer);
+        self.child = Some(child);
+        self.write_to_child(&held)?;
+        Ok(())
+    }
+
+    /// Writes to `$PAGER`, dropping what it no longer reads once quit.
+    fn write_to_child(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(stdin) = self.child.as_mut().and_then(|child| child.stdin.as_mut()) else {
+            return Ok(());
+        };
+        if self.quit {
+            return Ok(());
+        }
+        match stdin.write_all(bytes) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+                self.quit = true;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.child.is_some() {
+            self.write_to_child(bytes)?;
+            return Ok(bytes.len());
+        }
+        self.buffer.extend_from_slice(bytes);
+        let was_short = self.lines < self.threshold;
+        self.lines += bytes.iter().filter(|&&byte| byte == b'\n').count();
+        if was_short && self.lines >= self.threshold {
+            self.spawn()?;
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.quit {
+            return Ok(());
+        }
+        match self.child.as_mut().and_then(|child| child.stdin.as_mut()) {
+            Some(stdin) => stdin.flush().or_else(|error| match error.kind() {
+                io::ErrorKind::BrokenPipe => Ok(()),
+                _ => Err(error),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The number of lines of the terminal, from `$LINES` or `stty size`.
+fn terminal_height() -> Option<usize> {
+    if let Some(lines) = env::var("LINES").ok().and_then(|lines| lines.parse().ok()) {
+        return Some(lines).filter(|&lines| lines > 0);
+    }
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(File::open("/dev/tty").ok()?)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let size = String::from_utf8(output.stdout).ok()?;
+    size.split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+        .filter(|&lines| lines > 0)
+}
+
+/// Where the keys of the built-in pager are read: the terminal itself, as
+/// standard input may be where the inputs came from.
+fn open_keyboard() -> Option<Box<dyn BufRead>> {
+    if let Ok(tty) = File::open("/dev/tty") {
+        return Some(Box::new(BufReader::new(tty)));
+    }
+    io::stdin()
+        .is_terminal()
+        .then(|| Box::new(io::stdin().lock()) as Box<dyn BufRead>)
+}
+
+/// Shows `text` a screen at a time, as the built-in pager does: Enter shows
+/// the next screen, and `q` or the end of the keys stops.
+///
+/// # Arguments
+///
+/// * `text` - The results to show.
+/// * `height` - The number of lines of the terminal, one of which is kept
+///   for the prompt.
+/// * `keys` - The lines typed at the prompt.
+/// * `out` - The terminal.
+pub fn page(
+    text: &str,
+    height: usize,
+    keys: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let screen = height.saturating_sub(1).max(1);
+    let mut shown = 0;
+    while shown < lines.len() {
+        let end = (shown + screen).min(lines.len());
+        for line in &lines[shown..end] {
+            out.write_all(line.as_bytes())?;
+        }
+        shown = end;
+        if shown == lines.len() {
+            break;
+        }
+        write!(
+            out,
+            "-- {} of {} lines; Enter for more, q to quit -- ",
+            shown,
+            lines.len()
+        )?;
+        out.flush()?;
+        let mut answer = String::new();
+        let read = keys.read_line(&mut answer)?;
+        // The typed line moved down past the prompt, which is cleared.
+        write!(out, "\x1b[1A\x1b[2K\r")?;
+        if read == 0 || answer.trim() == "q" {
+            break;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_paging_modes() {
+        assert_that!(Paging::from_name("auto"), equal_to(Some(Paging::Auto)));
+        assert_that!(Paging::from_name("always"), equal_to(Some(Paging::Always)));
+        assert_that!(Paging::from_name("never"), equal_to(Some(Paging::Never)));
+        assert_that!(Paging::from_name("less"), equal_to(None));
+        assert_that!(Paging::default(), equal_to(Paging::Never));
+    }
+
+    #[test]
+    fn test_page() {
+        let text: String = (1..=7).map(|line| format!("{}\n", line)).collect();
+        let mut out = Vec::new();
+        page(&text, 4, &mut "\n".as_bytes(), &mut out).unwrap();
+        let prompt = |shown| {
+            format!(
+                "-- {} of 7 lines; Enter for more, q to quit -- \x1b[1A\x1b[2K\r",
+                shown
+            )
+        };
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(format!("1\n2\n3\n{}4\n5\n6\n{}", prompt(3), prompt(6)))
+        );
+
+        let mut out = Vec::new();
+        page(&text, 4, &mut "q\n\n".as_bytes(), &mut out).unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(format!("1\n2\n3\n{}", prompt(3)))
+        );
+
+        let mut out = Vec::new();
+        page("1\n2\n", 4, &mut "".as_bytes(), &mut out).unwrap();
+        assert_that!(out, equal_to(b"1\n2\n".to_vec()));
+    }
+}
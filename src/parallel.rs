@@ -0,0 +1,80 @@
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// The fewest inputs a batch needs before it is converted on several
+/// threads, below which starting them costs more than it saves.
+pub const PARALLEL_THRESHOLD: usize = 1000;
+
+/// The number of threads a batch of `items` inputs is converted on: the
+/// `--jobs` count if given, or one per core, and a single thread for small
+/// batches.
+pub fn worker_count(jobs: Option<usize>, items: usize) -> usize {
+    if items < PARALLEL_THRESHOLD {
+        return 1;
+    }
+    jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+}
+
+/// The number of threads the `--file` inputs are converted on: the `--jobs`
+/// count if given, or one per core, but no more than there are files.
+pub fn file_worker_count(jobs: Option<usize>, files: usize) -> usize {
+    jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+        .min(files)
+}
+
+/// Applies `f` to every item on up to `workers` threads, each taking one
+/// contiguous share of the items, and returns the results in the order of
+/// the items.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(parallel_map(&[1, 2, 3], 2, |n| n * 10), vec![10, 20, 30]);
+/// ```
+pub fn parallel_map<T: Sync, U: Send>(
+    items: &[T],
+    workers: usize,
+    f: impl Fn(&T) -> U + Sync,
+) -> Vec<U> {
+    if workers <= 1 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let share = items.len().div_ceil(workers);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(share)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<U>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("a conversion thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let items: Vec<u32> = (0..1001).collect();
+        let squares = parallel_map(&items, 4, |&n| n * n);
+        assert_that!(squares.len(), equal_to(1001));
+        assert!(squares
+            .iter()
+            .enumerate()
+            .all(|(i, &square)| square == (i * i) as u32));
+        assert_that!(parallel_map(&items[..1], 4, |&n| n), equal_to(vec![0]));
+    }
+
+    #[test]
+    fn test_worker_count() {
+        assert_that!(worker_count(Some(8), 10), equal_to(1));
+        assert_that!(worker_count(Some(8), PARALLEL_THRESHOLD), equal_to(8));
+        assert!(worker_count(None, PARALLEL_THRESHOLD) >= 1);
+    }
+}
@@ -0,0 +1,444 @@
+use std::io::{self, Write};
+
+use crate::conversion::Conversion;
+
+/// The bytes a Parquet file starts and ends with.
+const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+
+/// The rows held in memory and written as one row group, so that files of
+/// millions of rows are written as they are converted.
+const ROW_GROUP_ROWS: usize = 1 << 16;
+
+/// The rows of every data page of a column chunk, which keeps a page of
+/// long representations well within the 2 GiB a page header can give.
+const PAGE_ROWS: usize = 1 << 13;
+
+/// The physical types of the columns.
+const BOOLEAN: i32 = 0;
+const INT64: i32 = 2;
+const DOUBLE: i32 = 5;
+const BYTE_ARRAY: i32 = 6;
+
+/// The encodings of the pages: the values are `PLAIN`, and the levels,
+/// which required columns have none of, `RLE`.
+const PLAIN: i32 = 0;
+const RLE: i32 = 3;
+
+/// The types of the fields of the Thrift compact protocol the metadata is
+/// written in.
+const I32_FIELD: u8 = 5;
+const I64_FIELD: u8 = 6;
+const BINARY_FIELD: u8 = 8;
+const LIST_FIELD: u8 = 9;
+const STRUCT_FIELD: u8 = 12;
+
+/// The application the written files record as their writer.
+const CREATED_BY: &str = "baseconv";
+
+/// A column of the written files: its name, its physical type, whether it
+/// holds UTF-8 text, and how a page of its values is encoded.
+struct Column {
+    name: &'static str,
+    physical_type: i32,
+    string: bool,
+    encode: fn(&[Conversion], &mut Vec<u8>),
+}
+
+/// The column leading the others when the inputs were labelled.
+const LABEL_COLUMN: Column = Column {
+    name: "label",
+    physical_type: BYTE_ARRAY,
+    string: true,
+    encode: |rows, out| {
+        for row in rows {
+            write_byte_array(out, row.label.as_deref().unwrap_or_default());
+        }
+    },
+};
+
+/// The columns every written file has, with the fields of a `Conversion`.
+const COLUMNS: [Column; 5] = [
+    Column {
+        name: "input",
+        physical_type: DOUBLE,
+        string: false,
+        encode: |rows, out| {
+            for row in rows {
+                out.extend_from_slice(&row.input.to_le_bytes());
+            }
+        },
+    },
+    Column {
+        name: "base",
+        physical_type: BYTE_ARRAY,
+        string: true,
+        encode: |rows, out| {
+            for row in rows {
+                write_byte_array(out, &row.base);
+            }
+        },
+    },
+    Column {
+        name: "digits",
+        physical_type: INT64,
+        string: false,
+        encode: |rows, out| {
+            for row in rows {
+                out.extend_from_slice(&(row.digits as i64).to_le_bytes());
+            }
+        },
+    },
+    Column {
+        name: "truncated",
+        physical_type: BOOLEAN,
+        string: false,
+        // Booleans are packed eight to a byte, the first in the lowest bit.
+        encode: |rows, out| {
+            for byte in rows.chunks(8) {
+                out.push(
+                    byte.iter()
+                        .enumerate()
+                        .fold(0, |bits, (i, row)| bits | (u8::from(row.truncated) << i)),
+                );
+            }
+        },
+    },
+    Column {
+        name: "repr",
+        physical_type: BYTE_ARRAY,
+        string: true,
+        encode: |rows, out| {
+            for row in rows {
+                write_byte_array(out, &row.repr);
+            }
+        },
+    },
+];
+
+/// Writes a `BYTE_ARRAY` value: its length in four little-endian bytes,
+/// then its bytes.
+fn write_byte_array(out: &mut Vec<u8>, text: &str) {
+    out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Where a column chunk lies in the file and how much it holds, for the
+/// metadata in the footer.
+struct ChunkInfo {
+    offset: i64,
+    size: i64,
+    values: i64,
+}
+
+/// A Thrift struct being written with the compact protocol, in which every
+/// field header gives the field id as the difference from the previous one.
+struct Thrift {
+    bytes: Vec<u8>,
+    /// The id of the last field written in every struct being written, the
+    /// innermost last.
+    last_ids: Vec<i16>,
+}
+
+impl Thrift {
+    fn new() -> Thrift {
+        Thrift {
+            bytes: Vec::new(),
+            last_ids: vec![0],
+        }
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    /// Writes a signed integer in zigzag form, so that small negative
+    /// numbers stay short too.
+    fn zigzag(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = *self.last_ids.last().expect("a struct is being written");
+        match id - last {
+            delta @ 1..=15 => self.bytes.push((delta as u8) << 4 | kind),
+            _ => {
+                self.bytes.push(kind);
+                let id = i64::from(id);
+                self.zigzag(id);
+            }
+        }
+        *self.last_ids.last_mut().expect("a struct is being written") = id;
+    }
+
+    fn i32(&mut self, id: i16, value: i32) {
+        self.field(id, I32_FIELD);
+        self.zigzag(i64::from(value));
+    }
+
+    fn i64(&mut self, id: i16, value: i64) {
+        self.field(id, I64_FIELD);
+        self.zigzag(value);
+    }
+
+    fn binary(&mut self, id: i16, value: &[u8]) {
+        self.field(id, BINARY_FIELD);
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value);
+    }
+
+    /// Starts a list field of `len` elements of the given type, which are
+    /// then written bare, or with `element` if they are structs.
+    fn list(&mut self, id: i16, kind: u8, len: usize) {
+        self.field(id, LIST_FIELD);
+        if len < 15 {
+            self.bytes.push((len as u8) << 4 | kind);
+        } else {
+            self.bytes.push(0xf0 | kind);
+            self.varint(len as u64);
+        }
+    }
+
+    /// Starts a struct field, whose fields follow until `end`.
+    fn begin(&mut self, id: i16) {
+        self.field(id, STRUCT_FIELD);
+        self.element();
+    }
+
+    /// Starts a struct that is an element of a list.
+    fn element(&mut self) {
+        self.last_ids.push(0);
+    }
+
+    /// Ends the innermost struct with its stop byte.
+    fn end(&mut self) {
+        self.bytes.push(0);
+        self.last_ids.pop();
+    }
+}
+
+/// Writes the header of a data page of `values` values taking up `size`
+/// bytes.
+fn page_header(values: usize, size: usize) -> Vec<u8> {
+    let mut header = Thrift::new();
+    header.i32(1, 0); // a data page
+    header.i32(2, size as i32);
+    header.i32(3, size as i32);
+    header.begin(5);
+    header.i32(1, values as i32);
+    header.i32(2, PLAIN);
+    header.i32(3, RLE);
+    header.i32(4, RLE);
+    header.end();
+    header.end();
+    header.bytes
+}
+
+/// Writes the rows of a row group column by column, in data pages of
+/// `PAGE_ROWS` rows.
+///
+/// # Returns
+///
+/// Where each column chunk lies once written from `offset` on, in the
+/// order of `columns`.
+fn write_row_group(
+    out: &mut dyn Write,
+    columns: &[&Column],
+    rows: &[Conversion],
+    offset: &mut i64,
+) -> io::Result<Vec<ChunkInfo>> {
+    let mut chunks = Vec::new();
+    for column in columns {
+        let start = *offset;
+        for page in rows.chunks(PAGE_ROWS) {
+            let mut values = Vec::new();
+            (column.encode)(page, &mut values);
+            let header = page_header(page.len(), values.len());
+            out.write_all(&header)?;
+            out.write_all(&values)?;
+            *offset += (header.len() + values.len()) as i64;
+        }
+        chunks.push(ChunkInfo {
+            offset: start,
+            size: *offset - start,
+            values: rows.len() as i64,
+        });
+    }
+    Ok(chunks)
+}
+
+/// Writes the metadata of the file: its schema of required columns, and
+/// every row group with its rows and the chunk of every column.
+fn file_metadata(columns: &[&Column], row_groups: &[(usize, Vec<ChunkInfo>)]) -> Vec<u8> {
+    let mut metadata = Thrift::new();
+    metadata.i32(1, 1);
+    metadata.list(2, STRUCT_FIELD, columns.len() + 1);
+    metadata.element();
+    metadata.binary(4, b"schema");
+    metadata.i32(5, columns.len() as i32);
+    metadata.end();
+    for column in columns {
+        metadata.element();
+        metadata.i32(1, column.physical_type);
+        metadata.i32(3, 0); // required
+        metadata.binary(4, column.name.as_bytes());
+        if column.string {
+            metadata.i32(6, 0); // UTF8
+            metadata.begin(10);
+            metadata.begin(1); // STRING
+            metadata.end();
+            metadata.end();
+        }
+        metadata.end();
+    }
+    let rows: usize = row_groups.iter().map(|(rows, _)| rows).sum();
+    metadata.i64(3, rows as i64);
+    metadata.list(4, STRUCT_FIELD, row_groups.len());
+    for (rows, chunks) in row_groups {
+        metadata.element();
+        metadata.list(1, STRUCT_FIELD, chunks.len());
+        for (column, chunk) in columns.iter().zip(chunks) {
+            metadata.element();
+            metadata.i64(2, chunk.offset);
+            metadata.begin(3);
+            metadata.i32(1, column.physical_type);
+            metadata.list(2, I32_FIELD, 2);
+            metadata.zigzag(i64::from(PLAIN));
+            metadata.zigzag(i64::from(RLE));
+            metadata.list(3, BINARY_FIELD, 1);
+            metadata.varint(column.name.len() as u64);
+            metadata.bytes.extend_from_slice(column.name.as_bytes());
+            metadata.i32(4, 0); // uncompressed
+            metadata.i64(5, chunk.values);
+            metadata.i64(6, chunk.size);
+            metadata.i64(7, chunk.size);
+            metadata.i64(9, chunk.offset);
+            metadata.end();
+            metadata.end();
+        }
+        metadata.i64(2, chunks.iter().map(|chunk| chunk.size).sum());
+        metadata.i64(3, *rows as i64);
+        metadata.end();
+    }
+    metadata.binary(6, CREATED_BY.as_bytes());
+    metadata.end();
+    metadata.bytes
+}
+
+/// Writes conversions as an uncompressed Parquet file, built without a
+/// Parquet library, so that pandas, Polars and the like read the results
+/// as typed columns: `input` as doubles, `base` and `repr` as strings,
+/// `digits` as 64-bit integers and `truncated` as booleans, after a `label`
+/// string column when the inputs were labelled.
+///
+/// The rows are written a row group of `ROW_GROUP_ROWS` at a time, as they
+/// come, and the metadata locating every group ends the file.
+///
+/// # Arguments
+///
+/// * `out` - Where the file is written.
+/// * `conversions` - The conversion of every input to every target base,
+///   either all with a label or all without.
+pub fn write_parquet(
+    out: &mut dyn Write,
+    conversions: impl IntoIterator<Item = Conversion>,
+) -> io::Result<()> {
+    let mut conversions = conversions.into_iter().peekable();
+    let labelled = conversions
+        .peek()
+        .is_some_and(|conversion| conversion.label.is_some());
+    let columns: Vec<&Column> = labelled
+        .then_some(&LABEL_COLUMN)
+        .into_iter()
+        .chain(&COLUMNS)
+        .collect();
+
+    out.write_all(PARQUET_MAGIC)?;
+    let mut offset = PARQUET_MAGIC.len() as i64;
+    let mut row_groups = Vec::new();
+    let mut rows = Vec::with_capacity(ROW_GROUP_ROWS);
+    loop {
+        rows.extend(conversions.by_ref().take(ROW_GROUP_ROWS));
+        if rows.is_empty() {
+            break;
+        }
+        let chunks = write_row_group(out, &columns, &rows, &mut offset)?;
+        row_groups.push((rows.len(), chunks));
+        rows.clear();
+    }
+    let metadata = file_metadata(&columns, &row_groups);
+    out.write_all(&metadata)?;
+    out.write_all(&(metadata.len() as u32).to_le_bytes())?;
+    out.write_all(PARQUET_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_thrift_compact() {
+        let mut thrift = Thrift::new();
+        thrift.i32(1, -1);
+        thrift.i64(2, 150);
+        thrift.binary(20, b"ab");
+        thrift.begin(21);
+        thrift.end();
+        thrift.end();
+        assert_that!(
+            thrift.bytes,
+            equal_to(vec![
+                0x15, 0x01, // field 1, i32 -1 in zigzag form
+                0x16, 0xac, 0x02, // field 2, i64 150
+                0x08, 0x28, 0x02, b'a', b'b', // field 20, too far for a delta
+                0x1c, 0x00, // field 21, an empty struct
+                0x00,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_written_file() {
+        let conversions: Vec<Conversion> = [0.5, 0.1]
+            .iter()
+            .map(|&input| Conversion::new(input, 2.0, &expand(input, 2, 8)))
+            .collect();
+        let mut out = Vec::new();
+        write_parquet(&mut out, conversions).unwrap();
+        assert_that!(&out[..4], equal_to(&PARQUET_MAGIC[..]));
+        assert_that!(&out[out.len() - 4..], equal_to(&PARQUET_MAGIC[..]));
+        let footer = out.len() - 8;
+        let length = u32::from_le_bytes(out[footer..footer + 4].try_into().unwrap()) as usize;
+        let metadata = &out[footer - length..footer];
+        assert!(metadata
+            .windows(b"truncated".len())
+            .any(|window| window == b"truncated"));
+
+        // The first page holds the inputs, after its header, as doubles.
+        let header = page_header(2, 16);
+        assert_that!(&out[4..4 + header.len()], equal_to(&header[..]));
+        let values = &out[4 + header.len()..4 + header.len() + 16];
+        assert_that!(
+            values[..8].to_vec(),
+            equal_to(0.5f64.to_le_bytes().to_vec())
+        );
+
+        // Only 0.1 is truncated, in the second bit of the boolean page.
+        let mut bits = Vec::new();
+        let rows = vec![
+            Conversion::new(0.5, 2.0, &expand(0.5, 2, 8)),
+            Conversion::new(0.1, 2.0, &expand(0.1, 2, 8)),
+        ];
+        (COLUMNS[3].encode)(&rows, &mut bits);
+        assert_that!(bits, equal_to(vec![0b10]));
+
+        let mut empty = Vec::new();
+        write_parquet(&mut empty, Vec::new()).unwrap();
+        assert_that!(&empty[..4], equal_to(&PARQUET_MAGIC[..]));
+    }
+}
@@ -0,0 +1,908 @@
+use digits::MAX_BASE;
+use num_traits::Zero;
+
+use crate::backend::Backend;
+use crate::beta::{expand_beta, format_base, BetaRule};
+use crate::cancel::CancelToken;
+use crate::convert::{expand_negabase, Expansion, Rounding, MAX_DIGITS};
+use crate::decimal::parse_decimal;
+use crate::expression::evaluate;
+use crate::integer::expand_whole;
+use crate::locale::with_decimal_point;
+use crate::log::{log, Level};
+use crate::memo::{CacheKey, ExpansionCache};
+use crate::rational::{Fraction, Ratio};
+use crate::shortest::shortest;
+use crate::subscript::split_subscript;
+use crate::terminate::terminating_digits;
+
+/// How each command-line input is turned into the value to be converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decode {
+    /// The input is a plain base 10 number such as `0.75`, or a fraction
+    /// such as `1/3`.
+    Decimal,
+    /// The input is the raw two's complement integer of a Qm.n fixed-point
+    /// number, written in base 10 or as `0x…` hex.
+    Fixed {
+        integer_bits: u32,
+        fraction_bits: u32,
+    },
+}
+
+impl Decode {
+    /// Decodes a single command-line input.
+    ///
+    /// # Arguments
+    ///
+    /// * `arg` - The input as typed by the user.
+    ///
+    /// # Returns
+    ///
+    /// The decoded value, or `None` if the input is not valid for this stage.
+    ///
+    /// # Example
+    /// ```
+    /// let q = Decode::Fixed { integer_bits: 1, fraction_bits: 15 };
+    /// assert_eq!(q.decode("0x4000"), Some(0.5));
+    /// assert_eq!(q.decode("0xC000"), Some(-0.5));
+    /// ```
+    pub fn decode(&self, arg: &str) -> Option<f64> {
+        match *self {
+            Decode::Decimal => arg
+                .parse::<f64>()
+                .ok()
+                .or_else(|| Fraction::parse(arg).map(|fraction| fraction.value()))
+                .or_else(|| Fraction::parse_prefixed(arg).map(|fraction| fraction.value())),
+            Decode::Fixed {
+                integer_bits,
+                fraction_bits,
+            } => {
+                let width = integer_bits + fraction_bits;
+                let raw = match arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+                    Some(hex) => sign_extend(u64::from_str_radix(hex, 16).ok()?, width)?,
+                    None => arg.parse::<i64>().ok()?,
+                };
+                let limit = 1i64 << (width - 1);
+                if raw < -limit || raw >= limit {
+                    return None;
+                }
+                Some(raw as f64 / (1u64 << fraction_bits) as f64)
+            }
+        }
+    }
+}
+
+/// Takes the `_` separators out of a number written like a Rust literal,
+/// such as `0.000_001` or `0x7F_FF`.
+///
+/// # Returns
+///
+/// The number without them, or `None` if an `_` does not sit between two
+/// digits, as in `_1`, `1__000` or `1_.5`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(without_underscores("1_000.5").as_deref(), Some("1000.5"));
+/// assert_eq!(without_underscores("1_000_").as_deref(), None);
+/// ```
+pub fn without_underscores(arg: &str) -> Option<String> {
+    let bytes = arg.as_bytes();
+    let between_digits = bytes.iter().enumerate().all(|(i, &byte)| {
+        byte != b'_'
+            || (i > 0
+                && bytes[i - 1].is_ascii_alphanumeric()
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_alphanumeric))
+    });
+    between_digits.then(|| arg.replace('_', ""))
+}
+
+/// Rewrites a percentage such as `12.5%` as the number it stands for, here
+/// `12.5e-2`, so that it is decoded as exactly as any other decimal. A
+/// percentage of a fraction, such as `1/8%`, becomes `1/800`.
+///
+/// # Returns
+///
+/// The input as it was if it does not end in `%`, the number it stands for
+/// if it does, or `None` if what comes before the `%` cannot be scaled, as
+/// in `%` or `0x10%`.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(without_percent("12.5%").as_deref(), Some("12.5e-2"));
+/// assert_eq!(without_percent("1.5e3%").as_deref(), Some("1.5e1"));
+/// assert_eq!(without_percent("0.5").as_deref(), Some("0.5"));
+/// ```
+pub fn without_percent(arg: &str) -> Option<String> {
+    let Some(number) = arg.strip_suffix('%') else {
+        return Some(arg.to_string());
+    };
+    // Only a single number or fraction is scaled, not an expression.
+    let bytes = number.as_bytes();
+    let is_operator = |i: usize| match bytes[i] {
+        b'*' | b'(' | b')' => true,
+        b'+' | b'-' => i > 0 && !matches!(bytes[i - 1], b'e' | b'E'),
+        _ => false,
+    };
+    if (0..bytes.len()).any(is_operator) || number.matches('/').count() > 1 {
+        return None;
+    }
+    let (_, unsigned) = number.split_at(number.len() - number.trim_start_matches(['-', '+']).len());
+    if !unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        || unsigned.starts_with("0x")
+        || unsigned.starts_with("0b")
+        || unsigned.starts_with("0o")
+    {
+        return None;
+    }
+    if let Some((numerator, denominator)) = number.split_once('/') {
+        return Some(format!("{}/{}00", numerator, denominator));
+    }
+    Some(match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => format!("{}e{}", mantissa, exponent.parse::<i64>().ok()? - 2),
+        None => format!("{}e-2", number),
+    })
+}
+
+/// Reinterprets the low `width` bits of `bits` as a two's complement integer.
+///
+/// Returns `None` if any bit above `width` is set.
+fn sign_extend(bits: u64, width: u32) -> Option<i64> {
+    if bits >> width != 0 {
+        return None;
+    }
+    let shift = 64 - width;
+    Some(((bits << shift) as i64) >> shift)
+}
+
+/// A chain of decode, convert and format stages applied to every input.
+///
+/// The default conversion (decimal input, base 2, eight truncated digits) is
+/// itself a pipeline, so the plain positional arguments and `--pipe` share one
+/// code path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    /// How raw inputs are decoded into values.
+    pub decode: Decode,
+    /// Whether decimal inputs may be written with a decimal comma, as in
+    /// `0,5`, from `--decimal-comma` or `--locale`.
+    pub decimal_comma: bool,
+    /// Whether decimal inputs ending in a subscript base, such as `0.101₂`
+    /// or `0.C₁₆`, are read in that base, from `--auto-detect`.
+    pub auto_detect: bool,
+    /// The bases to convert to, one output column each. Negative bases such
+    /// as `-2` (negabinary) are converted without a sign, and non-integer
+    /// bases such as `1.5` by greedy digit extraction.
+    pub target_bases: Vec<f64>,
+    /// The maximum number of fractional digits to produce.
+    pub digits: u32,
+    /// Whether to produce, instead of `digits` digits, the fewest digits
+    /// that still read back as the same `f64` in positive integer bases.
+    pub shortest: bool,
+    /// Whether inputs whose expansion in a positive integer base ends are
+    /// written with exactly the digits it ends after, however many that is,
+    /// instead of at most `digits`.
+    pub exact_digits: bool,
+    /// How the last digit is settled when the expansion is cut off.
+    pub rounding: Rounding,
+    /// The arithmetic used for positive integer bases, or `None` to pick
+    /// the fastest exact one for every input with [`Backend::select`].
+    pub backend: Option<Backend>,
+    /// Whether every decimal input is kept as the exact fraction it is
+    /// written as, such as 1/10 for `0.1`, and its digits divided out of it,
+    /// rather than taken from the `f64` nearest to it, from `--exact`.
+    pub exact: bool,
+    /// Whether every decimal input is read as a 96-bit `rust_decimal::Decimal` and
+    /// converted as the exact fraction it is, and any other input refused,
+    /// from `--decimal`.
+    pub decimal: bool,
+    /// Whether every input is first rounded to the nearest `f32`, and that
+    /// value converted, from `--float-width 32`.
+    pub single_precision: bool,
+    /// Whether every input is converted as the `f64` it is stored as, even
+    /// one written as an exact fraction such as `1/3`, from
+    /// `--interpret float`.
+    pub stored_float: bool,
+    /// Stops the conversions of this pipeline early once it is cancelled.
+    pub cancel: CancelToken,
+    /// Keeps the fixed-precision expansions of a batch, so that repeated
+    /// inputs are converted once, or `None` to convert every input anew.
+    pub cache: Option<ExpansionCache>,
+}
+
+impl Pipeline {
+    /// Creates the default pipeline converting decimal inputs to every base
+    /// in `target_bases`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_bases` - The bases to convert to.
+    pub fn with_bases(target_bases: Vec<f64>) -> Pipeline {
+        Pipeline {
+            decode: Decode::Decimal,
+            decimal_comma: false,
+            auto_detect: false,
+            target_bases,
+            digits: MAX_DIGITS,
+            shortest: false,
+            exact_digits: false,
+            rounding: Rounding::Truncate,
+            backend: None,
+            exact: false,
+            decimal: false,
+            single_precision: false,
+            stored_float: false,
+            cancel: CancelToken::new(),
+            cache: None,
+        }
+    }
+
+    /// Parses a pipeline expression such as `from=q1.15 | to=base16 | round=half-even`.
+    ///
+    /// Stages are separated by `|` and each stage is a `key=value` pair. The
+    /// recognised keys are:
+    ///
+    /// * `from` - `decimal` or `q<m>.<n>` for Qm.n fixed-point raw integers.
+    /// * `to` - the target base, written as `base16` or just `16`, or a comma
+    ///   separated list of bases such as `2,8,16`.
+    /// * `digits` - the maximum number of fractional digits, `shortest`
+    ///   for the fewest that read back as the same `f64`, or `exact` for
+    ///   exactly as many as a terminating expansion needs.
+    /// * `round` - `trunc`, `half-up` or `half-even`.
+    /// * `backend` - `auto` or one of the backends such as `bignum`.
+    ///
+    /// Stages that are left out keep their default values.
+    ///
+    /// # Returns
+    ///
+    /// The parsed `Pipeline`, or a message describing the first invalid stage.
+    ///
+    /// # Example
+    /// ```
+    /// let pipeline = Pipeline::parse("to=base16 | round=half-even").unwrap();
+    /// assert_eq!(pipeline.target_bases, vec![16.0]);
+    /// ```
+    pub fn parse(expression: &str) -> Result<Pipeline, String> {
+        let mut pipeline = Pipeline::with_bases(vec![2.0]);
+
+        for stage in expression.split('|').map(str::trim) {
+            let (key, value) = stage
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| {
+                    format!("pipeline stage `{}` is not of the form key=value", stage)
+                })?;
+
+            match key {
+                "from" => pipeline.decode = parse_decode(value)?,
+                "to" => pipeline.target_bases = parse_base_list(value)?,
+                "digits" if value == "shortest" => pipeline.shortest = true,
+                "digits" if value == "exact" => pipeline.exact_digits = true,
+                "digits" => {
+                    pipeline.digits = value
+                        .parse::<u32>()
+                        .map_err(|_| format!("invalid digit count `{}`", value))?
+                }
+                "round" => {
+                    pipeline.rounding = Rounding::from_name(value)
+                        .ok_or_else(|| format!("unknown rounding mode `{}`", value))?
+                }
+                "backend" => pipeline.backend = parse_backend(value)?,
+                _ => return Err(format!("unknown pipeline stage `{}`", key)),
+            }
+        }
+
+        Ok(pipeline)
+    }
+
+    /// Runs the decode stage on one raw input.
+    ///
+    /// # Returns
+    ///
+    /// The decoded value with the exact fraction it was written as, if it
+    /// was written as one, in binary, octal, hexadecimal or a subscript
+    /// base, or as a decimal with more digits than its `f64` keeps, or
+    /// `None` if the input cannot be decoded, as infinities and NaN cannot,
+    /// having no digits in any base. With `single_precision`, the
+    /// value is the `f32` nearest to it, without a fraction, and an input
+    /// beyond the largest `f32` cannot be decoded. With `stored_float`,
+    /// there is never a fraction.
+    pub fn decode_input(&self, arg: &str) -> Option<(f64, Option<Fraction>)> {
+        let decoded = self
+            .decode_double(arg)
+            .filter(|(value, _)| value.is_finite())?;
+        if !self.single_precision {
+            return Some(if self.stored_float {
+                (decoded.0, None)
+            } else {
+                decoded
+            });
+        }
+        let single = decoded.0 as f32;
+        if single.is_infinite() && decoded.0.is_finite() {
+            return None;
+        }
+        Some((f64::from(single), None))
+    }
+
+    /// Runs the decode stage on one raw input as `decode_input` does, to an
+    /// `f64` whatever `single_precision` is.
+    fn decode_double(&self, arg: &str) -> Option<(f64, Option<Fraction>)> {
+        if self.auto_detect && self.decode == Decode::Decimal {
+            if let Some((number, base)) = split_subscript(arg) {
+                let exact = Fraction::parse_in_base(number, base)?;
+                return Some((exact.value(), Some(exact)));
+            }
+        }
+        // A mixed number such as `1_3/4` is read before its `_` could be
+        // taken for a digit separator, and a fraction such as `1_300/400`
+        // that reads both ways is refused.
+        let (number, percent) = match arg.strip_suffix('%') {
+            Some(number) => (number, "%"),
+            None => (arg, ""),
+        };
+        let mixed = match self.decode {
+            Decode::Decimal => Fraction::parse_mixed(number).ok()?,
+            _ => None,
+        };
+        let mut rewritten = match mixed {
+            Some(mixed) => format!("{}{}", Ratio::from(mixed), percent),
+            None => without_underscores(arg)?,
+        };
+        if self.decimal_comma && self.decode == Decode::Decimal {
+            rewritten = with_decimal_point(&rewritten)?;
+        }
+        if self.decimal && self.decode == Decode::Decimal {
+            let (number, percent) = match rewritten.strip_suffix('%') {
+                Some(number) => (number, true),
+                None => (rewritten.as_str(), false),
+            };
+            let mut exact = Fraction::from(parse_decimal(number).ok()?);
+            if percent {
+                exact.denominator *= 100u32;
+            }
+            return Some((exact.value(), Some(exact)));
+        }
+        if self.decode == Decode::Decimal {
+            rewritten = without_percent(&rewritten)?;
+        }
+        let arg = rewritten.as_str();
+        let Some(value) = self.decode.decode(arg) else {
+            // What no other decoder reads may still be an expression such
+            // as `0.1+0.2`, which is worked out exactly.
+            let exact = evaluate(arg).filter(|_| self.decode == Decode::Decimal)?;
+            let exact = Fraction::from(exact);
+            return Some((exact.value(), Some(exact)));
+        };
+        let fraction = match self.decode {
+            Decode::Decimal => {
+                // A decimal such as `1e400` that is beyond every `f64` is
+                // rejected rather than read as infinity, and one such as
+                // `1e-400` that is too small for any is kept exactly.
+                if value.is_infinite() && arg.bytes().any(|byte| byte.is_ascii_digit()) {
+                    return None;
+                }
+                let exact = Fraction::parse_decimal(arg);
+                Fraction::parse(arg)
+                    .or_else(|| Fraction::parse_prefixed(arg))
+                    .or_else(|| Fraction::parse_long_decimal(arg))
+                    .or_else(|| {
+                        exact.filter(|exact| {
+                            self.exact || (value == 0.0 && !exact.numerator.is_zero())
+                        })
+                    })
+            }
+            Decode::Fixed { .. } => None,
+        };
+        Some((value, fraction))
+    }
+
+    /// Runs the convert stage and the rounding of the format stage on an
+    /// already decoded value, once for every target base.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The decoded value.
+    /// * `fraction` - The exact fraction the input was written as, if any.
+    ///
+    /// # Returns
+    ///
+    /// The rounded expansions in the order of `target_bases`.
+    pub fn expand(&self, value: f64, fraction: Option<&Fraction>) -> Vec<Expansion> {
+        self.target_bases
+            .iter()
+            .map(|&target_base| self.expand_input(value, fraction, target_base))
+            .collect()
+    }
+
+    /// Runs the convert stage and the rounding of the format stage on an
+    /// already decoded value for a single target base.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The decoded value.
+    /// * `target_base` - The base to convert to. Expansions in a negative or
+    ///   non-integer base are never rounded.
+    ///
+    /// # Returns
+    ///
+    /// The rounded `Expansion` of the value.
+    pub fn expand_to(&self, value: f64, target_base: f64) -> Expansion {
+        self.expand_input(value, None, target_base)
+    }
+
+    /// Like [`Pipeline::expand_to`], but also passes on the exact fraction an
+    /// input was written as to the backends that can use it.
+    pub fn expand_input(
+        &self,
+        value: f64,
+        fraction: Option<&Fraction>,
+        target_base: f64,
+    ) -> Expansion {
+        let (path, expansion) = if target_base.fract() != 0.0 {
+            (
+                "greedy β-expansion",
+                expand_beta(value, target_base, BetaRule::Greedy, self.digits),
+            )
+        } else if target_base < 0.0 {
+            (
+                "negabase",
+                expand_negabase(value, -target_base as u64, self.digits),
+            )
+        } else if let Some(whole) = expand_whole(value, fraction, target_base as u64) {
+            // A whole number has no fractional digit to extract.
+            ("whole number", whole)
+        } else if self.shortest {
+            ("shortest", shortest(value, target_base as u64))
+        } else if let Some((exact, digits)) = self
+            .exact_digits
+            .then(|| terminating_digits(value, fraction, target_base as u64))
+            .flatten()
+        {
+            // Long division of the exact value stops by itself after its
+            // last digit, and a whole number still gets its one zero digit.
+            let digits = digits.clamp(1, u32::MAX as u64) as u32;
+            (
+                "exact digits",
+                Backend::LongDivision.expand_cancellable(
+                    value,
+                    Some(&exact),
+                    target_base as u64,
+                    digits,
+                    &self.cancel,
+                ),
+            )
+        } else {
+            let target_base = target_base as u64;
+            let backend = self
+                .backend
+                .unwrap_or_else(|| Backend::select(value, fraction, target_base));
+            let convert = || {
+                let mut expansion = backend.expand_cancellable(
+                    value,
+                    fraction,
+                    target_base,
+                    self.digits,
+                    &self.cancel,
+                );
+                expansion.round(self.rounding);
+                (expansion, !self.cancel.is_cancelled())
+            };
+            let expansion = match &self.cache {
+                Some(cache) => cache.get_or_convert(
+                    CacheKey {
+                        bits: value.to_bits(),
+                        fraction: fraction.cloned(),
+                        target_base,
+                        digits: self.digits,
+                        rounding: self.rounding,
+                        backend,
+                    },
+                    convert,
+                ),
+                None => convert().0,
+            };
+            (backend.name(), expansion)
+        };
+
+        log(Level::Info, "convert", || {
+            let written = match fraction {
+                Some(fraction) => format!(
+                    " (written as {}{}/{})",
+                    if fraction.negative { "-" } else { "" },
+                    fraction.numerator,
+                    fraction.denominator
+                ),
+                None => String::new(),
+            };
+            format!(
+                "{}{} to base {} with the {} path",
+                value,
+                written,
+                format_base(target_base),
+                path
+            )
+        });
+        log(Level::Debug, "convert", || {
+            let stop = if expansion.remainder == 0.0 {
+                "once nothing was left"
+            } else if self.cancel.is_cancelled() {
+                "when the run was cancelled"
+            } else {
+                "at the digit limit"
+            };
+            format!(
+                "the digit loop of {} in base {} ran {} iterations and stopped {}, leaving {}",
+                value,
+                format_base(target_base),
+                expansion.digits.len(),
+                stop,
+                expansion.remainder
+            )
+        });
+        expansion
+    }
+}
+
+/// Parses a comma separated list of target bases such as `2,8,16`, where
+/// every base may also be written as `base16` or as a power such as `2^32`
+/// or `10^9`, and negative bases such as `-2` are allowed. No base may be
+/// larger than [`MAX_BASE`], 2^32, whose digits are the largest a digit
+/// holds.
+///
+/// # Arguments
+///
+/// * `value` - The list as typed by the user.
+///
+/// # Returns
+///
+/// The bases in the order given, or a message naming the first invalid one.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(parse_base_list("2,-3,10^9"), Ok(vec![2.0, -3.0, 1e9]));
+/// assert!(parse_base_list("2^33").is_err());
+/// ```
+pub fn parse_base_list(value: &str) -> Result<Vec<f64>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|base| {
+            let target_base = parse_base(base.strip_prefix("base").unwrap_or(base))
+                .filter(|target_base| target_base.unsigned_abs() >= 2)
+                .ok_or_else(|| format!("invalid target base `{}`", base))?;
+            if target_base.unsigned_abs() > MAX_BASE {
+                return Err(format!(
+                    "target base `{}` is larger than 2^32, the largest base whose digits \
+                     fit in 32 bits",
+                    base
+                ));
+            }
+            Ok(target_base as f64)
+        })
+        .collect()
+}
+
+/// Reads a whole base, written out or as a power such as `2^32`, or `None`
+/// if it is neither or does not fit in an `i64`.
+fn parse_base(base: &str) -> Option<i64> {
+    let Some((radix, exponent)) = base.split_once('^') else {
+        return base.parse().ok();
+    };
+    let (negative, radix) = match radix.strip_prefix('-') {
+        Some(radix) => (true, radix),
+        None => (false, radix),
+    };
+    let power = radix
+        .parse::<i64>()
+        .ok()
+        .filter(|&radix| radix >= 0)?
+        .checked_pow(exponent.parse().ok()?)?;
+    Some(if negative { -power } else { power })
+}
+
+/// Parses a backend name, where `auto` stands for picking one per input.
+pub fn parse_backend(value: &str) -> Result<Option<Backend>, String> {
+    match value {
+        "auto" => Ok(None),
+        _ => Backend::from_name(value)
+            .map(Some)
+            .ok_or_else(|| format!("unknown backend `{}`", value)),
+    }
+}
+
+/// Parses the value of a `from=` stage.
+fn parse_decode(value: &str) -> Result<Decode, String> {
+    if value == "decimal" {
+        return Ok(Decode::Decimal);
+    }
+
+    parse_q_format(value)
+        .map(|(integer_bits, fraction_bits)| Decode::Fixed {
+            integer_bits,
+            fraction_bits,
+        })
+        .ok_or_else(|| format!("unknown input format `{}`", value))
+}
+
+/// Parses a fixed-point format written as `q<m>.<n>` into its integer and
+/// fraction bit widths, which together must be 1 to 63 bits.
+pub fn parse_q_format(value: &str) -> Option<(u32, u32)> {
+    value
+        .strip_prefix('q')
+        .or_else(|| value.strip_prefix('Q'))
+        .and_then(|format| format.split_once('.'))
+        .and_then(|(m, n)| Some((m.parse::<u32>().ok()?, n.parse::<u32>().ok()?)))
+        .filter(|&(m, n)| (1..=63).contains(&(m + n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_parse_pipeline() {
+        let pipeline = Pipeline::parse("from=q1.15 | to=base16 | round=half-even").unwrap();
+        assert_that!(
+            pipeline,
+            equal_to(Pipeline {
+                decode: Decode::Fixed {
+                    integer_bits: 1,
+                    fraction_bits: 15
+                },
+                decimal_comma: false,
+                auto_detect: false,
+                target_bases: vec![16.0],
+                digits: MAX_DIGITS,
+                shortest: false,
+                exact_digits: false,
+                rounding: Rounding::HalfEven,
+                backend: None,
+                exact: false,
+                decimal: false,
+                single_precision: false,
+                stored_float: false,
+                cancel: CancelToken::new(),
+                cache: None,
+            })
+        );
+        assert_that!(
+            Pipeline::parse("to=8"),
+            equal_to(Ok(Pipeline::with_bases(vec![8.0])))
+        );
+        assert_that!(
+            Pipeline::parse("to=base2,8, 16").map(|pipeline| pipeline.target_bases),
+            equal_to(Ok(vec![2.0, 8.0, 16.0]))
+        );
+        assert!(Pipeline::parse("to=base1").is_err());
+        let exact = Pipeline::parse("digits=exact | to=2,3").unwrap();
+        let fraction = Fraction::parse("5/1024");
+        let expansions = exact.expand(5.0 / 1024.0, fraction.as_ref());
+        assert_that!(
+            expansions[0].to_string(),
+            equal_to("0.0;0;0;0;0;0;0;1;0;1;")
+        );
+        assert_that!(expansions[1].digits.len(), equal_to(MAX_DIGITS as usize));
+        assert_that!(exact.expand(3.0, None)[0].to_string(), equal_to("1;1."));
+        assert!(Pipeline::parse("from=hex").is_err());
+        assert!(Pipeline::parse("round").is_err());
+        assert_that!(
+            Pipeline::parse("backend=bignum").map(|pipeline| pipeline.backend),
+            equal_to(Ok(Some(Backend::Bignum)))
+        );
+        assert!(Pipeline::parse("backend=abacus").is_err());
+    }
+
+    #[test]
+    fn test_parse_large_bases() {
+        assert_that!(
+            parse_base_list("base2^32, 10^9, -2^32"),
+            equal_to(Ok(vec![4294967296.0, 1e9, -4294967296.0]))
+        );
+        assert!(parse_base_list("4294967297").is_err());
+        assert!(parse_base_list("2^64").is_err());
+        assert!(parse_base_list("1^5").is_err());
+        let pipeline = Pipeline::parse("to=2^32").unwrap();
+        let expansion = pipeline.expand_input(4294967295.5, None, 4294967296.0);
+        assert_that!(expansion.integer_digits, equal_to(vec![u32::MAX]));
+        assert_that!(expansion.digits, equal_to(vec![1 << 31]));
+        let expansion = pipeline.expand_input(-0.5, None, -4294967296.0);
+        assert_that!(expansion.to_string(), equal_to("0.2147483648;".to_string()));
+    }
+
+    #[test]
+    fn test_decode_fixed_point() {
+        let q = Decode::Fixed {
+            integer_bits: 1,
+            fraction_bits: 15,
+        };
+        assert_that!(q.decode("16384"), equal_to(Some(0.5)));
+        assert_that!(q.decode("0x6000"), equal_to(Some(0.75)));
+        assert_that!(q.decode("0xC000"), equal_to(Some(-0.5)));
+        assert_that!(q.decode("-8192"), equal_to(Some(-0.25)));
+        assert_that!(q.decode("0x10000"), equal_to(None));
+        assert_that!(q.decode("40000"), equal_to(None));
+        assert_that!(Decode::Decimal.decode("3/4"), equal_to(Some(0.75)));
+    }
+
+    #[test]
+    fn test_decode_expressions() {
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let (value, fraction) = pipeline.decode_input("0.1+0.2").unwrap();
+        assert_that!(value, equal_to(0.3));
+        assert_that!(
+            fraction.map(|fraction| Ratio::from(fraction).to_string()),
+            equal_to(Some("3/10".to_string()))
+        );
+        assert_that!(
+            pipeline.decode_input("1/3*2").map(|(value, _)| value),
+            equal_to(Some(2.0 / 3.0))
+        );
+        assert_that!(pipeline.decode_input("1/(1-1)"), equal_to(None));
+        let fixed = Pipeline::parse("from=q1.15").unwrap();
+        assert_that!(fixed.decode_input("1+1"), equal_to(None));
+    }
+
+    #[test]
+    fn test_decode_underscores() {
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let value = |arg| pipeline.decode_input(arg).map(|(value, _)| value);
+        assert_that!(value("0.000_001"), equal_to(Some(0.000001)));
+        assert_that!(value("1_000.5"), equal_to(Some(1000.5)));
+        assert_that!(value("1/1_024"), equal_to(Some(1.0 / 1024.0)));
+        assert_that!(value("0b0.1_1"), equal_to(Some(0.75)));
+        assert_that!(value("_1"), equal_to(None));
+        assert_that!(value("1__0"), equal_to(None));
+        assert_that!(value("1_.5"), equal_to(None));
+        assert_that!(value("1.5_"), equal_to(None));
+
+        assert_that!(value("2.5E-2"), equal_to(Some(0.025)));
+        assert_that!(value("-1e3"), equal_to(Some(-1000.0)));
+        assert_that!(value("1e400"), equal_to(None));
+        assert_that!(value("-1e20000"), equal_to(None));
+        assert_that!(value("inf"), equal_to(None));
+        assert_that!(value("-inf"), equal_to(None));
+        assert_that!(value("NaN"), equal_to(None));
+        let tiny = pipeline.decode_input("1e-400").unwrap();
+        assert_that!(tiny.0, equal_to(0.0));
+        assert_that!(
+            tiny.1.map(|fraction| fraction.denominator),
+            equal_to(Some(BigUint::from(10u32).pow(400)))
+        );
+        assert_that!(pipeline.decode_input("0e-400").unwrap().1, equal_to(None));
+    }
+
+    #[test]
+    fn test_decode_percentages() {
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let value = |arg| pipeline.decode_input(arg).map(|(value, _)| value);
+        assert_that!(value("12.5%"), equal_to(Some(0.125)));
+        assert_that!(value("-50%"), equal_to(Some(-0.5)));
+        assert_that!(value("12.3%"), equal_to(Some(0.123)));
+        assert_that!(value("1.5e1%"), equal_to(Some(0.15)));
+        assert_that!(value("1_000%"), equal_to(Some(10.0)));
+        assert_that!(value("%"), equal_to(None));
+        assert_that!(value("0x10%"), equal_to(None));
+        assert_that!(value("12.5%%"), equal_to(None));
+        assert_that!(value("1_1/2%"), equal_to(Some(0.015)));
+        assert_that!(value("1/3*2%"), equal_to(None));
+        assert_that!(value("1+1%"), equal_to(None));
+        let (third, fraction) = pipeline.decode_input("100/3%").unwrap();
+        assert_that!(third, equal_to(1.0 / 3.0));
+        assert_that!(
+            fraction.map(|fraction| fraction.denominator),
+            equal_to(Some(BigUint::from(300u32)))
+        );
+        let long = pipeline
+            .decode_input("0.123_456_789_012_345_678_901")
+            .unwrap();
+        assert_that!(
+            long.1.map(|fraction| fraction.denominator),
+            equal_to(Some(BigUint::from(10u32).pow(21)))
+        );
+    }
+
+    #[test]
+    fn test_exact_decimal_inputs() {
+        let mut pipeline = Pipeline::with_bases(vec![10.0]);
+        assert_that!(pipeline.decode_input("0.1").unwrap().1, equal_to(None));
+        pipeline.exact = true;
+        pipeline.digits = 40;
+        let (value, fraction) = pipeline.decode_input("0.1").unwrap();
+        assert_that!(
+            fraction
+                .as_ref()
+                .map(|fraction| fraction.denominator.clone()),
+            equal_to(Some(BigUint::from(10u32)))
+        );
+        // The f64 nearest to 0.1 has digits of its own after the first.
+        let expansion = pipeline.expand_input(value, fraction.as_ref(), 10.0);
+        assert_that!(expansion.digits, equal_to(vec![1]));
+        assert_that!(expansion.remainder, equal_to(0.0));
+        let (value, fraction) = pipeline.decode_input("-2.5e-1").unwrap();
+        let expansion = pipeline.expand_input(value, fraction.as_ref(), 2.0);
+        assert_that!(expansion.negative, equal_to(true));
+        assert_that!(expansion.digits, equal_to(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_decimal_inputs() {
+        let mut pipeline = Pipeline::with_bases(vec![10.0]);
+        pipeline.decimal = true;
+        let (value, fraction) = pipeline.decode_input("1_234.50").unwrap();
+        assert_that!(value, equal_to(1234.5));
+        assert_that!(
+            fraction.map(|fraction| fraction.denominator),
+            equal_to(Some(BigUint::from(100u32)))
+        );
+        let (_, fraction) = pipeline.decode_input("12.5%").unwrap();
+        assert_that!(fraction.unwrap().value(), equal_to(0.125));
+        for invalid in [
+            "1/3",
+            "1e5",
+            "0x10",
+            "0.1+0.2",
+            "1.00000000000000000000000000001",
+        ] {
+            assert_that!(pipeline.decode_input(invalid), equal_to(None));
+        }
+    }
+
+    #[test]
+    fn test_float_inputs() {
+        let mut pipeline = Pipeline::with_bases(vec![2.0]);
+        pipeline.single_precision = true;
+        assert_that!(
+            pipeline.decode_input("0.1"),
+            equal_to(Some((f64::from(0.1f32), None)))
+        );
+        assert_that!(
+            pipeline.decode_input("1/3"),
+            equal_to(Some((f64::from(1.0f32 / 3.0), None)))
+        );
+        assert_that!(pipeline.decode_input("0.5"), equal_to(Some((0.5, None))));
+        assert_that!(pipeline.decode_input("1e39"), equal_to(None));
+
+        let mut pipeline = Pipeline::with_bases(vec![2.0]);
+        pipeline.stored_float = true;
+        assert_that!(
+            pipeline.decode_input("1/3"),
+            equal_to(Some((1.0 / 3.0, None)))
+        );
+        assert_that!(pipeline.decode_input("1e-400"), equal_to(Some((0.0, None))));
+    }
+
+    #[test]
+    fn test_pipeline_convert() {
+        let pipeline = Pipeline::parse("to=2 | digits=2 | round=half-even").unwrap();
+        assert_that!(
+            pipeline.expand_to(0.875, 2.0).to_string(),
+            equal_to("1.0;0;")
+        );
+        assert_that!(
+            pipeline.expand_to(-0.75, 2.0).to_string(),
+            equal_to("-0.1;1;")
+        );
+
+        let representations: Vec<String> = Pipeline::with_bases(vec![2.0, 8.0, 16.0])
+            .expand(0.75, None)
+            .iter()
+            .map(|expansion| expansion.to_string())
+            .collect();
+        assert_that!(representations, equal_to(vec!["0.1;1;", "0.6;", "0.12;"]));
+
+        let pipeline = Pipeline::parse("to=-2 | round=half-up").unwrap();
+        assert_that!(pipeline.expand_to(0.5, -2.0).to_string(), equal_to("1.1;"));
+        assert_that!(pipeline.expand_to(-0.5, -2.0).to_string(), equal_to("0.1;"));
+        assert!(Pipeline::parse("to=-1").is_err());
+
+        let expansion = Pipeline::with_bases(vec![1.5]).expand_to(0.5, 1.5);
+        assert_that!(expansion.to_string(), equal_to("0.0;1;0;0;0;0;0;1;"));
+        assert!(expansion.digits.iter().all(|&digit| digit <= 1));
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a batch runs before its progress bar appears, so that quick
+/// runs print nothing extra.
+pub const PROGRESS_DELAY: Duration = Duration::from_millis(250);
+
+/// How often the progress bar is redrawn at most.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The number of characters of the bar itself.
+const BAR_WIDTH: usize = 30;
+
+/// A progress bar on standard error for a batch of conversions, showing how
+/// many inputs are done, how fast and how long the rest should take.
+///
+/// Inputs can be counted from several threads at once. The bar is only drawn
+/// when standard error is a terminal and `--quiet` was not given.
+#[derive(Debug)]
+pub struct Progress {
+    /// The number of inputs in the batch.
+    total: usize,
+    /// The number of inputs converted so far.
+    done: AtomicUsize,
+    /// When the batch started.
+    started: Instant,
+    /// When the bar was last drawn, or `None` while it is not shown,
+    /// which is also the lock that keeps two threads from drawing at once.
+    drawn: Mutex<Option<Instant>>,
+    /// Whether the bar is drawn at all.
+    enabled: bool,
+}
+
+impl Progress {
+    /// Starts the progress of a batch of `total` inputs.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - The number of inputs in the batch.
+    /// * `quiet` - Whether `--quiet` was given, which hides the bar.
+    pub fn new(total: usize, quiet: bool) -> Progress {
+        Progress {
+            total,
+            done: AtomicUsize::new(0),
+            started: Instant::now(),
+            drawn: Mutex::new(None),
+            enabled: !quiet && io::stderr().is_terminal(),
+        }
+    }
+
+    /// Counts one more input as converted, and redraws the bar if it is due.
+    pub fn tick(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if !self.enabled {
+            return;
+        }
+        let elapsed = self.started.elapsed();
+        if elapsed < PROGRESS_DELAY {
+            return;
+        }
+        // A thread that finds another one drawing skips this redraw.
+        let Ok(mut drawn) = self.drawn.try_lock() else {
+            return;
+        };
+        if drawn.is_some_and(|last| last.elapsed() < REDRAW_INTERVAL) {
+            return;
+        }
+        let mut err = io::stderr().lock();
+        let _ = write!(err, "\r{}", render_progress(done, self.total, elapsed));
+        let _ = err.flush();
+        *drawn = Some(Instant::now());
+    }
+
+    /// Clears the bar, if it was drawn, before the results are printed.
+    pub fn finish(&self) {
+        let drawn = self.drawn.lock().map(|drawn| drawn.is_some());
+        if drawn.unwrap_or(false) {
+            let mut err = io::stderr().lock();
+            let _ = write!(err, "\r\x1b[2K");
+            let _ = err.flush();
+        }
+    }
+}
+
+/// Writes one line of the progress bar, such as
+/// `[#######·······] 250/1000 inputs, 500/s, ETA 2s`.
+///
+/// # Arguments
+///
+/// * `done` - The number of inputs converted so far.
+/// * `total` - The number of inputs in the batch.
+/// * `elapsed` - How long the batch has been running.
+///
+/// # Example
+///
+/// ```
+/// let line = render_progress(250, 1000, Duration::from_millis(500));
+/// assert!(line.ends_with("250/1000 inputs, 500/s, ETA 2s"));
+/// ```
+pub fn render_progress(done: usize, total: usize, elapsed: Duration) -> String {
+    let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(BAR_WIDTH);
+    let filled = filled.min(BAR_WIDTH);
+    let rate = done as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    let eta = if rate > 0.0 {
+        format!(
+            "{}s",
+            (total.saturating_sub(done) as f64 / rate).ceil() as u64
+        )
+    } else {
+        "?".to_string()
+    };
+    format!(
+        "[{}{}] {}/{} inputs, {:.0}/s, ETA {}",
+        "#".repeat(filled),
+        "·".repeat(BAR_WIDTH - filled),
+        done,
+        total,
+        rate,
+        eta
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_render_progress() {
+        assert_that!(
+            render_progress(250, 1000, Duration::from_millis(500)),
+            equal_to(format!(
+                "[{}{}] 250/1000 inputs, 500/s, ETA 2s",
+                "#".repeat(7),
+                "·".repeat(23)
+            ))
+        );
+        assert_that!(
+            render_progress(0, 10, Duration::from_secs(1)),
+            equal_to(format!("[{}] 0/10 inputs, 0/s, ETA ?", "·".repeat(30)))
+        );
+        assert_that!(
+            render_progress(10, 10, Duration::from_secs(2)),
+            equal_to(format!("[{}] 10/10 inputs, 5/s, ETA 0s", "#".repeat(30)))
+        );
+    }
+
+    #[test]
+    fn test_progress_counts_from_threads() {
+        let progress = Progress::new(100, true);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| (0..25).for_each(|_| progress.tick()));
+            }
+        });
+        assert_that!(progress.done.load(Ordering::Relaxed), equal_to(100));
+        progress.finish();
+    }
+}
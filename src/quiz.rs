@@ -0,0 +1,299 @@
+use std::io::{self, BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::answer::parse_written_digits;
+use crate::cli::flag_value;
+use crate::exit::write_error_message;
+use crate::find::{parse_digits, parse_single_base};
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// The settings of the `quiz` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuizOptions {
+    /// The base every answer is written in.
+    pub target_base: u64,
+    /// The number of questions asked.
+    pub rounds: usize,
+    /// The most fractional digits an answer needs.
+    pub digits: u32,
+    /// The seed of the questions, so that a quiz can be repeated, or `None`
+    /// for new questions every run.
+    pub seed: Option<u64>,
+}
+
+/// Parses the arguments of the `quiz` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `quiz`.
+///
+/// # Returns
+///
+/// The parsed `QuizOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- quiz --base 2 --rounds 10 --digits 4
+/// ```
+pub fn parse_quiz_args(args: &[String]) -> Result<QuizOptions, String> {
+    let mut options = QuizOptions {
+        target_base: 2,
+        rounds: 5,
+        digits: 4,
+        seed: None,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--rounds", &mut iter)? {
+            options.rounds = value
+                .parse()
+                .map_err(|_| format!("invalid round count `{}`", value))?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else if let Some(value) = flag_value(arg, "--seed", &mut iter)? {
+            options.seed = Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid seed `{}`", value))?,
+            );
+        } else {
+            return Err(format!("unknown quiz argument `{}`", arg));
+        }
+    }
+
+    check_difficulty("quiz", options.target_base, options.digits)?;
+    Ok(options)
+}
+
+/// Checks that questions can be made in a base with answers of up to a
+/// number of digits, for the subcommand named.
+pub fn check_difficulty(command: &str, target_base: u64, digits: u32) -> Result<(), String> {
+    if target_base < 2 {
+        return Err(format!("{} requires a base of at least 2", command));
+    }
+    if digits == 0 || target_base.checked_pow(digits).is_none() {
+        return Err(format!(
+            "{} cannot ask for {} digits in base {}",
+            command, digits, target_base
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the fractional digits of an answer such as `0.0101`, `.0101`,
+/// `0.0;1;0;1;` or `0.0 1 0 1`, whichever way its digits are separated.
+/// Without separators, every character is one digit, written with `0`-`9`
+/// and then `A`-`Z` in either case.
+///
+/// # Returns
+///
+/// The fractional digits without trailing zeros, or `None` if the answer has
+/// a non-zero integer part or a digit that does not fit the base.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(parse_answer("0.0;1;0;1;", 2), Some(vec![0, 1, 0, 1]));
+/// assert_eq!(parse_answer(".4c", 16), Some(vec![4, 12]));
+/// ```
+pub fn parse_answer(answer: &str, target_base: u64) -> Option<Vec<u32>> {
+    let decoded = parse_written_digits(answer, target_base)?;
+    if decoded.negative || !decoded.integer_digits.is_empty() {
+        return None;
+    }
+    let mut digits = decoded.digits;
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    Some(digits)
+}
+
+/// The greatest common divisor of two numbers.
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Whether fractions with this denominator end in base 10, i.e. whether it
+/// has no prime factors besides 2 and 5.
+fn ends_in_decimal(mut denominator: u64) -> bool {
+    for factor in [2, 5] {
+        while denominator.is_multiple_of(factor) {
+            denominator /= factor;
+        }
+    }
+    denominator == 1
+}
+
+/// Generates the questions of a quiz: fractions strictly between 0 and 1
+/// whose expansion in the base ends within the digit limit, in lowest terms.
+pub fn questions(options: &QuizOptions, seed: u64) -> Vec<Fraction> {
+    let denominator = options.target_base.pow(options.digits);
+    let mut state = seed;
+    (0..options.rounds)
+        .map(|_| {
+            // splitmix64, which spreads out even small seeds such as 1 and 2
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut mixed = state;
+            mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            mixed ^= mixed >> 31;
+            let numerator = 1 + mixed % (denominator - 1).max(1);
+            let divisor = gcd(numerator, denominator);
+            Fraction {
+                negative: false,
+                numerator: BigUint::from(numerator / divisor),
+                denominator: BigUint::from(denominator / divisor),
+            }
+        })
+        .collect()
+}
+
+/// A question as it is asked: its fraction, followed by its decimal value
+/// when that ends, as in `5/16 (= 0.3125)`.
+pub fn question_text(question: &Fraction) -> String {
+    if question.denominator.to_u64().is_some_and(ends_in_decimal) {
+        format!(
+            "{}/{} (= {})",
+            question.numerator,
+            question.denominator,
+            question.value()
+        )
+    } else {
+        format!("{}/{}", question.numerator, question.denominator)
+    }
+}
+
+/// A seed that gives new questions every run, from the current time.
+pub fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Asks every question, reading one answer per line from `answers`, and
+/// tells after each whether it was right. An empty line, `q` or the end of
+/// the input ends the quiz early.
+///
+/// # Returns
+///
+/// The number of correct answers and the number of questions answered.
+pub fn run_quiz(
+    options: &QuizOptions,
+    questions: &[Fraction],
+    mut answers: impl BufRead,
+    out: &mut dyn Write,
+) -> io::Result<(usize, usize)> {
+    let pipeline = Pipeline {
+        digits: options.digits,
+        ..Pipeline::with_bases(vec![options.target_base as f64])
+    };
+    let mut correct = 0;
+    let mut asked = 0;
+
+    for (round, question) in questions.iter().enumerate() {
+        let expansion =
+            pipeline.expand_input(question.value(), Some(question), options.target_base as f64);
+        write!(
+            out,
+            "{}/{}: write {} in base {}: ",
+            round + 1,
+            questions.len(),
+            question_text(question),
+            options.target_base
+        )?;
+        out.flush()?;
+
+        let mut answer = String::new();
+        if answers.read_line(&mut answer)? == 0 || matches!(answer.trim(), "" | "q") {
+            writeln!(out)?;
+            break;
+        }
+        asked += 1;
+        if parse_answer(&answer, options.target_base).as_ref() == Some(&expansion.digits) {
+            correct += 1;
+            writeln!(out, "correct! ({}/{})", correct, asked)?;
+        } else {
+            writeln!(
+                out,
+                "not quite: it is {} ({}/{})",
+                expansion, correct, asked
+            )?;
+        }
+    }
+
+    writeln!(out, "Score: {}/{}", correct, asked)?;
+    Ok((correct, asked))
+}
+
+/// Runs the `quiz` subcommand on the terminal.
+pub fn display_quiz(options: &QuizOptions) {
+    let seed = options.seed.unwrap_or_else(time_seed);
+    let questions = questions(options, seed);
+    if let Err(error) = run_quiz(options, &questions, io::stdin().lock(), &mut io::stdout()) {
+        eprintln!("error: {}", write_error_message(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use num_traits::Zero;
+
+    #[test]
+    fn test_parse_answer() {
+        assert_that!(parse_answer("0.0101", 2), equal_to(Some(vec![0, 1, 0, 1])));
+        assert_that!(
+            parse_answer("0.0 1 0 1 0", 2),
+            equal_to(Some(vec![0, 1, 0, 1]))
+        );
+        assert_that!(parse_answer("0.15;30;", 60), equal_to(Some(vec![15, 30])));
+        assert_that!(parse_answer("0.", 2), equal_to(Some(vec![])));
+        assert_that!(parse_answer("0.2", 2), equal_to(None));
+        assert_that!(parse_answer("1.1", 2), equal_to(None));
+        assert_that!(parse_answer("0.x", 10), equal_to(None));
+    }
+
+    #[test]
+    fn test_quiz_rounds() {
+        let options = QuizOptions {
+            target_base: 2,
+            rounds: 3,
+            digits: 4,
+            seed: Some(7),
+        };
+        let questions = questions(&options, 7);
+        assert_that!(questions.len(), equal_to(3));
+        assert!(questions
+            .iter()
+            .all(|question| question.numerator < question.denominator
+                && (BigUint::from(16u32) % &question.denominator).is_zero()));
+
+        let fixed = [
+            Fraction::parse("5/16").unwrap(),
+            Fraction::parse("3/4").unwrap(),
+            Fraction::parse("1/8").unwrap(),
+        ];
+        let mut out = Vec::new();
+        let score = run_quiz(&options, &fixed, "0.0101\n0.10\n".as_bytes(), &mut out).unwrap();
+        assert_that!(score, equal_to((1, 2)));
+        let transcript = String::from_utf8(out).unwrap();
+        assert!(transcript.starts_with("1/3: write 5/16 (= 0.3125) in base 2: correct! (1/1)\n"));
+        assert!(transcript.contains("not quite: it is 0.1;1; (1/2)"));
+        assert!(transcript.ends_with("Score: 1/2\n"));
+
+        assert!(parse_quiz_args(&["--base".to_string(), "1".to_string()]).is_err());
+        assert!(parse_quiz_args(&["--digits=100".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::convert::Representation;
+use crate::rational::{Fraction, Ratio};
+
+/// A number whose fractional places each have their own radix, such as
+/// hours, minutes and seconds for the radices 24, 60 and 60 of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixedRadix {
+    /// Whether the converted value was negative.
+    pub negative: bool,
+    /// The whole units.
+    pub integer: BigUint,
+    /// The radices of the places, the last one repeating for every place
+    /// beyond the list.
+    pub radices: Vec<u32>,
+    /// The fractional digits, the first below the first radix and so on.
+    pub digits: Vec<u32>,
+    /// The part of the value left over after the last digit, measured in
+    /// units of the last digit's place.
+    pub remainder: f64,
+}
+
+/// Parses the value of `--radices`: a comma-separated list of integer
+/// radices of at least 2, such as `24,60,60`.
+pub fn parse_radices(value: &str) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|radix| {
+            radix
+                .parse::<u32>()
+                .ok()
+                .filter(|&radix| radix >= 2)
+                .ok_or_else(|| format!("invalid radix `{}`", radix))
+        })
+        .collect()
+}
+
+/// Extracts the fractional digits of `rest / denominator` with a radix per
+/// place, multiplying by each radix in turn like long division does by a
+/// single base.
+///
+/// # Returns
+///
+/// The digits, stopping early once the fraction ends, and the numerator of
+/// what is left over after the last one.
+pub fn mixed_digits(
+    mut rest: BigUint,
+    denominator: &BigUint,
+    radices: impl Iterator<Item = u32>,
+) -> (Vec<u32>, BigUint) {
+    let mut digits = Vec::new();
+    for radix in radices {
+        if rest.is_zero() {
+            break;
+        }
+        rest *= radix;
+        digits.push((&rest / denominator).to_u32().unwrap_or(0));
+        rest %= denominator;
+    }
+    (digits, rest)
+}
+
+/// Converts an input into whole units and fractional places with the given
+/// radices, using its exact value: the fraction it was written as, if any,
+/// or else the exact value of its `f64`.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `fraction` - The exact fraction the input was written as, if any.
+/// * `radices` - The radix of every place, the last repeating beyond the list.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(expand_mixed_radix(1.75, None, &[24, 60, 60], 8).to_string(), "1.18;");
+/// ```
+pub fn expand_mixed_radix(
+    decimal: f64,
+    fraction: Option<&Fraction>,
+    radices: &[u32],
+    max_digits: u32,
+) -> MixedRadix {
+    let mut mixed = MixedRadix {
+        negative: decimal < 0.0,
+        integer: BigUint::zero(),
+        radices: radices.to_vec(),
+        digits: Vec::new(),
+        remainder: 0.0,
+    };
+    let exact = match fraction {
+        Some(fraction) => Ratio::from(fraction.clone()),
+        None => match Ratio::from_f64(decimal) {
+            Some(exact) => exact,
+            None => return mixed,
+        },
+    };
+    let last = radices.last().copied().unwrap_or(10);
+    let places = radices.iter().copied().chain(std::iter::repeat(last));
+
+    mixed.integer = &exact.numerator / &exact.denominator;
+    let (digits, rest) = mixed_digits(
+        &exact.numerator % &exact.denominator,
+        &exact.denominator,
+        places.take(max_digits as usize),
+    );
+    mixed.digits = digits;
+    mixed.remainder = Fraction {
+        negative: false,
+        numerator: rest,
+        denominator: exact.denominator,
+    }
+    .value();
+    mixed
+}
+
+impl Representation for MixedRadix {
+    fn value(&self) -> f64 {
+        let last = self.radices.last().copied().unwrap_or(10);
+        let places = self.radices.iter().copied().chain(std::iter::repeat(last));
+        let (fraction, _) =
+            self.digits
+                .iter()
+                .zip(places)
+                .fold((0.0, 1.0), |(sum, place), (&digit, radix)| {
+                    let place = place / radix as f64;
+                    (sum + digit as f64 * place, place)
+                });
+        let value = self.integer.to_f64().unwrap_or(f64::INFINITY) + fraction;
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        self.remainder == 0.0
+    }
+}
+
+impl fmt::Display for MixedRadix {
+    /// Writes the whole units in decimal and then every place, as in
+    /// `3.12;30;`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative && !(self.integer.is_zero() && self.digits.is_empty()) {
+            write!(f, "-")?;
+        }
+        write!(f, "{}.", self.integer)?;
+        for digit in &self.digits {
+            write!(f, "{};", digit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_mixed_radix() {
+        let day = [24, 60, 60];
+        assert_that!(
+            expand_mixed_radix(0.5, None, &day, 8).to_string(),
+            equal_to("0.12;")
+        );
+        let fraction = Fraction::parse("1/7").unwrap();
+        let seventh = expand_mixed_radix(fraction.value(), Some(&fraction), &day, 4);
+        // A seventh of a day is 3:25:42.857…, and 0.857… seconds is 51;25;… in base 60.
+        assert_that!(seventh.to_string(), equal_to("0.3;25;42;51;"));
+        assert!(!seventh.is_exact());
+        assert_that!(seventh.value(), close_to(1.0 / 7.0, 1e-6));
+        assert_that!(
+            expand_mixed_radix(-2.25, None, &[2], 8).to_string(),
+            equal_to("-2.0;1;")
+        );
+
+        assert_that!(parse_radices("24, 60,60"), equal_to(Ok(vec![24, 60, 60])));
+        assert!(parse_radices("24,1").is_err());
+        assert!(parse_radices("24,x").is_err());
+    }
+}
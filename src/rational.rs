@@ -0,0 +1,677 @@
+use std::fmt;
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::terminate::gcd;
+
+/// The title of the column giving the exact fraction of every input.
+pub const FRACTION_HEADER: &str = "Fraction";
+
+/// The most significant decimal digits an `f64` needs to be written
+/// exactly; longer decimals are kept as fractions so that no digit is lost.
+pub const F64_DIGITS: usize = 17;
+
+/// The largest decimal exponent, such as the 400 of `1e400`, kept exactly.
+const LARGEST_EXPONENT: i64 = 10_000;
+
+/// An input written as an exact fraction such as `1/3`, or as a decimal
+/// with more digits than an `f64` holds, kept alongside its `f64` value so
+/// that backends able to use it are not limited to the nearest binary
+/// number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fraction {
+    /// Whether the fraction is negative.
+    pub negative: bool,
+    /// The numerator, without its sign.
+    pub numerator: BigUint,
+    /// The denominator, never zero.
+    pub denominator: BigUint,
+}
+
+impl Fraction {
+    /// Parses a fraction written as `p/q`, with an optional sign in front.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let third = Fraction::parse("-1/3").unwrap();
+    /// assert_eq!(third.negative, true);
+    /// assert_eq!(third.denominator, BigUint::from(3u32));
+    /// assert_eq!(Fraction::parse("0.5"), None);
+    /// ```
+    pub fn parse(arg: &str) -> Option<Fraction> {
+        let (negative, unsigned) = split_sign(arg);
+        let (numerator, denominator) = unsigned.split_once('/')?;
+        let numerator = parse_digits(numerator.trim())?;
+        let denominator = parse_digits(denominator.trim())?;
+        if denominator.is_zero() {
+            return None;
+        }
+        Some(Fraction {
+            negative,
+            numerator,
+            denominator,
+        })
+    }
+
+    /// Parses a mixed number such as `1+3/4` or `1_3/4`, one and three
+    /// quarters, with an optional sign in front that applies to all of it.
+    ///
+    /// Since `_` also separates digits, it only does so in a fraction
+    /// between groups of three, as in `1_000/3`, and otherwise starts the
+    /// fraction of a mixed number, which has to be proper and have no
+    /// leading zero. A fraction that reads both ways, as `1_300/400` does,
+    /// is refused rather than guessed at.
+    ///
+    /// # Returns
+    ///
+    /// The mixed number, `None` if `arg` is not one, or why its `_`s cannot
+    /// be read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mixed = Fraction::parse_mixed("-1+3/4").unwrap().unwrap();
+    /// assert_eq!(mixed.value(), -1.75);
+    /// assert_eq!(Fraction::parse_mixed("2_1/3").unwrap().unwrap().numerator, BigUint::from(7u32));
+    /// assert_eq!(Fraction::parse_mixed("1_000/3"), Ok(None));
+    /// assert!(Fraction::parse_mixed("1_300/400").is_err());
+    /// ```
+    pub fn parse_mixed(arg: &str) -> Result<Option<Fraction>, String> {
+        let (negative, unsigned) = split_sign(arg);
+        let Some((top, denominator)) = unsigned.split_once('/') else {
+            return Ok(None);
+        };
+        if !is_digits(&unsigned.replace(['_', '+', '/'], "")) || !unsigned.contains(['_', '+']) {
+            return Ok(None);
+        }
+        let separators = || {
+            "an `_` in a fraction separates groups of three digits, or starts the proper \
+             fraction of a mixed number"
+                .to_string()
+        };
+        let (whole, numerator, proper) = match top.split_once('+') {
+            Some((whole, numerator)) => (whole, numerator, false),
+            None => match top.rsplit_once('_') {
+                Some((whole, numerator)) => (whole, numerator, true),
+                // Only the denominator of a fraction such as `1/1_000` has
+                // `_`s, which can only separate digits.
+                None if is_grouped_in_thousands(denominator) => return Ok(None),
+                None => return Err(separators()),
+            },
+        };
+        let (Some(whole), Some(numerator_value), Some(denominator)) = (
+            parse_grouped_digits(whole),
+            parse_grouped_digits(numerator),
+            parse_grouped_digits(denominator),
+        ) else {
+            return Err(separators());
+        };
+        if denominator.is_zero() {
+            return Ok(None);
+        }
+        if proper {
+            let mixed = numerator_value < denominator && !numerator.starts_with('0');
+            match (mixed, is_grouped_in_thousands(top)) {
+                (true, true) => {
+                    return Err(format!(
+                        "it reads both as {}/{denominator} and as \
+                         {whole}+{numerator_value}/{denominator}; write one of those",
+                        top.replace('_', "")
+                    ))
+                }
+                (false, true) => return Ok(None),
+                (false, false) => return Err(separators()),
+                (true, false) => {}
+            }
+        }
+        Ok(Some(Fraction {
+            negative,
+            numerator: whole * &denominator + numerator_value,
+            denominator,
+        }))
+    }
+
+    /// Parses a decimal such as `0.1234567890123456789012345` or `1.5e-30`
+    /// exactly, but only if it has more significant digits than
+    /// `F64_DIGITS`, since shorter ones are written exactly enough by their
+    /// `f64`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let long = Fraction::parse_long_decimal("-0.123456789012345678901").unwrap();
+    /// assert_eq!(long.numerator, BigUint::from(123456789012345678901u128));
+    /// assert_eq!(long.denominator, BigUint::from(10u32).pow(21));
+    /// assert_eq!(Fraction::parse_long_decimal("0.1"), None);
+    /// ```
+    pub fn parse_long_decimal(arg: &str) -> Option<Fraction> {
+        parse_decimal_digits(arg, F64_DIGITS + 1)
+    }
+
+    /// Parses a decimal such as `0.1` or `1.5e-30` exactly, however few
+    /// digits it has.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let tenth = Fraction::parse_decimal("0.1").unwrap();
+    /// assert_eq!(tenth.denominator, BigUint::from(10u32));
+    /// assert_eq!(Fraction::parse_decimal("1/3"), None);
+    /// ```
+    pub fn parse_decimal(arg: &str) -> Option<Fraction> {
+        parse_decimal_digits(arg, 0)
+    }
+
+    /// Parses a number written in binary, octal or hexadecimal behind a
+    /// `0b`, `0o` or `0x` prefix, such as `0b0.101` or `-0x0.C`, exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let eighths = Fraction::parse_prefixed("0b0.101").unwrap();
+    /// assert_eq!(eighths.value(), 0.625);
+    /// assert_eq!(Fraction::parse_prefixed("0x1A.8").unwrap().value(), 26.5);
+    /// assert_eq!(Fraction::parse_prefixed("0b0.2"), None);
+    /// ```
+    pub fn parse_prefixed(arg: &str) -> Option<Fraction> {
+        let (negative, unsigned) = split_sign(arg);
+        let prefix = unsigned.get(..2)?.to_ascii_lowercase();
+        let radix = match prefix.as_str() {
+            "0b" => 2,
+            "0o" => 8,
+            "0x" => 16,
+            _ => return None,
+        };
+        let mut fraction = parse_unsigned_in_base(&unsigned[2..], radix)?;
+        fraction.negative = negative;
+        Some(fraction)
+    }
+
+    /// Parses a number written in a base from 2 to 36, with `0`-`9` and then
+    /// `a`-`z` (or `A`-`Z`) as digits, such as `1A.8` in base 16, exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(Fraction::parse_in_base("-0.12", 3).unwrap().value(), -5.0 / 9.0);
+    /// assert_eq!(Fraction::parse_in_base("0.3", 3), None);
+    /// ```
+    pub fn parse_in_base(arg: &str, radix: u32) -> Option<Fraction> {
+        if !(2..=36).contains(&radix) {
+            return None;
+        }
+        let (negative, unsigned) = split_sign(arg);
+        let mut fraction = parse_unsigned_in_base(unsigned, radix)?;
+        fraction.negative = negative;
+        Some(fraction)
+    }
+
+    /// The nearest `f64` to the fraction, or close to it for fractions too
+    /// large to divide as two `f64`s.
+    pub fn value(&self) -> f64 {
+        // Beyond its first thousand bits, neither part changes the quotient.
+        let shift = self
+            .numerator
+            .bits()
+            .max(self.denominator.bits())
+            .saturating_sub(1000);
+        let part = |part: &BigUint| (part >> shift).to_f64().unwrap_or(f64::INFINITY);
+        let value = part(&self.numerator) / part(&self.denominator);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+/// Parses a decimal exactly if it has at least `shortest` significant digits.
+fn parse_decimal_digits(arg: &str, shortest: usize) -> Option<Fraction> {
+    let (negative, unsigned) = split_sign(arg);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (unsigned, 0),
+    };
+    let (integer, decimals) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer, decimals);
+    let significant = digits.trim_start_matches('0').trim_end_matches('0');
+    if significant.len() < shortest || exponent.abs() > LARGEST_EXPONENT {
+        return None;
+    }
+
+    let numerator = parse_digits(&digits)?;
+    let ten = BigUint::from(10u32);
+    let places = decimals.len() as i64 - exponent;
+    let (numerator, denominator) = if places >= 0 {
+        (numerator, ten.pow(places as u32))
+    } else {
+        (numerator * ten.pow(-places as u32), BigUint::from(1u32))
+    };
+    Some(Fraction {
+        negative,
+        numerator,
+        denominator,
+    })
+}
+
+/// Splits an optional `-` or `+` off a number.
+fn split_sign(arg: &str) -> (bool, &str) {
+    match arg.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, arg.strip_prefix('+').unwrap_or(arg)),
+    }
+}
+
+/// Parses the digits of an unsigned number in a base of at most 36, with
+/// an optional point.
+fn parse_unsigned_in_base(unsigned: &str, radix: u32) -> Option<Fraction> {
+    let (integer, digits) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let all = format!("{}{}", integer, digits);
+    if all.is_empty() || !all.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    Some(Fraction {
+        negative: false,
+        numerator: BigUint::parse_bytes(all.as_bytes(), radix)?,
+        denominator: BigUint::from(radix).pow(digits.len() as u32),
+    })
+}
+
+/// Parses a run of decimal digits that may be split into groups of three
+/// by single `_`s, as in `1_000`.
+fn parse_grouped_digits(digits: &str) -> Option<BigUint> {
+    if !is_grouped_in_thousands(digits) {
+        return None;
+    }
+    parse_digits(&digits.replace('_', ""))
+}
+
+/// Whether a run of decimal digits is either unbroken or split by single
+/// `_`s into groups of three after a first group of one to three, as in
+/// `1_000_000`.
+fn is_grouped_in_thousands(digits: &str) -> bool {
+    let mut groups = digits.split('_');
+    let first = groups.next().unwrap_or_default();
+    is_digits(first)
+        && (!digits.contains('_') || first.len() <= 3)
+        && groups.all(|group| group.len() == 3 && is_digits(group))
+}
+
+/// Whether a text is a non-empty run of decimal digits.
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|digit| digit.is_ascii_digit())
+}
+
+/// Parses a non-empty run of decimal digits of any length.
+fn parse_digits(digits: &str) -> Option<BigUint> {
+    if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+        return None;
+    }
+    BigUint::parse_bytes(digits.as_bytes(), 10)
+}
+
+/// An exact rational number whose numerator and denominator may be as large
+/// as needed, such as the exact value of any finite `f64`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ratio {
+    /// Whether the number is negative.
+    pub negative: bool,
+    /// The numerator, without its sign.
+    pub numerator: BigUint,
+    /// The denominator, never zero.
+    pub denominator: BigUint,
+}
+
+impl Ratio {
+    /// The exact value of a finite `f64`, with the power of two in its
+    /// denominator reduced as far as it goes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let tenth = Ratio::from_f64(0.1).unwrap();
+    /// assert_eq!(tenth.numerator, BigUint::from(3602879701896397u64));
+    /// assert_eq!(tenth.denominator, BigUint::from(1u64 << 55));
+    /// ```
+    pub fn from_f64(value: f64) -> Option<Ratio> {
+        if !value.is_finite() {
+            return None;
+        }
+        let bits = value.to_bits();
+        let biased = (bits >> 52 & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        // Subnormal numbers have no implicit leading one and the smallest exponent.
+        let (mantissa, exponent) = if biased == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, biased - 1075)
+        };
+
+        let (numerator, denominator) = if mantissa == 0 {
+            (BigUint::from(0u32), BigUint::from(1u32))
+        } else if exponent >= 0 {
+            (BigUint::from(mantissa) << exponent, BigUint::from(1u32))
+        } else {
+            let shift = (mantissa.trailing_zeros() as i64).min(-exponent);
+            (
+                BigUint::from(mantissa >> shift),
+                BigUint::from(1u32) << (-exponent - shift),
+            )
+        };
+        Some(Ratio {
+            negative: value < 0.0,
+            numerator,
+            denominator,
+        })
+    }
+}
+
+impl Ratio {
+    /// The same number in lowest terms.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let eighth = Ratio::from(Fraction::parse("125/1000").unwrap()).reduced();
+    /// assert_eq!(eighth.to_string(), "1/8");
+    /// ```
+    pub fn reduced(self) -> Ratio {
+        let divisor = gcd(self.numerator.clone(), self.denominator.clone());
+        Ratio {
+            negative: self.negative && !self.numerator.is_zero(),
+            numerator: self.numerator / &divisor,
+            denominator: self.denominator / &divisor,
+        }
+    }
+}
+
+impl Ratio {
+    /// The sum of two numbers, in lowest terms.
+    pub fn plus(&self, other: &Ratio) -> Ratio {
+        let signed = |value: &Ratio, scale: &BigUint| {
+            let sign = if value.negative {
+                Sign::Minus
+            } else {
+                Sign::Plus
+            };
+            BigInt::from_biguint(sign, &value.numerator * scale)
+        };
+        let sum = signed(self, &other.denominator) + signed(other, &self.denominator);
+        Ratio {
+            negative: sum.sign() == Sign::Minus,
+            numerator: sum.magnitude().clone(),
+            denominator: &self.denominator * &other.denominator,
+        }
+        .reduced()
+    }
+
+    /// The difference of two numbers, in lowest terms.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let half = Ratio::from(Fraction::parse("1/2").unwrap());
+    /// let third = Ratio::from(Fraction::parse("1/3").unwrap());
+    /// assert_eq!(third.minus(&half).to_string(), "-1/6");
+    /// ```
+    pub fn minus(&self, other: &Ratio) -> Ratio {
+        self.plus(&other.negated())
+    }
+
+    /// The product of two numbers, in lowest terms.
+    pub fn times(&self, other: &Ratio) -> Ratio {
+        Ratio {
+            negative: self.negative != other.negative,
+            numerator: &self.numerator * &other.numerator,
+            denominator: &self.denominator * &other.denominator,
+        }
+        .reduced()
+    }
+
+    /// The quotient of two numbers, in lowest terms, or `None` when `other`
+    /// is zero.
+    pub fn divided_by(&self, other: &Ratio) -> Option<Ratio> {
+        if other.numerator.is_zero() {
+            return None;
+        }
+        Some(
+            Ratio {
+                negative: self.negative != other.negative,
+                numerator: &self.numerator * &other.denominator,
+                denominator: &self.denominator * &other.numerator,
+            }
+            .reduced(),
+        )
+    }
+
+    /// The number with its sign flipped.
+    pub fn negated(&self) -> Ratio {
+        Ratio {
+            negative: !self.negative && !self.numerator.is_zero(),
+            ..self.clone()
+        }
+    }
+}
+
+impl fmt::Display for Ratio {
+    /// Writes the number as `-p/q`, or as `p` when the denominator is one.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.numerator)?;
+        if !self.denominator.is_one() {
+            write!(f, "/{}", self.denominator)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Ratio> for Fraction {
+    fn from(value: Ratio) -> Fraction {
+        Fraction {
+            negative: value.negative,
+            numerator: value.numerator,
+            denominator: value.denominator,
+        }
+    }
+}
+
+impl From<Fraction> for Ratio {
+    fn from(fraction: Fraction) -> Ratio {
+        Ratio {
+            negative: fraction.negative,
+            numerator: fraction.numerator,
+            denominator: fraction.denominator,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_parse_fraction() {
+        assert_that!(
+            Fraction::parse("3/4"),
+            equal_to(Some(Fraction {
+                negative: false,
+                numerator: BigUint::from(3u32),
+                denominator: BigUint::from(4u32)
+            }))
+        );
+        assert_that!(
+            Fraction::parse("-1/3").map(|f| f.value()),
+            equal_to(Some(-1.0 / 3.0))
+        );
+        assert_that!(Fraction::parse("1/0"), equal_to(None));
+        assert_that!(Fraction::parse("a/3"), equal_to(None));
+        assert_that!(Fraction::parse("0.25"), equal_to(None));
+
+        let mixed = |arg| Fraction::parse_mixed(arg).map(|f| f.map(|f| f.value()));
+        assert_that!(mixed("1+3/4"), equal_to(Ok(Some(1.75))));
+        assert_that!(mixed("1_3/4"), equal_to(Ok(Some(1.75))));
+        assert_that!(mixed("-2+1/2"), equal_to(Ok(Some(-2.5))));
+        assert_that!(mixed("1_000+1/8"), equal_to(Ok(Some(1000.125))));
+        assert_that!(mixed("1_000_3/4"), equal_to(Ok(Some(1000.75))));
+        assert_that!(mixed("1+5/4"), equal_to(Ok(Some(2.25))));
+        assert_that!(mixed("10_000/7"), equal_to(Ok(None)));
+        assert_that!(mixed("1_000/4"), equal_to(Ok(None)));
+        assert_that!(mixed("1_300/200"), equal_to(Ok(None)));
+        assert_that!(mixed("1/1_000"), equal_to(Ok(None)));
+        assert_that!(mixed("1+3/0"), equal_to(Ok(None)));
+        assert_that!(mixed("1e+5"), equal_to(Ok(None)));
+        assert_that!(mixed("3/4"), equal_to(Ok(None)));
+        assert_that!(
+            mixed("1_300/400"),
+            equal_to(Err(
+                "it reads both as 1300/400 and as 1+300/400; write one of those".to_string()
+            ))
+        );
+        assert!(mixed("1_5/4").is_err());
+        assert!(mixed("1_0/4").is_err());
+        assert!(mixed("10_00/7").is_err());
+        assert!(mixed("1/10_00").is_err());
+        assert!(mixed("1_00+1/2").is_err());
+        assert_that!(
+            Fraction::parse("1/123456789012345678901234567890").map(|f| f.value()),
+            equal_to(Some(1.0 / 123456789012345678901234567890.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_long_decimal() {
+        let long = Fraction::parse_long_decimal("3.14159265358979323846264338327950288").unwrap();
+        assert_that!(
+            long.numerator.to_string(),
+            equal_to("314159265358979323846264338327950288")
+        );
+        assert_that!(
+            long.denominator.clone(),
+            equal_to(BigUint::from(10u32).pow(35))
+        );
+        assert_that!(long.value(), equal_to(std::f64::consts::PI));
+
+        let scaled = Fraction::parse_long_decimal("-1234567890.12345678901e5").unwrap();
+        assert!(scaled.negative);
+        assert_that!(
+            scaled.denominator.clone(),
+            equal_to(BigUint::from(1_000_000u32))
+        );
+        assert_that!(
+            Fraction::parse_long_decimal("0.10000000000000000000000"),
+            equal_to(None)
+        );
+        assert_that!(
+            Fraction::parse_long_decimal("0.1234567890123456789x"),
+            equal_to(None)
+        );
+        assert_that!(
+            Ratio::from(Fraction::parse_decimal("2.50e-1").unwrap())
+                .reduced()
+                .to_string(),
+            equal_to("1/4")
+        );
+    }
+
+    #[test]
+    fn test_parse_prefixed() {
+        let value = |arg| Fraction::parse_prefixed(arg).map(|fraction| fraction.value());
+        assert_that!(value("0b0.101"), equal_to(Some(0.625)));
+        assert_that!(value("0x0.C"), equal_to(Some(0.75)));
+        assert_that!(value("-0O7.4"), equal_to(Some(-7.5)));
+        assert_that!(value("0xff"), equal_to(Some(255.0)));
+        assert_that!(value("0x."), equal_to(None));
+        assert_that!(value("0o0.8"), equal_to(None));
+        assert_that!(value("0.5"), equal_to(None));
+        assert_that!(value("0x-1"), equal_to(None));
+
+        let value =
+            |arg, radix| Fraction::parse_in_base(arg, radix).map(|fraction| fraction.value());
+        assert_that!(value("1A.8", 16), equal_to(Some(26.5)));
+        assert_that!(value("-0.z", 36), equal_to(Some(-35.0 / 36.0)));
+        assert_that!(value("0.2", 2), equal_to(None));
+        assert_that!(value("0.1", 37), equal_to(None));
+    }
+
+    #[test]
+    fn test_exact_ratio_of_f64() {
+        let ratio = |value| {
+            Ratio::from_f64(value).map(|ratio| {
+                (
+                    ratio.negative,
+                    ratio.numerator.to_string(),
+                    ratio.denominator.to_string(),
+                )
+            })
+        };
+        assert_that!(
+            ratio(-0.75),
+            equal_to(Some((true, "3".to_string(), "4".to_string())))
+        );
+        assert_that!(
+            ratio(6.0),
+            equal_to(Some((false, "6".to_string(), "1".to_string())))
+        );
+        assert_that!(
+            ratio(0.0),
+            equal_to(Some((false, "0".to_string(), "1".to_string())))
+        );
+        assert_that!(
+            ratio(f64::MIN_POSITIVE / 4.0),
+            equal_to(Some((
+                false,
+                "1".to_string(),
+                (BigUint::from(1u32) << 1024usize).to_string()
+            )))
+        );
+        assert_that!(ratio(f64::NAN), equal_to(None));
+        assert_that!(
+            Ratio::from(Fraction::parse("-1/3").unwrap()).denominator,
+            equal_to(BigUint::from(3u32))
+        );
+    }
+
+    #[test]
+    fn test_reduced_ratio() {
+        let reduced = |arg| {
+            Ratio::from(Fraction::parse(arg).unwrap())
+                .reduced()
+                .to_string()
+        };
+        assert_that!(reduced("125/1000"), equal_to("1/8"));
+        assert_that!(reduced("-6/4"), equal_to("-3/2"));
+        assert_that!(reduced("8/4"), equal_to("2"));
+        assert_that!(reduced("-0/5"), equal_to("0"));
+    }
+
+    #[test]
+    fn test_ratio_arithmetic() {
+        let ratio = |arg| Ratio::from(Fraction::parse(arg).unwrap());
+        assert_that!(
+            ratio("1/10").plus(&ratio("2/10")).to_string(),
+            equal_to("3/10")
+        );
+        assert_that!(
+            ratio("1/3").minus(&ratio("1/2")).to_string(),
+            equal_to("-1/6")
+        );
+        assert_that!(ratio("1/2").minus(&ratio("1/2")).to_string(), equal_to("0"));
+        assert_that!(
+            ratio("-2/3").times(&ratio("-3/4")).to_string(),
+            equal_to("1/2")
+        );
+        assert_that!(
+            ratio("1/3")
+                .divided_by(&ratio("-2/1"))
+                .map(|r| r.to_string()),
+            equal_to(Some("-1/6".to_string()))
+        );
+        assert_that!(ratio("1/3").divided_by(&ratio("0/1")), equal_to(None));
+        assert_that!(ratio("0/1").negated().negative, equal_to(false));
+    }
+}
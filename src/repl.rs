@@ -0,0 +1,383 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::flag_value;
+use crate::conversion::Conversion;
+use crate::editor::{LineSource, Terminal};
+use crate::exit::write_error_message;
+use crate::find::parse_digits;
+use crate::format::{write_conversions, OutputFormat};
+use crate::pipeline::{parse_base_list, Pipeline};
+#[cfg(feature = "serde")]
+use crate::schema::record_run_options;
+
+/// The settings of the `repl` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplOptions {
+    /// The bases every entered number is converted to.
+    pub target_bases: Vec<f64>,
+    /// The maximum number of fractional digits to produce.
+    pub digits: u32,
+    /// The file the entered lines are kept in between sessions, or `None`
+    /// with `--no-history`.
+    pub history: Option<PathBuf>,
+}
+
+/// The history file used without `--history`:
+/// `$XDG_STATE_HOME/base-convert/history`, or
+/// `~/.local/state/base-convert/history` when that variable is not set.
+pub fn default_history_path() -> Option<PathBuf> {
+    let directory = env::var_os("XDG_STATE_HOME")
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+        })?;
+    Some(directory.join("base-convert").join("history"))
+}
+
+/// Parses the arguments of the `repl` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `repl`.
+///
+/// # Returns
+///
+/// The parsed `ReplOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- repl --base 2,16 --digits 12 --history worksheet.history
+/// ```
+pub fn parse_repl_args(args: &[String]) -> Result<ReplOptions, String> {
+    let mut options = ReplOptions {
+        target_bases: vec![2.0],
+        digits: Pipeline::with_bases(Vec::new()).digits,
+        history: default_history_path(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_bases = parse_base_list(value)?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else if let Some(value) = flag_value(arg, "--history", &mut iter)? {
+            options.history = Some(PathBuf::from(value));
+        } else if arg == "--no-history" {
+            options.history = None;
+        } else {
+            return Err(format!("unknown repl argument `{}`", arg));
+        }
+    }
+    Ok(options)
+}
+
+/// The lines entered at the prompt, in this session and the ones before it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct History {
+    /// Every line entered, oldest first.
+    pub entries: Vec<String>,
+    /// The file every new line is appended to, if any.
+    pub path: Option<PathBuf>,
+}
+
+impl History {
+    /// Reads the lines kept in a history file, starting empty when there is
+    /// no file yet.
+    pub fn load(path: Option<PathBuf>) -> History {
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(String::from).collect())
+            .unwrap_or_default();
+        History { entries, path }
+    }
+
+    /// Remembers a line, appending it to the history file, whose directory
+    /// is created if needed.
+    pub fn push(&mut self, line: &str) -> io::Result<()> {
+        self.entries.push(line.to_string());
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(directory) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(directory)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Finds the line `!<n>` or `!<text>` recalls: the `n`th entry, counting
+    /// from 1, or the latest entry that contains the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let history = History { entries: vec!["0.1".into(), "1/3 0.5".into()], path: None };
+    /// assert_eq!(history.recall("1"), Some("0.1"));
+    /// assert_eq!(history.recall("/3"), Some("1/3 0.5"));
+    /// ```
+    pub fn recall(&self, query: &str) -> Option<&str> {
+        match query.parse::<usize>() {
+            Ok(number) => number
+                .checked_sub(1)
+                .and_then(|index| self.entries.get(index)),
+            Err(_) => self
+                .entries
+                .iter()
+                .rev()
+                .find(|entry| entry.contains(query)),
+        }
+        .map(String::as_str)
+    }
+}
+
+/// Writes the conversions of a session to a file, in the format its
+/// extension names, as `--output` picks one.
+///
+/// # Returns
+///
+/// A message describing why the file could not be written, if it could not.
+///
+/// # Example
+///
+/// ```
+/// export_session(&session, "results.json").unwrap();
+/// ```
+pub fn export_session(session: &[Conversion], path: &str) -> Result<(), String> {
+    let format = OutputFormat::from_extension(path).ok_or_else(|| {
+        format!(
+            "cannot tell the format of `{}` from its extension, such as .md or .json",
+            path
+        )
+    })?;
+    if format.needs_serde() && cfg!(not(feature = "serde")) {
+        return Err(format!(
+            "`{}` needs the serde feature; build with --features serde",
+            path
+        ));
+    }
+    File::create(path)
+        .and_then(|mut file| write_conversions(&mut file, format, session.iter().cloned()))
+        .map_err(|error| format!("cannot write `{}`: {}", path, error))
+}
+
+/// Runs the prompt: converts every number on every line read to every
+/// target base, until the input ends or `q` is entered.
+///
+/// Entering `history` lists the lines entered so far, numbered, and `!<n>` or
+/// `!<text>` runs one of them again, the `n`th or the latest one containing
+/// the text. Every other line is added to the history. `:export <path>`
+/// writes every conversion of the session so far to a file, in the format
+/// of its extension, such as `results.md` or `results.json`.
+///
+/// # Arguments
+///
+/// * `options` - The bases and digit limit.
+/// * `history` - The lines entered before, which the new ones are added to.
+/// * `lines` - Where the lines are read from: a terminal, where they can be
+///   edited, or any reader, such as a pipe.
+/// * `out` - Where the prompt and the conversions are written.
+pub fn run_repl(
+    options: &ReplOptions,
+    history: &mut History,
+    mut lines: impl LineSource,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let mut pipeline = Pipeline::with_bases(options.target_bases.clone());
+    pipeline.digits = options.digits;
+    // The documents `:export` writes record the options of the session.
+    #[cfg(feature = "serde")]
+    record_run_options(&pipeline);
+
+    let mut session = Vec::new();
+    loop {
+        let Some(line) = lines.read_entry(&history.entries, out)? else {
+            return Ok(());
+        };
+        let mut entry = line.trim().to_string();
+        match entry.as_str() {
+            "" => continue,
+            "q" | "quit" | "exit" => return Ok(()),
+            "history" => {
+                for (i, entry) in history.entries.iter().enumerate() {
+                    writeln!(out, "{:>5}  {}", i + 1, entry)?;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(path) = entry.strip_prefix(":export") {
+            match path.trim() {
+                "" => writeln!(
+                    out,
+                    "usage: :export <path>, such as results.md or results.json"
+                )?,
+                path if session.is_empty() => writeln!(out, "nothing to export to {} yet", path)?,
+                path => match export_session(&session, path) {
+                    Ok(()) => writeln!(
+                        out,
+                        "exported {} conversion{} to {}",
+                        session.len(),
+                        if session.len() == 1 { "" } else { "s" },
+                        path
+                    )?,
+                    Err(message) => writeln!(out, "{}", message)?,
+                },
+            }
+            continue;
+        }
+        if let Some(query) = entry.strip_prefix('!') {
+            match history.recall(query) {
+                Some(recalled) => {
+                    entry = recalled.to_string();
+                    writeln!(out, "{}", entry)?;
+                }
+                None => {
+                    writeln!(out, "no history entry matches `{}`", query)?;
+                    continue;
+                }
+            }
+        }
+        history.push(&entry)?;
+
+        for arg in entry.split_whitespace() {
+            let Some((value, fraction)) = pipeline.decode_input(arg) else {
+                writeln!(out, "`{}` is not a valid input", arg)?;
+                continue;
+            };
+            for &target_base in &pipeline.target_bases {
+                let expansion = pipeline.expand_input(value, fraction.as_ref(), target_base);
+                writeln!(out, "{} in base {}: {}", arg, target_base, expansion)?;
+                session.push(Conversion::new(value, target_base, &expansion));
+            }
+        }
+    }
+}
+
+/// Runs the `repl` subcommand on the terminal, with the history kept in the
+/// history file. The lines typed at a terminal can be edited, and standard
+/// input is read as it is otherwise, or when `stty` cannot put the terminal
+/// in raw mode.
+pub fn display_repl(options: &ReplOptions) {
+    let mut history = History::load(options.history.clone());
+    let mut out = io::stdout().lock();
+    let terminal = io::stdin().is_terminal().then(Terminal::open).flatten();
+    let result = match terminal {
+        Some(terminal) => run_repl(options, &mut history, terminal, &mut out),
+        None => run_repl(options, &mut history, io::stdin().lock(), &mut out),
+    };
+    if let Err(error) = result {
+        eprintln!("error: {}", write_error_message(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let options = parse_repl_args(&args("--base 2,4 --no-history")).unwrap();
+        let mut history = History {
+            entries: vec!["0.25".to_string()],
+            path: None,
+        };
+        let mut out = Vec::new();
+        run_repl(
+            &options,
+            &mut history,
+            "0.5 junk\n\n!1\n!nothing\nhistory\nq\n0.75\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to(
+                "> 0.5 in base 2: 0.1;\n\
+                 0.5 in base 4: 0.2;\n\
+                 `junk` is not a valid input\n\
+                 > > 0.25\n\
+                 0.25 in base 2: 0.0;1;\n\
+                 0.25 in base 4: 0.1;\n\
+                 > no history entry matches `nothing`\n\
+                 >     1  0.25\n    2  0.5 junk\n    3  0.25\n\
+                 > "
+                .to_string()
+            )
+        );
+        assert_that!(history.recall("5"), equal_to(None));
+        assert_that!(history.recall("junk"), equal_to(Some("0.5 junk")));
+
+        let path = env::temp_dir()
+            .join(format!("repl-{}", std::process::id()))
+            .join("history");
+        let mut saved = History::load(Some(path.clone()));
+        saved.push("1/3").unwrap();
+        saved.push("0.1 0.2").unwrap();
+        assert_that!(
+            History::load(Some(path.clone())).entries,
+            equal_to(vec!["1/3".to_string(), "0.1 0.2".to_string()])
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(parse_repl_args(&args("--base 1")).is_err());
+        assert!(parse_repl_args(&args("0.5")).is_err());
+        assert_that!(
+            parse_repl_args(&args("--history h.txt")).unwrap().history,
+            equal_to(Some(PathBuf::from("h.txt")))
+        );
+    }
+
+    #[test]
+    fn test_repl_export() {
+        let options = parse_repl_args(&args("--base 2,4 --no-history")).unwrap();
+        let directory = env::temp_dir().join(format!("repl-export-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let table = directory.join("results.md");
+        let json = directory.join("results.json");
+        let script = format!(
+            ":export {}\n0.5\n0.25 x\n:export {}\n:export {}\n:export results.txt\n:export\n",
+            table.display(),
+            table.display(),
+            json.display()
+        );
+        let mut history = History::default();
+        let mut out = Vec::new();
+        run_repl(&options, &mut history, script.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(&format!("nothing to export to {} yet", table.display())));
+        assert!(out.contains(&format!("exported 4 conversions to {}", table.display())));
+        assert!(out.contains("cannot tell the format of `results.txt`"));
+        assert!(out.contains("usage: :export <path>"));
+        assert_that!(
+            history.entries,
+            equal_to(vec!["0.5".to_string(), "0.25 x".to_string()])
+        );
+
+        let written = fs::read_to_string(&table).unwrap();
+        assert_that!(written.lines().count(), equal_to(4));
+        assert!(written.contains("| 0.25       | 0.0;1;     "));
+        #[cfg(feature = "serde")]
+        {
+            let document = fs::read_to_string(&json).unwrap();
+            assert!(document.contains(
+                r#"{"input":0.25,"base":"4","digits":1,"truncated":false,"repr":"0.1;"}"#
+            ));
+            assert!(document.contains(r#""options": {"bases":["2","4"],"digits":8,"#));
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
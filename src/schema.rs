@@ -0,0 +1,182 @@
+use std::io::{self, Write};
+use std::sync::RwLock;
+
+use serde::Serialize;
+
+use crate::beta::format_base;
+use crate::json::{to_json, write_json_string, JsonError};
+use crate::pipeline::Pipeline;
+
+/// The name every document of `--format json` declares its schema with.
+pub const SCHEMA_NAME: &str = "base-conversion-results";
+
+/// The version of the layout of `--format json`. It goes up whenever a field
+/// is renamed, removed or changes meaning, so that a reader can refuse a
+/// layout it does not know; a new field alone leaves it as it is.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The options of a run that shape its results, as a JSON document records
+/// them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunOptions {
+    /// The target bases, as the `base` field of every row writes them.
+    pub bases: Vec<String>,
+    /// The maximum number of fractional digits.
+    pub digits: u32,
+    /// Whether the fewest digits reading back as the same `f64` were
+    /// produced instead, from `--shortest`.
+    pub shortest: bool,
+    /// Whether terminating expansions kept all their digits, from
+    /// `--exact-digits`.
+    pub exact_digits: bool,
+    /// How the last digit was settled, as `--rounding` names it.
+    pub rounding: &'static str,
+    /// The backend every conversion used, or `None` for the one picked for
+    /// every input.
+    pub backend: Option<&'static str>,
+    /// Whether decimal inputs were converted as the exact fractions they are
+    /// written as, from `--exact`.
+    pub exact: bool,
+    /// Whether inputs were read as 96-bit decimals, from `--decimal`.
+    pub decimal: bool,
+    /// The width in bits of the float every input was rounded to, 32 or 64.
+    pub float_width: u32,
+    /// Whether inputs were converted as the `f64` they are stored as, from
+    /// `--interpret float`.
+    pub stored_float: bool,
+}
+
+impl RunOptions {
+    /// The options a pipeline converts with.
+    pub fn from_pipeline(pipeline: &Pipeline) -> RunOptions {
+        RunOptions {
+            bases: pipeline
+                .target_bases
+                .iter()
+                .map(|&target_base| format_base(target_base))
+                .collect(),
+            digits: pipeline.digits,
+            shortest: pipeline.shortest,
+            exact_digits: pipeline.exact_digits,
+            rounding: pipeline.rounding.name(),
+            backend: pipeline.backend.map(|backend| backend.name()),
+            exact: pipeline.exact,
+            decimal: pipeline.decimal,
+            float_width: if pipeline.single_precision { 32 } else { 64 },
+            stored_float: pipeline.stored_float,
+        }
+    }
+}
+
+/// The options of the run, which every JSON document written records.
+static RUN_OPTIONS: RwLock<Option<RunOptions>> = RwLock::new(None);
+
+/// Records the options the results of the run are converted with.
+pub fn record_run_options(pipeline: &Pipeline) {
+    *RUN_OPTIONS
+        .write()
+        .unwrap_or_else(|error| error.into_inner()) = Some(RunOptions::from_pipeline(pipeline));
+}
+
+/// Writes the rows of `--format json` as a document of the versioned schema:
+/// an object naming the schema, its version and the tool, with the options
+/// of the run (`null` if none were recorded) and the rows, one per line.
+///
+/// # Arguments
+///
+/// * `out` - Where the document is written.
+/// * `rows` - Every row, already written as a JSON object.
+///
+/// # Example
+///
+/// ```
+/// let mut out = Vec::new();
+/// write_json_document(&mut out, [Ok("{\"repr\":\"0.1;\"}".to_string())]).unwrap();
+/// // {
+/// //   "schema": "base-conversion-results",
+/// //   "schema_version": 1,
+/// //   "tool": {"name":"Machine_Assignment_1","version":"0.1.0"},
+/// //   "options": null,
+/// //   "rows": [
+/// //     {"repr":"0.1;"}
+/// //   ]
+/// // }
+/// ```
+pub fn write_json_document(
+    out: &mut dyn Write,
+    rows: impl IntoIterator<Item = Result<String, JsonError>>,
+) -> io::Result<()> {
+    let invalid_data = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+    let mut name = String::new();
+    write_json_string(&mut name, SCHEMA_NAME);
+    let options = RUN_OPTIONS
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"schema\": {},", name)?;
+    writeln!(out, "  \"schema_version\": {},", SCHEMA_VERSION)?;
+    writeln!(
+        out,
+        "  \"tool\": {{\"name\":\"{}\",\"version\":\"{}\"}},",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(
+        out,
+        "  \"options\": {},",
+        to_json(&options).map_err(invalid_data)?
+    )?;
+    write!(out, "  \"rows\": [")?;
+    let mut empty = true;
+    for row in rows {
+        let row = row.map_err(invalid_data)?;
+        write!(out, "{}\n    {}", if empty { "" } else { "," }, row)?;
+        empty = false;
+    }
+    if !empty {
+        write!(out, "\n  ")?;
+    }
+    writeln!(out, "]")?;
+    writeln!(out, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_run_options() {
+        let pipeline = Pipeline {
+            digits: 20,
+            single_precision: true,
+            ..Pipeline::with_bases(vec![2.0, -2.0])
+        };
+        assert_that!(
+            to_json(&RunOptions::from_pipeline(&pipeline)).unwrap(),
+            equal_to(
+                "{\"bases\":[\"2\",\"-2\"],\"digits\":20,\"shortest\":false,\
+                 \"exact_digits\":false,\"rounding\":\"trunc\",\"backend\":null,\
+                 \"exact\":false,\"decimal\":false,\"float_width\":32,\"stored_float\":false}"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_document() {
+        let mut out = Vec::new();
+        write_json_document(&mut out, []).unwrap();
+        let document = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = document.lines().collect();
+        assert_that!(lines[0], equal_to("{"));
+        assert_that!(
+            lines[1],
+            equal_to("  \"schema\": \"base-conversion-results\",")
+        );
+        assert_that!(lines[2], equal_to("  \"schema_version\": 1,"));
+        assert!(lines[3].contains(env!("CARGO_PKG_VERSION")));
+        assert_that!(lines[5..].to_vec(), equal_to(vec!["  \"rows\": []", "}"]));
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::convert::{Expansion, Representation};
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// An expansion written in normalized scientific notation in its own base,
+/// such as `1.0;1;1;0;…×2^-3`, so that small values do not start with a long
+/// run of zero digits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scientific(pub Expansion);
+
+/// Counts the zero digits between the point and the first non-zero digit of
+/// `value` in `base`, which is zero for values of at least one.
+///
+/// # Example
+///
+/// ```
+/// // 0.001 is 0.0000000001000001… in binary.
+/// assert_eq!(leading_zeros(0.001, 2), 9);
+/// ```
+pub fn leading_zeros(value: f64, base: u64) -> u32 {
+    let value = value.abs();
+    if value == 0.0 || value >= 1.0 || !value.is_finite() {
+        return 0;
+    }
+    let base = base as f64;
+    // The logarithm can be off by one next to a power of the base, which the
+    // loops settle by comparing against the powers themselves.
+    let mut zeros = ((-value.log(base)).ceil() - 1.0).max(0.0) as i32;
+    while base.powi(-(zeros + 1)) > value {
+        zeros += 1;
+    }
+    while zeros > 0 && base.powi(-zeros) <= value {
+        zeros -= 1;
+    }
+    zeros as u32
+}
+
+/// Converts an input for `--scientific`, producing as many significant
+/// digits as the pipeline would produce fractional digits.
+///
+/// # Arguments
+///
+/// * `pipeline` - The conversion settings.
+/// * `value` - The decoded input.
+/// * `fraction` - The exact fraction the input was written as, if any.
+/// * `target_base` - The positive integer base to convert to.
+pub fn expand_scientific(
+    pipeline: &Pipeline,
+    value: f64,
+    fraction: Option<&Fraction>,
+    target_base: f64,
+) -> Scientific {
+    let pipeline = Pipeline {
+        digits: pipeline.digits + leading_zeros(value, target_base as u64),
+        ..pipeline.clone()
+    };
+    Scientific(pipeline.expand_input(value, fraction, target_base))
+}
+
+impl Representation for Scientific {
+    fn value(&self) -> f64 {
+        self.0.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.0.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.0.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.0.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.0.positional_digits()
+    }
+}
+
+impl fmt::Display for Scientific {
+    /// Writes the first non-zero digit, the point, the digits after it
+    /// followed by `;`, a `…` when the expansion was cut off, and the
+    /// exponent, e.g. `1.0;1;×2^-3` for 0.15625. Zero is written as `0`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let expansion = &self.0;
+        let digits: Vec<u32> = expansion
+            .integer_digits
+            .iter()
+            .chain(&expansion.digits)
+            .copied()
+            .collect();
+        let first = match digits.iter().position(|&digit| digit != 0) {
+            Some(first) => first,
+            None => return write!(f, "0"),
+        };
+        let exponent = expansion.integer_digits.len() as i64 - 1 - first as i64;
+
+        if expansion.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}.", digits[first])?;
+        for digit in &digits[first + 1..] {
+            write!(f, "{};", digit)?;
+        }
+        if !expansion.is_exact() {
+            write!(f, "…")?;
+        }
+        write!(f, "×{}^{}", expansion.base, exponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_leading_zeros() {
+        assert_that!(leading_zeros(0.001, 2), equal_to(9));
+        assert_that!(leading_zeros(0.5, 2), equal_to(0));
+        assert_that!(leading_zeros(0.25, 2), equal_to(1));
+        assert_that!(leading_zeros(0.001, 10), equal_to(2));
+        assert_that!(leading_zeros(0.0009, 10), equal_to(3));
+        assert_that!(leading_zeros(0.0, 2), equal_to(0));
+    }
+
+    #[test]
+    fn test_scientific_notation() {
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let scientific =
+            |value: f64, base: f64| expand_scientific(&pipeline, value, None, base).to_string();
+        assert_that!(scientific(0.15625, 2.0), equal_to("1.0;1;×2^-3"));
+        assert_that!(scientific(-0.5, 2.0), equal_to("-1.×2^-1"));
+        assert_that!(scientific(0.0, 2.0), equal_to("0"));
+        assert_that!(
+            scientific(0.001, 16.0),
+            equal_to("4.1;8;9;3;7;4;11;…×16^-3")
+        );
+        // A small input keeps all of its significant digits.
+        assert_that!(scientific(0.001, 2.0), equal_to("1.0;0;0;0;0;1;1;…×2^-10"));
+    }
+}
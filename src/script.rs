@@ -0,0 +1,314 @@
+use std::fmt;
+use std::fs;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ImmutableString, Map, Scope, AST, INT};
+
+/// The constant naming the columns a script adds, in order.
+const COLUMNS_CONSTANT: &str = "COLUMNS";
+
+/// The most operations a script may take on one cell, so that a script
+/// looping forever fails rather than hanging the run.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A `--script` that rewrites the digits of every table cell and computes
+/// columns of its own from them, run once per expansion.
+///
+/// A script is a [Rhai](https://rhai.rs) script run with the variables of
+/// the cell in scope:
+///
+/// * `input`, `base` and `value` (the value of the digits) as numbers.
+/// * `integer` and `digits`, the arrays of integer and fractional digits,
+///   empty for representations not made of positional digits.
+/// * `negative` and `truncated` as booleans.
+/// * `repr`, the cell as written, which the script rewrites by assigning
+///   to it.
+/// * `columns`, an empty map the script fills with a value for every
+///   column it adds, such as `columns["ones"] = digits.filter(|d| d == 1).len()`.
+///
+/// The columns, added after every base column, are named by a constant
+/// array of their headers, such as `const COLUMNS = ["ones"];`. Besides
+/// Rhai's own functions, `digit(d)` writes a digit from 0 to 35 as 0-9 or
+/// A-Z.
+pub struct Script {
+    /// The text of the script, which tells scripts apart.
+    source: String,
+    /// The engine the script runs on, with `digit` and the limits set.
+    engine: Engine,
+    /// The compiled script.
+    ast: AST,
+    /// The headers of the columns the script adds, in order.
+    columns: Vec<String>,
+}
+
+impl fmt::Debug for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Script")
+            .field("source", &self.source)
+            .field("columns", &self.columns)
+            .finish()
+    }
+}
+
+impl PartialEq for Script {
+    fn eq(&self, other: &Script) -> bool {
+        self.source == other.source
+    }
+}
+
+/// The expansion a script is run on.
+#[derive(Debug, Clone, Copy)]
+pub struct Cell<'a> {
+    /// The input number.
+    pub input: f64,
+    /// The base of the expansion, or NaN if it has none.
+    pub base: f64,
+    /// The digits of the integer part, most significant first.
+    pub integer: &'a [u32],
+    /// The fractional digits, most significant first.
+    pub digits: &'a [u32],
+    /// Whether the value is negative.
+    pub negative: bool,
+    /// Whether the digits were cut off before the expansion ended.
+    pub truncated: bool,
+    /// The value of the digits.
+    pub value: f64,
+    /// The cell as it is written without the script.
+    pub repr: &'a str,
+}
+
+/// What a script made of a cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scripted {
+    /// The rewritten cell.
+    pub repr: String,
+    /// The value of every column of the script, in order.
+    pub columns: Vec<String>,
+}
+
+/// Writes a digit from 0 to 35 as `0`-`9` or `A`-`Z`.
+fn digit(number: INT) -> Result<String, Box<EvalAltResult>> {
+    u32::try_from(number)
+        .ok()
+        .and_then(|number| char::from_digit(number, 36))
+        .map(|digit| digit.to_ascii_uppercase().to_string())
+        .ok_or_else(|| format!("{} is not a digit from 0 to 35", number).into())
+}
+
+/// The variables of a cell, as a script reads them.
+fn cell_scope(cell: &Cell) -> Scope<'static> {
+    let digits = |digits: &[u32]| -> Array {
+        digits
+            .iter()
+            .map(|&digit| Dynamic::from(INT::from(digit)))
+            .collect()
+    };
+    let mut scope = Scope::new();
+    scope
+        .push("input", cell.input)
+        .push("base", cell.base)
+        .push("integer", digits(cell.integer))
+        .push("digits", digits(cell.digits))
+        .push("negative", cell.negative)
+        .push("truncated", cell.truncated)
+        .push("value", cell.value)
+        .push("repr", cell.repr.to_string())
+        .push("columns", Map::new());
+    scope
+}
+
+impl Script {
+    /// Compiles the text of a script.
+    ///
+    /// # Returns
+    ///
+    /// The script, or a message naming where the script is not well formed,
+    /// reads a variable that does not exist, or names its columns with
+    /// anything but an array of text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let script = Script::parse("const COLUMNS = [\"ones\"];\ncolumns.ones = digits.len();").unwrap();
+    /// assert_eq!(script.columns(), ["ones"]);
+    /// ```
+    pub fn parse(text: &str) -> Result<Script, String> {
+        let mut engine = Engine::new();
+        engine
+            .set_strict_variables(true)
+            .set_max_operations(MAX_OPERATIONS)
+            .register_fn("digit", digit)
+            .on_print(|text| eprintln!("{}", text));
+        let empty = Cell {
+            input: 0.0,
+            base: 2.0,
+            integer: &[],
+            digits: &[],
+            negative: false,
+            truncated: false,
+            value: 0.0,
+            repr: "",
+        };
+        let ast = engine
+            .compile_with_scope(&cell_scope(&empty), text)
+            .map_err(|error| format!("script: {}", error))?;
+        let columns = match ast
+            .iter_literal_variables(true, false)
+            .find(|&(name, ..)| name == COLUMNS_CONSTANT)
+        {
+            Some((_, _, headers)) => headers
+                .into_typed_array::<ImmutableString>()
+                .map_err(|_| format!("script: {} must be an array of text", COLUMNS_CONSTANT))?
+                .into_iter()
+                .map(|header| header.to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(Script {
+            source: text.to_string(),
+            engine,
+            ast,
+            columns,
+        })
+    }
+
+    /// Reads a script from a file, as [`Script::parse`] reads its text.
+    pub fn load(path: &str) -> Result<Script, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read script file `{}`: {}", path, error))?;
+        Script::parse(&text).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    /// The headers of the columns the script adds after every base column.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Runs the script on the expansion of one cell.
+    ///
+    /// # Returns
+    ///
+    /// The rewritten cell and the values of the columns, or a message naming
+    /// where the script failed, such as a division by zero or an index past
+    /// the end of an array, or the first column it left without a value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let script = Script::parse("repr = digits.map(|d| digit(d)).reduce(|text, d| text + d, \"\");").unwrap();
+    /// let cell = Cell { input: 0.75, base: 16.0, integer: &[], digits: &[12], negative: false, truncated: false, value: 0.75, repr: "0.12;" };
+    /// assert_eq!(script.run(&cell).unwrap().repr, "C");
+    /// ```
+    pub fn run(&self, cell: &Cell) -> Result<Scripted, String> {
+        let mut scope = cell_scope(cell);
+        self.engine
+            .run_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|error| format!("script: {}", error))?;
+        let repr = scope
+            .get_value::<Dynamic>("repr")
+            .map(|repr| repr.to_string())
+            .unwrap_or_default();
+        let values = scope.get_value::<Map>("columns").unwrap_or_default();
+        let columns = self
+            .columns
+            .iter()
+            .map(|header| {
+                values
+                    .get(header.as_str())
+                    .map(|value| value.to_string())
+                    .ok_or_else(|| format!("script: the column `{}` was given no value", header))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Scripted { repr, columns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn cell<'a>(base: f64, digits: &'a [u32], repr: &'a str) -> Cell<'a> {
+        Cell {
+            input: 0.75,
+            base,
+            integer: &[],
+            digits,
+            negative: false,
+            truncated: false,
+            value: 0.75,
+            repr,
+        }
+    }
+
+    #[test]
+    fn test_script_rewrites_and_columns() {
+        let script = Script::parse(
+            "// hexadecimal letters and a digit sum\n\
+             const COLUMNS = [\"Digit sum\", \"ones\", \"last\"];\n\
+             let letters = digits.map(|d| digit(d)).reduce(|text, d| text + d, \"\");\n\
+             if base == 16.0 { repr = \"0x0.\" + letters; }\n\
+             columns[\"Digit sum\"] = digits.reduce(|sum, d| sum + d, 0);\n\
+             columns.ones = digits.filter(|d| d == 1).len();\n\
+             columns.last = digits[-1] * 2 % 5;\n",
+        )
+        .unwrap();
+        assert_that!(
+            script.columns().to_vec(),
+            equal_to(vec![
+                "Digit sum".to_string(),
+                "ones".to_string(),
+                "last".to_string()
+            ])
+        );
+        assert_that!(
+            script.run(&cell(16.0, &[12], "0.12;")),
+            equal_to(Ok(Scripted {
+                repr: "0x0.C".to_string(),
+                columns: vec!["12".to_string(), "0".to_string(), "4".to_string()],
+            }))
+        );
+        assert_that!(
+            script.run(&cell(2.0, &[1, 1], "0.1;1;")).unwrap().repr,
+            equal_to("0.1;1;".to_string())
+        );
+        let past_the_end = script.run(&cell(2.0, &[], "0.")).unwrap_err();
+        assert_that!(past_the_end.contains("out of bounds"), is(true));
+        assert_that!(past_the_end.contains("line 7"), is(true));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        assert_that!(
+            Script::parse("repr = digitz;")
+                .unwrap_err()
+                .contains("Undefined variable: digitz"),
+            is(true)
+        );
+        assert_that!(Script::parse("repr = \"open").is_err(), is(true));
+        assert_that!(Script::parse("const COLUMNS = [1];").is_err(), is(true));
+        let script = Script::parse("repr = lenght(digits);").unwrap();
+        assert_that!(
+            script
+                .run(&cell(2.0, &[1], "0.1;"))
+                .unwrap_err()
+                .contains("Function not found: lenght"),
+            is(true)
+        );
+        let script =
+            Script::parse("const COLUMNS = [\"x\"];\ncolumns.x = 1 / (digits.len() - 1);").unwrap();
+        assert_that!(
+            script
+                .run(&cell(2.0, &[1], "0.1;"))
+                .unwrap_err()
+                .contains("Division by zero"),
+            is(true)
+        );
+        let unset = Script::parse("const COLUMNS = [\"x\"];").unwrap();
+        assert_that!(
+            unset.run(&cell(2.0, &[1], "0.1;")),
+            equal_to(Err("script: the column `x` was given no value".to_string()))
+        );
+        let forever = Script::parse("loop {}").unwrap();
+        assert_that!(forever.run(&cell(2.0, &[1], "0.1;")).is_err(), is(true));
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt::{self, Write};
+
+use crate::convert::Representation;
+
+/// The text written between digits when no other separator is asked for.
+pub const DEFAULT_SEPARATOR: &str = ";";
+
+/// A representation with every `;` between its digits written as another
+/// separator, such as a space, from `--separator`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Separated<'a, R> {
+    /// The representation shown.
+    pub representation: R,
+    /// The separator written instead of `;`, or `None` to keep it.
+    pub separator: Option<&'a str>,
+}
+
+impl<R: Representation> Representation for Separated<'_, R> {
+    fn value(&self) -> f64 {
+        self.representation.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.representation.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.representation.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.representation.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.representation.positional_digits()
+    }
+}
+
+/// Passes text through to a formatter with every `;` replaced.
+struct Replacing<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    separator: &'a str,
+}
+
+impl Write for Replacing<'_, '_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let mut parts = text.split(DEFAULT_SEPARATOR);
+        if let Some(first) = parts.next() {
+            self.f.write_str(first)?;
+        }
+        for part in parts {
+            self.f.write_str(self.separator)?;
+            self.f.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: fmt::Display> fmt::Display for Separated<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.separator {
+            Some(separator) => write!(Replacing { f, separator }, "{}", self.representation),
+            None => write!(f, "{}", self.representation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_separators() {
+        let separated = Separated {
+            representation: expand(0.625, 2, 8),
+            separator: Some(" "),
+        };
+        assert_that!(separated.to_string(), equal_to("0.1 0 1 ".to_string()));
+        assert_that!(separated.value(), equal_to(0.625));
+        let kept = Separated {
+            separator: None,
+            ..separated
+        };
+        assert_that!(kept.to_string(), equal_to("0.1;0;1;".to_string()));
+    }
+}
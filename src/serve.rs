@@ -0,0 +1,487 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::cli::flag_value;
+use crate::conversion::Conversion;
+use crate::exit::written;
+use crate::grpc::{serve_grpc, DEFAULT_GRPC_ADDRESS, PROTO};
+use crate::json::{to_json, write_json_string};
+use crate::pipeline::{parse_base_list, Pipeline};
+
+/// The address `serve` listens on without `--address`.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The longest request line or header that is read.
+const MAX_LINE: usize = 8 * 1024;
+
+/// The most fractional digits a request may ask for, over HTTP, gRPC or
+/// `--serve-stdio`, so that no request can take all the memory there is.
+pub const MAX_PRECISION: u32 = 1000;
+
+/// The most connections served at once; more are turned away until one of
+/// them ends.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// The settings of the `serve` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServeOptions {
+    /// The address and port to listen on, such as `0.0.0.0:8080`.
+    pub address: String,
+    /// Whether to answer gRPC calls of the `baseconv.Converter` service
+    /// instead of HTTP requests, with `--grpc`.
+    pub grpc: bool,
+    /// Whether to print the service definition instead of serving, with
+    /// `--proto`.
+    pub print_proto: bool,
+}
+
+/// Parses the arguments of the `serve` subcommand. With `--grpc` it
+/// listens on `DEFAULT_GRPC_ADDRESS` unless `--address` is given.
+///
+/// # Example
+/// ```
+/// // cargo run -- serve --address 0.0.0.0:8080
+/// // cargo run -- serve --grpc
+/// ```
+pub fn parse_serve_args(args: &[String]) -> Result<ServeOptions, String> {
+    let mut address = None;
+    let mut grpc = false;
+    let mut print_proto = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--address", &mut iter)? {
+            address = Some(value.to_string());
+        } else if arg == "--grpc" {
+            grpc = true;
+        } else if arg == "--proto" {
+            print_proto = true;
+        } else {
+            return Err(format!("unknown serve argument `{}`", arg));
+        }
+    }
+
+    if print_proto && !grpc {
+        return Err("--proto prints the service definition of --grpc".to_string());
+    }
+    let default = if grpc {
+        DEFAULT_GRPC_ADDRESS
+    } else {
+        DEFAULT_ADDRESS
+    };
+    Ok(ServeOptions {
+        address: address.unwrap_or_else(|| default.to_string()),
+        grpc,
+        print_proto,
+    })
+}
+
+/// A response to a request: its status and JSON body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The JSON body.
+    pub body: String,
+}
+
+impl Response {
+    /// A response with `{"error": message}` as its body.
+    fn error(status: u16, message: &str) -> Response {
+        let mut body = String::from("{\"error\":");
+        write_json_string(&mut body, message);
+        body.push('}');
+        Response { status, body }
+    }
+
+    /// The reason phrase of the status.
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Decodes the `%XX` escapes and `+` signs of a query string component.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.bytes();
+    while let Some(byte) = rest.next() {
+        match byte {
+            b'%' => {
+                let hex = [rest.next()?, rest.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Reads the precision of a request, which may not be above
+/// `MAX_PRECISION`.
+///
+/// # Returns
+///
+/// The precision, or a message describing why it cannot be used.
+pub fn parse_precision(text: &str) -> Result<u32, String> {
+    let precision = text
+        .parse::<u32>()
+        .map_err(|_| format!("invalid precision `{}`", text))?;
+    check_precision(precision)
+}
+
+/// Checks that a precision is not above `MAX_PRECISION`.
+pub fn check_precision(precision: u32) -> Result<u32, String> {
+    if precision > MAX_PRECISION {
+        return Err(format!(
+            "precision {} is above the maximum of {}",
+            precision, MAX_PRECISION
+        ));
+    }
+    Ok(precision)
+}
+
+/// Answers `GET /convert?value=0.1&base=2&precision=16` with the
+/// conversion as JSON. `base` defaults to 2 and `precision` to the usual
+/// 8 digits, at most `MAX_PRECISION`, and `value` may also be a fraction
+/// such as `1/3` or an expression such as `0.1+0.2`.
+///
+/// # Arguments
+///
+/// * `method` - The method of the request, such as `GET`.
+/// * `target` - The path and query of the request.
+///
+/// # Example
+///
+/// ```
+/// let response = respond("GET", "/convert?value=0.5");
+/// assert_eq!(response.body, r#"{"input":0.5,"base":"2","digits":1,"truncated":false,"repr":"0.1;"}"#);
+/// ```
+pub fn respond(method: &str, target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/convert" {
+        return Response::error(404, &format!("no such endpoint `{}`", path));
+    }
+    if method != "GET" {
+        return Response::error(405, "only GET is supported");
+    }
+
+    let mut pipeline = Pipeline::with_bases(vec![2.0]);
+    let mut value = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, raw) = pair.split_once('=').unwrap_or((pair, ""));
+        let decoded = match percent_decode(raw) {
+            Some(decoded) => decoded,
+            None => return Response::error(400, &format!("invalid escape in `{}`", key)),
+        };
+        match key {
+            "value" => value = Some(decoded),
+            "base" => match parse_base_list(&decoded).as_deref() {
+                Ok([target_base]) => pipeline.target_bases = vec![*target_base],
+                Ok(_) => return Response::error(400, "only one base can be given"),
+                Err(message) => return Response::error(400, message),
+            },
+            "precision" => match parse_precision(&decoded) {
+                Ok(precision) => pipeline.digits = precision,
+                Err(message) => return Response::error(400, &message),
+            },
+            _ => return Response::error(400, &format!("unknown parameter `{}`", key)),
+        }
+    }
+
+    let value = match value {
+        Some(value) => value,
+        None => return Response::error(400, "the value parameter is required"),
+    };
+    let conversion = match convert_value(&pipeline, &value) {
+        Ok(conversion) => conversion,
+        Err(message) => return Response::error(400, &message),
+    };
+    match to_json(&conversion) {
+        Ok(body) => Response { status: 200, body },
+        Err(error) => Response::error(500, &error.to_string()),
+    }
+}
+
+/// Converts a value to the only base of a pipeline, as every request to
+/// the server does, over HTTP or gRPC.
+///
+/// # Returns
+///
+/// The conversion, or a message if the value cannot be read.
+pub fn convert_value(pipeline: &Pipeline, value: &str) -> Result<Conversion, String> {
+    let (input, fraction) = pipeline
+        .decode_input(value)
+        .ok_or_else(|| format!("invalid value `{}`", value))?;
+    let target_base = pipeline.target_bases[0];
+    let expansion = pipeline.expand_input(input, fraction.as_ref(), target_base);
+    Ok(Conversion::new(input, target_base, &expansion))
+}
+
+/// Reads one line of at most `MAX_LINE` bytes, without its line ending.
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.by_ref().take(MAX_LINE as u64).read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Reads a request from a connection and writes the response to it. The
+/// headers and any body of the request are ignored.
+pub fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request_line = read_line(&mut reader)?;
+    while !read_line(&mut reader)?.is_empty() {}
+
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => respond(method, target),
+        _ => Response::error(400, "malformed request line"),
+    };
+
+    write_response(stream, &response)
+}
+
+/// Writes a response and closes the connection.
+fn write_response(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Counts the connections being served, so that no more than a limit are
+/// served at once.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimit {
+    /// The connections being served.
+    active: Arc<AtomicUsize>,
+    /// The most that may be served at once.
+    limit: usize,
+}
+
+/// Frees the place of a connection in its `ConnectionLimit` when it ends,
+/// however it ends.
+#[derive(Debug)]
+pub struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ConnectionLimit {
+    /// A limit of `limit` connections at once, with none being served.
+    pub fn new(limit: usize) -> ConnectionLimit {
+        ConnectionLimit {
+            active: Arc::new(AtomicUsize::new(0)),
+            limit,
+        }
+    }
+
+    /// Takes the place of a connection, if there is room for it, until the
+    /// slot is dropped.
+    pub fn acquire(&self) -> Option<ConnectionSlot> {
+        if self.active.fetch_add(1, Ordering::SeqCst) >= self.limit {
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot(Arc::clone(&self.active)))
+    }
+
+    /// Serves a connection on its own thread, if there is room for it.
+    ///
+    /// # Returns
+    ///
+    /// Whether the connection is served, or else the limit is reached.
+    pub fn spawn(&self, work: impl FnOnce() + Send + 'static) -> bool {
+        let Some(slot) = self.acquire() else {
+            return false;
+        };
+        thread::spawn(move || {
+            let _slot = slot;
+            work();
+        });
+        true
+    }
+}
+
+/// Turns a connection away with `503 Service Unavailable`.
+fn refuse_connection(stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    let busy = Response::error(503, "the server is busy; try again later");
+    write_response(stream, &busy)
+}
+
+/// Answers requests on a listener until it fails, each connection on its
+/// own thread, with at most `MAX_CONNECTIONS` served at once.
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    let limit = ConnectionLimit::new(MAX_CONNECTIONS);
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let Ok(refused) = stream.try_clone() else {
+            continue;
+        };
+        let served = limit.spawn(move || {
+            if let Err(error) = handle_connection(stream) {
+                eprintln!("warning: a request failed: {}", error);
+            }
+        });
+        if !served {
+            let _ = refuse_connection(refused);
+        }
+    }
+    Ok(())
+}
+
+/// Runs the `serve` subcommand until it is stopped.
+///
+/// # Returns
+///
+/// Whether the server could be started; it only returns once it stops.
+pub fn display_serve(options: &ServeOptions) -> bool {
+    if options.print_proto {
+        written(io::stdout().lock().write_all(PROTO.as_bytes()));
+        return true;
+    }
+    let listener = match TcpListener::bind(&options.address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("error: cannot listen on {}: {}", options.address, error);
+            return false;
+        }
+    };
+    let served = if options.grpc {
+        eprintln!(
+            "listening for gRPC calls of baseconv.Converter on {}",
+            options.address
+        );
+        serve_grpc(listener)
+    } else {
+        eprintln!(
+            "listening on http://{}/convert?value=0.1&base=2&precision=16",
+            options.address
+        );
+        serve(listener)
+    };
+    match served {
+        Ok(()) => true,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_convert_endpoint() {
+        let response = respond("GET", "/convert?value=0.1&base=2&precision=4");
+        assert_that!(response.status, equal_to(200));
+        assert_that!(
+            response.body,
+            equal_to(r#"{"input":0.1,"base":"2","digits":4,"truncated":true,"repr":"0.0;0;0;1;"}"#)
+        );
+        let third = respond("GET", "/convert?value=1%2F3&base=3");
+        assert_that!(third.body.contains(r#""truncated":false"#), equal_to(true));
+
+        assert_that!(respond("GET", "/convert?base=2").status, equal_to(400));
+        assert_that!(respond("GET", "/convert?value=x").status, equal_to(400));
+        assert_that!(
+            respond("GET", "/convert?value=1&base=2,8").status,
+            equal_to(400)
+        );
+        assert_that!(
+            respond("GET", "/convert?value=1&colour=red").body,
+            equal_to(r#"{"error":"unknown parameter `colour`"}"#)
+        );
+        assert_that!(respond("POST", "/convert?value=1").status, equal_to(405));
+        assert_that!(respond("GET", "/").status, equal_to(404));
+
+        let limit = format!("/convert?value=1/7&precision={}", MAX_PRECISION);
+        assert_that!(respond("GET", &limit).status, equal_to(200));
+        let huge = respond("GET", "/convert?value=1/7&precision=4000000000");
+        assert_that!(huge.status, equal_to(400));
+        assert_that!(
+            huge.body,
+            equal_to(r#"{"error":"precision 4000000000 is above the maximum of 1000"}"#)
+        );
+        let nested = format!("/convert?value={}", "(".repeat(2600));
+        assert_that!(respond("GET", &nested).status, equal_to(400));
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let limit = ConnectionLimit::new(1);
+        let (started, wait) = std::sync::mpsc::channel();
+        let (finish, finished) = std::sync::mpsc::channel::<()>();
+        assert!(limit.spawn(move || {
+            started.send(()).unwrap();
+            finished.recv().unwrap();
+        }));
+        wait.recv().unwrap();
+        assert!(!limit.spawn(|| {}));
+        finish.send(()).unwrap();
+        while limit.active.load(Ordering::SeqCst) > 0 {
+            thread::yield_now();
+        }
+        assert!(limit.spawn(|| {}));
+    }
+
+    #[test]
+    fn test_serve_arguments() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        let options = parse_serve_args(&args("--grpc")).unwrap();
+        assert_that!(options.address.as_str(), equal_to(DEFAULT_GRPC_ADDRESS));
+        let options = parse_serve_args(&args("--address 0.0.0.0:9000")).unwrap();
+        assert_that!(options.grpc, equal_to(false));
+        assert!(parse_serve_args(&args("--proto")).is_err());
+    }
+
+    #[test]
+    fn test_serving_a_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream).unwrap();
+        });
+
+        let mut client = TcpStream::connect(address).unwrap();
+        write!(
+            client,
+            "GET /convert?value=0.5 HTTP/1.1\r\nHost: test\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#""repr":"0.1;"}"#));
+    }
+}
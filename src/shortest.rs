@@ -0,0 +1,164 @@
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::convert::Expansion;
+use crate::rational::{Fraction, Ratio};
+
+/// The most fractional digits tried; every `f64` round-trips within as many
+/// binary digits as its smallest subnormal has, and within fewer in larger
+/// bases.
+const MOST_DIGITS: u32 = 1100;
+
+/// The numerator of an exact `f64` value over a larger power of two.
+fn over(value: &Ratio, denominator: &BigUint) -> BigUint {
+    &value.numerator * (denominator / &value.denominator)
+}
+
+/// Writes the shortest expansion of a value in a base that still reads back
+/// as the same `f64`: the first digit count whose nearest value on either
+/// side lies strictly closer to the value than to its neighbouring `f64`s,
+/// or as close and the value has an even mantissa, since reading rounds
+/// ties to even.
+///
+/// # Arguments
+///
+/// * `value` - A finite floating-point number.
+/// * `target_base` - The positive integer base to write it in.
+///
+/// # Returns
+///
+/// The expansion, whose remainder tells how far the digits are from the
+/// stored value in units of the last digit's place.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(shortest(0.1, 10).to_string(), "0.1;");
+/// assert_eq!(shortest(0.1, 2).digits.len(), 55);
+/// assert_eq!(shortest(1.0 / 3.0, 3).to_string(), "0.1;");
+/// ```
+pub fn shortest(value: f64, target_base: u64) -> Expansion {
+    let magnitude = value.abs();
+    let mut expansion = Expansion {
+        negative: value < 0.0,
+        base: target_base as f64,
+        integer_digits: Vec::new(),
+        digits: Vec::new(),
+        remainder: 0.0,
+    };
+    let Some(exact) = Ratio::from_f64(magnitude).filter(|_| magnitude != 0.0) else {
+        return expansion;
+    };
+
+    // Reading rounds to this `f64` up to halfway to either neighbour. All
+    // three are exact over a common power of two, the largest denominator.
+    let bits = magnitude.to_bits();
+    let below = Ratio::from_f64(f64::from_bits(bits - 1)).expect("below a finite f64");
+    let above = Ratio::from_f64(f64::from_bits(bits + 1));
+    let denominator = [Some(&below), Some(&exact), above.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|value| value.denominator.clone())
+        .max()
+        .expect("at least two values");
+    let exact_numerator = over(&exact, &denominator);
+    let below = over(&below, &denominator);
+    let above = match &above {
+        Some(above) => over(above, &denominator),
+        // Past `f64::MAX`, reading rounds as if the next value were as far
+        // above as the previous one is below.
+        _ => &exact_numerator * 2u32 - &below,
+    };
+    let low = &below + &exact_numerator;
+    let high = &above + &exact_numerator;
+    let ties_allowed = bits & 1 == 0;
+    let reads_back = |candidate: &BigUint, place: &BigUint| {
+        // Is candidate / place between low / 2 and high / 2 over `denominator`?
+        let doubled = candidate * &denominator * 2u32;
+        let (low, high) = (&low * place, &high * place);
+        (doubled > low && doubled < high) || (ties_allowed && (doubled == low || doubled == high))
+    };
+
+    let base = BigUint::from(target_base);
+    let mut place = BigUint::from(1u32);
+    for count in 0..=MOST_DIGITS {
+        let scaled = &exact_numerator * &place;
+        let floor = &scaled / &denominator;
+        let ceiling = &floor + 1u32;
+        let found = [floor, ceiling]
+            .into_iter()
+            .find(|candidate| reads_back(candidate, &place));
+        if let Some(candidate) = found {
+            let integer = &candidate / &place;
+            let mut fraction = &candidate % &place;
+            let mut digits = vec![0; count as usize];
+            for digit in digits.iter_mut().rev() {
+                *digit = (&fraction % target_base).to_u32().unwrap_or(0);
+                fraction /= target_base;
+            }
+            let mut integer_digits = Vec::new();
+            let mut rest = integer;
+            while !rest.is_zero() {
+                integer_digits.push((&rest % target_base).to_u32().unwrap_or(0));
+                rest /= target_base;
+            }
+            integer_digits.reverse();
+
+            let over = &candidate * &denominator > scaled;
+            let gap = if over {
+                &candidate * &denominator - &scaled
+            } else {
+                &scaled - &candidate * &denominator
+            };
+            let gap = Fraction {
+                negative: false,
+                numerator: gap,
+                denominator: denominator.clone(),
+            }
+            .value();
+            expansion.integer_digits = integer_digits;
+            expansion.digits = digits;
+            expansion.remainder = if over { -gap } else { gap };
+            return expansion;
+        }
+        place *= &base;
+    }
+    expansion
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    use crate::convert::Representation;
+
+    #[test]
+    fn test_shortest_round_trips() {
+        assert_that!(shortest(0.1, 10).to_string(), equal_to("0.1;"));
+        assert_that!(shortest(-2.5, 10).to_string(), equal_to("-2.5;"));
+        assert_that!(
+            shortest(0.3, 16).to_string(),
+            equal_to("0.4;12;12;12;12;12;12;12;12;12;12;12;12;12;")
+        );
+        assert_that!(shortest(0.0, 7).to_string(), equal_to("0."));
+        assert!(shortest(0.5, 2).is_exact());
+        assert!(!shortest(0.1, 10).is_exact());
+
+        for value in [0.1, 1.0 / 3.0, 123.456, 1e300, 2f64.powi(-20)] {
+            for target_base in [2, 3, 7, 10, 16, 60] {
+                let expansion = shortest(value, target_base);
+                assert_that!(expansion.value(), close_to(value, value * 1e-12));
+            }
+        }
+        assert_that!(
+            shortest(0.1, 10).digits.len() as u32,
+            less_than_or_equal_to(17)
+        );
+        assert_that!(shortest(5e-324, 10).digits.len(), equal_to(324));
+        assert_that!(
+            shortest(f64::MAX, 10).integer_digits[..17].to_vec(),
+            equal_to(vec![1, 7, 9, 7, 6, 9, 3, 1, 3, 4, 8, 6, 2, 3, 1, 5, 7])
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+use zeroize::Zeroize;
+
+use crate::cli::flag_value;
+
+/// Writes bytes as lowercase hexadecimal.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Reads exactly `N` bytes written as hexadecimal, ignoring surrounding
+/// whitespace.
+pub fn parse_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    let text = text.trim();
+    if text.len() != 2 * N || !text.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (byte, pair) in bytes.iter_mut().zip(text.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// An Ed25519 secret key, read from a `--sign-key` file holding its 32-byte
+/// seed in hexadecimal.
+///
+/// Signing is done by `ed25519-dalek`, in constant time, and the key and the
+/// text it was read from are overwritten with zeros once dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SigningKey(ed25519_dalek::SigningKey);
+
+impl fmt::Debug for SigningKey {
+    /// Leaves the seed out, so that it never ends up in a log.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SigningKey({})", to_hex(&self.public_key()))
+    }
+}
+
+impl SigningKey {
+    /// Creates the key with the given seed.
+    pub fn from_seed(mut seed: [u8; 32]) -> SigningKey {
+        let key = SigningKey(ed25519_dalek::SigningKey::from_bytes(&seed));
+        seed.zeroize();
+        key
+    }
+
+    /// Reads a key file.
+    pub fn load(path: &str) -> Result<SigningKey, String> {
+        let mut text = fs::read(path)
+            .map_err(|error| format!("cannot read key file `{}`: {}", path, error))?;
+        let key = std::str::from_utf8(&text)
+            .ok()
+            .and_then(parse_hex)
+            .map(SigningKey::from_seed);
+        text.zeroize();
+        key.ok_or_else(|| format!("`{}` does not hold 64 hexadecimal digits", path))
+    }
+
+    /// The public key that verifies what this key signs.
+    pub fn public_key(&self) -> [u8; 32] {
+        self.0.verifying_key().to_bytes()
+    }
+
+    /// Signs a message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let key = SigningKey::from_seed([7; 32]);
+    /// assert!(verify(&key.public_key(), b"results", &key.sign(b"results")));
+    /// ```
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.0.sign(message).to_bytes()
+    }
+}
+
+/// Checks an Ed25519 signature of a message against a public key, refusing
+/// keys that are not points of the curve.
+pub fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    VerifyingKey::from_bytes(public_key).is_ok_and(|key| {
+        key.verify(message, &Signature::from_bytes(signature))
+            .is_ok()
+    })
+}
+
+/// The path the signature of a results file is written to.
+pub fn signature_path(path: &str) -> String {
+    format!("{}.sig", path)
+}
+
+/// Signs a results file, writing the public key and the signature to
+/// `<path>.sig`. That file is UTF-8 text of three lines, each ending in
+/// `\n`:
+///
+/// ```text
+/// ed25519 signature of <file name>
+/// public key: <the 32-byte public key, as 64 lowercase hexadecimal digits>
+/// signature: <the 64-byte signature, as 128 lowercase hexadecimal digits>
+/// ```
+///
+/// The signature is the RFC 8032 Ed25519 signature of the bytes of the file,
+/// with no prehashing or context. Only the `signature:` line is read back;
+/// the first line names the file for people, and the public key there is
+/// only for handing out, as [`parse_verify_args`] never trusts it.
+pub fn write_signature(path: &str, key: &SigningKey) -> Result<(), String> {
+    let message =
+        fs::read(path).map_err(|error| format!("cannot read `{}` to sign it: {}", path, error))?;
+    let name = Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let contents = format!(
+        "ed25519 signature of {}\npublic key: {}\nsignature: {}\n",
+        name,
+        to_hex(&key.public_key()),
+        to_hex(&key.sign(&message))
+    );
+    fs::write(signature_path(path), contents)
+        .map_err(|error| format!("cannot write `{}`: {}", signature_path(path), error))
+}
+
+/// Reads the signature from a file written by [`write_signature`].
+fn read_signature(path: &str) -> Result<[u8; 64], String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("cannot read signature `{}`: {}", path, error))?;
+    text.lines()
+        .find_map(|line| line.strip_prefix("signature:"))
+        .and_then(parse_hex)
+        .ok_or_else(|| format!("`{}` holds no signature", path))
+}
+
+/// The settings of the `verify` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// The results file to check.
+    pub file: String,
+    /// The signature file, `<file>.sig` unless given with `--signature`.
+    pub signature: String,
+    /// The public key the file must be signed with.
+    pub public_key: [u8; 32],
+}
+
+/// Parses the arguments of the `verify` subcommand.
+///
+/// The public key is trusted only when it comes from the instructor rather
+/// than from the signature file, so `--public-key` is required. It is given
+/// as 64 hexadecimal digits or as a file holding them.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `verify`.
+///
+/// # Returns
+///
+/// The parsed `VerifyOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- verify answers.md --public-key instructor.pub
+/// ```
+pub fn parse_verify_args(args: &[String]) -> Result<VerifyOptions, String> {
+    let mut file = None;
+    let mut signature = None;
+    let mut public_key = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--public-key", &mut iter)? {
+            let key = match parse_hex(value) {
+                Some(key) => Some(key),
+                None => fs::read_to_string(value)
+                    .map_err(|error| format!("cannot read public key `{}`: {}", value, error))
+                    .map(|text| parse_hex(&text))?,
+            };
+            public_key = Some(key.ok_or_else(|| format!("invalid public key `{}`", value))?);
+        } else if let Some(value) = flag_value(arg, "--signature", &mut iter)? {
+            signature = Some(value.to_string());
+        } else if file.is_none() {
+            file = Some(arg.clone());
+        } else {
+            return Err(format!("unknown verify argument `{}`", arg));
+        }
+    }
+
+    let file = file.ok_or("verify requires the file to check")?;
+    Ok(VerifyOptions {
+        signature: signature.unwrap_or_else(|| signature_path(&file)),
+        public_key: public_key.ok_or("verify requires --public-key")?,
+        file,
+    })
+}
+
+/// Runs the `verify` subcommand, reporting whether the file is exactly what
+/// the holder of the public key signed.
+///
+/// # Returns
+///
+/// Whether the signature is valid, or the error writing to `out`.
+pub fn display_verify(out: &mut impl Write, options: &VerifyOptions) -> io::Result<bool> {
+    let checked = fs::read(&options.file)
+        .map_err(|error| format!("cannot read `{}`: {}", options.file, error))
+        .and_then(|message| {
+            let signature = read_signature(&options.signature)?;
+            Ok(verify(&options.public_key, &message, &signature))
+        });
+    match checked {
+        Ok(true) => {
+            writeln!(out, "{}: signature OK", options.file)?;
+            Ok(true)
+        }
+        Ok(false) => {
+            writeln!(out, "{}: BAD signature", options.file)?;
+            Ok(false)
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            Ok(false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_rfc_8032_vectors() {
+        let key = SigningKey::from_seed(
+            parse_hex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap(),
+        );
+        assert_that!(
+            to_hex(&key.public_key()),
+            equal_to("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+        );
+        let signature = key.sign(b"");
+        assert_that!(
+            to_hex(&signature),
+            equal_to(
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555\
+                 fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"
+            )
+        );
+        assert!(verify(&key.public_key(), b"", &signature));
+        assert!(!verify(&key.public_key(), b"x", &signature));
+
+        let other = SigningKey::from_seed([1; 32]);
+        assert!(!verify(&other.public_key(), b"", &signature));
+    }
+
+    #[test]
+    fn test_verify_arguments() {
+        let key = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+        let args: Vec<String> = ["answers.md", "--public-key", key]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let options = parse_verify_args(&args).unwrap();
+        assert_that!(options.signature, equal_to("answers.md.sig".to_string()));
+        assert!(parse_verify_args(&args[..1]).is_err());
+        assert!(parse_verify_args(&["--public-key=00".to_string()]).is_err());
+    }
+}
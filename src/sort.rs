@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// What `--sort` orders the rows of a run by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// The numeric value of every input.
+    Value,
+    /// The order the inputs were given in, which `--desc` reverses.
+    InputOrder,
+    /// The number of digits of every input's expansion in the first base,
+    /// before and after the point.
+    DigitCount,
+}
+
+impl SortKey {
+    /// Looks up a sort key by its command-line name.
+    pub fn from_name(name: &str) -> Option<SortKey> {
+        match name {
+            "value" => Some(SortKey::Value),
+            "input-order" => Some(SortKey::InputOrder),
+            "digit-count" => Some(SortKey::DigitCount),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the value of `--sort`.
+pub fn parse_sort_key(value: &str) -> Result<SortKey, String> {
+    SortKey::from_name(value).ok_or_else(|| {
+        format!(
+            "unknown sort key `{}`, expected value, input-order or digit-count",
+            value
+        )
+    })
+}
+
+/// Orders decoded inputs by a key, keeping inputs that tie in the order
+/// they were given.
+///
+/// # Arguments
+///
+/// * `inputs` - Every input's value and the fraction it was written as, if
+///   any, each with whatever else goes along with its row.
+/// * `key` - What to order them by.
+/// * `descending` - Whether the largest come first.
+/// * `pipeline` - Expands the inputs when they are ordered by digit count.
+///
+/// # Example
+///
+/// ```
+/// let mut inputs = vec![((0.5, None), 'a'), ((-1.0, None), 'b'), ((0.25, None), 'c')];
+/// sort_inputs(&mut inputs, SortKey::Value, false, &Pipeline::with_bases(vec![2.0]));
+/// assert_eq!(inputs, vec![((-1.0, None), 'b'), ((0.25, None), 'c'), ((0.5, None), 'a')]);
+/// ```
+pub fn sort_inputs<T>(
+    inputs: &mut [((f64, Option<Fraction>), T)],
+    key: SortKey,
+    descending: bool,
+    pipeline: &Pipeline,
+) {
+    let order = |ordering: Ordering| {
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+    match key {
+        SortKey::Value => inputs.sort_by(|((a, _), _), ((b, _), _)| order(a.total_cmp(b))),
+        SortKey::InputOrder => {
+            if descending {
+                inputs.reverse();
+            }
+        }
+        SortKey::DigitCount => {
+            let Some(&target_base) = pipeline.target_bases.first() else {
+                return;
+            };
+            // The counts are negated rather than the order reversed, so
+            // that ties stay in input order.
+            let sign = if descending { -1 } else { 1 };
+            inputs.sort_by_cached_key(|((num, fraction), _)| {
+                let expansion = pipeline.expand_input(*num, fraction.as_ref(), target_base);
+                sign * (expansion.integer_digits.len() + expansion.digits.len()) as i64
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn sorted(values: &[f64], key: SortKey, descending: bool) -> Vec<f64> {
+        let mut inputs: Vec<((f64, Option<Fraction>), ())> =
+            values.iter().map(|&value| ((value, None), ())).collect();
+        sort_inputs(
+            &mut inputs,
+            key,
+            descending,
+            &Pipeline::with_bases(vec![2.0]),
+        );
+        inputs.into_iter().map(|((value, _), ())| value).collect()
+    }
+
+    #[test]
+    fn test_sort_inputs() {
+        let values = [0.5, -2.0, 0.1, 0.375];
+        assert_that!(
+            sorted(&values, SortKey::Value, false),
+            equal_to(vec![-2.0, 0.1, 0.375, 0.5])
+        );
+        assert_that!(
+            sorted(&values, SortKey::Value, true),
+            equal_to(vec![0.5, 0.375, 0.1, -2.0])
+        );
+        assert_that!(
+            sorted(&values, SortKey::InputOrder, true),
+            equal_to(vec![0.375, 0.1, -2.0, 0.5])
+        );
+        // 0.5 is 0.1 with one digit, -2 is -10 with two, 0.375 is 0.011 with
+        // three, and 0.1 runs to the digit limit.
+        assert_that!(
+            sorted(&values, SortKey::DigitCount, false),
+            equal_to(vec![0.5, -2.0, 0.375, 0.1])
+        );
+        assert_that!(
+            sorted(&values, SortKey::DigitCount, true),
+            equal_to(vec![0.1, 0.375, -2.0, 0.5])
+        );
+        assert!(parse_sort_key("size").is_err());
+    }
+}
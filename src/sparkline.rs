@@ -0,0 +1,136 @@
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::digits_value::digits_value;
+use crate::rational::Ratio;
+
+/// The header of the column of how fast every expansion converges.
+pub const SPARKLINE_HEADER: &str = "Convergence";
+
+/// The bars of a sparkline, lowest first.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The base 2 logarithm of a positive number, which may be far too large
+/// or too small for an `f64`.
+fn log2(value: &Ratio) -> f64 {
+    let log2 = |value: &BigUint| {
+        let shift = value.bits().saturating_sub(64);
+        let top = (value >> shift).to_u64().unwrap_or(u64::MAX) as f64;
+        top.log2() + shift as f64
+    };
+    log2(&value.numerator) - log2(&value.denominator)
+}
+
+/// The running error `|value − partial sum|` of positional digits, as the
+/// integer part and then every fractional digit in turn are added, drawn as
+/// one bar per step on a logarithmic scale: the largest error is `█`, the
+/// smallest one that is not zero `▂`, and an error of zero, once the digits
+/// reach the value exactly, `▁`.
+///
+/// # Arguments
+///
+/// * `exact` - The exact value the digits approach.
+/// * `negative` - Whether the digits are written with a minus sign.
+/// * `target_base` - The base the digits are in, which may be negative.
+/// * `integer` - The digits of the integer part, most significant first.
+/// * `fraction` - The fractional digits, most significant first.
+///
+/// # Returns
+///
+/// The sparkline, one bar longer than there are fractional digits, or
+/// `None` for a base that is not a whole number of magnitude 2 or more.
+///
+/// # Example
+///
+/// ```
+/// // 0.0;0;0;1; in base 2, the first four digits of 1/10
+/// let tenth = Ratio::from(Fraction::parse("1/10").unwrap());
+/// let sparkline = convergence_sparkline(&tenth, false, 2.0, &[], &[0, 0, 0, 1]).unwrap();
+/// assert_eq!(sparkline, "████▂");
+/// ```
+pub fn convergence_sparkline(
+    exact: &Ratio,
+    negative: bool,
+    target_base: f64,
+    integer: &[u32],
+    fraction: &[u32],
+) -> Option<String> {
+    let base = BigUint::from(target_base.abs() as u64);
+    let mut error = exact.minus(&digits_value(negative, target_base, integer, &[])?);
+    let mut scale = BigUint::from(1u32);
+    let mut errors = vec![error.clone()];
+    for (i, &digit) in fraction.iter().enumerate() {
+        scale *= &base;
+        // The ith digit weighs base⁻ⁱ, negative for a negative base to an
+        // odd power.
+        let term = Ratio {
+            negative: negative != (target_base < 0.0 && i % 2 == 0),
+            numerator: BigUint::from(digit),
+            denominator: scale.clone(),
+        };
+        error = error.minus(&term);
+        errors.push(error.clone());
+    }
+
+    let logs: Vec<Option<f64>> = errors
+        .iter()
+        .map(|error| (!error.numerator.is_zero()).then(|| log2(error)))
+        .collect();
+    let lowest = logs.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+    let highest = logs
+        .iter()
+        .flatten()
+        .copied()
+        .fold(f64::NEG_INFINITY, f64::max);
+    Some(
+        logs.iter()
+            .map(|log| match log {
+                None => BARS[0],
+                Some(_) if highest == lowest => BARS[BARS.len() - 1],
+                Some(log) => {
+                    let height = (log - lowest) / (highest - lowest) * (BARS.len() - 2) as f64;
+                    BARS[1 + height.round() as usize]
+                }
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rational::Fraction;
+    use hamcrest2::prelude::*;
+
+    fn ratio(fraction: &str) -> Ratio {
+        Ratio::from(Fraction::parse(fraction).unwrap())
+    }
+
+    #[test]
+    fn test_convergence_sparkline() {
+        // 0.1 in base 2 is 0.0;0;0;1;1;0;0;1;…, with an error of 1/10, 1/10,
+        // 1/10, 1/10, 3/80, 1/160, 1/160, 1/160 and 3/1280.
+        assert_that!(
+            convergence_sparkline(&ratio("1/10"), false, 2.0, &[], &[0, 0, 0, 1, 1, 0, 0, 1]),
+            equal_to(Some("████▆▄▄▄▂".to_string()))
+        );
+        assert_that!(
+            convergence_sparkline(&ratio("1/4"), false, 2.0, &[], &[0, 1]),
+            equal_to(Some("██▁".to_string()))
+        );
+        assert_that!(
+            convergence_sparkline(&ratio("-5/4"), true, 2.0, &[1], &[0, 1]),
+            equal_to(Some("██▁".to_string()))
+        );
+        // 0.1;1; in base -2 is -1/2 + 1/4.
+        assert_that!(
+            convergence_sparkline(&ratio("-1/4"), false, -2.0, &[], &[1, 1]),
+            equal_to(Some("██▁".to_string()))
+        );
+        assert_that!(
+            convergence_sparkline(&ratio("1/2"), false, 1.5, &[], &[1]),
+            equal_to(None)
+        );
+        assert_that!(log2(&ratio("1/1024")), equal_to(-10.0));
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::{self, Write};
+
+use rusqlite::config::DbConfig;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags, MAIN_DB};
+
+use crate::conversion::Conversion;
+
+/// The first bytes of every SQLite database file.
+pub const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// The table `--format sqlite` writes the conversions into.
+pub const RESULTS_TABLE: &str = "conversions";
+
+/// A value of a column of a SQLite row as an input to convert: numbers and
+/// text as they read, and `NULL` and blobs as words no decoder reads.
+fn as_input(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(value) => value.to_string(),
+        ValueRef::Real(value) => value.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).trim().to_string(),
+        ValueRef::Blob(_) => "BLOB".to_string(),
+    }
+}
+
+/// Quotes a table or column name for SQL, so that any name can be given.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Turns a SQLite error into the message `--file` reports.
+fn sqlite_error(error: rusqlite::Error) -> String {
+    error.to_string()
+}
+
+/// The inputs of a `--file` that is a SQLite database: the values of the
+/// column of the table `--table` and `--column` name, each with its rowid,
+/// the row that warnings about it name.
+///
+/// # Returns
+///
+/// The rowid and the input of every row, in rowid order, or a message if
+/// the table or column is missing or the database cannot be read.
+///
+/// # Example
+///
+/// ```
+/// // cargo run -- --file prices.db --table items --column price
+/// ```
+pub fn table_inputs(
+    path: &str,
+    table: Option<&str>,
+    column: Option<&str>,
+) -> Result<Vec<(usize, String)>, String> {
+    let (Some(table), Some(column)) = (table, column) else {
+        return Err(
+            "a SQLite database is read with --table <name> and --column <name>".to_string(),
+        );
+    };
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(sqlite_error)?;
+    // Without this, SQLite reads a quoted name that is no column as a string.
+    connection
+        .set_db_config(DbConfig::SQLITE_DBCONFIG_DQS_DML, false)
+        .map_err(sqlite_error)?;
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT rowid, {} FROM {} ORDER BY rowid",
+            quote_identifier(column),
+            quote_identifier(table)
+        ))
+        .map_err(sqlite_error)?;
+    let rows = statement
+        .query_map([], |row| {
+            let rowid: i64 = row.get(0)?;
+            Ok((
+                usize::try_from(rowid).unwrap_or(0),
+                as_input(row.get_ref(1)?),
+            ))
+        })
+        .map_err(sqlite_error)?;
+    rows.collect::<Result<_, _>>().map_err(sqlite_error)
+}
+
+/// Writes conversions as a SQLite database with a `conversions` table of
+/// the columns `input REAL`, `base TEXT`, `digits INTEGER`,
+/// `truncated INTEGER` and `repr TEXT`, after a `label TEXT` column when
+/// the inputs were labelled, and a row per input and base.
+///
+/// # Arguments
+///
+/// * `out` - Where the database is written.
+/// * `conversions` - The conversion of every input to every target base,
+///   either all with a label or all without.
+pub fn write_sqlite(
+    out: &mut dyn Write,
+    conversions: impl IntoIterator<Item = Conversion>,
+) -> io::Result<()> {
+    let mut conversions = conversions.into_iter().peekable();
+    let labelled = conversions
+        .peek()
+        .is_some_and(|conversion| conversion.label.is_some());
+    let written = || -> rusqlite::Result<Vec<u8>> {
+        let mut connection = Connection::open_in_memory()?;
+        connection.execute(
+            &format!(
+                "CREATE TABLE {}({}input REAL, base TEXT, digits INTEGER, truncated INTEGER, \
+                 repr TEXT)",
+                RESULTS_TABLE,
+                if labelled { "label TEXT, " } else { "" }
+            ),
+            [],
+        )?;
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare(&format!(
+                "INSERT INTO {}({}input, base, digits, truncated, repr) VALUES ({}?, ?, ?, ?, ?)",
+                RESULTS_TABLE,
+                if labelled { "label, " } else { "" },
+                if labelled { "?, " } else { "" }
+            ))?;
+            for conversion in conversions {
+                let mut values = Vec::new();
+                if labelled {
+                    values.push(Value::Text(conversion.label.unwrap_or_default()));
+                }
+                values.extend([
+                    Value::Real(conversion.input),
+                    Value::Text(conversion.base),
+                    Value::Integer(conversion.digits as i64),
+                    Value::Integer(i64::from(conversion.truncated)),
+                    Value::Text(conversion.repr),
+                ]);
+                insert.execute(params_from_iter(values))?;
+            }
+        }
+        transaction.commit()?;
+        let data = connection.serialize(MAIN_DB)?;
+        Ok(data.to_vec())
+    };
+    let bytes = written().map_err(io::Error::other)?;
+    out.write_all(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_written_database_reads_back() {
+        let long = Conversion::new(0.1, 3.0, &expand(0.1, 3, 3000));
+        let rows: Vec<Conversion> = (0..2000)
+            .map(|i| Conversion::new(f64::from(i) / 8.0, 2.0, &expand(f64::from(i) / 8.0, 2, 8)))
+            .chain([long.clone()])
+            .collect();
+        let mut out = Vec::new();
+        write_sqlite(&mut out, rows).unwrap();
+        assert!(out.starts_with(SQLITE_MAGIC));
+
+        let path = env::temp_dir().join(format!("sqlite_test_{}.db", std::process::id()));
+        fs::write(&path, out).unwrap();
+        let path = path.to_str().unwrap();
+        let inputs = table_inputs(path, Some("conversions"), Some("input")).unwrap();
+        assert_that!(inputs.len(), equal_to(2001));
+        assert_that!(inputs[9].clone(), equal_to((10, "1.125".to_string())));
+        let reprs = table_inputs(path, Some("Conversions"), Some("repr")).unwrap();
+        assert_that!(reprs.last().cloned(), equal_to(Some((2001, long.repr))));
+        assert!(table_inputs(path, Some("conversions"), Some("price")).is_err());
+        assert!(table_inputs(path, Some("prices"), Some("input")).is_err());
+        assert!(table_inputs(path, Some("conversions"), None).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::{self, Write};
+
+use crate::backend::Backend;
+use crate::cli::flag_value;
+use crate::find::{parse_digits, parse_single_base};
+use crate::pipeline::{parse_backend, Pipeline};
+use crate::rational::Fraction;
+
+/// The number of fractional digits `stats` tallies when no `--digits` is
+/// given, enough for the counts of every digit to settle.
+pub const DEFAULT_STATS_DIGITS: u32 = 1000;
+
+/// The largest base `stats` tallies, which keeps its table readable.
+pub const LARGEST_STATS_BASE: u64 = 1000;
+
+/// The settings of the `stats` subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsOptions {
+    /// The base the expansions are computed in.
+    pub target_base: u64,
+    /// The maximum number of fractional digits to produce for every input.
+    pub digits: u32,
+    /// The backend forced with `--backend`, or `None` to pick an exact one
+    /// per input.
+    pub backend: Option<Backend>,
+    /// Every input as typed, with its value and the exact fraction it was
+    /// written as, if any.
+    pub inputs: Vec<(String, f64, Option<Fraction>)>,
+}
+
+/// How often every digit value appears in some expansions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigitCounts {
+    /// For every digit value, from 0 up, how many times it appears.
+    pub counts: Vec<u64>,
+}
+
+impl DigitCounts {
+    /// Starts the tally of the digits of a base, with every count at zero.
+    pub fn new(target_base: u64) -> DigitCounts {
+        DigitCounts {
+            counts: vec![0; target_base as usize],
+        }
+    }
+
+    /// Counts every digit of an expansion.
+    pub fn add(&mut self, digits: &[u32]) {
+        for &digit in digits {
+            self.counts[digit as usize] += 1;
+        }
+    }
+
+    /// The number of digits counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Pearson's χ² statistic of the counts against every digit being
+    /// equally likely, which stays near the base minus one for digits that
+    /// are evenly spread and grows with the number of digits when they are
+    /// not.
+    ///
+    /// # Returns
+    ///
+    /// The statistic, or `None` when no digits were counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut counts = DigitCounts::new(2);
+    /// counts.add(&[0, 1, 1, 1]);
+    /// assert_eq!(counts.chi_squared(), Some(1.0));
+    /// ```
+    pub fn chi_squared(&self) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let expected = total as f64 / self.counts.len() as f64;
+        Some(
+            self.counts
+                .iter()
+                .map(|&count| (count as f64 - expected).powi(2) / expected)
+                .sum(),
+        )
+    }
+}
+
+/// Parses the arguments of the `stats` subcommand.
+///
+/// Inputs are decimals or fractions such as `1/7`, which are divided out
+/// exactly.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `stats`.
+///
+/// # Returns
+///
+/// The parsed `StatsOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- stats --base 10 --digits 5000 1/7 1/97 0.1
+/// ```
+pub fn parse_stats_args(args: &[String]) -> Result<StatsOptions, String> {
+    let mut options = StatsOptions {
+        target_base: 10,
+        digits: DEFAULT_STATS_DIGITS,
+        backend: None,
+        inputs: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            options.target_base = parse_single_base(value)?;
+            if options.target_base > LARGEST_STATS_BASE {
+                return Err(format!(
+                    "stats supports bases up to {}, not {}",
+                    LARGEST_STATS_BASE, options.target_base
+                ));
+            }
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            options.digits = parse_digits(value)?;
+        } else if let Some(value) = flag_value(arg, "--backend", &mut iter)? {
+            options.backend = parse_backend(value)?;
+        } else {
+            let (value, fraction) = Pipeline::with_bases(vec![2.0])
+                .decode_input(arg)
+                .filter(|(value, _)| value.is_finite())
+                .ok_or_else(|| format!("unknown stats argument `{}`", arg))?;
+            options.inputs.push((arg.clone(), value, fraction));
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("stats requires a value to expand".to_string());
+    }
+    Ok(options)
+}
+
+/// Tallies the fractional digits of every input.
+///
+/// # Returns
+///
+/// The counts of every input, in the order they were given, followed by
+/// those of all of them together.
+pub fn digit_statistics(options: &StatsOptions) -> Vec<DigitCounts> {
+    let mut all = DigitCounts::new(options.target_base);
+    let mut tallies: Vec<DigitCounts> = options
+        .inputs
+        .iter()
+        .map(|(_, value, fraction)| {
+            let expansion = Backend::expand_auto(
+                options.backend,
+                *value,
+                fraction.as_ref(),
+                options.target_base,
+                options.digits,
+            );
+            let mut counts = DigitCounts::new(options.target_base);
+            counts.add(&expansion.digits);
+            all.add(&expansion.digits);
+            counts
+        })
+        .collect();
+    tallies.push(all);
+    tallies
+}
+
+/// Writes a count with its share of the total, as in `142 (14.2%)`.
+fn format_count(count: u64, total: u64) -> String {
+    if total == 0 {
+        return count.to_string();
+    }
+    format!("{} ({:.1}%)", count, 100.0 * count as f64 / total as f64)
+}
+
+/// Runs the `stats` subcommand, printing a table with a row for every digit
+/// value and a column for every input and for all of them together, ending
+/// with the number of digits and the χ² statistic of every column.
+///
+/// # Example
+/// ```
+/// // cargo run -- stats --base 2 --digits 4 0.625 0.75
+/// ```
+/// Output:
+/// Fractional digits in base 2, at most 4 per input
+/// |  Digit   |    0.625     |     0.75     |     All      |
+/// |:---------|:-------------|:-------------|:-------------|
+/// | 0        | 1 (33.3%)    | 0 (0.0%)     | 1 (20.0%)    |
+/// | 1        | 2 (66.7%)    | 2 (100.0%)   | 4 (80.0%)    |
+/// | Digits   | 3            | 2            | 5            |
+/// | χ²       | 0.333        | 2.000        | 1.800        |
+pub fn display_stats(out: &mut impl Write, options: &StatsOptions) -> io::Result<()> {
+    let tallies = digit_statistics(options);
+    let titles = options
+        .inputs
+        .iter()
+        .map(|(input, _, _)| input.as_str())
+        .chain(["All"]);
+    let row = |out: &mut dyn Write, label: &str, cells: Vec<String>| {
+        let cells: String = cells
+            .iter()
+            .map(|cell| format!(" {:<12} |", cell))
+            .collect();
+        writeln!(out, "| {:<8} |{}", label, cells)
+    };
+
+    writeln!(
+        out,
+        "Fractional digits in base {}, at most {} per input",
+        options.target_base, options.digits
+    )?;
+    let header: String = titles.map(|title| format!(" {:^12} |", title)).collect();
+    writeln!(out, "| {:^8} |{}", "Digit", header)?;
+    writeln!(
+        out,
+        "|{:-<10}|{}",
+        ":",
+        format!("{:-<14}|", ":").repeat(tallies.len())
+    )?;
+    for digit in 0..options.target_base as usize {
+        row(
+            out,
+            &digit.to_string(),
+            tallies
+                .iter()
+                .map(|counts| format_count(counts.counts[digit], counts.total()))
+                .collect(),
+        )?;
+    }
+    row(
+        out,
+        "Digits",
+        tallies
+            .iter()
+            .map(|counts| counts.total().to_string())
+            .collect(),
+    )?;
+    row(
+        out,
+        "χ²",
+        tallies
+            .iter()
+            .map(|counts| match counts.chi_squared() {
+                Some(chi_squared) => format!("{:.3}", chi_squared),
+                None => "n/a".to_string(),
+            })
+            .collect(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_digit_statistics() {
+        let options = parse_stats_args(&args("--base 10 --digits 60 1/7 0.25")).unwrap();
+        let tallies = digit_statistics(&options);
+        assert_that!(tallies.len(), equal_to(3));
+        // 1/7 repeats 142857, ten times in 60 digits.
+        assert_that!(
+            tallies[0].counts.clone(),
+            equal_to(vec![0, 10, 10, 0, 10, 10, 0, 10, 10, 0])
+        );
+        assert_that!(tallies[1].total(), equal_to(2));
+        assert_that!(tallies[2].total(), equal_to(62));
+        assert_that!(tallies[2].counts[2], equal_to(11));
+
+        let mut even = DigitCounts::new(4);
+        even.add(&[0, 1, 2, 3]);
+        assert_that!(even.chi_squared(), equal_to(Some(0.0)));
+        assert_that!(DigitCounts::new(4).chi_squared(), equal_to(None));
+
+        assert!(parse_stats_args(&args("--base 10")).is_err());
+        assert!(parse_stats_args(&args("--base 1001 0.5")).is_err());
+        assert!(parse_stats_args(&args("0.5 junk")).is_err());
+        assert!(parse_stats_args(&args("--digits many 0.5")).is_err());
+    }
+}
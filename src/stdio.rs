@@ -0,0 +1,271 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::aggregate::parse_json_fields;
+use crate::cli::flag_value;
+use crate::json::{to_json, write_json_string};
+use crate::pipeline::{parse_base_list, Pipeline};
+use crate::serve::{convert_value, parse_precision};
+
+/// The longest request `--serve-stdio` reads, in bytes.
+const MAX_REQUEST: usize = 64 * 1024;
+
+/// How the requests and responses of `--serve-stdio` are told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Every message is one line of JSON.
+    Line,
+    /// Every message is a line holding its length in bytes, followed by that
+    /// many bytes of JSON.
+    Length,
+}
+
+/// The settings of `--serve-stdio`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdioOptions {
+    /// How the messages are framed, from `--framing`.
+    pub framing: Framing,
+}
+
+/// Parses the arguments of `--serve-stdio`, which may come in any order.
+///
+/// # Returns
+///
+/// The parsed `StdioOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- --serve-stdio --framing length
+/// ```
+pub fn parse_stdio_args(args: &[String]) -> Result<StdioOptions, String> {
+    let mut framing = Framing::Line;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--framing", &mut iter)? {
+            framing = match value {
+                "line" => Framing::Line,
+                "length" => Framing::Length,
+                _ => {
+                    return Err(format!(
+                        "unknown framing `{}`, expected line or length",
+                        value
+                    ))
+                }
+            };
+        } else if arg != "--serve-stdio" {
+            return Err(format!("unknown --serve-stdio argument `{}`", arg));
+        }
+    }
+
+    Ok(StdioOptions { framing })
+}
+
+/// Answers one request, a JSON object such as
+/// `{"id":1,"value":"0.1","base":16,"precision":20}`, with the conversion
+/// as the HTTP server writes it. `base` defaults to 2 and `precision` to the
+/// usual 8 digits, at most `MAX_PRECISION`, and `value` may also be a
+/// fraction such as `1/3`. An `id` is echoed first in the response, as a
+/// string, so that answers can be matched to requests.
+///
+/// # Returns
+///
+/// The response, `{"error": message}` for a request that cannot be
+/// answered.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(
+///     answer(r#"{"id":1,"value":0.5}"#),
+///     r#"{"id":"1","input":0.5,"base":"2","digits":1,"truncated":false,"repr":"0.1;"}"#
+/// );
+/// ```
+pub fn answer(request: &str) -> String {
+    let mut id = None;
+    let body = match parse_json_fields(request) {
+        Some(fields) => {
+            id = fields
+                .iter()
+                .find(|(key, _)| key == "id")
+                .map(|(_, value)| value.clone());
+            convert_request(&fields).unwrap_or_else(|message| {
+                let mut body = String::from("{\"error\":");
+                write_json_string(&mut body, &message);
+                body.push('}');
+                body
+            })
+        }
+        None => "{\"error\":\"a request is a JSON object on its own\"}".to_string(),
+    };
+    match id {
+        Some(id) => {
+            let mut response = String::from("{\"id\":");
+            write_json_string(&mut response, &id);
+            response.push(',');
+            response.push_str(&body[1..]);
+            response
+        }
+        None => body,
+    }
+}
+
+/// Converts the value of a request to its base with its precision.
+fn convert_request(fields: &[(String, String)]) -> Result<String, String> {
+    let mut pipeline = Pipeline::with_bases(vec![2.0]);
+    let mut value = None;
+    for (key, field) in fields {
+        match key.as_str() {
+            "id" => {}
+            "value" => value = Some(field),
+            "base" => match parse_base_list(field).as_deref() {
+                Ok([target_base]) => pipeline.target_bases = vec![*target_base],
+                Ok(_) => return Err("only one base can be given".to_string()),
+                Err(message) => return Err(message.clone()),
+            },
+            "precision" => pipeline.digits = parse_precision(field)?,
+            _ => return Err(format!("unknown field `{}`", key)),
+        }
+    }
+    let value = value.ok_or("the value field is required")?;
+    let conversion = convert_value(&pipeline, value)?;
+    to_json(&conversion).map_err(|error| error.to_string())
+}
+
+/// Answers the requests read from `input` on `output`, each as soon as it
+/// is read, until `input` ends.
+///
+/// # Arguments
+///
+/// * `input` - Where the requests are read from.
+/// * `output` - Where the responses are written, flushed after each one.
+/// * `framing` - How the messages are framed in both directions.
+///
+/// # Returns
+///
+/// An error if reading or writing fails, or if a length-prefixed message
+/// has an invalid length, after which the messages cannot be told apart.
+pub fn serve_stdio(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    framing: Framing,
+) -> io::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input
+            .by_ref()
+            .take(MAX_REQUEST as u64)
+            .read_line(&mut line)?
+            == 0
+        {
+            return Ok(());
+        }
+        let header = line.trim();
+        if header.is_empty() {
+            continue;
+        }
+        match framing {
+            Framing::Line => writeln!(output, "{}", answer(header))?,
+            Framing::Length => {
+                let length = header
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&length| length <= MAX_REQUEST)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("invalid message length `{}`", header),
+                        )
+                    })?;
+                let mut request = vec![0; length];
+                input.read_exact(&mut request)?;
+                let response = answer(&String::from_utf8_lossy(&request));
+                write!(output, "{}\n{}", response.len(), response)?;
+            }
+        }
+        output.flush()?;
+    }
+}
+
+/// Runs `--serve-stdio`, answering requests on standard input until it
+/// ends.
+///
+/// # Returns
+///
+/// Whether every request could be read and answered.
+pub fn display_serve_stdio(options: &StdioOptions) -> bool {
+    match serve_stdio(
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+        options.framing,
+    ) {
+        Ok(()) => true,
+        Err(error) => {
+            eprintln!("error: --serve-stdio stopped: {}", error);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn served(requests: &str, framing: Framing) -> io::Result<String> {
+        let mut output = Vec::new();
+        serve_stdio(&mut requests.as_bytes(), &mut output, framing)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_answer() {
+        assert_that!(
+            answer(r#"{"value":"1/3","base":"10","precision":3}"#),
+            equal_to(
+                r#"{"input":0.3333333333333333,"base":"10","digits":3,"truncated":true,"repr":"0.3;3;3;"}"#
+                    .to_string()
+            )
+        );
+        assert_that!(
+            answer(r#"{"id":"a","value":"x"}"#),
+            equal_to(r#"{"id":"a","error":"invalid value `x`"}"#.to_string())
+        );
+        assert_that!(
+            answer(r#"{"base":2}"#),
+            equal_to(r#"{"error":"the value field is required"}"#.to_string())
+        );
+        assert!(answer(r#"{"value":0.5,"base":"2,8"}"#).contains("only one base"));
+        assert!(answer("0.5").starts_with("{\"error\":"));
+        assert!(answer(r#"{"value":"1/7","precision":4000000000}"#).contains("above the maximum"));
+    }
+
+    #[test]
+    fn test_serve_stdio() {
+        assert_that!(
+            served("{\"value\":0.5}\n\n{\"value\":0.25,\"base\":4}\n", Framing::Line).unwrap(),
+            equal_to(
+                "{\"input\":0.5,\"base\":\"2\",\"digits\":1,\"truncated\":false,\"repr\":\"0.1;\"}\n\
+                 {\"input\":0.25,\"base\":\"4\",\"digits\":1,\"truncated\":false,\"repr\":\"0.1;\"}\n"
+                    .to_string()
+            )
+        );
+        let response = served("13\n{\"value\":0.5}", Framing::Length).unwrap();
+        assert!(response.starts_with("67\n{\"input\":0.5,"));
+        assert_that!(response.len(), equal_to(3 + 67));
+        assert!(served("lots\n{}", Framing::Length).is_err());
+
+        assert_that!(
+            parse_stdio_args(&args("--serve-stdio --framing length")),
+            equal_to(Ok(StdioOptions {
+                framing: Framing::Length
+            }))
+        );
+        assert!(parse_stdio_args(&args("--serve-stdio --framing xml")).is_err());
+        assert!(parse_stdio_args(&args("--serve-stdio 0.5")).is_err());
+    }
+}
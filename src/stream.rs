@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+
+use crate::comment::strip_comment;
+use crate::pipeline::Pipeline;
+use crate::rational::Fraction;
+
+/// The `--input` path that stands for standard input.
+pub const STDIN_PATH: &str = "-";
+
+/// Where an input lies in a file read line by line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// The number of the line it is on, counted from 1.
+    pub line: u64,
+    /// The number of bytes before it.
+    pub offset: u64,
+}
+
+impl Position {
+    /// The start of a file.
+    pub const START: Position = Position { line: 1, offset: 0 };
+}
+
+/// Opens a file, or standard input for `-`, to be read line by line.
+pub fn open_input(path: &str) -> io::Result<Box<dyn BufRead>> {
+    open_input_at(path, 0)
+}
+
+/// Opens a file, or standard input for `-`, to be read line by line from
+/// `offset` bytes in. A file is sought to the offset, while the bytes of
+/// standard input before it are read and dropped.
+pub fn open_input_at(path: &str, offset: u64) -> io::Result<Box<dyn BufRead>> {
+    let cannot_read = |error: io::Error| {
+        io::Error::new(
+            error.kind(),
+            format!("cannot read input file `{}`: {}", path, error),
+        )
+    };
+    let mut reader: Box<dyn BufRead> = if path == STDIN_PATH {
+        Box::new(io::stdin().lock())
+    } else {
+        let mut file = File::open(path).map_err(cannot_read)?;
+        file.seek(SeekFrom::Start(offset)).map_err(cannot_read)?;
+        return Ok(Box::new(BufReader::new(file)));
+    };
+    let skipped = io::copy(&mut reader.by_ref().take(offset), &mut io::sink())?;
+    if skipped < offset {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("standard input ends before byte {}", offset),
+        ));
+    }
+    Ok(reader)
+}
+
+/// Reads inputs from a file, or standard input for `-`, one line at a time,
+/// and hands every one that decodes to `each` as soon as it is read. Only
+/// one line is held in memory at once, however long the input is.
+///
+/// # Arguments
+///
+/// * `path` - The file to read, or `-`.
+/// * `start` - Where to start reading, such as `Position::START`.
+/// * `pipeline` - Decodes the inputs, and stops the reading early once its
+///   cancel token is cancelled.
+/// * `each` - Called with every decoded value, the fraction it was written
+///   as, if any, and the position right after it. Reading stops at the
+///   first error it returns.
+/// * `invalid` - Called with every input that does not decode and the
+///   position right after it. Reading stops at the first error it returns.
+pub fn for_each_streamed_input(
+    path: &str,
+    start: Position,
+    pipeline: &Pipeline,
+    mut each: impl FnMut(f64, Option<&Fraction>, Position) -> io::Result<()>,
+    mut invalid: impl FnMut(&str, Position) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut reader = open_input_at(path, start.offset)?;
+    let mut line = String::new();
+    let mut line_start = start;
+    while reader.read_line(&mut line)? > 0 {
+        for arg in strip_comment(&line).split_whitespace() {
+            if pipeline.cancel.is_cancelled() {
+                return Ok(());
+            }
+            // Every input is a slice of the line, so its end is found from
+            // where it starts in memory.
+            let end = arg.as_ptr() as usize - line.as_ptr() as usize + arg.len();
+            let after = Position {
+                line: line_start.line,
+                offset: line_start.offset + end as u64,
+            };
+            match pipeline.decode_input(arg) {
+                Some((value, fraction)) => each(value, fraction.as_ref(), after)?,
+                None => invalid(arg, after)?,
+            }
+        }
+        line_start = Position {
+            line: line_start.line + 1,
+            offset: line_start.offset + line.len() as u64,
+        };
+        line.clear();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+    use std::{env, fs};
+
+    #[test]
+    fn test_streamed_inputs() {
+        let path = env::temp_dir().join(format!("stream-{}", std::process::id()));
+        fs::write(&path, "0.25 junk\n\n1/3\n0.5").unwrap();
+        let pipeline = Pipeline::with_bases(vec![2.0]);
+        let mut seen = Vec::new();
+        let mut invalid = Vec::new();
+        for_each_streamed_input(
+            &path.to_string_lossy(),
+            Position::START,
+            &pipeline,
+            |value, fraction, after| {
+                seen.push((value, fraction.is_some(), after.line, after.offset));
+                Ok(())
+            },
+            |arg, _| {
+                invalid.push(arg.to_string());
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_that!(
+            seen,
+            equal_to(vec![
+                (0.25, false, 1, 4),
+                (1.0 / 3.0, true, 3, 14),
+                (0.5, false, 4, 18)
+            ])
+        );
+        assert_that!(invalid, equal_to(vec!["junk".to_string()]));
+
+        // Reading on from right after `junk` picks up where it left off.
+        let mut resumed = Vec::new();
+        for_each_streamed_input(
+            &path.to_string_lossy(),
+            Position { line: 1, offset: 9 },
+            &pipeline,
+            |value, _, after| {
+                resumed.push((value, after.line, after.offset));
+                Ok(())
+            },
+            |_, _| Ok(()),
+        )
+        .unwrap();
+        assert_that!(resumed, equal_to(vec![(1.0 / 3.0, 3, 14), (0.5, 4, 18)]));
+
+        let mut read = 0;
+        let stopped = for_each_streamed_input(
+            &path.to_string_lossy(),
+            Position::START,
+            &pipeline,
+            |_, _, _| {
+                read += 1;
+                Ok(())
+            },
+            |arg, _| Err(io::Error::new(io::ErrorKind::InvalidData, arg.to_string())),
+        );
+        assert!(stopped.is_err());
+        assert_that!(read, equal_to(1));
+        fs::remove_file(&path).unwrap();
+
+        let missing = for_each_streamed_input(
+            "/nonexistent/inputs",
+            Position::START,
+            &pipeline,
+            |_, _, _| Ok(()),
+            |_, _| Ok(()),
+        );
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::fs;
+
+/// How the text of a cell is placed within its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// What happens to text longer than the width of its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncate {
+    /// The text is kept whole and pushes the rest of the row to the right.
+    Overflow,
+    /// The text is cut off at the width of the column.
+    Clip,
+    /// The text is cut off one character early and ends in `…`.
+    Ellipsis,
+}
+
+/// The ANSI terminal colors a column can be printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    /// Looks up a color by its name, as in `green`.
+    pub fn from_name(name: &str) -> Option<Color> {
+        match name {
+            "black" => Some(Color::Black),
+            "red" => Some(Color::Red),
+            "green" => Some(Color::Green),
+            "yellow" => Some(Color::Yellow),
+            "blue" => Some(Color::Blue),
+            "magenta" => Some(Color::Magenta),
+            "cyan" => Some(Color::Cyan),
+            "white" => Some(Color::White),
+            _ => None,
+        }
+    }
+
+    /// The ANSI escape code that switches to this color.
+    fn ansi_code(&self) -> u32 {
+        30 + *self as u32
+    }
+}
+
+/// The appearance of one table column. Settings left as `None` keep the
+/// look the table has without a style file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColumnStyle {
+    /// How the cells of the column are aligned. Headers are always centered.
+    pub align: Option<Align>,
+    /// The color of the cells of the column.
+    pub color: Option<Color>,
+    /// The width of the column in characters.
+    pub max_width: Option<usize>,
+    /// What happens to cells wider than `max_width`.
+    pub truncate: Option<Truncate>,
+}
+
+impl ColumnStyle {
+    /// Fills in every setting this style leaves open from `fallback`.
+    fn or(self, fallback: ColumnStyle) -> ColumnStyle {
+        ColumnStyle {
+            align: self.align.or(fallback.align),
+            color: self.color.or(fallback.color),
+            max_width: self.max_width.or(fallback.max_width),
+            truncate: self.truncate.or(fallback.truncate),
+        }
+    }
+
+    /// The width of the column, given the width it has without a style.
+    pub fn width(&self, default_width: usize) -> usize {
+        self.max_width.unwrap_or(default_width)
+    }
+
+    /// Shortens `text` to the width of the column, following the truncation
+    /// policy. Text holding terminal escape codes, such as a highlighted
+    /// period, is never cut, which could leave a code open.
+    fn fit<'a>(&self, text: &'a str, width: usize) -> Cow<'a, str> {
+        if text.contains('\x1b') || text.chars().count() <= width {
+            return Cow::Borrowed(text);
+        }
+        match self.truncate.unwrap_or(Truncate::Overflow) {
+            Truncate::Overflow => Cow::Borrowed(text),
+            Truncate::Clip => Cow::Owned(text.chars().take(width).collect()),
+            Truncate::Ellipsis => {
+                let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+                Cow::Owned(kept + "…")
+            }
+        }
+    }
+
+    /// Lays out the header of the column, centered.
+    pub fn header_cell(&self, title: &str, default_width: usize) -> String {
+        let width = self.width(default_width);
+        format!("{:^1$}", self.fit(title, width), width)
+    }
+
+    /// Lays out one cell of the column, aligned and colored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let style = ColumnStyle { max_width: Some(6), truncate: Some(Truncate::Ellipsis), ..ColumnStyle::default() };
+    /// assert_eq!(style.cell("0.0;0;0;1;", 22), "0.0;0…");
+    /// ```
+    #[allow(dead_code)]
+    pub fn cell(&self, text: &str, default_width: usize) -> String {
+        let mut cell = String::new();
+        let _ = self.write_cell(&mut cell, text, default_width);
+        cell
+    }
+
+    /// Lays out one cell of the column like `cell`, but appends it to `sink`
+    /// instead of allocating a string for it, so that a whole row can be
+    /// built in one buffer.
+    pub fn write_cell(
+        &self,
+        sink: &mut impl Write,
+        text: &str,
+        default_width: usize,
+    ) -> fmt::Result {
+        let width = self.width(default_width);
+        let text = self.fit(text, width);
+        if let Some(color) = self.color {
+            write!(sink, "\x1b[{}m", color.ansi_code())?;
+        }
+        let align = self.align.unwrap_or(Align::Left);
+        if text.contains('\x1b') {
+            write_padded(sink, &text, width, align)?;
+        } else {
+            match align {
+                Align::Left => write!(sink, "{:<1$}", text, width)?,
+                Align::Right => write!(sink, "{:>1$}", text, width)?,
+                Align::Center => write!(sink, "{:^1$}", text, width)?,
+            }
+        }
+        if self.color.is_some() {
+            write!(sink, "\x1b[0m")?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of characters some text takes up on a terminal, leaving out
+/// the `\x1b[…m` escape codes that only change its look.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(visible_width("0.\x1b[36m3;\x1b[39m"), 4);
+/// ```
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Aligns text holding escape codes within a width, padding it with
+/// spaces as `{:<}`, `{:>}` and `{:^}` would if the codes took up no room.
+fn write_padded(sink: &mut impl Write, text: &str, width: usize, align: Align) -> fmt::Result {
+    let padding = width.saturating_sub(visible_width(text));
+    let before = match align {
+        Align::Left => 0,
+        Align::Right => padding,
+        Align::Center => padding / 2,
+    };
+    write!(
+        sink,
+        "{:1$}{2}{3:4$}",
+        "",
+        before,
+        text,
+        "",
+        padding - before
+    )
+}
+
+/// The lines a table is drawn with, from `--table-style`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    /// A Markdown table: `|` between the cells and `|:---|` under the header.
+    #[default]
+    Markdown,
+    /// `|` between the cells and `+---+` rules above, under the header and
+    /// below the table.
+    Ascii,
+    /// A box of Unicode line-drawing characters, such as `│`, `┼` and `└`.
+    Unicode,
+    /// No lines at all but dashes under the header, with the cells lined up
+    /// by spaces, for pasting into plain text.
+    Borderless,
+}
+
+/// The rules of a table, in the order they are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// The line above the header.
+    Top,
+    /// The line between the header and the rows.
+    Divider,
+    /// The line below the last row.
+    Bottom,
+}
+
+impl TableStyle {
+    /// The names `--table-style` takes, in the order of the variants.
+    pub const NAMES: [&'static str; 4] = ["markdown", "ascii", "unicode", "borderless"];
+
+    /// Reads the name of a table style.
+    ///
+    /// # Returns
+    ///
+    /// The style, or a message listing the valid names.
+    pub fn parse(name: &str) -> Result<TableStyle, String> {
+        match name {
+            "markdown" => Ok(TableStyle::Markdown),
+            "ascii" => Ok(TableStyle::Ascii),
+            "unicode" => Ok(TableStyle::Unicode),
+            "borderless" => Ok(TableStyle::Borderless),
+            _ => Err(format!(
+                "unknown table style `{}`; expected one of {}",
+                name,
+                TableStyle::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// The line drawn before the first cell of a row and after every cell.
+    pub fn border(self) -> &'static str {
+        match self {
+            TableStyle::Markdown | TableStyle::Ascii => "|",
+            TableStyle::Unicode => "│",
+            TableStyle::Borderless => "",
+        }
+    }
+
+    /// Ends a row of cells, which without borders loses the spaces padding
+    /// its last cell.
+    pub fn end_row(self, row: &mut String) {
+        if self == TableStyle::Borderless {
+            row.truncate(row.trim_end().len());
+        }
+        row.push('\n');
+    }
+
+    /// Draws one of the rules of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - Which rule to draw.
+    /// * `widths` - The width of every column, without the space on either
+    ///   side of its cells.
+    ///
+    /// # Returns
+    ///
+    /// The line, or `None` if the style has no such rule.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(TableStyle::Ascii.rule(Rule::Top, &[3, 1]).unwrap(), "+-----+---+");
+    /// assert_eq!(TableStyle::Markdown.rule(Rule::Divider, &[3]).unwrap(), "|:----|");
+    /// assert_eq!(TableStyle::Markdown.rule(Rule::Bottom, &[3]), None);
+    /// ```
+    pub fn rule(self, rule: Rule, widths: &[usize]) -> Option<String> {
+        let (left, fill, middle, right) = match (self, rule) {
+            (TableStyle::Markdown, Rule::Divider) => {
+                let mut line = String::from("|");
+                for &width in widths {
+                    line += &format!("{:-<1$}|", ":", width + 2);
+                }
+                return Some(line);
+            }
+            (TableStyle::Markdown, _) | (TableStyle::Borderless, Rule::Top | Rule::Bottom) => {
+                return None
+            }
+            (TableStyle::Borderless, Rule::Divider) => {
+                let dashes: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+                return Some(format!(" {}", dashes.join("  ")));
+            }
+            (TableStyle::Ascii, _) => ('+', '-', '+', '+'),
+            (TableStyle::Unicode, Rule::Top) => ('┌', '─', '┬', '┐'),
+            (TableStyle::Unicode, Rule::Divider) => ('├', '─', '┼', '┤'),
+            (TableStyle::Unicode, Rule::Bottom) => ('└', '─', '┴', '┘'),
+        };
+        let mut line = String::new();
+        line.push(left);
+        for (i, &width) in widths.iter().enumerate() {
+            if i > 0 {
+                line.push(middle);
+            }
+            line.extend(std::iter::repeat_n(fill, width + 2));
+        }
+        line.push(right);
+        Some(line)
+    }
+}
+
+/// The column styles read from a `--style` file.
+///
+/// A style file is made of sections named after the column headers, such as
+/// `[Base 2]`, `[Error]` or `[Base 10]` for the input column, and `[*]` for
+/// every column. Each section sets `align` (`left`, `right` or `center`),
+/// `color`, `max-width` and `truncate` (`overflow`, `clip` or `ellipsis`)
+/// as `key = value` lines. Lines starting with `#` are comments.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Styles {
+    /// The style of every column named in the file, in the order given.
+    pub columns: Vec<(String, ColumnStyle)>,
+    /// The lines the table is drawn with, from `--table-style`.
+    pub table: TableStyle,
+}
+
+impl Styles {
+    /// Reads and parses a style file.
+    pub fn load(path: &str) -> Result<Styles, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("cannot read style file `{}`: {}", path, error))?;
+        Styles::parse(&text).map_err(|message| format!("{}: {}", path, message))
+    }
+
+    /// Parses the text of a style file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let styles = Styles::parse("[Base 2]\nalign = right\n").unwrap();
+    /// assert_eq!(styles.get("Base 2").align, Some(Align::Right));
+    /// ```
+    pub fn parse(text: &str) -> Result<Styles, String> {
+        let mut styles = Styles::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: String| format!("line {}: {}", number + 1, message);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                styles
+                    .columns
+                    .push((header.trim().to_string(), ColumnStyle::default()));
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `key = value`, found `{}`", line)))?;
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            let (_, style) = styles
+                .columns
+                .last_mut()
+                .ok_or_else(|| error(format!("`{}` is set before any [column]", key)))?;
+            let invalid = || error(format!("invalid {} `{}`", key, value));
+            match key {
+                "align" => {
+                    style.align = Some(match value {
+                        "left" => Align::Left,
+                        "right" => Align::Right,
+                        "center" => Align::Center,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "color" => style.color = Some(Color::from_name(value).ok_or_else(invalid)?),
+                "max-width" => {
+                    style.max_width = Some(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|&w| w > 0)
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                "truncate" => {
+                    style.truncate = Some(match value {
+                        "overflow" => Truncate::Overflow,
+                        "clip" => Truncate::Clip,
+                        "ellipsis" => Truncate::Ellipsis,
+                        _ => return Err(invalid()),
+                    })
+                }
+                _ => return Err(error(format!("unknown style setting `{}`", key))),
+            }
+        }
+
+        Ok(styles)
+    }
+
+    /// The style of the column with the given header: its own settings, and
+    /// those of `[*]` for anything it leaves open.
+    pub fn get(&self, header: &str) -> ColumnStyle {
+        let find = |name: &str| {
+            self.columns
+                .iter()
+                .rev()
+                .find(|(column, _)| column == name)
+                .map(|&(_, style)| style)
+                .unwrap_or_default()
+        };
+        find(header).or(find("*"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_parse_styles() {
+        let styles = Styles::parse(
+            "# report style\n[*]\ncolor = green\n\n[Base 2]\nalign = right\nmax-width = 12\ntruncate = \"clip\"\n",
+        )
+        .unwrap();
+        assert_that!(
+            styles.get("Base 2"),
+            equal_to(ColumnStyle {
+                align: Some(Align::Right),
+                color: Some(Color::Green),
+                max_width: Some(12),
+                truncate: Some(Truncate::Clip),
+            })
+        );
+        assert_that!(styles.get("Error").color, equal_to(Some(Color::Green)));
+        assert_that!(
+            Styles::default().get("Base 2"),
+            equal_to(ColumnStyle::default())
+        );
+
+        assert!(Styles::parse("align = left").is_err());
+        assert!(Styles::parse("[Base 2]\nalign = middle").is_err());
+        assert!(Styles::parse("[Base 2]\nmax-width = 0").is_err());
+        assert!(Styles::parse("[Base 2]\nfont = serif").is_err());
+        assert!(Styles::load("/nonexistent/style.ini").is_err());
+    }
+
+    #[test]
+    fn test_styled_cells() {
+        assert_that!(ColumnStyle::default().cell("0.1;", 6), equal_to("0.1;  "));
+        assert_that!(
+            ColumnStyle::default().cell("0.0;1;1;", 6),
+            equal_to("0.0;1;1;")
+        );
+
+        let style = |align, truncate| ColumnStyle {
+            align: Some(align),
+            max_width: Some(5),
+            truncate: Some(truncate),
+            ..ColumnStyle::default()
+        };
+        assert_that!(
+            style(Align::Right, Truncate::Clip).cell("0.1;", 22),
+            equal_to(" 0.1;")
+        );
+        assert_that!(
+            style(Align::Left, Truncate::Clip).cell("0.0;1;1;", 22),
+            equal_to("0.0;1")
+        );
+        assert_that!(
+            style(Align::Center, Truncate::Ellipsis).cell("0.0;1;1;", 22),
+            equal_to("0.0;…")
+        );
+        assert_that!(
+            style(Align::Left, Truncate::Ellipsis).header_cell("Base 16", 22),
+            equal_to("Base…")
+        );
+
+        let green = ColumnStyle {
+            color: Some(Color::Green),
+            ..ColumnStyle::default()
+        };
+        assert_that!(green.cell("0.1;", 4), equal_to("\x1b[32m0.1;\x1b[0m"));
+
+        // Escape codes in the text take up no room and are never cut.
+        let underlined = "0.\x1b[4m1;\x1b[24m";
+        assert_that!(
+            ColumnStyle::default().cell(underlined, 6),
+            equal_to(format!("{}  ", underlined))
+        );
+        assert_that!(
+            style(Align::Center, Truncate::Clip).cell(underlined, 22),
+            equal_to(format!("{} ", underlined))
+        );
+        assert_that!(
+            style(Align::Left, Truncate::Clip).cell("0.\x1b[4m1;0;1;\x1b[24m", 22),
+            equal_to("0.\x1b[4m1;0;1;\x1b[24m".to_string())
+        );
+    }
+}
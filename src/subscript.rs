@@ -0,0 +1,115 @@
+use std::fmt;
+
+use crate::convert::Representation;
+
+/// Writes an integer base as Unicode subscript digits, e.g. `₁₆` for 16 and
+/// `₋₂` for negabinary.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(subscript(16), "₁₆");
+/// ```
+pub fn subscript(base: i64) -> String {
+    base.to_string()
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => char::from_u32(0x2080 + digit).expect("subscript digit"),
+            None => '₋',
+        })
+        .collect()
+}
+
+/// Splits the base written as Unicode subscript digits off the end of an
+/// input, as in `0.101₂` or `1A.8₁₆`, the reverse of [`subscript`].
+///
+/// # Returns
+///
+/// The number before the subscript and the base, or `None` if the input
+/// does not end in one.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(split_subscript("0.C₁₆"), Some(("0.C", 16)));
+/// assert_eq!(split_subscript("0.5"), None);
+/// ```
+pub fn split_subscript(arg: &str) -> Option<(&str, u32)> {
+    let number = arg.trim_end_matches(|c| ('₀'..='₉').contains(&c));
+    let base = arg[number.len()..].chars().try_fold(0u32, |base, c| {
+        base.checked_mul(10)?.checked_add(c as u32 - '₀' as u32)
+    })?;
+    (number.len() < arg.len()).then_some((number, base))
+}
+
+/// A representation followed by the base it is written in as a subscript,
+/// as in `0.1;0;1;₂`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscripted<R> {
+    /// The representation shown.
+    pub representation: R,
+    /// The subscript written after it, or `None` to write it as it is.
+    pub subscript: Option<String>,
+}
+
+impl<R: Representation> Representation for Subscripted<R> {
+    fn value(&self) -> f64 {
+        self.representation.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.representation.is_exact()
+    }
+
+    fn base(&self) -> Option<f64> {
+        self.representation.base()
+    }
+
+    fn digit_count(&self) -> Option<usize> {
+        self.representation.digit_count()
+    }
+
+    fn positional_digits(&self) -> Option<(&[u32], &[u32])> {
+        self.representation.positional_digits()
+    }
+}
+
+impl<R: fmt::Display> fmt::Display for Subscripted<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.representation)?;
+        if let Some(subscript) = &self.subscript {
+            write!(f, "{}", subscript)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_subscripts() {
+        assert_that!(subscript(2), equal_to("₂".to_string()));
+        assert_that!(subscript(60), equal_to("₆₀".to_string()));
+        assert_that!(subscript(-10), equal_to("₋₁₀".to_string()));
+        assert_that!(split_subscript("-1A.8₁₆"), equal_to(Some(("-1A.8", 16))));
+        assert_that!(split_subscript("0.1₂"), equal_to(Some(("0.1", 2))));
+        assert_that!(split_subscript("₂"), equal_to(Some(("", 2))));
+        assert_that!(split_subscript("0.1"), equal_to(None));
+
+        let annotated = Subscripted {
+            representation: expand(0.625, 2, 8),
+            subscript: Some(subscript(2)),
+        };
+        assert_that!(annotated.to_string(), equal_to("0.1;0;1;₂".to_string()));
+        assert_that!(annotated.value(), equal_to(0.625));
+        let plain = Subscripted {
+            subscript: None,
+            ..annotated
+        };
+        assert_that!(plain.to_string(), equal_to("0.1;0;1;".to_string()));
+    }
+}
@@ -0,0 +1,211 @@
+use std::io::{self, Write};
+
+use crate::beta::format_base;
+use crate::convert::Representation;
+use crate::display::{format_error, round_trip_error};
+
+/// Aggregate statistics for one target base.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseSummary {
+    /// The base the statistics are about.
+    pub target_base: f64,
+    /// How many inputs were represented exactly within the digit limit.
+    pub exact: usize,
+    /// The largest round-trip error seen so far.
+    pub max_error: f64,
+}
+
+/// Aggregate statistics over a whole batch of conversions, accumulated one
+/// input at a time so that no per-row results need to be kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// How many inputs were converted.
+    pub inputs: usize,
+    /// The statistics for every target base, in the order of the columns.
+    pub bases: Vec<BaseSummary>,
+}
+
+impl Summary {
+    /// Creates an empty summary for the given target bases.
+    pub fn new(target_bases: &[f64]) -> Summary {
+        Summary {
+            inputs: 0,
+            bases: target_bases
+                .iter()
+                .map(|&target_base| BaseSummary {
+                    target_base,
+                    exact: 0,
+                    max_error: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds one input and its expansions (in the order of the target bases).
+    pub fn add<R: Representation>(&mut self, number: f64, expansions: &[R]) {
+        self.inputs += 1;
+        for (base, expansion) in self.bases.iter_mut().zip(expansions) {
+            if expansion.is_exact() {
+                base.exact += 1;
+            }
+            base.max_error = base.max_error.max(round_trip_error(number, expansion));
+        }
+    }
+}
+
+/// What `--footer` says below the table about the batch, accumulated one
+/// row at a time like [`Summary`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Footer {
+    /// How many inputs were converted.
+    pub inputs: usize,
+    /// How many conversions ended within the digit limit.
+    pub terminated: usize,
+    /// How many conversions were cut off at the digit limit.
+    pub truncated: usize,
+    /// The fewest and the most fractional digits a conversion was written
+    /// with, or `None` while no positional conversion was seen.
+    pub digits: Option<(usize, usize)>,
+}
+
+impl Footer {
+    /// Adds the expansions of one input, in the order of the columns.
+    pub fn add<R: Representation>(&mut self, expansions: &[R]) {
+        self.inputs += 1;
+        for expansion in expansions {
+            if expansion.is_exact() {
+                self.terminated += 1;
+            } else {
+                self.truncated += 1;
+            }
+            if let Some(count) = expansion.digit_count() {
+                self.digits = Some(match self.digits {
+                    Some((min, max)) => (min.min(count), max.max(count)),
+                    None => (count, count),
+                });
+            }
+        }
+    }
+}
+
+/// Prints the `--footer` line below the table.
+///
+/// # Arguments
+///
+/// * `out` - Where the line is written.
+/// * `footer` - The statistics of the rows of the table.
+/// * `skipped` - How many input arguments were skipped as invalid.
+///
+/// # Example
+/// ```
+/// let mut footer = Footer::default();
+/// footer.add(&pipeline.expand(0.5, None));
+/// display_footer(&mut std::io::stdout(), &footer, 1).unwrap();
+/// ```
+/// Output:
+/// Inputs: 1, terminated: 1, truncated: 0, digits: 1 to 1, skipped: 1
+pub fn display_footer(out: &mut dyn Write, footer: &Footer, skipped: usize) -> io::Result<()> {
+    let digits = match footer.digits {
+        Some((min, max)) => format!("{} to {}", min, max),
+        None => "n/a".to_string(),
+    };
+    writeln!(
+        out,
+        "Inputs: {}, terminated: {}, truncated: {}, digits: {}, skipped: {}",
+        footer.inputs, footer.terminated, footer.truncated, digits, skipped
+    )
+}
+
+/// Prints the aggregate statistics of a batch in place of the per-row table.
+///
+/// # Example
+/// ```
+/// let mut summary = Summary::new(&[2.0]);
+/// summary.add(0.5, &pipeline.expand(0.5, None));
+/// display_summary(&mut std::io::stdout(), &summary).unwrap();
+/// ```
+/// Output:
+/// Inputs: 1
+/// |  Base  |   Exact    | Exactness  | Max error  |
+/// |:-------|:-----------|:-----------|:-----------|
+/// | 2      | 1          | 100.00%    | 0          |
+pub fn display_summary(out: &mut dyn Write, summary: &Summary) -> io::Result<()> {
+    writeln!(out, "Inputs: {}", summary.inputs)?;
+    writeln!(
+        out,
+        "| {:^6} | {:^10} | {:^10} | {:^10} |",
+        "Base", "Exact", "Exactness", "Max error"
+    )?;
+    writeln!(out, "|{:-<8}|{:-<12}|{:-<12}|{:-<12}|", ":", ":", ":", ":")?;
+
+    for base in &summary.bases {
+        writeln!(
+            out,
+            "| {:<6} | {:<10} | {:<10} | {:<10} |",
+            format_base(base.target_base),
+            base.exact,
+            format_ratio(base.exact, summary.inputs),
+            format_error(base.max_error)
+        )?;
+    }
+    Ok(())
+}
+
+/// Formats `part / whole` as a percentage, or `-` when there is nothing to divide.
+fn format_ratio(part: usize, whole: usize) -> String {
+    if whole == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.2}%", 100.0 * part as f64 / whole as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_summary_accumulates_statistics() {
+        let pipeline = Pipeline {
+            digits: 4,
+            ..Pipeline::with_bases(vec![2.0, 10.0])
+        };
+        let mut summary = Summary::new(&pipeline.target_bases);
+        for number in [0.5, 0.1, 0.75] {
+            summary.add(number, &pipeline.expand(number, None));
+        }
+
+        assert_that!(summary.inputs, equal_to(3));
+        assert_that!(summary.bases[0].exact, equal_to(2));
+        assert_that!(summary.bases[0].max_error, equal_to(0.1 - 0.0625));
+        // The stored 0.1 is slightly above a tenth, which this conversion sees.
+        assert_that!(summary.bases[1].exact, equal_to(2));
+        assert_that!(format_ratio(2, 3), equal_to("66.67%"));
+        assert_that!(format_ratio(0, 0), equal_to("-"));
+    }
+
+    #[test]
+    fn test_footer_counts_rows() {
+        let pipeline = Pipeline {
+            digits: 4,
+            ..Pipeline::with_bases(vec![2.0, 10.0])
+        };
+        let mut footer = Footer::default();
+        for number in [0.5, 0.1] {
+            footer.add(&pipeline.expand(number, None));
+        }
+        assert_that!(footer.inputs, equal_to(2));
+        assert_that!(footer.terminated, equal_to(2));
+        assert_that!(footer.truncated, equal_to(2));
+        assert_that!(footer.digits, equal_to(Some((1, 4))));
+
+        let mut out = Vec::new();
+        display_footer(&mut out, &footer, 3).unwrap();
+        assert_that!(
+            String::from_utf8(out).unwrap(),
+            equal_to("Inputs: 2, terminated: 2, truncated: 2, digits: 1 to 4, skipped: 3\n")
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::rational::{Fraction, Ratio};
+
+/// The title of the column telling whether every expansion terminates.
+pub const TERMINATES_HEADER: &str = "Terminates?";
+/// The title of the column giving the pre-period and period of every
+/// expansion.
+pub const PERIOD_HEADER: &str = "Period";
+/// The title of the column giving how many digits every terminating
+/// expansion needs, with `--exact-digits`.
+pub const DIGITS_NEEDED_HEADER: &str = "Digits needed";
+
+/// The largest factor tried when looking for the prime that stops an
+/// expansion from terminating.
+const LARGEST_TRIED_FACTOR: u32 = 1_000_000;
+
+/// Whether the expansion of a number in a base ends or repeats forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// The expansion ends after finitely many digits.
+    Terminates,
+    /// The expansion repeats forever, since its reduced denominator has the
+    /// prime `factor`, which does not divide the base.
+    Repeats { factor: BigUint, target_base: u64 },
+    /// Termination is not defined for the base, such as a non-integer one.
+    Undefined,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Termination::Terminates => write!(f, "yes"),
+            Termination::Repeats {
+                factor,
+                target_base,
+            } => write!(f, "no: {} does not divide {}", factor, target_base),
+            Termination::Undefined => write!(f, "n/a"),
+        }
+    }
+}
+
+/// The exact value of an input: the fraction it was written as, or else the
+/// shortest decimal that reads back as the same `f64`, such as 1/10 for
+/// `0.1` rather than the nearest binary number.
+///
+/// # Returns
+///
+/// The exact value, or `None` for infinities and NaN.
+pub fn exact_input(number: f64, fraction: Option<&Fraction>) -> Option<Ratio> {
+    if let Some(fraction) = fraction {
+        return Some(Ratio::from(fraction.clone()));
+    }
+    if !number.is_finite() {
+        return None;
+    }
+    let written = format!("{}", number.abs());
+    let (integer, decimals) = written.split_once('.').unwrap_or((&written, ""));
+    Some(Ratio {
+        negative: number < 0.0,
+        numerator: format!("{}{}", integer, decimals).parse().ok()?,
+        denominator: BigUint::from(10u32).pow(decimals.len() as u32),
+    })
+}
+
+/// The greatest common divisor of two numbers.
+pub fn gcd(mut a: BigUint, mut b: BigUint) -> BigUint {
+    while !b.is_zero() {
+        let remainder = &a % &b;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+/// Factors a number into primes and their exponents, smallest first.
+///
+/// # Returns
+///
+/// The factors, or `None` if what is left after dividing out every factor
+/// up to `LARGEST_TRIED_FACTOR` is too large to be known prime.
+fn factorize(number: &BigUint) -> Option<Vec<(BigUint, u32)>> {
+    let mut rest = number.clone();
+    let mut factors = Vec::new();
+    for factor in (2..=LARGEST_TRIED_FACTOR).map(BigUint::from) {
+        if &factor * &factor > rest {
+            break;
+        }
+        let mut exponent = 0;
+        while (&rest % &factor).is_zero() {
+            rest /= &factor;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((factor, exponent));
+        }
+    }
+    if !rest.is_one() {
+        let largest = BigUint::from(LARGEST_TRIED_FACTOR);
+        if rest > &largest * &largest {
+            return None;
+        }
+        factors.push((rest, 1));
+    }
+    Some(factors)
+}
+
+/// The smallest prime factor of a number greater than one, or the number
+/// itself when it has no factor up to `LARGEST_TRIED_FACTOR`.
+fn smallest_factor(number: &BigUint) -> BigUint {
+    (2..=LARGEST_TRIED_FACTOR)
+        .map(BigUint::from)
+        .take_while(|factor| factor * factor <= *number)
+        .find(|factor| (number % factor).is_zero())
+        .unwrap_or_else(|| number.clone())
+}
+
+/// The magnitude of a base that a value can terminate or repeat in, or
+/// `None` for non-integer bases and those below 2 in magnitude.
+fn integer_base(target_base: f64) -> Option<u64> {
+    (target_base.fract() == 0.0 && target_base.abs() >= 2.0 && target_base.abs() <= u64::MAX as f64)
+        .then_some(target_base.abs() as u64)
+}
+
+/// Divides the primes of the base out of the reduced denominator of a value.
+///
+/// # Returns
+///
+/// The number of digits before the expansion starts repeating, and what is
+/// left of the denominator, which is one exactly when it terminates.
+fn split_denominator(value: &Ratio, target_base: u64) -> (u64, BigUint) {
+    let mut denominator =
+        &value.denominator / gcd(value.numerator.clone(), value.denominator.clone());
+    let mut pre_period = 0;
+    let mut rest = target_base;
+    let mut prime = 2;
+    while rest > 1 {
+        if prime > rest / prime {
+            // What is left of the base is itself prime.
+            prime = rest;
+        }
+        if rest.is_multiple_of(prime) {
+            let mut in_base: u64 = 0;
+            while rest.is_multiple_of(prime) {
+                rest /= prime;
+                in_base += 1;
+            }
+            let factor = BigUint::from(prime);
+            let mut in_denominator: u64 = 0;
+            while (&denominator % &factor).is_zero() {
+                denominator /= &factor;
+                in_denominator += 1;
+            }
+            // Every digit takes `in_base` of these factors off the denominator.
+            pre_period = pre_period.max(in_denominator.div_ceil(in_base));
+        }
+        prime += 1;
+    }
+    (pre_period, denominator)
+}
+
+/// Decides whether the expansion of an exact value in a base terminates,
+/// which it does exactly when every prime factor of its reduced denominator
+/// divides the base.
+///
+/// # Arguments
+///
+/// * `value` - The exact value to expand.
+/// * `target_base` - The base, where negative bases behave as their magnitude.
+///
+/// # Example
+///
+/// ```
+/// let third = Ratio::from(Fraction::parse("1/3").unwrap());
+/// assert_eq!(termination(&third, 3.0), Termination::Terminates);
+/// assert_eq!(termination(&third, 2.0).to_string(), "no: 3 does not divide 2");
+/// ```
+pub fn termination(value: &Ratio, target_base: f64) -> Termination {
+    let Some(target_base) = integer_base(target_base) else {
+        return Termination::Undefined;
+    };
+    let (_, rest) = split_denominator(value, target_base);
+    if rest.is_one() {
+        Termination::Terminates
+    } else {
+        Termination::Repeats {
+            factor: smallest_factor(&rest),
+            target_base,
+        }
+    }
+}
+
+/// The shape of the expansion of a number: the digits before it repeats,
+/// then the length of the block that repeats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Period {
+    /// How many fractional digits come before the repeating block.
+    pub pre_period: u64,
+    /// How many digits repeat, zero when the expansion terminates.
+    pub period: BigUint,
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pre-period {}, period {}", self.pre_period, self.period)
+    }
+}
+
+/// The number of fractional digits after which the expansion of an input in
+/// a base ends, such as 3 for 0.125 in base 2, with the exact value of the
+/// input those digits are written from.
+///
+/// # Returns
+///
+/// The exact value and the digit count, or `None` if the expansion never
+/// ends or the input is not finite.
+///
+/// # Example
+///
+/// ```
+/// let (_, digits) = terminating_digits(0.125, None, 2).unwrap();
+/// assert_eq!(digits, 3);
+/// assert_eq!(terminating_digits(0.1, None, 2), None);
+/// ```
+pub fn terminating_digits(
+    number: f64,
+    fraction: Option<&Fraction>,
+    target_base: u64,
+) -> Option<(Fraction, u64)> {
+    let exact = exact_input(number, fraction)?;
+    let (digits, rest) = split_denominator(&exact, target_base);
+    rest.is_one().then(|| {
+        let exact = Fraction {
+            negative: exact.negative,
+            numerator: exact.numerator,
+            denominator: exact.denominator,
+        };
+        (exact, digits)
+    })
+}
+
+/// Finds the pre-period and period of the expansion of an exact value in a
+/// base: the period is the multiplicative order of the base modulo the part
+/// of the reduced denominator coprime to it, such as 6 for 1/7 in base 10.
+///
+/// # Returns
+///
+/// The `Period`, or `None` for negative and non-integer bases, and for
+/// denominators too large to factor.
+///
+/// # Example
+///
+/// ```
+/// let seventh = Ratio::from(Fraction::parse("1/7").unwrap());
+/// assert_eq!(period(&seventh, 10.0).unwrap().to_string(), "pre-period 0, period 6");
+/// ```
+pub fn period(value: &Ratio, target_base: f64) -> Option<Period> {
+    if target_base < 0.0 {
+        return None;
+    }
+    let target_base = integer_base(target_base)?;
+    let (pre_period, rest) = split_denominator(value, target_base);
+    if rest.is_one() {
+        return Some(Period {
+            pre_period,
+            period: BigUint::zero(),
+        });
+    }
+
+    // The order divides the totient of `rest`, so it is found by dividing
+    // the totient by each of its prime factors for as long as that keeps
+    // the base a root of unity.
+    let mut totient = BigUint::one();
+    let mut totient_primes = Vec::new();
+    for (prime, exponent) in factorize(&rest)? {
+        let less = &prime - 1u32;
+        totient *= prime.pow(exponent - 1) * &less;
+        if exponent > 1 {
+            totient_primes.push(prime);
+        }
+        totient_primes.extend(factorize(&less)?.into_iter().map(|(factor, _)| factor));
+    }
+    totient_primes.sort();
+    totient_primes.dedup();
+
+    let base = BigUint::from(target_base);
+    let mut order = totient;
+    for prime in totient_primes {
+        while (&order % &prime).is_zero() && base.modpow(&(&order / &prime), &rest).is_one() {
+            order /= &prime;
+        }
+    }
+    Some(Period {
+        pre_period,
+        period: order,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_termination() {
+        let tenth = exact_input(0.1, None).unwrap();
+        assert_that!(tenth.denominator.clone(), equal_to(BigUint::from(10u32)));
+        assert_that!(termination(&tenth, 10.0), equal_to(Termination::Terminates));
+        assert_that!(termination(&tenth, 20.0), equal_to(Termination::Terminates));
+        assert_that!(
+            termination(&tenth, 2.0).to_string(),
+            equal_to("no: 5 does not divide 2")
+        );
+        assert_that!(
+            termination(&tenth, -2.0).to_string(),
+            equal_to("no: 5 does not divide 2")
+        );
+
+        let thirds = exact_input(0.0, Fraction::parse("-14/21").as_ref()).unwrap();
+        assert_that!(termination(&thirds, 3.0), equal_to(Termination::Terminates));
+        assert_that!(
+            termination(&thirds, 10.0).to_string(),
+            equal_to("no: 3 does not divide 10")
+        );
+        let big = exact_input(0.0, Fraction::parse("1/999983").as_ref()).unwrap();
+        assert_that!(
+            termination(&big, 10.0).to_string(),
+            equal_to("no: 999983 does not divide 10")
+        );
+
+        assert_that!(
+            terminating_digits(0.0, Fraction::parse("5/1024").as_ref(), 2)
+                .map(|(_, digits)| digits),
+            equal_to(Some(10))
+        );
+        assert_that!(
+            terminating_digits(0.1, None, 10).map(|(_, digits)| digits),
+            equal_to(Some(1))
+        );
+        assert_that!(
+            terminating_digits(0.0, Fraction::parse("1/3").as_ref(), 10),
+            equal_to(None)
+        );
+
+        assert_that!(
+            termination(&exact_input(3.0, None).unwrap(), 7.0),
+            equal_to(Termination::Terminates)
+        );
+        assert_that!(termination(&tenth, 1.5), equal_to(Termination::Undefined));
+        assert!(exact_input(f64::NAN, None).is_none());
+    }
+
+    #[test]
+    fn test_period() {
+        let shape = |fraction: &str, target_base| {
+            let value = exact_input(0.0, Fraction::parse(fraction).as_ref()).unwrap();
+            period(&value, target_base).map(|period| period.to_string())
+        };
+        let expected = |text: &str| Some(text.to_string());
+        assert_that!(
+            shape("1/7", 10.0),
+            equal_to(expected("pre-period 0, period 6"))
+        );
+        assert_that!(
+            shape("1/6", 10.0),
+            equal_to(expected("pre-period 1, period 1"))
+        );
+        assert_that!(
+            shape("3/8", 10.0),
+            equal_to(expected("pre-period 3, period 0"))
+        );
+        assert_that!(
+            shape("1/8", 4.0),
+            equal_to(expected("pre-period 2, period 0"))
+        );
+        assert_that!(
+            shape("1/12", 2.0),
+            equal_to(expected("pre-period 2, period 2"))
+        );
+        assert_that!(
+            shape("1/97", 10.0),
+            equal_to(expected("pre-period 0, period 96"))
+        );
+        assert_that!(shape("1/3", -2.0), equal_to(None));
+
+        let tiny = exact_input(1e-20, None).unwrap();
+        assert_that!(
+            period(&tiny, 2.0).unwrap(),
+            equal_to(Period {
+                pre_period: 20,
+                period: BigUint::from(4u32) * BigUint::from(5u32).pow(19),
+            })
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+
+use num_traits::Zero;
+
+use crate::convert::{evaluate_fraction, Expansion, Representation};
+use crate::pipeline::Pipeline;
+use crate::radices::{expand_mixed_radix, MixedRadix};
+use crate::rational::Fraction;
+
+/// The only base `--as-time` can render, with minutes and seconds as its places.
+pub const TIME_BASE: u32 = 60;
+
+/// The radices of the places of a day that `--duration` writes: hours,
+/// minutes, seconds and milliseconds.
+pub const DAY_RADICES: [u32; 4] = [24, 60, 60, 1000];
+
+/// What an input to `--as-time` is a fraction of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// The input is a number of hours, so its first sexagesimal place is minutes.
+    Hour,
+    /// The input is a number of days, which is first turned into hours.
+    Day,
+}
+
+impl TimeUnit {
+    /// Looks up a unit by its command-line name (`hour` or `day`).
+    pub fn from_name(name: &str) -> Option<TimeUnit> {
+        match name {
+            "hour" | "hours" => Some(TimeUnit::Hour),
+            "day" | "days" => Some(TimeUnit::Day),
+            _ => None,
+        }
+    }
+
+    /// The number of hours in one unit.
+    fn hours(&self) -> f64 {
+        match self {
+            TimeUnit::Hour => 1.0,
+            TimeUnit::Day => 24.0,
+        }
+    }
+}
+
+/// A base-60 expansion shown as a time of the form `HH:MM:SS.sss`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockTime {
+    /// What the converted value was a fraction of.
+    pub unit: TimeUnit,
+    /// Whether the converted value was negative.
+    pub negative: bool,
+    /// The whole hours.
+    pub hours: u64,
+    /// The base-60 expansion of the fraction of an hour: minutes, seconds and
+    /// then the sexagesimal fractions of a second.
+    pub expansion: Expansion,
+}
+
+/// Converts a fraction of an hour or day into hours and the base-60
+/// expansion of the remaining fraction of an hour.
+///
+/// # Arguments
+///
+/// * `decimal` - The number of hours or days.
+/// * `unit` - Whether `decimal` counts hours or days.
+/// * `pipeline` - Supplies the digit limit and the rounding of the last digit.
+///
+/// # Example
+///
+/// ```
+/// let time = to_clock_time(0.5, TimeUnit::Day, &Pipeline::with_bases(vec![60.0]));
+/// assert_eq!(time.to_string(), "12:00:00.000");
+/// ```
+pub fn to_clock_time(decimal: f64, unit: TimeUnit, pipeline: &Pipeline) -> ClockTime {
+    let total_hours = decimal.abs() * unit.hours();
+    let mut hours = total_hours.floor();
+    let mut expansion = pipeline.expand_to(total_hours - hours, TIME_BASE as f64);
+    // Rounding up 59:59 carries a whole hour out of the fraction.
+    if !expansion.integer_digits.is_empty() {
+        expansion.integer_digits.clear();
+        hours += 1.0;
+    }
+
+    ClockTime {
+        unit,
+        negative: decimal < 0.0,
+        hours: hours as u64,
+        expansion,
+    }
+}
+
+impl Representation for ClockTime {
+    fn value(&self) -> f64 {
+        let value = (self.hours as f64 + self.expansion.value()) / self.unit.hours();
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        self.expansion.is_exact()
+    }
+}
+
+impl fmt::Display for ClockTime {
+    /// Writes the time as `HH:MM:SS.sss`, zero-padding every sexagesimal place
+    /// to two digits and truncating the seconds to milliseconds.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = &self.expansion.digits;
+        let place = |i: usize| digits.get(i).copied().unwrap_or(0);
+        let fraction = evaluate_fraction(digits.get(2..).unwrap_or(&[]), TIME_BASE as f64);
+        let millis = ((fraction * 1000.0).floor() as u32).min(999);
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:03}",
+            self.hours,
+            place(0),
+            place(1),
+            millis
+        )
+    }
+}
+
+/// A number of days shown as a duration of the form `HH:MM:SS.mmm`, with
+/// the whole days counted as hours, as spreadsheets show their time
+/// fractions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duration(pub MixedRadix);
+
+/// Converts a number of days into hours, minutes, seconds and milliseconds
+/// with the mixed radices 24, 60, 60 and 1000, using the exact value of the
+/// input, and truncating what is left below a millisecond.
+///
+/// # Arguments
+///
+/// * `decimal` - The number of days.
+/// * `fraction` - The exact fraction the input was written as, if any.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(to_duration(1.5, None).to_string(), "36:00:00.000");
+/// ```
+pub fn to_duration(decimal: f64, fraction: Option<&Fraction>) -> Duration {
+    Duration(expand_mixed_radix(
+        decimal,
+        fraction,
+        &DAY_RADICES,
+        DAY_RADICES.len() as u32,
+    ))
+}
+
+impl Representation for Duration {
+    fn value(&self) -> f64 {
+        self.0.value()
+    }
+
+    fn is_exact(&self) -> bool {
+        self.0.is_exact()
+    }
+}
+
+impl fmt::Display for Duration {
+    /// Writes the duration as `HH:MM:SS.mmm`, zero-padding the hours, minutes
+    /// and seconds to two digits and the milliseconds to three.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let place = |i: usize| self.0.digits.get(i).copied().unwrap_or(0);
+        let hours = &self.0.integer * 24u32 + place(0);
+        if self.0.negative && !(hours.is_zero() && self.0.digits.iter().all(|&digit| digit == 0)) {
+            write!(f, "-")?;
+        }
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:03}",
+            hours,
+            place(1),
+            place(2),
+            place(3)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Rounding;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_clock_time_rendering() {
+        let pipeline = Pipeline::with_bases(vec![60.0]);
+        let time = |decimal, unit| to_clock_time(decimal, unit, &pipeline).to_string();
+        assert_that!(time(0.75, TimeUnit::Hour), equal_to("00:45:00.000"));
+        assert_that!(time(0.1, TimeUnit::Hour), equal_to("00:06:00.000"));
+        assert_that!(time(1.0 / 7.0, TimeUnit::Hour), equal_to("00:08:34.285"));
+        assert_that!(time(0.75, TimeUnit::Day), equal_to("18:00:00.000"));
+        assert_that!(time(-0.5, TimeUnit::Day), equal_to("-12:00:00.000"));
+        assert_that!(time(2.5, TimeUnit::Hour), equal_to("02:30:00.000"));
+    }
+
+    #[test]
+    fn test_clock_time_rounding_carries_into_hours() {
+        let pipeline = Pipeline {
+            digits: 2,
+            rounding: Rounding::HalfUp,
+            ..Pipeline::with_bases(vec![60.0])
+        };
+        let time = to_clock_time(0.9999, TimeUnit::Hour, &pipeline);
+        assert_that!(time.to_string(), equal_to("01:00:00.000"));
+        assert!((time.value() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_duration_rendering() {
+        let duration = |decimal| to_duration(decimal, None).to_string();
+        assert_that!(duration(0.75), equal_to("18:00:00.000"));
+        assert_that!(duration(1.5), equal_to("36:00:00.000"));
+        assert_that!(duration(-0.25), equal_to("-06:00:00.000"));
+        assert_that!(duration(0.0), equal_to("00:00:00.000"));
+        let seventh = Fraction::parse("1/7").unwrap();
+        let duration = to_duration(seventh.value(), Some(&seventh));
+        assert_that!(duration.to_string(), equal_to("03:25:42.857"));
+        assert!(!duration.is_exact());
+        assert!(to_duration(0.5, None).is_exact());
+    }
+}
@@ -0,0 +1,75 @@
+/// The title of the column `--verify-roundtrip` marks every row in.
+pub const VERIFY_HEADER: &str = "Round trip";
+
+/// How far the value of an expansion may be from its input before
+/// `--verify-roundtrip` marks its row as failed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// One unit in the last place the digit limit allows, `|base|^-digits`,
+    /// which every correctly cut off expansion stays within.
+    LastPlace(u32),
+    /// A fixed absolute error, from `--verify-roundtrip=<tolerance>`.
+    Absolute(f64),
+}
+
+impl Tolerance {
+    /// Whether a round-trip error passes.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The absolute difference between an input and the value of
+    ///   its expansion.
+    /// * `base` - The base of the expansion, which a `LastPlace` tolerance
+    ///   needs, or `None` for representations without one, which must then
+    ///   round-trip exactly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // 0.1 is 0.0;0;0;1; in base 2 to 4 digits, 0.0375 off and within 2⁻⁴.
+    /// assert!(Tolerance::LastPlace(4).allows(0.0375, Some(2.0)));
+    /// assert!(!Tolerance::Absolute(1e-3).allows(0.0375, Some(2.0)));
+    /// ```
+    pub fn allows(self, error: f64, base: Option<f64>) -> bool {
+        let limit = match (self, base) {
+            (Tolerance::Absolute(tolerance), _) => tolerance,
+            (Tolerance::LastPlace(digits), Some(base)) => base.abs().powf(-(digits as f64)),
+            (Tolerance::LastPlace(_), None) => 0.0,
+        };
+        // A NaN error, from an infinite or NaN input, never passes.
+        error <= limit
+    }
+}
+
+/// Parses the tolerance of `--verify-roundtrip=<tolerance>`, which must be a
+/// finite number that is not negative, such as `1e-6`.
+pub fn parse_tolerance(value: &str) -> Result<Tolerance, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|tolerance| tolerance.is_finite() && *tolerance >= 0.0)
+        .map(Tolerance::Absolute)
+        .ok_or_else(|| format!("invalid round-trip tolerance `{}`", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_tolerances() {
+        assert!(Tolerance::LastPlace(4).allows(0.0625, Some(2.0)));
+        assert!(!Tolerance::LastPlace(4).allows(0.07, Some(2.0)));
+        assert!(Tolerance::LastPlace(2).allows(0.1, Some(-3.0)));
+        assert!(!Tolerance::LastPlace(8).allows(1e-9, None));
+        assert!(Tolerance::LastPlace(8).allows(0.0, None));
+        assert!(!Tolerance::Absolute(1.0).allows(f64::NAN, Some(2.0)));
+        assert_that!(
+            parse_tolerance("1e-6"),
+            equal_to(Ok(Tolerance::Absolute(1e-6)))
+        );
+        assert!(parse_tolerance("-1").is_err());
+        assert!(parse_tolerance("inf").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::cancel::CancelToken;
+
+/// How often `--watch` looks at the input files.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// What is known of a watched file: its modification time and length, or
+/// `None` while it cannot be read, such as while an editor replaces it.
+pub type Stamp = Option<(SystemTime, u64)>;
+
+/// Looks up the stamp of every file.
+pub fn stamps(paths: &[String]) -> Vec<Stamp> {
+    paths
+        .iter()
+        .map(|path| {
+            let metadata = fs::metadata(path).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect()
+}
+
+/// Waits until any of the files changes on disk, looking every `interval`.
+///
+/// # Arguments
+///
+/// * `paths` - The files to watch.
+/// * `last` - Their stamps when they were last read.
+/// * `cancel` - Stops the waiting once it is cancelled.
+/// * `interval` - How long to sleep between two looks.
+///
+/// # Returns
+///
+/// The new stamps of the files, or `None` if the waiting was cancelled.
+pub fn wait_for_change(
+    paths: &[String],
+    last: &[Stamp],
+    cancel: &CancelToken,
+    interval: Duration,
+) -> Option<Vec<Stamp>> {
+    loop {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let current = stamps(paths);
+        if current != last {
+            return Some(current);
+        }
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_wait_for_change() {
+        let path = env::temp_dir().join(format!("watch-{}.txt", std::process::id()));
+        fs::write(&path, "0.5\n").unwrap();
+        let paths = vec![path.to_string_lossy().into_owned()];
+        let before = stamps(&paths);
+        assert!(before[0].is_some());
+
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                fs::write(&path, "0.5\n0.25\n").unwrap();
+            })
+        };
+        let after = wait_for_change(
+            &paths,
+            &before,
+            &CancelToken::new(),
+            Duration::from_millis(5),
+        );
+        writer.join().unwrap();
+        assert!(after.is_some_and(|after| after != before));
+
+        let cancelled = CancelToken::new();
+        cancelled.cancel();
+        assert!(wait_for_change(&paths, &stamps(&paths), &cancelled, POLL_INTERVAL).is_none());
+        fs::remove_file(&path).unwrap();
+    }
+}
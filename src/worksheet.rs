@@ -0,0 +1,305 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::cli::flag_value;
+use crate::find::{parse_digits, parse_single_base};
+use crate::pipeline::Pipeline;
+use crate::quiz::{check_difficulty, question_text, questions, time_seed, QuizOptions};
+use crate::rational::Fraction;
+
+/// The settings of the `worksheet` subcommand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorksheetOptions {
+    /// The base every answer is written in.
+    pub target_base: u64,
+    /// The number of problems.
+    pub count: usize,
+    /// The most fractional digits an answer needs.
+    pub digits: u32,
+    /// The seed of the problems, so that a worksheet can be made again, or
+    /// `None` for new problems every run.
+    pub seed: Option<u64>,
+    /// The Markdown file the problems are written to.
+    pub problems_path: String,
+    /// The Markdown file the answer key is written to.
+    pub answers_path: String,
+    /// Whether existing files may be replaced, from `--force`.
+    pub force: bool,
+}
+
+/// The answer key of a problems file, as in `worksheet-answers.md` for
+/// `worksheet.md`.
+fn answers_path_for(problems_path: &str) -> String {
+    let stem = problems_path.strip_suffix(".md").unwrap_or(problems_path);
+    format!("{}-answers.md", stem)
+}
+
+/// Parses the arguments of the `worksheet` subcommand.
+///
+/// # Arguments
+///
+/// * `args` - The arguments that follow `worksheet`.
+///
+/// # Returns
+///
+/// The parsed `WorksheetOptions`, or a message describing why the arguments are invalid.
+///
+/// # Example
+/// ```
+/// // cargo run -- worksheet --count 20 --base 8 --digits 3 --output week3.md
+/// ```
+pub fn parse_worksheet_args(args: &[String]) -> Result<WorksheetOptions, String> {
+    let mut target_base = 2;
+    let mut count = 10;
+    let mut digits = 4;
+    let mut seed = None;
+    let mut problems_path = "worksheet.md".to_string();
+    let mut answers_path = None;
+    let mut force = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = flag_value(arg, "--base", &mut iter)? {
+            target_base = parse_single_base(value)?;
+        } else if let Some(value) = flag_value(arg, "--count", &mut iter)? {
+            count = value
+                .parse()
+                .ok()
+                .filter(|&count| count > 0)
+                .ok_or_else(|| format!("invalid problem count `{}`", value))?;
+        } else if let Some(value) = flag_value(arg, "--digits", &mut iter)? {
+            digits = parse_digits(value)?;
+        } else if let Some(value) = flag_value(arg, "--seed", &mut iter)? {
+            seed = Some(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid seed `{}`", value))?,
+            );
+        } else if let Some(path) = flag_value(arg, "--output", &mut iter)? {
+            problems_path = path.to_string();
+        } else if let Some(path) = flag_value(arg, "--answers", &mut iter)? {
+            answers_path = Some(path.to_string());
+        } else if arg == "--force" {
+            force = true;
+        } else {
+            return Err(format!("unknown worksheet argument `{}`", arg));
+        }
+    }
+
+    check_difficulty("worksheet", target_base, digits)?;
+    // Every problem has a numerator below the base to the digits.
+    let different = target_base.pow(digits) - 1;
+    if count as u64 > different {
+        return Err(format!(
+            "there are only {} different problems in base {} with {} digits",
+            different, target_base, digits
+        ));
+    }
+    let answers_path = answers_path.unwrap_or_else(|| answers_path_for(&problems_path));
+    if answers_path == problems_path {
+        return Err("the problems and the answer key must go to different files".to_string());
+    }
+    Ok(WorksheetOptions {
+        target_base,
+        count,
+        digits,
+        seed,
+        problems_path,
+        answers_path,
+        force,
+    })
+}
+
+/// Draws the problems of a worksheet as the `quiz` draws its questions,
+/// leaving out those drawn before, until there are enough.
+fn distinct_problems(options: &WorksheetOptions, seed: u64) -> Vec<Fraction> {
+    let mut rounds = options.count;
+    loop {
+        let drawn = questions(
+            &QuizOptions {
+                target_base: options.target_base,
+                rounds,
+                digits: options.digits,
+                seed: Some(seed),
+            },
+            seed,
+        );
+        let mut problems: Vec<Fraction> = Vec::with_capacity(options.count);
+        for problem in drawn {
+            if !problems.contains(&problem) {
+                problems.push(problem);
+            }
+        }
+        if problems.len() >= options.count {
+            problems.truncate(options.count);
+            return problems;
+        }
+        rounds *= 2;
+    }
+}
+
+/// Makes the problems of a worksheet and their answer key, both Markdown
+/// documents numbering the problems alike: different random fractions
+/// between 0 and 1 whose expansion in the base ends within the digit limit,
+/// as the `quiz` asks, with their expansions in the key made by the
+/// converter.
+///
+/// # Arguments
+///
+/// * `options` - The base, digit limit and number of the problems.
+/// * `seed` - The seed the problems are drawn with, which both documents
+///   name so that they can be matched up and made again.
+///
+/// # Returns
+///
+/// The problems and the answer key.
+///
+/// # Example
+///
+/// ```
+/// let (problems, key) = worksheet_documents(&options, 7);
+/// // problems: "1. 5/16 (= 0.3125) = ____________"
+/// // key:      "1. 5/16 (= 0.3125) = 0.0;1;0;1;"
+/// ```
+pub fn worksheet_documents(options: &WorksheetOptions, seed: u64) -> (String, String) {
+    let problems = distinct_problems(options, seed);
+    let pipeline = Pipeline {
+        digits: options.digits,
+        ..Pipeline::with_bases(vec![options.target_base as f64])
+    };
+    let instructions = format!(
+        "Base {}, at most {} digit{} after the point, seed {}.",
+        options.target_base,
+        options.digits,
+        if options.digits == 1 { "" } else { "s" },
+        seed
+    );
+
+    let mut sheet = format!(
+        "# Base conversion worksheet\n\n{}\n\nWrite each fraction in base {}.\n\n",
+        instructions, options.target_base
+    );
+    let mut key = format!(
+        "# Base conversion worksheet: answer key\n\n{}\n\n",
+        instructions
+    );
+    for (number, problem) in problems.iter().enumerate() {
+        let text = question_text(problem);
+        let answer =
+            pipeline.expand_input(problem.value(), Some(problem), options.target_base as f64);
+        sheet.push_str(&format!("{}. {} = ____________\n", number + 1, text));
+        key.push_str(&format!("{}. {} = {}\n", number + 1, text, answer));
+    }
+    (sheet, key)
+}
+
+/// Runs the `worksheet` subcommand, writing the problems file and its
+/// answer key.
+///
+/// # Returns
+///
+/// Whether both files were written, or the error writing to `out`.
+///
+/// # Example
+/// ```
+/// // cargo run -- worksheet --count 5 --seed 7
+/// ```
+/// Output:
+/// wrote 5 problems to worksheet.md and their answers to worksheet-answers.md (seed 7)
+pub fn display_worksheet(out: &mut impl Write, options: &WorksheetOptions) -> io::Result<bool> {
+    let paths = [&options.problems_path, &options.answers_path];
+    if let Some(path) = paths
+        .iter()
+        .find(|path| !options.force && Path::new(path).exists())
+    {
+        eprintln!(
+            "error: `{}` already exists; use --force to overwrite it",
+            path
+        );
+        return Ok(false);
+    }
+
+    let seed = options.seed.unwrap_or_else(time_seed);
+    let (sheet, key) = worksheet_documents(options, seed);
+    for (path, document) in paths.into_iter().zip([sheet, key]) {
+        if let Err(error) = fs::write(path, document) {
+            eprintln!("error: cannot write `{}`: {}", path, error);
+            return Ok(false);
+        }
+    }
+    writeln!(
+        out,
+        "wrote {} problems to {} and their answers to {} (seed {})",
+        options.count, options.problems_path, options.answers_path, seed
+    )?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_worksheet_args() {
+        let options = parse_worksheet_args(&args("--count 3 --base 8 --output week3.md")).unwrap();
+        assert_that!(options.target_base, equal_to(8));
+        assert_that!(options.count, equal_to(3));
+        assert_that!(
+            options.answers_path,
+            equal_to("week3-answers.md".to_string())
+        );
+        assert!(parse_worksheet_args(&args("--count 0")).is_err());
+        assert!(parse_worksheet_args(&args("--count 4 --digits 2")).is_err());
+        assert!(parse_worksheet_args(&args("--base 1")).is_err());
+        assert!(parse_worksheet_args(&args("--digits 100")).is_err());
+        assert!(parse_worksheet_args(&args("--output a.md --answers a.md")).is_err());
+        assert!(parse_worksheet_args(&args("0.5")).is_err());
+    }
+
+    #[test]
+    fn test_worksheet_documents() {
+        let options = parse_worksheet_args(&args("--count 4 --digits 3")).unwrap();
+        let (sheet, key) = worksheet_documents(&options, 7);
+        assert_that!(
+            worksheet_documents(&options, 7),
+            equal_to((sheet.clone(), key.clone()))
+        );
+
+        // As many problems as there are are all drawn, each once.
+        let every = parse_worksheet_args(&args("--count 7 --digits 3")).unwrap();
+        let mut eighths: Vec<f64> = distinct_problems(&every, 7)
+            .iter()
+            .map(|problem| problem.value() * 8.0)
+            .collect();
+        eighths.sort_by(f64::total_cmp);
+        assert_that!(eighths, equal_to(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]));
+
+        let sheet: Vec<&str> = sheet.lines().collect();
+        let key: Vec<&str> = key.lines().collect();
+        assert_that!(sheet[0], equal_to("# Base conversion worksheet"));
+        assert_that!(
+            sheet[2],
+            equal_to("Base 2, at most 3 digits after the point, seed 7.")
+        );
+        assert_that!(key[2], equal_to(sheet[2]));
+        let problems: Vec<&str> = sheet
+            .iter()
+            .copied()
+            .filter(|line| line.ends_with("= ____________"))
+            .collect();
+        let answers = &key[4..];
+        assert_that!(problems.len(), equal_to(4));
+        assert_that!(answers.len(), equal_to(4));
+        for (problem, answer) in problems.iter().zip(answers) {
+            let question = problem.trim_end_matches("____________");
+            assert!(answer.starts_with(question), "{} / {}", problem, answer);
+            assert!(answer[question.len()..].starts_with("0."));
+        }
+    }
+}
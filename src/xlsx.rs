@@ -0,0 +1,360 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::conversion::Conversion;
+
+/// The titles of the columns of the sheet, after the `Label` column the
+/// sheet starts with when the inputs were labelled.
+const COLUMN_TITLES: [&str; 5] = ["Decimal", "Base", "Digits", "Truncated", "Representation"];
+
+/// The width of every column, in characters, in the order of
+/// `COLUMN_TITLES`.
+const COLUMN_WIDTHS: [u32; 5] = [20, 8, 8, 10, 40];
+
+/// The index in `STYLES` of the cell format of the header.
+const HEADER_STYLE: u32 = 1;
+/// The index in `STYLES` of the cell format of the decimals.
+const DECIMAL_STYLE: u32 = 2;
+/// The index in `STYLES` of the cell format of the bases.
+const BASE_STYLE: u32 = 3;
+/// The index in `STYLES` of the cell format of the representations.
+const REPRESENTATION_STYLE: u32 = 4;
+
+/// The fonts, number format and cell formats of the sheet: bold headers,
+/// decimals with up to 17 significant places, centered bases and the
+/// representations in a monospace font so that their digits line up.
+const STYLES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    r#"<numFmts count="1"><numFmt numFmtId="164" formatCode="0.0################"/></numFmts>"#,
+    r#"<fonts count="3"><font><sz val="11"/><name val="Calibri"/></font>"#,
+    r#"<font><b/><sz val="11"/><name val="Calibri"/></font>"#,
+    r#"<font><sz val="11"/><name val="Courier New"/></font></fonts>"#,
+    r#"<fills count="2"><fill><patternFill patternType="none"/></fill>"#,
+    r#"<fill><patternFill patternType="gray125"/></fill></fills>"#,
+    r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+    r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+    r#"<cellXfs count="5"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+    r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/>"#,
+    r#"<xf numFmtId="164" fontId="0" fillId="0" borderId="0" xfId="0" applyNumberFormat="1"/>"#,
+    r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0" applyAlignment="1">"#,
+    r#"<alignment horizontal="center"/></xf>"#,
+    r#"<xf numFmtId="0" fontId="2" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs>"#,
+    r#"</styleSheet>"#,
+);
+
+/// The parts of the workbook other than its sheet and styles.
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    r#"</Types>"#,
+);
+const ROOT_RELATIONSHIPS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#,
+);
+const WORKBOOK: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+    r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+    r#"<sheets><sheet name="Conversions" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+);
+const WORKBOOK_RELATIONSHIPS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+    r#"</Relationships>"#,
+);
+
+/// Writes conversions as an Excel workbook with one sheet, a row per input
+/// and base under a frozen bold header, with the decimals as numbers and the
+/// representations as text in a monospace font. Inputs that are no finite
+/// number are written as text.
+///
+/// The workbook is built in memory, since the archive holding it records
+/// the size of every part before the part.
+///
+/// # Arguments
+///
+/// * `out` - Where the workbook is written.
+/// * `conversions` - The conversion of every input to every target base,
+///   either all with a label or all without.
+pub fn write_xlsx(
+    out: &mut dyn Write,
+    conversions: impl IntoIterator<Item = Conversion>,
+) -> io::Result<()> {
+    let sheet = worksheet(conversions);
+    let parts = [
+        ("[Content_Types].xml", CONTENT_TYPES.as_bytes()),
+        ("_rels/.rels", ROOT_RELATIONSHIPS.as_bytes()),
+        ("xl/workbook.xml", WORKBOOK.as_bytes()),
+        (
+            "xl/_rels/workbook.xml.rels",
+            WORKBOOK_RELATIONSHIPS.as_bytes(),
+        ),
+        ("xl/styles.xml", STYLES.as_bytes()),
+        ("xl/worksheets/sheet1.xml", sheet.as_bytes()),
+    ];
+    out.write_all(&zip_stored(&parts))
+}
+
+/// Builds the XML of the sheet of the conversions.
+fn worksheet(conversions: impl IntoIterator<Item = Conversion>) -> String {
+    let mut conversions = conversions.into_iter().peekable();
+    let labelled = conversions
+        .peek()
+        .is_some_and(|conversion| conversion.label.is_some());
+    let first = usize::from(labelled);
+
+    // Writing into a `String` cannot fail.
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+    xml.push_str(
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+    );
+    xml.push_str(r#"<sheetViews><sheetView workbookViewId="0">"#);
+    xml.push_str(r#"<pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/>"#);
+    xml.push_str("</sheetView></sheetViews><cols>");
+    if labelled {
+        xml.push_str(r#"<col min="1" max="1" width="16" customWidth="1"/>"#);
+    }
+    for (i, width) in COLUMN_WIDTHS.iter().enumerate() {
+        let column = first + i + 1;
+        let _ = write!(
+            xml,
+            r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#,
+            column, width
+        );
+    }
+    xml.push_str(r#"</cols><sheetData><row r="1">"#);
+    let titles = labelled.then_some("Label").into_iter().chain(COLUMN_TITLES);
+    for (column, title) in titles.enumerate() {
+        text_cell(&mut xml, column, 1, title, HEADER_STYLE);
+    }
+    xml.push_str("</row>");
+
+    for (row, conversion) in (2..).zip(conversions) {
+        let _ = write!(xml, r#"<row r="{}">"#, row);
+        if let Some(label) = &conversion.label {
+            text_cell(&mut xml, 0, row, label, 0);
+        }
+        if conversion.input.is_finite() {
+            let _ = write!(
+                xml,
+                r#"<c r="{}{}" s="{}"><v>{}</v></c>"#,
+                column_name(first),
+                row,
+                DECIMAL_STYLE,
+                conversion.input
+            );
+        } else {
+            text_cell(&mut xml, first, row, &conversion.input.to_string(), 0);
+        }
+        text_cell(&mut xml, first + 1, row, &conversion.base, BASE_STYLE);
+        let _ = write!(
+            xml,
+            r#"<c r="{}{}"><v>{}</v></c><c r="{}{}" t="b"><v>{}</v></c>"#,
+            column_name(first + 2),
+            row,
+            conversion.digits,
+            column_name(first + 3),
+            row,
+            u8::from(conversion.truncated)
+        );
+        text_cell(
+            &mut xml,
+            first + 4,
+            row,
+            &conversion.repr,
+            REPRESENTATION_STYLE,
+        );
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// Appends a cell holding text, which is written inline rather than in a
+/// table of shared strings.
+fn text_cell(xml: &mut String, column: usize, row: u32, text: &str, style: u32) {
+    let _ = write!(
+        xml,
+        r#"<c r="{}{}" t="inlineStr""#,
+        column_name(column),
+        row
+    );
+    if style != 0 {
+        let _ = write!(xml, r#" s="{}""#, style);
+    }
+    xml.push_str(r#"><is><t xml:space="preserve">"#);
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            // XML 1.0 cannot hold the other control characters at all.
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => xml.push('\u{fffd}'),
+            c => xml.push(c),
+        }
+    }
+    xml.push_str("</t></is></c>");
+}
+
+/// The letters naming a 0-based column, such as `A` for 0 and `AA` for 26.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(column_name(27), "AB");
+/// ```
+fn column_name(column: usize) -> String {
+    let mut name = Vec::new();
+    let mut rest = column + 1;
+    while rest > 0 {
+        rest -= 1;
+        name.push(b'A' + (rest % 26) as u8);
+        rest /= 26;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("the letters are ASCII")
+}
+
+/// The CRC-32 of some bytes, with the polynomial of ZIP archives.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+/// ```
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Packs files into a ZIP archive without compressing them, which every
+/// spreadsheet application reads. Every file is dated 1 January 1980, the
+/// earliest date ZIP can record, so that the same conversions always make
+/// the same bytes.
+fn zip_stored(files: &[(&str, &[u8])]) -> Vec<u8> {
+    // The version of ZIP needed to extract the files, and their date, day 1
+    // of month 1 of year 0.
+    const VERSION: u16 = 20;
+    const DATE: u16 = (1 << 5) | 1;
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, data) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        // The fields the local header and the directory entry share: the
+        // version, no flags, no compression, the time and date, the CRC,
+        // the sizes, the length of the name and no extra field.
+        let mut fields = Vec::new();
+        for value in [VERSION, 0, 0, 0, DATE] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [crc, size, size] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        fields.extend_from_slice(&0u16.to_le_bytes());
+
+        archive.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        archive.extend_from_slice(&fields);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&VERSION.to_le_bytes());
+        directory.extend_from_slice(&fields);
+        // No comment, on disk 0, no attributes, and where the file starts.
+        for value in [0u16; 3] {
+            directory.extend_from_slice(&value.to_le_bytes());
+        }
+        directory.extend_from_slice(&0u32.to_le_bytes());
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    let count = files.len() as u16;
+    for value in [0, 0, count, count] {
+        archive.extend_from_slice(&u16::to_le_bytes(value));
+    }
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_zip_parts() {
+        assert_that!(crc32(b"123456789"), equal_to(0xcbf4_3926));
+        assert_that!(crc32(b""), equal_to(0));
+        assert_that!(column_name(0), equal_to("A"));
+        assert_that!(column_name(25), equal_to("Z"));
+        assert_that!(column_name(27), equal_to("AB"));
+
+        let archive = zip_stored(&[("a.txt", b"hi")]);
+        assert_that!(&archive[..4], equal_to(&b"PK\x03\x04"[..]));
+        // The local header and the name come before the data.
+        assert_that!(&archive[30..37], equal_to(&b"a.txthi"[..]));
+        let end = archive.len() - 22;
+        assert_that!(&archive[end..end + 4], equal_to(&b"PK\x05\x06"[..]));
+    }
+
+    #[test]
+    fn test_worksheet() {
+        let conversion = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+        let sheet = worksheet([conversion.clone()]);
+        assert!(
+            sheet.contains(r#"<c r="A1" t="inlineStr" s="1"><is><t xml:space="preserve">Decimal"#)
+        );
+        assert!(sheet.contains(r#"<c r="A2" s="2"><v>0.5</v></c>"#));
+        assert!(sheet.contains(r#"<c r="D2" t="b"><v>0</v></c>"#));
+        assert!(
+            sheet.contains(r#"<c r="E2" t="inlineStr" s="4"><is><t xml:space="preserve">0.1;</t>"#)
+        );
+
+        let labelled = Conversion {
+            label: Some("<half>".to_string()),
+            ..conversion
+        };
+        let sheet = worksheet([labelled]);
+        assert!(
+            sheet.contains(r#"<c r="A2" t="inlineStr"><is><t xml:space="preserve">&lt;half&gt;"#)
+        );
+        assert!(sheet.contains(r#"<c r="B2" s="2"><v>0.5</v></c>"#));
+
+        let mut out = Vec::new();
+        write_xlsx(&mut out, Vec::new()).unwrap();
+        assert_that!(&out[..2], equal_to(&b"PK"[..]));
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt::{self, Display, Write};
+
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::json::write_json_string;
+
+/// Why a value could not be written as YAML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YamlError(String);
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for YamlError {}
+
+impl ser::Error for YamlError {
+    fn custom<T: Display>(message: T) -> YamlError {
+        YamlError(message.to_string())
+    }
+}
+
+/// Writes a struct of plain values, such as a `Conversion`, as an entry of a
+/// YAML sequence:
+///
+/// ```text
+/// - input: 0.5
+///   base: "2"
+///   digits: 1
+///   truncated: false
+///   repr: "0.1;"
+/// ```
+///
+/// Strings are always double-quoted, with the escapes JSON uses, so that no
+/// representation is ever read back as a number, and NaN and the infinities
+/// are written as `.nan`, `.inf` and `-.inf`.
+///
+/// # Returns
+///
+/// The entry, ending in a newline, or an error for anything but a struct
+/// whose fields are all numbers, booleans, strings or missing values.
+pub fn to_yaml_entry<T: Serialize + ?Sized>(value: &T) -> Result<String, YamlError> {
+    value.serialize(EntrySerializer)
+}
+
+/// The error for a value `to_yaml_entry` cannot write.
+fn unsupported(what: &str) -> YamlError {
+    YamlError(format!("YAML entries cannot hold {}", what))
+}
+
+/// Implements the methods of `ser::Serializer` that take a plain value by
+/// rejecting it.
+macro_rules! reject_values {
+    ($($method:ident($type:ty)),* $(,)?) => {
+        $(
+            fn $method(self, _value: $type) -> Result<Self::Ok, YamlError> {
+                Err(unsupported("a plain value at the top level"))
+            }
+        )*
+    };
+}
+
+/// A serde serializer writing a struct as one entry of a YAML sequence.
+struct EntrySerializer;
+
+/// Writes the fields of a struct, one `key: value` line each.
+struct Fields {
+    /// The lines written so far.
+    output: String,
+}
+
+impl ser::Serializer for EntrySerializer {
+    type Ok = String;
+    type Error = YamlError;
+    type SerializeSeq = Impossible<String, YamlError>;
+    type SerializeTuple = Impossible<String, YamlError>;
+    type SerializeTupleStruct = Impossible<String, YamlError>;
+    type SerializeTupleVariant = Impossible<String, YamlError>;
+    type SerializeMap = Impossible<String, YamlError>;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<String, YamlError>;
+
+    reject_values!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+    );
+
+    fn serialize_none(self) -> Result<String, YamlError> {
+        Err(unsupported("a missing value at the top level"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, YamlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, YamlError> {
+        Err(unsupported("a unit at the top level"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, YamlError> {
+        Err(unsupported("a unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<String, YamlError> {
+        Err(unsupported("an enum at the top level"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, YamlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, YamlError> {
+        Err(unsupported("an enum at the top level"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, YamlError> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, YamlError> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, YamlError> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, YamlError> {
+        Err(unsupported("an enum at the top level"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, YamlError> {
+        Err(unsupported("a map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Fields, YamlError> {
+        Ok(Fields {
+            output: String::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, YamlError> {
+        Err(unsupported("an enum at the top level"))
+    }
+}
+
+impl ser::SerializeStruct for Fields {
+    type Ok = String;
+    type Error = YamlError;
+
+    /// Writes `key: value`, after the `- ` that opens the entry for the
+    /// first field and indented under it for the others.
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), YamlError> {
+        let indent = if self.output.is_empty() { "- " } else { "  " };
+        let value = value.serialize(ScalarSerializer)?;
+        let _ = writeln!(self.output, "{}{}: {}", indent, key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<String, YamlError> {
+        if self.output.is_empty() {
+            Ok("- {}\n".to_string())
+        } else {
+            Ok(self.output)
+        }
+    }
+}
+
+/// A serde serializer writing a plain value as a YAML scalar.
+struct ScalarSerializer;
+
+impl ScalarSerializer {
+    /// Writes a string as a double-quoted scalar.
+    fn quoted(text: &str) -> String {
+        let mut output = String::new();
+        write_json_string(&mut output, text);
+        output
+    }
+}
+
+impl ser::Serializer for ScalarSerializer {
+    type Ok = String;
+    type Error = YamlError;
+    type SerializeSeq = Impossible<String, YamlError>;
+    type SerializeTuple = Impossible<String, YamlError>;
+    type SerializeTupleStruct = Impossible<String, YamlError>;
+    type SerializeTupleVariant = Impossible<String, YamlError>;
+    type SerializeMap = Impossible<String, YamlError>;
+    type SerializeStruct = Impossible<String, YamlError>;
+    type SerializeStructVariant = Impossible<String, YamlError>;
+
+    fn serialize_bool(self, value: bool) -> Result<String, YamlError> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<String, YamlError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<String, YamlError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<String, YamlError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<String, YamlError> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<String, YamlError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<String, YamlError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<String, YamlError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<String, YamlError> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<String, YamlError> {
+        self.serialize_f64(value as f64)
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<String, YamlError> {
+        Ok(if value.is_nan() {
+            ".nan".to_string()
+        } else if value.is_infinite() {
+            if value > 0.0 { ".inf" } else { "-.inf" }.to_string()
+        } else {
+            value.to_string()
+        })
+    }
+
+    fn serialize_char(self, value: char) -> Result<String, YamlError> {
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<String, YamlError> {
+        Ok(ScalarSerializer::quoted(value))
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String, YamlError> {
+        Err(unsupported("bytes in a field"))
+    }
+
+    fn serialize_none(self) -> Result<String, YamlError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, YamlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, YamlError> {
+        Ok("null".to_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, YamlError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, YamlError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, YamlError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, YamlError> {
+        Err(unsupported("an enum with data in a field"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, YamlError> {
+        Err(unsupported("a sequence in a field"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, YamlError> {
+        Err(unsupported("a tuple in a field"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, YamlError> {
+        Err(unsupported("a tuple struct in a field"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, YamlError> {
+        Err(unsupported("an enum with data in a field"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, YamlError> {
+        Err(unsupported("a map in a field"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, YamlError> {
+        Err(unsupported("a struct in a field"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, YamlError> {
+        Err(unsupported("an enum with data in a field"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::Conversion;
+    use crate::convert::expand;
+    use hamcrest2::prelude::*;
+
+    #[test]
+    fn test_conversion_as_yaml() {
+        let conversion = Conversion::new(0.5, 2.0, &expand(0.5, 2, 8));
+        assert_that!(
+            to_yaml_entry(&conversion).unwrap(),
+            equal_to(
+                "- input: 0.5\n  base: \"2\"\n  digits: 1\n  truncated: false\n  repr: \"0.1;\"\n"
+                    .to_string()
+            )
+        );
+        let nan = Conversion::new(f64::NAN, 2.0, &expand(f64::NAN, 2, 8));
+        assert!(to_yaml_entry(&nan).unwrap().starts_with("- input: .nan\n"));
+        assert!(to_yaml_entry(&0.5).is_err());
+        assert!(to_yaml_entry(&vec![1, 2]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use crate::beta::GOLDEN_RATIO;
+use crate::convert::{evaluate_fraction, Representation};
+use crate::rational::Ratio;
+
+/// A number in the Fibonacci base: its integer part in Zeckendorf form, as a
+/// sum of Fibonacci numbers 1, 2, 3, 5, 8, …, and its fractional part in
+/// places 1/φ, 1/φ², …, which keep the Fibonacci rule of every place being
+/// worth the next two together. Neither part has two adjacent ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zeckendorf {
+    /// Whether the converted value was negative.
+    pub negative: bool,
+    /// The integer digits, most significant first, ending with that of 1,
+    /// and empty when the integer part is zero.
+    pub integer_digits: Vec<u32>,
+    /// The fractional digits of 1/φ, 1/φ² and so on.
+    pub digits: Vec<u32>,
+    /// The part of the value left over after the last digit, measured in
+    /// units of the last digit's place.
+    pub remainder: f64,
+}
+
+/// The Fibonacci numbers 1, 2, 3, 5, … up to and including the largest not
+/// above `limit`.
+fn fibonacci_up_to(limit: &BigUint) -> Vec<BigUint> {
+    let mut numbers = vec![BigUint::from(1u32)];
+    let mut next = BigUint::from(2u32);
+    while &next <= limit {
+        let after = &next + numbers.last().expect("at least one number");
+        numbers.push(next);
+        next = after;
+    }
+    numbers
+}
+
+/// Writes a value in the Fibonacci base. The integer part is exact and
+/// greedy, taking the largest Fibonacci number that fits each time, which
+/// never leaves room for the next one. The fractional part is greedy in
+/// base φ, and a one is always followed by a zero, even when rounding would
+/// have let the next place fit.
+///
+/// # Arguments
+///
+/// * `decimal` - A floating-point number to convert.
+/// * `max_digits` - The maximum number of fractional digits to produce.
+///
+/// # Example
+///
+/// ```
+/// // 12 = 8 + 3 + 1
+/// assert_eq!(expand_zeckendorf(12.0, 8).to_string(), "1;0;1;0;1.");
+/// assert_eq!(expand_zeckendorf(0.5, 5).to_string(), "0.0;1;0;0;1;");
+/// ```
+pub fn expand_zeckendorf(decimal: f64, max_digits: u32) -> Zeckendorf {
+    let mut zeckendorf = Zeckendorf {
+        negative: decimal < 0.0,
+        integer_digits: Vec::new(),
+        digits: Vec::new(),
+        remainder: 0.0,
+    };
+    let magnitude = decimal.abs();
+    let Some(whole) = Ratio::from_f64(magnitude.trunc()) else {
+        return zeckendorf;
+    };
+
+    let mut rest = &whole.numerator / &whole.denominator;
+    if !rest.is_zero() {
+        for number in fibonacci_up_to(&rest).iter().rev() {
+            if *number <= rest && zeckendorf.integer_digits.last() != Some(&1) {
+                rest -= number;
+                zeckendorf.integer_digits.push(1);
+            } else {
+                zeckendorf.integer_digits.push(0);
+            }
+        }
+    }
+
+    let mut fraction = magnitude.fract();
+    for _ in 0..max_digits {
+        if fraction == 0.0 {
+            break;
+        }
+        let product = fraction * GOLDEN_RATIO;
+        let digit = u32::from(product >= 1.0 && zeckendorf.digits.last() != Some(&1));
+        zeckendorf.digits.push(digit);
+        fraction = product - digit as f64;
+    }
+    zeckendorf.remainder = fraction;
+    zeckendorf
+}
+
+impl Representation for Zeckendorf {
+    fn value(&self) -> f64 {
+        let (integer, _, _) = self.integer_digits.iter().rev().fold(
+            (0.0, 1.0, 1.0),
+            |(sum, number, previous), &digit| {
+                (sum + digit as f64 * number, number + previous, number)
+            },
+        );
+        let value = integer + evaluate_fraction(&self.digits, GOLDEN_RATIO);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        self.remainder == 0.0
+    }
+}
+
+impl fmt::Display for Zeckendorf {
+    /// Writes the digits as `1;0;1.0;1;`, like an ordinary expansion.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative && !(self.integer_digits.is_empty() && self.digits.is_empty()) {
+            write!(f, "-")?;
+        }
+        if self.integer_digits.is_empty() {
+            write!(f, "0")?;
+        }
+        let integer: Vec<String> = self.integer_digits.iter().map(u32::to_string).collect();
+        write!(f, "{}.", integer.join(";"))?;
+        for digit in &self.digits {
+            write!(f, "{};", digit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hamcrest2::prelude::*;
+
+    /// Whether no two adjacent digits of a part are both one.
+    fn has_no_adjacent_ones(digits: &[u32]) -> bool {
+        digits.windows(2).all(|pair| pair != [1, 1])
+    }
+
+    #[test]
+    fn test_zeckendorf() {
+        assert_that!(
+            expand_zeckendorf(12.0, 8).to_string(),
+            equal_to("1;0;1;0;1.")
+        );
+        assert_that!(expand_zeckendorf(-4.0, 8).to_string(), equal_to("-1;0;1."));
+        assert_that!(expand_zeckendorf(0.0, 8).to_string(), equal_to("0."));
+        assert_that!(
+            expand_zeckendorf(0.5, 5).to_string(),
+            equal_to("0.0;1;0;0;1;")
+        );
+
+        for value in [0.1, 0.5, 1.0 / 3.0, 0.999, 100.618, 1e15 + 0.25] {
+            let zeckendorf = expand_zeckendorf(value, 60);
+            assert!(has_no_adjacent_ones(&zeckendorf.integer_digits));
+            assert!(has_no_adjacent_ones(&zeckendorf.digits));
+            assert_that!(zeckendorf.value(), close_to(value, 1e-9 * value.max(1.0)));
+        }
+        let big = expand_zeckendorf(1e15, 0);
+        assert_that!(big.value(), equal_to(1e15));
+        assert!(big.is_exact());
+        assert!(!expand_zeckendorf(0.5, 5).is_exact());
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{self, Write};
+
+use baseconv::conversion::Conversion;
+use baseconv::format::{display_conversions, OutputFormat};
+use baseconv::formatter::{register_formatter, OutputFormatter};
+use baseconv::pipeline::Pipeline;
+use hamcrest2::prelude::*;
+
+/// Writes every conversion as a LaTeX subscript, as a program using the
+/// crate would add a format of its own.
+struct Latex;
+
+impl OutputFormatter for Latex {
+    fn name(&self) -> &str {
+        "latex"
+    }
+
+    fn extension(&self) -> &str {
+        "tex"
+    }
+
+    fn write(
+        &self,
+        out: &mut dyn Write,
+        conversions: &mut dyn Iterator<Item = Conversion>,
+    ) -> io::Result<()> {
+        for conversion in conversions {
+            writeln!(out, "${}_{{{}}}$", conversion.repr, conversion.base)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_register_formatter() {
+    static LATEX: Latex = Latex;
+    let format = register_formatter(&LATEX).unwrap();
+    assert_that!(OutputFormat::from_name("latex"), equal_to(Some(format)));
+    assert_that!(
+        OutputFormat::from_extension("results.latex"),
+        equal_to(Some(format))
+    );
+    assert_that!(format.extension(), equal_to("tex"));
+    assert!(register_formatter(&LATEX).is_err());
+
+    let mut out = Vec::new();
+    let pipeline = Pipeline::with_bases(vec![2.0, 16.0]);
+    display_conversions(
+        &mut out,
+        format,
+        &pipeline,
+        &[0.5],
+        &[None],
+        &[None],
+        &[None],
+    )
+    .unwrap();
+    assert_that!(
+        String::from_utf8(out).unwrap(),
+        equal_to("$0.1;_{2}$\n$0.8;_{16}$\n")
+    );
+}